@@ -1,6 +1,12 @@
 //! Support for signature based authorization of actions on a user account
-//! using public key(s) and signature threshold (minimum number of signatures
-//! needed to authorize an action) stored on-chain.
+//! using public key(s), their weights and signature threshold (minimum sum of
+//! the weights of the signing keys needed to authorize an action) stored
+//! on-chain.
+//!
+//! The weights are part of the Borsh encoding of [`InitAccount`],
+//! [`UpdateAccount`] and of the [`AccountPublicKeysMap`] passed to the
+//! signature verification host functions, so the data encoded without them
+//! can no longer be decoded.
 
 mod storage;
 mod storage_key;
@@ -20,9 +26,11 @@ pub use types::*;
 )]
 /// Account data
 pub struct Account {
-    /// The map between indexes and public keys for an account
+    /// The map between indexes and public keys for an account, with the
+    /// weights of the keys
     pub public_keys_map: AccountPublicKeysMap,
-    /// The account signature threshold
+    /// The account signature threshold, i.e. the minimum sum of the weights
+    /// of the signing keys
     pub threshold: u8,
     /// The address corresponding to the account owner
     pub address: Address,
@@ -44,4 +52,9 @@ impl Account {
     ) -> Option<u8> {
         self.public_keys_map.get_index_from_public_key(public_key)
     }
+
+    /// Retrieve the weight of the public key with the given index
+    pub fn get_weight_from_index(&self, index: u8) -> u8 {
+        self.public_keys_map.get_weight_from_index(index)
+    }
 }
@@ -1,7 +1,8 @@
 //! Cryptographic signature keys storage API
 
-use namada_core::storage;
-use namada_storage::{Result, StorageRead, StorageWrite};
+use namada_core::storage::{self, Epoch};
+use namada_core::token::Amount;
+use namada_storage::{OptionExt, Result, StorageRead, StorageWrite};
 
 use super::*;
 
@@ -31,13 +32,22 @@ where
     S: StorageWrite + StorageRead,
 {
     for (index, public_key) in public_keys.iter().enumerate() {
-        let index = index as u8;
+        let index = public_key_index(index)?;
         pks_handle(owner).insert(storage, index, public_key.clone())?;
     }
     let threshold_key = threshold_key(owner);
     storage.write(&threshold_key, threshold)
 }
 
+/// Convert the position of a public key into its storage index
+pub fn public_key_index(index: usize) -> Result<u8> {
+    u8::try_from(index).map_err(|_| {
+        namada_storage::Error::new_const(
+            "Only up to 255 signers are allowed in a multisig account",
+        )
+    })
+}
+
 /// Get the threshold associated with an account
 pub fn threshold<S>(storage: &S, owner: &Address) -> Result<Option<u8>>
 where
@@ -75,8 +85,12 @@ where
     S: StorageRead,
 {
     let public_keys = public_keys(storage, owner)?;
+    let mut public_keys_map = AccountPublicKeysMap::from_iter(public_keys);
+    public_keys_map.idx_to_weight = pk_weights_handle(owner)
+        .iter(storage)?
+        .collect::<Result<_>>()?;
 
-    Ok(AccountPublicKeysMap::from_iter(public_keys))
+    Ok(public_keys_map)
 }
 
 /// Check if a user account exists in storage
@@ -113,9 +127,206 @@ pub fn clear_public_keys<S>(storage: &mut S, owner: &Address) -> Result<()>
 where
     S: StorageWrite + StorageRead,
 {
-    let total_pks = pks_handle(owner).len(storage)?;
-    for index in 0..total_pks as u8 {
+    let indices = pks_handle(owner)
+        .iter(storage)?
+        .map(|entry| entry.map(|(index, _public_key)| index))
+        .collect::<Result<Vec<u8>>>()?;
+    for index in indices {
         pks_handle(owner).remove(storage, &index)?;
     }
+    clear_public_key_weights(storage, owner)
+}
+
+/// Set the weights of the public keys of an account, in the order of their
+/// indices, replacing the previous ones. If empty, every key has a weight of
+/// 1. Otherwise, there must be a non-zero weight for each of the account's
+/// public keys and their sum must reach the threshold, so the public keys and
+/// the threshold must already be written.
+pub fn set_public_key_weights<S>(
+    storage: &mut S,
+    owner: &Address,
+    weights: &[u8],
+) -> Result<()>
+where
+    S: StorageWrite + StorageRead,
+{
+    if !weights.is_empty() {
+        let total_pks = pks_handle(owner).len(storage)?;
+        if u64::try_from(weights.len()).ok() != Some(total_pks) {
+            return Err(namada_storage::Error::new_const(
+                "The number of weights must match the number of public keys",
+            ));
+        }
+        if weights.contains(&0) {
+            return Err(namada_storage::Error::new_const(
+                "The weights of the public keys must be non-zero",
+            ));
+        }
+    }
+    clear_public_key_weights(storage, owner)?;
+    for (index, weight) in weights.iter().enumerate() {
+        let index = public_key_index(index)?;
+        pk_weights_handle(owner).insert(storage, index, *weight)?;
+    }
+    validate_threshold(storage, owner)
+}
+
+/// Check that the sum of the weights of the public keys of a weighted account
+/// reaches its threshold, so that it can still be signed for. The accounts
+/// without weights are not checked.
+pub fn validate_threshold<S>(storage: &S, owner: &Address) -> Result<()>
+where
+    S: StorageRead,
+{
+    if pk_weights_handle(owner).is_empty(storage)? {
+        return Ok(());
+    }
+    let threshold = threshold(storage, owner)?
+        .ok_or_err_msg("Missing the threshold of a weighted account")?;
+    let public_keys_map = public_keys_index_map(storage, owner)?;
+    if public_keys_map.total_weight() < u32::from(threshold) {
+        return Err(namada_storage::Error::new_const(
+            "The sum of the weights of the public keys must reach the \
+             threshold",
+        ));
+    }
     Ok(())
 }
+
+/// Clear the weights of the public keys of an account
+pub fn clear_public_key_weights<S>(
+    storage: &mut S,
+    owner: &Address,
+) -> Result<()>
+where
+    S: StorageWrite + StorageRead,
+{
+    let indices = pk_weights_handle(owner)
+        .iter(storage)?
+        .map(|entry| entry.map(|(index, _weight)| index))
+        .collect::<Result<Vec<u8>>>()?;
+    for index in indices {
+        pk_weights_handle(owner).remove(storage, &index)?;
+    }
+    Ok(())
+}
+
+/// Get the fee allowance granted by the granter to the grantee, if any
+pub fn fee_allowance<S>(
+    storage: &S,
+    granter: &Address,
+    grantee: &Address,
+) -> Result<Option<FeeAllowance>>
+where
+    S: StorageRead,
+{
+    storage.read(&fee_allowance_key(granter, grantee))
+}
+
+/// Grant a fee allowance, replacing any allowance previously granted to the
+/// same grantee by the granter
+pub fn grant_fee_allowance<S>(
+    storage: &mut S,
+    GrantFeeAllowance {
+        granter,
+        grantee,
+        allowance,
+    }: GrantFeeAllowance,
+) -> Result<()>
+where
+    S: StorageWrite + StorageRead,
+{
+    storage.write(&fee_allowance_key(&granter, &grantee), allowance)
+}
+
+/// Revoke the fee allowance granted by the granter to the grantee
+pub fn revoke_fee_allowance<S>(
+    storage: &mut S,
+    granter: &Address,
+    grantee: &Address,
+) -> Result<()>
+where
+    S: StorageWrite + StorageRead,
+{
+    storage.delete(&fee_allowance_key(granter, grantee))
+}
+
+/// Get the spending policy of an account, if any
+pub fn spending_policy<S>(
+    storage: &S,
+    owner: &Address,
+) -> Result<Option<SpendingPolicy>>
+where
+    S: StorageRead,
+{
+    storage.read(&spending_policy_key(owner))
+}
+
+/// Set the spending policy of an account, replacing any previous policy
+pub fn update_spending_policy<S>(
+    storage: &mut S,
+    UpdateSpendingPolicy { owner, policy }: UpdateSpendingPolicy,
+) -> Result<()>
+where
+    S: StorageWrite + StorageRead,
+{
+    storage.write(&spending_policy_key(&owner), policy)
+}
+
+/// Remove the spending policy of an account
+pub fn remove_spending_policy<S>(storage: &mut S, owner: &Address) -> Result<()>
+where
+    S: StorageWrite + StorageRead,
+{
+    storage.delete(&spending_policy_key(owner))
+}
+
+/// Get the amount of the token spent by the owner in the given epoch
+pub fn spent_amount<S>(
+    storage: &S,
+    owner: &Address,
+    token: &Address,
+    epoch: Epoch,
+) -> Result<Amount>
+where
+    S: StorageRead,
+{
+    let spent: Option<SpentAmount> =
+        storage.read(&spent_amount_key(owner, token))?;
+    Ok(spent
+        .filter(|spent| spent.epoch == epoch)
+        .map(|spent| spent.amount)
+        .unwrap_or_default())
+}
+
+/// Add a debit of the owner's balance to the amount of the token spent in the
+/// current epoch, if the spending policy of the owner caps the token. This is
+/// called on every transfer, so the policy is only read for the accounts that
+/// have one, the others paying for a key existence check only.
+pub fn record_spending<S>(
+    storage: &mut S,
+    owner: &Address,
+    token: &Address,
+    amount: Amount,
+) -> Result<()>
+where
+    S: StorageWrite + StorageRead,
+{
+    if !storage.has_key(&spending_policy_key(owner))? {
+        return Ok(());
+    }
+    let is_capped = spending_policy(storage, owner)?
+        .map(|policy| policy.spending_caps.contains_key(token))
+        .unwrap_or_default();
+    if !is_capped {
+        return Ok(());
+    }
+    let epoch = storage.get_block_epoch()?;
+    let amount = spent_amount(storage, owner, token, epoch)?
+        .checked_add(amount)
+        .ok_or_err_msg("The spent amount overflowed")?;
+    storage.write(
+        &spent_amount_key(owner, token),
+        SpentAmount { epoch, amount },
+    )
+}
@@ -9,8 +9,12 @@ use namada_storage::collections::{lazy_map, LazyCollection};
 #[derive(StorageKeys)]
 struct Keys {
     public_keys: &'static str,
+    public_key_weights: &'static str,
     threshold: &'static str,
     protocol_public_keys: &'static str,
+    fee_allowances: &'static str,
+    spending_policy: &'static str,
+    spent_amounts: &'static str,
 }
 
 /// Obtain a storage key for user's public key.
@@ -46,6 +50,41 @@ pub fn is_pks_key(key: &storage::Key) -> Option<&Address> {
     }
 }
 
+/// Obtain a storage key prefix for the weights of user's public keys.
+pub fn pk_weights_key_prefix(owner: &Address) -> storage::Key {
+    storage::Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(owner.to_owned()),
+            DbKeySeg::StringSeg(Keys::VALUES.public_key_weights.to_string()),
+        ],
+    }
+}
+
+/// LazyMap handler for the weights of the user's public keys, indexed like
+/// the public keys
+pub fn pk_weights_handle(owner: &Address) -> LazyMap<u8, u8> {
+    LazyMap::open(pk_weights_key_prefix(owner))
+}
+
+/// Check if the given storage key is the weight of a public key. If it is,
+/// returns the owner.
+pub fn is_pk_weight_key(key: &storage::Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(owner),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(data),
+            DbKeySeg::StringSeg(index),
+        ] if prefix.as_str() == Keys::VALUES.public_key_weights
+            && data.as_str() == lazy_map::DATA_SUBKEY
+            && index.parse::<u8>().is_ok() =>
+        {
+            Some(owner)
+        }
+        _ => None,
+    }
+}
+
 /// Check if the given storage key is a threshol key.
 pub fn is_threshold_key(key: &storage::Key) -> Option<&Address> {
     match &key.segments[..] {
@@ -89,3 +128,83 @@ pub fn is_protocol_pk_key(key: &storage::Key) -> Option<&Address> {
         _ => None,
     }
 }
+
+/// Obtain the storage key for the fee allowance granted by the granter to the
+/// grantee
+pub fn fee_allowance_key(granter: &Address, grantee: &Address) -> storage::Key {
+    storage::Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(granter.to_owned()),
+            DbKeySeg::StringSeg(Keys::VALUES.fee_allowances.to_string()),
+            DbKeySeg::AddressSeg(grantee.to_owned()),
+        ],
+    }
+}
+
+/// Check if the given storage key is a fee allowance key. If it is, returns
+/// the granter and the grantee.
+pub fn is_fee_allowance_key(
+    key: &storage::Key,
+) -> Option<(&Address, &Address)> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(granter),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::AddressSeg(grantee),
+        ] if prefix.as_str() == Keys::VALUES.fee_allowances =>
+        {
+            Some((granter, grantee))
+        }
+        _ => None,
+    }
+}
+
+/// Obtain the storage key for the spending policy of an account
+pub fn spending_policy_key(owner: &Address) -> storage::Key {
+    storage::Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(owner.to_owned()),
+            DbKeySeg::StringSeg(Keys::VALUES.spending_policy.to_string()),
+        ],
+    }
+}
+
+/// Check if the given storage key is a spending policy key. If it is, returns
+/// the owner.
+pub fn is_spending_policy_key(key: &storage::Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(owner), DbKeySeg::StringSeg(prefix)]
+            if prefix.as_str() == Keys::VALUES.spending_policy =>
+        {
+            Some(owner)
+        }
+        _ => None,
+    }
+}
+
+/// Obtain the storage key for the amount of the token spent by the owner in
+/// the last epoch of its spendings
+pub fn spent_amount_key(owner: &Address, token: &Address) -> storage::Key {
+    storage::Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(owner.to_owned()),
+            DbKeySeg::StringSeg(Keys::VALUES.spent_amounts.to_string()),
+            DbKeySeg::AddressSeg(token.to_owned()),
+        ],
+    }
+}
+
+/// Check if the given storage key is a spent amount key. If it is, returns
+/// the owner and the token.
+pub fn is_spent_amount_key(key: &storage::Key) -> Option<(&Address, &Address)> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(owner),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::AddressSeg(token),
+        ] if prefix.as_str() == Keys::VALUES.spent_amounts => {
+            Some((owner, token))
+        }
+        _ => None,
+    }
+}
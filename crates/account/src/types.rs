@@ -1,7 +1,12 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use namada_core::account::AccountPublicKeysMap;
 use namada_core::address::Address;
 use namada_core::borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use namada_core::hash::Hash;
 use namada_core::key::common;
+use namada_core::storage::Epoch;
+use namada_core::token::Amount;
 use serde::{Deserialize, Serialize};
 
 /// A tx data type to initialize a new established account
@@ -22,8 +27,12 @@ pub struct InitAccount {
     pub public_keys: Vec<common::PublicKey>,
     /// The VP code hash
     pub vp_code_hash: Hash,
-    /// The account signature threshold
+    /// The account signature threshold, i.e. the minimum sum of the weights
+    /// of the signing keys
     pub threshold: u8,
+    /// The weights of the public keys, in the same order. If empty, every
+    /// key has a weight of 1.
+    pub public_key_weights: Vec<u8>,
 }
 
 /// A tx data type to update an account's validity predicate
@@ -46,8 +55,194 @@ pub struct UpdateAccount {
     /// for signature verification of transactions for the newly created
     /// account.
     pub public_keys: Vec<common::PublicKey>,
-    /// The account signature threshold
+    /// The account signature threshold, i.e. the minimum sum of the weights
+    /// of the signing keys
     pub threshold: Option<u8>,
+    /// The weights of the public keys, in the same order. If empty, every
+    /// key has a weight of 1. The weights are replaced whenever either the
+    /// public keys or the weights are updated.
+    pub public_key_weights: Vec<u8>,
+}
+
+/// An allowance granted by an account to pay for the wrapper fees of another
+/// account
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct FeeAllowance {
+    /// The token in which the fees can be paid
+    pub token: Address,
+    /// The remaining amount of the token that can be spent on fees
+    pub spend_limit: Amount,
+    /// The last epoch in which the allowance can be used, if any
+    pub expiration: Option<Epoch>,
+    /// The code hashes of the inner transactions whose fees can be paid. If
+    /// empty, the fees of any transaction can be paid.
+    pub allowed_code_hashes: BTreeSet<Hash>,
+}
+
+impl FeeAllowance {
+    /// Check if the allowance has expired at the given epoch
+    pub fn is_expired(&self, current_epoch: Epoch) -> bool {
+        self.expiration
+            .map(|expiration| current_epoch > expiration)
+            .unwrap_or_default()
+    }
+
+    /// Check if the allowance can pay for the fees of a transaction with the
+    /// given code hash
+    pub fn allows_code(&self, code_hash: &Hash) -> bool {
+        self.allowed_code_hashes.is_empty()
+            || self.allowed_code_hashes.contains(code_hash)
+    }
+}
+
+/// A tx data type to grant a fee allowance to an account, replacing any
+/// allowance previously granted to it by the same granter
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct GrantFeeAllowance {
+    /// The account paying for the fees
+    pub granter: Address,
+    /// The account whose wrapper fees are paid
+    pub grantee: Address,
+    /// The granted allowance
+    pub allowance: FeeAllowance,
+}
+
+/// A tx data type to revoke a fee allowance
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct RevokeFeeAllowance {
+    /// The account that granted the allowance
+    pub granter: Address,
+    /// The account that was granted the allowance
+    pub grantee: Address,
+}
+
+/// A policy restricting the debits of an account's balances. Above the
+/// spending caps, the debits must be authorized by the account's threshold.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct SpendingPolicy {
+    /// The maximum amounts of the tokens that can be spent in an epoch with
+    /// the signatures of the spending keys only
+    pub spending_caps: BTreeMap<Address, Amount>,
+    /// The only accounts that can be credited by the debits of the account's
+    /// balances. If empty, any account can be credited.
+    pub allowed_destinations: BTreeSet<Address>,
+    /// The public keys authorized to sign the debits within the spending caps
+    pub spending_keys: Vec<common::PublicKey>,
+    /// The number of signatures of the spending keys required to authorize
+    /// the debits within the spending caps
+    pub spending_threshold: u8,
+}
+
+impl SpendingPolicy {
+    /// Check that the spending threshold can be met by the spending keys. A
+    /// policy without spending keys must have a null threshold.
+    pub fn is_valid(&self) -> bool {
+        usize::from(self.spending_threshold) <= self.spending_keys.len()
+            && (self.spending_threshold > 0 || self.spending_keys.is_empty())
+    }
+
+    /// Check if the debits of the account's balances can credit the given
+    /// account
+    pub fn allows_destination(&self, destination: &Address) -> bool {
+        self.allowed_destinations.is_empty()
+            || self.allowed_destinations.contains(destination)
+    }
+
+    /// The map between indexes and the spending keys
+    pub fn spending_keys_map(&self) -> AccountPublicKeysMap {
+        AccountPublicKeysMap::from_iter(self.spending_keys.clone())
+    }
+}
+
+/// The amount of a token spent by an account within the spending cap of an
+/// epoch
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct SpentAmount {
+    /// The epoch of the spendings
+    pub epoch: Epoch,
+    /// The total amount spent in the epoch
+    pub amount: Amount,
+}
+
+/// A tx data type to set the spending policy of an account, replacing any
+/// previous policy
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct UpdateSpendingPolicy {
+    /// The account whose debits are restricted
+    pub owner: Address,
+    /// The new spending policy
+    pub policy: SpendingPolicy,
+}
+
+/// A tx data type to remove the spending policy of an account
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct RemoveSpendingPolicy {
+    /// The account whose debits are restricted
+    pub owner: Address,
 }
 
 #[cfg(any(test, feature = "testing"))]
@@ -67,11 +262,13 @@ pub mod tests {
             public_keys in collection::vec(arb_common_pk(), 0..10),
         )(
             threshold in 0..=public_keys.len() as u8,
+            public_key_weights in collection::vec(1..=3u8, public_keys.len()),
             public_keys in Just(public_keys),
             vp_code_hash in arb_hash(),
         ) -> InitAccount {
             InitAccount {
                 public_keys,
+                public_key_weights,
                 vp_code_hash,
                 threshold,
             }
@@ -86,12 +283,14 @@ pub mod tests {
             addr in arb_non_internal_address(),
             vp_code_hash in option::of(arb_hash()),
             threshold in option::of(0..=public_keys.len() as u8),
+            public_key_weights in collection::vec(1..=3u8, public_keys.len()),
             public_keys in Just(public_keys),
         ) -> UpdateAccount {
             UpdateAccount {
                 addr,
                 vp_code_hash,
                 public_keys,
+                public_key_weights,
                 threshold,
             }
         }
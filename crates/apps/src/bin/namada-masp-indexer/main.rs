@@ -0,0 +1,18 @@
+use color_eyre::eyre::Result;
+use namada_apps::client::masp_indexer;
+use namada_apps::{cli, logging};
+use tracing_subscriber::filter::LevelFilter;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // init error reporting
+    color_eyre::install()?;
+
+    // init logging
+    let _log_guard = logging::init_from_env_or(LevelFilter::INFO)?;
+
+    let args = cli::namada_masp_indexer_cli();
+    // run the indexer
+    masp_indexer::run(args).await?;
+    Ok(())
+}
@@ -31,13 +31,31 @@ pub fn main() -> Result<()> {
             }
             cmds::Ledger::DumpDb(cmds::LedgerDumpDb(args)) => {
                 let chain_ctx = ctx.take_chain_or_exit();
-                ledger::dump_db(chain_ctx.config.ledger, args);
+                ledger::dump_db(chain_ctx.config.ledger, args)
+                    .wrap_err("Failed to dump the DB")?;
             }
-            cmds::Ledger::RollBack(_) => {
+            cmds::Ledger::RollBack(cmds::LedgerRollBack(args)) => {
                 let chain_ctx = ctx.take_chain_or_exit();
-                ledger::rollback(chain_ctx.config.ledger)
+                ledger::rollback(chain_ctx.config.ledger, args)
                     .wrap_err("Failed to rollback the Namada node")?;
             }
+            cmds::Ledger::ExportGenesis(cmds::LedgerExportGenesis(args)) => {
+                let chain_ctx = ctx.take_chain_or_exit();
+                ledger::export_genesis(chain_ctx.config.ledger, args)
+                    .wrap_err("Failed to export the genesis")?;
+            }
+            cmds::Ledger::CheckDb(cmds::LedgerCheckDb(args)) => {
+                let chain_ctx = ctx.take_chain_or_exit();
+                ledger::check_db(chain_ctx.config.ledger, args)
+                    .wrap_err("Failed to check the DB")?;
+            }
+            cmds::Ledger::DryRunMigrations(cmds::LedgerDryRunMigrations(
+                args,
+            )) => {
+                let chain_ctx = ctx.take_chain_or_exit();
+                ledger::dry_run_migrations(chain_ctx.config.ledger, args)
+                    .wrap_err("Failed to dry-run the storage migrations")?;
+            }
         },
         cmds::NamadaNode::Config(sub) => match sub {
             cmds::Config::Gen(cmds::ConfigGen) => {
@@ -2,7 +2,7 @@
 //! `BenchShell` and helper functions to generate transactions.
 
 use std::cell::RefCell;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Write};
 use std::ops::{Deref, DerefMut};
@@ -744,7 +744,6 @@ impl Client for BenchShell {
 
         let ctx = RequestCtx {
             state: &self.state,
-            event_log: self.event_log(),
             vp_wasm_cache: self.vp_wasm_cache.read_only(),
             tx_wasm_cache: self.tx_wasm_cache.read_only(),
             storage_read_past_height_limit: None,
@@ -927,12 +926,14 @@ impl Default for BenchShieldedCtx {
         chain_ctx.wallet.gen_store_spending_key(
             ALBERT_SPENDING_KEY.to_string(),
             None,
+            None,
             true,
             &mut OsRng,
         );
         chain_ctx.wallet.gen_store_spending_key(
             BERTHA_SPENDING_KEY.to_string(),
             None,
+            None,
             true,
             &mut OsRng,
         );
@@ -999,9 +1000,11 @@ impl BenchShieldedCtx {
                 &self.shell,
                 &StdIo,
                 1,
+                1,
                 None,
                 &[spending_key.into()],
                 &[],
+                &BTreeMap::new(),
             ))
             .unwrap();
         let native_token = self.shell.state.in_mem().native_token.clone();
@@ -214,9 +214,17 @@ pub mod cmds {
             app
                 // Simple transactions
                 .subcommand(TxCustom::def().display_order(1))
+                .subcommand(TxBatch::def().display_order(1))
                 .subcommand(TxTransfer::def().display_order(1))
                 .subcommand(TxIbcTransfer::def().display_order(1))
+                .subcommand(TxConsolidateNotes::def().display_order(1))
                 .subcommand(TxUpdateAccount::def().display_order(1))
+                .subcommand(TxGrantFeeAllowance::def().display_order(1))
+                .subcommand(TxRevokeFeeAllowance::def().display_order(1))
+                .subcommand(TxScheduleTx::def().display_order(1))
+                .subcommand(TxCancelScheduledTx::def().display_order(1))
+                .subcommand(TxUpdateSpendingPolicy::def().display_order(1))
+                .subcommand(TxRemoveSpendingPolicy::def().display_order(1))
                 .subcommand(TxInitAccount::def().display_order(1))
                 .subcommand(TxRevealPk::def().display_order(1))
                 // Governance transactions
@@ -246,7 +254,9 @@ pub mod cmds {
                 .subcommand(QueryNextEpochInfo::def().display_order(5))
                 .subcommand(QueryStatus::def().display_order(5))
                 .subcommand(QueryAccount::def().display_order(5))
+                .subcommand(QueryScheduledTxs::def().display_order(5))
                 .subcommand(QueryTransfers::def().display_order(5))
+                .subcommand(QueryShieldedHistory::def().display_order(5))
                 .subcommand(QueryConversions::def().display_order(5))
                 .subcommand(QueryMaspRewardTokens::def().display_order(5))
                 .subcommand(QueryBlock::def().display_order(5))
@@ -278,10 +288,25 @@ pub mod cmds {
         fn parse(matches: &ArgMatches) -> Option<Self> {
             use NamadaClientWithContext::*;
             let tx_custom = Self::parse_with_ctx(matches, TxCustom);
+            let tx_inspect = Self::parse_with_ctx(matches, TxInspect);
+            let tx_batch = Self::parse_with_ctx(matches, TxBatch);
             let tx_transfer = Self::parse_with_ctx(matches, TxTransfer);
             let tx_ibc_transfer = Self::parse_with_ctx(matches, TxIbcTransfer);
+            let tx_consolidate_notes =
+                Self::parse_with_ctx(matches, TxConsolidateNotes);
             let tx_update_account =
                 Self::parse_with_ctx(matches, TxUpdateAccount);
+            let tx_grant_fee_allowance =
+                Self::parse_with_ctx(matches, TxGrantFeeAllowance);
+            let tx_revoke_fee_allowance =
+                Self::parse_with_ctx(matches, TxRevokeFeeAllowance);
+            let tx_schedule_tx = Self::parse_with_ctx(matches, TxScheduleTx);
+            let tx_cancel_scheduled_tx =
+                Self::parse_with_ctx(matches, TxCancelScheduledTx);
+            let tx_update_spending_policy =
+                Self::parse_with_ctx(matches, TxUpdateSpendingPolicy);
+            let tx_remove_spending_policy =
+                Self::parse_with_ctx(matches, TxRemoveSpendingPolicy);
             let tx_init_account = Self::parse_with_ctx(matches, TxInitAccount);
             let tx_become_validator =
                 Self::parse_with_ctx(matches, TxBecomeValidator);
@@ -318,7 +343,11 @@ pub mod cmds {
                 Self::parse_with_ctx(matches, QueryNextEpochInfo);
             let query_status = Self::parse_with_ctx(matches, QueryStatus);
             let query_account = Self::parse_with_ctx(matches, QueryAccount);
+            let query_scheduled_txs =
+                Self::parse_with_ctx(matches, QueryScheduledTxs);
             let query_transfers = Self::parse_with_ctx(matches, QueryTransfers);
+            let query_shielded_history =
+                Self::parse_with_ctx(matches, QueryShieldedHistory);
             let query_conversions =
                 Self::parse_with_ctx(matches, QueryConversions);
             let query_masp_reward_tokens =
@@ -357,9 +386,18 @@ pub mod cmds {
                 Self::parse_with_ctx(matches, GenIbcShieldedTransfer);
             let utils = SubCmd::parse(matches).map(Self::WithoutContext);
             tx_custom
+                .or(tx_inspect)
+                .or(tx_batch)
                 .or(tx_transfer)
                 .or(tx_ibc_transfer)
+                .or(tx_consolidate_notes)
                 .or(tx_update_account)
+                .or(tx_grant_fee_allowance)
+                .or(tx_revoke_fee_allowance)
+                .or(tx_schedule_tx)
+                .or(tx_cancel_scheduled_tx)
+                .or(tx_update_spending_policy)
+                .or(tx_remove_spending_policy)
                 .or(tx_init_account)
                 .or(tx_reveal_pk)
                 .or(tx_init_proposal)
@@ -384,6 +422,7 @@ pub mod cmds {
                 .or(query_next_epoch_info)
                 .or(query_status)
                 .or(query_transfers)
+                .or(query_shielded_history)
                 .or(query_conversions)
                 .or(query_masp_reward_tokens)
                 .or(query_block)
@@ -405,6 +444,7 @@ pub mod cmds {
                 .or(query_commission)
                 .or(query_metadata)
                 .or(query_account)
+                .or(query_scheduled_txs)
                 .or(sign_tx)
                 .or(shielded_sync)
                 .or(gen_ibc_shielded)
@@ -446,10 +486,19 @@ pub mod cmds {
     pub enum NamadaClientWithContext {
         // Ledger cmds
         TxCustom(TxCustom),
+        TxInspect(TxInspect),
+        TxBatch(TxBatch),
         TxTransfer(TxTransfer),
         TxIbcTransfer(TxIbcTransfer),
+        TxConsolidateNotes(TxConsolidateNotes),
         QueryResult(QueryResult),
         TxUpdateAccount(TxUpdateAccount),
+        TxGrantFeeAllowance(TxGrantFeeAllowance),
+        TxRevokeFeeAllowance(TxRevokeFeeAllowance),
+        TxScheduleTx(TxScheduleTx),
+        TxCancelScheduledTx(TxCancelScheduledTx),
+        TxUpdateSpendingPolicy(TxUpdateSpendingPolicy),
+        TxRemoveSpendingPolicy(TxRemoveSpendingPolicy),
         TxInitAccount(TxInitAccount),
         TxBecomeValidator(TxBecomeValidator),
         TxInitValidator(TxInitValidator),
@@ -474,7 +523,9 @@ pub mod cmds {
         QueryNextEpochInfo(QueryNextEpochInfo),
         QueryStatus(QueryStatus),
         QueryAccount(QueryAccount),
+        QueryScheduledTxs(QueryScheduledTxs),
         QueryTransfers(QueryTransfers),
+        QueryShieldedHistory(QueryShieldedHistory),
         QueryConversions(QueryConversions),
         QueryMaspRewardTokens(QueryMaspRewardTokens),
         QueryBlock(QueryBlock),
@@ -840,6 +891,9 @@ pub mod cmds {
         Reset(LedgerReset),
         DumpDb(LedgerDumpDb),
         RollBack(LedgerRollBack),
+        ExportGenesis(LedgerExportGenesis),
+        CheckDb(LedgerCheckDb),
+        DryRunMigrations(LedgerDryRunMigrations),
     }
 
     impl SubCmd for Ledger {
@@ -852,10 +906,18 @@ pub mod cmds {
                 let dump_db = SubCmd::parse(matches).map(Self::DumpDb);
                 let rollback = SubCmd::parse(matches).map(Self::RollBack);
                 let run_until = SubCmd::parse(matches).map(Self::RunUntil);
+                let export_genesis =
+                    SubCmd::parse(matches).map(Self::ExportGenesis);
+                let check_db = SubCmd::parse(matches).map(Self::CheckDb);
+                let dry_run_migrations =
+                    SubCmd::parse(matches).map(Self::DryRunMigrations);
                 run.or(reset)
                     .or(dump_db)
                     .or(rollback)
                     .or(run_until)
+                    .or(export_genesis)
+                    .or(check_db)
+                    .or(dry_run_migrations)
                     // The `run` command is the default if no sub-command given
                     .or(Some(Self::Run(LedgerRun(args::LedgerRun {
                         start_time: None,
@@ -874,6 +936,9 @@ pub mod cmds {
                 .subcommand(LedgerReset::def())
                 .subcommand(LedgerDumpDb::def())
                 .subcommand(LedgerRollBack::def())
+                .subcommand(LedgerExportGenesis::def())
+                .subcommand(LedgerCheckDb::def())
+                .subcommand(LedgerDryRunMigrations::def())
         }
     }
 
@@ -956,22 +1021,97 @@ pub mod cmds {
     }
 
     #[derive(Clone, Debug)]
-    pub struct LedgerRollBack;
+    pub struct LedgerRollBack(pub args::LedgerRollBack);
 
     impl SubCmd for LedgerRollBack {
         const CMD: &'static str = "rollback";
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
-            matches.subcommand_matches(Self::CMD).map(|_matches| Self)
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::LedgerRollBack::parse(matches)))
         }
 
         fn def() -> App {
-            App::new(Self::CMD).about(
-                "Roll Namada state back to the previous height. This command \
-                 does not create a backup of neither the Namada nor the \
-                 Tendermint state before execution: for extra safety, it is \
-                 recommended to make a backup in advance.",
-            )
+            App::new(Self::CMD)
+                .about(
+                    "Roll Namada state back to the given height, or to the \
+                     previous height if none is given. This command does not \
+                     create a backup of neither the Namada nor the Tendermint \
+                     state before execution: for extra safety, it is \
+                     recommended to make a backup in advance.",
+                )
+                .add_args::<args::LedgerRollBack>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerExportGenesis(pub args::LedgerExportGenesis);
+
+    impl SubCmd for LedgerExportGenesis {
+        const CMD: &'static str = "export-genesis";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::LedgerExportGenesis::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Export Namada ledger node's state at a block height as \
+                     genesis templates, to restart the chain from it.",
+                )
+                .add_args::<args::LedgerExportGenesis>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerCheckDb(pub args::LedgerCheckDb);
+
+    impl SubCmd for LedgerCheckDb {
+        const CMD: &'static str = "check-db";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::LedgerCheckDb::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Check the integrity of Namada ledger node's DB by \
+                     rebuilding the merkle tree from the storage subspace and \
+                     comparing it with the stored merkle tree. The node must \
+                     not be running.",
+                )
+                .add_args::<args::LedgerCheckDb>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerDryRunMigrations(pub args::LedgerDryRunMigrations);
+
+    impl SubCmd for LedgerDryRunMigrations {
+        const CMD: &'static str = "dry-run-migrations";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                Self(args::LedgerDryRunMigrations::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Apply the pending storage migrations of this binary to a \
+                     copy of Namada ledger node's DB and report the changes. \
+                     The node's DB is not modified. The node must not be \
+                     running.",
+                )
+                .add_args::<args::LedgerDryRunMigrations>()
         }
     }
 
@@ -1183,6 +1323,8 @@ pub mod cmds {
         fn parse(matches: &ArgMatches) -> Option<Self> {
             matches
                 .subcommand_matches(Self::CMD)
+                // The `inspect` subcommand is handled by `TxInspect`
+                .filter(|matches| matches.subcommand().is_none())
                 .map(|matches| TxCustom(args::TxCustom::parse(matches)))
         }
 
@@ -1190,6 +1332,54 @@ pub mod cmds {
             App::new(Self::CMD)
                 .about("Send a transaction with custom WASM code.")
                 .add_args::<args::TxCustom<args::CliTypes>>()
+                .subcommand(TxInspect::def())
+                .args_conflicts_with_subcommands(true)
+                .subcommand_negates_reqs(true)
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxInspect(pub args::TxInspect<args::CliTypes>);
+
+    impl SubCmd for TxInspect {
+        const CMD: &'static str = "inspect";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(TxCustom::CMD)
+                .and_then(|matches| matches.subcommand_matches(Self::CMD))
+                .map(|matches| TxInspect(args::TxInspect::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Decode a serialized transaction and print its content in \
+                     JSON.",
+                )
+                .add_args::<args::TxInspect<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxBatch(pub args::TxBatch<args::CliTypes>);
+
+    impl SubCmd for TxBatch {
+        const CMD: &'static str = "batch";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| TxBatch(args::TxBatch::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a batch of serialized transactions executed \
+                     atomically in a single wrapper.",
+                )
+                .add_args::<args::TxBatch<args::CliTypes>>()
         }
     }
 
@@ -1231,6 +1421,28 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TxConsolidateNotes(pub args::TxConsolidateNotes<args::CliTypes>);
+
+    impl SubCmd for TxConsolidateNotes {
+        const CMD: &'static str = "consolidate-notes";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxConsolidateNotes(args::TxConsolidateNotes::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send signed shielded transfers to self merging the small \
+                     notes of a spending key into few notes.",
+                )
+                .add_args::<args::TxConsolidateNotes<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TxUpdateAccount(pub args::TxUpdateAccount<args::CliTypes>);
 
@@ -1253,6 +1465,153 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TxGrantFeeAllowance(
+        pub args::TxGrantFeeAllowance<args::CliTypes>,
+    );
+
+    impl SubCmd for TxGrantFeeAllowance {
+        const CMD: &'static str = "grant-fee-allowance";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxGrantFeeAllowance(args::TxGrantFeeAllowance::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transaction to allow another account to \
+                     pay its transaction fees with the tokens of this account.",
+                )
+                .add_args::<args::TxGrantFeeAllowance<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxRevokeFeeAllowance(
+        pub args::TxRevokeFeeAllowance<args::CliTypes>,
+    );
+
+    impl SubCmd for TxRevokeFeeAllowance {
+        const CMD: &'static str = "revoke-fee-allowance";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxRevokeFeeAllowance(args::TxRevokeFeeAllowance::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transaction to revoke a fee allowance \
+                     previously granted to another account.",
+                )
+                .add_args::<args::TxRevokeFeeAllowance<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxScheduleTx(pub args::TxScheduleTx<args::CliTypes>);
+
+    impl SubCmd for TxScheduleTx {
+        const CMD: &'static str = "schedule-tx";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| TxScheduleTx(args::TxScheduleTx::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transaction to schedule another \
+                     transaction for an execution at a future block height or \
+                     epoch, prepaying its fee.",
+                )
+                .add_args::<args::TxScheduleTx<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxCancelScheduledTx(
+        pub args::TxCancelScheduledTx<args::CliTypes>,
+    );
+
+    impl SubCmd for TxCancelScheduledTx {
+        const CMD: &'static str = "cancel-scheduled-tx";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxCancelScheduledTx(args::TxCancelScheduledTx::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transaction to cancel a scheduled \
+                     transaction and refund its prepaid fee.",
+                )
+                .add_args::<args::TxCancelScheduledTx<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxUpdateSpendingPolicy(
+        pub args::TxUpdateSpendingPolicy<args::CliTypes>,
+    );
+
+    impl SubCmd for TxUpdateSpendingPolicy {
+        const CMD: &'static str = "update-spending-policy";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxUpdateSpendingPolicy(args::TxUpdateSpendingPolicy::parse(
+                    matches,
+                ))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transaction to set the spending policy of \
+                     an account.",
+                )
+                .add_args::<args::TxUpdateSpendingPolicy<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxRemoveSpendingPolicy(
+        pub args::TxRemoveSpendingPolicy<args::CliTypes>,
+    );
+
+    impl SubCmd for TxRemoveSpendingPolicy {
+        const CMD: &'static str = "remove-spending-policy";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxRemoveSpendingPolicy(args::TxRemoveSpendingPolicy::parse(
+                    matches,
+                ))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transaction to remove the spending policy \
+                     of an account.",
+                )
+                .add_args::<args::TxRemoveSpendingPolicy<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TxInitAccount(pub args::TxInitAccount<args::CliTypes>);
 
@@ -1591,6 +1950,28 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct QueryScheduledTxs(pub args::QueryScheduledTxs<args::CliTypes>);
+
+    impl SubCmd for QueryScheduledTxs {
+        const CMD: &'static str = "query-scheduled-txs";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryScheduledTxs(args::QueryScheduledTxs::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Query the transactions scheduled at a block height or \
+                     epoch.",
+                )
+                .add_args::<args::QueryScheduledTxs<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct QueryConversions(pub args::QueryConversions<args::CliTypes>);
 
@@ -1767,6 +2148,30 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct QueryShieldedHistory(
+        pub args::QueryShieldedHistory<args::CliTypes>,
+    );
+
+    impl SubCmd for QueryShieldedHistory {
+        const CMD: &'static str = "shielded-history";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryShieldedHistory(args::QueryShieldedHistory::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Export the history of the notes received and spent by \
+                     viewing keys to JSON or CSV.",
+                )
+                .add_args::<args::QueryShieldedHistory<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct QueryCommissionRate(
         pub args::QueryCommissionRate<args::CliTypes>,
@@ -2935,6 +3340,7 @@ pub mod args {
     use namada::core::chain::{ChainId, ChainIdPrefix};
     use namada::core::dec::Dec;
     use namada::core::ethereum_events::EthAddress;
+    use namada::core::hash::Hash;
     use namada::core::keccak::KeccakHash;
     use namada::core::key::*;
     use namada::core::masp::PaymentAddress;
@@ -2943,18 +3349,25 @@ pub mod args {
     use namada::core::token;
     use namada::core::token::NATIVE_MAX_DECIMAL_PLACES;
     use namada::ibc::core::host::types::identifiers::{ChannelId, PortId};
+    use namada::tx::data::schedule::ScheduleTarget;
     use namada::tx::data::GasLimit;
     pub use namada_sdk::args::*;
+    use namada_sdk::masp::{
+        DEFAULT_CONSOLIDATION_MAX_DESCRIPTIONS, DEFAULT_MAX_CONCURRENT_FETCHES,
+        DEFAULT_SYNC_BATCH_SIZE,
+    };
     pub use namada_sdk::tx::{
         TX_BECOME_VALIDATOR_WASM, TX_BOND_WASM, TX_BRIDGE_POOL_WASM,
-        TX_CHANGE_COMMISSION_WASM, TX_CHANGE_CONSENSUS_KEY_WASM,
-        TX_CHANGE_METADATA_WASM, TX_CLAIM_REWARDS_WASM,
-        TX_DEACTIVATE_VALIDATOR_WASM, TX_IBC_WASM, TX_INIT_ACCOUNT_WASM,
+        TX_CANCEL_SCHEDULED_TX_WASM, TX_CHANGE_COMMISSION_WASM,
+        TX_CHANGE_CONSENSUS_KEY_WASM, TX_CHANGE_METADATA_WASM,
+        TX_CLAIM_REWARDS_WASM, TX_DEACTIVATE_VALIDATOR_WASM,
+        TX_GRANT_FEE_ALLOWANCE_WASM, TX_IBC_WASM, TX_INIT_ACCOUNT_WASM,
         TX_INIT_PROPOSAL, TX_REACTIVATE_VALIDATOR_WASM, TX_REDELEGATE_WASM,
-        TX_RESIGN_STEWARD, TX_REVEAL_PK, TX_TRANSFER_WASM, TX_UNBOND_WASM,
-        TX_UNJAIL_VALIDATOR_WASM, TX_UPDATE_ACCOUNT_WASM,
-        TX_UPDATE_STEWARD_COMMISSION, TX_VOTE_PROPOSAL, TX_WITHDRAW_WASM,
-        VP_USER_WASM,
+        TX_REMOVE_SPENDING_POLICY_WASM, TX_RESIGN_STEWARD, TX_REVEAL_PK,
+        TX_REVOKE_FEE_ALLOWANCE_WASM, TX_SCHEDULE_TX_WASM, TX_TRANSFER_WASM,
+        TX_UNBOND_WASM, TX_UNJAIL_VALIDATOR_WASM, TX_UPDATE_ACCOUNT_WASM,
+        TX_UPDATE_SPENDING_POLICY_WASM, TX_UPDATE_STEWARD_COMMISSION,
+        TX_VOTE_PROPOSAL, TX_WITHDRAW_WASM, VP_USER_WASM,
     };
 
     use super::context::*;
@@ -2965,6 +3378,9 @@ pub mod args {
     use crate::config::{self, Action, ActionAtHeight};
     use crate::facade::tendermint::Timeout;
     use crate::facade::tendermint_rpc::Url;
+    use crate::node::ledger::storage::dump::{
+        DumpDbColumnFamily, DumpDbFormat,
+    };
 
     pub const ADDRESS: Arg<WalletAddress> = arg("address");
     pub const ALIAS_OPT: ArgOpt<String> = ALIAS.opt();
@@ -2972,6 +3388,10 @@ pub mod args {
     pub const ALIAS_FORCE: ArgFlag = flag("alias-force");
     pub const ALIAS_MANY: ArgMulti<String, GlobPlus> = arg_multi("aliases");
     pub const ALLOW_DUPLICATE_IP: ArgFlag = flag("allow-duplicate-ip");
+    pub const ALLOWED_DESTINATIONS: ArgMulti<WalletAddress, GlobStar> =
+        arg_multi("allowed-destinations");
+    pub const ALLOWED_TX_CODE_PATHS: ArgMulti<PathBuf, GlobStar> =
+        arg_multi("allowed-tx-codes");
     pub const AMOUNT: Arg<token::DenominatedAmount> = arg("amount");
     pub const ARCHIVE_DIR: ArgOpt<PathBuf> = arg_opt("archive-dir");
     pub const AVATAR_OPT: ArgOpt<String> = arg_opt("avatar");
@@ -2984,9 +3404,12 @@ pub mod args {
         }),
     );
     pub const BATCH_SIZE_OPT: ArgDefault<u64> =
-        arg_default("batch-size", DefaultFn(|| 1));
+        arg_default("batch-size", DefaultFn(|| DEFAULT_SYNC_BATCH_SIZE));
+    pub const BIRTHDAY: ArgOpt<BlockHeight> = arg_opt("birthday");
     pub const BLOCK_HEIGHT: Arg<BlockHeight> = arg("block-height");
     pub const BLOCK_HEIGHT_OPT: ArgOpt<BlockHeight> = arg_opt("height");
+    pub const BLOCK_HEIGHT_FROM_OPT: ArgOpt<BlockHeight> =
+        arg_opt("from-height");
     pub const BRIDGE_POOL_GAS_AMOUNT: ArgDefault<token::DenominatedAmount> =
         arg_default(
             "pool-gas-amount",
@@ -3037,7 +3460,13 @@ pub mod args {
     pub const DONT_PREFETCH_WASM: ArgFlag = flag("dont-prefetch-wasm");
     pub const DRY_RUN_TX: ArgFlag = flag("dry-run");
     pub const DRY_RUN_WRAPPER_TX: ArgFlag = flag("dry-run-wrapper");
+    pub const DUMP_COLUMN_FAMILIES: ArgMulti<DumpDbColumnFamily, GlobStar> =
+        arg_multi("column-families");
+    pub const DUMP_FORMAT: ArgDefault<DumpDbFormat> =
+        arg_default("format", DefaultFn(DumpDbFormat::default));
     pub const DUMP_TX: ArgFlag = flag("dump-tx");
+    pub const DUST_THRESHOLD: ArgOpt<token::DenominatedAmount> =
+        arg_opt("dust-threshold");
     pub const EPOCH: ArgOpt<Epoch> = arg_opt("epoch");
     pub const ERC20: Arg<EthAddress> = arg("erc20");
     pub const ETH_CONFIRMATIONS: Arg<u64> = arg("confirmations");
@@ -3051,6 +3480,7 @@ pub mod args {
     );
     pub const ETH_SYNC: ArgFlag = flag("sync");
     pub const EXPIRATION_OPT: ArgOpt<DateTimeUtc> = arg_opt("expiration");
+    pub const EXPIRATION_EPOCH_OPT: ArgOpt<Epoch> = arg_opt("expiration-epoch");
     pub const EMAIL: Arg<String> = arg("email");
     pub const EMAIL_OPT: ArgOpt<String> = EMAIL.opt();
     pub const FEE_UNSHIELD_SPENDING_KEY: ArgOpt<WalletTransferSource> =
@@ -3058,8 +3488,11 @@ pub mod args {
     pub const FEE_AMOUNT_OPT: ArgOpt<token::DenominatedAmount> =
         arg_opt("gas-price");
     pub const FEE_PAYER_OPT: ArgOpt<WalletPublicKey> = arg_opt("gas-payer");
+    pub const FEE_GRANTER_OPT: ArgOpt<WalletAddress> = arg_opt("gas-granter");
     pub const FILE_PATH: Arg<String> = arg("file");
     pub const FORCE: ArgFlag = flag("force");
+    pub const GAS_ESTIMATE_MULTIPLIER_OPT: ArgOpt<Dec> =
+        arg_opt("estimate-gas");
     pub const GAS_LIMIT: ArgDefault<GasLimit> =
         arg_default("gas-limit", DefaultFn(|| GasLimit::from(25_000)));
     pub const FEE_TOKEN: ArgDefaultFromCtx<WalletAddrOrNativeToken> =
@@ -3081,6 +3514,8 @@ pub mod args {
         arg("genesis-validator").opt();
     pub const GENESIS_VALIDATOR_ADDRESS: Arg<EstablishedAddress> =
         arg("validator");
+    pub const GRANTEE: Arg<WalletAddress> = arg("grantee");
+    pub const GRANTER: Arg<WalletAddress> = arg("granter");
     pub const HALT_ACTION: ArgFlag = flag("halt");
     pub const HASH_LIST: Arg<String> = arg("hash-list");
     pub const HD_DERIVATION_PATH: ArgDefault<String> =
@@ -3091,6 +3526,8 @@ pub mod args {
     pub const HISTORIC: ArgFlag = flag("historic");
     pub const IBC_TRANSFER_MEMO_PATH: ArgOpt<PathBuf> = arg_opt("memo-path");
     pub const INPUT_OPT: ArgOpt<PathBuf> = arg_opt("input");
+    pub const KEY_PREFIXES: ArgMulti<String, GlobStar> =
+        arg_multi("key-prefixes");
     pub const LEDGER_ADDRESS_ABOUT: &str =
         "Address of a ledger node as \"{scheme}://{host}:{port}\". If the \
          scheme is not supplied, it is assumed to be TCP.";
@@ -3103,10 +3540,24 @@ pub mod args {
         }));
     pub const LIST_FIND_ADDRESSES_ONLY: ArgFlag = flag("addr");
     pub const LIST_FIND_KEYS_ONLY: ArgFlag = flag("keys");
+    pub const LISTEN_ADDRESS: ArgDefault<SocketAddr> = arg_default(
+        "listen-address",
+        DefaultFn(|| SocketAddr::from(([127, 0, 0, 1], 26670))),
+    );
     pub const LOCALHOST: ArgFlag = flag("localhost");
     pub const MAX_COMMISSION_RATE_CHANGE: Arg<Dec> =
         arg("max-commission-rate-change");
+    pub const MAX_CONCURRENT_FETCHES: ArgDefault<usize> = arg_default(
+        "max-concurrent-fetches",
+        DefaultFn(|| DEFAULT_MAX_CONCURRENT_FETCHES),
+    );
+    pub const MAX_DESCRIPTIONS: ArgDefault<usize> = arg_default(
+        "max-descriptions",
+        DefaultFn(|| DEFAULT_CONSOLIDATION_MAX_DESCRIPTIONS),
+    );
     pub const MAX_ETH_GAS: ArgOpt<u64> = arg_opt("max_eth-gas");
+    pub const MAX_REPORTED_KEYS: ArgDefault<usize> =
+        arg_default("max-reported-keys", DefaultFn(|| 10));
     pub const MEMO_OPT: ArgOpt<String> = arg_opt("memo");
     pub const MODE: ArgOpt<String> = arg_opt("mode");
     pub const NET_ADDRESS: Arg<SocketAddr> = arg("net-address");
@@ -3135,6 +3586,8 @@ pub mod args {
     pub const PUBLIC_KEY: Arg<WalletPublicKey> = arg("public-key");
     pub const PUBLIC_KEYS: ArgMulti<WalletPublicKey, GlobStar> =
         arg_multi("public-keys");
+    pub const PUBLIC_KEY_WEIGHTS: ArgMulti<u8, GlobStar> =
+        arg_multi("public-key-weights");
     pub const PROPOSAL_ID: Arg<u64> = arg("proposal-id");
     pub const PROPOSAL_ID_OPT: ArgOpt<u64> = arg_opt("proposal-id");
     pub const PROPOSAL_VOTE_PGF_OPT: ArgOpt<String> = arg_opt("pgf");
@@ -3156,20 +3609,32 @@ pub mod args {
     pub const RECEIVER: Arg<String> = arg("receiver");
     pub const RELAYER: Arg<Address> = arg("relayer");
     pub const SAFE_MODE: ArgFlag = flag("safe-mode");
+    pub const SCANNING_SERVICE: ArgOpt<String> = arg_opt("scanning-service");
+    pub const SCHEDULED_GAS_LIMIT: Arg<GasLimit> = arg("scheduled-gas-limit");
+    pub const SCHEDULED_TX_HASH: Arg<Hash> = arg("tx-hash");
     pub const SCHEME: ArgDefault<SchemeType> =
         arg_default("scheme", DefaultFn(|| SchemeType::Ed25519));
     pub const SELF_BOND_AMOUNT: Arg<token::DenominatedAmount> =
         arg("self-bond-amount");
     pub const SENDER: Arg<String> = arg("sender");
     pub const SHIELDED: ArgFlag = flag("shielded");
+    pub const SHIELDED_HISTORY_FORMAT: ArgDefault<ShieldedHistoryFormat> =
+        arg_default("format", DefaultFn(ShieldedHistoryFormat::default));
     pub const SIGNER: ArgOpt<WalletAddress> = arg_opt("signer");
     pub const SIGNING_KEYS: ArgMulti<WalletPublicKey, GlobStar> =
         arg_multi("signing-keys");
     pub const SIGNATURES: ArgMulti<PathBuf, GlobStar> = arg_multi("signatures");
     pub const SOURCE: Arg<WalletAddress> = arg("source");
     pub const SOURCE_OPT: ArgOpt<WalletAddress> = SOURCE.opt();
+    pub const SPENDING_CAPS: ArgMulti<String, GlobStar> =
+        arg_multi("spending-caps");
+    pub const SPENDING_KEY: Arg<WalletSpendingKey> = arg("spending-key");
     pub const SPENDING_KEYS: ArgMulti<WalletSpendingKey, GlobStar> =
         arg_multi("spending-keys");
+    pub const SPENDING_POLICY_KEYS: ArgMulti<WalletPublicKey, GlobStar> =
+        arg_multi("spending-policy-keys");
+    pub const SPENDING_THRESHOLD: ArgOpt<u8> = arg_opt("spending-threshold");
+    pub const SPEND_LIMIT: Arg<token::DenominatedAmount> = arg("spend-limit");
     pub const STEWARD: Arg<WalletAddress> = arg("steward");
     pub const SOURCE_VALIDATOR: Arg<WalletAddress> = arg("source-validator");
     pub const STORAGE_KEY: Arg<storage::Key> = arg("storage-key");
@@ -3211,10 +3676,13 @@ pub mod args {
     pub const VP: ArgOpt<String> = arg_opt("vp");
     pub const WALLET_ALIAS_FORCE: ArgFlag = flag("wallet-alias-force");
     pub const WASM_CHECKSUMS_PATH: Arg<PathBuf> = arg("wasm-checksums-path");
+    pub const WASM_CHECKSUMS_PATH_OPT: ArgOpt<PathBuf> =
+        WASM_CHECKSUMS_PATH.opt();
     pub const WASM_DIR: ArgOpt<PathBuf> = arg_opt("wasm-dir");
     pub const WEBSITE_OPT: ArgOpt<String> = arg_opt("website");
     pub const TX_PATH: Arg<PathBuf> = arg("tx-path");
     pub const TX_PATH_OPT: ArgOpt<PathBuf> = TX_PATH.opt();
+    pub const TX_PATHS: ArgMulti<PathBuf, GlobPlus> = arg_multi("tx-paths");
 
     /// Global command arguments
     #[derive(Clone, Debug)]
@@ -3270,28 +3738,75 @@ pub mod args {
     }
 
     #[derive(Clone, Debug)]
-    pub struct LedgerRun {
-        pub start_time: Option<DateTimeUtc>,
+    pub struct MaspIndexer {
+        pub ledger_address: Url,
+        pub data_path: PathBuf,
+        pub listen_address: SocketAddr,
+        pub batch_size: u64,
+        pub max_concurrent_fetches: usize,
     }
 
-    impl Args for LedgerRun {
+    impl Args for MaspIndexer {
         fn parse(matches: &ArgMatches) -> Self {
-            let start_time = NAMADA_START_TIME.parse(matches);
-            Self { start_time }
+            let ledger_address = LEDGER_ADDRESS.parse(matches);
+            let data_path = DATA_PATH.parse(matches);
+            let listen_address = LISTEN_ADDRESS.parse(matches);
+            let batch_size = BATCH_SIZE_OPT.parse(matches);
+            let max_concurrent_fetches = MAX_CONCURRENT_FETCHES.parse(matches);
+            Self {
+                ledger_address,
+                data_path,
+                listen_address,
+                batch_size,
+                max_concurrent_fetches,
+            }
         }
 
         fn def(app: App) -> App {
-            app.arg(NAMADA_START_TIME.def().help(
-                "The start time of the ledger. Accepts a relaxed form of \
-                 RFC3339. A space or a 'T' are accepted as the separator \
-                 between the date and time components. Additional spaces are \
-                 allowed between each component.\nAll of these examples are \
-                 equivalent:\n2023-01-20T12:12:12Z\n2023-01-20 \
-                 12:12:12Z\n2023-  01-20T12:  12:12Z",
-            ))
-        }
-    }
-
+            app.arg(LEDGER_ADDRESS.def().help(LEDGER_ADDRESS_ABOUT))
+                .arg(DATA_PATH.def().help(
+                    "The directory in which to persist the index, so that it \
+                     resumes from the last indexed block on restart.",
+                ))
+                .arg(LISTEN_ADDRESS.def().help(
+                    "The address on which to serve the indexed shielded \
+                     transactions. Default is 127.0.0.1:26670.",
+                ))
+                .arg(BATCH_SIZE_OPT.def().help(
+                    "Optional batch size which determines how many blocks to \
+                     fetch before serving their shielded transactions. \
+                     Default is 100.",
+                ))
+                .arg(MAX_CONCURRENT_FETCHES.def().help(
+                    "Optional maximum number of blocks to fetch concurrently. \
+                     Default is 10.",
+                ))
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerRun {
+        pub start_time: Option<DateTimeUtc>,
+    }
+
+    impl Args for LedgerRun {
+        fn parse(matches: &ArgMatches) -> Self {
+            let start_time = NAMADA_START_TIME.parse(matches);
+            Self { start_time }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(NAMADA_START_TIME.def().help(
+                "The start time of the ledger. Accepts a relaxed form of \
+                 RFC3339. A space or a 'T' are accepted as the separator \
+                 between the date and time components. Additional spaces are \
+                 allowed between each component.\nAll of these examples are \
+                 equivalent:\n2023-01-20T12:12:12Z\n2023-01-20 \
+                 12:12:12Z\n2023-  01-20T12:  12:12Z",
+            ))
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct LedgerRunUntil {
         pub time: Option<DateTimeUtc>,
@@ -3336,10 +3851,13 @@ pub mod args {
 
     #[derive(Clone, Debug)]
     pub struct LedgerDumpDb {
-        // TODO: allow to specify height
         pub block_height: Option<BlockHeight>,
         pub out_file_path: PathBuf,
         pub historic: bool,
+        pub key_prefixes: Vec<String>,
+        pub column_families: Vec<DumpDbColumnFamily>,
+        pub from_height: Option<BlockHeight>,
+        pub format: DumpDbFormat,
     }
 
     impl Args for LedgerDumpDb {
@@ -3349,11 +3867,19 @@ pub mod args {
                 .parse(matches)
                 .unwrap_or_else(|| PathBuf::from("db_dump".to_string()));
             let historic = HISTORIC.parse(matches);
+            let key_prefixes = KEY_PREFIXES.parse(matches);
+            let column_families = DUMP_COLUMN_FAMILIES.parse(matches);
+            let from_height = BLOCK_HEIGHT_FROM_OPT.parse(matches);
+            let format = DUMP_FORMAT.parse(matches);
 
             Self {
                 block_height,
                 out_file_path,
                 historic,
+                key_prefixes,
+                column_families,
+                from_height,
+                format,
             }
         }
 
@@ -3372,6 +3898,125 @@ pub mod args {
                     .def()
                     .help("If provided, dump also the diff of the last height"),
             )
+            .arg(KEY_PREFIXES.def().help(
+                "Comma separated list of prefixes of the subspace keys to \
+                 dump. The diffs are matched on their subspace key. Defaults \
+                 to all the keys.",
+            ))
+            .arg(DUMP_COLUMN_FAMILIES.def().help(
+                "Comma separated list of the column families to dump, out of \
+                 subspace, diffs, state, block and replay_protection. \
+                 Defaults to subspace and replay_protection, and also diffs \
+                 and block with --historic.",
+            ))
+            .arg(BLOCK_HEIGHT_FROM_OPT.def().help(
+                "If provided, only dump the subspace keys that changed after \
+                 this height up to the dumped height, with their values at \
+                 both heights.",
+            ))
+            .arg(DUMP_FORMAT.def().help(
+                "The output format, either toml or json-lines. The json-lines \
+                 format also decodes the values of the balances, bonds and \
+                 parameters keys. Defaults to toml.",
+            ))
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerRollBack {
+        pub block_height: Option<BlockHeight>,
+    }
+
+    impl Args for LedgerRollBack {
+        fn parse(matches: &ArgMatches) -> Self {
+            let block_height = BLOCK_HEIGHT_OPT.parse(matches);
+
+            Self { block_height }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(BLOCK_HEIGHT_OPT.def().help(
+                "The block height to roll back to. It must be lower than the \
+                 last committed height and its state must not have been \
+                 pruned. The MASP and IBC counter keys only keep the diff of \
+                 their last change, so rolling back more than one block is \
+                 refused if any of them changed in the rolled back blocks, \
+                 and once they have been rolled back, the state can't be \
+                 rolled back below the height anymore. Defaults to the height \
+                 preceding the last committed block.",
+            ))
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerExportGenesis {
+        pub block_height: Option<BlockHeight>,
+        pub output_folder: PathBuf,
+    }
+
+    impl Args for LedgerExportGenesis {
+        fn parse(matches: &ArgMatches) -> Self {
+            let block_height = BLOCK_HEIGHT_OPT.parse(matches);
+            let output_folder = OUTPUT_FOLDER_PATH
+                .parse(matches)
+                .unwrap_or_else(|| PathBuf::from("genesis_export".to_string()));
+
+            Self {
+                block_height,
+                output_folder,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(BLOCK_HEIGHT_OPT.def().help(
+                "The block height to export. Defaults to the latest committed \
+                 block.",
+            ))
+            .arg(OUTPUT_FOLDER_PATH.def().help(
+                "Path to the directory to write the genesis templates into. \
+                 Defaults to \"genesis_export\" in the current working \
+                 directory.",
+            ))
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerCheckDb {
+        pub max_reported_keys: usize,
+    }
+
+    impl Args for LedgerCheckDb {
+        fn parse(matches: &ArgMatches) -> Self {
+            let max_reported_keys = MAX_REPORTED_KEYS.parse(matches);
+
+            Self { max_reported_keys }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(MAX_REPORTED_KEYS.def().help(
+                "The maximum number of divergent keys to report for every \
+                 kind of inconsistency.",
+            ))
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerDryRunMigrations {
+        pub max_reported_keys: usize,
+    }
+
+    impl Args for LedgerDryRunMigrations {
+        fn parse(matches: &ArgMatches) -> Self {
+            let max_reported_keys = MAX_REPORTED_KEYS.parse(matches);
+
+            Self { max_reported_keys }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(MAX_REPORTED_KEYS.def().help(
+                "The maximum number of changed keys to report for every \
+                 migration.",
+            ))
         }
     }
 
@@ -4002,6 +4647,48 @@ pub mod args {
         }
     }
 
+    impl CliToSdk<TxBatch<SdkTypes>> for TxBatch<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> TxBatch<SdkTypes> {
+            TxBatch::<SdkTypes> {
+                tx: self.tx.to_sdk(ctx),
+                serialized_txs: self
+                    .serialized_txs
+                    .into_iter()
+                    .map(|path| {
+                        std::fs::read(path)
+                            .expect("Expected a file at given path")
+                    })
+                    .collect(),
+                owner: ctx.borrow_chain_or_exit().get(&self.owner),
+            }
+        }
+    }
+
+    impl Args for TxBatch<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let serialized_txs = TX_PATHS.parse(matches);
+            let owner = OWNER.parse(matches);
+            Self {
+                tx,
+                serialized_txs,
+                owner,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(TX_PATHS.def().help(
+                    "The paths to the serialized transactions to batch, in \
+                     the order in which they must be executed.",
+                ))
+                .arg(OWNER.def().help(
+                    "The address corresponding to the signatures or signing \
+                     keys.",
+                ))
+        }
+    }
+
     impl CliToSdk<TxTransfer<SdkTypes>> for TxTransfer<CliTypes> {
         fn to_sdk(self, ctx: &mut Context) -> TxTransfer<SdkTypes> {
             let tx = self.tx.to_sdk(ctx);
@@ -4050,6 +4737,61 @@ pub mod args {
         }
     }
 
+    impl CliToSdk<TxConsolidateNotes<SdkTypes>>
+        for TxConsolidateNotes<CliTypes>
+    {
+        fn to_sdk(self, ctx: &mut Context) -> TxConsolidateNotes<SdkTypes> {
+            let tx = self.tx.to_sdk(ctx);
+            let chain_ctx = ctx.borrow_mut_chain_or_exit();
+            TxConsolidateNotes::<SdkTypes> {
+                tx,
+                spending_key: chain_ctx.get_cached(&self.spending_key),
+                token: chain_ctx.get(&self.token),
+                threshold: self.threshold,
+                max_descriptions: self.max_descriptions,
+                tx_code_path: self.tx_code_path.to_path_buf(),
+            }
+        }
+    }
+
+    impl Args for TxConsolidateNotes<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let spending_key = SPENDING_KEY.parse(matches);
+            let token = TOKEN.parse(matches);
+            let threshold =
+                DUST_THRESHOLD.parse(matches).map(InputAmount::Unvalidated);
+            let max_descriptions = MAX_DESCRIPTIONS.parse(matches);
+            let tx_code_path = PathBuf::from(TX_TRANSFER_WASM);
+            Self {
+                tx,
+                spending_key,
+                token,
+                threshold,
+                max_descriptions,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(
+                    SPENDING_KEY
+                        .def()
+                        .help("The spending key of the notes to consolidate."),
+                )
+                .arg(TOKEN.def().help("The token of the notes to consolidate."))
+                .arg(DUST_THRESHOLD.def().help(
+                    "Only consolidate the notes holding less than this amount \
+                     in decimal. Defaults to all the notes of the token.",
+                ))
+                .arg(MAX_DESCRIPTIONS.def().help(
+                    "The maximum number of spend and output descriptions of \
+                     each consolidation transaction. Default is 20.",
+                ))
+        }
+    }
+
     impl CliToSdk<TxIbcTransfer<SdkTypes>> for TxIbcTransfer<CliTypes> {
         fn to_sdk(self, ctx: &mut Context) -> TxIbcTransfer<SdkTypes> {
             let tx = self.tx.to_sdk(ctx);
@@ -4141,6 +4883,7 @@ pub mod args {
                     .iter()
                     .map(|pk| chain_ctx.get(pk))
                     .collect(),
+                public_key_weights: self.public_key_weights,
                 threshold: self.threshold,
             }
         }
@@ -4154,11 +4897,13 @@ pub mod args {
                 .unwrap_or_else(|| PathBuf::from(VP_USER_WASM));
             let tx_code_path = PathBuf::from(TX_INIT_ACCOUNT_WASM);
             let public_keys = PUBLIC_KEYS.parse(matches);
+            let public_key_weights = PUBLIC_KEY_WEIGHTS.parse(matches);
             let threshold = THRESHOLD.parse(matches);
             Self {
                 tx,
                 vp_code_path,
                 public_keys,
+                public_key_weights,
                 threshold,
                 tx_code_path,
             }
@@ -4175,10 +4920,14 @@ pub mod args {
                     "A list public keys to be associated with the new account \
                      in hexadecimal encoding.",
                 ))
+                .arg(PUBLIC_KEY_WEIGHTS.def().help(
+                    "The weights of the public keys, in the same order. Every \
+                     key has a weight of 1 if none specified.",
+                ))
                 .arg(THRESHOLD.def().help(
-                    "The minimum number of signature to be provided for \
-                     authorization. Must be less then the maximum number of \
-                     public keys provided.",
+                    "The minimum sum of the weights of the signing keys to be \
+                     provided for authorization. Must be at most the total \
+                     weight of the public keys provided.",
                 ))
         }
     }
@@ -4460,199 +5209,587 @@ pub mod args {
                 tx,
                 vp_code_path: self.vp_code_path,
                 tx_code_path: self.tx_code_path,
-                addr: chain_ctx.get(&self.addr),
-                public_keys: self
-                    .public_keys
-                    .iter()
-                    .map(|pk| chain_ctx.get(pk))
-                    .collect(),
-                threshold: self.threshold,
+                addr: chain_ctx.get(&self.addr),
+                public_keys: self
+                    .public_keys
+                    .iter()
+                    .map(|pk| chain_ctx.get(pk))
+                    .collect(),
+                public_key_weights: self.public_key_weights,
+                threshold: self.threshold,
+            }
+        }
+    }
+
+    impl Args for TxUpdateAccount<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let vp_code_path = CODE_PATH_OPT.parse(matches);
+            let addr = ADDRESS.parse(matches);
+            let tx_code_path = PathBuf::from(TX_UPDATE_ACCOUNT_WASM);
+            let public_keys = PUBLIC_KEYS.parse(matches);
+            let public_key_weights = PUBLIC_KEY_WEIGHTS.parse(matches);
+            let threshold = THRESHOLD.parse(matches);
+            Self {
+                tx,
+                vp_code_path,
+                addr,
+                tx_code_path,
+                public_keys,
+                public_key_weights,
+                threshold,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(
+                    CODE_PATH_OPT.def().help(
+                        "The path to the new validity predicate WASM code.",
+                    ),
+                )
+                .arg(ADDRESS.def().help(
+                    "The account's address. It's key is used to produce the \
+                     signature.",
+                ))
+                .arg(PUBLIC_KEYS.def().help(
+                    "A list public keys to be associated with the new account \
+                     in hexadecimal encoding.",
+                ))
+                .arg(PUBLIC_KEY_WEIGHTS.def().help(
+                    "The weights of the public keys, in the same order. Every \
+                     key has a weight of 1 if none specified.",
+                ))
+                .arg(THRESHOLD.def().help(
+                    "The minimum sum of the weights of the signing keys to be \
+                     provided for authorization. Must be at most the total \
+                     weight of the public keys provided.",
+                ))
+        }
+    }
+
+    impl CliToSdk<Bond<SdkTypes>> for Bond<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> Bond<SdkTypes> {
+            let tx = self.tx.to_sdk(ctx);
+            let chain_ctx = ctx.borrow_chain_or_exit();
+            Bond::<SdkTypes> {
+                tx,
+                validator: chain_ctx.get(&self.validator),
+                amount: self.amount,
+                source: self.source.map(|x| chain_ctx.get(&x)),
+                tx_code_path: self.tx_code_path.to_path_buf(),
+            }
+        }
+    }
+
+    impl Args for Bond<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            let amount = AMOUNT.parse(matches);
+            let amount = amount
+                .canonical()
+                .increase_precision(NATIVE_MAX_DECIMAL_PLACES.into())
+                .unwrap_or_else(|e| {
+                    println!("Could not parse bond amount: {:?}", e);
+                    safe_exit(1);
+                })
+                .amount();
+            let source = SOURCE_OPT.parse(matches);
+            let tx_code_path = PathBuf::from(TX_BOND_WASM);
+            Self {
+                tx,
+                validator,
+                amount,
+                source,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(VALIDATOR.def().help("Validator address."))
+                .arg(AMOUNT.def().help("Amount of tokens to stake in a bond."))
+                .arg(SOURCE_OPT.def().help(
+                    "Source address for delegations. For self-bonds, the \
+                     validator is also the source.",
+                ))
+        }
+    }
+
+    impl CliToSdk<Unbond<SdkTypes>> for Unbond<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> Unbond<SdkTypes> {
+            let tx = self.tx.to_sdk(ctx);
+            let chain_ctx = ctx.borrow_chain_or_exit();
+            Unbond::<SdkTypes> {
+                tx,
+                validator: chain_ctx.get(&self.validator),
+                amount: self.amount,
+                source: self.source.map(|x| chain_ctx.get(&x)),
+                tx_code_path: self.tx_code_path.to_path_buf(),
+            }
+        }
+    }
+
+    impl Args for Unbond<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            let amount = AMOUNT.parse(matches);
+            let amount = amount
+                .canonical()
+                .increase_precision(NATIVE_MAX_DECIMAL_PLACES.into())
+                .unwrap_or_else(|e| {
+                    println!("Could not parse bond amount: {:?}", e);
+                    safe_exit(1);
+                })
+                .amount();
+            let source = SOURCE_OPT.parse(matches);
+            let tx_code_path = PathBuf::from(TX_UNBOND_WASM);
+            Self {
+                tx,
+                validator,
+                amount,
+                source,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(VALIDATOR.def().help("Validator address."))
+                .arg(
+                    AMOUNT
+                        .def()
+                        .help("Amount of tokens to unbond from a bond."),
+                )
+                .arg(SOURCE_OPT.def().help(
+                    "Source address for unbonding from delegations. For \
+                     unbonding from self-bonds, the validator is also the \
+                     source.",
+                ))
+        }
+    }
+
+    impl CliToSdk<UpdateStewardCommission<SdkTypes>>
+        for UpdateStewardCommission<CliTypes>
+    {
+        fn to_sdk(
+            self,
+            ctx: &mut Context,
+        ) -> UpdateStewardCommission<SdkTypes> {
+            UpdateStewardCommission::<SdkTypes> {
+                tx: self.tx.to_sdk(ctx),
+                steward: ctx.borrow_chain_or_exit().get(&self.steward),
+                commission: std::fs::read(self.commission).expect(""),
+                tx_code_path: self.tx_code_path.to_path_buf(),
+            }
+        }
+    }
+
+    impl Args for UpdateStewardCommission<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let steward = STEWARD.parse(matches);
+            let commission = DATA_PATH.parse(matches);
+            let tx_code_path = PathBuf::from(TX_UPDATE_STEWARD_COMMISSION);
+            Self {
+                tx,
+                steward,
+                commission,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(STEWARD.def().help("Steward address."))
+                .arg(DATA_PATH.def().help(
+                    "The path to the file that describes the commission \
+                     split. The file must contain a map from namada address \
+                     to a percentage. Percentages must sum to 1 or less.",
+                ))
+        }
+    }
+
+    impl CliToSdk<TxGrantFeeAllowance<SdkTypes>>
+        for TxGrantFeeAllowance<CliTypes>
+    {
+        fn to_sdk(self, ctx: &mut Context) -> TxGrantFeeAllowance<SdkTypes> {
+            let tx = self.tx.to_sdk(ctx);
+            let chain_ctx = ctx.borrow_mut_chain_or_exit();
+            TxGrantFeeAllowance::<SdkTypes> {
+                tx,
+                granter: chain_ctx.get(&self.granter),
+                grantee: chain_ctx.get(&self.grantee),
+                token: chain_ctx.get(&self.token),
+                spend_limit: self.spend_limit,
+                expiration: self.expiration,
+                allowed_tx_code_paths: self.allowed_tx_code_paths,
+                tx_code_path: self.tx_code_path,
+            }
+        }
+    }
+
+    impl Args for TxGrantFeeAllowance<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let granter = GRANTER.parse(matches);
+            let grantee = GRANTEE.parse(matches);
+            let token = TOKEN.parse(matches);
+            let spend_limit =
+                InputAmount::Unvalidated(SPEND_LIMIT.parse(matches));
+            let expiration = EXPIRATION_EPOCH_OPT.parse(matches);
+            let allowed_tx_code_paths = ALLOWED_TX_CODE_PATHS.parse(matches);
+            let tx_code_path = PathBuf::from(TX_GRANT_FEE_ALLOWANCE_WASM);
+            Self {
+                tx,
+                granter,
+                grantee,
+                token,
+                spend_limit,
+                expiration,
+                allowed_tx_code_paths,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(GRANTER.def().help(
+                    "The address of the account paying for the fees. It's key \
+                     is used to produce the signature.",
+                ))
+                .arg(
+                    GRANTEE.def().help(
+                        "The address of the account whose fees are paid.",
+                    ),
+                )
+                .arg(
+                    TOKEN
+                        .def()
+                        .help("The token in which the fees can be paid."),
+                )
+                .arg(SPEND_LIMIT.def().help(
+                    "The maximum amount of the token that can be spent on \
+                     fees.",
+                ))
+                .arg(EXPIRATION_EPOCH_OPT.def().help(
+                    "The last epoch in which the allowance can be used. If \
+                     not provided, the allowance never expires.",
+                ))
+                .arg(ALLOWED_TX_CODE_PATHS.def().help(
+                    "The paths to the WASM codes of the transactions whose \
+                     fees can be paid. If not provided, the fees of any \
+                     transaction can be paid.",
+                ))
+        }
+    }
+
+    impl CliToSdk<TxRevokeFeeAllowance<SdkTypes>>
+        for TxRevokeFeeAllowance<CliTypes>
+    {
+        fn to_sdk(self, ctx: &mut Context) -> TxRevokeFeeAllowance<SdkTypes> {
+            let tx = self.tx.to_sdk(ctx);
+            let chain_ctx = ctx.borrow_mut_chain_or_exit();
+            TxRevokeFeeAllowance::<SdkTypes> {
+                tx,
+                granter: chain_ctx.get(&self.granter),
+                grantee: chain_ctx.get(&self.grantee),
+                tx_code_path: self.tx_code_path,
+            }
+        }
+    }
+
+    impl Args for TxRevokeFeeAllowance<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let granter = GRANTER.parse(matches);
+            let grantee = GRANTEE.parse(matches);
+            let tx_code_path = PathBuf::from(TX_REVOKE_FEE_ALLOWANCE_WASM);
+            Self {
+                tx,
+                granter,
+                grantee,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(GRANTER.def().help(
+                    "The address of the account that granted the allowance. \
+                     It's key is used to produce the signature.",
+                ))
+                .arg(GRANTEE.def().help(
+                    "The address of the account that was granted the \
+                     allowance.",
+                ))
+        }
+    }
+
+    /// Parse the block height or epoch at which a scheduled tx is executed
+    fn parse_schedule_target(matches: &ArgMatches) -> ScheduleTarget {
+        match (BLOCK_HEIGHT_OPT.parse(matches), EPOCH.parse(matches)) {
+            (Some(height), _) => ScheduleTarget::Height(height),
+            (None, Some(epoch)) => ScheduleTarget::Epoch(epoch),
+            (None, None) => {
+                unreachable!("Either a block height or an epoch is required")
+            }
+        }
+    }
+
+    /// Define the block height and epoch arguments of a scheduled tx, exactly
+    /// one of which must be provided
+    fn def_schedule_target(app: App) -> App {
+        app.arg(
+            BLOCK_HEIGHT_OPT
+                .def()
+                .help("The block height at which the transaction is executed."),
+        )
+        .arg(EPOCH.def().help(
+            "The epoch at the start of which the transaction is executed.",
+        ))
+        .group(
+            ArgGroup::new("schedule_target")
+                .args([BLOCK_HEIGHT_OPT.name, EPOCH.name])
+                .required(true),
+        )
+    }
+
+    impl CliToSdk<TxScheduleTx<SdkTypes>> for TxScheduleTx<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> TxScheduleTx<SdkTypes> {
+            let tx = self.tx.to_sdk(ctx);
+            let chain_ctx = ctx.borrow_mut_chain_or_exit();
+            TxScheduleTx::<SdkTypes> {
+                tx,
+                owner: chain_ctx.get(&self.owner),
+                target: self.target,
+                serialized_tx: std::fs::read(self.serialized_tx).expect(""),
+                gas_limit: self.gas_limit,
+                tx_code_path: self.tx_code_path,
             }
         }
     }
 
-    impl Args for TxUpdateAccount<CliTypes> {
+    impl Args for TxScheduleTx<CliTypes> {
         fn parse(matches: &ArgMatches) -> Self {
             let tx = Tx::parse(matches);
-            let vp_code_path = CODE_PATH_OPT.parse(matches);
-            let addr = ADDRESS.parse(matches);
-            let tx_code_path = PathBuf::from(TX_UPDATE_ACCOUNT_WASM);
-            let public_keys = PUBLIC_KEYS.parse(matches);
-            let threshold = THRESHOLD.parse(matches);
+            let owner = OWNER.parse(matches);
+            let target = parse_schedule_target(matches);
+            let serialized_tx = TX_PATH.parse(matches);
+            let gas_limit = SCHEDULED_GAS_LIMIT.parse(matches);
+            let tx_code_path = PathBuf::from(TX_SCHEDULE_TX_WASM);
             Self {
                 tx,
-                vp_code_path,
-                addr,
+                owner,
+                target,
+                serialized_tx,
+                gas_limit,
                 tx_code_path,
-                public_keys,
-                threshold,
             }
         }
 
         fn def(app: App) -> App {
-            app.add_args::<Tx<CliTypes>>()
-                .arg(
-                    CODE_PATH_OPT.def().help(
-                        "The path to the new validity predicate WASM code.",
-                    ),
-                )
-                .arg(ADDRESS.def().help(
-                    "The account's address. It's key is used to produce the \
-                     signature.",
+            def_schedule_target(app.add_args::<Tx<CliTypes>>())
+                .arg(OWNER.def().help(
+                    "The address of the account scheduling the transaction \
+                     and prepaying its fee. It's key is used to produce the \
+                     signatures.",
                 ))
-                .arg(PUBLIC_KEYS.def().help(
-                    "A list public keys to be associated with the new account \
-                     in hexadecimal encoding.",
+                .arg(TX_PATH.def().help(
+                    "The path to the serialized transaction to schedule. It's \
+                     signed with the owner's keys unless it's already signed. \
+                     If it's not signed and has no expiration, its lifetime \
+                     starts at the estimated time of the target. It must not \
+                     expire before that time.",
                 ))
-                .arg(THRESHOLD.def().help(
-                    "The minimum number of signature to be provided for \
-                     authorization. Must be less then the maximum number of \
-                     public keys provided.",
+                .arg(SCHEDULED_GAS_LIMIT.def().help(
+                    "The gas limit of the execution of the scheduled \
+                     transaction. Its fee is prepaid at the gas price of this \
+                     transaction.",
                 ))
         }
     }
 
-    impl CliToSdk<Bond<SdkTypes>> for Bond<CliTypes> {
-        fn to_sdk(self, ctx: &mut Context) -> Bond<SdkTypes> {
+    impl CliToSdk<TxCancelScheduledTx<SdkTypes>>
+        for TxCancelScheduledTx<CliTypes>
+    {
+        fn to_sdk(self, ctx: &mut Context) -> TxCancelScheduledTx<SdkTypes> {
             let tx = self.tx.to_sdk(ctx);
-            let chain_ctx = ctx.borrow_chain_or_exit();
-            Bond::<SdkTypes> {
+            let chain_ctx = ctx.borrow_mut_chain_or_exit();
+            TxCancelScheduledTx::<SdkTypes> {
                 tx,
-                validator: chain_ctx.get(&self.validator),
-                amount: self.amount,
-                source: self.source.map(|x| chain_ctx.get(&x)),
-                tx_code_path: self.tx_code_path.to_path_buf(),
+                owner: chain_ctx.get(&self.owner),
+                target: self.target,
+                hash: self.hash,
+                tx_code_path: self.tx_code_path,
             }
         }
     }
 
-    impl Args for Bond<CliTypes> {
+    impl Args for TxCancelScheduledTx<CliTypes> {
         fn parse(matches: &ArgMatches) -> Self {
             let tx = Tx::parse(matches);
-            let validator = VALIDATOR.parse(matches);
-            let amount = AMOUNT.parse(matches);
-            let amount = amount
-                .canonical()
-                .increase_precision(NATIVE_MAX_DECIMAL_PLACES.into())
-                .unwrap_or_else(|e| {
-                    println!("Could not parse bond amount: {:?}", e);
-                    safe_exit(1);
-                })
-                .amount();
-            let source = SOURCE_OPT.parse(matches);
-            let tx_code_path = PathBuf::from(TX_BOND_WASM);
+            let owner = OWNER.parse(matches);
+            let target = parse_schedule_target(matches);
+            let hash = SCHEDULED_TX_HASH.parse(matches);
+            let tx_code_path = PathBuf::from(TX_CANCEL_SCHEDULED_TX_WASM);
             Self {
                 tx,
-                validator,
-                amount,
-                source,
+                owner,
+                target,
+                hash,
                 tx_code_path,
             }
         }
 
         fn def(app: App) -> App {
-            app.add_args::<Tx<CliTypes>>()
-                .arg(VALIDATOR.def().help("Validator address."))
-                .arg(AMOUNT.def().help("Amount of tokens to stake in a bond."))
-                .arg(SOURCE_OPT.def().help(
-                    "Source address for delegations. For self-bonds, the \
-                     validator is also the source.",
+            def_schedule_target(app.add_args::<Tx<CliTypes>>())
+                .arg(OWNER.def().help(
+                    "The address of the account that scheduled the \
+                     transaction. It's key is used to produce the signature.",
                 ))
+                .arg(
+                    SCHEDULED_TX_HASH
+                        .def()
+                        .help("The hash of the scheduled transaction."),
+                )
         }
     }
 
-    impl CliToSdk<Unbond<SdkTypes>> for Unbond<CliTypes> {
-        fn to_sdk(self, ctx: &mut Context) -> Unbond<SdkTypes> {
+    impl CliToSdk<TxUpdateSpendingPolicy<SdkTypes>>
+        for TxUpdateSpendingPolicy<CliTypes>
+    {
+        fn to_sdk(self, ctx: &mut Context) -> TxUpdateSpendingPolicy<SdkTypes> {
             let tx = self.tx.to_sdk(ctx);
-            let chain_ctx = ctx.borrow_chain_or_exit();
-            Unbond::<SdkTypes> {
+            let chain_ctx = ctx.borrow_mut_chain_or_exit();
+            TxUpdateSpendingPolicy::<SdkTypes> {
                 tx,
-                validator: chain_ctx.get(&self.validator),
-                amount: self.amount,
-                source: self.source.map(|x| chain_ctx.get(&x)),
-                tx_code_path: self.tx_code_path.to_path_buf(),
+                owner: chain_ctx.get(&self.owner),
+                spending_caps: self
+                    .spending_caps
+                    .iter()
+                    .map(|(token, cap)| (chain_ctx.get(token), *cap))
+                    .collect(),
+                allowed_destinations: self
+                    .allowed_destinations
+                    .iter()
+                    .map(|destination| chain_ctx.get(destination))
+                    .collect(),
+                spending_keys: self
+                    .spending_keys
+                    .iter()
+                    .map(|pk| chain_ctx.get(pk))
+                    .collect(),
+                spending_threshold: self.spending_threshold,
+                tx_code_path: self.tx_code_path,
             }
         }
     }
 
-    impl Args for Unbond<CliTypes> {
+    impl Args for TxUpdateSpendingPolicy<CliTypes> {
         fn parse(matches: &ArgMatches) -> Self {
             let tx = Tx::parse(matches);
-            let validator = VALIDATOR.parse(matches);
-            let amount = AMOUNT.parse(matches);
-            let amount = amount
-                .canonical()
-                .increase_precision(NATIVE_MAX_DECIMAL_PLACES.into())
-                .unwrap_or_else(|e| {
-                    println!("Could not parse bond amount: {:?}", e);
-                    safe_exit(1);
+            let owner = OWNER.parse(matches);
+            let spending_caps = SPENDING_CAPS
+                .parse(matches)
+                .into_iter()
+                .map(|cap| {
+                    let (token, amount) = cap
+                        .split_once(':')
+                        .and_then(|(token, amount)| {
+                            Some((token, amount.parse().ok()?))
+                        })
+                        .unwrap_or_else(|| {
+                            eprintln!(
+                                "Could not parse '{}' as a TOKEN:AMOUNT \
+                                 spending cap.",
+                                cap
+                            );
+                            safe_exit(1)
+                        });
+                    (
+                        FromContext::new(token.to_string()),
+                        InputAmount::Unvalidated(amount),
+                    )
                 })
-                .amount();
-            let source = SOURCE_OPT.parse(matches);
-            let tx_code_path = PathBuf::from(TX_UNBOND_WASM);
+                .collect();
+            let allowed_destinations = ALLOWED_DESTINATIONS.parse(matches);
+            let spending_keys = SPENDING_POLICY_KEYS.parse(matches);
+            let spending_threshold = SPENDING_THRESHOLD.parse(matches);
+            let tx_code_path = PathBuf::from(TX_UPDATE_SPENDING_POLICY_WASM);
             Self {
                 tx,
-                validator,
-                amount,
-                source,
+                owner,
+                spending_caps,
+                allowed_destinations,
+                spending_keys,
+                spending_threshold,
                 tx_code_path,
             }
         }
 
         fn def(app: App) -> App {
             app.add_args::<Tx<CliTypes>>()
-                .arg(VALIDATOR.def().help("Validator address."))
-                .arg(
-                    AMOUNT
-                        .def()
-                        .help("Amount of tokens to unbond from a bond."),
-                )
-                .arg(SOURCE_OPT.def().help(
-                    "Source address for unbonding from delegations. For \
-                     unbonding from self-bonds, the validator is also the \
-                     source.",
+                .arg(OWNER.def().help(
+                    "The address of the account whose debits are restricted. \
+                     It's key is used to produce the signature.",
+                ))
+                .arg(SPENDING_CAPS.def().help(
+                    "The maximum amounts of the tokens that can be spent in \
+                     an epoch with the signatures of the spending policy keys \
+                     only, formatted as TOKEN:AMOUNT. Above these caps, the \
+                     debits must be signed with the account's threshold.",
+                ))
+                .arg(ALLOWED_DESTINATIONS.def().help(
+                    "The addresses of the only accounts that can be credited \
+                     by the debits of the account. If not provided, any \
+                     account can be credited.",
+                ))
+                .arg(SPENDING_POLICY_KEYS.def().help(
+                    "The public keys authorized to sign the debits within the \
+                     spending caps.",
+                ))
+                .arg(SPENDING_THRESHOLD.def().help(
+                    "The number of signatures of the spending policy keys \
+                     required to authorize the debits within the spending \
+                     caps. Defaults to 1 with a single key.",
                 ))
         }
     }
 
-    impl CliToSdk<UpdateStewardCommission<SdkTypes>>
-        for UpdateStewardCommission<CliTypes>
+    impl CliToSdk<TxRemoveSpendingPolicy<SdkTypes>>
+        for TxRemoveSpendingPolicy<CliTypes>
     {
-        fn to_sdk(
-            self,
-            ctx: &mut Context,
-        ) -> UpdateStewardCommission<SdkTypes> {
-            UpdateStewardCommission::<SdkTypes> {
-                tx: self.tx.to_sdk(ctx),
-                steward: ctx.borrow_chain_or_exit().get(&self.steward),
-                commission: std::fs::read(self.commission).expect(""),
-                tx_code_path: self.tx_code_path.to_path_buf(),
+        fn to_sdk(self, ctx: &mut Context) -> TxRemoveSpendingPolicy<SdkTypes> {
+            let tx = self.tx.to_sdk(ctx);
+            let chain_ctx = ctx.borrow_mut_chain_or_exit();
+            TxRemoveSpendingPolicy::<SdkTypes> {
+                tx,
+                owner: chain_ctx.get(&self.owner),
+                tx_code_path: self.tx_code_path,
             }
         }
     }
 
-    impl Args for UpdateStewardCommission<CliTypes> {
+    impl Args for TxRemoveSpendingPolicy<CliTypes> {
         fn parse(matches: &ArgMatches) -> Self {
             let tx = Tx::parse(matches);
-            let steward = STEWARD.parse(matches);
-            let commission = DATA_PATH.parse(matches);
-            let tx_code_path = PathBuf::from(TX_UPDATE_STEWARD_COMMISSION);
+            let owner = OWNER.parse(matches);
+            let tx_code_path = PathBuf::from(TX_REMOVE_SPENDING_POLICY_WASM);
             Self {
                 tx,
-                steward,
-                commission,
+                owner,
                 tx_code_path,
             }
         }
 
         fn def(app: App) -> App {
-            app.add_args::<Tx<CliTypes>>()
-                .arg(STEWARD.def().help("Steward address."))
-                .arg(DATA_PATH.def().help(
-                    "The path to the file that describes the commission \
-                     split. The file must contain a map from namada address \
-                     to a percentage. Percentages must sum to 1 or less.",
-                ))
+            app.add_args::<Tx<CliTypes>>().arg(OWNER.def().help(
+                "The address of the account whose debits are restricted. It's \
+                 key is used to produce the signature.",
+            ))
         }
     }
 
@@ -5234,6 +6371,27 @@ pub mod args {
         }
     }
 
+    impl CliToSdk<QueryScheduledTxs<SdkTypes>> for QueryScheduledTxs<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> QueryScheduledTxs<SdkTypes> {
+            QueryScheduledTxs::<SdkTypes> {
+                query: self.query.to_sdk(ctx),
+                target: self.target,
+            }
+        }
+    }
+
+    impl Args for QueryScheduledTxs<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let target = parse_schedule_target(matches);
+            Self { query, target }
+        }
+
+        fn def(app: App) -> App {
+            def_schedule_target(app.add_args::<Query<CliTypes>>())
+        }
+    }
+
     impl CliToSdk<QueryBalance<SdkTypes>> for QueryBalance<CliTypes> {
         fn to_sdk(self, ctx: &mut Context) -> QueryBalance<SdkTypes> {
             let query = self.query.to_sdk(ctx);
@@ -5316,6 +6474,58 @@ pub mod args {
         }
     }
 
+    impl CliToSdk<QueryShieldedHistory<SdkTypes>>
+        for QueryShieldedHistory<CliTypes>
+    {
+        fn to_sdk(self, ctx: &mut Context) -> QueryShieldedHistory<SdkTypes> {
+            let query = self.query.to_sdk(ctx);
+            let chain_ctx = ctx.borrow_mut_chain_or_exit();
+            QueryShieldedHistory::<SdkTypes> {
+                query,
+                viewing_keys: self
+                    .viewing_keys
+                    .iter()
+                    .map(|vk| chain_ctx.get_cached(vk))
+                    .collect(),
+                format: self.format,
+                output: self.output,
+            }
+        }
+    }
+
+    impl Args for QueryShieldedHistory<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let viewing_keys = VIEWING_KEYS.parse(matches);
+            let format = SHIELDED_HISTORY_FORMAT.parse(matches);
+            let output = OUTPUT.parse(matches);
+            Self {
+                query,
+                viewing_keys,
+                format,
+                output,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query<CliTypes>>()
+                .arg(VIEWING_KEYS.def().help(
+                    "The viewing keys to export the history of. If none \
+                     provided, the history of all the known viewing keys is \
+                     exported. The history is built from the local shielded \
+                     context, which should be synced beforehand.",
+                ))
+                .arg(SHIELDED_HISTORY_FORMAT.def().help(
+                    "The format of the export, either json or csv. Defaults \
+                     to json.",
+                ))
+                .arg(OUTPUT.def().help(
+                    "Save the export to the given file. When not supplied, \
+                     the export is printed to stdout instead.",
+                ))
+        }
+    }
+
     impl CliToSdk<QueryBonds<SdkTypes>> for QueryBonds<CliTypes> {
         fn to_sdk(self, ctx: &mut Context) -> QueryBonds<SdkTypes> {
             let query = self.query.to_sdk(ctx);
@@ -5718,27 +6928,78 @@ pub mod args {
         }
     }
 
+    impl CliToSdk<TxInspect<SdkTypes>> for TxInspect<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> TxInspect<SdkTypes> {
+            // Default to the checksums of the chain's WASM directory, if any
+            let wasm_checksums_path = self.wasm_checksums_path.or_else(|| {
+                ctx.chain.as_ref().map(|chain| {
+                    chain
+                        .wasm_dir()
+                        .join(crate::config::DEFAULT_WASM_CHECKSUMS_FILE)
+                })
+            });
+            TxInspect::<SdkTypes> {
+                tx_data: std::fs::read(self.tx_data)
+                    .expect("Expected a file at given path"),
+                wasm_checksums_path,
+            }
+        }
+    }
+
+    impl Args for TxInspect<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx_data = TX_PATH.parse(matches);
+            let wasm_checksums_path = WASM_CHECKSUMS_PATH_OPT.parse(matches);
+            Self {
+                tx_data,
+                wasm_checksums_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(
+                TX_PATH
+                    .def()
+                    .help("The path to the tx file with the serialized tx."),
+            )
+            .arg(WASM_CHECKSUMS_PATH_OPT.def().help(
+                "Path to the WASM checksums file used to resolve the code \
+                 hashes to WASM names. Defaults to the checksums of the \
+                 chain's WASM directory.",
+            ))
+        }
+    }
+
     impl Args for ShieldedSync<CliTypes> {
         fn parse(matches: &ArgMatches) -> Self {
             let ledger_address = LEDGER_ADDRESS.parse(matches);
             let batch_size = BATCH_SIZE_OPT.parse(matches);
+            let max_concurrent_fetches = MAX_CONCURRENT_FETCHES.parse(matches);
             let last_query_height = BLOCK_HEIGHT_OPT.parse(matches);
             let spending_keys = SPENDING_KEYS.parse(matches);
             let viewing_keys = VIEWING_KEYS.parse(matches);
+            let scanning_service = SCANNING_SERVICE.parse(matches);
             Self {
                 ledger_address,
                 batch_size,
+                max_concurrent_fetches,
                 last_query_height,
                 spending_keys,
                 viewing_keys,
+                scanning_service,
             }
         }
 
         fn def(app: App) -> App {
             app.arg(LEDGER_ADDRESS.def().help(LEDGER_ADDRESS_ABOUT))
                 .arg(BATCH_SIZE_OPT.def().help(
-                    "Optional batch size which determines how many txs to \
-                     fetch before caching locally. Default is 1.",
+                    "Optional batch size which determines how many blocks to \
+                     fetch and scan before saving a checkpoint of the \
+                     shielded context locally. Default is 100.",
+                ))
+                .arg(MAX_CONCURRENT_FETCHES.def().help(
+                    "Optional maximum number of blocks to fetch concurrently. \
+                     Default is 10.",
                 ))
                 .arg(BLOCK_HEIGHT_OPT.def().help(
                     "Option block height to sync up to. Default is latest.",
@@ -5751,6 +7012,12 @@ pub mod args {
                     "List of new viewing keys with which to check note \
                      ownership. These will be added to the shielded context.",
                 ))
+                .arg(SCANNING_SERVICE.def().help(
+                    "Optional URL of a MASP indexer to retrieve the shielded \
+                     transactions from, instead of querying every block from \
+                     the node. The sync stops at the last block indexed by \
+                     the service.",
+                ))
         }
     }
 
@@ -5760,6 +7027,7 @@ pub mod args {
             ShieldedSync {
                 ledger_address: self.ledger_address,
                 batch_size: self.batch_size,
+                max_concurrent_fetches: self.max_concurrent_fetches,
                 last_query_height: self.last_query_height,
                 spending_keys: self
                     .spending_keys
@@ -5771,6 +7039,7 @@ pub mod args {
                     .iter()
                     .map(|vk| chain_ctx.get_cached(vk))
                     .collect(),
+                scanning_service: self.scanning_service,
             }
         }
     }
@@ -6081,7 +7350,9 @@ pub mod args {
                 fee_unshield: self
                     .fee_unshield
                     .map(|ref fee_unshield| ctx.get_cached(fee_unshield)),
+                fee_granter: self.fee_granter.map(|x| ctx.get(&x)),
                 gas_limit: self.gas_limit,
+                gas_estimate_multiplier: self.gas_estimate_multiplier,
                 signing_keys: self
                     .signing_keys
                     .iter()
@@ -6153,10 +7424,25 @@ pub mod args {
                 "The spending key to be used for fee unshielding. If none is \
                  provided, fee will be paid from the unshielded balance only.",
             ))
+            .arg(
+                FEE_GRANTER_OPT
+                    .def()
+                    .help(
+                        "The address of the account that granted a fee \
+                         allowance to the gas payer. The fees are drawn from \
+                         this allowance instead of the gas payer's balance.",
+                    )
+                    .conflicts_with(FEE_UNSHIELD_SPENDING_KEY.name),
+            )
             .arg(GAS_LIMIT.def().help(
                 "The multiplier of the gas limit resolution defining the \
                  maximum amount of gas needed to run transaction.",
             ))
+            .arg(GAS_ESTIMATE_MULTIPLIER_OPT.def().help(
+                "Estimate the gas limit by dry-running the transaction and \
+                 scaling the gas it used by the given safety multiplier (e.g. \
+                 1.2). Overrides --gas-limit.",
+            ))
             .arg(WALLET_ALIAS_FORCE.def().help(
                 "Override the alias without confirmation if it already exists.",
             ))
@@ -6234,8 +7520,11 @@ pub mod args {
                 FEE_AMOUNT_OPT.parse(matches).map(InputAmount::Unvalidated);
             let fee_token = FEE_TOKEN.parse(matches);
             let fee_unshield = FEE_UNSHIELD_SPENDING_KEY.parse(matches);
+            let fee_granter = FEE_GRANTER_OPT.parse(matches);
             let _wallet_alias_force = WALLET_ALIAS_FORCE.parse(matches);
             let gas_limit = GAS_LIMIT.parse(matches);
+            let gas_estimate_multiplier =
+                GAS_ESTIMATE_MULTIPLIER_OPT.parse(matches);
             let wallet_alias_force = WALLET_ALIAS_FORCE.parse(matches);
             let expiration = EXPIRATION_OPT.parse(matches);
             let disposable_signing_key = DISPOSABLE_SIGNING_KEY.parse(matches);
@@ -6260,7 +7549,9 @@ pub mod args {
                 fee_amount,
                 fee_token,
                 fee_unshield,
+                fee_granter,
                 gas_limit,
+                gas_estimate_multiplier,
                 expiration,
                 disposable_signing_key,
                 signing_keys,
@@ -6488,6 +7779,7 @@ pub mod args {
                 HD_ALLOW_NON_COMPLIANT_DERIVATION_PATH.parse(matches);
             let prompt_bip39_passphrase =
                 HD_PROMPT_BIP39_PASSPHRASE.parse(matches);
+            let birthday = BIRTHDAY.parse(matches);
             Self {
                 scheme,
                 shielded,
@@ -6498,6 +7790,7 @@ pub mod args {
                 derivation_path,
                 allow_non_compliant,
                 prompt_bip39_passphrase,
+                birthday,
             }
         }
 
@@ -6558,6 +7851,11 @@ pub mod args {
                     "Use an additional passphrase for HD-key generation.",
                 ),
             )
+            .arg(BIRTHDAY.def().requires(SHIELDED.name).help(
+                "The block height from which the shielded sync should scan \
+                 for the notes of the new spending key. If none provided, the \
+                 last height synced by the local shielded context is used.",
+            ))
         }
     }
 
@@ -6691,11 +7989,13 @@ pub mod args {
             let alias_force = ALIAS_FORCE.parse(matches);
             let value = VALUE.parse(matches);
             let unsafe_dont_encrypt = UNSAFE_DONT_ENCRYPT.parse(matches);
+            let birthday = BIRTHDAY.parse(matches);
             Self {
                 alias,
                 alias_force,
                 value,
                 unsafe_dont_encrypt,
+                birthday,
             }
         }
 
@@ -6718,6 +8018,11 @@ pub mod args {
                 "UNSAFE: Do not encrypt the added keys. Do not use this for \
                  keys used in a live network.",
             ))
+            .arg(BIRTHDAY.def().help(
+                "The block height from which the shielded sync should scan \
+                 for the notes of an added viewing or spending key. If none \
+                 provided, the key is scanned from the first block.",
+            ))
         }
     }
 
@@ -7355,6 +8660,11 @@ pub fn namada_relayer_cli() -> Result<NamadaRelayer> {
     }
 }
 
+pub fn namada_masp_indexer_cli() -> args::MaspIndexer {
+    let matches = namada_masp_indexer_app().get_matches();
+    args::MaspIndexer::parse(&matches)
+}
+
 fn namada_app() -> App {
     let app = App::new(APP_NAME)
         .version(namada_version())
@@ -7402,3 +8712,11 @@ fn namada_relayer_app() -> App {
         .subcommand_required(true);
     cmds::NamadaRelayer::add_sub(args::Global::def(app))
 }
+
+fn namada_masp_indexer_app() -> App {
+    let app = App::new(APP_NAME)
+        .version(namada_version())
+        .about("Namada MASP indexer serving the shielded transactions.")
+        .color(ColorChoice::Auto);
+    args::MaspIndexer::def(app)
+}
@@ -1,3 +1,6 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
 use color_eyre::eyre::Result;
 use masp_primitives::zip32::ExtendedFullViewingKey;
 use namada::io::Io;
@@ -7,6 +10,7 @@ use crate::cli;
 use crate::cli::api::{CliApi, CliClient};
 use crate::cli::args::CliToSdk;
 use crate::cli::cmds::*;
+use crate::client::masp_indexer::HttpScanningService;
 use crate::client::{rpc, tx, utils};
 
 impl CliApi {
@@ -51,6 +55,36 @@ impl CliApi {
                             )
                         }
                     }
+                    Sub::TxInspect(TxInspect(args)) => {
+                        let args = args.to_sdk(&mut ctx);
+                        tx::inspect_tx(&io, args)?;
+                    }
+                    Sub::TxBatch(TxBatch(args)) => {
+                        let chain_ctx = ctx.borrow_mut_chain_or_exit();
+                        let ledger_address =
+                            chain_ctx.get(&args.tx.ledger_address);
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(&ledger_address)
+                        });
+                        client.wait_until_node_is_synced(&io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(client, io);
+                        let dry_run =
+                            args.tx.dry_run || args.tx.dry_run_wrapper;
+                        tx::submit_batch(&namada, args).await?;
+                        if !dry_run {
+                            namada
+                                .wallet()
+                                .await
+                                .save()
+                                .unwrap_or_else(|err| eprintln!("{}", err));
+                        } else {
+                            namada.io().println(
+                                "Transaction dry run. No addresses have been \
+                                 saved.",
+                            )
+                        }
+                    }
                     Sub::TxTransfer(TxTransfer(args)) => {
                         let chain_ctx = ctx.borrow_mut_chain_or_exit();
                         let ledger_address =
@@ -63,6 +97,18 @@ impl CliApi {
                         let namada = ctx.to_sdk(client, io);
                         tx::submit_transfer(&namada, args).await?;
                     }
+                    Sub::TxConsolidateNotes(TxConsolidateNotes(args)) => {
+                        let chain_ctx = ctx.borrow_mut_chain_or_exit();
+                        let ledger_address =
+                            chain_ctx.get(&args.tx.ledger_address);
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(&ledger_address)
+                        });
+                        client.wait_until_node_is_synced(&io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(client, io);
+                        tx::submit_consolidate_notes(&namada, args).await?;
+                    }
                     Sub::TxIbcTransfer(TxIbcTransfer(args)) => {
                         let chain_ctx = ctx.borrow_mut_chain_or_exit();
                         let ledger_address =
@@ -338,14 +384,34 @@ impl CliApi {
                             .into_iter()
                             .map(|sk| sk.into())
                             .collect::<Vec<_>>();
+                        let birthdays = chain_ctx
+                            .wallet
+                            .get_viewing_keys()
+                            .into_iter()
+                            .filter_map(|(alias, vk)| {
+                                let birthday =
+                                    chain_ctx.wallet.find_birthday(alias)?;
+                                let vk =
+                                    ExtendedFullViewingKey::from(vk).fvk.vk;
+                                Some((vk, birthday))
+                            })
+                            .collect::<BTreeMap<_, _>>();
+                        let mut shielded = chain_ctx.shielded;
+                        if let Some(url) = args.scanning_service {
+                            shielded.utils.set_scanning_service(Arc::new(
+                                HttpScanningService::new(url),
+                            ));
+                        }
                         crate::client::masp::syncing(
-                            chain_ctx.shielded,
+                            shielded,
                             &client,
                             &io,
                             args.batch_size,
+                            args.max_concurrent_fetches,
                             args.last_query_height,
                             &sks,
                             &vks,
+                            &birthdays,
                         )
                         .await?;
                     }
@@ -414,6 +480,84 @@ impl CliApi {
                         tx::submit_update_steward_commission(&namada, args)
                             .await?;
                     }
+                    Sub::TxGrantFeeAllowance(TxGrantFeeAllowance(args)) => {
+                        let chain_ctx = ctx.borrow_mut_chain_or_exit();
+                        let ledger_address =
+                            chain_ctx.get(&args.tx.ledger_address);
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(&ledger_address)
+                        });
+                        client.wait_until_node_is_synced(&io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(client, io);
+                        tx::submit_grant_fee_allowance(&namada, args).await?;
+                    }
+                    Sub::TxRevokeFeeAllowance(TxRevokeFeeAllowance(args)) => {
+                        let chain_ctx = ctx.borrow_mut_chain_or_exit();
+                        let ledger_address =
+                            chain_ctx.get(&args.tx.ledger_address);
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(&ledger_address)
+                        });
+                        client.wait_until_node_is_synced(&io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(client, io);
+                        tx::submit_revoke_fee_allowance(&namada, args).await?;
+                    }
+                    Sub::TxScheduleTx(TxScheduleTx(args)) => {
+                        let chain_ctx = ctx.borrow_mut_chain_or_exit();
+                        let ledger_address =
+                            chain_ctx.get(&args.tx.ledger_address);
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(&ledger_address)
+                        });
+                        client.wait_until_node_is_synced(&io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(client, io);
+                        tx::submit_schedule_tx(&namada, args).await?;
+                    }
+                    Sub::TxCancelScheduledTx(TxCancelScheduledTx(args)) => {
+                        let chain_ctx = ctx.borrow_mut_chain_or_exit();
+                        let ledger_address =
+                            chain_ctx.get(&args.tx.ledger_address);
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(&ledger_address)
+                        });
+                        client.wait_until_node_is_synced(&io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(client, io);
+                        tx::submit_cancel_scheduled_tx(&namada, args).await?;
+                    }
+                    Sub::TxUpdateSpendingPolicy(TxUpdateSpendingPolicy(
+                        args,
+                    )) => {
+                        let chain_ctx = ctx.borrow_mut_chain_or_exit();
+                        let ledger_address =
+                            chain_ctx.get(&args.tx.ledger_address);
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(&ledger_address)
+                        });
+                        client.wait_until_node_is_synced(&io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(client, io);
+                        tx::submit_update_spending_policy(&namada, args)
+                            .await?;
+                    }
+                    Sub::TxRemoveSpendingPolicy(TxRemoveSpendingPolicy(
+                        args,
+                    )) => {
+                        let chain_ctx = ctx.borrow_mut_chain_or_exit();
+                        let ledger_address =
+                            chain_ctx.get(&args.tx.ledger_address);
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(&ledger_address)
+                        });
+                        client.wait_until_node_is_synced(&io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(client, io);
+                        tx::submit_remove_spending_policy(&namada, args)
+                            .await?;
+                    }
                     Sub::TxResignSteward(TxResignSteward(args)) => {
                         let chain_ctx = ctx.borrow_mut_chain_or_exit();
                         let ledger_address =
@@ -484,6 +628,18 @@ impl CliApi {
                         let namada = ctx.to_sdk(client, io);
                         rpc::query_transfers(&namada, args).await;
                     }
+                    Sub::QueryShieldedHistory(QueryShieldedHistory(args)) => {
+                        let chain_ctx = ctx.borrow_mut_chain_or_exit();
+                        let ledger_address =
+                            chain_ctx.get(&args.query.ledger_address);
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(&ledger_address)
+                        });
+                        client.wait_until_node_is_synced(&io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(client, io);
+                        rpc::query_shielded_history(&namada, args).await;
+                    }
                     Sub::QueryConversions(QueryConversions(args)) => {
                         let chain_ctx = ctx.borrow_mut_chain_or_exit();
                         let ledger_address =
@@ -727,6 +883,18 @@ impl CliApi {
                         let namada = ctx.to_sdk(client, io);
                         rpc::query_account(&namada, args).await;
                     }
+                    Sub::QueryScheduledTxs(QueryScheduledTxs(args)) => {
+                        let chain_ctx = ctx.borrow_mut_chain_or_exit();
+                        let ledger_address =
+                            chain_ctx.get(&args.query.ledger_address);
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(&ledger_address)
+                        });
+                        client.wait_until_node_is_synced(&io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(client, io);
+                        rpc::query_scheduled_txs(&namada, args).await;
+                    }
                     Sub::SignTx(SignTx(args)) => {
                         let chain_ctx = ctx.borrow_mut_chain_or_exit();
                         let ledger_address =
@@ -15,6 +15,7 @@ use masp_primitives::zip32::ExtendedFullViewingKey;
 use namada::core::address::{Address, DecodeError};
 use namada::core::key::*;
 use namada::core::masp::{ExtendedSpendingKey, MaspValue, PaymentAddress};
+use namada::core::storage::BlockHeight;
 use namada::io::Io;
 use namada_sdk::masp::find_valid_diversifier;
 use namada_sdk::wallet::{
@@ -41,7 +42,7 @@ impl CliApi {
     ) -> Result<()> {
         match cmd {
             cmds::NamadaWallet::KeyGen(cmds::WalletGen(args)) => {
-                key_gen(ctx, io, args)
+                key_gen(ctx, io, args).await
             }
             cmds::NamadaWallet::KeyDerive(cmds::WalletDerive(args)) => {
                 key_derive(ctx, io, args).await
@@ -112,6 +113,10 @@ fn shielded_keys_list(
             display_line!(io, &mut w_lock; "  Alias \"{}\" ({}):", alias, encrypted_status).unwrap();
             // Always print the corresponding viewing key
             display_line!(io, &mut w_lock; "    Viewing Key: {}", key).unwrap();
+            if let Some(birthday) = wallet.find_birthday(&alias) {
+                display_line!(io, &mut w_lock; "    Birthday: {}", birthday)
+                    .unwrap();
+            }
             // A subset of viewing keys will have corresponding spending keys.
             // Print those too if they are available and requested.
             if let Some(spending_key) = spending_key_opt {
@@ -243,9 +248,20 @@ fn shielded_key_derive(
     );
 }
 
+/// The height of the last shielded transaction synced by the local shielded
+/// context, if any. A key generated now cannot own any note created before it.
+async fn last_synced_height(ctx: &mut Context) -> Option<BlockHeight> {
+    if ctx.global_args.is_pre_genesis {
+        return None;
+    }
+    let shielded = &mut ctx.borrow_mut_chain_or_exit().shielded;
+    shielded.load_confirmed().await.ok()?;
+    shielded.last_witnessed_height()
+}
+
 /// Generate a spending key.
-fn shielded_key_gen(
-    ctx: Context,
+async fn shielded_key_gen(
+    mut ctx: Context,
     io: &impl Io,
     args::KeyGen {
         raw,
@@ -255,14 +271,25 @@ fn shielded_key_gen(
         derivation_path,
         allow_non_compliant,
         prompt_bip39_passphrase,
+        birthday,
         ..
     }: args::KeyGen,
 ) {
+    let birthday = match birthday {
+        Some(birthday) => Some(birthday),
+        None => last_synced_height(&mut ctx).await,
+    };
     let mut wallet = load_wallet(ctx);
     let alias = alias.to_lowercase();
     let password = read_and_confirm_encryption_password(unsafe_dont_encrypt);
     let alias = if raw {
-        wallet.gen_store_spending_key(alias, password, alias_force, &mut OsRng)
+        wallet.gen_store_spending_key(
+            alias,
+            password,
+            birthday,
+            alias_force,
+            &mut OsRng,
+        )
     } else {
         let derivation_path = decode_shielded_derivation_path(derivation_path)
             .unwrap_or_else(|err| {
@@ -288,6 +315,7 @@ fn shielded_key_gen(
             seed,
             derivation_path,
             password,
+            birthday,
         )
     }
     .map(|x| x.0)
@@ -350,13 +378,14 @@ fn shielded_key_address_add(
     alias_force: bool,
     masp_value: MaspValue,
     unsafe_dont_encrypt: bool,
+    birthday: Option<BlockHeight>,
 ) {
     let alias = alias.to_lowercase();
     let mut wallet = load_wallet(ctx);
     let (alias, typ) = match masp_value {
         MaspValue::FullViewingKey(viewing_key) => {
             let alias = wallet
-                .insert_viewing_key(alias, viewing_key, alias_force)
+                .insert_viewing_key(alias, viewing_key, birthday, alias_force)
                 .unwrap_or_else(|| {
                     edisplay_line!(io, "Viewing key not added");
                     cli::safe_exit(1);
@@ -373,6 +402,7 @@ fn shielded_key_address_add(
                     spending_key,
                     password,
                     None,
+                    birthday,
                 )
                 .unwrap_or_else(|| {
                     edisplay_line!(io, "Spending key not added");
@@ -616,11 +646,11 @@ fn transparent_key_and_address_gen(
 }
 
 /// Key generation
-fn key_gen(ctx: Context, io: &impl Io, args_key_gen: args::KeyGen) {
+async fn key_gen(ctx: Context, io: &impl Io, args_key_gen: args::KeyGen) {
     if !args_key_gen.shielded {
         transparent_key_and_address_gen(ctx, io, args_key_gen)
     } else {
-        shielded_key_gen(ctx, io, args_key_gen)
+        shielded_key_gen(ctx, io, args_key_gen).await
     }
 }
 
@@ -799,6 +829,7 @@ fn add_key_or_address(
     alias_force: bool,
     value: KeyAddrAddValue,
     unsafe_dont_encrypt: bool,
+    birthday: Option<BlockHeight>,
 ) {
     match value {
         KeyAddrAddValue::TranspValue(TransparentValue::TranspSecretKey(sk)) => {
@@ -824,6 +855,7 @@ fn add_key_or_address(
             alias_force,
             masp_value,
             unsafe_dont_encrypt,
+            birthday,
         ),
     }
 }
@@ -837,6 +869,7 @@ fn key_address_add(
         alias_force,
         value,
         unsafe_dont_encrypt,
+        birthday,
         ..
     }: args::KeyAddressAdd,
 ) {
@@ -845,7 +878,15 @@ fn key_address_add(
         display_line!(io, "No changes are persisted. Exiting.");
         cli::safe_exit(1)
     });
-    add_key_or_address(ctx, io, alias, alias_force, value, unsafe_dont_encrypt)
+    add_key_or_address(
+        ctx,
+        io,
+        alias,
+        alias_force,
+        value,
+        unsafe_dont_encrypt,
+        birthday,
+    )
 }
 
 /// Remove keys and addresses
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 
 use color_eyre::owo_colors::OwoColorize;
@@ -13,6 +14,7 @@ use namada_sdk::queries::Client;
 use namada_sdk::storage::BlockHeight;
 use namada_sdk::{display, display_line, MaybeSend, MaybeSync};
 
+#[allow(clippy::too_many_arguments)]
 pub async fn syncing<
     U: ShieldedUtils + MaybeSend + MaybeSync,
     C: Client + Sync,
@@ -22,9 +24,11 @@ pub async fn syncing<
     client: &C,
     io: &IO,
     batch_size: u64,
+    max_concurrent_fetches: usize,
     last_query_height: Option<BlockHeight>,
     sks: &[ExtendedSpendingKey],
     fvks: &[ViewingKey],
+    birthdays: &BTreeMap<ViewingKey, BlockHeight>,
 ) -> Result<ShieldedContext<U>, Error> {
     let shutdown_signal = async {
         let (tx, rx) = tokio::sync::oneshot::channel();
@@ -37,7 +41,16 @@ pub async fn syncing<
     let logger = CliLogger::new(io);
     let sync = async move {
         shielded
-            .fetch(client, &logger, last_query_height, batch_size, sks, fvks)
+            .fetch(
+                client,
+                &logger,
+                last_query_height,
+                batch_size,
+                max_concurrent_fetches,
+                sks,
+                fvks,
+                birthdays,
+            )
             .await
             .map(|_| shielded)
     };
@@ -0,0 +1,382 @@
+//! An indexer of the shielded transactions of the chain, serving their compact
+//! data to the light clients over HTTP, and the client of this service.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use borsh::BorshDeserialize;
+use borsh_ext::BorshSerializeExt;
+use namada::tendermint_rpc::HttpClient;
+use namada_sdk::error::Error;
+use namada_sdk::masp::fs::FsShieldedUtils;
+use namada_sdk::masp::{
+    IndexedNoteData, LocalScanningService, ScanningService, ShieldedContext,
+};
+use namada_sdk::queries::Client;
+use namada_sdk::rpc::query_block;
+use namada_sdk::storage::BlockHeight;
+use warp::http::StatusCode;
+use warp::{Filter, Reply};
+
+use crate::cli::api::{CliClient, CliIo};
+use crate::cli::args;
+
+/// The endpoint serving the Borsh-serialized last indexed block height
+const HEIGHT_ENDPOINT: &str = "height";
+/// The endpoint serving the Borsh-serialized compact shielded transactions of
+/// a range of blocks, as `txs/<from>/<to>` with both heights inclusive
+const TXS_ENDPOINT: &str = "txs";
+/// The maximum number of blocks whose shielded transactions are served by a
+/// single request. Larger ranges are rejected, so the client queries them in
+/// chunks of this size.
+const MAX_TXS_RANGE: u64 = 1_000;
+/// The delay between two queries of the node for new blocks
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// The name of the file persisting the index in the data directory
+const INDEX_FILE_NAME: &str = "masp-index";
+
+/// Index the shielded transactions of the blocks produced by the node and
+/// serve them until a shutdown signal is received. The index is persisted in
+/// the data directory and resumed from the last indexed block on restart.
+///
+/// The whole index is loaded in a [`LocalScanningService`], so the memory
+/// used by the indexer grows with the number of shielded transactions of the
+/// chain.
+pub async fn run(args: args::MaspIndexer) -> Result<(), Error> {
+    let client = HttpClient::from_tendermint_address(&args.ledger_address);
+    client.wait_until_node_is_synced(&CliIo).await?;
+    std::fs::create_dir_all(&args.data_path).map_err(|err| {
+        Error::Other(format!(
+            "Failed to create the data directory {}: {}",
+            args.data_path.display(),
+            err
+        ))
+    })?;
+    let (service, mut index_file) =
+        load_index(&args.data_path.join(INDEX_FILE_NAME))?;
+    let service = Arc::new(service);
+    if let Some(height) = service.indexed_height() {
+        tracing::info!("Resuming the MASP index from block {}", height);
+    }
+
+    let routes = {
+        let height_service = service.clone();
+        let height = warp::get()
+            .and(warp::path(HEIGHT_ENDPOINT))
+            .and(warp::path::end())
+            .map(move || height_service.indexed_height().serialize_to_vec());
+        let txs_service = service.clone();
+        let txs = warp::get()
+            .and(warp::path(TXS_ENDPOINT))
+            .and(warp::path::param::<u64>())
+            .and(warp::path::param::<u64>())
+            .and(warp::path::end())
+            .map(move |from, to| match check_txs_range(from, to) {
+                Ok(()) => txs_service
+                    .txs_between(BlockHeight(from), BlockHeight(to))
+                    .serialize_to_vec()
+                    .into_response(),
+                Err(err) => {
+                    warp::reply::with_status(err, StatusCode::BAD_REQUEST)
+                        .into_response()
+                }
+            });
+        height.or(txs)
+    };
+    let shutdown_signal = async {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        namada_sdk::control_flow::shutdown_send(tx).await;
+        let _ = rx.await;
+    };
+    let (listen_address, server) = warp::serve(routes)
+        .try_bind_with_graceful_shutdown(args.listen_address, shutdown_signal)
+        .map_err(|err| {
+            Error::Other(format!(
+                "Failed to bind the MASP indexer to {}: {}",
+                args.listen_address, err
+            ))
+        })?;
+    tracing::info!(?listen_address, "The MASP indexer is listening");
+
+    let indexing = async {
+        loop {
+            if let Err(err) = index_new_blocks(
+                &client,
+                &service,
+                &mut index_file,
+                args.batch_size,
+                args.max_concurrent_fetches,
+            )
+            .await
+            {
+                tracing::warn!("Failed to index the new blocks: {}", err);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    };
+    tokio::select! {
+        () = server => {
+            tracing::info!("The MASP indexer has been shut down");
+        },
+        _ = indexing => {},
+    }
+    Ok(())
+}
+
+/// Check that a range of block heights requested from the `txs` endpoint is
+/// ordered and doesn't span more than [`MAX_TXS_RANGE`] blocks
+fn check_txs_range(from: u64, to: u64) -> Result<(), String> {
+    match to.checked_sub(from) {
+        Some(span) if span < MAX_TXS_RANGE => Ok(()),
+        Some(_) => Err(format!(
+            "The range of block heights {}..={} spans more than {} blocks",
+            from, to, MAX_TXS_RANGE
+        )),
+        None => Err(format!(
+            "The range of block heights {}..={} is empty",
+            from, to
+        )),
+    }
+}
+
+/// Load the index persisted in the file at the given path, if any, and open
+/// this file to append the next indexed blocks to it. Each batch of indexed
+/// blocks is stored as a Borsh-serialized record of the last indexed height
+/// and the shielded transactions of the batch. A truncated last record, left
+/// by an interrupted write, is discarded.
+fn load_index(path: &Path) -> Result<(LocalScanningService, File), Error> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(err) => {
+            return Err(Error::Other(format!(
+                "Failed to read the index from {}: {}",
+                path.display(),
+                err
+            )));
+        }
+    };
+    let service = LocalScanningService::default();
+    let mut records = bytes.as_slice();
+    while !records.is_empty() {
+        let record = records;
+        match <(BlockHeight, IndexedNoteData)>::deserialize(&mut records) {
+            Ok((height, txs)) => service.index(height, txs),
+            Err(err) => {
+                tracing::warn!(
+                    "Discarding the last record of the index: {}",
+                    err
+                );
+                records = record;
+                break;
+            }
+        }
+    }
+    let valid_len = bytes.len() - records.len();
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|file| {
+            file.set_len(valid_len as u64)?;
+            Ok(file)
+        })
+        .map_err(|err| {
+            Error::Other(format!(
+                "Failed to open the index at {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+    Ok((service, file))
+}
+
+/// Index the shielded transactions of the blocks produced since the last
+/// indexed one, in batches of the given size. Each batch is appended to the
+/// index file before it is served.
+async fn index_new_blocks<C: Client + Sync>(
+    client: &C,
+    service: &LocalScanningService,
+    index_file: &mut File,
+    batch_size: u64,
+    max_concurrent_fetches: usize,
+) -> Result<(), Error> {
+    // Only used to extract the shielded transactions from the blocks
+    let shielded = ShieldedContext::<FsShieldedUtils>::default();
+    let Some(last_block_height) =
+        query_block(client).await?.map(|block| block.height)
+    else {
+        return Ok(());
+    };
+    let batch_size = batch_size.max(1);
+    let mut from = service
+        .indexed_height()
+        .map_or(1, |height| height.0.saturating_add(1));
+    while from <= last_block_height.0 {
+        let to = std::cmp::min(
+            from.saturating_add(batch_size - 1),
+            last_block_height.0,
+        );
+        let txs = shielded
+            .fetch_shielded_transfers(client, from..=to, max_concurrent_fetches)
+            .await?;
+        tracing::info!(
+            "Indexed {} shielded transactions from block {} to {}",
+            txs.len(),
+            from,
+            to
+        );
+        index_file
+            .write_all(&(BlockHeight(to), &txs).serialize_to_vec())
+            .and_then(|()| index_file.sync_data())
+            .map_err(|err| {
+                Error::Other(format!("Failed to persist the index: {}", err))
+            })?;
+        service.index(BlockHeight(to), txs);
+        from = to.saturating_add(1);
+    }
+    Ok(())
+}
+
+/// A [`ScanningService`] retrieving the shielded transactions from a remote
+/// MASP indexer
+#[derive(Debug, Clone)]
+pub struct HttpScanningService {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl HttpScanningService {
+    /// Connect to the MASP indexer served at the given URL
+    pub fn new(url: impl AsRef<str>) -> Self {
+        Self {
+            url: url.as_ref().trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Query the given endpoint and deserialize its response
+    async fn query<T: BorshDeserialize>(&self, path: &str) -> Result<T, Error> {
+        let url = format!("{}/{}", self.url, path);
+        let bytes = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| {
+                Error::Other(format!("Failed to query {}: {}", url, err))
+            })?
+            .bytes()
+            .await
+            .map_err(|err| {
+                Error::Other(format!("Failed to read from {}: {}", url, err))
+            })?;
+        T::try_from_slice(&bytes).map_err(|err| {
+            Error::Other(format!(
+                "Failed to decode the response of {}: {}",
+                url, err
+            ))
+        })
+    }
+}
+
+#[cfg_attr(feature = "async-send", async_trait::async_trait)]
+#[cfg_attr(not(feature = "async-send"), async_trait::async_trait(?Send))]
+impl ScanningService for HttpScanningService {
+    async fn last_indexed_height(&self) -> Result<Option<BlockHeight>, Error> {
+        self.query(HEIGHT_ENDPOINT).await
+    }
+
+    async fn fetch_range(
+        &self,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> Result<IndexedNoteData, Error> {
+        // The indexer serves at most `MAX_TXS_RANGE` blocks per request
+        let mut txs = IndexedNoteData::new();
+        let mut start = from.0;
+        while start <= to.0 {
+            let end =
+                std::cmp::min(start.saturating_add(MAX_TXS_RANGE - 1), to.0);
+            let chunk: IndexedNoteData = self
+                .query(&format!("{}/{}/{}", TXS_ENDPOINT, start, end))
+                .await?;
+            txs.extend(chunk);
+            match end.checked_add(1) {
+                Some(next) => start = next,
+                None => break,
+            }
+        }
+        Ok(txs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namada_sdk::masp::CompactTx;
+    use namada_sdk::storage::{Epoch, IndexedTx, TxIndex};
+
+    use super::*;
+
+    /// Test that the ranges of block heights served by a single request are
+    /// bounded
+    #[test]
+    fn test_check_txs_range() {
+        assert!(check_txs_range(1, 1).is_ok());
+        assert!(check_txs_range(1, MAX_TXS_RANGE).is_ok());
+        assert!(check_txs_range(1, MAX_TXS_RANGE + 1).is_err());
+        assert!(check_txs_range(0, u64::MAX).is_err());
+        assert!(check_txs_range(2, 1).is_err());
+    }
+
+    /// Test that the persisted index is resumed from its last complete
+    /// record, discarding a truncated one
+    #[test]
+    fn test_load_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(INDEX_FILE_NAME);
+
+        // Nothing is indexed without an index file
+        let (service, mut file) = load_index(&path).unwrap();
+        assert_eq!(service.indexed_height(), None);
+
+        let indexed_tx = |height: u64| IndexedTx {
+            height: BlockHeight(height),
+            index: TxIndex(0),
+        };
+        let txs = |height: u64| -> IndexedNoteData {
+            [(
+                indexed_tx(height),
+                (Epoch(0), Default::default(), CompactTx::default()),
+            )]
+            .into_iter()
+            .collect()
+        };
+        file.write_all(&(BlockHeight(2), &txs(1)).serialize_to_vec())
+            .unwrap();
+        file.write_all(&(BlockHeight(4), &txs(3)).serialize_to_vec())
+            .unwrap();
+        let valid_len = file.metadata().unwrap().len();
+        let record = (BlockHeight(6), &txs(5)).serialize_to_vec();
+        file.write_all(&record[..record.len() - 1]).unwrap();
+        drop(file);
+
+        let (service, file) = load_index(&path).unwrap();
+        assert_eq!(service.indexed_height(), Some(BlockHeight(4)));
+        assert_eq!(
+            service
+                .txs_between(BlockHeight(1), BlockHeight(6))
+                .keys()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![indexed_tx(1), indexed_tx(3)]
+        );
+        // The truncated record is dropped from the file
+        assert_eq!(file.metadata().unwrap().len(), valid_len);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod masp;
+pub mod masp_indexer;
 pub mod rpc;
 pub mod tx;
 pub mod utils;
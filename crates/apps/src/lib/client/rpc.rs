@@ -19,6 +19,7 @@ use namada::core::hash::Hash;
 use namada::core::ibc::{is_ibc_denom, IbcTokenHash};
 use namada::core::key::*;
 use namada::core::masp::{BalanceOwner, ExtendedViewingKey, PaymentAddress};
+use namada::core::parameters::BaseFeeParams;
 use namada::core::storage::{
     BlockHeight, BlockResults, Epoch, IndexedTx, Key, KeySeg,
 };
@@ -61,6 +62,7 @@ use namada_sdk::tendermint_rpc::endpoint::status;
 use namada_sdk::tx::{display_inner_resp, display_wrapper_resp_and_get_result};
 use namada_sdk::wallet::AddressVpType;
 use namada_sdk::{display, display_line, edisplay_line, error, prompt, Namada};
+use serde::Serialize;
 use tokio::time::Instant;
 
 use crate::cli::{self, args};
@@ -317,6 +319,164 @@ pub async fn query_transfers(
     }
 }
 
+/// A row of an exported shielded history
+#[derive(Serialize)]
+struct ShieldedHistoryRow {
+    viewing_key: String,
+    height: u64,
+    index: u32,
+    epoch: u64,
+    tx_hash: Option<String>,
+    kind: String,
+    counterparty: Option<String>,
+    asset_type: Option<String>,
+    token: Option<String>,
+    amount: String,
+    memo: Option<String>,
+}
+
+impl ShieldedHistoryRow {
+    /// The header line of a CSV export
+    const CSV_HEADER: &'static str = "viewing_key,height,index,epoch,tx_hash,\
+                                      kind,counterparty,asset_type,token,\
+                                      amount,memo";
+
+    /// Format the row as a CSV line
+    fn to_csv(&self) -> String {
+        [
+            self.viewing_key.clone(),
+            self.height.to_string(),
+            self.index.to_string(),
+            self.epoch.to_string(),
+            self.tx_hash.clone().unwrap_or_default(),
+            self.kind.clone(),
+            self.counterparty.clone().unwrap_or_default(),
+            self.asset_type.clone().unwrap_or_default(),
+            self.token.clone().unwrap_or_default(),
+            self.amount.clone(),
+            self.memo.clone().unwrap_or_default(),
+        ]
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+/// Quote a CSV field if it contains a separator, a quote or a line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Export the shielded history of the given viewing keys
+pub async fn query_shielded_history(
+    context: &impl Namada,
+    args: args::QueryShieldedHistory,
+) {
+    let viewing_keys = if args.viewing_keys.is_empty() {
+        context
+            .wallet()
+            .await
+            .get_viewing_keys()
+            .into_values()
+            .collect()
+    } else {
+        args.viewing_keys
+    };
+    // To enable ExtendedViewingKeys to be exported instead of ViewingKeys
+    let vk_map: HashMap<_, _> = viewing_keys
+        .into_iter()
+        .map(|evk| (ExtendedFullViewingKey::from(evk).fvk.vk, evk))
+        .collect();
+    let vks: Vec<_> = vk_map.keys().cloned().collect();
+    let mut shielded = context.shielded_mut().await;
+    let _ = shielded.load().await;
+    // Precompute asset types to increase chances of success in decoding
+    let token_map = query_tokens(context, None, None).await;
+    let tokens = token_map.values().collect();
+    let _ = shielded
+        .precompute_asset_types(context.client(), tokens)
+        .await;
+    let history = match shielded
+        .query_history(context.client(), context.io(), &vks)
+        .await
+    {
+        Ok(history) => history,
+        Err(err) => {
+            edisplay_line!(
+                context.io(),
+                "Failed to query the shielded history: {}",
+                err
+            );
+            cli::safe_exit(1)
+        }
+    };
+    drop(shielded);
+
+    let mut rows = vec![];
+    for (vk, entries) in history {
+        for entry in entries {
+            let amount = match &entry.token {
+                Some(token) => context.format_amount(token, entry.amount).await,
+                None => entry.amount.to_string(),
+            };
+            rows.push(ShieldedHistoryRow {
+                viewing_key: vk_map[&vk].to_string(),
+                height: entry.indexed_tx.height.0,
+                index: entry.indexed_tx.index.0,
+                epoch: entry.epoch.0,
+                tx_hash: entry.tx_hash,
+                kind: entry.kind.to_string(),
+                counterparty: entry
+                    .counterparty
+                    .as_ref()
+                    .map(ToString::to_string),
+                asset_type: entry.asset_type.as_ref().map(ToString::to_string),
+                token: entry.token.as_ref().map(ToString::to_string),
+                amount,
+                memo: entry.memo,
+            });
+        }
+    }
+
+    let export = match args.format {
+        args::ShieldedHistoryFormat::Json => {
+            serde_json::to_string_pretty(&rows)
+                .expect("Serializing the shielded history shouldn't fail")
+        }
+        args::ShieldedHistoryFormat::Csv => {
+            std::iter::once(ShieldedHistoryRow::CSV_HEADER.to_string())
+                .chain(rows.iter().map(ShieldedHistoryRow::to_csv))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    };
+    match args.output {
+        Some(path) => {
+            if let Err(err) = fs::write(&path, export + "\n") {
+                edisplay_line!(
+                    context.io(),
+                    "Failed to write the shielded history to {}: {}",
+                    path.to_string_lossy(),
+                    err
+                );
+                cli::safe_exit(1)
+            }
+            display_line!(
+                context.io(),
+                "Exported {} shielded history entries to {}",
+                rows.len(),
+                path.to_string_lossy()
+            );
+        }
+        None => display_line!(context.io(), "{}", export),
+    }
+}
+
 /// Query the raw bytes of given storage key
 pub async fn query_raw_bytes<N: Namada>(
     context: &N,
@@ -1389,14 +1549,85 @@ pub async fn query_account(context: &impl Namada, args: args::QueryAccount) {
         display_line!(context.io(), "Address: {}", account.address);
         display_line!(context.io(), "Threshold: {}", account.threshold);
         display_line!(context.io(), "Public keys:");
-        for (public_key, _) in account.public_keys_map.pk_to_idx {
-            display_line!(context.io(), "- {}", public_key);
+        for (public_key, index) in &account.public_keys_map.pk_to_idx {
+            display_line!(
+                context.io(),
+                "- {} (weight {})",
+                public_key,
+                account.get_weight_from_index(*index)
+            );
+        }
+        let policy = rpc::get_spending_policy(context.client(), &args.owner)
+            .await
+            .unwrap();
+        if let Some(policy) = policy {
+            display_line!(context.io(), "Spending policy:");
+            display_line!(context.io(), "  Spending caps per epoch:");
+            for (token, cap) in policy.spending_caps {
+                display_line!(
+                    context.io(),
+                    "  - {}: {}",
+                    token,
+                    context.format_amount(&token, cap).await
+                );
+            }
+            if policy.allowed_destinations.is_empty() {
+                display_line!(context.io(), "  Allowed destinations: any");
+            } else {
+                display_line!(context.io(), "  Allowed destinations:");
+                for destination in policy.allowed_destinations {
+                    display_line!(context.io(), "  - {}", destination);
+                }
+            }
+            display_line!(
+                context.io(),
+                "  Spending threshold: {}",
+                policy.spending_threshold
+            );
+            display_line!(context.io(), "  Spending keys:");
+            for public_key in policy.spending_keys {
+                display_line!(context.io(), "  - {}", public_key);
+            }
         }
     } else {
         display_line!(context.io(), "No account exists for {}", args.owner);
     }
 }
 
+pub async fn query_scheduled_txs(
+    context: &impl Namada,
+    args: args::QueryScheduledTxs,
+) {
+    let scheduled_txs =
+        rpc::query_scheduled_txs(context.client(), &args.target)
+            .await
+            .unwrap();
+    if scheduled_txs.is_empty() {
+        display_line!(
+            context.io(),
+            "No transactions scheduled at {}",
+            args.target
+        );
+        return;
+    }
+    display_line!(context.io(), "Transactions scheduled at {}:", args.target);
+    for scheduled in scheduled_txs {
+        display_line!(context.io(), "- Hash: {}", scheduled.hash());
+        display_line!(context.io(), "  Owner: {}", scheduled.owner);
+        display_line!(
+            context.io(),
+            "  Gas limit: {}",
+            u64::from(scheduled.gas_limit)
+        );
+        display_line!(
+            context.io(),
+            "  Fee: {} {} per gas unit",
+            scheduled.fee.amount_per_gas_unit,
+            scheduled.fee.token
+        );
+    }
+}
+
 pub async fn query_pgf(context: &impl Namada, _args: args::QueryPgf) {
     let stewards = query_pgf_stewards(context.client()).await;
     let fundings = query_pgf_fundings(context.client()).await;
@@ -1590,10 +1821,45 @@ pub async fn query_protocol_parameters(
             .await
             .expect("Parameter should be defined.");
     display_line!(context.io(), "{:4}Gas cost table:", "");
-    for (token, gas_cost) in gas_cost_table {
+    for (token, gas_cost) in &gas_cost_table {
         display_line!(context.io(), "{:8}{}: {:?}", "", token, gas_cost);
     }
 
+    let key = param_storage::get_base_fee_params_key();
+    let base_fee_params: Option<BaseFeeParams> =
+        query_storage_value(context.client(), &key).await.ok();
+    match base_fee_params {
+        Some(base_fee_params) => {
+            display_line!(
+                context.io(),
+                "{:4}Base fee target block gas fraction: {}",
+                "",
+                base_fee_params.target_block_gas_fraction
+            );
+            display_line!(
+                context.io(),
+                "{:4}Base fee max change rate: {}",
+                "",
+                base_fee_params.max_change_rate
+            );
+            display_line!(context.io(), "{:4}Current base fee:", "");
+            for token in gas_cost_table.keys() {
+                let base_fee = rpc::query_base_fee(context.client(), token)
+                    .await
+                    .expect("Base fee should be defined.")
+                    .unwrap_or_default();
+                display_line!(
+                    context.io(),
+                    "{:8}{}: {:?}",
+                    "",
+                    token,
+                    base_fee
+                );
+            }
+        }
+        None => display_line!(context.io(), "{:4}Base fee: disabled", ""),
+    }
+
     display_line!(context.io(), "PoS parameters");
     let pos_params = query_pos_parameters(context.client()).await;
     display_line!(
@@ -3016,3 +3282,46 @@ pub async fn compute_proposal_votes<
         delegator_voting_power,
     }
 }
+
+#[cfg(test)]
+mod test_shielded_history {
+    use super::*;
+
+    /// Test that the CSV fields are only quoted when needed, with their quotes
+    /// doubled
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("carriage\rreturn"), "\"carriage\rreturn\"");
+    }
+
+    /// Test that a row is exported as a CSV line matching the header, with
+    /// its missing fields left empty and its memo escaped
+    #[test]
+    fn test_history_row_to_csv() {
+        let row = ShieldedHistoryRow {
+            viewing_key: "zvknam1".to_string(),
+            height: 10,
+            index: 2,
+            epoch: 1,
+            tx_hash: None,
+            kind: "incoming".to_string(),
+            counterparty: None,
+            asset_type: None,
+            token: Some("tnam1".to_string()),
+            amount: "1.5".to_string(),
+            memo: Some("rent, \"march\"".to_string()),
+        };
+        let line = row.to_csv();
+        assert_eq!(
+            line,
+            "zvknam1,10,2,1,,incoming,,,tnam1,1.5,\"rent, \"\"march\"\"\""
+        );
+        // The header has a column for each of the 11 fields of the row
+        assert_eq!(ShieldedHistoryRow::CSV_HEADER.split(',').count(), 11);
+    }
+}
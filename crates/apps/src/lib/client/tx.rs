@@ -41,6 +41,7 @@ use crate::config::TendermintMode;
 use crate::facade::tendermint_rpc::endpoint::broadcast::tx_sync::Response;
 use crate::node::ledger::tendermint_node;
 use crate::wallet::{gen_validator_keys, read_and_confirm_encryption_password};
+use crate::wasm_loader::Checksums;
 
 /// Wrapper around `signing::aux_signing_data` that stores the optional
 /// disposable address to the wallet
@@ -275,6 +276,28 @@ where
     Ok(())
 }
 
+pub async fn submit_batch<N: Namada>(
+    namada: &N,
+    args: args::TxBatch,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
+{
+    submit_reveal_aux(namada, args.tx.clone(), &args.owner).await?;
+
+    let (mut tx, signing_data) = args.build(namada).await?;
+
+    if args.tx.dump_tx {
+        tx::dump_tx(namada.io(), &args.tx, tx);
+    } else {
+        sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+        namada.submit(tx, &args.tx).await?;
+    }
+
+    Ok(())
+}
+
 pub async fn submit_update_account<N: Namada>(
     namada: &N,
     args: args::TxUpdateAccount,
@@ -432,7 +455,7 @@ pub async fn submit_change_consensus_key(
         &mut tx,
         unshield,
         fee_amount,
-        signing_data.fee_payer.clone(),
+        &signing_data,
     )
     .await?;
 
@@ -771,7 +794,7 @@ pub async fn submit_become_validator(
         &mut tx,
         unshield,
         fee_amount,
-        signing_data.fee_payer.clone(),
+        &signing_data,
     )
     .await?;
 
@@ -887,6 +910,7 @@ pub async fn submit_init_validator(
             vp_code_path: validator_vp_code_path,
             tx_code_path: tx_init_account_code_path,
             public_keys: account_keys,
+            public_key_weights: vec![],
             threshold,
         },
     )
@@ -984,6 +1008,67 @@ pub async fn submit_transfer(
     Ok(())
 }
 
+pub async fn submit_consolidate_notes(
+    namada: &impl Namada,
+    args: args::TxConsolidateNotes,
+) -> Result<(), error::Error> {
+    let plan = args.plan(namada).await?;
+    if plan.is_empty() {
+        display_line!(
+            namada.io(),
+            "No notes of {} to consolidate.",
+            args.token
+        );
+        return Ok(());
+    }
+    display_line!(namada.io(), "Note consolidation plan:");
+    for (idx, step) in plan.iter().enumerate() {
+        display_line!(
+            namada.io(),
+            "  {}. Merge {} notes into {} holding {} {} with {} descriptions \
+             and about {} gas",
+            idx + 1,
+            step.notes.len(),
+            step.outputs.len(),
+            namada.format_amount(&args.token, step.amount).await,
+            args.token,
+            step.descriptions(),
+            step.estimated_gas(),
+        );
+    }
+    display_line!(
+        namada.io(),
+        "The {} transactions remove {} notes in total.",
+        plan.len(),
+        plan.iter().map(|step| step.merged_notes()).sum::<usize>(),
+    );
+
+    for (idx, step) in plan.iter().enumerate() {
+        let (mut tx, signing_data, _) = args.build(namada, step).await?;
+
+        if args.tx.dump_tx {
+            tx::dump_tx(namada.io(), &args.tx, tx);
+        } else {
+            sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+            let result = namada.submit(tx, &args.tx).await?;
+            // The notes of a rejected step stay marked as spent until the
+            // next sync, so don't go on with the plan
+            if matches!(result, ProcessTxResponse::Applied(_))
+                && result.is_applied_and_valid().is_none()
+            {
+                return Err(error::Error::Other(format!(
+                    "The note consolidation step {} was rejected. Sync the \
+                     shielded context before retrying.",
+                    idx + 1
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
 pub async fn submit_ibc_transfer<N: Namada>(
     namada: &N,
     args: args::TxIbcTransfer,
@@ -1237,6 +1322,37 @@ where
     Ok(())
 }
 
+/// Print the content of a serialized transaction in JSON
+pub fn inspect_tx(
+    io: &impl Io,
+    args::TxInspect {
+        tx_data,
+        wasm_checksums_path,
+    }: args::TxInspect,
+) -> Result<(), error::Error> {
+    let tx = if let Ok(transaction) = Tx::deserialize(tx_data.as_ref()) {
+        transaction
+    } else {
+        edisplay_line!(io, "Couldn't decode the transaction.");
+        safe_exit(1)
+    };
+    // Without checksums, the code hashes are left unresolved and the tx data
+    // are only decoded according to the code tags
+    let wasm_names = wasm_checksums_path
+        .and_then(|path| Checksums::read_checksums_file(path).ok())
+        .map(|checksums| tx::inspect::wasm_names_from_checksums(&checksums.0))
+        .unwrap_or_default();
+    let inspected = tx::inspect::inspect_tx(&tx, &wasm_names);
+    display_line!(
+        io,
+        "{}",
+        serde_json::to_string_pretty(&inspected).map_err(|err| {
+            error::Error::Encode(error::EncodingError::Serde(err.to_string()))
+        })?
+    );
+    Ok(())
+}
+
 pub async fn sign_tx<N: Namada>(
     namada: &N,
     args::SignTx {
@@ -1558,6 +1674,126 @@ where
     Ok(())
 }
 
+pub async fn submit_grant_fee_allowance<N: Namada>(
+    namada: &N,
+    args: args::TxGrantFeeAllowance,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
+{
+    let (mut tx, signing_data) = args.build(namada).await?;
+
+    if args.tx.dump_tx {
+        tx::dump_tx(namada.io(), &args.tx, tx);
+    } else {
+        sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+        namada.submit(tx, &args.tx).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn submit_revoke_fee_allowance<N: Namada>(
+    namada: &N,
+    args: args::TxRevokeFeeAllowance,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
+{
+    let (mut tx, signing_data) = args.build(namada).await?;
+
+    if args.tx.dump_tx {
+        tx::dump_tx(namada.io(), &args.tx, tx);
+    } else {
+        sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+        namada.submit(tx, &args.tx).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn submit_schedule_tx<N: Namada>(
+    namada: &N,
+    args: args::TxScheduleTx,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
+{
+    let (mut tx, signing_data) = args.build(namada).await?;
+
+    if args.tx.dump_tx {
+        tx::dump_tx(namada.io(), &args.tx, tx);
+    } else {
+        sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+        namada.submit(tx, &args.tx).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn submit_cancel_scheduled_tx<N: Namada>(
+    namada: &N,
+    args: args::TxCancelScheduledTx,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
+{
+    let (mut tx, signing_data) = args.build(namada).await?;
+
+    if args.tx.dump_tx {
+        tx::dump_tx(namada.io(), &args.tx, tx);
+    } else {
+        sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+        namada.submit(tx, &args.tx).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn submit_update_spending_policy<N: Namada>(
+    namada: &N,
+    args: args::TxUpdateSpendingPolicy,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
+{
+    let (mut tx, signing_data) = args.build(namada).await?;
+
+    if args.tx.dump_tx {
+        tx::dump_tx(namada.io(), &args.tx, tx);
+    } else {
+        sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+        namada.submit(tx, &args.tx).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn submit_remove_spending_policy<N: Namada>(
+    namada: &N,
+    args: args::TxRemoveSpendingPolicy,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
+{
+    let (mut tx, signing_data) = args.build(namada).await?;
+
+    if args.tx.dump_tx {
+        tx::dump_tx(namada.io(), &args.tx, tx);
+    } else {
+        sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+        namada.submit(tx, &args.tx).await?;
+    }
+
+    Ok(())
+}
+
 pub async fn submit_resign_steward<N: Namada>(
     namada: &N,
     args: args::ResignSteward,
@@ -10,6 +10,7 @@ use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
 use flate2::Compression;
 use itertools::Either;
+use namada::core::address::Address;
 use namada::core::chain::ChainId;
 use namada::core::dec::Dec;
 use namada::core::key::*;
@@ -26,7 +27,6 @@ use tokio::sync::RwLock;
 use crate::cli::args;
 use crate::cli::args::TestGenesis;
 use crate::cli::context::ENV_VAR_WASM_DIR;
-use crate::config::genesis::chain::DeriveEstablishedAddress;
 use crate::config::genesis::transactions::{
     sign_delegation_bond_tx, sign_validator_account_tx, UnsignedTransactions,
 };
@@ -674,11 +674,7 @@ pub fn derive_genesis_addresses(
     println!("{}", "Established account txs:".underline().bold());
     for tx in &estbd_txs {
         println!();
-        println!(
-            "{} {}",
-            "Address:".bold().bright_green(),
-            tx.derive_address()
-        );
+        println!("{} {}", "Address:".bold().bright_green(), tx.address());
 
         println!("{}", "Public key(s):".bold().bright_green());
         for (ix, pk) in tx.public_keys.iter().enumerate() {
@@ -820,9 +816,9 @@ pub fn init_genesis_validator(
         .established_account
         .as_ref()
         .and_then(|accts| {
-            accts
-                .iter()
-                .find(|acct| acct.derive_established_address() == address)
+            accts.iter().find(|acct| {
+                acct.address() == Address::Established(address.clone())
+            })
         })
         .is_none()
     {
@@ -2,6 +2,7 @@
 //! The parameters used for the chain's genesis
 
 pub mod chain;
+pub mod export;
 pub mod templates;
 pub mod transactions;
 pub mod utils;
@@ -446,6 +447,7 @@ pub fn make_dev_genesis(
                 genesis.transactions.established_account.as_mut().unwrap();
 
             let tx = transactions::EstablishedAccountTx {
+                address: None,
                 vp: utils::VP_USER.to_string(),
                 public_keys: vec![StringEncoded::new(
                     consensus_keypair.ref_to(),
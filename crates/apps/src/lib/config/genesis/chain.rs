@@ -165,6 +165,7 @@ impl Finalized {
             InternalAddress::EthBridgePool,
             InternalAddress::Governance,
             InternalAddress::Pgf,
+            InternalAddress::Scheduler,
         ] {
             wallet.insert_address(
                 int_add.to_string().to_lowercase(),
@@ -292,6 +293,7 @@ impl Finalized {
             max_block_gas,
             minimum_gas_price,
             max_tx_bytes,
+            base_fee_params,
             ..
         } = self.parameters.parameters.clone();
 
@@ -345,6 +347,7 @@ impl Finalized {
                     )
                 })
                 .collect(),
+            base_fee_params,
         }
     }
 
@@ -662,8 +665,13 @@ impl FinalizedTransactions {
         let established_account = established_account.map(|txs| {
             txs.into_iter()
                 .map(|tx| FinalizedEstablishedAccountTx {
-                    address: tx.derive_address(),
-                    tx,
+                    address: tx.address(),
+                    // The address is only kept in the finalized tx, with
+                    // which the tx is flattened
+                    tx: transactions::EstablishedAccountTx {
+                        address: None,
+                        ..tx
+                    },
                 })
                 .collect()
         });
@@ -705,6 +713,9 @@ pub struct FinalizedParameters {
     pub pos_params: templates::PosParams,
     pub gov_params: templates::GovernanceParams,
     pub pgf_params: namada::governance::pgf::parameters::PgfParameters,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "templates::PgfState::is_empty")]
+    pub pgf_state: templates::PgfState,
     pub eth_bridge_params: Option<templates::EthBridgeParams>,
 }
 
@@ -729,6 +740,7 @@ impl FinalizedParameters {
             pos_params,
             gov_params,
             pgf_params: finalized_pgf_params,
+            pgf_state: pgf_params.state,
             eth_bridge_params,
         }
     }
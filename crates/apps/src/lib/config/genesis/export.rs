@@ -0,0 +1,901 @@
+//! Export of a chain's state at some block height into genesis templates, used
+//! to restart a chain from its last state after it was halted.
+//!
+//! Nothing is dropped silently: the export fails with a list of all the parts
+//! of the state that have no representation in the genesis templates. The
+//! state is carried over as follows:
+//! - Established accounts, including the ones initialized on-chain, are
+//!   exported with their address and their current VP, public keys and
+//!   threshold. Their VP has to be one of the genesis VPs, and they may not
+//!   have public key weights.
+//! - Implicit accounts are carried over if their public key has been revealed.
+//!   Neither kind of account may have a spending policy or fee allowances.
+//! - Validators have to be initialized in genesis, because their node key and
+//!   net address are only found in their genesis txs. The genesis txs are
+//!   reused for the validators whose keys, commission rates and metadata are
+//!   unchanged, while the others are exported unsigned into
+//!   [`UNSIGNED_TRANSACTIONS_FILE_NAME`] with their current values.
+//! - Bonds are exported unsigned as well. The unsigned txs have to be signed
+//!   (with `namadac utils sign-genesis-txs`) and added to the transactions
+//!   template before the templates can be used to `init-network`.
+//! - Unbonded tokens and unclaimed PoS rewards are returned to the balances of
+//!   their owners, and bonds are exported minus their slashes.
+//! - The governance parameters are exported. There may be no pending proposals,
+//!   while the finished ones are history that the new chain starts without.
+//! - The PGF parameters are exported with the stewards' reward distributions,
+//!   the continuous fundings and the treasury.
+//! - Only the tokens of the original genesis can be exported, and no internal
+//!   address other than PoS and PGF may hold any.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+use borsh::BorshDeserialize;
+use itertools::Itertools;
+use namada::core::address::{self, Address};
+use namada::core::chain::ChainId;
+use namada::core::key::common;
+use namada::core::storage::{
+    BlockHash, BlockHeight, Epoch, Epochs, Header, Key, KeySeg, TxIndex,
+};
+use namada::core::string_encoding::StringEncoded;
+use namada::core::token::{Amount, DenominatedAmount, Denomination};
+use namada::governance::pgf::storage::steward::StewardDetail;
+use namada::governance::storage::{
+    get_proposal_by_id, get_proposal_result, keys as gov_keys,
+};
+use namada::proof_of_stake::queries::bonds_and_unbonds;
+use namada::proof_of_stake::storage::{
+    read_all_validator_addresses, read_pos_params, read_validator_avatar,
+    read_validator_description, read_validator_discord_handle,
+    read_validator_email, read_validator_max_commission_rate_change,
+    read_validator_website, validator_commission_rate_handle,
+    validator_consensus_key_handle, validator_eth_cold_key_handle,
+    validator_eth_hot_key_handle, validator_protocol_key_handle,
+};
+use namada::proof_of_stake::types::{
+    BondId, BondsAndUnbondsDetail, ValidatorMetaData,
+};
+use namada::proof_of_stake::{query_reward_tokens, OwnedPosParams};
+use namada::state::{
+    iter_prefix_bytes, DBIter, StorageError, StorageRead, StorageResult, DB,
+};
+use namada::token::storage_key::{balance_key, is_any_token_balance_key};
+
+use super::chain::{Finalized, FinalizedValidatorAccountTx};
+use super::templates::{
+    self, ChainParams, GovernanceParams, Parameters, PgfFunding, PgfParams,
+    PgfState, PosParams, RawTokenBalances, Tokens, UndenominatedBalances,
+    Unvalidated,
+};
+use super::transactions::{
+    BondTx, EstablishedAccountTx, Transactions, UnsignedTransactions,
+    UnsignedValidatorAccountTx,
+};
+use super::utils::write_toml;
+use super::GenesisAddress;
+use crate::wallet::Alias;
+
+pub const UNSIGNED_TRANSACTIONS_FILE_NAME: &str = "unsigned-transactions.toml";
+
+/// A read-only view of the subspace of a DB at some committed block height.
+///
+/// The values are read from the DB on demand, so that the storage API can be
+/// used to read the state of a past block.
+#[derive(Debug)]
+pub struct HistoricState<'db, D> {
+    db: &'db D,
+    chain_id: ChainId,
+    height: BlockHeight,
+    last_height: BlockHeight,
+    epoch: Epoch,
+    pred_epochs: Epochs,
+    native_token: Address,
+}
+
+impl<'db, D> HistoricState<'db, D>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    /// Open a view of the given DB at the given block height, which defaults
+    /// to the last committed one. Reading a past height requires the diffs of
+    /// all the following heights to be present in the DB.
+    pub fn load(
+        db: &'db D,
+        chain_id: ChainId,
+        height: Option<BlockHeight>,
+        native_token: Address,
+    ) -> eyre::Result<Self> {
+        let last_block = db
+            .read_last_block()?
+            .ok_or_else(|| eyre::eyre!("No committed block found in the DB"))?;
+        let last_height = last_block.height;
+        let height = height.unwrap_or(last_height);
+        if height > last_height {
+            eyre::bail!(
+                "The height {height} is above the last committed height \
+                 {last_height}"
+            );
+        }
+        if db.read_pruned_diffs()?.is_pruned(height) {
+            eyre::bail!(
+                "The diffs needed to read the height {height} have been pruned"
+            );
+        }
+        let epoch = last_block
+            .pred_epochs
+            .get_epoch(height)
+            .ok_or_else(|| eyre::eyre!("Unknown epoch of height {height}"))?;
+
+        Ok(Self {
+            db,
+            chain_id,
+            height,
+            last_height,
+            epoch,
+            pred_epochs: last_block.pred_epochs,
+            native_token,
+        })
+    }
+
+    /// Read the value of a subspace key at the view's height
+    fn read_at_height(&self, key: &Key) -> StorageResult<Option<Vec<u8>>> {
+        let val = if self.height == self.last_height {
+            self.db.read_subspace_val(key)
+        } else {
+            self.db.read_subspace_val_with_height(
+                key,
+                self.height,
+                self.last_height,
+            )
+        };
+        val.map_err(StorageError::new)
+    }
+}
+
+impl<'db, D> StorageRead for HistoricState<'db, D>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    type PrefixIter<'iter>
+        = Box<dyn Iterator<Item = StorageResult<(String, Vec<u8>)>> + 'iter>
+    where
+        Self: 'iter;
+
+    fn read_bytes(&self, key: &Key) -> StorageResult<Option<Vec<u8>>> {
+        self.read_at_height(key)
+    }
+
+    fn has_key(&self, key: &Key) -> StorageResult<bool> {
+        Ok(self.read_bytes(key)?.is_some())
+    }
+
+    fn iter_prefix<'iter>(
+        &'iter self,
+        prefix: &Key,
+    ) -> StorageResult<Self::PrefixIter<'iter>> {
+        let current_keys = self
+            .db
+            .iter_prefix(Some(prefix))
+            .map(|(key, val, _gas)| (key, val));
+        if self.height == self.last_height {
+            return Ok(Box::new(current_keys.map(Ok)));
+        }
+        // The keys deleted after the view's height can only be found in the
+        // diffs. Only these are collected, while the current keys are merged
+        // into them in order.
+        let mut deleted_keys = BTreeSet::new();
+        for raw_height in self.height.0 + 1..=self.last_height.0 {
+            deleted_keys.extend(
+                self.db
+                    .iter_old_diffs(BlockHeight(raw_height), Some(prefix))
+                    .map(|(key, _val, _gas)| key),
+            );
+        }
+        let keys = current_keys
+            .map(|(key, _val)| key)
+            .merge(deleted_keys)
+            .dedup();
+        Ok(Box::new(keys.filter_map(move |key| {
+            self.read_at_height(&Key::from(key.to_db_key()))
+                .transpose()
+                .map(|val| val.map(|val| (key, val)))
+        })))
+    }
+
+    fn iter_next<'iter>(
+        &'iter self,
+        iter: &mut Self::PrefixIter<'iter>,
+    ) -> StorageResult<Option<(String, Vec<u8>)>> {
+        iter.next().transpose()
+    }
+
+    fn get_chain_id(&self) -> StorageResult<String> {
+        Ok(self.chain_id.to_string())
+    }
+
+    fn get_block_height(&self) -> StorageResult<BlockHeight> {
+        Ok(self.height)
+    }
+
+    fn get_block_header(
+        &self,
+        _height: BlockHeight,
+    ) -> StorageResult<Option<Header>> {
+        Err(StorageError::new_const(
+            "Block headers are not available in a historic state",
+        ))
+    }
+
+    fn get_block_hash(&self) -> StorageResult<BlockHash> {
+        Err(StorageError::new_const(
+            "Block hashes are not available in a historic state",
+        ))
+    }
+
+    fn get_block_epoch(&self) -> StorageResult<Epoch> {
+        Ok(self.epoch)
+    }
+
+    fn get_pred_epochs(&self) -> StorageResult<Epochs> {
+        Ok(self.pred_epochs.clone())
+    }
+
+    fn get_tx_index(&self) -> StorageResult<TxIndex> {
+        Ok(TxIndex::default())
+    }
+
+    fn get_native_token(&self) -> StorageResult<Address> {
+        Ok(self.native_token.clone())
+    }
+}
+
+/// Genesis templates exported from a chain's state.
+#[derive(Debug)]
+pub struct ExportedGenesis {
+    /// The templates to `init-network` with, once the unsigned txs are signed
+    pub templates: templates::All<Unvalidated>,
+    /// The validator accounts and bonds that have to be signed and added to
+    /// the transactions template
+    pub unsigned_txs: UnsignedTransactions,
+}
+
+impl ExportedGenesis {
+    /// Write the templates and the unsigned txs into the given directory.
+    pub fn write_toml_files(&self, output_dir: &Path) -> eyre::Result<()> {
+        std::fs::create_dir_all(output_dir)?;
+        self.templates.write_toml_files(output_dir)?;
+        write_toml(
+            &self.unsigned_txs,
+            &output_dir.join(UNSIGNED_TRANSACTIONS_FILE_NAME),
+            "Unsigned transactions",
+        )
+    }
+}
+
+/// Export the given state into genesis templates. The `genesis` of the chain
+/// is used to find the aliases of tokens and VPs and the txs of the
+/// validators. Fails if any part of the state cannot be exported.
+pub fn export_genesis<S>(
+    storage: &S,
+    genesis: &Finalized,
+) -> eyre::Result<ExportedGenesis>
+where
+    S: StorageRead,
+{
+    // The parts of the state that cannot be exported, all of which are
+    // reported before failing
+    let mut unexportable = BTreeSet::new();
+    let epoch = storage.get_block_epoch()?;
+
+    // Established accounts, with the account state that has no
+    // representation in genesis found in the same pass over the subspace
+    let vp_names = genesis_vp_names(storage, genesis)?;
+    let mut established_account = vec![];
+    let mut exported_accounts = BTreeSet::new();
+    for result in iter_prefix_bytes(storage, &Key::default())? {
+        let (key, val) = result?;
+        if let Some(owner) = namada::account::is_pk_weight_key(&key) {
+            unexportable
+                .insert(format!("The account {owner} has public key weights."));
+        } else if let Some(owner) =
+            namada::account::is_spending_policy_key(&key)
+        {
+            unexportable
+                .insert(format!("The account {owner} has a spending policy."));
+        } else if let Some((granter, grantee)) =
+            namada::account::is_fee_allowance_key(&key)
+        {
+            unexportable.insert(format!(
+                "The account {granter} granted a fee allowance to {grantee}."
+            ));
+        }
+        let Some(owner) = key.is_validity_predicate() else {
+            continue;
+        };
+        let Address::Established(address) = owner else {
+            continue;
+        };
+        let Some(vp) = vp_names.get(&val) else {
+            unexportable.insert(format!(
+                "The VP of the account {owner} is not a genesis VP."
+            ));
+            continue;
+        };
+        let public_keys = account_public_keys(storage, owner)?;
+        let threshold = namada::account::threshold(storage, owner)?;
+        let Some(threshold) = threshold.filter(|_| !public_keys.is_empty())
+        else {
+            unexportable.insert(format!(
+                "The account {owner} has no public keys or threshold."
+            ));
+            continue;
+        };
+        exported_accounts.insert(owner.clone());
+        established_account.push(EstablishedAccountTx {
+            address: Some(StringEncoded::new(address.clone())),
+            vp: vp.clone(),
+            threshold,
+            public_keys: public_keys
+                .into_iter()
+                .map(StringEncoded::new)
+                .collect(),
+        });
+    }
+
+    // Validators, of which only the ones initialized in genesis can be
+    // exported
+    let pos_params = read_pos_params(storage)?;
+    let validators = read_all_validator_addresses(storage, epoch)?;
+    let mut validator_account = vec![];
+    let mut unsigned_validator_account = vec![];
+    let mut genesis_validators = HashSet::new();
+    for FinalizedValidatorAccountTx { tx } in
+        genesis.transactions.validator_account.iter().flatten()
+    {
+        let address = Address::Established(tx.data.address.raw.clone());
+        if !validators.contains(&address) {
+            continue;
+        }
+        let genesis_tx = UnsignedValidatorAccountTx::from(&tx.data);
+        let current_tx = current_validator_tx(
+            storage,
+            &pos_params,
+            epoch,
+            &address,
+            &genesis_tx,
+        )?;
+        // The signatures of the genesis tx are made with the account's keys,
+        // so these have to be unchanged as well
+        let account_keys =
+            |tx: &EstablishedAccountTx| (tx.public_keys.clone(), tx.threshold);
+        let genesis_keys = genesis
+            .transactions
+            .established_account
+            .iter()
+            .flatten()
+            .find(|account| account.address == address)
+            .map(|account| account_keys(&account.tx));
+        let current_keys = established_account
+            .iter()
+            .find(|account| account.address() == address)
+            .map(account_keys);
+        if current_tx == genesis_tx && current_keys == genesis_keys {
+            validator_account.push(tx.clone());
+        } else {
+            unsigned_validator_account.push(current_tx);
+        }
+        genesis_validators.insert(address);
+    }
+    for validator in validators.difference(&genesis_validators) {
+        unexportable.insert(format!(
+            "The validator {validator} was not initialized in genesis, so its \
+             node key and net address are unknown."
+        ));
+    }
+
+    let tokens: BTreeMap<&Address, (&Alias, Denomination)> = genesis
+        .tokens
+        .token
+        .iter()
+        .map(|(alias, token)| (&token.address, (alias, token.config.denom)))
+        .collect();
+    let native_alias = &genesis.parameters.parameters.native_token;
+
+    // Liquid balances, with the balances of PGF exported into its treasury
+    let mut balances: BTreeMap<Alias, BTreeMap<GenesisAddress, Amount>> =
+        BTreeMap::new();
+    let mut treasury: BTreeMap<Alias, Amount> = BTreeMap::new();
+    let multitoken_prefix = Key::from(address::MULTITOKEN.to_db_key());
+    for result in iter_prefix_bytes(storage, &multitoken_prefix)? {
+        let (key, val) = result?;
+        let Some([token, owner]) = is_any_token_balance_key(&key) else {
+            continue;
+        };
+        let amount = Amount::try_from_slice(&val)
+            .map_err(|_| StorageError::new_const("Invalid token balance"))?;
+        // The bonded tokens are re-bonded in genesis
+        if amount.is_zero() || *owner == address::POS {
+            continue;
+        }
+        let Some((alias, denom)) = tokens.get(token) else {
+            unexportable.insert(format!(
+                "The token {token} held by {owner} was not in genesis."
+            ));
+            continue;
+        };
+        if *owner == namada::governance::pgf::ADDRESS {
+            *treasury.entry((*alias).clone()).or_default() += amount;
+            continue;
+        }
+        match genesis_address(storage, &exported_accounts, owner)? {
+            Some(owner) => {
+                *balances
+                    .entry((*alias).clone())
+                    .or_default()
+                    .entry(owner)
+                    .or_default() += amount;
+            }
+            None => {
+                unexportable.insert(format!(
+                    "The balance of {} {alias} of {owner} has no owner in \
+                     genesis.",
+                    DenominatedAmount::new(amount, *denom),
+                ));
+            }
+        }
+    }
+
+    // Bonds, with unbonds and rewards returned to their owners
+    let mut bond = vec![];
+    let mut total_pos_amount = Amount::zero();
+    for (BondId { source, validator }, details) in
+        bonds_and_unbonds(storage, None, None)?
+    {
+        let (bonded, unbonded) = bond_amounts(&details);
+        let rewards =
+            query_reward_tokens(storage, Some(&source), &validator, epoch)?;
+        total_pos_amount += bonded + unbonded + rewards;
+        let Some(owner) =
+            genesis_address(storage, &exported_accounts, &source)?
+        else {
+            unexportable.insert(format!(
+                "The bonds of {source} to {validator} have no owner in \
+                 genesis."
+            ));
+            continue;
+        };
+        // The source has to own the bonded tokens before it can bond them
+        *balances
+            .entry(native_alias.clone())
+            .or_default()
+            .entry(owner.clone())
+            .or_default() += bonded + unbonded + rewards;
+        if !bonded.is_zero() && genesis_validators.contains(&validator) {
+            bond.push(BondTx {
+                source: owner,
+                validator,
+                amount: DenominatedAmount::native(bonded),
+            });
+        }
+    }
+    let pos_balance: Amount = storage
+        .read(&balance_key(genesis.get_native_token(), &address::POS))?
+        .unwrap_or_default();
+    if let Some(unaccounted) = pos_balance.checked_sub(total_pos_amount) {
+        if !unaccounted.is_zero() {
+            unexportable.insert(format!(
+                "{} {native_alias} held by PoS are not in bonds, unbonds or \
+                 rewards.",
+                unaccounted.to_string_native()
+            ));
+        }
+    }
+
+    // Only the finished governance proposals may be left behind
+    let proposals_count: u64 = storage
+        .read(&gov_keys::get_counter_key())?
+        .unwrap_or_default();
+    for id in 0..proposals_count {
+        if get_proposal_by_id(storage, id)?.is_some()
+            && get_proposal_result(storage, id)?.is_none()
+        {
+            unexportable
+                .insert(format!("The governance proposal {id} is pending."));
+        }
+    }
+
+    let balances = UndenominatedBalances {
+        token: balances
+            .into_iter()
+            .map(|(alias, balances)| {
+                let denom = genesis
+                    .tokens
+                    .token
+                    .get(&alias)
+                    .expect("Only genesis tokens are exported")
+                    .config
+                    .denom;
+                let balances = balances
+                    .into_iter()
+                    .map(|(owner, amount)| {
+                        (owner, DenominatedAmount::new(amount, denom))
+                    })
+                    .collect();
+                (alias, RawTokenBalances(balances))
+            })
+            .collect(),
+    };
+    let pgf_params = export_pgf_params(
+        storage,
+        &exported_accounts,
+        treasury,
+        &mut unexportable,
+    )?;
+    let parameters = export_parameters(
+        storage,
+        genesis,
+        &pos_params.owned,
+        &tokens,
+        &vp_names,
+        pgf_params,
+        &mut unexportable,
+    )?;
+
+    if !unexportable.is_empty() {
+        eyre::bail!(
+            "The state cannot be exported into genesis templates:\n{}",
+            unexportable
+                .iter()
+                .map(|part| format!("  - {part}"))
+                .join("\n")
+        );
+    }
+
+    let templates = templates::All {
+        vps: genesis.vps.clone(),
+        tokens: Tokens {
+            token: genesis
+                .tokens
+                .token
+                .iter()
+                .map(|(alias, token)| (alias.clone(), token.config.clone()))
+                .collect(),
+        },
+        balances,
+        parameters,
+        transactions: Transactions {
+            established_account: (!established_account.is_empty())
+                .then_some(established_account),
+            validator_account: (!validator_account.is_empty())
+                .then_some(validator_account),
+            bond: None,
+        },
+    };
+    let unsigned_txs = UnsignedTransactions {
+        established_account: None,
+        validator_account: (!unsigned_validator_account.is_empty())
+            .then_some(unsigned_validator_account),
+        bond: (!bond.is_empty()).then_some(bond),
+    };
+    Ok(ExportedGenesis {
+        templates,
+        unsigned_txs,
+    })
+}
+
+/// Find the names of the genesis VPs by the hashes of their code.
+fn genesis_vp_names<S>(
+    storage: &S,
+    genesis: &Finalized,
+) -> StorageResult<HashMap<Vec<u8>, String>>
+where
+    S: StorageRead,
+{
+    let mut vp_names = HashMap::new();
+    for (name, config) in &genesis.vps.wasm {
+        if let Some(hash) =
+            storage.read_bytes(&Key::wasm_hash(&config.filename))?
+        {
+            vp_names.entry(hash).or_insert_with(|| name.clone());
+        }
+    }
+    Ok(vp_names)
+}
+
+/// Read the public keys of an account in the order of their indices, which
+/// the signatures of the account refer to.
+fn account_public_keys<S>(
+    storage: &S,
+    owner: &Address,
+) -> StorageResult<Vec<common::PublicKey>>
+where
+    S: StorageRead,
+{
+    let public_keys: BTreeMap<u8, common::PublicKey> =
+        namada::account::pks_handle(owner)
+            .iter(storage)?
+            .collect::<StorageResult<_>>()?;
+    Ok(public_keys.into_values().collect())
+}
+
+/// Update the genesis tx of a validator with its current keys, commission
+/// rates and metadata.
+fn current_validator_tx<S>(
+    storage: &S,
+    params: &namada::proof_of_stake::PosParams,
+    epoch: Epoch,
+    validator: &Address,
+    genesis_tx: &UnsignedValidatorAccountTx,
+) -> StorageResult<UnsignedValidatorAccountTx>
+where
+    S: StorageRead,
+{
+    let key = |current: Option<common::PublicKey>,
+               genesis: &StringEncoded<common::PublicKey>| {
+        current
+            .map(StringEncoded::new)
+            .unwrap_or_else(|| genesis.clone())
+    };
+    Ok(UnsignedValidatorAccountTx {
+        commission_rate: validator_commission_rate_handle(validator)
+            .get(storage, epoch, params)?
+            .unwrap_or(genesis_tx.commission_rate),
+        max_commission_rate_change: read_validator_max_commission_rate_change(
+            storage, validator,
+        )?
+        .unwrap_or(genesis_tx.max_commission_rate_change),
+        consensus_key: key(
+            validator_consensus_key_handle(validator)
+                .get(storage, epoch, params)?,
+            &genesis_tx.consensus_key,
+        ),
+        protocol_key: key(
+            validator_protocol_key_handle(validator)
+                .get(storage, epoch, params)?,
+            &genesis_tx.protocol_key,
+        ),
+        eth_hot_key: key(
+            validator_eth_hot_key_handle(validator)
+                .get(storage, epoch, params)?,
+            &genesis_tx.eth_hot_key,
+        ),
+        eth_cold_key: key(
+            validator_eth_cold_key_handle(validator)
+                .get(storage, epoch, params)?,
+            &genesis_tx.eth_cold_key,
+        ),
+        metadata: ValidatorMetaData {
+            email: read_validator_email(storage, validator)?
+                .unwrap_or_else(|| genesis_tx.metadata.email.clone()),
+            description: read_validator_description(storage, validator)?,
+            website: read_validator_website(storage, validator)?,
+            discord_handle: read_validator_discord_handle(storage, validator)?,
+            avatar: read_validator_avatar(storage, validator)?,
+        },
+        ..genesis_tx.clone()
+    })
+}
+
+/// Find the genesis address of the given owner, if it can be initialized in
+/// genesis.
+fn genesis_address<S>(
+    storage: &S,
+    exported_accounts: &BTreeSet<Address>,
+    owner: &Address,
+) -> StorageResult<Option<GenesisAddress>>
+where
+    S: StorageRead,
+{
+    Ok(match owner {
+        Address::Established(address) if exported_accounts.contains(owner) => {
+            Some(GenesisAddress::EstablishedAddress(address.clone()))
+        }
+        Address::Implicit(_) => namada::account::public_keys(storage, owner)?
+            .into_iter()
+            .find(|pk| &Address::from(pk) == owner)
+            .map(|pk| GenesisAddress::PublicKey(StringEncoded::new(pk))),
+        _ => None,
+    })
+}
+
+/// Get the bonded and unbonded amounts of a bond, minus their slashes.
+fn bond_amounts(details: &BondsAndUnbondsDetail) -> (Amount, Amount) {
+    let unslashed = |amount: Amount, slashed: Option<Amount>| {
+        amount
+            .checked_sub(slashed.unwrap_or_default())
+            .unwrap_or_default()
+    };
+    let bonded = details
+        .bonds
+        .iter()
+        .map(|bond| unslashed(bond.amount, bond.slashed_amount))
+        .fold(Amount::zero(), |acc, amount| acc + amount);
+    let unbonded = details
+        .unbonds
+        .iter()
+        .map(|unbond| unslashed(unbond.amount, unbond.slashed_amount))
+        .fold(Amount::zero(), |acc, amount| acc + amount);
+    (bonded, unbonded)
+}
+
+/// Read the parameters from the state. The parameters that have no
+/// representation in the state are taken from the given genesis.
+fn export_parameters<S>(
+    storage: &S,
+    genesis: &Finalized,
+    pos_params: &OwnedPosParams,
+    tokens: &BTreeMap<&Address, (&Alias, Denomination)>,
+    vp_names: &HashMap<Vec<u8>, String>,
+    pgf_params: PgfParams<Unvalidated>,
+    unexportable: &mut BTreeSet<String>,
+) -> StorageResult<Parameters<Unvalidated>>
+where
+    S: StorageRead,
+{
+    let params = namada::ledger::parameters::read(storage)?;
+    let mut minimum_gas_price = BTreeMap::new();
+    for (token, amount) in params.minimum_gas_price {
+        match tokens.get(&token) {
+            Some((alias, denom)) => {
+                minimum_gas_price.insert(
+                    (*alias).clone(),
+                    DenominatedAmount::new(amount, *denom),
+                );
+            }
+            None => {
+                unexportable.insert(format!(
+                    "The minimum gas price is in the token {token}, which was \
+                     not in genesis."
+                ));
+            }
+        }
+    }
+    let genesis_params = &genesis.parameters.parameters;
+    let implicit_vp = match params
+        .implicit_vp_code_hash
+        .and_then(|hash| vp_names.get(hash.0.as_slice()))
+    {
+        Some(implicit_vp) => implicit_vp.clone(),
+        None => {
+            unexportable.insert("The implicit VP is not a genesis VP.".into());
+            genesis_params.implicit_vp.clone()
+        }
+    };
+    let parameters = ChainParams {
+        max_tx_bytes: params.max_tx_bytes,
+        native_token: genesis_params.native_token.clone(),
+        min_num_of_blocks: params.epoch_duration.min_num_of_blocks,
+        max_expected_time_per_block: params.max_expected_time_per_block.0
+            as i64,
+        max_proposal_bytes: params.max_proposal_bytes,
+        vp_allowlist: (!params.vp_allowlist.is_empty())
+            .then_some(params.vp_allowlist),
+        tx_allowlist: (!params.tx_allowlist.is_empty())
+            .then_some(params.tx_allowlist),
+        implicit_vp,
+        epochs_per_year: params.epochs_per_year,
+        max_signatures_per_transaction: params.max_signatures_per_transaction,
+        max_block_gas: params.max_block_gas,
+        fee_unshielding_gas_limit: params.fee_unshielding_gas_limit,
+        fee_unshielding_descriptions_limit: params
+            .fee_unshielding_descriptions_limit,
+        minimum_gas_price,
+        base_fee_params: params.base_fee_params,
+    };
+
+    let OwnedPosParams {
+        max_validator_slots,
+        pipeline_len,
+        unbonding_len,
+        tm_votes_per_token,
+        block_proposer_reward,
+        block_vote_reward,
+        max_inflation_rate,
+        target_staked_ratio,
+        duplicate_vote_min_slash_rate,
+        light_client_attack_min_slash_rate,
+        cubic_slashing_window_length,
+        validator_stake_threshold,
+        liveness_window_check,
+        liveness_threshold,
+        rewards_gain_p,
+        rewards_gain_d,
+    } = pos_params.clone();
+    let pos_params = PosParams {
+        max_validator_slots,
+        pipeline_len,
+        unbonding_len,
+        tm_votes_per_token,
+        block_proposer_reward,
+        block_vote_reward,
+        max_inflation_rate,
+        target_staked_ratio,
+        duplicate_vote_min_slash_rate,
+        light_client_attack_min_slash_rate,
+        cubic_slashing_window_length,
+        validator_stake_threshold,
+        liveness_window_check,
+        liveness_threshold,
+        rewards_gain_p,
+        rewards_gain_d,
+    };
+
+    let gov_params = namada::governance::storage::get_parameters(storage)?;
+    // The template only supports whole tokens
+    let whole_token = Amount::native_whole(1);
+    let min_proposal_fund = gov_params
+        .min_proposal_fund
+        .checked_div(whole_token)
+        .unwrap_or_default();
+    if min_proposal_fund.checked_mul(whole_token)
+        != Some(gov_params.min_proposal_fund)
+    {
+        unexportable.insert(format!(
+            "The minimum proposal fund {} is not a whole amount.",
+            gov_params.min_proposal_fund.to_string_native()
+        ));
+    }
+    let gov_params = GovernanceParams {
+        min_proposal_fund: min_proposal_fund.raw_amount().as_u64(),
+        max_proposal_code_size: gov_params.max_proposal_code_size,
+        min_proposal_voting_period: gov_params.min_proposal_voting_period,
+        max_proposal_period: gov_params.max_proposal_period,
+        max_proposal_content_size: gov_params.max_proposal_content_size,
+        min_proposal_grace_epochs: gov_params.min_proposal_grace_epochs,
+    };
+
+    Ok(Parameters {
+        parameters,
+        pos_params,
+        gov_params,
+        pgf_params,
+        eth_bridge_params: genesis.parameters.eth_bridge_params.clone(),
+    })
+}
+
+/// Read the PGF parameters, the stewards' reward distributions and the
+/// continuous fundings from the state, with the given treasury.
+fn export_pgf_params<S>(
+    storage: &S,
+    exported_accounts: &BTreeSet<Address>,
+    treasury: BTreeMap<Alias, Amount>,
+    unexportable: &mut BTreeSet<String>,
+) -> StorageResult<PgfParams<Unvalidated>>
+where
+    S: StorageRead,
+{
+    let pgf_params = namada::governance::pgf::storage::get_parameters(storage)?;
+    let mut stewards = BTreeSet::new();
+    let mut reward_distributions = BTreeMap::new();
+    for steward in namada::governance::pgf::storage::get_stewards(storage)? {
+        if !exported_accounts.contains(&steward.address) {
+            unexportable.insert(format!(
+                "The PGF steward {} has no established account in genesis.",
+                steward.address
+            ));
+            continue;
+        }
+        if steward != StewardDetail::base(steward.address.clone()) {
+            reward_distributions.insert(
+                steward.address.clone(),
+                steward.reward_distribution.into_iter().collect(),
+            );
+        }
+        stewards.insert(steward.address);
+    }
+    let fundings = namada::governance::pgf::storage::get_payments(storage)?
+        .into_iter()
+        .map(PgfFunding::from)
+        .collect();
+    Ok(PgfParams::new(
+        stewards,
+        pgf_params.pgf_inflation_rate,
+        pgf_params.stewards_inflation_rate,
+        PgfState {
+            treasury,
+            fundings,
+            reward_distributions,
+        },
+    ))
+}
@@ -9,18 +9,20 @@ use namada::core::address::Address;
 use namada::core::chain::ProposalBytes;
 use namada::core::dec::Dec;
 use namada::core::ethereum_structs;
+use namada::core::parameters::BaseFeeParams;
 use namada::core::token::{
     Amount, DenominatedAmount, Denomination, NATIVE_MAX_DECIMAL_PLACES,
 };
 use namada::eth_bridge::storage::parameters::{
     Contracts, Erc20WhitelistEntry, MinimumConfirmations,
 };
+use namada::governance::pgf::storage::steward::StewardDetail;
+use namada::governance::storage::proposal::{PGFTarget, StoragePgfFunding};
 use namada::token;
 use serde::{Deserialize, Serialize};
 
 use super::transactions::{self, Transactions};
 use super::utils::{read_toml, write_toml};
-use crate::config::genesis::chain::DeriveEstablishedAddress;
 use crate::config::genesis::transactions::{BondTx, SignedBondTx};
 use crate::config::genesis::GenesisAddress;
 use crate::wallet::Alias;
@@ -289,6 +291,9 @@ pub struct ChainParams<T: TemplateValidation> {
     pub fee_unshielding_descriptions_limit: u64,
     /// Map of the cost per gas unit for every token allowed for fee payment
     pub minimum_gas_price: T::GasMinimums,
+    /// Parameters of the adaptive base fee. `None` value disables the base
+    /// fee.
+    pub base_fee_params: Option<BaseFeeParams>,
 }
 
 impl ChainParams<Unvalidated> {
@@ -311,6 +316,7 @@ impl ChainParams<Unvalidated> {
             fee_unshielding_gas_limit,
             fee_unshielding_descriptions_limit,
             minimum_gas_price,
+            base_fee_params,
         } = self;
         let mut min_gas_prices = BTreeMap::default();
         for (token, amount) in minimum_gas_price.into_iter() {
@@ -356,6 +362,7 @@ impl ChainParams<Unvalidated> {
             fee_unshielding_gas_limit,
             fee_unshielding_descriptions_limit,
             minimum_gas_price: min_gas_prices,
+            base_fee_params,
         })
     }
 }
@@ -453,6 +460,11 @@ pub struct PgfParams<T: TemplateValidation> {
     pub pgf_inflation_rate: Dec,
     /// The pgf stewards inflation rate
     pub stewards_inflation_rate: Dec,
+    /// The state of PGF carried over from a chain's state by
+    /// `export-genesis`. Empty for a new chain.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "PgfState::is_empty")]
+    pub state: PgfState,
     #[serde(default)]
     #[serde(skip_serializing)]
     #[cfg(test)]
@@ -463,6 +475,90 @@ pub struct PgfParams<T: TemplateValidation> {
     valid: PhantomData<T>,
 }
 
+impl PgfParams<Unvalidated> {
+    /// Construct PGF parameters that are yet to be validated.
+    pub fn new(
+        stewards: BTreeSet<Address>,
+        pgf_inflation_rate: Dec,
+        stewards_inflation_rate: Dec,
+        state: PgfState,
+    ) -> Self {
+        Self {
+            stewards,
+            pgf_inflation_rate,
+            stewards_inflation_rate,
+            state,
+            valid: PhantomData,
+        }
+    }
+}
+
+/// The state of PGF exported from a chain's state, which has no
+/// representation in the PGF parameters of a new chain.
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Deserialize,
+    Serialize,
+    BorshDeserialize,
+    BorshSerialize,
+    PartialEq,
+    Eq,
+)]
+pub struct PgfState {
+    /// The raw amounts of the tokens held by PGF
+    #[serde(default)]
+    pub treasury: BTreeMap<Alias, token::Amount>,
+    /// The continuous PGF fundings
+    #[serde(default)]
+    pub fundings: Vec<PgfFunding>,
+    /// The reward distributions of the stewards that differ from the default
+    /// one, in which a steward gets all of its rewards
+    #[serde(default)]
+    pub reward_distributions: BTreeMap<Address, BTreeMap<Address, Dec>>,
+}
+
+impl PgfState {
+    /// Check if there's no PGF state to carry over
+    pub fn is_empty(&self) -> bool {
+        self.treasury.is_empty()
+            && self.fundings.is_empty()
+            && self.reward_distributions.is_empty()
+    }
+}
+
+/// A continuous PGF funding. The proposal id comes first, because TOML
+/// requires the values of a table to come before its sub-tables.
+#[derive(
+    Clone,
+    Debug,
+    Deserialize,
+    Serialize,
+    BorshDeserialize,
+    BorshSerialize,
+    PartialEq,
+    Eq,
+)]
+pub struct PgfFunding {
+    /// The id of the proposal that added the funding
+    pub id: u64,
+    /// The target and amount of the funding
+    pub detail: PGFTarget,
+}
+
+impl From<StoragePgfFunding> for PgfFunding {
+    fn from(StoragePgfFunding { detail, id }: StoragePgfFunding) -> Self {
+        Self { id, detail }
+    }
+}
+
+impl From<PgfFunding> for StoragePgfFunding {
+    fn from(PgfFunding { id, detail }: PgfFunding) -> Self {
+        Self { detail, id }
+    }
+}
+
 #[derive(
     Clone,
     Debug,
@@ -834,10 +930,7 @@ pub fn validate_parameters(
     for steward in &parameters.pgf_params.stewards {
         let mut found_steward = false;
         if let Some(accs) = &txs.established_account {
-            if accs.iter().any(|acct| {
-                let addr = acct.derive_address();
-                &addr == steward
-            }) {
+            if accs.iter().any(|acct| &acct.address() == steward) {
                 found_steward = true;
             }
         }
@@ -851,6 +944,40 @@ pub fn validate_parameters(
             );
         }
     }
+    // check the PGF state carried over from an exported chain
+    let pgf_state = &parameters.pgf_params.state;
+    for alias in pgf_state.treasury.keys() {
+        if !tokens.token.contains_key(alias) {
+            eprintln!(
+                "Token \"{alias}\" of the PGF treasury is not present in the \
+                 Tokens file."
+            );
+            is_valid = false;
+        }
+    }
+    for (steward, distribution) in &pgf_state.reward_distributions {
+        if !parameters.pgf_params.stewards.contains(steward) {
+            eprintln!(
+                "The PGF reward distribution of {steward} is not of a PGF \
+                 steward."
+            );
+            is_valid = false;
+        }
+        let detail = StewardDetail {
+            address: steward.clone(),
+            reward_distribution: distribution
+                .iter()
+                .map(|(address, share)| (address.clone(), *share))
+                .collect(),
+        };
+        if !detail.is_valid_reward_distribution() {
+            eprintln!(
+                "The PGF reward distribution of the steward {steward} is \
+                 invalid."
+            );
+            is_valid = false;
+        }
+    }
     let Parameters {
         parameters,
         pos_params,
@@ -871,6 +998,7 @@ pub fn validate_parameters(
                 stewards: pgf_params.stewards,
                 pgf_inflation_rate: pgf_params.pgf_inflation_rate,
                 stewards_inflation_rate: pgf_params.stewards_inflation_rate,
+                state: pgf_params.state,
                 valid: Default::default(),
             },
             eth_bridge_params,
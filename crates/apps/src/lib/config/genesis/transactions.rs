@@ -81,7 +81,9 @@ fn get_tx_args(use_device: bool) -> TxArgs {
         wrapper_fee_payer: None,
         fee_token: genesis_fee_token_address(),
         fee_unshield: None,
+        fee_granter: None,
         gas_limit: Default::default(),
+        gas_estimate_multiplier: None,
         expiration: None,
         disposable_signing_key: false,
         chain_id: None,
@@ -238,6 +240,7 @@ pub fn init_established_account(
     threshold: u8,
 ) -> (Address, UnsignedTransactions) {
     let unsigned_tx = EstablishedAccountTx {
+        address: None,
         vp,
         threshold,
         public_keys,
@@ -637,7 +640,9 @@ impl TxToSign for ValidatorAccountTx<SignedPk> {
         established_accounts
             .iter()
             .find_map(|account| {
-                if account.derive_established_address() == self.address.raw {
+                if account.address()
+                    == Address::Established(self.address.raw.clone())
+                {
                     Some((
                         account
                             .public_keys
@@ -677,6 +682,13 @@ impl TxToSign for ValidatorAccountTx<SignedPk> {
     Ord,
 )]
 pub struct EstablishedAccountTx {
+    /// The address of an account carried over from a chain's state by
+    /// `export-genesis`. The address of a new account is derived from the tx.
+    /// It is skipped by Borsh, so that it doesn't change derived addresses.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[borsh(skip)]
+    pub address: Option<StringEncoded<EstablishedAddress>>,
     pub vp: String,
     #[serde(default = "default_threshold")]
     pub threshold: u8,
@@ -692,6 +704,17 @@ impl DeriveEstablishedAddress for EstablishedAccountTx {
     const SALT: &'static str = "established-account-tx";
 }
 
+impl EstablishedAccountTx {
+    /// The address of the account, which is derived from the tx unless it is
+    /// given explicitly.
+    pub fn address(&self) -> Address {
+        match &self.address {
+            Some(address) => Address::Established(address.raw.clone()),
+            None => self.derive_address(),
+        }
+    }
+}
+
 #[derive(
     Clone,
     Debug,
@@ -906,7 +929,8 @@ where
             GenesisAddress::EstablishedAddress(owner) => established_accounts
                 .iter()
                 .find_map(|account| {
-                    if &account.derive_established_address() == owner {
+                    if account.address() == Address::Established(owner.clone())
+                    {
                         Some((
                             account
                                 .public_keys
@@ -1252,7 +1276,7 @@ pub fn validate_established_account(
 ) -> bool {
     let mut is_valid = true;
 
-    let established_address = tx.derive_address();
+    let established_address = tx.address();
     if tx.threshold == 0 {
         eprintln!("An established account may not have zero threshold");
         is_valid = false;
@@ -14,6 +14,7 @@ use directories::ProjectDirs;
 use namada::core::chain::ChainId;
 use namada::core::storage::BlockHeight;
 use namada::core::time::Rfc3339String;
+use namada::state::{DiffsPruning, EventsRetention};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -35,6 +36,8 @@ pub const FILENAME: &str = "config.toml";
 pub const COMETBFT_DIR: &str = "cometbft";
 /// Chain-specific Namada DB. Nested in chain dirs.
 pub const DB_DIR: &str = "db";
+/// Chain-specific state-sync snapshots. Nested in chain dirs.
+pub const SNAPSHOTS_DIR: &str = "snapshots";
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Config {
@@ -111,6 +114,20 @@ pub struct Shell {
     /// When set, will limit the how many block heights in the past can the
     /// storage be queried for reading values.
     pub storage_read_past_height_limit: Option<u64>,
+    /// The pruning mode of the subspace diffs, which are needed to read
+    /// values at past block heights. Defaults to keeping all of them.
+    #[serde(default)]
+    pub diffs_pruning: DiffsPruning,
+    /// The retention of the events emitted by the ledger, which are
+    /// persisted in the DB's event store. Defaults to keeping all of them.
+    #[serde(default)]
+    pub events_retention: EventsRetention,
+    /// When set, a state-sync snapshot of the DB is taken at every block
+    /// height that is a multiple of this interval.
+    pub snapshot_interval: Option<u64>,
+    /// How many of the most recent state-sync snapshots to keep on disk.
+    /// Older snapshots are deleted after a new one is taken.
+    pub snapshots_to_keep: Option<u64>,
     /// Use the [`Ledger::db_dir()`] method to read the value.
     db_dir: PathBuf,
     /// Use the [`Ledger::cometbft_dir()`] method to read the value.
@@ -143,6 +160,10 @@ impl Ledger {
                 tx_wasm_compilation_cache_bytes: None,
                 // Default corresponds to 1 hour of past blocks at 1 block/sec
                 storage_read_past_height_limit: Some(3600),
+                diffs_pruning: DiffsPruning::Archive,
+                events_retention: EventsRetention::Archive,
+                snapshot_interval: None,
+                snapshots_to_keep: Some(2),
                 db_dir: DB_DIR.into(),
                 cometbft_dir: COMETBFT_DIR.into(),
                 action_at_height: None,
@@ -181,6 +202,11 @@ impl Shell {
             .join(chain_id.as_str())
             .join(&self.cometbft_dir)
     }
+
+    /// Get the directory path to the state-sync snapshots
+    pub fn snapshots_dir(&self, chain_id: &ChainId) -> PathBuf {
+        self.base_dir.join(chain_id.as_str()).join(SNAPSHOTS_DIR)
+    }
 }
 
 #[derive(Error, Debug)]
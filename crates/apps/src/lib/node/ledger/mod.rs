@@ -148,16 +148,18 @@ impl Shell {
                 Ok(Response::CheckTx(self.mempool_validate(&tx.tx, r#type)))
             }
             Request::ListSnapshots => {
-                Ok(Response::ListSnapshots(Default::default()))
+                Ok(Response::ListSnapshots(self.list_snapshots()))
             }
-            Request::OfferSnapshot(_) => {
-                Ok(Response::OfferSnapshot(Default::default()))
+            Request::OfferSnapshot(req) => {
+                tracing::debug!("Request OfferSnapshot");
+                Ok(Response::OfferSnapshot(self.offer_snapshot(req)))
             }
-            Request::LoadSnapshotChunk(_) => {
-                Ok(Response::LoadSnapshotChunk(Default::default()))
+            Request::LoadSnapshotChunk(req) => {
+                Ok(Response::LoadSnapshotChunk(self.load_snapshot_chunk(req)))
             }
-            Request::ApplySnapshotChunk(_) => {
-                Ok(Response::ApplySnapshotChunk(Default::default()))
+            Request::ApplySnapshotChunk(req) => {
+                tracing::debug!("Request ApplySnapshotChunk");
+                Ok(Response::ApplySnapshotChunk(self.apply_snapshot_chunk(req)))
             }
         }
     }
@@ -213,20 +215,234 @@ pub fn dump_db(
         block_height,
         out_file_path,
         historic,
+        key_prefixes,
+        column_families,
+        from_height,
+        format,
     }: args::LedgerDumpDb,
-) {
+) -> eyre::Result<()> {
     use namada::state::DB;
 
     let chain_id = config.chain_id;
     let db_path = config.shell.db_dir(&chain_id);
 
+    let column_families = if column_families.is_empty() {
+        storage::dump::DumpDbColumnFamily::defaults(historic)
+    } else {
+        column_families
+    };
+    let options = storage::dump::DumpDbOptions {
+        height: block_height,
+        diff_from_height: from_height,
+        key_prefixes,
+        column_families,
+        format,
+    };
+
+    let db = storage::PersistentDB::open(db_path, None);
+    if let Some(from_height) = from_height {
+        let height = match block_height {
+            Some(height) => height,
+            None => {
+                db.read_last_block()?
+                    .ok_or_else(|| {
+                        eyre::eyre!("No committed block found in the DB")
+                    })?
+                    .height
+            }
+        };
+        if from_height >= height {
+            eyre::bail!(
+                "The height to diff from {from_height} must be lower than the \
+                 dumped height {height}"
+            );
+        }
+    }
+    db.dump_block(out_file_path, options);
+    Ok(())
+}
+
+/// Export the state at a block height as genesis templates
+pub fn export_genesis(
+    config: config::Ledger,
+    args::LedgerExportGenesis {
+        block_height,
+        output_folder,
+    }: args::LedgerExportGenesis,
+) -> eyre::Result<()> {
+    use crate::config::genesis::chain::Finalized;
+    use crate::config::genesis::export::{self, HistoricState};
+
+    let genesis = Finalized::read_toml_files(&config.chain_dir())?;
+    let db_path = config.shell.db_dir(&config.chain_id);
     let db = storage::PersistentDB::open(db_path, None);
-    db.dump_block(out_file_path, historic, block_height);
+    let state = HistoricState::load(
+        &db,
+        config.chain_id,
+        block_height,
+        genesis.get_native_token().clone(),
+    )?;
+    let exported = export::export_genesis(&state, &genesis)?;
+    exported.write_toml_files(&output_folder)?;
+
+    println!(
+        "Exported the genesis templates to {}. The txs in {} have to be \
+         signed and added to the transactions before the templates can be \
+         used.",
+        output_folder.to_string_lossy(),
+        export::UNSIGNED_TRANSACTIONS_FILE_NAME,
+    );
+    Ok(())
+}
+
+/// Check the integrity of Namada ledger node's DB
+pub fn check_db(
+    config: config::Ledger,
+    args::LedgerCheckDb { max_reported_keys }: args::LedgerCheckDb,
+) -> eyre::Result<()> {
+    use namada::state::{check_db_integrity, DB};
+
+    let chain_id = config.chain_id;
+    let db_path = config.shell.db_dir(&chain_id);
+    let db = storage::PersistentDB::open(db_path, None);
+    let report = check_db_integrity::<_, storage::PersistentStorageHasher>(
+        &db,
+        shell::is_merklized_storage_key,
+        max_reported_keys,
+    )?;
+
+    println!(
+        "Checked the DB at the last committed height {}",
+        report.height
+    );
+    println!(
+        "Merkle root: stored {}, rebuilt from the subspace {}",
+        report.stored_root, report.rebuilt_root
+    );
+    for subtree in &report.subtrees {
+        match &subtree.stored_root {
+            Ok(stored_root) if subtree.is_ok() => {
+                println!("Sub-tree {}: OK ({stored_root})", subtree.store_type)
+            }
+            Ok(stored_root) => {
+                println!(
+                    "Sub-tree {}: stored root {stored_root}, rebuilt root {}",
+                    subtree.store_type, subtree.rebuilt_root
+                );
+                if subtree.divergent_keys.is_empty() {
+                    println!(
+                        "  The stored sub-tree contains leaves that are not \
+                         in the subspace"
+                    );
+                }
+                for key in &subtree.divergent_keys {
+                    println!("  Divergent key: {key}");
+                }
+            }
+            Err(err) => println!(
+                "Sub-tree {}: failed to restore the stored sub-tree: {err}",
+                subtree.store_type
+            ),
+        }
+    }
+    for key in &report.inconsistent_diffs {
+        println!(
+            "The diff at the last height doesn't match the subspace: {key}"
+        );
+    }
+    for key in &report.invalid_keys {
+        println!("Invalid storage key: {key}");
+    }
+
+    if report.is_ok() {
+        println!("No inconsistency found");
+        Ok(())
+    } else {
+        Err(eyre::eyre!("The DB is inconsistent"))
+    }
 }
 
-/// Roll Namada state back to the previous height
-pub fn rollback(config: config::Ledger) -> Result<(), shell::Error> {
-    shell::rollback(config)
+/// Apply the pending storage migrations to a copy of Namada ledger node's DB
+/// and report what they change
+pub fn dry_run_migrations(
+    config: config::Ledger,
+    args: args::LedgerDryRunMigrations,
+) -> eyre::Result<()> {
+    use namada::state::migrations::{self, StorageChange};
+    use namada::state::DB;
+
+    use crate::config::genesis::chain::Finalized;
+
+    let genesis = Finalized::read_toml_files(&config.chain_dir())?;
+    let db_path = config.shell.db_dir(&config.chain_id);
+
+    // Work on a copy, so that the node's DB is never modified
+    let copy_dir = tempfile::tempdir()?;
+    let copy_path = copy_dir.path().join("db");
+    storage::PersistentDB::open(db_path, None).checkpoint(&copy_path)?;
+    let mut state = storage::PersistentState::open(
+        copy_path,
+        None,
+        config.chain_id,
+        genesis.get_native_token().clone(),
+        config.shell.storage_read_past_height_limit,
+        shell::is_merklized_storage_key,
+    );
+
+    let version = migrations::read_storage_version(&state)?;
+    println!(
+        "Storage version at the last committed height {}: {version}",
+        state.in_mem().get_last_block_height()
+    );
+    let all_migrations = shell::migrations::storage_migrations();
+    let pending = migrations::pending_migrations(&state, &all_migrations)?;
+    if pending.is_empty() {
+        println!("No pending storage migrations");
+        return Ok(());
+    }
+
+    // The changes are only written to the write log of the copy, which is
+    // never committed
+    let applied =
+        migrations::apply_migrations(&mut state, pending.iter().copied())?;
+    for (migration, (_version, changes)) in pending.iter().zip(&applied) {
+        println!(
+            "Migration to version {} at height {}: {}",
+            migration.version, migration.height, migration.description
+        );
+        println!("  {} changed keys", changes.len());
+        for StorageChange { key, old, new } in
+            changes.iter().take(args.max_reported_keys)
+        {
+            match (old, new) {
+                (None, Some(new)) => {
+                    println!("  Write {key} ({} bytes)", new.len())
+                }
+                (Some(old), Some(new)) => println!(
+                    "  Update {key} ({} -> {} bytes)",
+                    old.len(),
+                    new.len()
+                ),
+                (_, None) => println!("  Delete {key}"),
+            }
+        }
+        if changes.len() > args.max_reported_keys {
+            println!(
+                "  ... and {} more",
+                changes.len() - args.max_reported_keys
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Roll Namada state back to the given height, or to the previous height if
+/// none is given
+pub fn rollback(
+    config: config::Ledger,
+    args::LedgerRollBack { block_height }: args::LedgerRollBack,
+) -> Result<(), shell::Error> {
+    shell::rollback(config, block_height)
 }
 
 /// Runs and monitors a few concurrent tasks.
@@ -91,6 +91,10 @@ where
                 .expect("Failed tx hashes finalization")
         }
 
+        // Apply the storage migrations scheduled up to this height before any
+        // sub-system reads the storage
+        self.apply_storage_migrations(height)?;
+
         let emit_events = &mut response.events;
         // Get the actual votes from cometBFT in the preferred format
         let votes = pos_votes_from_abci(&self.state, &req.votes);
@@ -141,9 +145,22 @@ where
                 )
         };
 
+        // Execute the txs scheduled at this height or epoch before the txs of
+        // the block
+        let scheduled_gas_used = scheduler::finalize_block(
+            self,
+            &mut response.events,
+            height,
+            current_epoch,
+            new_epoch,
+            &native_block_proposer_address,
+        )?;
+
         // Tracks the accepted transactions
         self.state.in_mem_mut().block.results = BlockResults::default();
         let mut changed_keys = BTreeSet::new();
+        // The gas consumed by the scheduled txs and the txs of the block
+        let mut block_gas_used: u64 = scheduled_gas_used;
         for (tx_index, processed_tx) in req.txs.iter().enumerate() {
             let tx = if let Ok(tx) = Tx::try_from(processed_tx.tx.as_ref()) {
                 tx
@@ -248,13 +265,25 @@ where
 
                     match inner {
                         DecryptedTx::Decrypted => {
-                            if let Some(code_sec) = tx
-                                .get_section(tx.code_sechash())
-                                .and_then(|x| Section::code_sec(x.as_ref()))
-                            {
-                                stats.increment_tx_type(
-                                    code_sec.code.hash().to_string(),
-                                );
+                            let code_sechashes = if tx.is_batch() {
+                                tx.batch()
+                                    .iter()
+                                    .map(|inner| inner.code_hash)
+                                    .collect()
+                            } else {
+                                vec![*tx.code_sechash()]
+                            };
+                            for code_sechash in code_sechashes {
+                                if let Some(code_sec) = tx
+                                    .get_section(&code_sechash)
+                                    .and_then(|x| {
+                                        Section::code_sec(x.as_ref())
+                                    })
+                                {
+                                    stats.increment_tx_type(
+                                        code_sec.code.hash().to_string(),
+                                    );
+                                }
                             }
                         }
                         DecryptedTx::Undecryptable => {
@@ -373,6 +402,9 @@ where
                 })
                 .map_err(Error::TxApply);
             let tx_gas_meter = tx_gas_meter.into_inner();
+            block_gas_used = block_gas_used.saturating_add(
+                tx_gas_meter.get_tx_consumed_gas().get_whole_gas_units(),
+            );
             match tx_result {
                 Ok(result) => {
                     if result.is_accepted() {
@@ -445,6 +477,16 @@ where
                                         .map(Event::from),
                                 ),
                         );
+                        // PoS events of the validator changes made by the tx
+                        response.events.extend(
+                            proof_of_stake::event::tx_events(
+                                &self.state,
+                                &result.changed_keys,
+                                current_epoch,
+                            )?
+                            .into_iter()
+                            .map(Event::from),
+                        );
                     } else {
                         tracing::trace!(
                             "some VPs rejected transaction {} storage \
@@ -559,6 +601,9 @@ where
             self.state.write(&anchor_key, ())?;
         }
 
+        // Adjust the base fee for the next block to the gas used by this one
+        namada::parameters::update_base_fee(&mut self.state, block_gas_used)?;
+
         if update_for_tendermint {
             self.update_epoch(&mut response);
             // send the latest oracle configs. These may have changed due to
@@ -571,7 +616,9 @@ where
             native_block_proposer_address,
         )?;
 
-        self.event_log_mut().log_events(response.events.clone());
+        // The events are persisted in the event store when the block is
+        // committed
+        self.state.in_mem_mut().block_events = response.events.clone();
         tracing::debug!("End finalize_block {height} of epoch {current_epoch}");
 
         Ok(response)
@@ -663,7 +710,10 @@ where
     // both the hashes.
     fn commit_inner_tx_hash(&mut self, wrapper_tx: Tx) {
         self.state
-            .write_tx_hash(wrapper_tx.raw_header_hash())
+            .write_tx_hash(
+                wrapper_tx.raw_header_hash(),
+                wrapper_tx.header.effective_expiration(),
+            )
             .expect("Error while writing tx hash to storage");
 
         self.state
@@ -760,7 +810,7 @@ mod test_finalize_block {
     use namada::ledger::gas::VpGasMeter;
     use namada::ledger::native_vp::parameters::ParametersVp;
     use namada::ledger::native_vp::NativeVp;
-    use namada::ledger::parameters::EpochDuration;
+    use namada::ledger::parameters::{BaseFeeParams, EpochDuration};
     use namada::proof_of_stake::storage::{
         enqueued_slashes_handle, get_num_consensus_validators,
         read_consensus_validator_set_addresses_with_stake, read_total_stake,
@@ -3296,6 +3346,159 @@ mod test_finalize_block {
         )
     }
 
+    // Test that the base fee portion of the fees is burned, that only the tip
+    // is credited to the block proposer and that the base fee is updated from
+    // the gas used by the block
+    #[test]
+    fn test_base_fee_payment() {
+        let (mut shell, _, _, _) = setup();
+        let native_token = shell.state.in_mem().native_token.clone();
+
+        // Enable the base fee, with a target that a single tx exceeds
+        let base_fee_params = BaseFeeParams {
+            target_block_gas_fraction: Dec::new(1, 8).unwrap(),
+            max_change_rate: Dec::new(125, 3).unwrap(),
+        };
+        shell
+            .state
+            .write(
+                &namada::parameters::storage::get_base_fee_params_key(),
+                base_fee_params,
+            )
+            .unwrap();
+        let base_fee = Amount::from(2);
+        shell
+            .state
+            .write(
+                &namada::parameters::storage::get_base_fee_key(),
+                BTreeMap::from([(native_token.clone(), base_fee)]),
+            )
+            .unwrap();
+
+        let validator = shell.mode.get_validator_address().unwrap().to_owned();
+        let pos_params =
+            namada_proof_of_stake::storage::read_pos_params(&shell.state)
+                .unwrap();
+        let consensus_key =
+            namada_proof_of_stake::storage::validator_consensus_key_handle(
+                &validator,
+            )
+            .get(&shell.state, Epoch::default(), &pos_params)
+            .unwrap()
+            .unwrap();
+        let proposer_address = HEXUPPER
+            .decode(consensus_key.tm_raw_hash().as_bytes())
+            .unwrap();
+        let proposer_balance = namada::token::read_balance(
+            &shell.state,
+            &native_token,
+            &validator,
+        )
+        .unwrap();
+        let total_supply =
+            namada::token::read_total_supply(&shell.state, &native_token)
+                .unwrap();
+
+        let gas_limit = 5_000_000;
+        let mut wrapper =
+            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                Fee {
+                    amount_per_gas_unit: DenominatedAmount::native(3.into()),
+                    token: native_token.clone(),
+                },
+                crate::wallet::defaults::albert_keypair().ref_to(),
+                Epoch(0),
+                gas_limit.into(),
+                None,
+            ))));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.set_code(Code::new(TestWasms::TxNoOp.read_bytes(), None));
+        wrapper.set_data(Data::new(
+            "Enxrypted transaction data".as_bytes().to_owned(),
+        ));
+        wrapper.add_section(Section::Signature(Signature::new(
+            wrapper.sechashes(),
+            [(0, crate::wallet::defaults::albert_keypair())]
+                .into_iter()
+                .collect(),
+            None,
+        )));
+        let fee_payer = wrapper.header().wrapper().unwrap().fee_payer();
+        let signer_balance = namada::token::read_balance(
+            &shell.state,
+            &native_token,
+            &fee_payer,
+        )
+        .unwrap();
+
+        let processed_tx = ProcessedTx {
+            tx: wrapper.to_bytes().into(),
+            result: TxResult {
+                code: ResultCode::Ok.into(),
+                info: "".into(),
+            },
+        };
+        let event = &shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx],
+                proposer_address,
+                ..Default::default()
+            })
+            .expect("Test failed")[0];
+        let code = event.attributes.get("code").expect("Test failed").as_str();
+        assert_eq!(code, String::from(ResultCode::Ok).as_str());
+        // Only the gas consumed by the wrapper counts as used by the block
+        let gas_used: u64 = event["gas_used"].parse().unwrap();
+        assert!(gas_used > 0 && gas_used < gas_limit);
+
+        let fee_amount = Amount::from(3 * gas_limit);
+        let base_fee_amount = Amount::from(2 * gas_limit);
+        let tip = Amount::from(gas_limit);
+        assert_eq!(
+            namada::token::read_balance(
+                &shell.state,
+                &native_token,
+                &fee_payer
+            )
+            .unwrap(),
+            signer_balance.checked_sub(fee_amount).unwrap()
+        );
+        assert_eq!(
+            namada::token::read_balance(
+                &shell.state,
+                &native_token,
+                &validator
+            )
+            .unwrap(),
+            proposer_balance.checked_add(tip).unwrap()
+        );
+        assert_eq!(
+            namada::token::read_total_supply(&shell.state, &native_token)
+                .unwrap(),
+            total_supply.checked_sub(base_fee_amount).unwrap()
+        );
+
+        // The block used more than the target gas, so the base fee increased
+        let minimum_gas_price =
+            namada::parameters::read_gas_cost(&shell.state, &native_token)
+                .unwrap()
+                .unwrap();
+        let max_block_gas =
+            namada::parameters::get_max_block_gas(&shell.state).unwrap();
+        let next_base_fee = base_fee_params.next_base_fee(
+            base_fee,
+            minimum_gas_price,
+            max_block_gas,
+            gas_used,
+        );
+        assert!(next_base_fee > base_fee);
+        assert_eq!(
+            namada::parameters::read_base_fee(&shell.state, &native_token)
+                .unwrap(),
+            Some(next_base_fee)
+        );
+    }
+
     #[test]
     fn test_ledger_slashing() -> namada::state::StorageResult<()> {
         let num_validators = 7_u64;
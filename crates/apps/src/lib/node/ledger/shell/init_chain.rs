@@ -8,6 +8,8 @@ use masp_proofs::bls12_381;
 use namada::account::protocol_pk_key;
 use namada::core::hash::Hash as CodeHash;
 use namada::core::time::{TimeZone, Utc};
+use namada::governance::pgf::storage::keys as pgf_storage;
+use namada::governance::pgf::storage::steward::StewardDetail;
 use namada::ledger::parameters::Parameters;
 use namada::ledger::{ibc, pos};
 use namada::proof_of_stake::BecomeValidator;
@@ -22,7 +24,7 @@ use crate::config::genesis::chain::{
     FinalizedEstablishedAccountTx, FinalizedTokenConfig,
     FinalizedValidatorAccountTx,
 };
-use crate::config::genesis::templates::{TokenBalances, TokenConfig};
+use crate::config::genesis::templates::{PgfState, TokenBalances, TokenConfig};
 use crate::config::genesis::transactions::{
     BondTx, EstablishedAccountTx, Signed as SignedTx, ValidatorAccountTx,
 };
@@ -241,6 +243,7 @@ where
         let mut vp_cache: HashMap<String, Vec<u8>> = HashMap::default();
         self.init_token_accounts(&genesis);
         self.init_token_balances(&genesis);
+        self.init_pgf_state(&genesis);
         self.apply_genesis_txs_established_account(&genesis, &mut vp_cache);
         self.apply_genesis_txs_validator_account(
             &genesis,
@@ -508,6 +511,69 @@ where
         self.proceed_with(())
     }
 
+    /// Init the PGF state carried over from an exported chain. Depends on the
+    /// PGF parameters and the token balances being initialized.
+    fn init_pgf_state(
+        &mut self,
+        genesis: &genesis::chain::Finalized,
+    ) -> ControlFlow<()> {
+        let PgfState {
+            treasury,
+            fundings,
+            reward_distributions,
+        } = &genesis.parameters.pgf_state;
+        for (token_alias, amount) in treasury {
+            let Some(token_address) = self
+                .validate(
+                    genesis
+                        .tokens
+                        .token
+                        .get(token_alias)
+                        .ok_or_else(|| {
+                            Panic::MissingTokenConfig(token_alias.to_string())
+                        })
+                        .map(|conf| &conf.address),
+                )
+                .or_placeholder(None)?
+            else {
+                continue;
+            };
+            tracing::info!(
+                "Crediting {amount} raw {token_alias} tokens to the PGF \
+                 treasury"
+            );
+            credit_tokens(
+                &mut self.state,
+                token_address,
+                &namada::governance::pgf::ADDRESS,
+                *amount,
+            )
+            .expect("Couldn't credit the PGF treasury");
+        }
+        for funding in fundings {
+            pgf_storage::fundings_handle()
+                .insert(
+                    &mut self.state,
+                    funding.detail.target(),
+                    funding.clone().into(),
+                )
+                .expect("Couldn't initialize a continuous PGF funding");
+        }
+        for (steward, distribution) in reward_distributions {
+            let detail = StewardDetail {
+                address: steward.clone(),
+                reward_distribution: distribution
+                    .iter()
+                    .map(|(address, share)| (address.clone(), *share))
+                    .collect(),
+            };
+            pgf_storage::stewards_handle()
+                .insert(&mut self.state, steward.clone(), detail)
+                .expect("Couldn't initialize a PGF reward distribution");
+        }
+        self.proceed_with(())
+    }
+
     /// Apply genesis txs to initialize established accounts
     fn apply_genesis_txs_established_account(
         &mut self,
@@ -519,6 +585,7 @@ where
                 address,
                 tx:
                     EstablishedAccountTx {
+                        address: _,
                         vp,
                         threshold,
                         public_keys,
@@ -1187,4 +1254,258 @@ mod test {
         )];
         assert_eq!(expected, initializer.warnings);
     }
+
+    /// Test that a chain re-initialized from the genesis templates exported
+    /// from a chain's state at some height has the same balances.
+    #[test]
+    fn test_init_chain_from_exported_genesis() {
+        use namada::core::address::{self, InternalAddress};
+        use namada::core::chain::ChainIdPrefix;
+        use namada::core::dec::Dec;
+        use namada::core::storage::KeySeg;
+        use namada::governance::storage::proposal::{
+            PGFInternalTarget, PGFTarget, StoragePgfFunding,
+        };
+        use namada::state::StorageRead;
+        use namada::token::storage_key::{
+            balance_key, is_any_token_balance_key,
+        };
+
+        use crate::config::genesis::chain::finalize;
+        use crate::config::genesis::export::{
+            export_genesis, HistoricState, UNSIGNED_TRANSACTIONS_FILE_NAME,
+        };
+        use crate::config::genesis::templates;
+        use crate::config::genesis::utils::read_toml;
+
+        let read_balances =
+            |shell: &TestShell| -> BTreeMap<Key, token::Amount> {
+                let prefix = Key::from(address::MULTITOKEN.to_db_key());
+                namada::state::iter_prefix(&shell.state, &prefix)
+                    .unwrap()
+                    .filter_map(|result| {
+                        let (key, amount): (Key, token::Amount) =
+                            result.ok()?;
+                        let [_token, owner] = is_any_token_balance_key(&key)?;
+                        // Only the balances of PoS and PGF can be exported
+                        // from internal addresses
+                        let is_exported =
+                            !matches!(owner, Address::Internal(_))
+                                || *owner
+                                    == Address::Internal(InternalAddress::PoS)
+                                || *owner
+                                    == Address::Internal(InternalAddress::Pgf);
+                        (is_exported && !amount.is_zero())
+                            .then_some((key, amount))
+                    })
+                    .collect()
+            };
+        // The state of the accounts, PGF and governance that must be carried
+        // over by the export
+        let read_exported_state = |shell: &TestShell, owners: &[Address]| {
+            let accounts: Vec<_> = owners
+                .iter()
+                .map(|owner| {
+                    (
+                        namada::account::public_keys(&shell.state, owner)
+                            .unwrap(),
+                        namada::account::threshold(&shell.state, owner)
+                            .unwrap(),
+                        shell
+                            .state
+                            .read_bytes(&Key::validity_predicate(owner))
+                            .unwrap(),
+                    )
+                })
+                .collect();
+            (
+                accounts,
+                namada::governance::pgf::storage::get_stewards(&shell.state)
+                    .unwrap(),
+                namada::governance::pgf::storage::get_payments(&shell.state)
+                    .unwrap(),
+                namada::governance::storage::get_parameters(&shell.state)
+                    .unwrap(),
+            )
+        };
+
+        let (mut shell, _x, _y, _z) = test_utils::setup();
+        let genesis = genesis::make_dev_genesis(1, &shell.base_dir);
+        let native_token = genesis.get_native_token().clone();
+
+        // Change the state that is not in the genesis templates before the
+        // exported height: an account initialized after genesis, the keys of
+        // a genesis account, a steward's reward distribution, a continuous
+        // funding and the PGF treasury
+        let albert = defaults::albert_address();
+        let bertha = defaults::bertha_address();
+        let new_account = address::testing::established_address_1();
+        let vp_user_hash = shell
+            .state
+            .read_bytes(&Key::wasm_hash(
+                &genesis.vps.wasm.get("vp_user").unwrap().filename,
+            ))
+            .unwrap()
+            .expect("The user VP must be stored");
+        shell
+            .state
+            .write_bytes(&Key::validity_predicate(&new_account), vp_user_hash)
+            .unwrap();
+        namada::account::init_account_storage(
+            &mut shell.state,
+            &new_account,
+            &[defaults::christel_keypair().ref_to()],
+            1,
+        )
+        .unwrap();
+        credit_tokens(
+            &mut shell.state,
+            &native_token,
+            &new_account,
+            token::Amount::native_whole(10),
+        )
+        .unwrap();
+        namada::account::init_account_storage(
+            &mut shell.state,
+            &albert,
+            &[
+                defaults::albert_keypair().ref_to(),
+                defaults::bertha_keypair().ref_to(),
+            ],
+            2,
+        )
+        .unwrap();
+        pgf_storage::stewards_handle()
+            .insert(
+                &mut shell.state,
+                albert.clone(),
+                StewardDetail {
+                    address: albert.clone(),
+                    reward_distribution: HashMap::from_iter([
+                        (albert.clone(), Dec::from_str("0.5").unwrap()),
+                        (bertha.clone(), Dec::from_str("0.5").unwrap()),
+                    ]),
+                },
+            )
+            .unwrap();
+        let funding = StoragePgfFunding::new(
+            PGFTarget::Internal(PGFInternalTarget {
+                target: new_account.clone(),
+                amount: token::Amount::native_whole(1),
+            }),
+            0,
+        );
+        pgf_storage::fundings_handle()
+            .insert(&mut shell.state, funding.detail.target(), funding)
+            .unwrap();
+        credit_tokens(
+            &mut shell.state,
+            &native_token,
+            &namada::governance::pgf::ADDRESS,
+            token::Amount::native_whole(100),
+        )
+        .unwrap();
+        shell.state.commit_tx();
+        shell.finalize_and_commit(None);
+
+        let height = shell.state.in_mem().get_last_block_height();
+        let balances = read_balances(&shell);
+        assert!(!balances.is_empty());
+        let owners = [albert.clone(), new_account];
+        let exported_state = read_exported_state(&shell, &owners);
+
+        // Change a balance after the exported height
+        let albert_balance_key = balance_key(&native_token, &albert);
+        shell
+            .state
+            .db_write(
+                &albert_balance_key,
+                token::Amount::native_whole(1).serialize_to_vec(),
+            )
+            .unwrap();
+        shell.finalize_and_commit(None);
+
+        let state = HistoricState::load(
+            shell.state.db(),
+            shell.state.in_mem().chain_id.clone(),
+            Some(height),
+            native_token,
+        )
+        .unwrap();
+        let exported = export_genesis(&state, &genesis).unwrap();
+        let templates_dir = tempfile::tempdir().unwrap();
+        exported.write_toml_files(templates_dir.path()).unwrap();
+
+        // Re-initialize a chain from the exported templates, with the bonds
+        // that would have to be signed by their sources
+        let templates = templates::load_and_validate(templates_dir.path())
+            .expect("The exported templates must be valid");
+        let mut exported_genesis = finalize(
+            templates,
+            ChainIdPrefix::from_str("test").unwrap(),
+            DateTimeUtc::now(),
+            std::time::Duration::from_secs(30).into(),
+        );
+        let unsigned_txs: transactions::UnsignedTransactions = read_toml(
+            &templates_dir.path().join(UNSIGNED_TRANSACTIONS_FILE_NAME),
+            "Unsigned transactions",
+        )
+        .unwrap();
+        // The validators are unchanged, so their signed txs are reused
+        assert!(unsigned_txs.validator_account.is_none());
+        let bonds = unsigned_txs.bond.expect("The bonds must be exported");
+        exported_genesis.transactions.bond = Some(
+            bonds
+                .into_iter()
+                .map(|bond| bond.denominate().unwrap())
+                .collect(),
+        );
+
+        let (mut reinit, _x, _y, _z) = TestShell::new_at_height(0);
+        reinit.state.in_mem_mut().native_token =
+            exported_genesis.get_native_token().clone();
+        let chain_id = reinit.state.in_mem().chain_id.to_string();
+        let mut initializer = InitChainValidation::new(&mut reinit, false);
+        initializer.run_validation(chain_id, exported_genesis);
+        assert!(initializer.errors.is_empty());
+        assert!(initializer.panics.is_empty());
+        assert!(initializer.warnings.is_empty());
+
+        assert_eq!(read_balances(&reinit), balances);
+        assert_eq!(read_exported_state(&reinit, &owners), exported_state);
+    }
+
+    /// Test that the export fails on the state that cannot be carried over
+    /// by the genesis templates, instead of dropping it.
+    #[test]
+    fn test_export_genesis_fails_on_unexportable_state() {
+        use crate::config::genesis::export::{export_genesis, HistoricState};
+
+        let (mut shell, _x, _y, _z) = test_utils::setup();
+        let genesis = genesis::make_dev_genesis(1, &shell.base_dir);
+
+        // An account whose VP is not a genesis VP
+        let account = namada::core::address::testing::established_address_1();
+        shell
+            .state
+            .write_bytes(&Key::validity_predicate(&account), [0_u8; 32])
+            .unwrap();
+        shell.state.commit_tx();
+        shell.finalize_and_commit(None);
+
+        let state = HistoricState::load(
+            shell.state.db(),
+            shell.state.in_mem().chain_id.clone(),
+            None,
+            genesis.get_native_token().clone(),
+        )
+        .unwrap();
+        let err = export_genesis(&state, &genesis).unwrap_err();
+        assert!(
+            err.to_string().contains(&format!(
+                "The VP of the account {account} is not a genesis VP."
+            )),
+            "{err}"
+        );
+    }
 }
@@ -0,0 +1,63 @@
+//! Storage migrations carried by this node binary. A protocol change that
+//! alters the storage layout adds a migration to [`storage_migrations`],
+//! targeting the next storage version at an agreed upon block height.
+
+use namada::state::migrations::{self, StorageMigration};
+use namada::state::{BlockHeight, DBIter, StorageHasher, DB};
+
+use super::{Result, Shell};
+
+/// The storage migrations known to this binary, in version order
+pub fn storage_migrations() -> Vec<StorageMigration> {
+    vec![]
+}
+
+impl<D, H> Shell<D, H>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    /// Refuse to start the node if a storage migration was scheduled at an
+    /// already committed height, but it hasn't been applied. Applying it
+    /// late would diverge from the nodes that applied it on time.
+    pub(super) fn check_missed_storage_migrations(&self) {
+        let last_height = self.state.in_mem().get_last_block_height();
+        let missed = migrations::missed_migrations(
+            &self.state,
+            &self.storage_migrations,
+            last_height,
+        )
+        .expect("Must be able to read the storage version");
+        if let Some(migration) = missed.first() {
+            panic!(
+                "The storage migration to version {} scheduled at height {} \
+                 has not been applied, but the last committed height is \
+                 {last_height}. The node has to be restarted from a state \
+                 before the scheduled height.",
+                migration.version, migration.height
+            );
+        }
+    }
+
+    /// Apply the pending storage migrations scheduled at or before the given
+    /// height, in version order. The changes are written to the block's
+    /// write log, so they are merklized when the block is committed.
+    pub(super) fn apply_storage_migrations(
+        &mut self,
+        height: BlockHeight,
+    ) -> Result<()> {
+        let applied = migrations::apply_scheduled_migrations(
+            &mut self.state,
+            &self.storage_migrations,
+            height,
+        )?;
+        for (version, changes) in applied {
+            tracing::info!(
+                "Applied storage migration to version {version} at height \
+                 {height} with {} changed keys",
+                changes.len()
+            );
+        }
+        Ok(())
+    }
+}
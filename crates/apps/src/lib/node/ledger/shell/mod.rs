@@ -9,6 +9,7 @@ pub mod block_alloc;
 mod finalize_block;
 mod governance;
 mod init_chain;
+pub mod migrations;
 pub use init_chain::InitChainValidation;
 use namada_sdk::state::StateRead;
 use namada_sdk::tx::data::GasLimit;
@@ -16,6 +17,8 @@ pub mod prepare_proposal;
 use namada::state::State;
 pub mod process_proposal;
 pub(super) mod queries;
+mod scheduler;
+pub mod snapshots;
 mod stats;
 #[cfg(any(test, feature = "testing"))]
 #[allow(dead_code)]
@@ -42,7 +45,6 @@ use namada::core::{address, hints};
 use namada::ethereum_bridge::protocol::validation::bridge_pool_roots::validate_bp_roots_vext;
 use namada::ethereum_bridge::protocol::validation::ethereum_events::validate_eth_events_vext;
 use namada::ethereum_bridge::protocol::validation::validator_set_update::validate_valset_upd_vext;
-use namada::ledger::events::log::EventLog;
 use namada::ledger::events::Event;
 use namada::ledger::gas::{Gas, TxGasMeter};
 use namada::ledger::pos::namada_proof_of_stake::types::{
@@ -55,6 +57,7 @@ use namada::ledger::protocol::{
 use namada::ledger::{parameters, protocol};
 use namada::parameters::validate_tx_bytes;
 use namada::proof_of_stake::storage::read_pos_params;
+use namada::state::migrations::StorageMigration;
 use namada::state::tx_queue::{ExpiredTx, TxInQueue};
 use namada::state::{
     DBIter, FullAccessState, Sha256Hasher, StorageHasher, StorageRead,
@@ -123,6 +126,8 @@ pub enum Error {
     Storage(#[from] namada::state::StorageError),
     #[error("Transaction replay attempt: {0}")]
     ReplayAttempt(String),
+    #[error("State-sync snapshot error: {0}")]
+    Snapshot(String),
 }
 
 impl From<Error> for TxResult {
@@ -148,19 +153,47 @@ pub fn reset(config: config::Ledger) -> Result<()> {
     Ok(())
 }
 
-pub fn rollback(config: config::Ledger) -> Result<()> {
-    // Rollback Tendermint state
-    tracing::info!("Rollback Tendermint state");
-    let tendermint_block_height =
-        tendermint_node::rollback(config.cometbft_dir())
-            .map_err(Error::Tendermint)?;
-
-    // Rollback Namada state
+/// Roll back the CometBFT and Namada states to the given height, or by a
+/// single block if no height is given
+pub fn rollback(
+    config: config::Ledger,
+    height: Option<BlockHeight>,
+) -> Result<()> {
     let db_path = config.shell.db_dir(&config.chain_id);
     let mut db = storage::PersistentDB::open(db_path, None);
+    let last_height = db
+        .read_last_block()
+        .map_err(|e| Error::Storage(namada::state::StorageError::new(e)))?
+        .map(|last_block| last_block.height)
+        .unwrap_or_default();
+    let target_height = height.unwrap_or_else(|| last_height.prev_height());
+    // Check that the Namada state can be rolled back before touching the
+    // Tendermint state
+    db.check_rollback_height(target_height, is_merklized_storage_key)
+        .map_err(|e| Error::Storage(namada::state::StorageError::new(e)))?;
+
+    // Rollback Tendermint state, one block at a time. The blocks are removed
+    // from the block store, except for the last one that gets replayed on
+    // restart.
+    tracing::info!("Rollback Tendermint state to height {target_height}");
+    let mut tendermint_block_height = last_height;
+    while tendermint_block_height > target_height {
+        let hard = tendermint_block_height > target_height.next_height();
+        let rolled_back_height =
+            tendermint_node::rollback(config.cometbft_dir(), hard)
+                .map_err(Error::Tendermint)?;
+        if rolled_back_height >= tendermint_block_height {
+            return Err(Error::Tendermint(tendermint_node::Error::RollBack(
+                format!("Couldn't roll back below height {rolled_back_height}"),
+            )));
+        }
+        tendermint_block_height = rolled_back_height;
+    }
+
+    // Rollback Namada state
     tracing::info!("Rollback Namada state");
 
-    db.rollback(tendermint_block_height)
+    db.rollback(tendermint_block_height, is_merklized_storage_key)
         .map_err(|e| Error::Storage(namada::state::StorageError::new(e)))
 }
 
@@ -350,8 +383,20 @@ where
     storage_read_past_height_limit: Option<u64>,
     /// Proposal execution tracking
     pub proposal_data: BTreeSet<u64>,
-    /// Log of events emitted by `FinalizeBlock` ABCI calls.
-    event_log: EventLog,
+    /// Path to the directory with state-sync snapshots
+    pub(super) snapshots_dir: PathBuf,
+    /// Taken from config `snapshot_interval`. When set, a state-sync snapshot
+    /// is taken at every block height that is a multiple of the interval.
+    snapshot_interval: Option<u64>,
+    /// Taken from config `snapshots_to_keep`. When set, only this many of the
+    /// most recent snapshots are kept on disk.
+    snapshots_to_keep: Option<u64>,
+    /// The task writing a snapshot in the background, if any
+    snapshot_task: Option<std::thread::JoinHandle<()>>,
+    /// The progress of a state-sync from a snapshot, if any
+    snapshot_restore: Option<snapshots::SnapshotRestore>,
+    /// The storage migrations carried by this binary
+    pub storage_migrations: Vec<StorageMigration>,
 }
 
 /// Merkle tree storage key filter. Return `false` for keys that shouldn't be
@@ -406,6 +451,9 @@ where
         let mode = config.shell.tendermint_mode;
         let storage_read_past_height_limit =
             config.shell.storage_read_past_height_limit;
+        let snapshots_dir = config.shell.snapshots_dir(&chain_id);
+        let snapshot_interval = config.shell.snapshot_interval;
+        let snapshots_to_keep = config.shell.snapshots_to_keep;
         if !Path::new(&base_dir).is_dir() {
             std::fs::create_dir(&base_dir)
                 .expect("Creating directory for Namada should not fail");
@@ -431,7 +479,7 @@ where
         };
 
         // load last state from storage
-        let state = FullAccessState::open(
+        let mut state = FullAccessState::open(
             db_path,
             db_cache,
             chain_id.clone(),
@@ -439,6 +487,8 @@ where
             config.shell.storage_read_past_height_limit,
             is_merklized_storage_key,
         );
+        state.in_mem_mut().diffs_pruning = config.shell.diffs_pruning;
+        state.in_mem_mut().events_retention = config.shell.events_retention;
         let vp_wasm_cache_dir =
             base_dir.join(chain_id.as_str()).join("vp_wasm_cache");
         let tx_wasm_cache_dir =
@@ -523,25 +573,18 @@ where
             ),
             storage_read_past_height_limit,
             proposal_data: BTreeSet::new(),
-            // TODO: config event log params
-            event_log: EventLog::default(),
+            snapshots_dir,
+            snapshot_interval,
+            snapshots_to_keep,
+            snapshot_task: None,
+            snapshot_restore: None,
+            storage_migrations: migrations::storage_migrations(),
         };
+        shell.check_missed_storage_migrations();
         shell.update_eth_oracle(&Default::default());
         shell
     }
 
-    /// Return a reference to the [`EventLog`].
-    #[inline]
-    pub fn event_log(&self) -> &EventLog {
-        &self.event_log
-    }
-
-    /// Return a mutable reference to the [`EventLog`].
-    #[inline]
-    pub fn event_log_mut(&mut self) -> &mut EventLog {
-        &mut self.event_log
-    }
-
     /// Iterate over the wrapper txs in order
     #[allow(dead_code)]
     fn iter_tx_queue(&mut self) -> impl Iterator<Item = &TxInQueue> {
@@ -647,6 +690,7 @@ where
         );
 
         self.broadcast_queued_txs();
+        self.maybe_take_snapshot(committed_height);
 
         response::Commit {
             // NB: by passing 0, we forbid CometBFT from deleting
@@ -911,21 +955,21 @@ where
             return response;
         }
 
-        // Tx expiration
-        if let Some(exp) = tx.header.expiration {
-            let last_block_timestamp = self
-                .state
-                .get_last_block_timestamp()
-                .expect("Failed to retrieve last block timestamp");
+        // Tx expiration, defaulting to a lifetime after the tx timestamp
+        // to keep the replay protection storage bounded
+        let exp = tx.header.effective_expiration();
+        let last_block_timestamp = self
+            .state
+            .get_last_block_timestamp()
+            .expect("Failed to retrieve last block timestamp");
 
-            if last_block_timestamp > exp {
-                response.code = ResultCode::ExpiredTx.into();
-                response.log = format!(
-                    "{INVALID_MSG}: Tx expired at {exp:#?}, last committed \
-                     block time: {last_block_timestamp:#?}",
-                );
-                return response;
-            }
+        if last_block_timestamp > exp {
+            response.code = ResultCode::ExpiredTx.into();
+            response.log = format!(
+                "{INVALID_MSG}: Tx expired at {exp:#?}, last committed block \
+                 time: {last_block_timestamp:#?}",
+            );
+            return response;
         }
 
         // Tx signature check
@@ -1064,6 +1108,16 @@ where
                     return response;
                 }
 
+                // Scheduled txs can only be executed by the scheduler
+                if tx.carries_schedule() {
+                    response.code = ResultCode::InvalidTx.into();
+                    response.log = format!(
+                        "{INVALID_MSG}: Scheduled transactions cannot be \
+                         wrapped"
+                    );
+                    return response;
+                }
+
                 // Replay protection check
                 let inner_tx_hash = tx.raw_header_hash();
                 if self
@@ -1097,6 +1151,7 @@ where
 
                 // Validate wrapper fees
                 if let Err(e) = mempool_fee_check(
+                    &tx,
                     &wrapper,
                     get_fee_unshielding_transaction(&tx, &wrapper),
                     &mut self.state.with_temp_write_log(),
@@ -1226,12 +1281,13 @@ where
 
     // Write wrapper hash to WAL
     temp_state
-        .write_tx_hash(wrapper_hash)
+        .write_tx_hash(wrapper_hash, wrapper.header.effective_expiration())
         .map_err(|e| Error::ReplayAttempt(e.to_string()))
 }
 
 // Perform the fee check in mempool
 fn mempool_fee_check<D, H, CA>(
+    tx: &Tx,
     wrapper: &WrapperTx,
     masp_transaction: Option<Transaction>,
     temp_state: &mut TempWlState<D, H>,
@@ -1261,11 +1317,12 @@ where
         vp_wasm_cache,
         tx_wasm_cache,
     )?;
-    protocol::check_fees(temp_state, wrapper).map_err(Error::TxApply)
+    protocol::check_fees(temp_state, tx, wrapper).map_err(Error::TxApply)
 }
 
 /// Check the validity of the fee payment, including the minimum amounts
-/// required and the optional unshield
+/// required and the optional unshield. The gas price must also cover the
+/// current base fee, if enabled.
 pub fn wrapper_fee_check<D, H, CA>(
     wrapper: &WrapperTx,
     masp_transaction: Option<Transaction>,
@@ -1279,6 +1336,10 @@ where
     H: StorageHasher + Sync + 'static,
     CA: 'static + WasmCacheAccess + Sync,
 {
+    let base_fee = parameters::read_base_fee(temp_state, &wrapper.fee.token)
+        .expect("Must be able to read the base fee")
+        .unwrap_or_default();
+    let minimum_gas_price = std::cmp::max(minimum_gas_price, base_fee);
     match token::denom_to_amount(
         wrapper.fee.amount_per_gas_unit,
         &wrapper.fee.token,
@@ -1985,6 +2046,7 @@ mod test_utils {
             fee_unshielding_gas_limit: 0,
             fee_unshielding_descriptions_limit: 0,
             minimum_gas_price: Default::default(),
+            base_fee_params: None,
         };
         parameters::init_storage(&params, &mut shell.state)
             .expect("Test failed");
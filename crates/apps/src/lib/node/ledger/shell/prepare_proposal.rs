@@ -329,18 +329,18 @@ where
 {
     let tx = Tx::try_from(tx_bytes).map_err(|_| ())?;
 
-    // If tx doesn't have an expiration it is valid. If time cannot be
-    // retrieved from block default to last block datetime which has
-    // already been checked by mempool_validate, so it's valid
-    if let (Some(block_time), Some(exp)) =
-        (block_time.as_ref(), &tx.header().expiration)
-    {
-        if block_time > exp {
+    // If time cannot be retrieved from block default to last block datetime
+    // which has already been checked by mempool_validate, so it's valid
+    if let Some(block_time) = block_time.as_ref() {
+        if *block_time > tx.header().effective_expiration() {
             return Err(());
         }
     }
 
     tx.validate_tx().map_err(|_| ())?;
+    if tx.carries_schedule() {
+        return Err(());
+    }
     if let TxType::Wrapper(wrapper) = tx.header().tx_type {
         // Check tx gas limit for tx size
         let mut tx_gas_meter = TxGasMeter::new(wrapper.gas_limit);
@@ -350,6 +350,7 @@ where
 
         // Check fees and extract the gas limit of this transaction
         match prepare_proposal_fee_check(
+            &tx,
             &wrapper,
             protocol::get_fee_unshielding_transaction(&tx, &wrapper),
             block_proposer,
@@ -367,6 +368,7 @@ where
 }
 
 fn prepare_proposal_fee_check<D, H, CA>(
+    tx: &Tx,
     wrapper: &WrapperTx,
     masp_transaction: Option<Transaction>,
     proposer: &Address,
@@ -414,7 +416,7 @@ where
         tx_wasm_cache,
     )?;
 
-    protocol::transfer_fee(temp_state, proposer, wrapper)
+    protocol::transfer_fee(temp_state, proposer, tx, wrapper)
         .map_err(Error::TxApply)
 }
 
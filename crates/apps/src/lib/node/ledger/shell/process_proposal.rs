@@ -280,7 +280,7 @@ where
             },
             |tx| {
                 let tx_chain_id = tx.header.chain_id.clone();
-                let tx_expiration = tx.header.expiration;
+                let tx_expiration = tx.header.effective_expiration();
                 if let Err(err) = tx.validate_tx() {
                     // This occurs if the wrapper / protocol tx signature is
                     // invalid
@@ -325,16 +325,14 @@ where
                 }
 
                 // Tx expiration
-                if let Some(exp) = tx_expiration {
-                    if block_time > exp {
-                        return TxResult {
-                            code: ResultCode::ExpiredTx.into(),
-                            info: format!(
-                                "Tx expired at {:#?}, block time: {:#?}",
-                                exp, block_time
-                            ),
-                        };
-                    }
+                if block_time > tx_expiration {
+                    return TxResult {
+                        code: ResultCode::ExpiredTx.into(),
+                        info: format!(
+                            "Tx expired at {:#?}, block time: {:#?}",
+                            tx_expiration, block_time
+                        ),
+                    };
                 }
                 match protocol_tx.tx {
                     ProtocolTxType::EthEventsVext => {
@@ -462,10 +460,8 @@ where
                                     .into(),
                             }
                         } else {
-                            match tx.header().expiration {
-                                Some(tx_expiration)
-                                    if block_time > tx_expiration =>
-                                {
+                            match tx.header().effective_expiration() {
+                                tx_expiration if block_time > tx_expiration => {
                                     TxResult {
                                         code: ResultCode::ExpiredDecryptedTx
                                             .into(),
@@ -554,16 +550,22 @@ where
                 }
 
                 // Tx expiration
-                if let Some(exp) = tx_expiration {
-                    if block_time > exp {
-                        return TxResult {
-                            code: ResultCode::ExpiredTx.into(),
-                            info: format!(
-                                "Tx expired at {:#?}, block time: {:#?}",
-                                exp, block_time
-                            ),
-                        };
-                    }
+                if block_time > tx_expiration {
+                    return TxResult {
+                        code: ResultCode::ExpiredTx.into(),
+                        info: format!(
+                            "Tx expired at {:#?}, block time: {:#?}",
+                            tx_expiration, block_time
+                        ),
+                    };
+                }
+
+                // Scheduled txs can only be executed by the scheduler
+                if tx.carries_schedule() {
+                    return TxResult {
+                        code: ResultCode::InvalidTx.into(),
+                        info: "Scheduled transactions cannot be wrapped".into(),
+                    };
                 }
 
                 // Replay protection checks
@@ -577,6 +579,7 @@ where
 
                 // Check that the fee payer has sufficient balance.
                 match process_proposal_fee_check(
+                    &tx,
                     &wrapper,
                     get_fee_unshielding_transaction(&tx, &wrapper),
                     block_proposer,
@@ -615,6 +618,7 @@ where
 }
 
 fn process_proposal_fee_check<D, H, CA>(
+    tx: &Tx,
     wrapper: &WrapperTx,
     masp_transaction: Option<Transaction>,
     proposer: &Address,
@@ -646,7 +650,7 @@ where
         tx_wasm_cache,
     )?;
 
-    protocol::transfer_fee(temp_state, proposer, wrapper)
+    protocol::transfer_fee(temp_state, proposer, tx, wrapper)
         .map_err(Error::TxApply)
 }
 
@@ -17,7 +17,6 @@ where
     pub fn query(&self, query: request::Query) -> response::Query {
         let ctx = RequestCtx {
             state: self.state.read_only(),
-            event_log: self.event_log(),
             vp_wasm_cache: self.vp_wasm_cache.read_only(),
             tx_wasm_cache: self.tx_wasm_cache.read_only(),
             storage_read_past_height_limit: self.storage_read_past_height_limit,
@@ -0,0 +1,289 @@
+//! Execution of the transactions scheduled at a block height or epoch
+
+use namada::core::address::SCHEDULER;
+use namada::core::event::EmitEvents;
+use namada::core::storage::Epoch;
+use namada::ledger::gas::GasMetering;
+use namada::state::StorageWrite;
+use namada::tx::data::schedule::{
+    scheduled_txs_prefix, ScheduleTarget, ScheduledTx,
+};
+use namada_sdk::tx::new_tx_event;
+
+use super::*;
+
+/// Execute the transactions scheduled at the given block height and, on a new
+/// epoch, the ones scheduled at the current epoch. The scheduled transactions
+/// are removed from the queue whatever the outcome of their execution and
+/// their prepaid fees are paid to the block proposer. Returns the gas consumed
+/// by the scheduled transactions, in whole gas units, to be counted against
+/// the block gas.
+pub fn finalize_block<D, H>(
+    shell: &mut Shell<D, H>,
+    events: &mut impl EmitEvents,
+    height: BlockHeight,
+    current_epoch: Epoch,
+    new_epoch: bool,
+    block_proposer: &Address,
+) -> Result<u64>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let mut gas_used: u64 = 0;
+    let mut targets = vec![ScheduleTarget::Height(height)];
+    if new_epoch {
+        targets.push(ScheduleTarget::Epoch(current_epoch));
+    }
+
+    for target in targets {
+        let scheduled_txs: Vec<ScheduledTx> = namada::state::iter_prefix(
+            &shell.state,
+            &scheduled_txs_prefix(&target),
+        )?
+        .map(|entry| entry.map(|(_key, scheduled)| scheduled))
+        .collect::<namada::state::StorageResult<_>>()?;
+
+        for scheduled in scheduled_txs {
+            tracing::debug!(
+                "Executing the tx {} scheduled at {} by {}",
+                scheduled.hash(),
+                target,
+                scheduled.owner
+            );
+            let tx_gas_used = execute_scheduled_tx(
+                shell,
+                events,
+                height,
+                scheduled,
+                block_proposer,
+            )?;
+            gas_used =
+                gas_used.saturating_add(tx_gas_used.get_whole_gas_units());
+        }
+    }
+
+    Ok(gas_used)
+}
+
+/// Dequeue a scheduled transaction, pay its prepaid fee to the block proposer
+/// and apply it unless it has been replayed or has expired. Returns the gas
+/// consumed by its execution.
+fn execute_scheduled_tx<D, H>(
+    shell: &mut Shell<D, H>,
+    events: &mut impl EmitEvents,
+    height: BlockHeight,
+    scheduled: ScheduledTx,
+    block_proposer: &Address,
+) -> Result<Gas>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let hash = scheduled.hash();
+    shell.state.delete(&scheduled.storage_key())?;
+    if let Some(fee) = scheduled.get_prepaid_fee() {
+        let fee =
+            token::denom_to_amount(fee, &scheduled.fee.token, &shell.state)?;
+        token::transfer(
+            &mut shell.state,
+            &scheduled.fee.token,
+            &SCHEDULER,
+            block_proposer,
+            fee,
+        )?;
+    }
+
+    // Execute the code and data committed to by the schedule of the tx, which
+    // the scheduler VP requires to be bound to its target
+    let mut tx = match scheduled.tx.scheduled_inner() {
+        Some(inner) => inner,
+        None => scheduled.tx,
+    };
+    tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
+    let mut tx_event = new_tx_event(&tx, height.0);
+
+    let block_time = shell
+        .state
+        .in_mem()
+        .header
+        .as_ref()
+        .map(|header| header.time);
+    let expiration = tx.header.effective_expiration();
+    let is_expired = matches!(
+        block_time,
+        Some(block_time) if block_time > expiration
+    );
+    if shell
+        .state
+        .has_replay_protection_entry(&hash)
+        .expect("Error while checking inner tx hash key in storage")
+    {
+        tracing::info!("Scheduled tx {} has already been applied", hash);
+        tx_event["code"] = ResultCode::ReplayTx.into();
+        tx_event["info"] = "Replay attempt".to_string();
+        tx_event["gas_used"] = "0".into();
+        events.emit(tx_event);
+        return Ok(Gas::default());
+    } else if is_expired {
+        tracing::info!("Scheduled tx {} has expired", hash);
+        tx_event["code"] = ResultCode::ExpiredTx.into();
+        tx_event["info"] = "Expired transaction".to_string();
+        tx_event["gas_used"] = "0".into();
+        events.emit(tx_event);
+        return Ok(Gas::default());
+    } else if tx.header.chain_id != shell.chain_id {
+        tracing::info!("Scheduled tx {} has an invalid chain id", hash);
+        tx_event["code"] = ResultCode::InvalidChainId.into();
+        tx_event["info"] = "Invalid chain id".to_string();
+        tx_event["gas_used"] = "0".into();
+        events.emit(tx_event);
+        return Ok(Gas::default());
+    }
+
+    let tx_gas_meter = RefCell::new(TxGasMeter::new(scheduled.gas_limit));
+    let tx_result = protocol::check_tx_allowed(&tx, &shell.state)
+        .and_then(|()| {
+            protocol::dispatch_tx(
+                tx,
+                &[],
+                TxIndex::default(),
+                &tx_gas_meter,
+                &mut shell.state,
+                &mut shell.vp_wasm_cache,
+                &mut shell.tx_wasm_cache,
+                None,
+            )
+        })
+        .map_err(Error::TxApply);
+    let gas_used = tx_gas_meter.borrow().get_tx_consumed_gas();
+    match tx_result {
+        Ok(result) => {
+            if result.is_accepted() {
+                shell.state.commit_tx();
+                tx_event["code"] = ResultCode::Ok.into();
+                for ibc_event in result.ibc_events.iter().cloned() {
+                    let mut event = Event::from(ibc_event);
+                    event["height"] = height.to_string();
+                    events.emit(event);
+                }
+            } else {
+                tracing::trace!(
+                    "some VPs rejected scheduled transaction {} storage \
+                     modification {:#?}",
+                    hash,
+                    result.vps_result.rejected_vps
+                );
+                shell.state.drop_tx();
+                tx_event["code"] = ResultCode::InvalidTx.into();
+            }
+            tx_event["gas_used"] = result.gas_used.to_string();
+            tx_event["info"] = "Check inner_tx for result.".to_string();
+            tx_event["inner_tx"] = result.to_string();
+        }
+        Err(msg) => {
+            tracing::info!("Scheduled tx {} failed with: {}", hash, msg);
+            shell.state.drop_tx();
+            tx_event["code"] = ResultCode::WasmRuntimeError.into();
+            tx_event["gas_used"] = gas_used.to_string();
+            tx_event["info"] = msg.to_string();
+        }
+    }
+    // A scheduled tx is applied at most once, so commit its hash to prevent
+    // a replay of its inner tx in a wrapper
+    shell
+        .state
+        .write_tx_hash(hash, expiration)
+        .expect("Error while writing tx hash to storage");
+    events.emit(tx_event);
+
+    Ok(gas_used)
+}
+
+#[cfg(test)]
+mod test_scheduler {
+    use namada::core::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use namada::core::hash::Hash;
+    use namada::token::storage_key::balance_key;
+    use namada::token::{Amount, DenominatedAmount};
+    use namada::tx::data::{Fee, GasLimit};
+    use namada::tx::{Code, Data};
+
+    use super::*;
+    use crate::node::ledger::shell::test_utils::setup;
+
+    /// Test that a tx scheduled at the current height is dequeued, its fee
+    /// paid to the block proposer and its hash committed even if its
+    /// execution fails
+    #[test]
+    fn test_execute_scheduled_tx() {
+        let (mut shell, _, _, _) = setup();
+        let native_token = shell.state.in_mem().native_token.clone();
+        let owner = established_address_1();
+        let proposer = established_address_2();
+        let height = BlockHeight(10);
+
+        let mut tx = Tx::from_type(TxType::Raw);
+        tx.header.chain_id = shell.chain_id.clone();
+        tx.set_code(Code::from_hash(Hash::sha256(b"missing code"), None));
+        tx.set_data(Data::new(vec![]));
+        tx.set_schedule(ScheduleTarget::Height(height));
+        let scheduled = ScheduledTx {
+            owner,
+            target: ScheduleTarget::Height(height),
+            gas_limit: GasLimit::from(1_000),
+            fee: Fee {
+                amount_per_gas_unit: DenominatedAmount::native(
+                    Amount::from_u64(1),
+                ),
+                token: native_token.clone(),
+            },
+            tx,
+        };
+        let hash = scheduled.hash();
+        let queue_key = scheduled.storage_key();
+        shell.state.write(&queue_key, scheduled).unwrap();
+        shell
+            .state
+            .write(
+                &balance_key(&native_token, &SCHEDULER),
+                Amount::from_u64(1_000),
+            )
+            .unwrap();
+
+        let mut events = vec![];
+        finalize_block(
+            &mut shell,
+            &mut events,
+            height,
+            Epoch::default(),
+            false,
+            &proposer,
+        )
+        .unwrap();
+
+        assert!(!shell.state.has_key(&queue_key).unwrap());
+        assert_eq!(
+            token::read_balance(&shell.state, &native_token, &proposer)
+                .unwrap(),
+            Amount::from_u64(1_000)
+        );
+        assert_eq!(
+            token::read_balance(&shell.state, &native_token, &SCHEDULER)
+                .unwrap(),
+            Amount::zero()
+        );
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["hash"], hash.to_string());
+        assert_eq!(
+            events[0]["code"],
+            String::from(ResultCode::WasmRuntimeError)
+        );
+        assert_eq!(
+            shell.state.write_log().has_replay_protection_entry(&hash),
+            Some(true)
+        );
+    }
+}
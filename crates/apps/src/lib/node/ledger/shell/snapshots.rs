@@ -0,0 +1,993 @@
+//! ABCI state-sync snapshots of the DB.
+//!
+//! A snapshot is taken right after committing a block whose height is a
+//! multiple of the configured `snapshot_interval`. The commit only creates a
+//! checkpoint of the DB, from which the snapshot is written in the background.
+//! It contains the full Merkle tree stores of the block, followed by the
+//! entries of every [`SnapshotStore`] of the DB, split into chunks of up to
+//! [`CHUNK_SIZE`] bytes. The chunks are written into `{height}/{index}` files
+//! in the snapshots directory, together with a `{height}/metadata` file that
+//! contains the hashes of the chunks.
+//!
+//! A node that is being state-synced applies the chunks in order. Every chunk
+//! is checked against its hash from the snapshot's metadata. The Merkle tree
+//! built from the full stores has to match the app hash that CometBFT has
+//! obtained from the light client before any entries are written into the DB.
+//! Once all of them have been applied, the Merkle tree restored from the
+//! written stores and diffs has to match the app hash too, and every sub-tree
+//! rebuilt from the written subspace has to match the restored one, before the
+//! restored state gets loaded. The replay protection entries are not committed
+//! to by the app hash, so only their keys can be checked. If the restored
+//! state cannot be verified, the written entries are wiped from the DB.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use borsh_ext::BorshSerializeExt;
+use namada::core::hash::Hash;
+use namada::core::storage::{BlockHeight, Key};
+use namada::replay_protection;
+use namada::state::{
+    check_db_integrity, restore_merkle_tree, DBIter, MerkleTree,
+    MerkleTreeStoresRead, PrunedDiffs, SnapshotEntry, SnapshotStore,
+    StorageHasher, StoreType, DB,
+};
+
+use super::{Error, Result, Shell};
+use crate::facade::tendermint::abci::types::Snapshot;
+use crate::facade::tendermint::v0_37::abci::{request, response};
+
+/// The format of the snapshots. It must be incremented on any change of the
+/// snapshots encoding or of the layout of the snapshot stores.
+pub const SNAPSHOT_FORMAT: u32 = 2;
+
+/// The maximum size in bytes of the entries of a snapshot chunk
+pub const CHUNK_SIZE: usize = 10 * 1024 * 1024;
+
+/// The maximum size in bytes of an encoded Merkle tree store received in a
+/// snapshot chunk. The tree stores are held in memory until they are
+/// verified against the app hash.
+pub const MAX_TREE_STORE_SIZE: usize = 512 * 1024 * 1024;
+
+/// The name of the file with a snapshot's metadata
+const METADATA_FILE: &str = "metadata";
+
+/// The name of the directory with the DB checkpoint from which a snapshot is
+/// being written
+const CHECKPOINT_DIR: &str = "checkpoint";
+
+/// The metadata of a snapshot, passed to CometBFT inside [`Snapshot`].
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct SnapshotMetadata {
+    /// The height of the block at which the snapshot was taken
+    pub height: BlockHeight,
+    /// The hashes of the encoded chunks, in order
+    pub chunk_hashes: Vec<Hash>,
+}
+
+impl SnapshotMetadata {
+    /// The hash of the snapshot, derived from the hashes of its chunks
+    pub fn hash(&self) -> Hash {
+        Hash::sha256(self.chunk_hashes.serialize_to_vec())
+    }
+
+    /// Build the ABCI snapshot description
+    fn to_abci_snapshot(&self) -> Snapshot {
+        Snapshot {
+            height: self
+                .height
+                .0
+                .try_into()
+                .expect("Snapshot height should be valid"),
+            format: SNAPSHOT_FORMAT,
+            chunks: self.chunk_hashes.len() as u32,
+            hash: self.hash().to_vec().into(),
+            metadata: self.serialize_to_vec().into(),
+        }
+    }
+}
+
+/// A chunk of a snapshot
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub enum SnapshotChunk {
+    /// One of the full Merkle tree stores at the snapshot's height. These
+    /// chunks come first, so that a snapshot that doesn't match the app hash
+    /// is rejected before anything gets written into the DB.
+    MerkleTree {
+        /// The type of the store
+        store_type: StoreType,
+        /// The root of the store
+        root: Hash,
+        /// The encoded store
+        store: Vec<u8>,
+    },
+    /// Entries from a single store of the DB
+    Entries {
+        /// The store that the entries belong to
+        store: SnapshotStore,
+        /// The store's entries ordered by their keys
+        entries: Vec<SnapshotEntry>,
+    },
+}
+
+/// The progress of a state-sync from a snapshot accepted by the shell
+#[derive(Debug)]
+pub struct SnapshotRestore {
+    /// The metadata of the snapshot that's being restored
+    metadata: SnapshotMetadata,
+    /// The app hash at the height of the snapshot
+    app_hash: Vec<u8>,
+    /// The index of the next chunk to be applied
+    next_chunk: u32,
+    /// The roots and the encoded full Merkle tree stores received so far,
+    /// until they are verified against the app hash
+    tree_stores: Option<Vec<(StoreType, Hash, Vec<u8>)>>,
+}
+
+/// Read the metadata of all the snapshots found in the given directory,
+/// ordered by their heights.
+pub fn read_snapshots(snapshots_dir: &Path) -> Vec<SnapshotMetadata> {
+    let dir = match fs::read_dir(snapshots_dir) {
+        Ok(dir) => dir,
+        Err(_) => return vec![],
+    };
+    let mut snapshots: Vec<SnapshotMetadata> = dir
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            // Skip any snapshot that's still being written
+            entry.file_name().to_str()?.parse::<u64>().ok()?;
+            let bytes = fs::read(entry.path().join(METADATA_FILE)).ok()?;
+            SnapshotMetadata::try_from_slice(&bytes).ok()
+        })
+        .collect();
+    snapshots.sort_by_key(|snapshot| snapshot.height);
+    snapshots
+}
+
+/// Write a snapshot of the last committed block from the DB into the given
+/// directory. The snapshot is first written into a temporary directory that
+/// gets renamed once complete.
+pub fn write_snapshot<D, H>(
+    db: &D,
+    merkle_tree_key_filter: fn(&Key) -> bool,
+    height: BlockHeight,
+    snapshots_dir: &Path,
+) -> Result<SnapshotMetadata>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+    H: StorageHasher,
+{
+    let last_block = db
+        .read_last_block()
+        .map_err(|e| Error::Storage(namada::state::StorageError::new(e)))?
+        .ok_or_else(|| Error::Snapshot("Missing the last block".to_string()))?;
+    if last_block.height != height {
+        return Err(Error::Snapshot(format!(
+            "The last block height {} doesn't match the snapshot height \
+             {height}",
+            last_block.height
+        )));
+    }
+    // The full tree is restored the same way as on the restoring node, which
+    // checks that the snapshot's stores and diffs are sufficient for it
+    let tree = restore_merkle_tree::<D, H>(
+        db,
+        merkle_tree_key_filter,
+        &last_block.pred_epochs,
+        height,
+        None,
+    )
+    .map_err(|e| Error::Snapshot(e.to_string()))?;
+
+    let snapshot_dir = snapshot_dir(snapshots_dir, height);
+    let tmp_dir = snapshot_dir.with_extension("tmp");
+    if tmp_dir.exists() {
+        fs::remove_dir_all(&tmp_dir).map_err(io_error)?;
+    }
+    fs::create_dir_all(&tmp_dir).map_err(io_error)?;
+
+    let mut chunk_hashes = vec![];
+    let mut write_chunk = |chunk: SnapshotChunk| -> Result<()> {
+        let chunk = chunk.serialize_to_vec();
+        fs::write(tmp_dir.join(chunk_hashes.len().to_string()), &chunk)
+            .map_err(io_error)?;
+        chunk_hashes.push(Hash::sha256(&chunk));
+        Ok(())
+    };
+    let stores = tree.stores();
+    for st in StoreType::iter() {
+        write_chunk(SnapshotChunk::MerkleTree {
+            store_type: *st,
+            root: *stores.root(st),
+            store: stores.store(st).encode(),
+        })?;
+    }
+    for store in SnapshotStore::iter() {
+        let mut start_after: Option<String> = None;
+        loop {
+            let entries = db
+                .read_snapshot_page(*store, start_after.as_deref(), CHUNK_SIZE)
+                .map_err(|e| {
+                    Error::Storage(namada::state::StorageError::new(e))
+                })?;
+            let Some((last_key, _)) = entries.last() else {
+                break;
+            };
+            start_after = Some(last_key.clone());
+            write_chunk(SnapshotChunk::Entries {
+                store: *store,
+                entries,
+            })?;
+        }
+    }
+
+    let metadata = SnapshotMetadata {
+        height,
+        chunk_hashes,
+    };
+    fs::write(tmp_dir.join(METADATA_FILE), metadata.serialize_to_vec())
+        .map_err(io_error)?;
+    if snapshot_dir.exists() {
+        fs::remove_dir_all(&snapshot_dir).map_err(io_error)?;
+    }
+    fs::rename(&tmp_dir, &snapshot_dir).map_err(io_error)?;
+    Ok(metadata)
+}
+
+/// Delete all but the given number of the most recent snapshots
+pub fn prune_snapshots(snapshots_dir: &Path, snapshots_to_keep: u64) {
+    let snapshots = read_snapshots(snapshots_dir);
+    let num_to_prune =
+        snapshots.len().saturating_sub(snapshots_to_keep as usize);
+    for snapshot in snapshots.into_iter().take(num_to_prune) {
+        let dir = snapshot_dir(snapshots_dir, snapshot.height);
+        if let Err(err) = fs::remove_dir_all(&dir) {
+            tracing::error!(
+                "Failed to remove a snapshot at {}: {err}",
+                dir.to_string_lossy()
+            );
+        }
+    }
+}
+
+/// Get the directory of a snapshot at the given height
+fn snapshot_dir(snapshots_dir: &Path, height: BlockHeight) -> PathBuf {
+    snapshots_dir.join(height.0.to_string())
+}
+
+/// Remove the DB checkpoint directory, if any
+fn remove_checkpoint(checkpoint_dir: &Path) -> Result<()> {
+    if checkpoint_dir.exists() {
+        fs::remove_dir_all(checkpoint_dir).map_err(io_error)?;
+    }
+    Ok(())
+}
+
+fn io_error(err: std::io::Error) -> Error {
+    Error::Snapshot(err.to_string())
+}
+
+impl<D, H> Shell<D, H>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    /// Take a snapshot of the last committed block in the background, if the
+    /// snapshots are enabled and the block is at a snapshot height. Only a
+    /// checkpoint of the DB is created here and a new snapshot is skipped
+    /// while the previous one is still being written.
+    pub(super) fn maybe_take_snapshot(&mut self, height: BlockHeight) {
+        match self.snapshot_interval {
+            Some(interval) if interval > 0 && height.0 % interval == 0 => {}
+            _ => return,
+        }
+        if matches!(&self.snapshot_task, Some(task) if !task.is_finished()) {
+            tracing::warn!(
+                "Skipping a state-sync snapshot at height {height}, the \
+                 previous snapshot is still being written."
+            );
+            return;
+        }
+        let checkpoint_dir = self.snapshots_dir.join(CHECKPOINT_DIR);
+        let checkpoint = match self.checkpoint_db(&checkpoint_dir) {
+            Ok(checkpoint) => checkpoint,
+            Err(err) => {
+                tracing::error!(
+                    "Failed to take a state-sync snapshot at height {height}: \
+                     {err}"
+                );
+                return;
+            }
+        };
+        let merkle_tree_key_filter = self.state.merkle_tree_key_filter;
+        let snapshots_dir = self.snapshots_dir.clone();
+        let snapshots_to_keep = self.snapshots_to_keep;
+        self.snapshot_task = Some(std::thread::spawn(move || {
+            match write_snapshot::<_, H>(
+                &checkpoint,
+                merkle_tree_key_filter,
+                height,
+                &snapshots_dir,
+            ) {
+                Ok(metadata) => {
+                    tracing::info!(
+                        "Took a state-sync snapshot at height {height} with \
+                         {} chunks.",
+                        metadata.chunk_hashes.len()
+                    );
+                    if let Some(snapshots_to_keep) = snapshots_to_keep {
+                        prune_snapshots(&snapshots_dir, snapshots_to_keep);
+                    }
+                }
+                Err(err) => tracing::error!(
+                    "Failed to take a state-sync snapshot at height {height}: \
+                     {err}"
+                ),
+            }
+            drop(checkpoint);
+            if let Err(err) = remove_checkpoint(&checkpoint_dir) {
+                tracing::error!("Failed to remove the DB checkpoint: {err}");
+            }
+        }));
+    }
+
+    /// Create a checkpoint of the DB in the given directory, replacing any
+    /// checkpoint left behind by an interrupted snapshot
+    fn checkpoint_db(&self, checkpoint_dir: &Path) -> Result<D::Checkpoint> {
+        remove_checkpoint(checkpoint_dir)?;
+        fs::create_dir_all(&self.snapshots_dir).map_err(io_error)?;
+        self.state
+            .db()
+            .checkpoint_copy(checkpoint_dir)
+            .map_err(|e| Error::Storage(namada::state::StorageError::new(e)))
+    }
+
+    /// Take a snapshot of the last committed block
+    pub fn take_snapshot(
+        &self,
+        height: BlockHeight,
+    ) -> Result<SnapshotMetadata> {
+        write_snapshot::<D, H>(
+            self.state.db(),
+            self.state.merkle_tree_key_filter,
+            height,
+            &self.snapshots_dir,
+        )
+    }
+
+    /// List the snapshots available on disk
+    pub fn list_snapshots(&self) -> response::ListSnapshots {
+        response::ListSnapshots {
+            snapshots: read_snapshots(&self.snapshots_dir)
+                .iter()
+                .map(SnapshotMetadata::to_abci_snapshot)
+                .collect(),
+        }
+    }
+
+    /// Load a chunk of one of the snapshots available on disk
+    pub fn load_snapshot_chunk(
+        &self,
+        req: request::LoadSnapshotChunk,
+    ) -> response::LoadSnapshotChunk {
+        if req.format != SNAPSHOT_FORMAT {
+            return Default::default();
+        }
+        let height = BlockHeight(req.height.value());
+        let path = snapshot_dir(&self.snapshots_dir, height)
+            .join(req.chunk.to_string());
+        match fs::read(&path) {
+            Ok(chunk) => response::LoadSnapshotChunk {
+                chunk: chunk.into(),
+            },
+            Err(err) => {
+                tracing::error!(
+                    "Failed to load snapshot chunk {}: {err}",
+                    path.to_string_lossy()
+                );
+                Default::default()
+            }
+        }
+    }
+
+    /// Decide whether to state-sync from the offered snapshot
+    pub fn offer_snapshot(
+        &mut self,
+        req: request::OfferSnapshot,
+    ) -> response::OfferSnapshot {
+        if self.state.in_mem().last_block.is_some() {
+            tracing::error!(
+                "Rejecting an offered snapshot, the DB is not empty."
+            );
+            return response::OfferSnapshot::Abort;
+        }
+        if req.snapshot.format != SNAPSHOT_FORMAT {
+            return response::OfferSnapshot::RejectFormat;
+        }
+        let metadata =
+            match SnapshotMetadata::try_from_slice(&req.snapshot.metadata) {
+                Ok(metadata) => metadata,
+                Err(_) => return response::OfferSnapshot::Reject,
+            };
+        if metadata.height.0 != req.snapshot.height.value()
+            || metadata.chunk_hashes.len() as u32 != req.snapshot.chunks
+            || metadata.hash().0.as_slice() != req.snapshot.hash.as_ref()
+        {
+            return response::OfferSnapshot::Reject;
+        }
+        tracing::info!(
+            "Accepted a state-sync snapshot at height {}",
+            metadata.height
+        );
+        self.snapshot_restore = Some(SnapshotRestore {
+            metadata,
+            app_hash: req.app_hash.as_bytes().to_vec(),
+            next_chunk: 0,
+            tree_stores: Some(vec![]),
+        });
+        response::OfferSnapshot::Accept
+    }
+
+    /// Apply a chunk of the accepted snapshot. Once the last chunk is applied,
+    /// the restored state is verified against the app hash and loaded.
+    pub fn apply_snapshot_chunk(
+        &mut self,
+        req: request::ApplySnapshotChunk,
+    ) -> response::ApplySnapshotChunk {
+        use response::ApplySnapshotChunkResult;
+
+        let respond = |result| response::ApplySnapshotChunk {
+            result,
+            ..Default::default()
+        };
+        let Some(restore) = self.snapshot_restore.as_mut() else {
+            return respond(ApplySnapshotChunkResult::Abort);
+        };
+        if req.index != restore.next_chunk {
+            return response::ApplySnapshotChunk {
+                result: ApplySnapshotChunkResult::Retry,
+                refetch_chunks: vec![restore.next_chunk],
+                reject_senders: vec![],
+            };
+        }
+        let expected_hash = restore.metadata.chunk_hashes[req.index as usize];
+        if Hash::sha256(&req.chunk) != expected_hash {
+            tracing::warn!(
+                "Snapshot chunk {} from {} doesn't match its hash",
+                req.index,
+                req.sender
+            );
+            return response::ApplySnapshotChunk {
+                result: ApplySnapshotChunkResult::Retry,
+                refetch_chunks: vec![req.index],
+                reject_senders: vec![req.sender],
+            };
+        }
+        // The chunk matches its hash, so the snapshot itself is invalid if
+        // the chunk is
+        let chunk = match SnapshotChunk::try_from_slice(&req.chunk) {
+            Ok(chunk) => chunk,
+            Err(_) => {
+                self.discard_snapshot_restore();
+                return respond(ApplySnapshotChunkResult::RejectSnapshot);
+            }
+        };
+
+        match chunk {
+            SnapshotChunk::MerkleTree {
+                store_type,
+                root,
+                store,
+            } => {
+                // The tree stores must precede the DB entries and each one
+                // must be received once
+                let Some(tree_stores) = restore.tree_stores.as_mut() else {
+                    self.discard_snapshot_restore();
+                    return respond(ApplySnapshotChunkResult::RejectSnapshot);
+                };
+                if store.len() > MAX_TREE_STORE_SIZE
+                    || tree_stores.iter().any(|(st, _, _)| *st == store_type)
+                {
+                    tracing::warn!(
+                        "Rejecting a snapshot at height {} with an invalid {} \
+                         tree store",
+                        restore.metadata.height,
+                        store_type
+                    );
+                    self.discard_snapshot_restore();
+                    return respond(ApplySnapshotChunkResult::RejectSnapshot);
+                }
+                tree_stores.push((store_type, root, store));
+            }
+            SnapshotChunk::Entries { store, entries } => {
+                if let Some(tree_stores) = restore.tree_stores.take() {
+                    if let Err(err) =
+                        verify_tree_stores::<H>(tree_stores, &restore.app_hash)
+                    {
+                        tracing::warn!(
+                            "Rejecting a snapshot at height {}: {err}",
+                            restore.metadata.height
+                        );
+                        self.discard_snapshot_restore();
+                        return respond(
+                            ApplySnapshotChunkResult::RejectSnapshot,
+                        );
+                    }
+                }
+                if store == SnapshotStore::ReplayProtection
+                    && !entries.iter().all(is_replay_protection_entry)
+                {
+                    tracing::warn!(
+                        "Rejecting a snapshot at height {} with invalid \
+                         replay protection entries",
+                        restore.metadata.height
+                    );
+                    self.discard_snapshot_restore();
+                    return respond(ApplySnapshotChunkResult::RejectSnapshot);
+                }
+                let db = self.state.db_mut();
+                let mut batch = D::batch();
+                if let Err(err) = db
+                    .batch_write_snapshot_entries(&mut batch, store, &entries)
+                    .and_then(|()| db.exec_batch(batch))
+                {
+                    tracing::error!("Failed to write a snapshot chunk: {err}");
+                    self.discard_snapshot_restore();
+                    return respond(ApplySnapshotChunkResult::Abort);
+                }
+            }
+        }
+
+        let restore = self
+            .snapshot_restore
+            .as_mut()
+            .expect("The snapshot restore must be in progress");
+        restore.next_chunk += 1;
+        if (restore.next_chunk as usize) < restore.metadata.chunk_hashes.len() {
+            return respond(ApplySnapshotChunkResult::Accept);
+        }
+
+        // All the chunks have been applied
+        let restore = self
+            .snapshot_restore
+            .take()
+            .expect("The snapshot restore must be in progress");
+        match self.verify_restored_snapshot(&restore).and_then(
+            |epoch_start_height| {
+                self.mark_restored_diffs_pruned(epoch_start_height)
+            },
+        ) {
+            Ok(()) => {
+                self.state.load_last_state();
+                tracing::info!(
+                    "Restored the state from a snapshot at height {}",
+                    restore.metadata.height
+                );
+                respond(ApplySnapshotChunkResult::Accept)
+            }
+            Err(err) => {
+                tracing::error!(
+                    "Failed to verify the state restored from a snapshot, the \
+                     restored entries are wiped from the DB: {err}"
+                );
+                self.discard_snapshot_restore();
+                respond(ApplySnapshotChunkResult::Abort)
+            }
+        }
+    }
+
+    /// Stop restoring the accepted snapshot and wipe the entries that have
+    /// already been written from it, so that the DB is left empty for
+    /// another state-sync
+    fn discard_snapshot_restore(&mut self) {
+        self.snapshot_restore = None;
+        let db = self.state.db_mut();
+        let mut batch = D::batch();
+        if let Err(err) = SnapshotStore::iter()
+            .try_for_each(|store| {
+                db.batch_wipe_snapshot_store(&mut batch, *store)
+            })
+            .and_then(|()| db.exec_batch(batch))
+        {
+            tracing::error!(
+                "Failed to wipe the entries restored from a snapshot, the DB \
+                 has to be reset: {err}"
+            );
+        }
+    }
+
+    /// Only the subspace diffs since the start of the snapshot's epoch are
+    /// included in the snapshots, so the values at the heights before it
+    /// cannot be read
+    fn mark_restored_diffs_pruned(
+        &mut self,
+        epoch_start_height: BlockHeight,
+    ) -> Result<()> {
+        let pruned = PrunedDiffs {
+            height: epoch_start_height,
+            checkpoints: None,
+        };
+        let db = self.state.db_mut();
+        let mut batch = D::batch();
+        db.batch_write_pruned_diffs(&mut batch, &pruned)
+            .and_then(|()| db.exec_batch(batch))
+            .map_err(|e| Error::Storage(namada::state::StorageError::new(e)))
+    }
+
+    /// Check that the Merkle root restored from the DB matches the app hash
+    /// and that every sub-tree rebuilt from the restored subspace matches the
+    /// restored one. Returns the start height of the snapshot's epoch.
+    fn verify_restored_snapshot(
+        &self,
+        restore: &SnapshotRestore,
+    ) -> Result<BlockHeight> {
+        let last_block = self
+            .state
+            .db()
+            .read_last_block()
+            .map_err(|e| Error::Storage(namada::state::StorageError::new(e)))?
+            .ok_or_else(|| {
+                Error::Snapshot("Missing the last block".to_string())
+            })?;
+        if last_block.height != restore.metadata.height {
+            return Err(Error::Snapshot(format!(
+                "The restored block height {} doesn't match the snapshot \
+                 height {}",
+                last_block.height, restore.metadata.height
+            )));
+        }
+        let tree = restore_merkle_tree::<D, H>(
+            self.state.db(),
+            self.state.merkle_tree_key_filter,
+            &last_block.pred_epochs,
+            last_block.height,
+            None,
+        )
+        .map_err(|e| Error::Snapshot(e.to_string()))?;
+        let root = tree.root();
+        if root.0.as_slice() != restore.app_hash.as_slice() {
+            return Err(Error::Snapshot(format!(
+                "The restored Merkle root {root} doesn't match the app hash"
+            )));
+        }
+        // The subspace is not covered by the stores and diffs from which the
+        // tree is restored, so it has to be checked against it separately
+        let report = check_db_integrity::<D, H>(
+            self.state.db(),
+            self.state.merkle_tree_key_filter,
+            1,
+        )
+        .map_err(|e| Error::Snapshot(e.to_string()))?;
+        if !report.is_ok() {
+            return Err(Error::Snapshot(format!(
+                "The restored subspace doesn't match the Merkle tree: \
+                 {report:?}"
+            )));
+        }
+        let epoch_start_height = match last_block
+            .pred_epochs
+            .get_start_height_of_epoch(last_block.epoch)
+        {
+            Some(BlockHeight(0)) | None => BlockHeight(1),
+            Some(height) => height,
+        };
+        Ok(epoch_start_height)
+    }
+}
+
+/// Check that the Merkle root of the full tree stores from a snapshot matches
+/// the app hash
+fn verify_tree_stores<H: StorageHasher>(
+    tree_stores: Vec<(StoreType, Hash, Vec<u8>)>,
+    app_hash: &[u8],
+) -> Result<()> {
+    if !StoreType::iter().all(|st| {
+        tree_stores
+            .iter()
+            .any(|(store_type, _, _)| store_type == st)
+    }) {
+        return Err(Error::Snapshot(
+            "The snapshot is missing some Merkle tree stores".to_string(),
+        ));
+    }
+    let mut stores = MerkleTreeStoresRead::default();
+    for (store_type, root, store) in tree_stores {
+        let store = store_type
+            .decode_store(store)
+            .map_err(|e| Error::Snapshot(e.to_string()))?;
+        stores.set_root(&store_type, root);
+        stores.set_store(store);
+    }
+    let tree = MerkleTree::<H>::new(stores)
+        .map_err(|e| Error::Snapshot(e.to_string()))?;
+    let root = tree.root();
+    if root.0.as_slice() != app_hash {
+        return Err(Error::Snapshot(format!(
+            "The snapshot's Merkle root {root} doesn't match the app hash"
+        )));
+    }
+    Ok(())
+}
+
+/// Check that a replay protection entry from a snapshot is keyed by a tx hash
+/// under one of the replay protection subkeys
+fn is_replay_protection_entry((key, _value): &SnapshotEntry) -> bool {
+    if replay_protection::parse_expiry_key(key).is_some() {
+        return true;
+    }
+    [
+        replay_protection::all_prefix(),
+        replay_protection::last_prefix(),
+    ]
+    .iter()
+    .any(|prefix| {
+        key.strip_prefix(&format!("{prefix}/"))
+            .is_some_and(|hash| hash.parse::<Hash>().is_ok())
+    })
+}
+
+#[cfg(test)]
+mod test_snapshots {
+    use namada::state::StorageRead;
+    use namada::tendermint::AppHash;
+
+    use super::*;
+    use crate::node::ledger::shell::test_utils::{self, TestShell};
+
+    /// Write some values into the shell's storage and commit a few blocks
+    fn setup_committed_shell() -> TestShell {
+        let (mut shell, _recv, _, _) = test_utils::setup();
+        for i in 0..3_u64 {
+            let key = Key::parse(format!("snapshot_test_{i}")).unwrap();
+            shell.state.db_write(&key, i.serialize_to_vec()).unwrap();
+            shell.finalize_and_commit(None);
+        }
+        shell
+    }
+
+    /// Offer the given snapshot to the shell
+    fn offer(
+        shell: &mut TestShell,
+        metadata: &SnapshotMetadata,
+        app_hash: Vec<u8>,
+    ) -> response::OfferSnapshot {
+        shell.offer_snapshot(request::OfferSnapshot {
+            snapshot: metadata.to_abci_snapshot(),
+            app_hash: AppHash::try_from(app_hash).unwrap(),
+        })
+    }
+
+    /// Test that a snapshot written by one shell can be used to restore the
+    /// state in another one.
+    #[test]
+    fn test_snapshot_round_trip() {
+        let shell = setup_committed_shell();
+        let height = shell.state.in_mem().get_last_block_height();
+        // The snapshot is taken in the middle of an epoch, so the tree has to
+        // be restored from the epoch's stores and the diffs since
+        let epoch_start_height = shell
+            .state
+            .in_mem()
+            .block
+            .pred_epochs
+            .get_start_height_of_epoch(shell.state.in_mem().block.epoch)
+            .unwrap();
+        assert!(epoch_start_height.next_height() < height);
+        let metadata = shell.take_snapshot(height).unwrap();
+        assert_eq!(metadata.height, height);
+        assert!(!metadata.chunk_hashes.is_empty());
+
+        let snapshots = shell.list_snapshots().snapshots;
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].chunks as usize, metadata.chunk_hashes.len());
+
+        let (mut restored, _recv, _, _) = TestShell::new();
+        let app_hash = shell.state.in_mem().merkle_root().0.to_vec();
+        assert_eq!(
+            offer(&mut restored, &metadata, app_hash),
+            response::OfferSnapshot::Accept
+        );
+        for index in 0..metadata.chunk_hashes.len() as u32 {
+            let chunk = shell.load_snapshot_chunk(request::LoadSnapshotChunk {
+                height: snapshots[0].height,
+                format: SNAPSHOT_FORMAT,
+                chunk: index,
+            });
+            let resp =
+                restored.apply_snapshot_chunk(request::ApplySnapshotChunk {
+                    index,
+                    chunk: chunk.chunk,
+                    sender: "peer".to_string(),
+                });
+            assert_eq!(resp.result, response::ApplySnapshotChunkResult::Accept);
+        }
+
+        assert_eq!(restored.state.in_mem().get_last_block_height(), height);
+        assert_eq!(
+            restored.state.in_mem().merkle_root(),
+            shell.state.in_mem().merkle_root()
+        );
+        for i in 0..3_u64 {
+            let key = Key::parse(format!("snapshot_test_{i}")).unwrap();
+            let value: Option<u64> = restored.state.read(&key).unwrap();
+            assert_eq!(value, Some(i));
+        }
+        let tree = restored.state.get_merkle_tree(height, None).unwrap();
+        assert_eq!(tree.root(), shell.state.in_mem().merkle_root());
+    }
+
+    /// Test that a snapshot is written in the background from a checkpoint of
+    /// the DB when a block at a snapshot height is committed.
+    #[test]
+    fn test_snapshot_in_background() {
+        let mut shell = setup_committed_shell();
+        let height = shell.state.in_mem().get_last_block_height().next_height();
+        shell.snapshot_interval = Some(height.0);
+        shell.finalize_and_commit(None);
+
+        let task = shell.snapshot_task.take().expect("Snapshot must be taken");
+        task.join().unwrap();
+        let snapshots = read_snapshots(&shell.snapshots_dir);
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].height, height);
+        assert!(!shell.snapshots_dir.join(CHECKPOINT_DIR).exists());
+    }
+
+    /// Test that tampered chunks and a wrong app hash are rejected.
+    #[test]
+    fn test_snapshot_verification() {
+        let shell = setup_committed_shell();
+        let height = shell.state.in_mem().get_last_block_height();
+        let metadata = shell.take_snapshot(height).unwrap();
+        let chunks: Vec<Vec<u8>> = (0..metadata.chunk_hashes.len())
+            .map(|index| {
+                fs::read(
+                    snapshot_dir(&shell.snapshots_dir, height)
+                        .join(index.to_string()),
+                )
+                .unwrap()
+            })
+            .collect();
+
+        // A tampered chunk must be re-fetched from another peer
+        let (mut restored, _recv, _, _) = TestShell::new();
+        let app_hash = shell.state.in_mem().merkle_root().0.to_vec();
+        offer(&mut restored, &metadata, app_hash);
+        let mut tampered = chunks[0].clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        let resp = restored.apply_snapshot_chunk(request::ApplySnapshotChunk {
+            index: 0,
+            chunk: tampered.into(),
+            sender: "bad_peer".to_string(),
+        });
+        assert_eq!(resp.result, response::ApplySnapshotChunkResult::Retry);
+        assert_eq!(resp.refetch_chunks, vec![0]);
+        assert_eq!(resp.reject_senders, vec!["bad_peer".to_string()]);
+
+        // A mismatching app hash must reject the snapshot once the Merkle
+        // tree stores have been received, before writing anything into the DB
+        let (mut restored, _recv, _, _) = TestShell::new();
+        offer(&mut restored, &metadata, vec![1; 32]);
+        let num_tree_chunks = StoreType::iter().len();
+        let results: Vec<_> = chunks
+            .into_iter()
+            .take(num_tree_chunks + 1)
+            .enumerate()
+            .map(|(index, chunk)| {
+                restored
+                    .apply_snapshot_chunk(request::ApplySnapshotChunk {
+                        index: index as u32,
+                        chunk: chunk.into(),
+                        sender: "peer".to_string(),
+                    })
+                    .result
+            })
+            .collect();
+        assert!(results[..num_tree_chunks].iter().all(
+            |result| *result == response::ApplySnapshotChunkResult::Accept
+        ));
+        assert_eq!(
+            results.last(),
+            Some(&response::ApplySnapshotChunkResult::RejectSnapshot)
+        );
+        assert!(restored.snapshot_restore.is_none());
+        assert!(restored.state.db().read_last_block().unwrap().is_none());
+    }
+
+    /// Test that a snapshot whose subspace doesn't match its Merkle tree is
+    /// aborted once restored, and that the restored entries are wiped.
+    #[test]
+    fn test_snapshot_tampered_subspace() {
+        let shell = setup_committed_shell();
+        let height = shell.state.in_mem().get_last_block_height();
+        let metadata = shell.take_snapshot(height).unwrap();
+        // Change a value of the subspace and the snapshot's metadata
+        // accordingly, as a malicious peer would
+        let chunks: Vec<Vec<u8>> = (0..metadata.chunk_hashes.len())
+            .map(|index| {
+                let chunk = fs::read(
+                    snapshot_dir(&shell.snapshots_dir, height)
+                        .join(index.to_string()),
+                )
+                .unwrap();
+                match SnapshotChunk::try_from_slice(&chunk).unwrap() {
+                    SnapshotChunk::Entries {
+                        store: SnapshotStore::Subspace,
+                        mut entries,
+                    } => {
+                        for (key, value) in entries.iter_mut() {
+                            if key == "snapshot_test_0" {
+                                *value = 100_u64.serialize_to_vec();
+                            }
+                        }
+                        SnapshotChunk::Entries {
+                            store: SnapshotStore::Subspace,
+                            entries,
+                        }
+                        .serialize_to_vec()
+                    }
+                    _ => chunk,
+                }
+            })
+            .collect();
+        let metadata = SnapshotMetadata {
+            height,
+            chunk_hashes: chunks.iter().map(Hash::sha256).collect(),
+        };
+
+        let (mut restored, _recv, _, _) = TestShell::new();
+        let app_hash = shell.state.in_mem().merkle_root().0.to_vec();
+        offer(&mut restored, &metadata, app_hash);
+        let results: Vec<_> = chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                restored
+                    .apply_snapshot_chunk(request::ApplySnapshotChunk {
+                        index: index as u32,
+                        chunk: chunk.into(),
+                        sender: "peer".to_string(),
+                    })
+                    .result
+            })
+            .collect();
+        assert_eq!(
+            results.last(),
+            Some(&response::ApplySnapshotChunkResult::Abort)
+        );
+        assert!(restored.snapshot_restore.is_none());
+        assert!(restored.state.db().read_last_block().unwrap().is_none());
+        let key = Key::parse("snapshot_test_0").unwrap();
+        assert!(restored
+            .state
+            .db()
+            .read_subspace_val(&key)
+            .unwrap()
+            .is_none());
+    }
+
+    /// Test that only the most recent snapshots are kept.
+    #[test]
+    fn test_prune_snapshots() {
+        let mut shell = setup_committed_shell();
+        for _ in 0..3 {
+            let height = shell.state.in_mem().get_last_block_height();
+            shell.take_snapshot(height).unwrap();
+            shell.finalize_and_commit(None);
+        }
+        prune_snapshots(&shell.snapshots_dir, 2);
+        let heights: Vec<_> = read_snapshots(&shell.snapshots_dir)
+            .into_iter()
+            .map(|snapshot| snapshot.height)
+            .collect();
+        let last_height = shell.state.in_mem().get_last_block_height();
+        assert_eq!(
+            heights,
+            vec![
+                last_height.prev_height().prev_height(),
+                last_height.prev_height()
+            ]
+        );
+    }
+}
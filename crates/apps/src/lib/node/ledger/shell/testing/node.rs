@@ -9,7 +9,6 @@ use std::task::Poll;
 use color_eyre::eyre::{Report, Result};
 use data_encoding::HEXUPPER;
 use itertools::Either;
-use lazy_static::lazy_static;
 use namada::control_flow::time::Duration;
 use namada::core::ethereum_events::EthereumEvent;
 use namada::core::ethereum_structs;
@@ -19,7 +18,8 @@ use namada::core::storage::{BlockHash, BlockHeight, Epoch, Header};
 use namada::core::time::DateTimeUtc;
 use namada::eth_bridge::oracle::config::Config as OracleConfig;
 use namada::ledger::dry_run_tx;
-use namada::ledger::events::log::dumb_queries;
+use namada::ledger::events::log::iter_matching_events;
+use namada::ledger::events::log::query::EventQuery;
 use namada::ledger::queries::{
     EncodedResponseQuery, RequestCtx, RequestQuery, Router, RPC,
 };
@@ -29,13 +29,12 @@ use namada::proof_of_stake::storage::{
     validator_consensus_key_handle,
 };
 use namada::proof_of_stake::types::WeightedValidator;
-use namada::state::{LastBlock, Sha256Hasher, EPOCH_SWITCH_BLOCKS_DELAY};
+use namada::state::{LastBlock, Sha256Hasher, DB, EPOCH_SWITCH_BLOCKS_DELAY};
 use namada::tendermint::abci::response::Info;
 use namada::tendermint::abci::types::VoteInfo;
 use namada_sdk::queries::Client;
 use namada_sdk::tendermint_proto::google::protobuf::Timestamp;
 use namada_sdk::tx::data::ResultCode;
-use regex::Regex;
 use tendermint_rpc::endpoint::block;
 use tendermint_rpc::SimpleRequest;
 use tokio::sync::mpsc;
@@ -704,7 +703,6 @@ impl<'a> Client for &'a MockNode {
         let borrowed = self.shell.lock().unwrap();
         let ctx = RequestCtx {
             state: &borrowed.state,
-            event_log: borrowed.event_log(),
             vp_wasm_cache: borrowed.vp_wasm_cache.read_only(),
             tx_wasm_cache: borrowed.tx_wasm_cache.read_only(),
             storage_read_past_height_limit: None,
@@ -809,19 +807,18 @@ impl<'a> Client for &'a MockNode {
         self.drive_mock_services_bg().await;
         let matcher = parse_tm_query(query);
         let borrowed = self.shell.lock().unwrap();
-        // we store an index into the event log as a block
-        // height in the response of the query... VERY NAISSSE
-        let matching_events = borrowed.event_log().iter().enumerate().flat_map(
-            |(index, event)| {
-                if matcher.matches(event) {
-                    Some(EncodedEvent(index as u64))
-                } else {
-                    None
-                }
-            },
-        );
-        let blocks = matching_events
-            .map(|encoded_event| namada::tendermint_rpc::endpoint::block::Response {
+        let last_height = borrowed.state.in_mem().get_last_block_height();
+        let mut heights =
+            iter_matching_events(borrowed.state.db(), &matcher, last_height)
+                .map_err(|err| RpcError::server(err.to_string()))?
+                .map(|found| found.map(|(height, _event)| height))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| RpcError::server(err.to_string()))?;
+        // the matching events are ordered by descending height
+        heights.dedup();
+        let blocks = heights
+            .into_iter()
+            .map(|height| namada::tendermint_rpc::endpoint::block::Response {
                 block_id: Default::default(),
                 block: namada::tendermint_proto::types::Block {
                     header: Some(namada::tendermint_proto::types::Header {
@@ -830,7 +827,7 @@ impl<'a> Client for &'a MockNode {
                             app: 0,
                         }),
                         chain_id: "Namada".into(),
-                        height: encoded_event.0 as i64,
+                        height: height.0 as i64,
                         time: None,
                         last_block_id: None,
                         last_commit_hash: vec![],
@@ -847,7 +844,7 @@ impl<'a> Client for &'a MockNode {
                     data: Default::default(),
                     evidence: Default::default(),
                     last_commit: Some(namada::tendermint_proto::types::Commit {
-                        height: encoded_event.0 as i64,
+                        height: height.0 as i64,
                         round: 0,
                         block_id: Some(namada::tendermint_proto::types::BlockId {
                             hash: vec![0u8; 32],
@@ -878,21 +875,13 @@ impl<'a> Client for &'a MockNode {
     {
         self.drive_mock_services_bg().await;
         let height = height.into();
-        let encoded_event = EncodedEvent(height.value());
         let locked = self.shell.lock().unwrap();
         let events: Vec<_> = locked
-            .event_log()
-            .iter()
-            .flat_map(|event| {
-                if usize::from_str(event.attributes.get("height").unwrap())
-                    .unwrap()
-                    == encoded_event.log_index()
-                {
-                    Some(event)
-                } else {
-                    None
-                }
-            })
+            .state
+            .db()
+            .read_events(BlockHeight(height.value()))
+            .map_err(|err| RpcError::server(err.to_string()))?
+            .into_iter()
             .map(|event| namada::tendermint::abci::Event {
                 kind: event.event_type.to_string(),
                 attributes: event
@@ -968,39 +957,9 @@ impl<'a> Client for &'a MockNode {
 }
 
 /// Parse a Tendermint query.
-fn parse_tm_query(
-    query: namada::tendermint_rpc::query::Query,
-) -> dumb_queries::QueryMatcher {
-    const QUERY_PARSING_REGEX_STR: &str =
-        r"^tm\.event='NewBlock' AND (accepted|applied)\.hash='([^']+)'$";
-
-    lazy_static! {
-        /// Compiled regular expression used to parse Tendermint queries.
-        static ref QUERY_PARSING_REGEX: Regex = Regex::new(QUERY_PARSING_REGEX_STR).unwrap();
-    }
-
-    let query = query.to_string();
-    let captures = QUERY_PARSING_REGEX.captures(&query).unwrap();
-
-    match captures.get(0).unwrap().as_str() {
-        "accepted" => dumb_queries::QueryMatcher::accepted(
-            captures.get(1).unwrap().as_str().try_into().unwrap(),
-        ),
-        "applied" => dumb_queries::QueryMatcher::applied(
-            captures.get(1).unwrap().as_str().try_into().unwrap(),
-        ),
-        _ => unreachable!("We only query accepted or applied txs"),
-    }
-}
-
-/// A Namada event log index and event type encoded as
-/// a Tendermint block height.
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
-struct EncodedEvent(u64);
-
-impl EncodedEvent {
-    /// Get the encoded event log index.
-    const fn log_index(self) -> usize {
-        self.0 as usize
-    }
+fn parse_tm_query(query: namada::tendermint_rpc::query::Query) -> EventQuery {
+    query
+        .to_string()
+        .parse()
+        .expect("Tendermint queries should be valid event queries")
 }
@@ -0,0 +1,422 @@
+//! Options of the DB dump and decoding of the values of known storage keys.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use borsh::BorshDeserialize;
+use namada::core::address::Address;
+use namada::core::chain::ProposalBytes;
+use namada::core::dec::Dec;
+use namada::core::hash::Hash;
+use namada::core::parameters::{BaseFeeParams, EpochDuration};
+use namada::core::storage::{BlockHeight, Key};
+use namada::core::time::DurationSecs;
+use namada::ledger::parameters::storage as params_storage;
+use namada::proof_of_stake::storage_key::is_bond_key;
+use namada::token::storage_key::is_any_token_balance_key;
+use namada::token::Amount;
+use serde_json::{json, Value};
+
+/// A column family of the DB that can be dumped
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DumpDbColumnFamily {
+    /// The accounts sub-spaces
+    Subspace,
+    /// The diffs of the sub-spaces at the dumped height
+    Diffs,
+    /// The latest ledger state metadata
+    State,
+    /// The block state at the dumped height
+    Block,
+    /// The hashes of the processed txs
+    ReplayProtection,
+}
+
+impl DumpDbColumnFamily {
+    /// The column families dumped when none is selected
+    pub fn defaults(historic: bool) -> Vec<Self> {
+        if historic {
+            vec![
+                Self::Diffs,
+                Self::Block,
+                Self::Subspace,
+                Self::ReplayProtection,
+            ]
+        } else {
+            vec![Self::Subspace, Self::ReplayProtection]
+        }
+    }
+}
+
+impl fmt::Display for DumpDbColumnFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Subspace => write!(f, "subspace"),
+            Self::Diffs => write!(f, "diffs"),
+            Self::State => write!(f, "state"),
+            Self::Block => write!(f, "block"),
+            Self::ReplayProtection => write!(f, "replay_protection"),
+        }
+    }
+}
+
+impl FromStr for DumpDbColumnFamily {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "subspace" => Ok(Self::Subspace),
+            "diffs" => Ok(Self::Diffs),
+            "state" => Ok(Self::State),
+            "block" => Ok(Self::Block),
+            "replay_protection" => Ok(Self::ReplayProtection),
+            _ => Err(format!(
+                "Unknown column family {s}, expected one of subspace, diffs, \
+                 state, block or replay_protection"
+            )),
+        }
+    }
+}
+
+/// The format of the DB dump
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DumpDbFormat {
+    /// TOML key-vals with hex-encoded values
+    #[default]
+    Toml,
+    /// A JSON object per line, with the values of known keys decoded
+    JsonLines,
+}
+
+impl DumpDbFormat {
+    /// The extension of the output file
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Toml => "toml",
+            Self::JsonLines => "jsonl",
+        }
+    }
+}
+
+impl FromStr for DumpDbFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "toml" => Ok(Self::Toml),
+            "json-lines" | "jsonl" => Ok(Self::JsonLines),
+            _ => Err(format!(
+                "Unknown dump format {s}, expected toml or json-lines"
+            )),
+        }
+    }
+}
+
+/// What to dump from the DB
+#[derive(Clone, Debug, Default)]
+pub struct DumpDbOptions {
+    /// The height to dump, defaults to the last committed height
+    pub height: Option<BlockHeight>,
+    /// If set, only dump the sub-space keys that changed after this height
+    /// up to the dumped height, with their values at both heights
+    pub diff_from_height: Option<BlockHeight>,
+    /// Only dump the keys starting with one of these prefixes. The diffs are
+    /// matched on their sub-space key.
+    pub key_prefixes: Vec<String>,
+    /// The column families to dump
+    pub column_families: Vec<DumpDbColumnFamily>,
+    /// The output format
+    pub format: DumpDbFormat,
+}
+
+impl DumpDbOptions {
+    /// The key prefixes to iterate, without the ones covered by a shorter
+    /// prefix. An empty prefix matches all the keys.
+    pub fn iter_key_prefixes(&self) -> Vec<String> {
+        let mut prefixes = self.key_prefixes.clone();
+        prefixes.sort();
+        let mut iter_prefixes: Vec<String> = vec![];
+        for prefix in prefixes {
+            if !iter_prefixes
+                .last()
+                .map(|last| prefix.starts_with(last.as_str()))
+                .unwrap_or_default()
+            {
+                iter_prefixes.push(prefix);
+            }
+        }
+        if iter_prefixes.is_empty() {
+            iter_prefixes.push(String::new());
+        }
+        iter_prefixes
+    }
+}
+
+/// Format a dumped entry as a line of the output
+pub fn format_entry(
+    format: DumpDbFormat,
+    cf: DumpDbColumnFamily,
+    key: &str,
+    storage_key: Option<&Key>,
+    value: &[u8],
+) -> String {
+    let hex = data_encoding::HEXLOWER.encode(value);
+    match format {
+        DumpDbFormat::Toml => format!("\"{key}\" = \"{hex}\"\n"),
+        DumpDbFormat::JsonLines => {
+            let mut entry = json!({
+                "cf": cf.to_string(),
+                "key": key,
+                "value": hex,
+            });
+            if let Some(decoded) =
+                storage_key.and_then(|key| decode_value(key, value))
+            {
+                entry["decoded"] = decoded;
+            }
+            format!("{entry}\n")
+        }
+    }
+}
+
+/// Format the change of a sub-space key between two heights as a line of the
+/// output
+pub fn format_diff_entry(
+    format: DumpDbFormat,
+    key: &str,
+    storage_key: Option<&Key>,
+    old: Option<&[u8]>,
+    new: Option<&[u8]>,
+) -> String {
+    let hex = |value: Option<&[u8]>| {
+        value.map(|value| data_encoding::HEXLOWER.encode(value))
+    };
+    match format {
+        DumpDbFormat::Toml => {
+            let fields = [("old", hex(old)), ("new", hex(new))]
+                .into_iter()
+                .filter_map(|(name, value)| {
+                    value.map(|value| format!("{name} = \"{value}\""))
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("\"{key}\" = {{ {fields} }}\n")
+        }
+        DumpDbFormat::JsonLines => {
+            let mut entry = json!({
+                "key": key,
+                "old": hex(old),
+                "new": hex(new),
+            });
+            if let Some(storage_key) = storage_key {
+                for (name, value) in
+                    [("old_decoded", old), ("new_decoded", new)]
+                {
+                    if let Some(decoded) =
+                        value.and_then(|value| decode_value(storage_key, value))
+                    {
+                        entry[name] = decoded;
+                    }
+                }
+            }
+            format!("{entry}\n")
+        }
+    }
+}
+
+/// Decode the borsh-encoded value of a known key type. Returns `None` for
+/// the keys whose type is unknown or if the value cannot be decoded.
+pub fn decode_value(key: &Key, value: &[u8]) -> Option<Value> {
+    if let Some([token, owner]) = is_any_token_balance_key(key) {
+        let amount = Amount::try_from_slice(value).ok()?;
+        return Some(json!({
+            "type": "balance",
+            "token": token.to_string(),
+            "owner": owner.to_string(),
+            "amount": amount.to_string(),
+        }));
+    }
+    if let Some((bond_id, start)) = is_bond_key(key) {
+        let amount = Amount::try_from_slice(value).ok()?;
+        return Some(json!({
+            "type": "bond",
+            "source": bond_id.source.to_string(),
+            "validator": bond_id.validator.to_string(),
+            "start_epoch": start.0,
+            "amount": amount.to_string(),
+        }));
+    }
+    if params_storage::is_parameter_key(key) {
+        let (name, value) = decode_parameter(key, value)?;
+        return Some(json!({
+            "type": "parameter",
+            "name": name,
+            "value": value,
+        }));
+    }
+    None
+}
+
+/// Decode the value of a protocol parameter
+fn decode_parameter(key: &Key, value: &[u8]) -> Option<(&'static str, Value)> {
+    fn decode<T: BorshDeserialize>(value: &[u8]) -> Option<T> {
+        T::try_from_slice(value).ok()
+    }
+
+    let decoded = if *key == params_storage::get_epoch_duration_storage_key() {
+        let duration: EpochDuration = decode(value)?;
+        (
+            "epoch_duration",
+            json!({
+                "min_num_of_blocks": duration.min_num_of_blocks,
+                "min_duration": duration.min_duration.0,
+            }),
+        )
+    } else if *key == params_storage::get_max_expected_time_per_block_key() {
+        let duration: DurationSecs = decode(value)?;
+        ("max_expected_time_per_block", json!(duration.0))
+    } else if *key == params_storage::get_vp_allowlist_storage_key() {
+        ("vp_allowlist", json!(decode::<Vec<String>>(value)?))
+    } else if *key == params_storage::get_tx_allowlist_storage_key() {
+        ("tx_allowlist", json!(decode::<Vec<String>>(value)?))
+    } else if *key == params_storage::get_implicit_vp_key() {
+        // The code hash is not borsh-encoded
+        let hash = Hash::try_from(value).ok()?;
+        ("implicit_vp", json!(hash.to_string()))
+    } else if *key == params_storage::get_epochs_per_year_key() {
+        ("epochs_per_year", json!(decode::<u64>(value)?))
+    } else if *key == params_storage::get_staked_ratio_key() {
+        ("staked_ratio", json!(decode::<Dec>(value)?.to_string()))
+    } else if *key == params_storage::get_pos_inflation_amount_key() {
+        (
+            "pos_inflation_amount",
+            json!(decode::<Amount>(value)?.to_string()),
+        )
+    } else if *key == params_storage::get_max_proposal_bytes_key() {
+        let bytes: ProposalBytes = decode(value)?;
+        ("max_proposal_bytes", json!(bytes.get()))
+    } else if *key == params_storage::get_max_tx_bytes_key() {
+        ("max_tx_bytes", json!(decode::<u32>(value)?))
+    } else if *key == params_storage::get_max_block_gas_key() {
+        ("max_block_gas", json!(decode::<u64>(value)?))
+    } else if *key == params_storage::get_gas_cost_key() {
+        let gas_cost: BTreeMap<Address, Amount> = decode(value)?;
+        let gas_cost: BTreeMap<String, String> = gas_cost
+            .into_iter()
+            .map(|(token, amount)| (token.to_string(), amount.to_string()))
+            .collect();
+        ("minimum_gas_price", json!(gas_cost))
+    } else if *key == params_storage::get_fee_unshielding_gas_limit_key() {
+        ("fee_unshielding_gas_limit", json!(decode::<u64>(value)?))
+    } else if *key
+        == params_storage::get_fee_unshielding_descriptions_limit_key()
+    {
+        (
+            "fee_unshielding_descriptions_limit",
+            json!(decode::<u64>(value)?),
+        )
+    } else if *key == params_storage::get_max_signatures_per_transaction_key() {
+        (
+            "max_signatures_per_transaction",
+            json!(decode::<u8>(value)?),
+        )
+    } else if *key == params_storage::get_base_fee_params_key() {
+        ("base_fee_params", json!(decode::<BaseFeeParams>(value)?))
+    } else if *key == params_storage::get_base_fee_key() {
+        let base_fee: BTreeMap<Address, Amount> = decode(value)?;
+        let base_fee: BTreeMap<String, String> = base_fee
+            .into_iter()
+            .map(|(token, amount)| (token.to_string(), amount.to_string()))
+            .collect();
+        ("base_fee", json!(base_fee))
+    } else {
+        return None;
+    };
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::core::address::testing::{established_address_1, nam};
+    use namada::core::borsh::BorshSerializeExt;
+    use namada::token::storage_key::balance_key;
+
+    use super::*;
+
+    /// Test that the values of the known keys are decoded and the others are
+    /// left as is
+    #[test]
+    fn test_decode_value() {
+        let owner = established_address_1();
+        let key = balance_key(&nam(), &owner);
+        let amount = Amount::native_whole(10);
+        let decoded = decode_value(&key, &amount.serialize_to_vec()).unwrap();
+        assert_eq!(decoded["type"], "balance");
+        assert_eq!(decoded["owner"], owner.to_string());
+        assert_eq!(decoded["amount"], amount.to_string());
+
+        let key = params_storage::get_epochs_per_year_key();
+        let decoded = decode_value(&key, &365_u64.serialize_to_vec()).unwrap();
+        assert_eq!(decoded["name"], "epochs_per_year");
+        assert_eq!(decoded["value"], 365);
+
+        // An invalid value isn't decoded
+        assert!(decode_value(&key, &[1]).is_none());
+        // An unknown key isn't decoded
+        let key = Key::parse("unknown").unwrap();
+        assert!(decode_value(&key, &[1]).is_none());
+    }
+
+    /// Test the output formats of the dumped entries
+    #[test]
+    fn test_format_entry() {
+        let key = params_storage::get_epochs_per_year_key();
+        let value = 365_u64.serialize_to_vec();
+        let hex = data_encoding::HEXLOWER.encode(&value);
+        let raw_key = key.to_string();
+
+        let line = format_entry(
+            DumpDbFormat::Toml,
+            DumpDbColumnFamily::Subspace,
+            &raw_key,
+            Some(&key),
+            &value,
+        );
+        assert_eq!(line, format!("\"{raw_key}\" = \"{hex}\"\n"));
+
+        let line = format_entry(
+            DumpDbFormat::JsonLines,
+            DumpDbColumnFamily::Subspace,
+            &raw_key,
+            Some(&key),
+            &value,
+        );
+        let entry: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(entry["cf"], "subspace");
+        assert_eq!(entry["key"], raw_key);
+        assert_eq!(entry["value"], hex);
+        assert_eq!(entry["decoded"]["value"], 365);
+
+        let line = format_diff_entry(
+            DumpDbFormat::Toml,
+            &raw_key,
+            Some(&key),
+            None,
+            Some(&value),
+        );
+        assert_eq!(line, format!("\"{raw_key}\" = {{ new = \"{hex}\" }}\n"));
+
+        let line = format_diff_entry(
+            DumpDbFormat::JsonLines,
+            &raw_key,
+            Some(&key),
+            None,
+            Some(&value),
+        );
+        let entry: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(entry["old"], Value::Null);
+        assert_eq!(entry["new"], hex);
+        assert_eq!(entry["new_decoded"]["value"], 365);
+    }
+}
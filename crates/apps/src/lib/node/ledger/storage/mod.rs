@@ -1,6 +1,7 @@
 //! The storage module handles both the current state in-memory and the stored
 //! state in DB.
 
+pub mod dump;
 mod rocksdb;
 
 use std::fmt;
@@ -171,6 +172,7 @@ mod tests {
             fee_unshielding_gas_limit: 0,
             fee_unshielding_descriptions_limit: 0,
             minimum_gas_price: Default::default(),
+            base_fee_params: None,
         };
         parameters::init_storage(&params, &mut state).expect("Test failed");
         // insert and commit
@@ -670,6 +672,107 @@ mod tests {
         assert!(result.is_err(), "The bridge pool tree should be pruned");
     }
 
+    /// Commit a block at each of the given heights that either writes the
+    /// height into the `key` or deletes it and return the expected values
+    /// after each block. A new epoch is started at each of the given epoch
+    /// start heights.
+    fn commit_writes_and_deletes(
+        state: &mut PersistentState,
+        key: &Key,
+        blocks_write_value: &[bool],
+        epoch_start_heights: &[u64],
+    ) -> Vec<(BlockHeight, Option<BlockHeight>)> {
+        let mut expected = vec![];
+        for (height, write_value) in (1_u64..).zip(blocks_write_value) {
+            let height = BlockHeight(height);
+            state
+                .in_mem_mut()
+                .begin_block(BlockHash::default(), height)
+                .expect("begin_block failed");
+            if epoch_start_heights.contains(&height.0) {
+                state.in_mem_mut().block.epoch =
+                    state.in_mem().block.epoch.next();
+                state.in_mem_mut().block.pred_epochs.new_epoch(height);
+            }
+            if *write_value {
+                state.db_write(key, encode(&height)).expect("write failed");
+                expected.push((height, Some(height)));
+            } else {
+                state.db_delete(key).expect("delete failed");
+                expected.push((height, None));
+            }
+            state.commit_block().expect("commit failed");
+        }
+        expected
+    }
+
+    /// Test the pruning of the subspace diffs
+    #[test]
+    fn test_diffs_pruning() {
+        let blocks_write_value = [
+            true, true, false, true, true, true, false, false, true, true,
+        ];
+        let key = Key::parse("key").expect("cannot parse the key string");
+        let is_pruned = |result: &namada::state::Result<_>| {
+            matches!(
+                result,
+                Err(state::Error::DbError(state::DbError::PrunedHeight { .. }))
+            )
+        };
+
+        for (diffs_pruning, readable_heights) in [
+            (state::DiffsPruning::Archive, (1..=10).collect::<Vec<u64>>()),
+            (state::DiffsPruning::KeepLast { heights: 3 }, vec![8, 9, 10]),
+            (
+                state::DiffsPruning::KeepEveryNth { interval: 4 },
+                vec![4, 8, 9, 10],
+            ),
+        ] {
+            let db_path = TempDir::new()
+                .expect("Unable to create a temporary DB directory");
+            let mut state = PersistentState::open(
+                db_path.path(),
+                None,
+                ChainId::default(),
+                address::testing::nam(),
+                None,
+                is_merklized_storage_key,
+            );
+            state.in_mem_mut().diffs_pruning = diffs_pruning;
+            // The diffs after the start of the last block's epoch at height 9
+            // must not be pruned, as the block's tree is restored from them
+            let expected = commit_writes_and_deletes(
+                &mut state,
+                &key,
+                &blocks_write_value,
+                &[5, 9],
+            );
+            let tree = state
+                .get_merkle_tree(BlockHeight(10), None)
+                .expect("The last block's tree should be restored");
+            assert_eq!(tree.root(), state.in_mem().merkle_root());
+
+            for (height, expected_value) in expected {
+                let result = state.db_read_with_height(&key, height);
+                if !readable_heights.contains(&height.0) {
+                    assert!(
+                        is_pruned(&result),
+                        "Expected {height} to be pruned with \
+                         {diffs_pruning:?}, got {result:?}"
+                    );
+                    continue;
+                }
+                let (value_bytes, _gas) = result.unwrap();
+                let value: Option<BlockHeight> =
+                    value_bytes.map(|bytes| decode(bytes).unwrap());
+                assert_eq!(
+                    value, expected_value,
+                    "Unexpected value at {height} with {diffs_pruning:?}"
+                );
+            }
+        }
+    }
+
     /// Test the prefix iterator with RocksDB.
     #[test]
     fn test_persistent_storage_prefix_iter() {
@@ -34,23 +34,34 @@
 //!     - `epoch`: block epoch
 //!     - `address_gen`: established address generator
 //!     - `header`: block's header
+//!     - `rollback`: the data needed to roll back to the block
+//!       - `{metadata}`: the value of every `state` metadata key that gets
+//!         updated with every block
+//!       - `conversion_state`: MASP conversion state, only at the first height
+//!         of an epoch
+//!       - `replay_protection`: the hashes of the txs included in the block
 //! - `replay_protection`: hashes of processed tx
 //!     - `all`: the hashes included up to the last block
 //!     - `last`: the hashes included in the last block
+//!     - `expiry`: the hashes indexed by the expiration of their txs, for
+//!       pruning
+//! - `events`: the event store, laid out as described in
+//!   [`namada::state::event_store`]
 
+use std::collections::BTreeSet;
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::Path;
 use std::str::FromStr;
-use std::sync::Mutex;
+use std::sync::{mpsc, Arc};
 
 use borsh::BorshDeserialize;
 use borsh_ext::BorshSerializeExt;
-use data_encoding::HEXLOWER;
 use itertools::Either;
+use namada::core::event::Event;
 use namada::core::storage::{
-    BlockHeight, BlockResults, Epoch, EthEventsQueue, Header, Key, KeySeg,
-    KEY_SEGMENT_SEPARATOR,
+    BlockHeight, BlockResults, Epoch, Epochs, EthEventsQueue, Header, Key,
+    KeySeg, KEY_SEGMENT_SEPARATOR,
 };
 use namada::core::time::DateTimeUtc;
 use namada::core::{decode, encode, ethereum_events, ethereum_structs};
@@ -60,8 +71,11 @@ use namada::ledger::storage::tx_queue::TxQueue;
 use namada::replay_protection;
 use namada::state::merkle_tree::{base_tree_key_prefix, subtree_key_prefix};
 use namada::state::{
-    BlockStateRead, BlockStateWrite, DBIter, DBWriteBatch, DbError as Error,
-    DbResult as Result, MerkleTreeStoresRead, PrefixIterator, StoreType, DB,
+    event_store, merge_height_diffs, take_snapshot_page, BlockStateRead,
+    BlockStateWrite, DBIter, DBWriteBatch, DbError as Error,
+    DbResult as Result, DiffsPruningJob, EventIndex, HeightDiffs, MerkleTree,
+    MerkleTreeStoresRead, PrefixIterator, PrunedDiffs, SnapshotEntry,
+    SnapshotStore, StoreType, DB,
 };
 use namada::token::ConversionState;
 use rayon::prelude::*;
@@ -71,7 +85,13 @@ use rocksdb::{
     ReadOptions, WriteBatch,
 };
 
+use super::dump::{
+    format_diff_entry, format_entry, DumpDbColumnFamily, DumpDbFormat,
+    DumpDbOptions,
+};
+use super::PersistentStorageHasher;
 use crate::config::utils::num_of_threads;
+use crate::node::ledger::shell::is_merklized_storage_key;
 
 // TODO the DB schema will probably need some kind of versioning
 
@@ -85,13 +105,28 @@ const DIFFS_CF: &str = "diffs";
 const STATE_CF: &str = "state";
 const BLOCK_CF: &str = "block";
 const REPLAY_PROTECTION_CF: &str = "replay_protection";
+const EVENTS_CF: &str = "events";
 
 const OLD_DIFF_PREFIX: &str = "old";
 const NEW_DIFF_PREFIX: &str = "new";
 
-/// RocksDB handle
+/// The prefix of the block data needed to roll the block back
+const ROLLBACK_PREFIX: &str = "rollback";
+/// The metadata keys that get updated with every block, whose values are kept
+/// at every height for rollback
+const ROLLBACK_METADATA_KEYS: [&str; 6] = [
+    "next_epoch_min_start_height",
+    "next_epoch_min_start_time",
+    "update_epoch_blocks_delay",
+    "tx_queue",
+    "ethereum_height",
+    "eth_events_queue",
+];
+
+/// RocksDB handle, with the sender of the subspace diffs prunings to the
+/// thread that runs them in the background, once it's started
 #[derive(Debug)]
-pub struct RocksDB(rocksdb::DB);
+pub struct RocksDB(Arc<rocksdb::DB>, Option<mpsc::Sender<DiffsPruningJob>>);
 
 /// DB Handle for batch writes.
 #[derive(Default)]
@@ -188,8 +223,16 @@ pub fn open(
         replay_protection_cf_opts,
     ));
 
+    // for events (insert-intensive)
+    let mut events_cf_opts = Options::default();
+    events_cf_opts.set_compression_type(DBCompressionType::Zstd);
+    events_cf_opts.set_compression_options(0, 0, 0, 1024 * 1024);
+    events_cf_opts.set_compaction_style(DBCompactionStyle::Universal);
+    events_cf_opts.set_block_based_table_factory(&table_opts);
+    cfs.push(ColumnFamilyDescriptor::new(EVENTS_CF, events_cf_opts));
+
     rocksdb::DB::open_cf_descriptors(&db_opts, path, cfs)
-        .map(RocksDB)
+        .map(|db| RocksDB(Arc::new(db), None))
         .map_err(|e| Error::DBError(e.into_string()))
 }
 
@@ -206,6 +249,18 @@ impl RocksDB {
             .ok_or(Error::DBError("No {cf_name} column family".to_string()))
     }
 
+    /// Read all the subspace diffs at the given height
+    fn read_height_diffs(&self, height: BlockHeight) -> HeightDiffs {
+        let mut diffs = HeightDiffs::new();
+        for (key, old, _gas) in iter_diffs_prefix(self, height, None, true) {
+            diffs.entry(key).or_default().0 = Some(old);
+        }
+        for (key, new, _gas) in iter_diffs_prefix(self, height, None, false) {
+            diffs.entry(key).or_default().1 = Some(new);
+        }
+        diffs
+    }
+
     /// Persist the diff of an account subspace key-val under the height where
     /// it was changed.
     fn write_subspace_diff(
@@ -325,12 +380,24 @@ impl RocksDB {
             .map_err(|e| Error::DBError(e.into_string()))
     }
 
-    /// Dump last known block
+    /// Create a consistent copy of the DB in the given directory, which must
+    /// not exist. The copy hard-links the DB's files where possible.
+    pub fn checkpoint(&self, path: impl AsRef<Path>) -> Result<()> {
+        let checkpoint = rocksdb::checkpoint::Checkpoint::new(&self.0)
+            .map_err(|e| Error::DBError(e.into_string()))?;
+        checkpoint
+            .create_checkpoint(path)
+            .map_err(|e| Error::DBError(e.into_string()))
+    }
+
+    /// Dump the DB at a block height into a file. Only the selected column
+    /// families and the keys matching the prefixes are dumped, or the changes
+    /// of the subspace since an earlier height, which must be lower than the
+    /// dumped height.
     pub fn dump_block(
         &self,
         out_file_path: std::path::PathBuf,
-        historic: bool,
-        height: Option<BlockHeight>,
+        options: DumpDbOptions,
     ) {
         // Find the last block height
         let state_cf = self
@@ -345,296 +412,728 @@ impl RocksDB {
         )
         .expect("Unable to decode block height");
 
-        let height = height.unwrap_or(last_height);
+        let height = options.height.unwrap_or(last_height);
+        let heights = match options.diff_from_height {
+            Some(from_height) => format!("{from_height}_{height}"),
+            None => height.to_string(),
+        };
 
         let full_path = out_file_path
             .with_file_name(format!(
-                "{}_{height}",
+                "{}_{heights}",
                 out_file_path
                     .file_name()
                     .map(|name| name.to_string_lossy().into_owned())
                     .unwrap_or_else(|| "dump_db".to_string())
             ))
-            .with_extension("toml");
+            .with_extension(options.format.extension());
 
-        let mut file = File::options()
+        let file = File::options()
             .append(true)
             .create_new(true)
             .open(&full_path)
             .expect("Cannot open the output file");
+        let mut buf = BufWriter::new(file);
 
         println!("Will write to {} ...", full_path.to_string_lossy());
 
-        if historic {
-            // Dump the keys prepended with the selected block height (includes
-            // subspace diff keys)
-
-            // Diffs
-            let cf = self
-                .get_column_family(DIFFS_CF)
-                .expect("Diffs column family should exist");
-            let prefix = height.raw();
-            self.dump_it(cf, Some(prefix.clone()), &mut file);
-
-            // Block
-            let cf = self
-                .get_column_family(BLOCK_CF)
-                .expect("Block column family should exist");
-            self.dump_it(cf, Some(prefix), &mut file);
-        }
-
-        // subspace
-        if height != last_height {
-            // Restoring subspace at specified height
-            let restored_subspace = self
-                .iter_prefix(None)
-                .par_bridge()
-                .fold(
-                    || "".to_string(),
-                    |mut cur, (key, _value, _gas)| match self
-                        .read_subspace_val_with_height(
-                            &Key::from(key.to_db_key()),
-                            height,
-                            last_height,
-                        )
-                        .expect("Unable to find subspace key")
-                    {
-                        Some(value) => {
-                            let val = HEXLOWER.encode(&value);
-                            let new_line = format!("\"{key}\" = \"{val}\"\n");
-                            cur.push_str(new_line.as_str());
-                            cur
-                        }
-                        None => cur,
-                    },
-                )
-                .reduce(
-                    || "".to_string(),
-                    |mut a: String, b: String| {
-                        a.push_str(&b);
-                        a
-                    },
-                );
-            file.write_all(restored_subspace.as_bytes())
-                .expect("Unable to write to output file");
+        // The diff between two heights is only about the subspace
+        if let Some(from_height) = options.diff_from_height {
+            self.dump_diff(
+                &mut buf,
+                &options,
+                from_height,
+                height,
+                last_height,
+            );
         } else {
-            // Just dump the current subspace
-            let cf = self
-                .get_column_family(SUBSPACE_CF)
-                .expect("Subspace column family should exist");
-            self.dump_it(cf, None, &mut file);
-        }
-
-        // replay protection
-        // Dump of replay protection keys is possible only at the last height or
-        // the previous one
-        if height == last_height {
-            let cf = self
-                .get_column_family(REPLAY_PROTECTION_CF)
-                .expect("Replay protection column family should exist");
-            self.dump_it(cf, None, &mut file);
-        } else if height == last_height - 1 {
-            let cf = self
-                .get_column_family(REPLAY_PROTECTION_CF)
-                .expect("Replay protection column family should exist");
-            self.dump_it(cf, Some("all".to_string()), &mut file);
+            self.dump_column_families(&mut buf, &options, height, last_height);
         }
 
+        buf.flush().expect("Unable to write to output file");
         println!("Done writing to {}", full_path.to_string_lossy());
     }
 
-    /// Dump data
-    fn dump_it(
+    /// Dump the selected column families at the given height
+    fn dump_column_families(
         &self,
-        cf: &ColumnFamily,
-        prefix: Option<String>,
-        file: &mut File,
+        buf: &mut impl Write,
+        options: &DumpDbOptions,
+        height: BlockHeight,
+        last_height: BlockHeight,
     ) {
-        let read_opts = make_iter_read_opts(prefix.clone());
-        let iter = if let Some(prefix) = prefix {
-            self.0.iterator_cf_opt(
-                cf,
-                read_opts,
-                IteratorMode::From(prefix.as_bytes(), Direction::Forward),
-            )
-        } else {
-            self.0.iterator_cf_opt(cf, read_opts, IteratorMode::Start)
+        for cf in &options.column_families {
+            match cf {
+                DumpDbColumnFamily::Diffs => {
+                    // Dump the subspace diff keys prepended with the selected
+                    // block height
+                    for kind in [OLD_DIFF_PREFIX, NEW_DIFF_PREFIX] {
+                        for key_prefix in options.iter_key_prefixes() {
+                            let prefix =
+                                format!("{}/{kind}/{key_prefix}", height.raw());
+                            self.dump_it(*cf, &prefix, options.format, buf);
+                        }
+                    }
+                }
+                DumpDbColumnFamily::Block => {
+                    // Dump the keys prepended with the selected block height
+                    let prefix = format!("{}/", height.raw());
+                    self.dump_it(*cf, &prefix, options.format, buf);
+                }
+                DumpDbColumnFamily::Subspace if height != last_height => {
+                    // Restoring subspace at specified height
+                    let subspace_cf = self
+                        .get_column_family(SUBSPACE_CF)
+                        .expect("Subspace column family should exist");
+                    for key_prefix in options.iter_key_prefixes() {
+                        let restored_subspace = self
+                            .iter_raw_prefix(subspace_cf, &key_prefix)
+                            .par_bridge()
+                            .fold(
+                                || "".to_string(),
+                                |mut cur, (key, _value, _gas)| {
+                                    let storage_key = Key::parse(&key).ok();
+                                    match self
+                                        .read_subspace_val_with_height(
+                                            &Key::from(key.to_db_key()),
+                                            height,
+                                            last_height,
+                                        )
+                                        .expect("Unable to find subspace key")
+                                    {
+                                        Some(value) => {
+                                            cur.push_str(&format_entry(
+                                                options.format,
+                                                *cf,
+                                                &key,
+                                                storage_key.as_ref(),
+                                                &value,
+                                            ));
+                                            cur
+                                        }
+                                        None => cur,
+                                    }
+                                },
+                            )
+                            .reduce(
+                                || "".to_string(),
+                                |mut a: String, b: String| {
+                                    a.push_str(&b);
+                                    a
+                                },
+                            );
+                        buf.write_all(restored_subspace.as_bytes())
+                            .expect("Unable to write to output file");
+                    }
+                }
+                DumpDbColumnFamily::Subspace => {
+                    // Just dump the current subspace
+                    for key_prefix in options.iter_key_prefixes() {
+                        self.dump_it(*cf, &key_prefix, options.format, buf);
+                    }
+                }
+                DumpDbColumnFamily::State => {
+                    // The state only holds the latest metadata
+                    self.dump_it(*cf, "", options.format, buf);
+                }
+                DumpDbColumnFamily::ReplayProtection => {
+                    // Dump of replay protection keys is possible only at the
+                    // last height or the previous one
+                    if height == last_height {
+                        self.dump_it(*cf, "", options.format, buf);
+                    } else if height == last_height - 1 {
+                        self.dump_it(*cf, "all", options.format, buf);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Dump the subspace keys that changed between the two heights, with
+    /// their values at both heights
+    fn dump_diff(
+        &self,
+        buf: &mut impl Write,
+        options: &DumpDbOptions,
+        from_height: BlockHeight,
+        height: BlockHeight,
+        last_height: BlockHeight,
+    ) {
+        let diffs_cf = self
+            .get_column_family(DIFFS_CF)
+            .expect("Diffs column family should exist");
+
+        // Find the keys that changed after the first height
+        let mut changed_keys = BTreeSet::new();
+        let mut diff_height = from_height.next_height();
+        while diff_height <= height {
+            for kind in [OLD_DIFF_PREFIX, NEW_DIFF_PREFIX] {
+                let stripped_prefix = format!("{}/{kind}/", diff_height.raw());
+                for key_prefix in options.iter_key_prefixes() {
+                    let prefix = format!("{stripped_prefix}{key_prefix}");
+                    changed_keys.extend(
+                        self.iter_raw_prefix(diffs_cf, &prefix).filter_map(
+                            |(key, _value, _gas)| {
+                                key.strip_prefix(&stripped_prefix)
+                                    .map(ToOwned::to_owned)
+                            },
+                        ),
+                    );
+                }
+            }
+            diff_height = diff_height.next_height();
+        }
+
+        let read_at_height = |key: &Key, at_height: BlockHeight| {
+            let value = if at_height == last_height {
+                self.read_subspace_val(key)
+            } else {
+                self.read_subspace_val_with_height(key, at_height, last_height)
+            };
+            value.expect("Unable to read subspace key")
         };
+        for key in changed_keys {
+            let storage_key = Key::parse(&key).ok();
+            let db_key = Key::from(key.to_db_key());
+            let old = read_at_height(&db_key, from_height);
+            let new = read_at_height(&db_key, height);
+            if old != new {
+                let line = format_diff_entry(
+                    options.format,
+                    &key,
+                    storage_key.as_ref(),
+                    old.as_deref(),
+                    new.as_deref(),
+                );
+                buf.write_all(line.as_bytes())
+                    .expect("Unable to write to buffer");
+            }
+        }
+    }
 
-        let mut buf = BufWriter::new(file);
-        for (key, raw_val, _gas) in PersistentPrefixIterator(
+    /// Dump the entries of a column family under a raw key prefix
+    fn dump_it(
+        &self,
+        cf: DumpDbColumnFamily,
+        prefix: &str,
+        format: DumpDbFormat,
+        buf: &mut impl Write,
+    ) {
+        let cf_handle = self
+            .get_column_family(match cf {
+                DumpDbColumnFamily::Subspace => SUBSPACE_CF,
+                DumpDbColumnFamily::Diffs => DIFFS_CF,
+                DumpDbColumnFamily::State => STATE_CF,
+                DumpDbColumnFamily::Block => BLOCK_CF,
+                DumpDbColumnFamily::ReplayProtection => REPLAY_PROTECTION_CF,
+            })
+            .expect("The column family should exist");
+
+        for (key, raw_val, _gas) in self.iter_raw_prefix(cf_handle, prefix) {
+            // Decode the subspace keys, also from the diffs keys
+            let storage_key = match cf {
+                DumpDbColumnFamily::Subspace => Key::parse(&key).ok(),
+                DumpDbColumnFamily::Diffs => key
+                    .splitn(3, KEY_SEGMENT_SEPARATOR)
+                    .nth(2)
+                    .and_then(|key| Key::parse(key).ok()),
+                _ => None,
+            };
+            let line =
+                format_entry(format, cf, &key, storage_key.as_ref(), &raw_val);
+            buf.write_all(line.as_bytes())
+                .expect("Unable to write to buffer");
+        }
+    }
+
+    /// Read the height of the last rollback over keys that don't persist
+    /// their diffs, below which the state can't be rolled back.
+    fn read_lost_diffs_height(&self) -> Result<Option<BlockHeight>> {
+        let state_cf = self.get_column_family(STATE_CF)?;
+        self.0
+            .get_cf(state_cf, "lost_diffs_height")
+            .map_err(|e| Error::DBError(e.into_string()))?
+            .map(|bytes| decode(bytes).map_err(Error::CodingError))
+            .transpose()
+    }
+
+    /// Iterate the entries of a column family under a raw key prefix, which
+    /// is kept in the keys
+    fn iter_raw_prefix<'a>(
+        &'a self,
+        cf: &'a ColumnFamily,
+        prefix: &str,
+    ) -> PersistentPrefixIterator<'a> {
+        let read_opts = make_iter_read_opts(Some(prefix.to_owned()));
+        let iter = self.0.iterator_cf_opt(
+            cf,
+            read_opts,
+            IteratorMode::From(prefix.as_bytes(), Direction::Forward),
+        );
+        PersistentPrefixIterator(
             PrefixIterator::new(iter, String::default()),
             // Empty string to prevent prefix stripping, the prefix is
             // already in the enclosed iterator
-        ) {
-            let val = HEXLOWER.encode(&raw_val);
-            let bytes = format!("\"{key}\" = \"{val}\"\n");
-            buf.write_all(bytes.as_bytes())
-                .expect("Unable to write to buffer");
+        )
+    }
+
+    /// Check that the state can be rolled back to the given height, which
+    /// must be lower than the last committed height. The rollback is bounded
+    /// by the subspace diffs and the merkle tree stores that are available in
+    /// the DB. The keys rejected by the merkle tree key filter only keep
+    /// their last diff, so they can't be rolled back by more than one block.
+    pub fn check_rollback_height(
+        &self,
+        height: BlockHeight,
+        merkle_tree_key_filter: fn(&Key) -> bool,
+    ) -> Result<()> {
+        let last_block = self.read_last_block()?.ok_or(Error::DBError(
+            "Missing last block in storage".to_string(),
+        ))?;
+        self.check_rollback(&last_block, height, merkle_tree_key_filter)
+    }
+
+    fn check_rollback(
+        &self,
+        last_block: &BlockStateRead,
+        height: BlockHeight,
+        merkle_tree_key_filter: fn(&Key) -> bool,
+    ) -> Result<()> {
+        if height.0 == 0 || height >= last_block.height {
+            return Err(Error::DBError(format!(
+                "Cannot roll back to height {height}, it must be at least 1 \
+                 and lower than the last committed height {}",
+                last_block.height
+            )));
         }
-        buf.flush().expect("Unable to write to output file");
+        let pruned_diffs = self.read_pruned_diffs()?;
+        if pruned_diffs.is_pruned(height) {
+            return Err(Error::PrunedHeight { height });
+        }
+        // The diffs of the earlier changes of the keys that don't persist
+        // their diffs are lost once these keys have been rolled back
+        if let Some(lost_height) = self.read_lost_diffs_height()? {
+            if height < lost_height {
+                return Err(Error::DBError(format!(
+                    "Cannot roll back to height {height}, below the height \
+                     {lost_height} of a previous rollback over keys that \
+                     don't persist their diffs (the MASP and IBC counter keys)"
+                )));
+            }
+        }
+
+        // The merkle tree is restored from the base tree at the height and
+        // the sub-trees at the start of its epoch
+        let (epoch, epoch_start_height) =
+            epoch_and_start_height(&last_block.pred_epochs, height);
+        for base_height in [height, epoch_start_height] {
+            if self
+                .read_merkle_tree_stores(
+                    epoch,
+                    base_height,
+                    Some(StoreType::Base),
+                )?
+                .is_none()
+            {
+                return Err(Error::NoMerkleTree {
+                    height: base_height,
+                });
+            }
+        }
+        // A pruned sub-tree can be restored from the values at the start of
+        // the epoch, except for the bridge pool which holds the heights at
+        // which the transfers were added
+        for st in StoreType::iter_subtrees() {
+            if self
+                .read_merkle_tree_stores(epoch, epoch_start_height, Some(*st))?
+                .is_none()
+                && (*st == StoreType::BridgePool
+                    || pruned_diffs.is_pruned(epoch_start_height))
+            {
+                return Err(Error::DBError(format!(
+                    "The {st} merkle tree stores of epoch {epoch} needed to \
+                     roll back to height {height} have been pruned"
+                )));
+            }
+        }
+
+        // The blocks committed without the rollback data only have the
+        // predecessor values, which are enough to roll back a single block
+        if height < last_block.height.prev_height() {
+            let mut required = vec![(height, "tx_queue")];
+            let mut rolled_back = height.next_height();
+            while rolled_back < last_block.height {
+                required.push((rolled_back, "replay_protection"));
+                rolled_back = rolled_back.next_height();
+            }
+            if epoch != last_block.epoch {
+                required.push((epoch_start_height, "conversion_state"));
+            }
+            for (data_height, name) in required {
+                if self.read_rollback_data(data_height, name)?.is_none() {
+                    return Err(Error::DBError(format!(
+                        "Missing the {name} rollback data at height \
+                         {data_height} needed to roll back to height {height}"
+                    )));
+                }
+            }
+
+            // The earlier diffs of the keys that are not merklized are
+            // deleted when they change again, so their value at the height
+            // may be lost
+            let mut rolled_back = height.next_height();
+            while rolled_back <= last_block.height {
+                for is_old in [true, false] {
+                    for (key, _value, _gas) in
+                        iter_diffs_prefix(self, rolled_back, None, is_old)
+                    {
+                        if !merkle_tree_key_filter(&Key::from(key.to_db_key()))
+                        {
+                            return Err(Error::DBError(format!(
+                                "The key {key} changed at height \
+                                 {rolled_back} only keeps the diff of its \
+                                 last change (like all the MASP and IBC \
+                                 counter keys), so its value at height \
+                                 {height} is lost and the state can only be \
+                                 rolled back by a single block"
+                            )));
+                        }
+                    }
+                }
+                rolled_back = rolled_back.next_height();
+            }
+        }
+        Ok(())
     }
 
-    /// Rollback to previous block. Given the inner working of tendermint
-    /// rollback and of the key structure of Namada, calling rollback more than
-    /// once without restarting the chain results in a single rollback.
+    /// Roll back to the given height, which must be lower than the last
+    /// committed height. The subspace values, merkle tree stores, block
+    /// metadata, replay protection entries and tx queue are reverted to their
+    /// state at the given height.
+    ///
+    /// Only the last diff of the keys rejected by the merkle tree key filter
+    /// is kept, so the rollback is refused if any of these changed in the
+    /// rolled back blocks, unless only a single block is rolled back. Once
+    /// these keys have been rolled back, the diffs of their earlier changes
+    /// are lost, so the state can't be rolled back below the height anymore.
     pub fn rollback(
         &mut self,
-        tendermint_block_height: BlockHeight,
+        height: BlockHeight,
+        merkle_tree_key_filter: fn(&Key) -> bool,
     ) -> Result<()> {
         let last_block = self.read_last_block()?.ok_or(Error::DBError(
             "Missing last block in storage".to_string(),
         ))?;
         tracing::info!(
-            "Namada last block height: {}, Tendermint last block height: {}",
+            "Namada last block height: {}, rollback height: {}",
             last_block.height,
-            tendermint_block_height
+            height
         );
 
-        // If the block height to which tendermint rolled back matches the
-        // Namada height, there's no need to rollback
-        if tendermint_block_height == last_block.height {
+        // If the rollback height matches the Namada height, there's no need to
+        // rollback
+        if height == last_block.height {
             tracing::info!(
-                "Namada height already matches the rollback Tendermint \
-                 height, no need to rollback."
+                "Namada height already matches the rollback height, no need \
+                 to rollback."
             );
             return Ok(());
         }
+        self.check_rollback(&last_block, height, merkle_tree_key_filter)?;
 
         let mut batch = WriteBatch::default();
-        let previous_height =
-            BlockHeight::from(u64::from(last_block.height) - 1);
+        let first_rolled_back = height.next_height();
+        let after_last = last_block.height.next_height();
+        let (epoch, epoch_start_height) =
+            epoch_and_start_height(&last_block.pred_epochs, height);
 
         let state_cf = self.get_column_family(STATE_CF)?;
         // Revert the non-height-prepended metadata storage keys which get
-        // updated with every block. Because of the way we save these
-        // three keys in storage we can only perform one rollback before
-        // restarting the chain
+        // updated with every block
         tracing::info!("Reverting non-height-prepended metadata keys");
-        batch.put_cf(state_cf, "height", encode(&previous_height));
-        for metadata_key in [
-            "next_epoch_min_start_height",
-            "next_epoch_min_start_time",
-            "tx_queue",
-        ] {
-            let previous_key = format!("pred/{}", metadata_key);
-            let previous_value = self
-                .0
-                .get_cf(state_cf, previous_key.as_bytes())
-                .map_err(|e| Error::DBError(e.to_string()))?
-                .ok_or(Error::UnknownKey { key: previous_key })?;
-
-            batch.put_cf(state_cf, metadata_key, previous_value);
-            // NOTE: we cannot restore the "pred/" keys themselves since we
-            // don't have their predecessors in storage, but there's no need to
-            // since we cannot do more than one rollback anyway because of
-            // Tendermint.
+        batch.put_cf(state_cf, "height", encode(&height));
+        for metadata_key in ROLLBACK_METADATA_KEYS {
+            let value = match self.read_rollback_data(height, metadata_key)? {
+                Some(value) => value,
+                // Without the rollback data, only the predecessor values of
+                // some of the keys are available, which is checked to be
+                // enough to roll back a single block
+                None => {
+                    let previous_key = format!("pred/{}", metadata_key);
+                    match self
+                        .0
+                        .get_cf(state_cf, previous_key.as_bytes())
+                        .map_err(|e| Error::DBError(e.to_string()))?
+                    {
+                        Some(value) => value,
+                        None => {
+                            tracing::warn!(
+                                "Couldn't revert the {metadata_key} metadata \
+                                 key"
+                            );
+                            continue;
+                        }
+                    }
+                }
+            };
+            batch.put_cf(state_cf, metadata_key, value);
+            // Restore the predecessor values for the next rollback
+            if let Some(previous_value) =
+                self.read_rollback_data(height.prev_height(), metadata_key)?
+            {
+                batch.put_cf(
+                    state_cf,
+                    format!("pred/{}", metadata_key),
+                    previous_value,
+                );
+            }
         }
 
         // Revert conversion state if the epoch had been changed
-        if last_block.pred_epochs.get_epoch(previous_height)
-            != Some(last_block.epoch)
-        {
-            let previous_key = "pred/conversion_state".to_string();
-            let previous_value = self
-                .0
-                .get_cf(state_cf, previous_key.as_bytes())
-                .map_err(|e| Error::DBError(e.to_string()))?
-                .ok_or(Error::UnknownKey { key: previous_key })?;
-            batch.put_cf(state_cf, "conversion_state", previous_value);
+        if epoch != last_block.epoch {
+            let value = match self
+                .read_rollback_data(epoch_start_height, "conversion_state")?
+            {
+                Some(value) => value,
+                None => {
+                    let previous_key = "pred/conversion_state".to_string();
+                    self.0
+                        .get_cf(state_cf, previous_key.as_bytes())
+                        .map_err(|e| Error::DBError(e.to_string()))?
+                        .ok_or(Error::UnknownKey { key: previous_key })?
+                }
+            };
+            batch.put_cf(state_cf, "conversion_state", value);
         }
 
-        // Delete block results for the last block
-        let block_cf = self.get_column_family(BLOCK_CF)?;
-        tracing::info!("Removing last block results");
-        batch.delete_cf(block_cf, format!("results/{}", last_block.height));
-
-        // Delete the tx hashes included in the last block
+        // Delete the tx hashes included in the rolled back blocks
         let reprot_cf = self.get_column_family(REPLAY_PROTECTION_CF)?;
         tracing::info!("Removing replay protection hashes");
-        batch
-            .delete_cf(reprot_cf, replay_protection::last_prefix().to_string());
-
-        // Execute next step in parallel
-        let batch = Mutex::new(batch);
-
-        tracing::info!("Restoring previous height subspace diffs");
-        self.iter_prefix(None).par_bridge().try_for_each(
-            |(key, _value, _gas)| -> Result<()> {
-                // Restore previous height diff if present, otherwise delete the
-                // subspace key
-                let subspace_cf = self.get_column_family(SUBSPACE_CF)?;
-                match self.read_subspace_val_with_height(
-                    &Key::from(key.to_db_key()),
-                    previous_height,
-                    last_block.height,
-                )? {
-                    Some(previous_value) => batch.lock().unwrap().put_cf(
-                        subspace_cf,
-                        &key,
-                        previous_value,
-                    ),
-                    None => batch.lock().unwrap().delete_cf(subspace_cf, &key),
+        let mut rolled_back = first_rolled_back;
+        while rolled_back < after_last {
+            if let Some(bytes) =
+                self.read_rollback_data(rolled_back, "replay_protection")?
+            {
+                let hashes: Vec<namada::core::hash::Hash> =
+                    decode(bytes).map_err(Error::CodingError)?;
+                for hash in hashes {
+                    batch.delete_cf(
+                        reprot_cf,
+                        replay_protection::all_key(&hash).to_string(),
+                    );
+                    batch.delete_cf(
+                        reprot_cf,
+                        replay_protection::last_key(&hash).to_string(),
+                    );
                 }
+            }
+            rolled_back = rolled_back.next_height();
+        }
+        // The hashes of the last block may have been committed without the
+        // rollback data
+        let last_prefix = replay_protection::last_prefix();
+        for (key, _value, _gas) in
+            iter_prefix(self, reprot_cf, None, Some(&last_prefix))
+        {
+            batch.delete_cf(reprot_cf, key);
+        }
 
-                Ok(())
-            },
-        )?;
-
-        // Look for diffs in this block to find what has been deleted
-        let diff_new_key_prefix = Key {
-            segments: vec![
-                last_block.height.to_db_key(),
-                NEW_DIFF_PREFIX.to_string().to_db_key(),
-            ],
-        };
+        // Restore the values of the keys that changed after the height
+        tracing::info!("Restoring the subspace at the rollback height");
+        let subspace_cf = self.get_column_family(SUBSPACE_CF)?;
+        let mut changed_keys = BTreeSet::new();
+        let mut rolled_back = first_rolled_back;
+        while rolled_back < after_last {
+            for is_old in [true, false] {
+                changed_keys.extend(
+                    iter_diffs_prefix(self, rolled_back, None, is_old)
+                        .map(|(key, _value, _gas)| key),
+                );
+            }
+            rolled_back = rolled_back.next_height();
+        }
+        if changed_keys
+            .iter()
+            .any(|key| !merkle_tree_key_filter(&Key::from(key.to_db_key())))
         {
-            let mut batch_guard = batch.lock().unwrap();
-            let subspace_cf = self.get_column_family(SUBSPACE_CF)?;
-            for (key, val, _) in
-                iter_diffs_prefix(self, last_block.height, None, true)
+            batch.put_cf(state_cf, "lost_diffs_height", encode(&height));
+        }
+        for key in changed_keys {
+            match self.read_subspace_val_with_height(
+                &Key::from(key.to_db_key()),
+                height,
+                last_block.height,
+            )? {
+                Some(value) => batch.put_cf(subspace_cf, &key, value),
+                None => batch.delete_cf(subspace_cf, &key),
+            }
+        }
+
+        // Restore the sub-tree stores of the epoch of the height that have
+        // been pruned in the rolled back blocks
+        for st in StoreType::iter_subtrees() {
+            if self
+                .read_merkle_tree_stores(epoch, epoch_start_height, Some(*st))?
+                .is_none()
             {
-                let key = Key::parse(key).unwrap();
-                let diff_new_key = diff_new_key_prefix.join(&key);
-                if self.read_subspace_val(&diff_new_key)?.is_none() {
-                    // If there is no new value, it has been deleted in this
-                    // block and we have to restore it
-                    batch_guard.put_cf(subspace_cf, key.to_string(), val)
-                }
+                tracing::info!(
+                    "Restoring the {st} merkle tree stores at epoch {epoch}"
+                );
+                self.restore_subtree_stores(
+                    &mut batch,
+                    st,
+                    epoch,
+                    epoch_start_height,
+                    last_block.height,
+                )?;
             }
         }
 
-        tracing::info!("Deleting keys prepended with the last height");
-        let mut batch = batch.into_inner().unwrap();
-        let prefix = last_block.height.to_string();
-        let mut delete_keys = |cf: &ColumnFamily| {
-            let read_opts = make_iter_read_opts(Some(prefix.clone()));
-            let iter = self.0.iterator_cf_opt(
+        tracing::info!("Deleting keys prepended with the rolled back heights");
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+        let diffs_cf = self.get_column_family(DIFFS_CF)?;
+        // The height prefixes are ordered, so a single range covers all the
+        // rolled back heights
+        for cf in [block_cf, diffs_cf] {
+            batch.delete_range_cf(
                 cf,
-                read_opts,
-                IteratorMode::From(prefix.as_bytes(), Direction::Forward),
+                format!("{}/", first_rolled_back.raw()),
+                format!("{}/", after_last.raw()),
             );
-            for (key, _value, _gas) in PersistentPrefixIterator(
-                // Empty prefix string to prevent stripping
-                PrefixIterator::new(iter, String::default()),
-            ) {
-                batch.delete_cf(cf, key);
+        }
+        batch.delete_range_cf(
+            block_cf,
+            format!("results/{}", first_rolled_back.raw()),
+            format!("results/{}", after_last.raw()),
+        );
+        // Delete the events emitted in the rolled back blocks
+        let mut rolled_back = first_rolled_back;
+        while rolled_back < after_last {
+            self.batch_delete_events(&mut batch, rolled_back)?;
+            rolled_back = rolled_back.next_height();
+        }
+        // Delete the sub-tree stores of the rolled back epochs
+        let mut rolled_back_epoch = epoch.next();
+        while rolled_back_epoch <= last_block.epoch {
+            for st in StoreType::iter_subtrees() {
+                let key_prefix = subtree_key_prefix(st, rolled_back_epoch);
+                batch.delete_cf(
+                    block_cf,
+                    key_prefix
+                        .clone()
+                        .with_segment("root".to_owned())
+                        .to_string(),
+                );
+                batch.delete_cf(
+                    block_cf,
+                    key_prefix.with_segment("store".to_owned()).to_string(),
+                );
             }
-        };
-        // Delete any height-prepended key in subspace diffs
-        let diffs_cf = self.get_column_family(DIFFS_CF)?;
-        delete_keys(diffs_cf);
-        // Delete any height-prepended key in the block
-        delete_keys(block_cf);
+            rolled_back_epoch = rolled_back_epoch.next();
+        }
 
         // Write the batch and persist changes to disk
         tracing::info!("Flushing restored state to disk");
         self.exec_batch(batch)
     }
+
+    /// Delete the events emitted at the given height from the event store,
+    /// together with their index entries
+    fn batch_delete_events(
+        &self,
+        batch: &mut WriteBatch,
+        height: BlockHeight,
+    ) -> Result<()> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        for (position, event) in (0_u32..).zip(self.read_events(height)?) {
+            batch
+                .delete_cf(events_cf, event_store::event_key(height, position));
+            for index_key in
+                event_store::event_index_keys(height, position, &event)
+            {
+                batch.delete_cf(events_cf, index_key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the data needed to roll back to the given height
+    fn read_rollback_data(
+        &self,
+        height: BlockHeight,
+        name: &str,
+    ) -> Result<Option<Vec<u8>>> {
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+        self.0
+            .get_cf(block_cf, rollback_data_key(height, name))
+            .map_err(|e| Error::DBError(e.into_string()))
+    }
+
+    /// Rebuild the stores of a sub-tree at the start of an epoch from the
+    /// subspace values at that height
+    fn restore_subtree_stores(
+        &self,
+        batch: &mut WriteBatch,
+        store_type: &StoreType,
+        epoch: Epoch,
+        epoch_start_height: BlockHeight,
+        last_height: BlockHeight,
+    ) -> Result<()> {
+        // The keys present at the start of the epoch are either still present
+        // or have been deleted since
+        let mut keys: BTreeSet<String> = self
+            .iter_prefix(None)
+            .map(|(key, _value, _gas)| key)
+            .collect();
+        let mut diffs_height = epoch_start_height.next_height();
+        while diffs_height <= last_height {
+            keys.extend(
+                iter_diffs_prefix(self, diffs_height, None, true)
+                    .map(|(key, _value, _gas)| key),
+            );
+            diffs_height = diffs_height.next_height();
+        }
+
+        let mut tree = MerkleTree::<PersistentStorageHasher>::default();
+        for key in keys {
+            let key = Key::parse(key).map_err(Error::KeyError)?;
+            if !is_merklized_storage_key(&key)
+                || StoreType::sub_key(&key)?.0 != *store_type
+            {
+                continue;
+            }
+            if let Some(value) = self.read_subspace_val_with_height(
+                &key,
+                epoch_start_height,
+                last_height,
+            )? {
+                tree.update(&key, value)?;
+            }
+        }
+
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+        let stores = tree.stores();
+        let key_prefix = subtree_key_prefix(store_type, epoch);
+        batch.put_cf(
+            block_cf,
+            key_prefix
+                .clone()
+                .with_segment("root".to_owned())
+                .to_string(),
+            encode(stores.root(store_type)),
+        );
+        batch.put_cf(
+            block_cf,
+            key_prefix.with_segment("store".to_owned()).to_string(),
+            stores.store(store_type).encode(),
+        );
+        Ok(())
+    }
 }
 
 impl DB for RocksDB {
     type Cache = rocksdb::Cache;
+    type Checkpoint = Self;
     type WriteBatch = RocksDBWriteBatch;
 
     fn open(
@@ -644,6 +1143,11 @@ impl DB for RocksDB {
         open(db_path, cache).expect("cannot open the DB")
     }
 
+    fn checkpoint_copy(&self, path: &std::path::Path) -> Result<Self> {
+        self.checkpoint(path)?;
+        open(path, None)
+    }
+
     fn flush(&self, wait: bool) -> Result<()> {
         let mut flush_opts = FlushOptions::default();
         flush_opts.set_wait(wait);
@@ -819,8 +1323,9 @@ impl DB for RocksDB {
                         }
                         None => unknown_key_error(path)?,
                     },
-                    "header" => {
-                        // the block header doesn't have to be restored
+                    "header" | ROLLBACK_PREFIX => {
+                        // the block header and the data needed to roll the
+                        // block back don't have to be restored
                     }
                     "hash" => {
                         hash = Some(decode(bytes).map_err(Error::CodingError)?)
@@ -1102,6 +1607,32 @@ impl DB for RocksDB {
                 .put_cf(block_cf, key.to_string(), encode(&address_gen));
         }
 
+        // The metadata at this height, needed to roll back to it
+        {
+            let metadata = [
+                encode(&next_epoch_min_start_height),
+                encode(&next_epoch_min_start_time),
+                encode(&update_epoch_blocks_delay),
+                encode(&tx_queue),
+                encode(&ethereum_height),
+                encode(&eth_events_queue),
+            ];
+            for (name, value) in ROLLBACK_METADATA_KEYS.iter().zip(metadata) {
+                batch.0.put_cf(
+                    block_cf,
+                    rollback_data_key(height, name),
+                    value,
+                );
+            }
+            if is_full_commit {
+                batch.0.put_cf(
+                    block_cf,
+                    rollback_data_key(height, "conversion_state"),
+                    encode(conversion_state),
+                );
+            }
+        }
+
         // Block height
         batch.0.put_cf(state_cf, "height", encode(&height));
 
@@ -1228,6 +1759,10 @@ impl DB for RocksDB {
         height: BlockHeight,
         last_height: BlockHeight,
     ) -> Result<Option<Vec<u8>>> {
+        if self.read_pruned_diffs()?.is_pruned(height) {
+            return Err(Error::PrunedHeight { height });
+        }
+
         // Check if the value changed at this height
         let diffs_cf = self.get_column_family(DIFFS_CF)?;
         let (old_val_key, new_val_key) = old_and_new_diff_key(key, height)?;
@@ -1533,14 +2068,415 @@ impl DB for RocksDB {
 
         Ok(())
     }
-}
 
-impl<'iter> DBIter<'iter> for RocksDB {
-    type PrefixIter = PersistentPrefixIterator<'iter>;
+    fn prune_replay_protection(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        up_to: &DateTimeUtc,
+    ) -> Result<()> {
+        let reprot_cf = self.get_column_family(REPLAY_PROTECTION_CF)?;
+        let expiry_prefix = replay_protection::expiry_prefix();
+        // The index is ordered by expiration, so stop at the first tx that
+        // hasn't expired yet
+        for (key, _value, _gas) in
+            iter_prefix(self, reprot_cf, None, Some(&expiry_prefix))
+        {
+            let Some((seconds, hash)) =
+                replay_protection::parse_expiry_key(&key)
+            else {
+                continue;
+            };
+            if seconds > up_to.0.timestamp() {
+                break;
+            }
+            for subkey in [
+                replay_protection::all_key(&hash),
+                replay_protection::last_key(&hash),
+            ] {
+                batch.0.delete_cf(reprot_cf, subkey.to_string());
+            }
+            batch.0.delete_cf(reprot_cf, key);
+        }
+        Ok(())
+    }
 
-    fn iter_prefix(
-        &'iter self,
-        prefix: Option<&Key>,
+    fn write_replay_protection_block_hashes(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        hashes: &[namada::core::hash::Hash],
+    ) -> Result<()> {
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+
+        batch.0.put_cf(
+            block_cf,
+            rollback_data_key(height, "replay_protection"),
+            encode(&hashes),
+        );
+
+        Ok(())
+    }
+
+    fn read_snapshot_page(
+        &self,
+        store: SnapshotStore,
+        start_after: Option<&str>,
+        max_bytes: usize,
+    ) -> Result<Vec<SnapshotEntry>> {
+        match store {
+            SnapshotStore::State => {
+                let state_cf = self.get_column_family(STATE_CF)?;
+                let entries = iter_prefix(self, state_cf, None, None)
+                    .map(|(key, value, _gas)| (key, value));
+                Ok(take_snapshot_page(entries, start_after, max_bytes))
+            }
+            SnapshotStore::Block => {
+                let block_cf = self.get_column_family(BLOCK_CF)?;
+                let last_block = match self.read_last_block()? {
+                    Some(last_block) => last_block,
+                    None => return Ok(vec![]),
+                };
+                // The last block's metadata, including its base tree
+                let height_prefix = Key::from(last_block.height.to_db_key());
+                let mut entries: Vec<SnapshotEntry> =
+                    iter_prefix(self, block_cf, None, Some(&height_prefix))
+                        .map(|(key, value, _gas)| (key, value))
+                        .collect();
+                // The last block's results
+                let results_key =
+                    format!("results/{}", last_block.height.raw());
+                if let Some(value) = self
+                    .0
+                    .get_cf(block_cf, &results_key)
+                    .map_err(|e| Error::DBError(e.into_string()))?
+                {
+                    entries.push((results_key, value));
+                }
+                // The Merkle tree stores written at the start of the last
+                // block's epoch, from which its tree is restored
+                let (epoch, epoch_start_height) = epoch_and_start_height(
+                    &last_block.pred_epochs,
+                    last_block.height,
+                );
+                for st in StoreType::iter() {
+                    let key_prefix = if *st == StoreType::Base {
+                        base_tree_key_prefix(epoch_start_height)
+                    } else {
+                        subtree_key_prefix(st, epoch)
+                    };
+                    for key in [
+                        key_prefix.clone().with_segment("root".to_owned()),
+                        key_prefix.with_segment("store".to_owned()),
+                    ] {
+                        let key = key.to_string();
+                        if let Some(value) = self
+                            .0
+                            .get_cf(block_cf, &key)
+                            .map_err(|e| Error::DBError(e.into_string()))?
+                        {
+                            entries.push((key, value));
+                        }
+                    }
+                }
+                entries.sort();
+                entries.dedup();
+                Ok(take_snapshot_page(entries, start_after, max_bytes))
+            }
+            SnapshotStore::Diffs
+            | SnapshotStore::Subspace
+            | SnapshotStore::ReplayProtection => {
+                let first_key = if store == SnapshotStore::Diffs {
+                    let last_block = match self.read_last_block()? {
+                        Some(last_block) => last_block,
+                        None => return Ok(vec![]),
+                    };
+                    let (_epoch, epoch_start_height) = epoch_and_start_height(
+                        &last_block.pred_epochs,
+                        last_block.height,
+                    );
+                    // The height prefixes are ordered, so the diffs after the
+                    // epoch's start are all the diffs from the next height on
+                    Some(format!("{}/", epoch_start_height.next_height().raw()))
+                } else {
+                    None
+                };
+                let start = match (start_after, first_key.as_deref()) {
+                    (Some(start), Some(first)) => Some(start.max(first)),
+                    (start, first) => start.or(first),
+                };
+                let cf = self.get_column_family(snapshot_store_cf(store))?;
+                let read_opts = make_iter_read_opts(None);
+                let iter = match start {
+                    Some(start) => self.0.iterator_cf_opt(
+                        cf,
+                        read_opts,
+                        IteratorMode::From(
+                            start.as_bytes(),
+                            Direction::Forward,
+                        ),
+                    ),
+                    None => self.0.iterator_cf_opt(
+                        cf,
+                        read_opts,
+                        IteratorMode::Start,
+                    ),
+                };
+                let entries = PersistentPrefixIterator(
+                    // Empty string to prevent prefix stripping
+                    PrefixIterator::new(iter, String::default()),
+                )
+                .map(|(key, value, _gas)| (key, value));
+                Ok(take_snapshot_page(entries, start_after, max_bytes))
+            }
+        }
+    }
+
+    fn batch_write_snapshot_entries(
+        &self,
+        batch: &mut Self::WriteBatch,
+        store: SnapshotStore,
+        entries: &[SnapshotEntry],
+    ) -> Result<()> {
+        let cf = self.get_column_family(snapshot_store_cf(store))?;
+        for (key, value) in entries {
+            batch.0.put_cf(cf, key, value);
+        }
+        Ok(())
+    }
+
+    fn batch_wipe_snapshot_store(
+        &self,
+        batch: &mut Self::WriteBatch,
+        store: SnapshotStore,
+    ) -> Result<()> {
+        let cf = self.get_column_family(snapshot_store_cf(store))?;
+        for key in self.0.iterator_cf(cf, IteratorMode::Start) {
+            let (key, _value) =
+                key.map_err(|e| Error::DBError(e.into_string()))?;
+            batch.0.delete_cf(cf, key);
+        }
+        Ok(())
+    }
+
+    fn read_pruned_diffs(&self) -> Result<PrunedDiffs> {
+        let state_cf = self.get_column_family(STATE_CF)?;
+        match self
+            .0
+            .get_cf(state_cf, "pruned_diffs")
+            .map_err(|e| Error::DBError(e.into_string()))?
+        {
+            Some(bytes) => decode(bytes).map_err(Error::CodingError),
+            None => Ok(PrunedDiffs::default()),
+        }
+    }
+
+    fn batch_write_pruned_diffs(
+        &self,
+        batch: &mut Self::WriteBatch,
+        pruned: &PrunedDiffs,
+    ) -> Result<()> {
+        let state_cf = self.get_column_family(STATE_CF)?;
+        batch.0.put_cf(state_cf, "pruned_diffs", encode(pruned));
+        Ok(())
+    }
+
+    fn prune_diffs(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> Result<()> {
+        let diffs_cf = self.get_column_family(DIFFS_CF)?;
+        // The height prefixes are ordered, so a single range covers all the
+        // diffs at the pruned heights
+        batch.0.delete_range_cf(
+            diffs_cf,
+            format!("{}/", from.raw()),
+            format!("{}/", to.next_height().raw()),
+        );
+        Ok(())
+    }
+
+    fn merge_diffs(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        from: BlockHeight,
+        into: BlockHeight,
+    ) -> Result<()> {
+        let mut merged = self.read_height_diffs(into);
+        let mut height = into;
+        while height > from {
+            height = height.prev_height();
+            merge_height_diffs(&mut merged, self.read_height_diffs(height));
+        }
+        // Replace the diffs at all the merged heights with the merged diffs
+        self.prune_diffs(batch, from, into)?;
+        let diffs_cf = self.get_column_family(DIFFS_CF)?;
+        for (key, (old, new)) in merged {
+            if let Some(old) = old {
+                batch.0.put_cf(
+                    diffs_cf,
+                    format!("{}/{OLD_DIFF_PREFIX}/{key}", into.raw()),
+                    old,
+                );
+            }
+            if let Some(new) = new {
+                batch.0.put_cf(
+                    diffs_cf,
+                    format!("{}/{NEW_DIFF_PREFIX}/{key}", into.raw()),
+                    new,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn run_diffs_pruning(&mut self, job: DiffsPruningJob) -> Result<()> {
+        if self.1.is_none() {
+            let db = RocksDB(self.0.clone(), None);
+            let (sender, jobs) = mpsc::channel();
+            std::thread::Builder::new()
+                .name("diffs-pruning".to_string())
+                .spawn(move || run_diffs_pruning_jobs(db, jobs))
+                .map_err(|e| Error::DBError(e.to_string()))?;
+            self.1 = Some(sender);
+        }
+        self.1
+            .as_ref()
+            .expect("The diffs pruning thread must be started")
+            .send(job)
+            .map_err(|_| {
+                Error::DBError(
+                    "The diffs pruning thread has stopped".to_string(),
+                )
+            })
+    }
+
+    fn batch_write_events(
+        &self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        events: &[Event],
+    ) -> Result<()> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        for (position, event) in (0_u32..).zip(events) {
+            batch.0.put_cf(
+                events_cf,
+                event_store::event_key(height, position),
+                encode(event),
+            );
+            for index_key in
+                event_store::event_index_keys(height, position, event)
+            {
+                batch.0.put_cf(events_cf, index_key, b"");
+            }
+        }
+        Ok(())
+    }
+
+    fn read_events(&self, height: BlockHeight) -> Result<Vec<Event>> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        self.iter_raw_prefix(
+            events_cf,
+            &event_store::height_events_prefix(height),
+        )
+        .map(|(_key, bytes, _gas)| decode(bytes).map_err(Error::CodingError))
+        .collect()
+    }
+
+    fn read_event(
+        &self,
+        height: BlockHeight,
+        position: u32,
+    ) -> Result<Option<Event>> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        self.0
+            .get_cf(events_cf, event_store::event_key(height, position))
+            .map_err(|e| Error::DBError(e.into_string()))?
+            .map(|bytes| decode(bytes).map_err(Error::CodingError))
+            .transpose()
+    }
+
+    fn read_event_index(
+        &self,
+        index: &EventIndex,
+        from: BlockHeight,
+        before: (BlockHeight, u32),
+        limit: usize,
+    ) -> Result<Vec<(BlockHeight, u32)>> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        let (start, end) = event_store::index_range(index, from, before);
+        if start >= end {
+            return Ok(vec![]);
+        }
+        let mut read_opts = make_iter_read_opts(None);
+        read_opts.set_iterate_lower_bound(start);
+        read_opts.set_iterate_upper_bound(end);
+        let mut positions = vec![];
+        for entry in
+            self.0
+                .iterator_cf_opt(events_cf, read_opts, IteratorMode::End)
+        {
+            if positions.len() >= limit {
+                break;
+            }
+            let (key, _value) =
+                entry.map_err(|e| Error::DBError(e.into_string()))?;
+            let key = String::from_utf8_lossy(&key);
+            if let Some(position) = event_store::parse_event_position(&key) {
+                positions.push(position);
+            }
+        }
+        Ok(positions)
+    }
+
+    fn read_pruned_events_height(&self) -> Result<Option<BlockHeight>> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        self.0
+            .get_cf(events_cf, event_store::PRUNED_EVENTS_KEY)
+            .map_err(|e| Error::DBError(e.into_string()))?
+            .map(|bytes| decode(bytes).map_err(Error::CodingError))
+            .transpose()
+    }
+
+    fn prune_events(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> Result<()> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        // The entries of the log and of every index are ordered by height, so
+        // a single range covers the pruned heights of each of them
+        let mut prefixes = BTreeSet::from([event_store::events_prefix()]);
+        let mut height = from;
+        while height <= to {
+            for event in self.read_events(height)? {
+                prefixes.extend(
+                    event_store::event_indices(&event)
+                        .iter()
+                        .map(EventIndex::prefix),
+                );
+            }
+            height = height.next_height();
+        }
+        for prefix in prefixes {
+            let (start, end) = event_store::height_range(&prefix, from, to);
+            batch.0.delete_range_cf(events_cf, start, end);
+        }
+        batch
+            .0
+            .put_cf(events_cf, event_store::PRUNED_EVENTS_KEY, encode(&to));
+        Ok(())
+    }
+}
+
+impl<'iter> DBIter<'iter> for RocksDB {
+    type PrefixIter = PersistentPrefixIterator<'iter>;
+
+    fn iter_prefix(
+        &'iter self,
+        prefix: Option<&Key>,
     ) -> PersistentPrefixIterator<'iter> {
         iter_subspace_prefix(self, prefix)
     }
@@ -1719,6 +2655,61 @@ fn old_and_new_diff_key(
     Ok((old.to_string(), new.to_string()))
 }
 
+/// Run the subspace diffs prunings in the order in which they are received,
+/// until the DB that sends them is dropped. A failed pruning only leaves
+/// behind diffs that cannot be read anymore.
+fn run_diffs_pruning_jobs(
+    mut db: RocksDB,
+    jobs: mpsc::Receiver<DiffsPruningJob>,
+) {
+    for job in jobs {
+        let mut batch = RocksDB::batch();
+        let result = match job {
+            DiffsPruningJob::Prune { from, to } => {
+                db.prune_diffs(&mut batch, from, to)
+            }
+            DiffsPruningJob::Merge { from, into } => {
+                db.merge_diffs(&mut batch, from, into)
+            }
+        }
+        .and_then(|()| DB::exec_batch(&mut db, batch));
+        if let Err(err) = result {
+            tracing::error!("Failed to prune the subspace diffs: {err}");
+        }
+    }
+}
+
+/// The key of the data needed to roll back the block at the given height
+fn rollback_data_key(height: BlockHeight, name: &str) -> String {
+    format!("{}/{ROLLBACK_PREFIX}/{name}", height.raw())
+}
+
+/// Get the epoch of the given height and the height at which the merkle tree
+/// stores of the epoch have been written
+fn epoch_and_start_height(
+    pred_epochs: &Epochs,
+    height: BlockHeight,
+) -> (Epoch, BlockHeight) {
+    let epoch = pred_epochs.get_epoch(height).unwrap_or_default();
+    let epoch_start_height = match pred_epochs.get_start_height_of_epoch(epoch)
+    {
+        Some(BlockHeight(0)) | None => BlockHeight(1),
+        Some(height) => height,
+    };
+    (epoch, epoch_start_height)
+}
+
+/// Get the name of the column family of the given snapshot store
+fn snapshot_store_cf(store: SnapshotStore) -> &'static str {
+    match store {
+        SnapshotStore::State => STATE_CF,
+        SnapshotStore::Block => BLOCK_CF,
+        SnapshotStore::Diffs => DIFFS_CF,
+        SnapshotStore::Subspace => SUBSPACE_CF,
+        SnapshotStore::ReplayProtection => REPLAY_PROTECTION_CF,
+    }
+}
+
 fn unknown_key_error(key: &str) -> Result<()> {
     Err(Error::UnknownKey {
         key: key.to_owned(),
@@ -1996,6 +2987,7 @@ mod test {
         let mut batch = RocksDB::batch();
         let height_0 = BlockHeight(100);
         let mut pred_epochs = Epochs::default();
+        pred_epochs.new_epoch(BlockHeight(1));
         pred_epochs.new_epoch(height_0);
         let mut conversion_state_0 = ConversionState::default();
         conversion_state_0
@@ -2076,7 +3068,7 @@ mod test {
         assert_eq!(deleted, None);
 
         // Rollback to the first block height
-        db.rollback(height_0).unwrap();
+        db.rollback(height_0, |_| true).unwrap();
 
         // Check that the values are back to the state at the first block
         let added = db.read_subspace_val(&add_key).unwrap();
@@ -2094,6 +3086,353 @@ mod test {
         assert_eq!(conversion_state, encode(&conversion_state_0));
     }
 
+    /// Test that the state can be rolled back by multiple blocks, across an
+    /// epoch boundary
+    #[test]
+    fn test_rollback_multiple_blocks() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path(), None).unwrap();
+
+        let key_a = Key::parse("a").unwrap();
+        let key_b = Key::parse("b").unwrap();
+        let key_c = Key::parse("c").unwrap();
+        let key_d = Key::parse("d").unwrap();
+        let hash_2 = namada::core::hash::Hash::sha256("tx2");
+        let hash_3 = namada::core::hash::Hash::sha256("tx3");
+        let hash_4 = namada::core::hash::Hash::sha256("tx4");
+        let mut conversion_state_0 = ConversionState::default();
+        conversion_state_0
+            .tokens
+            .insert("dummy1".to_string(), gen_established_address("test"));
+        let mut conversion_state_1 = ConversionState::default();
+        conversion_state_1
+            .tokens
+            .insert("dummy2".to_string(), gen_established_address("test"));
+
+        // Epoch 0 starts at height 1, epoch 1 at height 3
+        let mut pred_epochs = Epochs::default();
+        pred_epochs.new_epoch(BlockHeight(1));
+
+        // Height 1
+        let mut batch = RocksDB::batch();
+        let height = BlockHeight(1);
+        db.batch_write_subspace_val(&mut batch, height, &key_a, [1_u8], true)
+            .unwrap();
+        db.batch_write_subspace_val(&mut batch, height, &key_b, [1_u8], true)
+            .unwrap();
+        add_block_to_batch(
+            &db,
+            &mut batch,
+            height,
+            Epoch(0),
+            pred_epochs.clone(),
+            &conversion_state_0,
+        )
+        .unwrap();
+        db.exec_batch(batch.0).unwrap();
+
+        // Height 2, the rollback target
+        let mut batch = RocksDB::batch();
+        let target = BlockHeight(2);
+        db.batch_write_subspace_val(&mut batch, target, &key_a, [2_u8], true)
+            .unwrap();
+        db.batch_write_subspace_val(&mut batch, target, &key_c, [1_u8], true)
+            .unwrap();
+        db.write_replay_protection_entry(
+            &mut batch,
+            &replay_protection::last_key(&hash_2),
+        )
+        .unwrap();
+        db.write_replay_protection_block_hashes(&mut batch, target, &[hash_2])
+            .unwrap();
+        add_block_to_batch(
+            &db,
+            &mut batch,
+            target,
+            Epoch(0),
+            pred_epochs.clone(),
+            &conversion_state_0,
+        )
+        .unwrap();
+        db.exec_batch(batch.0).unwrap();
+
+        // Height 3, a new epoch
+        let mut batch = RocksDB::batch();
+        let height = BlockHeight(3);
+        pred_epochs.new_epoch(height);
+        db.batch_write_subspace_val(&mut batch, height, &key_a, [3_u8], true)
+            .unwrap();
+        db.batch_delete_subspace_val(&mut batch, height, &key_b, true)
+            .unwrap();
+        db.write_replay_protection_entry(
+            &mut batch,
+            &replay_protection::all_key(&hash_2),
+        )
+        .unwrap();
+        db.delete_replay_protection_entry(
+            &mut batch,
+            &replay_protection::last_key(&hash_2),
+        )
+        .unwrap();
+        db.write_replay_protection_entry(
+            &mut batch,
+            &replay_protection::last_key(&hash_3),
+        )
+        .unwrap();
+        db.write_replay_protection_block_hashes(&mut batch, height, &[hash_3])
+            .unwrap();
+        // The account sub-tree of the previous epoch is pruned
+        db.prune_merkle_tree_store(&mut batch, &StoreType::Account, Epoch(0))
+            .unwrap();
+        add_block_to_batch(
+            &db,
+            &mut batch,
+            height,
+            Epoch(1),
+            pred_epochs.clone(),
+            &conversion_state_1,
+        )
+        .unwrap();
+        db.exec_batch(batch.0).unwrap();
+
+        // Height 4
+        let mut batch = RocksDB::batch();
+        let last_height = BlockHeight(4);
+        db.batch_write_subspace_val(
+            &mut batch,
+            last_height,
+            &key_c,
+            [2_u8],
+            true,
+        )
+        .unwrap();
+        db.batch_write_subspace_val(
+            &mut batch,
+            last_height,
+            &key_d,
+            [1_u8],
+            true,
+        )
+        .unwrap();
+        db.write_replay_protection_entry(
+            &mut batch,
+            &replay_protection::all_key(&hash_3),
+        )
+        .unwrap();
+        db.delete_replay_protection_entry(
+            &mut batch,
+            &replay_protection::last_key(&hash_3),
+        )
+        .unwrap();
+        db.write_replay_protection_entry(
+            &mut batch,
+            &replay_protection::last_key(&hash_4),
+        )
+        .unwrap();
+        db.write_replay_protection_block_hashes(
+            &mut batch,
+            last_height,
+            &[hash_4],
+        )
+        .unwrap();
+        add_block_to_batch(
+            &db,
+            &mut batch,
+            last_height,
+            Epoch(1),
+            pred_epochs,
+            &conversion_state_1,
+        )
+        .unwrap();
+        db.exec_batch(batch.0).unwrap();
+
+        // Only heights below the last one can be rolled back to
+        db.check_rollback_height(BlockHeight(0), |_| true)
+            .unwrap_err();
+        db.check_rollback_height(last_height, |_| true).unwrap_err();
+        db.check_rollback_height(target, |_| true).unwrap();
+        // The keys that are not merklized can't be rolled back by more than
+        // one block
+        let not_merklized_c = |key: &Key| key.to_string() != "c";
+        db.check_rollback_height(target, not_merklized_c)
+            .unwrap_err();
+        db.check_rollback_height(BlockHeight(3), not_merklized_c)
+            .unwrap();
+
+        db.rollback(target, |_| true).unwrap();
+
+        // Check the subspace
+        assert_eq!(db.read_subspace_val(&key_a).unwrap(), Some(vec![2]));
+        assert_eq!(db.read_subspace_val(&key_b).unwrap(), Some(vec![1]));
+        assert_eq!(db.read_subspace_val(&key_c).unwrap(), Some(vec![1]));
+        assert_eq!(db.read_subspace_val(&key_d).unwrap(), None);
+        // Check the replay protection
+        assert!(db.has_replay_protection_entry(&hash_2).unwrap());
+        assert!(!db.has_replay_protection_entry(&hash_3).unwrap());
+        assert!(!db.has_replay_protection_entry(&hash_4).unwrap());
+        // Check the conversion state
+        let state_cf = db.get_column_family(STATE_CF).unwrap();
+        let conversion_state =
+            db.0.get_cf(state_cf, "conversion_state".as_bytes())
+                .unwrap()
+                .unwrap();
+        assert_eq!(conversion_state, encode(&conversion_state_0));
+        // Check that the rolled back blocks have been deleted
+        let last_block = db.read_last_block().unwrap().unwrap();
+        assert_eq!(last_block.height, target);
+        assert_eq!(last_block.epoch, Epoch(0));
+        let block_cf = db.get_column_family(BLOCK_CF).unwrap();
+        for height in [BlockHeight(3), last_height] {
+            let results_key = format!("results/{}", height.raw());
+            assert!(db.0.get_cf(block_cf, results_key).unwrap().is_none());
+            assert!(db.read_block_header(height).unwrap().is_none());
+            assert_eq!(iter_diffs_prefix(&db, height, None, false).count(), 0);
+        }
+        assert!(db
+            .read_merkle_tree_stores(
+                Epoch(1),
+                BlockHeight(3),
+                Some(StoreType::PoS)
+            )
+            .unwrap()
+            .is_none());
+        // Check that the pruned account sub-tree has been restored from the
+        // values at the start of the epoch
+        let mut expected = MerkleTree::<PersistentStorageHasher>::default();
+        expected.update(&key_a, [1_u8]).unwrap();
+        expected.update(&key_b, [1_u8]).unwrap();
+        let stores = db
+            .read_merkle_tree_stores(
+                Epoch(0),
+                BlockHeight(1),
+                Some(StoreType::Account),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            stores.get_root(StoreType::Account),
+            *expected.stores().root(&StoreType::Account)
+        );
+
+        // The state can't be rolled back to the same height again
+        db.check_rollback_height(target, |_| true).unwrap_err();
+    }
+
+    /// Test that a key that doesn't persist its diffs can't be rolled back
+    /// below the height of a previous rollback, which lost its earlier diffs
+    #[test]
+    fn test_rollback_non_persisted_diffs() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path(), None).unwrap();
+
+        let key = Key::parse("masp").unwrap();
+        let not_merklized = |_: &Key| false;
+        let mut pred_epochs = Epochs::default();
+        pred_epochs.new_epoch(BlockHeight(1));
+
+        for value in 1..=3_u8 {
+            let height = BlockHeight(value.into());
+            let mut batch = RocksDB::batch();
+            db.batch_write_subspace_val(
+                &mut batch,
+                height,
+                &key,
+                [value],
+                false,
+            )
+            .unwrap();
+            add_block_to_batch(
+                &db,
+                &mut batch,
+                height,
+                Epoch(0),
+                pred_epochs.clone(),
+                &ConversionState::default(),
+            )
+            .unwrap();
+            db.exec_batch(batch.0).unwrap();
+        }
+
+        // Only the last diff is kept, so only the last block can be rolled
+        // back
+        db.check_rollback_height(BlockHeight(1), not_merklized)
+            .unwrap_err();
+        db.rollback(BlockHeight(2), not_merklized).unwrap();
+        assert_eq!(db.read_subspace_val(&key).unwrap(), Some(vec![2]));
+
+        // The diff of the change at height 2 was deleted by the change at the
+        // rolled back height 3
+        db.check_rollback_height(BlockHeight(1), not_merklized)
+            .unwrap_err();
+    }
+
+    /// Test the dump of the changes of the subspace between two heights
+    #[test]
+    fn test_dump_diff() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path(), None).unwrap();
+
+        let key_a = Key::parse("a").unwrap();
+        let key_b = Key::parse("b").unwrap();
+        let key_c = Key::parse("c").unwrap();
+        let mut pred_epochs = Epochs::default();
+        pred_epochs.new_epoch(BlockHeight(1));
+
+        let mut batch = RocksDB::batch();
+        let height_1 = BlockHeight(1);
+        db.batch_write_subspace_val(&mut batch, height_1, &key_a, [1_u8], true)
+            .unwrap();
+        db.batch_write_subspace_val(&mut batch, height_1, &key_b, [1_u8], true)
+            .unwrap();
+        add_block_to_batch(
+            &db,
+            &mut batch,
+            height_1,
+            Epoch(0),
+            pred_epochs.clone(),
+            &ConversionState::default(),
+        )
+        .unwrap();
+        db.exec_batch(batch.0).unwrap();
+
+        let mut batch = RocksDB::batch();
+        let height_2 = BlockHeight(2);
+        db.batch_write_subspace_val(&mut batch, height_2, &key_a, [2_u8], true)
+            .unwrap();
+        db.batch_write_subspace_val(&mut batch, height_2, &key_c, [1_u8], true)
+            .unwrap();
+        add_block_to_batch(
+            &db,
+            &mut batch,
+            height_2,
+            Epoch(0),
+            pred_epochs,
+            &ConversionState::default(),
+        )
+        .unwrap();
+        db.exec_batch(batch.0).unwrap();
+
+        // Only the keys matching the prefixes are dumped
+        let options = DumpDbOptions {
+            diff_from_height: Some(height_1),
+            key_prefixes: vec!["a".to_string(), "b".to_string()],
+            format: DumpDbFormat::JsonLines,
+            ..Default::default()
+        };
+        db.dump_block(dir.path().join("dump"), options);
+
+        let dump =
+            std::fs::read_to_string(dir.path().join("dump_1_2.jsonl")).unwrap();
+        let entries: Vec<serde_json::Value> = dump
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["key"], "a");
+        assert_eq!(entries[0]["old"], "01");
+        assert_eq!(entries[0]["new"], "02");
+    }
+
     #[test]
     fn test_diffs() {
         let dir = tempdir().unwrap();
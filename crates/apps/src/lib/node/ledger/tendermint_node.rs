@@ -217,21 +217,31 @@ pub fn reset(tendermint_dir: impl AsRef<Path>) -> Result<()> {
     Ok(())
 }
 
-pub fn rollback(tendermint_dir: impl AsRef<Path>) -> Result<BlockHeight> {
+/// Roll back the CometBFT state by one block and return the height it has
+/// been rolled back to. With `hard`, the last block is also removed from the
+/// block store, which allows to roll back again.
+pub fn rollback(
+    tendermint_dir: impl AsRef<Path>,
+    hard: bool,
+) -> Result<BlockHeight> {
     let tendermint_path = from_env_or_default()?;
     let tendermint_dir = tendermint_dir.as_ref().to_string_lossy();
 
     // Rollback tendermint state, see https://github.com/tendermint/tendermint/blob/main/cmd/tendermint/commands/rollback.go for details
     // on how the tendermint rollback behaves
+    let mut args = vec![
+        "rollback",
+        "unsafe-all",
+        // NOTE: log config: https://docs.tendermint.com/master/nodes/logging.html#configuring-log-levels
+        // "--log-level=\"*debug\"",
+        "--home",
+        &tendermint_dir,
+    ];
+    if hard {
+        args.push("--hard");
+    }
     let output = std::process::Command::new(tendermint_path)
-        .args([
-            "rollback",
-            "unsafe-all",
-            // NOTE: log config: https://docs.tendermint.com/master/nodes/logging.html#configuring-log-levels
-            // "--log-level=\"*debug\"",
-            "--home",
-            &tendermint_dir,
-        ])
+        .args(args)
         .output()
         .map_err(|e| Error::RollBack(e.to_string()))?;
 
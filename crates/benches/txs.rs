@@ -381,6 +381,7 @@ fn update_account(c: &mut Criterion) {
                 .into(),
         )),
         public_keys: vec![defaults::albert_keypair().ref_to()],
+        public_key_weights: vec![],
         threshold: None,
     };
     let vp = shell.generate_tx(
@@ -423,6 +424,7 @@ fn init_account(c: &mut Criterion) {
     );
     let data = InitAccount {
         public_keys: vec![new_account.to_public()],
+        public_key_weights: vec![],
         vp_code_hash: extra_hash,
         threshold: 1,
     };
@@ -836,6 +838,7 @@ fn unjail_validator(c: &mut Criterion) {
                 let evidence_epoch = current_epoch.prev();
                 proof_of_stake::slashing::slash(
                     &mut shell.state,
+                    &mut vec![],
                     &pos_params,
                     current_epoch,
                     evidence_epoch,
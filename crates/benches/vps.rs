@@ -83,6 +83,7 @@ fn vp_user(c: &mut Criterion) {
                 .into(),
         )),
         public_keys: vec![defaults::albert_keypair().to_public()],
+        public_key_weights: vec![],
         threshold: None,
     };
     let vp = shell.generate_tx(
@@ -369,6 +370,7 @@ fn vp_validator(c: &mut Criterion) {
                 .into(),
         )),
         public_keys: vec![defaults::validator_account_keypair().to_public()],
+        public_key_weights: vec![],
         threshold: None,
     };
     let vp = shell.generate_tx(
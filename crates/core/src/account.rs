@@ -23,6 +23,9 @@ pub struct AccountPublicKeysMap {
     pub pk_to_idx: HashMap<common::PublicKey, u8>,
     /// Hashmap from index key to public key
     pub idx_to_pk: HashMap<u8, common::PublicKey>,
+    /// Hashmap from index key to the weight of the public key. The keys
+    /// missing from this map have a weight of 1.
+    pub idx_to_weight: HashMap<u8, u8>,
 }
 
 impl FromIterator<common::PublicKey> for AccountPublicKeysMap {
@@ -43,11 +46,38 @@ impl FromIterator<common::PublicKey> for AccountPublicKeysMap {
         Self {
             pk_to_idx,
             idx_to_pk,
+            idx_to_weight: HashMap::new(),
         }
     }
 }
 
 impl AccountPublicKeysMap {
+    /// Assign the given weights to the public keys, in the order of their
+    /// indices. The keys without a weight keep the default weight of 1.
+    pub fn with_weights(mut self, weights: &[u8]) -> Self {
+        self.idx_to_weight = weights
+            .iter()
+            .enumerate()
+            .filter_map(|(index, weight)| {
+                u8::try_from(index).ok().map(|index| (index, *weight))
+            })
+            .collect();
+        self
+    }
+
+    /// Retrieve the weight of the public key with the given index
+    pub fn get_weight_from_index(&self, index: u8) -> u8 {
+        self.idx_to_weight.get(&index).copied().unwrap_or(1)
+    }
+
+    /// The sum of the weights of all the public keys
+    pub fn total_weight(&self) -> u32 {
+        self.idx_to_pk
+            .keys()
+            .map(|index| u32::from(self.get_weight_from_index(*index)))
+            .sum()
+    }
+
     /// Retrieve a public key from the index
     pub fn get_public_key_from_index(
         &self,
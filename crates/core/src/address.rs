@@ -63,6 +63,8 @@ pub const MASP: Address = Address::Internal(InternalAddress::Masp);
 pub const MULTITOKEN: Address = Address::Internal(InternalAddress::Multitoken);
 /// Internal Eth bridge address
 pub const ETH_BRIDGE: Address = Address::Internal(InternalAddress::EthBridge);
+/// Internal scheduled transactions address
+pub const SCHEDULER: Address = Address::Internal(InternalAddress::Scheduler);
 
 /// Error from decoding address from string
 pub type DecodeError = string_encoding::DecodeError;
@@ -125,6 +127,9 @@ impl From<raw::Address<'_, raw::Validated>> for Address {
                 InternalAddress::IbcToken(IbcTokenHash(*raw_addr.data())),
             ),
             raw::Discriminant::Masp => Address::Internal(InternalAddress::Masp),
+            raw::Discriminant::Scheduler => {
+                Address::Internal(InternalAddress::Scheduler)
+            }
         }
     }
 }
@@ -219,6 +224,11 @@ impl<'addr> From<&'addr Address> for raw::Address<'addr, raw::Validated> {
                     .validate()
                     .expect("This raw address is valid")
             }
+            Address::Internal(InternalAddress::Scheduler) => {
+                raw::Address::from_discriminant(raw::Discriminant::Scheduler)
+                    .validate()
+                    .expect("This raw address is valid")
+            }
         }
     }
 }
@@ -543,6 +553,8 @@ pub enum InternalAddress {
     Pgf,
     /// Masp
     Masp,
+    /// Transactions scheduled for a future execution
+    Scheduler,
 }
 
 impl Display for InternalAddress {
@@ -564,6 +576,7 @@ impl Display for InternalAddress {
                 Self::Multitoken => "Multitoken".to_string(),
                 Self::Pgf => "PublicGoodFundings".to_string(),
                 Self::Masp => "MASP".to_string(),
+                Self::Scheduler => "Scheduler".to_string(),
             }
         )
     }
@@ -579,6 +592,7 @@ impl InternalAddress {
             "bridgepool" => Some(InternalAddress::EthBridgePool),
             "governance" => Some(InternalAddress::Governance),
             "masp" => Some(InternalAddress::Masp),
+            "scheduler" => Some(InternalAddress::Scheduler),
             _ => None,
         }
     }
@@ -788,6 +802,7 @@ pub mod testing {
             InternalAddress::Nut(_) => {}
             InternalAddress::Pgf => {}
             InternalAddress::Masp => {}
+            InternalAddress::Scheduler => {}
             InternalAddress::Multitoken => {} /* Add new addresses in the
                                                * `prop_oneof` below. */
         };
@@ -805,6 +820,7 @@ pub mod testing {
             Just(InternalAddress::Multitoken),
             Just(InternalAddress::Pgf),
             Just(InternalAddress::Masp),
+            Just(InternalAddress::Scheduler),
         ]
     }
 
@@ -61,6 +61,8 @@ pub enum EventType {
     PgfPayment,
     /// Ethereum Bridge event
     EthereumBridge,
+    /// Proof-of-stake event
+    ProofOfStake,
 }
 
 impl Display for EventType {
@@ -72,6 +74,7 @@ impl Display for EventType {
             EventType::Proposal => write!(f, "proposal"),
             EventType::PgfPayment => write!(f, "pgf_payment"),
             EventType::EthereumBridge => write!(f, "ethereum_bridge"),
+            EventType::ProofOfStake => write!(f, "proof_of_stake"),
         }?;
         Ok(())
     }
@@ -93,6 +96,7 @@ impl FromStr for EventType {
                 Ok(EventType::Ibc("write_acknowledgement".to_string()))
             }
             "ethereum_bridge" => Ok(EventType::EthereumBridge),
+            "proof_of_stake" => Ok(EventType::ProofOfStake),
             _ => Err(EventError::InvalidEventType),
         }
     }
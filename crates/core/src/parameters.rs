@@ -2,6 +2,8 @@
 
 use std::collections::BTreeMap;
 
+use serde::{Deserialize, Serialize};
+
 use super::address::Address;
 use super::chain::ProposalBytes;
 use super::dec::Dec;
@@ -54,6 +56,68 @@ pub struct Parameters {
     pub fee_unshielding_descriptions_limit: u64,
     /// Map of the cost per gas unit for every token allowed for fee payment
     pub minimum_gas_price: BTreeMap<Address, token::Amount>,
+    /// Parameters of the adaptive base fee, if enabled
+    pub base_fee_params: Option<BaseFeeParams>,
+}
+
+/// Parameters of an EIP-1559 style base fee. The base fee of every token
+/// allowed for fee payment is adjusted after each block, depending on the gas
+/// used by the block relative to a target fraction of the max block gas. The
+/// base fee never drops below the token's minimum gas price.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct BaseFeeParams {
+    /// The fraction of the max block gas that blocks are targeted to use
+    pub target_block_gas_fraction: Dec,
+    /// The max relative change of the base fee from one block to the next,
+    /// reached when a block uses none or twice the target of its gas
+    pub max_change_rate: Dec,
+}
+
+impl BaseFeeParams {
+    /// Compute the base fee after a block that used the given amount of gas
+    pub fn next_base_fee(
+        &self,
+        base_fee: token::Amount,
+        minimum_gas_price: token::Amount,
+        max_block_gas: u64,
+        block_gas_used: u64,
+    ) -> token::Amount {
+        let target = Dec::from(max_block_gas) * self.target_block_gas_fraction;
+        let used = Dec::from(block_gas_used);
+        let deviation = (!target.is_zero())
+            .then(|| used.abs_diff(&target).trunc_div(&target))
+            .flatten();
+        let Some(deviation) = deviation else {
+            return std::cmp::max(base_fee, minimum_gas_price);
+        };
+        // The change is capped at the max rate
+        let change_rate =
+            self.max_change_rate * std::cmp::min(deviation, Dec::one());
+        let next_base_fee = if used > target {
+            base_fee
+                .checked_add(base_fee.mul_ceil(change_rate))
+                .unwrap_or(base_fee)
+        } else {
+            base_fee
+                .checked_sub(change_rate * base_fee)
+                .unwrap_or_default()
+        };
+        std::cmp::max(next_base_fee, minimum_gas_price)
+    }
 }
 
 /// Epoch duration. A new epoch begins as soon as both the `min_num_of_blocks`
@@ -76,3 +140,36 @@ pub struct EpochDuration {
     /// Minimum duration of an epoch
     pub min_duration: DurationSecs,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_base_fee() {
+        let params = BaseFeeParams {
+            target_block_gas_fraction: Dec::new(5, 1).unwrap(),
+            max_change_rate: Dec::new(125, 3).unwrap(),
+        };
+        let minimum = token::Amount::from(100);
+        let base_fee = token::Amount::from(1000);
+        let next = |used| params.next_base_fee(base_fee, minimum, 1000, used);
+
+        // At the target, the base fee is unchanged
+        assert_eq!(next(500), base_fee);
+        // A full block increases it by the max rate
+        assert_eq!(next(1000), token::Amount::from(1125));
+        // An empty block decreases it by the max rate
+        assert_eq!(next(0), token::Amount::from(875));
+        // The change is proportional to the deviation from the target
+        assert_eq!(next(750), token::Amount::from(1063));
+        assert_eq!(next(250), token::Amount::from(938));
+        // The base fee doesn't drop below the minimum gas price
+        assert_eq!(params.next_base_fee(minimum, minimum, 1000, 0), minimum);
+        // A tiny base fee still increases
+        assert_eq!(
+            params.next_base_fee(1.into(), 0.into(), 1000, 1000),
+            token::Amount::from(2)
+        );
+    }
+}
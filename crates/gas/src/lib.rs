@@ -1,6 +1,7 @@
 //! Gas accounting module to track the gas usage in a block for transactions and
 //! validity predicates triggered by transactions.
 
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::ops::Div;
 
@@ -94,7 +95,7 @@ impl Gas {
 
     /// Converts the sub gas units to whole ones. If the sub units are not a
     /// multiple of the `SCALE` than ceil the quotient
-    fn get_whole_gas_units(&self) -> u64 {
+    pub fn get_whole_gas_units(&self) -> u64 {
         let quotient = self.sub / SCALE;
         if self.sub % SCALE == 0 {
             quotient
@@ -138,16 +139,148 @@ impl Display for Gas {
     }
 }
 
+/// The gas required by a wrapper transaction of the given size in bytes,
+/// which is comprised of the cost of validating it, of the space that it
+/// requires in the block and of downloading it over the network
+pub fn get_wrapper_gas(tx_len: u64) -> Result<Gas> {
+    tx_len
+        .checked_mul(
+            STORAGE_OCCUPATION_GAS_PER_BYTE + NETWORK_TRANSMISSION_GAS_PER_BYTE,
+        )
+        .and_then(|gas| gas.checked_add(WRAPPER_TX_VALIDATION_GAS))
+        .map(Gas::from)
+        .ok_or(Error::GasOverflow)
+}
+
+/// The categories of the gas costs, used to break down the gas used by a
+/// transaction
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshDeserialize,
+    BorshSerialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub enum GasCategory {
+    /// Validation of a wrapper tx, and its space in the block
+    Wrapper,
+    /// Loading, validation and compilation of wasm code
+    WasmCompilation,
+    /// Execution of wasm code, including its accesses to the host memory
+    WasmExecution,
+    /// Storage reads, including prefix iterations
+    StorageRead,
+    /// Storage writes and deletions
+    StorageWrite,
+    /// Verification of the tx signatures
+    SignatureVerification,
+    /// Execution and validation of IBC actions
+    Ibc,
+    /// Verification of shielded transfers
+    MaspVerification,
+    /// Validity predicates triggered by a tx, as charged to the tx
+    Vps,
+    /// Anything else
+    Other,
+}
+
+impl Display for GasCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let category = match self {
+            GasCategory::Wrapper => "wrapper",
+            GasCategory::WasmCompilation => "wasm compilation",
+            GasCategory::WasmExecution => "wasm execution",
+            GasCategory::StorageRead => "storage read",
+            GasCategory::StorageWrite => "storage write",
+            GasCategory::SignatureVerification => "signature verification",
+            GasCategory::Ibc => "ibc",
+            GasCategory::MaspVerification => "masp verification",
+            GasCategory::Vps => "validity predicates",
+            GasCategory::Other => "other",
+        };
+        write!(f, "{category}")
+    }
+}
+
+/// The gas consumed by a gas meter, broken down by category
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    BorshDeserialize,
+    BorshSerialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct GasBreakdown {
+    categories: BTreeMap<GasCategory, Gas>,
+}
+
+impl GasBreakdown {
+    /// Record some gas in the given category
+    fn add(&mut self, category: GasCategory, gas: Gas) {
+        let consumed = self.categories.entry(category).or_default();
+        // The sum can't overflow if the meter's total didn't
+        if let Some(sum) = consumed.checked_add(gas) {
+            *consumed = sum;
+        }
+    }
+
+    /// Merge the gas of another breakdown into this one
+    pub fn merge(&mut self, other: &GasBreakdown) {
+        for (category, gas) in &other.categories {
+            self.add(*category, *gas);
+        }
+    }
+
+    /// Get the gas consumed in the given category
+    pub fn get(&self, category: GasCategory) -> Gas {
+        self.categories.get(&category).copied().unwrap_or_default()
+    }
+
+    /// Iterate over the categories in which some gas was consumed
+    pub fn iter(&self) -> impl Iterator<Item = (GasCategory, Gas)> + '_ {
+        self.categories
+            .iter()
+            .map(|(category, gas)| (*category, *gas))
+    }
+
+    /// Get the total gas of all the categories
+    pub fn total(&self) -> Gas {
+        self.categories.values().fold(Gas::default(), |acc, gas| {
+            acc.checked_add(*gas).unwrap_or(acc)
+        })
+    }
+}
+
 /// Trait to share gas operations for transactions and validity predicates
 pub trait GasMetering {
+    /// Add gas cost of the given category. It will return error when the
+    /// consumed gas exceeds the provided transaction gas limit, but the state
+    /// will still be updated
+    fn consume_in(&mut self, category: GasCategory, gas: u64) -> Result<()>;
+
     /// Add gas cost. It will return error when the
     /// consumed gas exceeds the provided transaction gas limit, but the state
     /// will still be updated
-    fn consume(&mut self, gas: u64) -> Result<()>;
+    fn consume(&mut self, gas: u64) -> Result<()> {
+        self.consume_in(GasCategory::Other, gas)
+    }
 
     /// Add the compiling cost proportionate to the code length
     fn add_compiling_gas(&mut self, bytes_len: u64) -> Result<()> {
-        self.consume(
+        self.consume_in(
+            GasCategory::WasmCompilation,
             bytes_len
                 .checked_mul(COMPILE_GAS_PER_BYTE)
                 .ok_or(Error::GasOverflow)?,
@@ -156,7 +289,8 @@ pub trait GasMetering {
 
     /// Add the gas for loading the wasm code from storage
     fn add_wasm_load_from_storage_gas(&mut self, bytes_len: u64) -> Result<()> {
-        self.consume(
+        self.consume_in(
+            GasCategory::WasmCompilation,
             bytes_len
                 .checked_mul(STORAGE_ACCESS_GAS_PER_BYTE)
                 .ok_or(Error::GasOverflow)?,
@@ -165,7 +299,8 @@ pub trait GasMetering {
 
     /// Add the gas for validating untrusted wasm code
     fn add_wasm_validation_gas(&mut self, bytes_len: u64) -> Result<()> {
-        self.consume(
+        self.consume_in(
+            GasCategory::WasmCompilation,
             bytes_len
                 .checked_mul(WASM_CODE_VALIDATION_GAS_PER_BYTE)
                 .ok_or(Error::GasOverflow)?,
@@ -185,6 +320,8 @@ pub struct TxGasMeter {
     /// The gas limit for a transaction
     pub tx_gas_limit: Gas,
     transaction_gas: Gas,
+    /// The breakdown of the consumed gas, if recorded
+    breakdown: Option<GasBreakdown>,
 }
 
 /// Gas metering in a validity predicate
@@ -196,6 +333,8 @@ pub struct VpGasMeter {
     initial_gas: Gas,
     /// The current gas usage in the VP
     current_gas: Gas,
+    /// The breakdown of the gas used in the VP, if recorded
+    breakdown: Option<GasBreakdown>,
 }
 
 /// Gas meter for VPs parallel runs
@@ -215,11 +354,14 @@ pub struct VpsGas {
 }
 
 impl GasMetering for TxGasMeter {
-    fn consume(&mut self, gas: u64) -> Result<()> {
+    fn consume_in(&mut self, category: GasCategory, gas: u64) -> Result<()> {
         self.transaction_gas = self
             .transaction_gas
             .checked_add(gas.into())
             .ok_or(Error::GasOverflow)?;
+        if let Some(breakdown) = self.breakdown.as_mut() {
+            breakdown.add(category, gas.into());
+        }
 
         if self.transaction_gas > self.tx_gas_limit {
             return Err(Error::TransactionGasExceededError);
@@ -244,6 +386,7 @@ impl TxGasMeter {
         Self {
             tx_gas_limit: tx_gas_limit.into(),
             transaction_gas: Gas::default(),
+            breakdown: None,
         }
     }
 
@@ -253,31 +396,35 @@ impl TxGasMeter {
         Self {
             tx_gas_limit,
             transaction_gas: Gas::default(),
+            breakdown: None,
         }
     }
 
+    /// Record a breakdown of the consumed gas by category, both in this
+    /// meter and in the VP gas meters created from it
+    pub fn with_breakdown(mut self) -> Self {
+        self.breakdown = Some(GasBreakdown::default());
+        self
+    }
+
+    /// Get the breakdown of the consumed gas, if recorded
+    pub fn breakdown(&self) -> Option<&GasBreakdown> {
+        self.breakdown.as_ref()
+    }
+
     /// Add the gas required by a wrapper transaction which is comprised of:
     ///  - cost of validating the wrapper tx
     ///  - space that the transaction requires in the block
     ///  - cost of downloading (as part of the block) the transaction bytes over
     ///    the network
     pub fn add_wrapper_gas(&mut self, tx_bytes: &[u8]) -> Result<()> {
-        self.consume(WRAPPER_TX_VALIDATION_GAS)?;
-
-        let bytes_len = tx_bytes.len() as u64;
-        self.consume(
-            bytes_len
-                .checked_mul(
-                    STORAGE_OCCUPATION_GAS_PER_BYTE
-                        + NETWORK_TRANSMISSION_GAS_PER_BYTE,
-                )
-                .ok_or(Error::GasOverflow)?,
-        )
+        let wrapper_gas = get_wrapper_gas(tx_bytes.len() as u64)?;
+        self.consume_in(GasCategory::Wrapper, wrapper_gas.into())
     }
 
     /// Add the gas cost used in validity predicates to the current transaction.
     pub fn add_vps_gas(&mut self, vps_gas: &VpsGas) -> Result<()> {
-        self.consume(vps_gas.get_current_gas()?.into())
+        self.consume_in(GasCategory::Vps, vps_gas.get_current_gas()?.into())
     }
 
     /// Get the amount of gas still available to the transaction
@@ -289,11 +436,14 @@ impl TxGasMeter {
 }
 
 impl GasMetering for VpGasMeter {
-    fn consume(&mut self, gas: u64) -> Result<()> {
+    fn consume_in(&mut self, category: GasCategory, gas: u64) -> Result<()> {
         self.current_gas = self
             .current_gas
             .checked_add(gas.into())
             .ok_or(Error::GasOverflow)?;
+        if let Some(breakdown) = self.breakdown.as_mut() {
+            breakdown.add(category, gas.into());
+        }
 
         let current_total = self
             .initial_gas
@@ -323,8 +473,17 @@ impl VpGasMeter {
             tx_gas_limit: tx_gas_meter.tx_gas_limit,
             initial_gas: tx_gas_meter.transaction_gas,
             current_gas: Gas::default(),
+            breakdown: tx_gas_meter
+                .breakdown
+                .as_ref()
+                .map(|_| GasBreakdown::default()),
         }
     }
+
+    /// Get the breakdown of the gas used in the VP, if recorded
+    pub fn breakdown(&self) -> Option<&GasBreakdown> {
+        self.breakdown.as_ref()
+    }
 }
 
 impl VpsGas {
@@ -397,6 +556,7 @@ mod tests {
         let tx_gas_meter = TxGasMeter {
             tx_gas_limit: BLOCK_GAS_LIMIT.into(),
             transaction_gas: Gas::default(),
+            breakdown: None,
         };
             let mut meter = VpGasMeter::new_from_tx_meter(&tx_gas_meter);
             meter.consume(gas).expect("cannot add the gas");
@@ -409,6 +569,7 @@ mod tests {
         let tx_gas_meter = TxGasMeter {
             tx_gas_limit: BLOCK_GAS_LIMIT.into(),
             transaction_gas: (TX_GAS_LIMIT - 1).into(),
+            breakdown: None,
         };
         let mut meter = VpGasMeter::new_from_tx_meter(&tx_gas_meter);
         assert_matches!(
@@ -422,6 +583,7 @@ mod tests {
         let tx_gas_meter = TxGasMeter {
             tx_gas_limit: TX_GAS_LIMIT.into(),
             transaction_gas: (TX_GAS_LIMIT - 1).into(),
+            breakdown: None,
         };
         let mut meter = VpGasMeter::new_from_tx_meter(&tx_gas_meter);
         assert_matches!(
@@ -452,4 +614,50 @@ mod tests {
             Error::TransactionGasExceededError
         );
     }
+
+    #[test]
+    fn test_gas_breakdown() {
+        let mut meter = TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into())
+            .with_breakdown();
+        meter.add_wrapper_gas(&[0; 10]).expect("cannot add the gas");
+        meter.add_compiling_gas(10).expect("cannot add the gas");
+        meter.consume(7).expect("cannot add the gas");
+
+        let mut vp_meter = VpGasMeter::new_from_tx_meter(&meter);
+        vp_meter
+            .consume_in(GasCategory::StorageRead, 100)
+            .expect("cannot add the gas");
+        vp_meter
+            .consume_in(GasCategory::StorageRead, 50)
+            .expect("cannot add the gas");
+        let vp_breakdown = vp_meter.breakdown().cloned().unwrap();
+        assert_eq!(vp_breakdown.get(GasCategory::StorageRead), 150.into());
+        assert_eq!(vp_breakdown.total(), 150.into());
+
+        let mut vps_gas = VpsGas::default();
+        vps_gas.set(vp_meter).expect("cannot add the gas");
+        meter.add_vps_gas(&vps_gas).expect("cannot add the gas");
+
+        let breakdown = meter.breakdown().unwrap();
+        assert_eq!(
+            breakdown.get(GasCategory::Wrapper),
+            (WRAPPER_TX_VALIDATION_GAS
+                + 10 * (STORAGE_OCCUPATION_GAS_PER_BYTE
+                    + NETWORK_TRANSMISSION_GAS_PER_BYTE))
+                .into()
+        );
+        assert_eq!(
+            breakdown.get(GasCategory::WasmCompilation),
+            (10 * COMPILE_GAS_PER_BYTE).into()
+        );
+        assert_eq!(breakdown.get(GasCategory::Other), 7.into());
+        assert_eq!(breakdown.get(GasCategory::Vps), 150.into());
+        assert_eq!(breakdown.total(), meter.get_tx_consumed_gas());
+
+        // Nothing is recorded by default
+        let mut meter = TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into());
+        meter.consume(7).expect("cannot add the gas");
+        assert!(meter.breakdown().is_none());
+        assert!(VpGasMeter::new_from_tx_meter(&meter).breakdown().is_none());
+    }
 }
@@ -20,12 +20,14 @@ impl InitAccount {
     /// Build a raw InitAccount transaction from the given parameters
     pub fn new(
         public_keys: Vec<common::PublicKey>,
+        public_key_weights: Vec<u8>,
         vp_code_hash: Hash,
         threshold: u8,
         args: GlobalArgs,
     ) -> Self {
         let init_account = namada_sdk::account::InitAccount {
             public_keys,
+            public_key_weights,
             vp_code_hash,
             threshold,
         };
@@ -176,6 +178,7 @@ impl UpdateAccount {
         addr: Address,
         vp_code_hash: Option<Hash>,
         public_keys: Vec<common::PublicKey>,
+        public_key_weights: Vec<u8>,
         threshold: Option<u8>,
         args: GlobalArgs,
     ) -> Self {
@@ -183,6 +186,7 @@ impl UpdateAccount {
             addr,
             vp_code_hash,
             public_keys,
+            public_key_weights,
             threshold,
         };
 
@@ -42,7 +42,7 @@ mod dry_run_tx {
         CA: 'static + WasmCacheAccess + Sync,
     {
         use borsh_ext::BorshSerializeExt;
-        use namada_gas::{Gas, GasMetering, TxGasMeter};
+        use namada_gas::{Gas, GasBreakdown, GasMetering, TxGasMeter};
         use namada_tx::data::{DecryptedTx, TxType};
         use namada_tx::Tx;
 
@@ -54,12 +54,15 @@ mod dry_run_tx {
         tx.validate_tx().into_storage_result()?;
 
         let mut cumulated_gas = Gas::default();
+        let mut breakdown = GasBreakdown::default();
 
         // Wrapper dry run to allow estimating the gas cost of a transaction
         let tx_gas_meter = match tx.header().tx_type {
             TxType::Wrapper(wrapper) => {
-                let tx_gas_meter =
-                    RefCell::new(TxGasMeter::new(wrapper.gas_limit.to_owned()));
+                let tx_gas_meter = RefCell::new(
+                    TxGasMeter::new(wrapper.gas_limit.to_owned())
+                        .with_breakdown(),
+                );
                 protocol::apply_wrapper_tx(
                     tx.clone(),
                     &wrapper,
@@ -77,10 +80,15 @@ mod dry_run_tx {
 
                 temp_state.write_log_mut().commit_tx();
                 cumulated_gas = tx_gas_meter.borrow_mut().get_tx_consumed_gas();
+                if let Some(wrapper_breakdown) =
+                    tx_gas_meter.borrow().breakdown()
+                {
+                    breakdown.merge(wrapper_breakdown);
+                }
 
                 tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
                 let available_gas = tx_gas_meter.borrow().get_available_gas();
-                TxGasMeter::new_from_sub_limit(available_gas)
+                TxGasMeter::new_from_sub_limit(available_gas).with_breakdown()
             }
             TxType::Protocol(_) | TxType::Decrypted(_) => {
                 // If dry run only the inner tx, use the max block gas as
@@ -88,6 +96,7 @@ mod dry_run_tx {
                 TxGasMeter::new(GasLimit::from(
                     namada_parameters::get_max_block_gas(ctx.state).unwrap(),
                 ))
+                .with_breakdown()
             }
             TxType::Raw => {
                 // Cast tx to a decrypted for execution
@@ -98,6 +107,7 @@ mod dry_run_tx {
                 TxGasMeter::new(GasLimit::from(
                     namada_parameters::get_max_block_gas(ctx.state).unwrap(),
                 ))
+                .with_breakdown()
             }
         };
 
@@ -120,6 +130,10 @@ mod dry_run_tx {
             ))?;
         // Account gas for both inner and wrapper (if available)
         data.gas_used = cumulated_gas;
+        if let Some(inner_breakdown) = &data.gas_breakdown {
+            breakdown.merge(inner_breakdown);
+        }
+        data.gas_breakdown = Some(breakdown);
         // NOTE: the keys changed by the wrapper transaction (if any) are
         // not returned from this function
         let data = data.serialize_to_vec();
@@ -150,7 +164,6 @@ mod test {
     use namada_tx::{Code, Data, Tx};
     use tempfile::TempDir;
 
-    use crate::ledger::events::log::EventLog;
     use crate::ledger::queries::Client;
     use crate::token;
     use crate::vm::wasm::{TxCache, VpCache};
@@ -165,8 +178,6 @@ mod test {
         pub rpc: RPC,
         /// state
         pub state: TestState,
-        /// event log
-        pub event_log: EventLog,
         /// VP wasm compilation cache
         pub vp_wasm_cache: VpCache<WasmCacheRoAccess>,
         /// tx wasm compilation cache
@@ -195,7 +206,6 @@ mod test {
                 .expect(
                     "Max block gas parameter must be initialized in storage",
                 );
-            let event_log = EventLog::default();
             let (vp_wasm_cache, vp_cache_dir) =
                 wasm::compilation_cache::common::testing::cache();
             let (tx_wasm_cache, tx_cache_dir) =
@@ -203,7 +213,6 @@ mod test {
             Self {
                 rpc,
                 state,
-                event_log,
                 vp_wasm_cache: vp_wasm_cache.read_only(),
                 tx_wasm_cache: tx_wasm_cache.read_only(),
                 vp_cache_dir,
@@ -239,7 +248,6 @@ mod test {
             };
             let ctx = RequestCtx {
                 state: &self.state,
-                event_log: &self.event_log,
                 vp_wasm_cache: self.vp_wasm_cache.clone(),
                 tx_wasm_cache: self.tx_wasm_cache.clone(),
                 storage_read_past_height_limit: None,
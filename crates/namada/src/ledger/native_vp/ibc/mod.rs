@@ -10,7 +10,9 @@ use std::time::Duration;
 use context::{PseudoExecutionContext, VpValidationContext};
 use namada_core::address::Address;
 use namada_core::storage::Key;
-use namada_gas::{IBC_ACTION_EXECUTE_GAS, IBC_ACTION_VALIDATE_GAS};
+use namada_gas::{
+    GasCategory, IBC_ACTION_EXECUTE_GAS, IBC_ACTION_VALIDATE_GAS,
+};
 use namada_ibc::{
     Error as ActionError, IbcActions, TransferModule, ValidationParams,
 };
@@ -104,7 +106,7 @@ where
         actions.add_transfer_module(module.module_id(), module);
         // Charge gas for the expensive execution
         self.ctx
-            .charge_gas(IBC_ACTION_EXECUTE_GAS)
+            .charge_gas_in(GasCategory::Ibc, IBC_ACTION_EXECUTE_GAS)
             .map_err(Error::NativeVpError)?;
         actions.execute(tx_data)?;
 
@@ -150,7 +152,7 @@ where
         actions.add_transfer_module(module.module_id(), module);
         // Charge gas for the expensive validation
         self.ctx
-            .charge_gas(IBC_ACTION_VALIDATE_GAS)
+            .charge_gas_in(GasCategory::Ibc, IBC_ACTION_VALIDATE_GAS)
             .map_err(Error::NativeVpError)?;
         actions.validate(tx_data).map_err(Error::IbcAction)
     }
@@ -6,6 +6,7 @@ pub mod ibc;
 pub mod masp;
 pub mod multitoken;
 pub mod parameters;
+pub mod scheduler;
 
 use std::cell::RefCell;
 use std::collections::BTreeSet;
@@ -16,7 +17,7 @@ use eyre::WrapErr;
 use namada_core::storage;
 use namada_core::storage::Epochs;
 use namada_core::validity_predicate::VpSentinel;
-use namada_gas::GasMetering;
+use namada_gas::{GasCategory, GasMetering};
 use namada_tx::Tx;
 pub use namada_vp_env::VpEnv;
 use state::StateRead;
@@ -159,6 +160,20 @@ where
     pub fn post<'view>(&'view self) -> CtxPostStorageRead<'view, 'a, S, CA> {
         CtxPostStorageRead { ctx: self }
     }
+
+    /// Charge gas of the given category for an expensive operation of the VP
+    pub fn charge_gas_in(
+        &self,
+        category: GasCategory,
+        used_gas: u64,
+    ) -> Result<(), state::StorageError> {
+        self.gas_meter
+            .borrow_mut()
+            .consume_in(category, used_gas)
+            .map_err(|_| {
+                Error::SimpleMessage("Gas limit exceeded in native vp")
+            })
+    }
 }
 
 impl<'view, 'a: 'view, S, CA> StorageRead
@@ -521,9 +536,7 @@ where
     }
 
     fn charge_gas(&self, used_gas: u64) -> Result<(), state::StorageError> {
-        self.gas_meter.borrow_mut().consume(used_gas).map_err(|_| {
-            Error::SimpleMessage("Gas limit exceeded in native vp")
-        })
+        self.charge_gas_in(GasCategory::Other, used_gas)
     }
 
     fn get_tx_code_hash(&self) -> Result<Option<Hash>, state::StorageError> {
@@ -0,0 +1,636 @@
+//! Native VP for scheduled transactions
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use namada_state::{StateRead, StorageRead};
+use namada_tx::data::schedule::{
+    is_scheduled_tx_key, scheduled_txs_prefix, ScheduleTarget, ScheduledTx,
+};
+use namada_tx::data::TxType;
+use namada_tx::Tx;
+use namada_vp_env::VpEnv;
+use thiserror::Error;
+
+use crate::address::{Address, SCHEDULER};
+use crate::hash::Hash;
+use crate::ledger::native_vp::{self, Ctx, NativeVp};
+use crate::storage::{Key, KeySeg};
+use crate::token::storage_key::is_any_token_balance_key;
+use crate::token::{Amount, Change};
+use crate::vm::WasmCacheAccess;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Native VP error: {0}")]
+    NativeVpError(#[from] native_vp::Error),
+}
+
+/// Scheduler functions result
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Scheduler VP
+pub struct SchedulerVp<'a, S, CA>
+where
+    S: StateRead,
+    CA: WasmCacheAccess,
+{
+    /// Context to interact with the host structures.
+    pub ctx: Ctx<'a, S, CA>,
+}
+
+impl<'a, S, CA> NativeVp for SchedulerVp<'a, S, CA>
+where
+    S: StateRead,
+    CA: 'static + WasmCacheAccess,
+{
+    type Error = Error;
+
+    fn validate_tx(
+        &self,
+        _tx_data: &Tx,
+        keys_changed: &BTreeSet<Key>,
+        verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        // The changes of the escrowed balances implied by the updates of the
+        // queue and the actual ones
+        let mut expected_changes: BTreeMap<Address, Change> = BTreeMap::new();
+        let mut actual_changes: BTreeMap<Address, Change> = BTreeMap::new();
+        for key in keys_changed {
+            if let Some((target, owner, hash)) = is_scheduled_tx_key(key) {
+                // The owner's VP must authorize any change to its scheduled
+                // txs
+                if !verifiers.contains(owner) {
+                    tracing::debug!(
+                        "The owner {} of the scheduled tx {} is not a verifier",
+                        owner,
+                        hash
+                    );
+                    return Ok(false);
+                }
+                let pre: Option<ScheduledTx> = self.ctx.read_pre(key)?;
+                let post: Option<ScheduledTx> = self.ctx.read_post(key)?;
+                let (scheduled, is_new) = match (pre, post) {
+                    (None, Some(scheduled)) => {
+                        if !self.is_valid_scheduled_tx(
+                            &scheduled, &target, owner, &hash,
+                        )? {
+                            return Ok(false);
+                        }
+                        (scheduled, true)
+                    }
+                    (Some(scheduled), None) => (scheduled, false),
+                    // Scheduled txs can only be added or removed
+                    _ => return Ok(false),
+                };
+                let Some(fee) = self.prepaid_fee(&scheduled)? else {
+                    return Ok(false);
+                };
+                let change =
+                    expected_changes.entry(scheduled.fee.token).or_default();
+                if is_new {
+                    *change += fee.change();
+                } else {
+                    *change -= fee.change();
+                }
+            } else if let Some([token, owner]) = is_any_token_balance_key(key) {
+                if owner == &SCHEDULER {
+                    let pre: Amount =
+                        self.ctx.read_pre(key)?.unwrap_or_default();
+                    let post: Amount =
+                        self.ctx.read_post(key)?.unwrap_or_default();
+                    let change =
+                        actual_changes.entry(token.clone()).or_default();
+                    *change += post.change() - pre.change();
+                }
+            } else if key.segments.first() == Some(&SCHEDULER.to_db_key()) {
+                // Reject when trying to update an unexpected key under
+                // `#Scheduler/...`
+                return Ok(false);
+            }
+        }
+
+        // The escrowed balances must change exactly by the prepaid fees of
+        // the scheduled and cancelled txs
+        expected_changes.retain(|_, change| !change.is_zero());
+        actual_changes.retain(|_, change| !change.is_zero());
+        if expected_changes != actual_changes {
+            tracing::debug!(
+                "The escrowed balances changes {:?} don't match the prepaid \
+                 fees {:?}",
+                actual_changes,
+                expected_changes
+            );
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+impl<'a, S, CA> SchedulerVp<'a, S, CA>
+where
+    S: StateRead,
+    CA: 'static + WasmCacheAccess,
+{
+    /// Check that a newly scheduled tx is stored under the expected key, is
+    /// bound to its target, can be executed at its target before expiring,
+    /// pays a valid fee and doesn't make the txs scheduled at its target
+    /// exceed their share of the block gas limit
+    fn is_valid_scheduled_tx(
+        &self,
+        scheduled: &ScheduledTx,
+        target: &ScheduleTarget,
+        owner: &Address,
+        hash: &Hash,
+    ) -> Result<bool> {
+        if &scheduled.target != target
+            || &scheduled.owner != owner
+            || &scheduled.hash() != hash
+        {
+            tracing::debug!("The scheduled tx {} has an invalid key", hash);
+            return Ok(false);
+        }
+        if !matches!(scheduled.tx.header().tx_type, TxType::Raw) {
+            tracing::debug!("The scheduled tx {} is not a raw tx", hash);
+            return Ok(false);
+        }
+        // The signed header must commit to the target, otherwise anyone could
+        // schedule the tx of someone else at any target
+        if scheduled.tx.schedule().map(|schedule| schedule.target)
+            != Some(*target)
+        {
+            tracing::debug!(
+                "The scheduled tx {} is not bound to {}",
+                hash,
+                target
+            );
+            return Ok(false);
+        }
+        let is_future_target = match target {
+            ScheduleTarget::Height(height) => {
+                *height > self.ctx.get_block_height()?
+            }
+            ScheduleTarget::Epoch(epoch) => {
+                *epoch > self.ctx.get_block_epoch()?
+            }
+        };
+        if !is_future_target {
+            tracing::debug!(
+                "The scheduled tx {} targets {} which is not in the future",
+                hash,
+                target
+            );
+            return Ok(false);
+        }
+        if !self.is_executed_before_expiration(scheduled, target, hash)? {
+            return Ok(false);
+        }
+        if !self.is_valid_fee(scheduled, hash)? {
+            return Ok(false);
+        }
+        // The scheduled txs are executed in the block of their target. The
+        // txs scheduled at a height and at the epoch starting at that height
+        // run in the same block, so each of them gets half of the block gas
+        let max_block_gas =
+            namada_parameters::get_max_block_gas(&self.ctx.pre())?;
+        if scheduled_gas(&self.ctx.post(), target)? > max_block_gas / 2 {
+            tracing::debug!(
+                "The gas limits of the txs scheduled at {} exceed half of the \
+                 block gas limit",
+                target
+            );
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Check that a newly scheduled tx doesn't expire before the estimated
+    /// time of its target. An expired tx is dropped by the scheduler while its
+    /// prepaid fee is still paid to the block proposer.
+    fn is_executed_before_expiration(
+        &self,
+        scheduled: &ScheduledTx,
+        target: &ScheduleTarget,
+        hash: &Hash,
+    ) -> Result<bool> {
+        let height = self.ctx.get_block_height()?;
+        let Some(header) = self.ctx.get_block_header(height)? else {
+            tracing::debug!(
+                "Missing the header of the current block to validate the \
+                 expiration of the scheduled tx {}",
+                hash
+            );
+            return Ok(false);
+        };
+        let max_time_per_block =
+            namada_parameters::read_max_expected_time_per_block(
+                &self.ctx.pre(),
+            )?;
+        let epoch_duration =
+            namada_parameters::read_epoch_duration_parameter(&self.ctx.pre())?;
+        let execution_time = target.estimate_execution_time(
+            height,
+            self.ctx.get_block_epoch()?,
+            header.time,
+            max_time_per_block,
+            &epoch_duration,
+        );
+        let expiration = scheduled.tx.header().effective_expiration();
+        match execution_time {
+            Some(execution_time) if execution_time <= expiration => Ok(true),
+            _ => {
+                tracing::debug!(
+                    "The scheduled tx {} expires at {} before the estimated \
+                     time of {}",
+                    hash,
+                    expiration,
+                    target
+                );
+                Ok(false)
+            }
+        }
+    }
+
+    /// Check that the fee of a newly scheduled tx is paid in a token allowed
+    /// for fee payment and covers the minimum gas price and the current base
+    /// fee, as for wrapper txs
+    fn is_valid_fee(
+        &self,
+        scheduled: &ScheduledTx,
+        hash: &Hash,
+    ) -> Result<bool> {
+        let token = &scheduled.fee.token;
+        let Some(minimum_gas_price) =
+            namada_parameters::read_gas_cost(&self.ctx.pre(), token)?
+        else {
+            tracing::debug!(
+                "The fee token {} of the scheduled tx {} is not allowed",
+                token,
+                hash
+            );
+            return Ok(false);
+        };
+        let base_fee =
+            namada_parameters::read_base_fee(&self.ctx.pre(), token)?
+                .unwrap_or_default();
+        let minimum_gas_price = std::cmp::max(minimum_gas_price, base_fee);
+        match crate::token::denom_to_amount(
+            scheduled.fee.amount_per_gas_unit,
+            token,
+            &self.ctx.pre(),
+        ) {
+            Ok(amount_per_gas_unit)
+                if amount_per_gas_unit >= minimum_gas_price =>
+            {
+                Ok(true)
+            }
+            _ => {
+                tracing::debug!(
+                    "The fee {} of the scheduled tx {} doesn't match the \
+                     minimum required amount {} for token {}",
+                    scheduled.fee.amount_per_gas_unit,
+                    hash,
+                    minimum_gas_price.to_string_native(),
+                    token
+                );
+                Ok(false)
+            }
+        }
+    }
+
+    /// The prepaid fee of the scheduled tx, if it's valid
+    fn prepaid_fee(&self, scheduled: &ScheduledTx) -> Result<Option<Amount>> {
+        let Some(fee) = scheduled.get_prepaid_fee() else {
+            return Ok(None);
+        };
+        Ok(crate::token::denom_to_amount(
+            fee,
+            &scheduled.fee.token,
+            &self.ctx.pre(),
+        )
+        .ok())
+    }
+}
+
+/// The sum of the gas limits of the txs scheduled at the given target
+pub fn scheduled_gas<S>(
+    storage: &S,
+    target: &ScheduleTarget,
+) -> namada_state::StorageResult<u64>
+where
+    S: StorageRead,
+{
+    namada_state::iter_prefix::<ScheduledTx>(
+        storage,
+        &scheduled_txs_prefix(target),
+    )?
+    .try_fold(0u64, |gas, entry| {
+        let (_key, scheduled) = entry?;
+        Ok(gas.saturating_add(u64::from(scheduled.gas_limit)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use borsh_ext::BorshSerializeExt;
+    use namada_core::validity_predicate::VpSentinel;
+    use namada_gas::TxGasMeter;
+    use namada_state::testing::TestState;
+    use namada_tx::data::{Fee, GasLimit};
+    use namada_tx::{Code, Data, Section};
+
+    use super::*;
+    use crate::core::address::testing::{
+        established_address_1, established_address_2, nam,
+    };
+    use crate::ledger::gas::VpGasMeter;
+    use crate::parameters::storage::{
+        get_epoch_duration_storage_key, get_gas_cost_key,
+        get_max_block_gas_key, get_max_expected_time_per_block_key,
+    };
+    use crate::parameters::EpochDuration;
+    use crate::storage::{BlockHeight, Epoch, Header, TxIndex};
+    use crate::time::{DateTimeUtc, Duration, DurationSecs};
+    use crate::token::storage_key::{balance_key, denom_key};
+    use crate::token::{DenominatedAmount, Denomination};
+    use crate::vm::wasm::compilation_cache::common::testing::cache as wasm_cache;
+
+    fn scheduled_tx(state: &TestState, owner: &Address) -> ScheduledTx {
+        scheduled_tx_with(state, owner, b"", 100)
+    }
+
+    /// A tx with the given data and gas limit scheduled at height 10
+    fn scheduled_tx_with(
+        state: &TestState,
+        owner: &Address,
+        data: &[u8],
+        gas_limit: u64,
+    ) -> ScheduledTx {
+        let target = ScheduleTarget::Height(BlockHeight(10));
+        let mut tx = Tx::from_type(TxType::Raw);
+        tx.header.chain_id = state.in_mem().chain_id.clone();
+        tx.set_code(Code::new(vec![], None));
+        tx.set_data(Data::new(data.to_vec()));
+        tx.set_schedule(target);
+        ScheduledTx {
+            owner: owner.clone(),
+            target,
+            gas_limit: GasLimit::from(gas_limit),
+            fee: Fee {
+                amount_per_gas_unit: DenominatedAmount::native(
+                    Amount::from_u64(1),
+                ),
+                token: nam(),
+            },
+            tx,
+        }
+    }
+
+    /// Schedule a tx and escrow the given amount, then run the VP
+    fn validate_schedule(escrowed: Amount, owner_is_verifier: bool) -> bool {
+        validate_schedule_of(
+            |state, owner| scheduled_tx(state, owner),
+            escrowed,
+            owner_is_verifier,
+        )
+    }
+
+    /// Schedule the tx built by the given function, which may also update the
+    /// initial storage, and escrow the given amount, then run the VP
+    fn validate_schedule_of(
+        build: impl FnOnce(&mut TestState, &Address) -> ScheduledTx,
+        escrowed: Amount,
+        owner_is_verifier: bool,
+    ) -> bool {
+        let mut state = TestState::default();
+        state.in_mem_mut().header = Some(Header {
+            hash: Hash::default(),
+            time: DateTimeUtc::now(),
+            next_validators_hash: Hash::default(),
+        });
+        state
+            .db_write(&get_max_block_gas_key(), 10_000u64.serialize_to_vec())
+            .expect("write failed");
+        state
+            .db_write(
+                &get_max_expected_time_per_block_key(),
+                DurationSecs(30).serialize_to_vec(),
+            )
+            .expect("write failed");
+        let epoch_duration = EpochDuration {
+            min_num_of_blocks: 10,
+            min_duration: DurationSecs(60),
+        };
+        state
+            .db_write(
+                &get_epoch_duration_storage_key(),
+                epoch_duration.serialize_to_vec(),
+            )
+            .expect("write failed");
+        state
+            .db_write(&denom_key(&nam()), Denomination(6).serialize_to_vec())
+            .expect("write failed");
+        let gas_cost_table = BTreeMap::from([(nam(), Amount::from_u64(1))]);
+        state
+            .db_write(&get_gas_cost_key(), gas_cost_table.serialize_to_vec())
+            .expect("write failed");
+
+        let owner = established_address_1();
+        let scheduled = build(&mut state, &owner);
+        let mut keys_changed = BTreeSet::new();
+        let queue_key = scheduled.storage_key();
+        state
+            .write_log_mut()
+            .write(&queue_key, scheduled.serialize_to_vec())
+            .expect("write failed");
+        keys_changed.insert(queue_key);
+        let escrow_key = balance_key(&nam(), &SCHEDULER);
+        state
+            .write_log_mut()
+            .write(&escrow_key, escrowed.serialize_to_vec())
+            .expect("write failed");
+        keys_changed.insert(escrow_key);
+
+        let tx_index = TxIndex::default();
+        let tx = scheduled.tx.clone();
+        let gas_meter = RefCell::new(VpGasMeter::new_from_tx_meter(
+            &TxGasMeter::new_from_sub_limit(u64::MAX.into()),
+        ));
+        let (vp_wasm_cache, _vp_cache_dir) = wasm_cache();
+        let mut verifiers = BTreeSet::new();
+        if owner_is_verifier {
+            verifiers.insert(owner);
+        }
+        let sentinel = RefCell::new(VpSentinel::default());
+        let ctx = Ctx::new(
+            &SCHEDULER,
+            &state,
+            &tx,
+            &tx_index,
+            &gas_meter,
+            &sentinel,
+            &keys_changed,
+            &verifiers,
+            vp_wasm_cache,
+        );
+
+        let vp = SchedulerVp { ctx };
+        vp.validate_tx(&tx, &keys_changed, &verifiers)
+            .expect("validation failed")
+    }
+
+    #[test]
+    fn test_valid_schedule() {
+        assert!(validate_schedule(Amount::from_u64(100), true));
+    }
+
+    #[test]
+    fn test_schedule_without_escrow() {
+        assert!(!validate_schedule(Amount::from_u64(99), true));
+    }
+
+    #[test]
+    fn test_schedule_without_owner() {
+        assert!(!validate_schedule(Amount::from_u64(100), false));
+    }
+
+    /// Test that a tx whose signed header doesn't commit to its target can't
+    /// be scheduled
+    #[test]
+    fn test_schedule_not_bound_to_target() {
+        let unbound = validate_schedule_of(
+            |state, owner| {
+                let mut scheduled = scheduled_tx(state, owner);
+                scheduled.tx = scheduled.tx.scheduled_inner().unwrap();
+                scheduled
+                    .tx
+                    .sections
+                    .retain(|section| !matches!(section, Section::Schedule(_)));
+                scheduled
+            },
+            Amount::from_u64(100),
+            true,
+        );
+        assert!(!unbound);
+
+        let other_target = validate_schedule_of(
+            |state, owner| {
+                let mut scheduled = scheduled_tx(state, owner);
+                scheduled
+                    .tx
+                    .set_schedule(ScheduleTarget::Height(BlockHeight(11)));
+                scheduled
+            },
+            Amount::from_u64(100),
+            true,
+        );
+        assert!(!other_target);
+    }
+
+    /// Test that the fee of a scheduled tx must be paid in an allowed token
+    /// and cover the minimum gas price
+    #[test]
+    fn test_schedule_invalid_fee() {
+        let not_allowed = validate_schedule_of(
+            |state, owner| {
+                let mut scheduled = scheduled_tx(state, owner);
+                scheduled.fee.token = established_address_2();
+                scheduled
+            },
+            Amount::zero(),
+            true,
+        );
+        assert!(!not_allowed);
+
+        let below_minimum = validate_schedule_of(
+            |state, owner| {
+                let mut scheduled = scheduled_tx(state, owner);
+                scheduled.fee.amount_per_gas_unit =
+                    DenominatedAmount::native(Amount::zero());
+                scheduled
+            },
+            Amount::zero(),
+            true,
+        );
+        assert!(!below_minimum);
+    }
+
+    /// Test that a tx can't be scheduled if it expires before its target
+    #[test]
+    fn test_schedule_expiring_before_target() {
+        // The target at height 10 is expected within 300s
+        let expires_after = validate_schedule_of(
+            |state, owner| {
+                let mut scheduled = scheduled_tx(state, owner);
+                scheduled.tx.header.expiration =
+                    Some(DateTimeUtc::now() + DurationSecs(600));
+                scheduled
+            },
+            Amount::from_u64(100),
+            true,
+        );
+        assert!(expires_after);
+
+        let expires_before = validate_schedule_of(
+            |state, owner| {
+                let mut scheduled = scheduled_tx(state, owner);
+                scheduled.tx.header.expiration =
+                    Some(DateTimeUtc::now() + DurationSecs(60));
+                scheduled
+            },
+            Amount::from_u64(100),
+            true,
+        );
+        assert!(!expires_before);
+
+        // The default lifetime doesn't cover a target in 10 epochs, expected
+        // within 3300s
+        let default_lifetime = validate_schedule_of(
+            |state, owner| {
+                let mut scheduled = scheduled_tx(state, owner);
+                let target = ScheduleTarget::Epoch(Epoch(10));
+                scheduled.target = target;
+                scheduled.tx.header.timestamp =
+                    DateTimeUtc::now() - Duration::hours(24);
+                scheduled.tx.set_schedule(target);
+                scheduled
+            },
+            Amount::from_u64(100),
+            true,
+        );
+        assert!(!default_lifetime);
+    }
+
+    /// Test that the txs scheduled at a target can't exceed half of the block
+    /// gas limit in total
+    #[test]
+    fn test_scheduled_gas_exceeds_block_gas() {
+        let fits = validate_schedule_of(
+            |state, owner| {
+                let queued = scheduled_tx_with(state, owner, b"queued", 4_900);
+                state
+                    .db_write(&queued.storage_key(), queued.serialize_to_vec())
+                    .expect("write failed");
+                scheduled_tx(state, owner)
+            },
+            Amount::from_u64(100),
+            true,
+        );
+        assert!(fits);
+
+        let exceeds = validate_schedule_of(
+            |state, owner| {
+                let queued = scheduled_tx_with(state, owner, b"queued", 4_901);
+                state
+                    .db_write(&queued.storage_key(), queued.serialize_to_vec())
+                    .expect("write failed");
+                scheduled_tx(state, owner)
+            },
+            Amount::from_u64(100),
+            true,
+        );
+        assert!(!exceeds);
+    }
+}
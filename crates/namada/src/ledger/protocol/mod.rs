@@ -14,15 +14,17 @@ use namada_sdk::tx::TX_TRANSFER_WASM;
 use namada_state::StorageWrite;
 use namada_tx::data::protocol::ProtocolTxType;
 use namada_tx::data::{
-    DecryptedTx, GasLimit, TxResult, TxType, VpsResult, WrapperTx,
+    BatchedTxResult, DecryptedTx, GasLimit, TxResult, TxType, VpsResult,
+    WrapperTx,
 };
 use namada_tx::{Section, Tx};
 use namada_vote_ext::EthereumTxData;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use thiserror::Error;
 
+use crate::account::FeeAllowance;
 use crate::address::{Address, InternalAddress};
-use crate::ledger::gas::{GasMetering, VpGasMeter};
+use crate::ledger::gas::{GasCategory, GasMetering, VpGasMeter};
 use crate::ledger::governance::GovernanceVp;
 use crate::ledger::native_vp::ethereum_bridge::bridge_pool_vp::BridgePoolVp;
 use crate::ledger::native_vp::ethereum_bridge::nut::NonUsableTokens;
@@ -31,6 +33,7 @@ use crate::ledger::native_vp::ibc::Ibc;
 use crate::ledger::native_vp::masp::MaspVp;
 use crate::ledger::native_vp::multitoken::MultitokenVp;
 use crate::ledger::native_vp::parameters::{self, ParametersVp};
+use crate::ledger::native_vp::scheduler::{self, SchedulerVp};
 use crate::ledger::native_vp::{self, NativeVp};
 use crate::ledger::pgf::PgfVp;
 use crate::ledger::pos::{self, PosVP};
@@ -52,6 +55,8 @@ pub enum Error {
     StorageError(namada_state::StorageError),
     #[error("Transaction runner error: {0}")]
     TxRunnerError(vm::wasm::run::Error),
+    #[error("Transaction runner error in inner tx {0} of the batch: {1}")]
+    BatchInnerTxRunnerError(usize, vm::wasm::run::Error),
     #[error("{0:?}")]
     ProtocolTxError(#[from] eyre::Error),
     #[error("Txs must either be encrypted or a decryption of an encrypted tx")]
@@ -68,6 +73,11 @@ pub enum Error {
         "The decrypted transaction {0} has already been applied in this block"
     )]
     ReplayAttempt(Hash),
+    #[error(
+        "The transaction carries a batch section that is not designated by \
+         its code hash"
+    )]
+    UndesignatedBatch,
     #[error("Error executing VP for addresses: {0:?}")]
     VpRunnerError(vm::wasm::run::Error),
     #[error("The address {0} doesn't exist")]
@@ -94,6 +104,8 @@ pub enum Error {
     NutNativeVpError(native_vp::ethereum_bridge::nut::Error),
     #[error("MASP native VP error: {0}")]
     MaspNativeVpError(native_vp::masp::Error),
+    #[error("Scheduler native VP error: {0}")]
+    SchedulerNativeVpError(scheduler::Error),
     #[error("Access to an internal address {0:?} is forbidden")]
     AccessForbidden(InternalAddress),
     #[error("Tx is not allowed in allowlist parameter.")]
@@ -211,6 +223,8 @@ where
                 initialized_accounts: vec![],
                 ibc_events: BTreeSet::default(),
                 eth_bridge_events: BTreeSet::default(),
+                gas_breakdown: tx_gas_meter.borrow().breakdown().cloned(),
+                batch_results: vec![],
             })
         }
         TxType::Decrypted(DecryptedTx::Undecryptable) => {
@@ -261,11 +275,12 @@ where
     shell_params
         .state
         .write_log_mut()
-        .write_tx_hash(tx.header_hash())
+        .write_tx_hash(tx.header_hash(), tx.header.effective_expiration())
         .expect("Error while writing tx hash to storage");
 
     // Charge fee before performing any fallible operations
     charge_fee(
+        &tx,
         wrapper,
         fee_unshield_transaction,
         &mut shell_params,
@@ -307,7 +322,9 @@ pub fn get_fee_unshielding_transaction(
 /// - Fee amount overflows
 /// - Not enough funds are available to pay the entire amount of the fee
 /// - The accumulated fee amount to be credited to the block proposer overflows
+/// - The fee allowance referenced by the wrapper cannot pay for the fee
 fn charge_fee<'a, S, D, H, CA>(
+    tx: &Tx,
     wrapper: &WrapperTx,
     masp_transaction: Option<Transaction>,
     shell_params: &mut ShellParams<'a, S, D, H, CA>,
@@ -400,8 +417,8 @@ where
         Some(WrapperArgs {
             block_proposer,
             is_committed_fee_unshield: _,
-        }) => transfer_fee(*state, block_proposer, wrapper)?,
-        None => check_fees(*state, wrapper)?,
+        }) => transfer_fee(*state, block_proposer, tx, wrapper)?,
+        None => check_fees(*state, tx, wrapper)?,
     }
 
     changed_keys.extend(state.write_log_mut().get_keys_with_precommit());
@@ -416,61 +433,90 @@ where
     Ok(())
 }
 
-/// Perform the actual transfer of fess from the fee payer to the block
-/// proposer.
+/// Get the address whose balance pays for the fees of the wrapper: the fee
+/// granter, if any, or the fee payer otherwise
+fn fee_source(tx: &Tx, wrapper: &WrapperTx) -> Address {
+    tx.fee_granter().unwrap_or_else(|| wrapper.fee_payer())
+}
+
+/// Perform the actual transfer of fess from the fee payer, or from the fee
+/// granter if the wrapper draws from a fee allowance, to the block proposer.
+/// If the base fee is enabled, its portion of the fees is burned and only the
+/// tip is transferred to the block proposer.
 pub fn transfer_fee<S>(
     state: &mut S,
     block_proposer: &Address,
+    tx: &Tx,
     wrapper: &WrapperTx,
 ) -> Result<()>
 where
     S: State + StorageRead + StorageWrite,
 {
-    let balance = crate::token::read_balance(
-        state,
-        &wrapper.fee.token,
-        &wrapper.fee_payer(),
-    )
-    .unwrap();
+    let fee_source = fee_source(tx, wrapper);
+    let balance =
+        crate::token::read_balance(state, &wrapper.fee.token, &fee_source)
+            .unwrap();
 
     match wrapper.get_tx_fee() {
         Ok(fees) => {
             let fees =
                 crate::token::denom_to_amount(fees, &wrapper.fee.token, state)
                     .map_err(|e| Error::FeeError(e.to_string()))?;
+            // Deduct the fees from the spend limit of the fee allowance, if
+            // any
+            if let Some(allowance) =
+                check_fee_allowance(state, tx, wrapper, fees)?
+            {
+                let allowance_key = crate::account::fee_allowance_key(
+                    &fee_source,
+                    &wrapper.fee_payer(),
+                );
+                state
+                    .write_log_mut()
+                    .write(&allowance_key, allowance.serialize_to_vec())
+                    .map_err(|e| Error::FeeError(e.to_string()))?;
+            }
             if balance.checked_sub(fees).is_some() {
+                let base_fee = base_fee_portion(state, wrapper, fees)?;
+                burn_base_fee(state, wrapper, &fee_source, base_fee)?;
                 token_transfer(
                     state,
                     &wrapper.fee.token,
-                    &wrapper.fee_payer(),
+                    &fee_source,
                     block_proposer,
-                    fees,
+                    fees.checked_sub(base_fee).unwrap_or_default(),
                 )
                 .map_err(|e| Error::FeeError(e.to_string()))
             } else {
                 // Balance was insufficient for fee payment, move all the
                 // available funds in the transparent balance of
-                // the fee payer. This shouldn't happen as it should be
-                // prevented from mempool.
+                // the fee payer. The base fee portion is still burned first.
+                // This shouldn't happen as it should be prevented from
+                // mempool.
                 tracing::error!(
                     "Transfer of tx fee cannot be applied to due to \
                      insufficient funds. Falling back to transferring the \
                      available balance which is less than the fee. This \
                      shouldn't happen."
                 );
+                let base_fee = std::cmp::min(
+                    base_fee_portion(state, wrapper, fees)?,
+                    balance,
+                );
+                burn_base_fee(state, wrapper, &fee_source, base_fee)?;
                 token_transfer(
                     state,
                     &wrapper.fee.token,
-                    &wrapper.fee_payer(),
+                    &fee_source,
                     block_proposer,
-                    balance,
+                    balance.checked_sub(base_fee).unwrap_or_default(),
                 )
                 .map_err(|e| Error::FeeError(e.to_string()))?;
 
                 Err(Error::FeeError(
                     "Transparent balance of wrapper's signer was insufficient \
                      to pay fee. All the available transparent funds have \
-                     been moved to the block proposer"
+                     been burned as base fee or moved to the block proposer"
                         .to_string(),
                 ))
             }
@@ -488,6 +534,44 @@ where
     }
 }
 
+/// Burn the base fee portion of the fees of a wrapper tx from the fee source
+fn burn_base_fee<S>(
+    state: &mut S,
+    wrapper: &WrapperTx,
+    fee_source: &Address,
+    base_fee: Amount,
+) -> Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    if base_fee.is_zero() {
+        return Ok(());
+    }
+    crate::token::burn_tokens(state, &wrapper.fee.token, fee_source, base_fee)
+        .map_err(|e| Error::FeeError(e.to_string()))
+}
+
+/// Get the portion of the fees of a wrapper tx that covers the base fee, if
+/// enabled. The rest of the fees is the tip to the block proposer.
+fn base_fee_portion<S>(
+    state: &S,
+    wrapper: &WrapperTx,
+    fees: Amount,
+) -> Result<Amount>
+where
+    S: StorageRead,
+{
+    let base_fee = namada_parameters::read_base_fee(state, &wrapper.fee.token)
+        .map_err(|e| Error::FeeError(e.to_string()))?
+        .unwrap_or_default();
+    let base_fee = base_fee
+        .checked_mul(Amount::from(u64::from(wrapper.gas_limit)))
+        .ok_or_else(|| {
+            Error::FeeError("Overflow in the base fee".to_string())
+        })?;
+    Ok(std::cmp::min(base_fee, fees))
+}
+
 /// Transfer `token` from `src` to `dest`. Returns an `Err` if `src` has
 /// insufficient balance or if the transfer the `dest` would overflow (This can
 /// only happen if the total supply doesn't fit in `token::Amount`). Contrary to
@@ -538,15 +622,16 @@ where
     }
 }
 
-/// Check if the fee payer has enough transparent balance to pay fees
-pub fn check_fees<S>(state: &S, wrapper: &WrapperTx) -> Result<()>
+/// Check if the fee payer, or the fee granter if the wrapper draws from a fee
+/// allowance, has enough transparent balance to pay fees
+pub fn check_fees<S>(state: &S, tx: &Tx, wrapper: &WrapperTx) -> Result<()>
 where
     S: State + StorageRead,
 {
     let balance = crate::token::read_balance(
         state,
         &wrapper.fee.token,
-        &wrapper.fee_payer(),
+        &fee_source(tx, wrapper),
     )
     .unwrap();
 
@@ -556,6 +641,7 @@ where
 
     let fees = crate::token::denom_to_amount(fees, &wrapper.fee.token, state)
         .map_err(|e| Error::FeeError(e.to_string()))?;
+    check_fee_allowance(state, tx, wrapper, fees)?;
     if balance.checked_sub(fees).is_some() {
         Ok(())
     } else {
@@ -565,8 +651,88 @@ where
     }
 }
 
+/// Check that the fee allowance referenced by the wrapper tx, if any, can pay
+/// for the given fees and for every inner transaction. Returns the allowance
+/// with its spend limit reduced by the fees.
+fn check_fee_allowance<S>(
+    state: &S,
+    tx: &Tx,
+    wrapper: &WrapperTx,
+    fees: Amount,
+) -> Result<Option<FeeAllowance>>
+where
+    S: StorageRead,
+{
+    let Some(granter) = tx.fee_granter() else {
+        return Ok(None);
+    };
+    // The unshielded funds would be credited to the fee payer and not to the
+    // granter
+    if wrapper.unshield_section_hash.is_some() {
+        return Err(Error::FeeError(
+            "Fee unshielding cannot be combined with a fee allowance"
+                .to_string(),
+        ));
+    }
+    let grantee = wrapper.fee_payer();
+    let allowance = crate::account::fee_allowance(state, &granter, &grantee)
+        .map_err(Error::StorageError)?
+        .ok_or_else(|| {
+            Error::FeeError(format!(
+                "No fee allowance granted by {granter} to {grantee}"
+            ))
+        })?;
+    if allowance.token != wrapper.fee.token {
+        return Err(Error::FeeError(format!(
+            "The fee allowance granted by {granter} only covers fees paid in \
+             {}",
+            allowance.token
+        )));
+    }
+    let current_epoch = state.get_block_epoch().map_err(Error::StorageError)?;
+    if allowance.is_expired(current_epoch) {
+        return Err(Error::FeeError(format!(
+            "The fee allowance granted by {granter} has expired"
+        )));
+    }
+    // Every inner transaction must be covered by the allowance
+    let code_sechashes = if tx.is_batch() {
+        tx.batch()
+            .iter()
+            .map(|commitments| commitments.code_hash)
+            .collect()
+    } else {
+        vec![*tx.code_sechash()]
+    };
+    for code_sechash in code_sechashes {
+        let is_allowed = tx
+            .get_section(&code_sechash)
+            .and_then(|section| section.code_sec())
+            .map(|code_sec| allowance.allows_code(&code_sec.code.hash()))
+            .unwrap_or_else(|| allowance.allowed_code_hashes.is_empty());
+        if !is_allowed {
+            return Err(Error::FeeError(format!(
+                "The fee allowance granted by {granter} does not cover the \
+                 inner transaction's code"
+            )));
+        }
+    }
+    let spend_limit =
+        allowance.spend_limit.checked_sub(fees).ok_or_else(|| {
+            Error::FeeError(format!(
+                "The fee allowance granted by {granter} is insufficient to \
+                 pay fees"
+            ))
+        })?;
+    Ok(Some(FeeAllowance {
+        spend_limit,
+        ..allowance
+    }))
+}
+
 /// Apply a transaction going via the wasm environment. Gas will be metered and
-/// validity predicates will be triggered in the normal way.
+/// validity predicates will be triggered in the normal way. The inner
+/// transactions of a batch are applied with [`apply_wasm_batch`].
 pub fn apply_wasm_tx<'a, S, D, H, CA>(
     tx: Tx,
     tx_index: &TxIndex,
@@ -593,6 +759,21 @@ where
         return Err(Error::ReplayAttempt(tx_hash));
     }
 
+    // An inner tx extracted from a batch must not be applied on its own
+    if tx.has_undesignated_batch() {
+        return Err(Error::UndesignatedBatch);
+    }
+    if tx.is_batch() {
+        return apply_wasm_batch(
+            &tx,
+            tx_index,
+            state,
+            tx_gas_meter,
+            vp_wasm_cache,
+            tx_wasm_cache,
+        );
+    }
+
     let verifiers = execute_tx(
         &tx,
         tx_index,
@@ -612,6 +793,7 @@ where
     })?;
 
     let gas_used = tx_gas_meter.borrow().get_tx_consumed_gas();
+    let gas_breakdown = tx_gas_meter.borrow().breakdown().cloned();
     let initialized_accounts = state.write_log().get_initialized_accounts();
     let changed_keys = state.write_log().get_keys();
     let ibc_events = state.write_log_mut().take_ibc_events();
@@ -623,6 +805,99 @@ where
         initialized_accounts,
         ibc_events,
         eth_bridge_events: BTreeSet::default(),
+        gas_breakdown,
+        batch_results: vec![],
+    })
+}
+
+/// Apply the inner transactions of a batch in order. Each inner transaction is
+/// validated by the VPs triggered by its own changes, with its own code and
+/// data designated in the header, and its changes are then precommitted to
+/// the batch precommit so that they make the prior state of the following
+/// inner transactions, unlike the changes of a fee unshielding. Stops
+/// at the first inner transaction rejected by the VPs. The caller then drops
+/// the precommitted changes together with the rejected ones, so that the inner
+/// transactions are either all committed or all dropped.
+fn apply_wasm_batch<S, D, H, CA>(
+    tx: &Tx,
+    tx_index: &TxIndex,
+    state: &mut S,
+    tx_gas_meter: &RefCell<TxGasMeter>,
+    vp_wasm_cache: &mut VpCache<CA>,
+    tx_wasm_cache: &mut TxCache<CA>,
+) -> Result<TxResult>
+where
+    S: State<D = D, H = H> + Sync,
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+    CA: 'static + WasmCacheAccess + Sync,
+{
+    let batch = tx.batch();
+    let mut vps_result = VpsResult::default();
+    let mut changed_keys = BTreeSet::new();
+    let mut initialized_accounts = vec![];
+    let mut batch_results = Vec::with_capacity(batch.len());
+    for (index, commitments) in batch.iter().enumerate() {
+        let inner_tx = tx.batch_inner(commitments);
+        let gas_before = tx_gas_meter.borrow().get_tx_consumed_gas();
+        let verifiers = execute_tx(
+            &inner_tx,
+            tx_index,
+            state,
+            tx_gas_meter,
+            vp_wasm_cache,
+            tx_wasm_cache,
+        )
+        .map_err(|err| match err {
+            Error::TxRunnerError(err) => {
+                Error::BatchInnerTxRunnerError(index, err)
+            }
+            err => err,
+        })?;
+        let inner_vps_result = check_vps(CheckVps {
+            tx: &inner_tx,
+            tx_index,
+            state,
+            tx_gas_meter: &mut tx_gas_meter.borrow_mut(),
+            verifiers_from_tx: &verifiers,
+            vp_wasm_cache,
+        })?;
+        let gas_used = tx_gas_meter
+            .borrow()
+            .get_tx_consumed_gas()
+            .checked_sub(gas_before)
+            .unwrap_or_default();
+        changed_keys.extend(state.write_log().get_keys());
+        initialized_accounts
+            .extend(state.write_log().get_initialized_accounts());
+
+        let is_accepted = inner_vps_result.rejected_vps.is_empty();
+        vps_result.merge(inner_vps_result.clone());
+        batch_results.push(BatchedTxResult {
+            code_hash: commitments.code_hash,
+            gas_used,
+            verifiers,
+            vps_result: inner_vps_result,
+        });
+        if !is_accepted {
+            break;
+        }
+        state.write_log_mut().precommit_batch_tx();
+    }
+
+    let gas_used = tx_gas_meter.borrow().get_tx_consumed_gas();
+    let gas_breakdown = tx_gas_meter.borrow().breakdown().cloned();
+    let ibc_events = state.write_log_mut().take_ibc_events();
+
+    Ok(TxResult {
+        gas_used,
+        changed_keys,
+        vps_result,
+        initialized_accounts,
+        ibc_events,
+        eth_bridge_events: BTreeSet::default(),
+        gas_breakdown,
+        batch_results,
     })
 }
 
@@ -634,21 +909,43 @@ where
     H: 'static + StorageHasher + Sync,
 {
     if let TxType::Decrypted(DecryptedTx::Decrypted) = tx.header().tx_type {
-        if let Some(code_sec) = tx
-            .get_section(tx.code_sechash())
-            .and_then(|x| Section::code_sec(&x))
-        {
-            if crate::parameters::is_tx_allowed(state, &code_sec.code.hash())
-                .map_err(Error::StorageError)?
-            {
-                return Ok(());
+        if tx.is_batch() {
+            // Every inner transaction of a batch must be allowed
+            for commitments in tx.batch() {
+                check_code_allowed(&commitments.code_hash, tx, state)?;
             }
+            return Ok(());
         }
-        return Err(Error::DisallowedTx);
+        return check_code_allowed(tx.code_sechash(), tx, state);
     }
     Ok(())
 }
 
+/// Returns [`Error::DisallowedTx`] when the code section with the given hash
+/// is missing from the tx or its code `Hash` is not included in the
+/// `tx_allowlist` parameter.
+fn check_code_allowed<D, H>(
+    code_sechash: &Hash,
+    tx: &Tx,
+    state: &WlState<D, H>,
+) -> Result<()>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    if let Some(code_sec) = tx
+        .get_section(code_sechash)
+        .and_then(|x| Section::code_sec(&x))
+    {
+        if crate::parameters::is_tx_allowed(state, &code_sec.code.hash())
+            .map_err(Error::StorageError)?
+        {
+            return Ok(());
+        }
+    }
+    Err(Error::DisallowedTx)
+}
+
 /// Apply a derived transaction to storage based on some protocol transaction.
 /// The logic here must be completely deterministic and will be executed by all
 /// full nodes every time a protocol transaction is included in a block. Storage
@@ -830,7 +1127,7 @@ where
                         .map_err(Error::StateError)?;
                     gas_meter
                         .borrow_mut()
-                        .consume(gas)
+                        .consume_in(GasCategory::StorageRead, gas)
                         .map_err(|err| Error::GasError(err.to_string()))?;
                     let Some(vp_code_hash) = vp_hash else {
                         return Err(Error::MissingAddress(addr.clone()));
@@ -943,6 +1240,12 @@ where
                             masp.validate_tx(tx, &keys_changed, &verifiers)
                                 .map_err(Error::MaspNativeVpError)
                         }
+                        InternalAddress::Scheduler => {
+                            let scheduler = SchedulerVp { ctx };
+                            scheduler
+                                .validate_tx(tx, &keys_changed, &verifiers)
+                                .map_err(Error::SchedulerNativeVpError)
+                        }
                     };
 
                     accepted.map_err(|err| {
@@ -988,9 +1291,13 @@ where
                 },
             }
 
+            let gas_meter = gas_meter.into_inner();
+            if let Some(breakdown) = gas_meter.breakdown() {
+                result.gas_breakdown.insert(addr.clone(), breakdown.clone());
+            }
             result
                 .gas_used
-                .set(gas_meter.into_inner())
+                .set(gas_meter)
                 .map_err(|err| Error::GasError(err.to_string()))?;
 
             Ok(result)
@@ -1016,6 +1323,8 @@ fn merge_vp_results(
     errors.append(&mut b.errors);
     let invalid_sig = a.invalid_sig || b.invalid_sig;
     let mut gas_used = a.gas_used;
+    let mut gas_breakdown = a.gas_breakdown;
+    gas_breakdown.append(&mut b.gas_breakdown);
 
     gas_used
         .merge(b.gas_used, tx_gas_meter)
@@ -1025,6 +1334,7 @@ fn merge_vp_results(
         accepted_vps,
         rejected_vps,
         gas_used,
+        gas_breakdown,
         errors,
         invalid_sig,
     })
@@ -1040,7 +1350,9 @@ mod tests {
     use namada_core::ethereum_events::testing::DAI_ERC20_ETH_ADDRESS;
     use namada_core::ethereum_events::{EthereumEvent, TransferToNamada};
     use namada_core::keccak::keccak_hash;
-    use namada_core::storage::BlockHeight;
+    use namada_core::key::RefTo;
+    use namada_core::storage::{BlockHeight, Epoch};
+    use namada_core::token::DenominatedAmount;
     use namada_core::voting_power::FractionalVotingPower;
     use namada_core::{address, key};
     use namada_ethereum_bridge::protocol::transactions::votes::{
@@ -1050,6 +1362,7 @@ mod tests {
     use namada_ethereum_bridge::storage::proof::EthereumProof;
     use namada_ethereum_bridge::storage::{vote_tallies, vp};
     use namada_ethereum_bridge::test_utils;
+    use namada_tx::data::Fee;
     use namada_tx::{SignableEthMessage, Signed};
     use namada_vote_ext::bridge_pool_roots::BridgePoolRootVext;
     use namada_vote_ext::ethereum_events::EthereumEventsVext;
@@ -1227,4 +1540,218 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_apply_wasm_batch_tx_allowlist() {
+        let (mut state, _validators) = test_utils::setup_default_storage();
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
+        // pseudo-random code hashes
+        let allowed_code = vec![1_u8, 2, 3];
+        let allowed_hash = Hash::sha256(&allowed_code);
+        let other_code = vec![4_u8, 5, 6];
+        tx.push_batch_inner(
+            namada_tx::Code::new(allowed_code, None),
+            namada_tx::Data::new(vec![]),
+        )
+        .push_batch_inner(
+            namada_tx::Code::new(other_code, None),
+            namada_tx::Data::new(vec![]),
+        );
+
+        // Check that a single disallowed inner tx rejects the whole batch
+        let allowlist = vec![allowed_hash.to_string()];
+        crate::parameters::update_tx_allowlist_parameter(&mut state, allowlist)
+            .unwrap();
+        state.commit_tx();
+
+        let result = check_tx_allowed(&tx, &state);
+        assert_matches!(result.unwrap_err(), Error::DisallowedTx);
+    }
+
+    /// Test that the changes of the inner txs of a batch are all dropped when a
+    /// later inner tx gets rejected by the VPs
+    #[test]
+    fn test_apply_wasm_batch_atomicity() {
+        use namada_test_utils::tx_data::TxWriteData;
+        use namada_test_utils::TestWasms;
+
+        let (mut state, _validators) = test_utils::setup_default_storage();
+        let (mut vp_wasm_cache, _vp_cache_dir) =
+            wasm::compilation_cache::common::testing::cache();
+        let (mut tx_wasm_cache, _tx_cache_dir) =
+            wasm::compilation_cache::common::testing::cache();
+
+        // The first account accepts any change, the second one rejects them
+        let accepting = address::testing::established_address_1();
+        let rejecting = address::testing::established_address_2();
+        for (addr, vp) in [
+            (&accepting, TestWasms::VpAlwaysTrue),
+            (&rejecting, TestWasms::VpAlwaysFalse),
+        ] {
+            let vp_code = vp.read_bytes();
+            let vp_hash = Hash::sha256(&vp_code);
+            state
+                .db_write(
+                    &Key::wasm_code_len(&vp_hash),
+                    (vp_code.len() as u64).serialize_to_vec(),
+                )
+                .unwrap();
+            state.db_write(&Key::wasm_code(&vp_hash), vp_code).unwrap();
+            state
+                .db_write(
+                    &Key::validity_predicate(addr),
+                    vp_hash.serialize_to_vec(),
+                )
+                .unwrap();
+        }
+
+        let accepted_key = Key::from(accepting.to_db_key())
+            .push(&"value".to_string())
+            .unwrap();
+        let rejected_key = Key::from(rejecting.to_db_key())
+            .push(&"value".to_string())
+            .unwrap();
+        let tx_code = TestWasms::TxWriteStorageKey.read_bytes();
+        let mut tx = Tx::new(state.in_mem().chain_id.clone(), None);
+        tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
+        for key in [&accepted_key, &rejected_key] {
+            let data = TxWriteData {
+                key: key.clone(),
+                value: b"batched".to_vec(),
+            };
+            tx.push_batch_inner(
+                namada_tx::Code::new(tx_code.clone(), None),
+                namada_tx::Data::new(data.serialize_to_vec()),
+            );
+        }
+
+        let tx_gas_meter =
+            RefCell::new(TxGasMeter::new_from_sub_limit(u64::MAX.into()));
+        let result = apply_wasm_tx(
+            tx,
+            &TxIndex::default(),
+            ShellParams::new(
+                &tx_gas_meter,
+                &mut state,
+                &mut vp_wasm_cache,
+                &mut tx_wasm_cache,
+            ),
+        )
+        .unwrap();
+
+        // Each inner tx is only validated by the VPs triggered by its own
+        // changes
+        assert!(!result.is_accepted());
+        assert_eq!(result.batch_results.len(), 2);
+        let first = &result.batch_results[0].vps_result;
+        assert!(first.accepted_vps.contains(&accepting));
+        assert!(first.rejected_vps.is_empty());
+        let second = &result.batch_results[1].vps_result;
+        assert!(second.rejected_vps.contains(&rejecting));
+        assert!(!second.accepted_vps.contains(&accepting));
+
+        // Dropping the rejected batch also drops the changes of the first
+        // inner tx, even though it was accepted
+        state.drop_tx();
+        assert!(!state.has_key(&accepted_key).unwrap());
+        assert!(!state.has_key(&rejected_key).unwrap());
+    }
+
+    /// Test that the fees of a wrapper referencing a fee allowance are drawn
+    /// from the granter's balance, up to the allowance's spend limit
+    #[test]
+    fn test_transfer_fee_from_allowance() {
+        let (mut state, _validators) = test_utils::setup_default_storage();
+        let token = state.in_mem().native_token.clone();
+        let granter = address::testing::established_address_1();
+        let block_proposer = address::testing::established_address_2();
+        let keypair = key::testing::keypair_1();
+        let grantee = Address::from(&keypair.ref_to());
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![1_u8, 2, 3], None)
+            .add_serialized_data(vec![])
+            .add_wrapper(
+                Fee {
+                    amount_per_gas_unit: DenominatedAmount::native(
+                        Amount::from_u64(1),
+                    ),
+                    token: token.clone(),
+                },
+                keypair.ref_to(),
+                Epoch(0),
+                100.into(),
+                None,
+            )
+            .add_fee_granter(granter.clone());
+        let wrapper = tx.header().wrapper().unwrap();
+
+        crate::token::write_denom(&mut state, &token, 6.into()).unwrap();
+        let fees = crate::token::denom_to_amount(
+            wrapper.get_tx_fee().unwrap(),
+            &token,
+            &state,
+        )
+        .unwrap();
+        let initial_balance = Amount::native_whole(100);
+        crate::token::credit_tokens(
+            &mut state,
+            &token,
+            &granter,
+            initial_balance,
+        )
+        .unwrap();
+        // The allowance only covers a single payment of the fees
+        let allowance = FeeAllowance {
+            token: token.clone(),
+            spend_limit: fees,
+            expiration: None,
+            allowed_code_hashes: Default::default(),
+        };
+        crate::account::grant_fee_allowance(
+            &mut state,
+            crate::account::GrantFeeAllowance {
+                granter: granter.clone(),
+                grantee: grantee.clone(),
+                allowance: allowance.clone(),
+            },
+        )
+        .unwrap();
+        state.commit_tx();
+
+        check_fees(&state, &tx, &wrapper).unwrap();
+        transfer_fee(&mut state, &block_proposer, &tx, &wrapper).unwrap();
+        state.commit_tx();
+        let granter_balance =
+            crate::token::read_balance(&state, &token, &granter).unwrap();
+        assert_eq!(granter_balance, initial_balance.checked_sub(fees).unwrap());
+        let remaining_allowance =
+            crate::account::fee_allowance(&state, &granter, &grantee)
+                .unwrap()
+                .unwrap();
+        assert!(remaining_allowance.spend_limit.is_zero());
+
+        // The spend limit is exhausted
+        let result = check_fees(&state, &tx, &wrapper);
+        assert_matches!(result.unwrap_err(), Error::FeeError(_));
+
+        // The allowance doesn't cover the code of the inner tx
+        crate::account::grant_fee_allowance(
+            &mut state,
+            crate::account::GrantFeeAllowance {
+                granter,
+                grantee,
+                allowance: FeeAllowance {
+                    allowed_code_hashes: [Hash::sha256([4_u8, 5, 6])].into(),
+                    ..allowance
+                },
+            },
+        )
+        .unwrap();
+        state.commit_tx();
+        let result = check_fees(&state, &tx, &wrapper);
+        assert_matches!(result.unwrap_err(), Error::FeeError(_));
+    }
 }
@@ -19,7 +19,7 @@ use thiserror::Error;
 
 use crate::ibc::IbcEvent;
 use crate::ledger::gas;
-use crate::ledger::gas::{GasMetering, VpGasMeter};
+use crate::ledger::gas::{GasCategory, GasMetering, VpGasMeter};
 
 /// These runtime errors will abort VP execution immediately
 #[allow(missing_docs)]
@@ -50,17 +50,34 @@ pub enum RuntimeError {
 /// VP environment function result
 pub type EnvResult<T> = std::result::Result<T, RuntimeError>;
 
-/// Add a gas cost incured in a validity predicate
+/// Add a gas cost incured in a validity predicate by the execution of its
+/// wasm code
 pub fn add_gas(
     gas_meter: &RefCell<VpGasMeter>,
     used_gas: u64,
     sentinel: &RefCell<VpSentinel>,
 ) -> EnvResult<()> {
-    gas_meter.borrow_mut().consume(used_gas).map_err(|err| {
-        sentinel.borrow_mut().set_out_of_gas();
-        tracing::info!("Stopping VP execution because of gas error: {}", err);
-        RuntimeError::OutOfGas(err)
-    })
+    add_gas_in(gas_meter, GasCategory::WasmExecution, used_gas, sentinel)
+}
+
+/// Add a gas cost of the given category incured in a validity predicate
+pub fn add_gas_in(
+    gas_meter: &RefCell<VpGasMeter>,
+    category: GasCategory,
+    used_gas: u64,
+    sentinel: &RefCell<VpSentinel>,
+) -> EnvResult<()> {
+    gas_meter
+        .borrow_mut()
+        .consume_in(category, used_gas)
+        .map_err(|err| {
+            sentinel.borrow_mut().set_out_of_gas();
+            tracing::info!(
+                "Stopping VP execution because of gas error: {}",
+                err
+            );
+            RuntimeError::OutOfGas(err)
+        })
 }
 
 /// Storage read prior state (before tx execution). It will try to read from the
@@ -75,7 +92,7 @@ where
     S: StateRead + Debug,
 {
     let (log_val, gas) = state.write_log().read_pre(key);
-    add_gas(gas_meter, gas, sentinel)?;
+    add_gas_in(gas_meter, GasCategory::StorageRead, gas, sentinel)?;
     match log_val {
         Some(write_log::StorageModification::Write { ref value }) => {
             Ok(Some(value.clone()))
@@ -97,7 +114,7 @@ where
             // When not found in write log, try to read from the storage
             let (value, gas) =
                 state.db_read(key).map_err(RuntimeError::StorageError)?;
-            add_gas(gas_meter, gas, sentinel)?;
+            add_gas_in(gas_meter, GasCategory::StorageRead, gas, sentinel)?;
             Ok(value)
         }
     }
@@ -116,7 +133,7 @@ where
 {
     // Try to read from the write log first
     let (log_val, gas) = state.write_log().read(key);
-    add_gas(gas_meter, gas, sentinel)?;
+    add_gas_in(gas_meter, GasCategory::StorageRead, gas, sentinel)?;
     match log_val {
         Some(write_log::StorageModification::Write { ref value }) => {
             Ok(Some(value.clone()))
@@ -138,7 +155,7 @@ where
             // When not found in write log, try to read from the storage
             let (value, gas) =
                 state.db_read(key).map_err(RuntimeError::StorageError)?;
-            add_gas(gas_meter, gas, sentinel)?;
+            add_gas_in(gas_meter, GasCategory::StorageRead, gas, sentinel)?;
             Ok(value)
         }
     }
@@ -157,7 +174,7 @@ where
 {
     // Try to read from the write log first
     let (log_val, gas) = state.write_log().read(key);
-    add_gas(gas_meter, gas, sentinel)?;
+    add_gas_in(gas_meter, GasCategory::StorageRead, gas, sentinel)?;
     match log_val {
         Some(write_log::StorageModification::Temp { ref value }) => {
             Ok(Some(value.clone()))
@@ -180,7 +197,7 @@ where
 {
     // Try to read from the write log first
     let (log_val, gas) = state.write_log().read_pre(key);
-    add_gas(gas_meter, gas, sentinel)?;
+    add_gas_in(gas_meter, GasCategory::StorageRead, gas, sentinel)?;
     match log_val {
         Some(&write_log::StorageModification::Write { .. }) => Ok(true),
         Some(&write_log::StorageModification::Delete) => {
@@ -193,7 +210,7 @@ where
             // When not found in write log, try to check the storage
             let (present, gas) =
                 state.db_has_key(key).map_err(RuntimeError::StorageError)?;
-            add_gas(gas_meter, gas, sentinel)?;
+            add_gas_in(gas_meter, GasCategory::StorageRead, gas, sentinel)?;
             Ok(present)
         }
     }
@@ -212,7 +229,7 @@ where
 {
     // Try to read from the write log first
     let (log_val, gas) = state.write_log().read(key);
-    add_gas(gas_meter, gas, sentinel)?;
+    add_gas_in(gas_meter, GasCategory::StorageRead, gas, sentinel)?;
     match log_val {
         Some(&write_log::StorageModification::Write { .. }) => Ok(true),
         Some(&write_log::StorageModification::Delete) => {
@@ -225,7 +242,7 @@ where
             // When not found in write log, try to check the storage
             let (present, gas) =
                 state.db_has_key(key).map_err(RuntimeError::StorageError)?;
-            add_gas(gas_meter, gas, sentinel)?;
+            add_gas_in(gas_meter, GasCategory::StorageRead, gas, sentinel)?;
             Ok(present)
         }
     }
@@ -241,7 +258,7 @@ where
     S: StateRead + Debug,
 {
     let (chain_id, gas) = state.in_mem().get_chain_id();
-    add_gas(gas_meter, gas, sentinel)?;
+    add_gas_in(gas_meter, GasCategory::Other, gas, sentinel)?;
     Ok(chain_id)
 }
 
@@ -256,7 +273,7 @@ where
     S: StateRead + Debug,
 {
     let (height, gas) = state.in_mem().get_block_height();
-    add_gas(gas_meter, gas, sentinel)?;
+    add_gas_in(gas_meter, GasCategory::Other, gas, sentinel)?;
     Ok(height)
 }
 
@@ -272,7 +289,7 @@ where
 {
     let (header, gas) = StateRead::get_block_header(state, Some(height))
         .map_err(RuntimeError::StorageError)?;
-    add_gas(gas_meter, gas, sentinel)?;
+    add_gas_in(gas_meter, GasCategory::Other, gas, sentinel)?;
     Ok(header)
 }
 
@@ -287,7 +304,7 @@ where
     S: StateRead + Debug,
 {
     let (hash, gas) = state.in_mem().get_block_hash();
-    add_gas(gas_meter, gas, sentinel)?;
+    add_gas_in(gas_meter, GasCategory::Other, gas, sentinel)?;
     Ok(hash)
 }
 
@@ -298,8 +315,9 @@ pub fn get_tx_code_hash(
     tx: &Tx,
     sentinel: &RefCell<VpSentinel>,
 ) -> EnvResult<Option<Hash>> {
-    add_gas(
+    add_gas_in(
         gas_meter,
+        GasCategory::Other,
         HASH_LENGTH as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
         sentinel,
     )?;
@@ -321,7 +339,7 @@ where
     S: StateRead + Debug,
 {
     let (epoch, gas) = state.in_mem().get_current_epoch();
-    add_gas(gas_meter, gas, sentinel)?;
+    add_gas_in(gas_meter, GasCategory::Other, gas, sentinel)?;
     Ok(epoch)
 }
 
@@ -332,8 +350,9 @@ pub fn get_tx_index(
     tx_index: &TxIndex,
     sentinel: &RefCell<VpSentinel>,
 ) -> EnvResult<TxIndex> {
-    add_gas(
+    add_gas_in(
         gas_meter,
+        GasCategory::Other,
         TX_INDEX_LENGTH as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
         sentinel,
     )?;
@@ -349,8 +368,9 @@ pub fn get_native_token<S>(
 where
     S: StateRead + Debug,
 {
-    add_gas(
+    add_gas_in(
         gas_meter,
+        GasCategory::Other,
         ESTABLISHED_ADDRESS_BYTES_LEN as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
         sentinel,
     )?;
@@ -366,8 +386,9 @@ pub fn get_pred_epochs<S>(
 where
     S: StateRead + Debug,
 {
-    add_gas(
+    add_gas_in(
         gas_meter,
+        GasCategory::Other,
         state.in_mem().block.pred_epochs.first_block_heights.len() as u64
             * 8
             * MEMORY_ACCESS_GAS_PER_BYTE,
@@ -410,7 +431,7 @@ where
     D: DB + for<'iter> DBIter<'iter>,
 {
     let (iter, gas) = namada_state::iter_prefix_pre(write_log, db, prefix);
-    add_gas(gas_meter, gas, sentinel)?;
+    add_gas_in(gas_meter, GasCategory::StorageRead, gas, sentinel)?;
     Ok(iter)
 }
 
@@ -430,7 +451,7 @@ where
     D: DB + for<'iter> DBIter<'iter>,
 {
     let (iter, gas) = namada_state::iter_prefix_post(write_log, db, prefix);
-    add_gas(gas_meter, gas, sentinel)?;
+    add_gas_in(gas_meter, GasCategory::StorageRead, gas, sentinel)?;
     Ok(iter)
 }
 
@@ -444,7 +465,7 @@ where
     DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
 {
     if let Some((key, val, gas)) = iter.next() {
-        add_gas(gas_meter, gas, sentinel)?;
+        add_gas_in(gas_meter, GasCategory::StorageRead, gas, sentinel)?;
         return Ok(Some((key, val)));
     }
     Ok(None)
@@ -13,7 +13,7 @@ use namada_core::internal::KeyVal;
 use namada_core::storage::TX_INDEX_LENGTH;
 use namada_core::validity_predicate::VpSentinel;
 use namada_gas::{
-    self as gas, GasMetering, TxGasMeter, VpGasMeter,
+    self as gas, GasCategory, GasMetering, TxGasMeter, VpGasMeter,
     MEMORY_ACCESS_GAS_PER_BYTE,
 };
 use namada_state::write_log::{self, WriteLog};
@@ -572,11 +572,26 @@ where
     }
 }
 
-/// Add a gas cost incured in a transaction
+/// Add a gas cost incured in a transaction by the execution of its wasm code
 pub fn tx_charge_gas<MEM, D, H, CA>(
     env: &TxVmEnv<MEM, D, H, CA>,
     used_gas: u64,
 ) -> TxResult<()>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: WasmCacheAccess,
+{
+    tx_charge_gas_in::<MEM, D, H, CA>(env, GasCategory::WasmExecution, used_gas)
+}
+
+/// Add a gas cost of the given category incured in a transaction
+fn tx_charge_gas_in<MEM, D, H, CA>(
+    env: &TxVmEnv<MEM, D, H, CA>,
+    category: GasCategory,
+    used_gas: u64,
+) -> TxResult<()>
 where
     MEM: VmMemory,
     D: 'static + DB + for<'iter> DBIter<'iter>,
@@ -585,15 +600,18 @@ where
 {
     let (gas_meter, sentinel) = env.ctx.gas_meter_and_sentinel();
     // if we run out of gas, we need to stop the execution
-    gas_meter.borrow_mut().consume(used_gas).map_err(|err| {
-        sentinel.borrow_mut().set_out_of_gas();
-        tracing::info!(
-            "Stopping transaction execution because of gas error: {}",
-            err
-        );
+    gas_meter
+        .borrow_mut()
+        .consume_in(category, used_gas)
+        .map_err(|err| {
+            sentinel.borrow_mut().set_out_of_gas();
+            tracing::info!(
+                "Stopping transaction execution because of gas error: {}",
+                err
+            );
 
-        TxRuntimeError::OutOfGas(err)
-    })
+            TxRuntimeError::OutOfGas(err)
+        })
 }
 
 /// Called from VP wasm to request to use the given gas amount
@@ -609,7 +627,12 @@ where
     CA: WasmCacheAccess,
 {
     let (gas_meter, sentinel) = env.ctx.gas_meter_and_sentinel();
-    vp_host_fns::add_gas(gas_meter, used_gas, sentinel)
+    vp_host_fns::add_gas_in(
+        gas_meter,
+        GasCategory::WasmExecution,
+        used_gas,
+        sentinel,
+    )
 }
 
 /// Storage `has_key` function exposed to the wasm VM Tx environment. It will
@@ -740,7 +763,7 @@ where
     let write_log = unsafe { env.ctx.write_log.get() };
     let db = unsafe { env.ctx.db.get() };
     let (iter, gas) = namada_state::iter_prefix_post(write_log, db, &prefix);
-    tx_charge_gas::<MEM, D, H, CA>(env, gas)?;
+    tx_charge_gas_in::<MEM, D, H, CA>(env, GasCategory::StorageRead, gas)?;
 
     let iterators = unsafe { env.ctx.iterators.get() };
     Ok(iterators.insert(iter).id())
@@ -772,7 +795,11 @@ where
             &Key::parse(key.clone())
                 .map_err(TxRuntimeError::StorageDataError)?,
         );
-        tx_charge_gas::<MEM, D, H, CA>(env, iter_gas + log_gas)?;
+        tx_charge_gas_in::<MEM, D, H, CA>(
+            env,
+            GasCategory::StorageRead,
+            iter_gas + log_gas,
+        )?;
         match log_val {
             Some(write_log::StorageModification::Write { ref value }) => {
                 let key_val = borsh::to_vec(&KeyVal {
@@ -905,7 +932,7 @@ where
         .write_log_mut()
         .write_temp(&key, value)
         .map_err(TxRuntimeError::StorageModificationError)?;
-    tx_charge_gas::<MEM, D, H, CA>(env, gas)
+    tx_charge_gas_in::<MEM, D, H, CA>(env, GasCategory::StorageWrite, gas)
 }
 
 fn check_address_existence<MEM, D, H, CA>(
@@ -939,14 +966,18 @@ where
         }
         let vp_key = Key::validity_predicate(&addr);
         let (vp, gas) = state.write_log().read(&vp_key);
-        tx_charge_gas::<MEM, D, H, CA>(env, gas)?;
+        tx_charge_gas_in::<MEM, D, H, CA>(env, GasCategory::StorageRead, gas)?;
         // just check the existence because the write log should not have the
         // delete log of the VP
         if vp.is_none() {
             let (is_present, gas) = state
                 .db_has_key(&vp_key)
                 .map_err(TxRuntimeError::StateError)?;
-            tx_charge_gas::<MEM, D, H, CA>(env, gas)?;
+            tx_charge_gas_in::<MEM, D, H, CA>(
+                env,
+                GasCategory::StorageRead,
+                gas,
+            )?;
             if !is_present {
                 tracing::info!(
                     "Trying to write into storage with a key containing an \
@@ -1477,7 +1508,7 @@ where
         .write_log_mut()
         .write(&key, code_hash)
         .map_err(TxRuntimeError::StorageModificationError)?;
-    tx_charge_gas::<MEM, D, H, CA>(env, gas)
+    tx_charge_gas_in::<MEM, D, H, CA>(env, GasCategory::StorageWrite, gas)
 }
 
 /// Initialize a new account established address.
@@ -1520,7 +1551,7 @@ where
     let gen = &in_mem.address_gen;
     let (addr, gas) = write_log.init_account(gen, code_hash);
     let addr_bytes = addr.serialize_to_vec();
-    tx_charge_gas::<MEM, D, H, CA>(env, gas)?;
+    tx_charge_gas_in::<MEM, D, H, CA>(env, GasCategory::StorageWrite, gas)?;
     let gas = env
         .memory
         .write_bytes(result_ptr, addr_bytes)
@@ -2001,7 +2032,12 @@ where
         &Some(signer),
         threshold,
         max_signatures,
-        || gas_meter.borrow_mut().consume(gas::VERIFY_TX_SIG_GAS),
+        || {
+            gas_meter.borrow_mut().consume_in(
+                GasCategory::SignatureVerification,
+                gas::VERIFY_TX_SIG_GAS,
+            )
+        },
     ) {
         Ok(_) => Ok(HostEnvResult::Success.to_i64()),
         Err(err) => match err {
@@ -2205,7 +2241,12 @@ where
         &None,
         threshold,
         max_signatures,
-        || gas_meter.borrow_mut().consume(gas::VERIFY_TX_SIG_GAS),
+        || {
+            gas_meter.borrow_mut().consume_in(
+                GasCategory::SignatureVerification,
+                gas::VERIFY_TX_SIG_GAS,
+            )
+        },
     ) {
         Ok(_) => Ok(HostEnvResult::Success.to_i64()),
         Err(err) => match err {
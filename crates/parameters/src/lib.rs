@@ -64,6 +64,7 @@ where
         minimum_gas_price,
         fee_unshielding_gas_limit,
         fee_unshielding_descriptions_limit,
+        base_fee_params,
     } = parameters;
 
     // write max tx bytes parameter
@@ -147,6 +148,15 @@ where
     let gas_cost_key = storage::get_gas_cost_key();
     storage.write(&gas_cost_key, minimum_gas_price)?;
 
+    if let Some(base_fee_params) = base_fee_params {
+        let base_fee_params_key = storage::get_base_fee_params_key();
+        storage.write(&base_fee_params_key, base_fee_params)?;
+
+        // The base fee starts at the minimum gas price
+        let base_fee_key = storage::get_base_fee_key();
+        storage.write(&base_fee_key, minimum_gas_price)?;
+    }
+
     Ok(())
 }
 
@@ -305,6 +315,20 @@ where
         .into_storage_result()
 }
 
+/// Read the max expected time per block parameter from store
+pub fn read_max_expected_time_per_block<S>(
+    storage: &S,
+) -> namada_storage::Result<DurationSecs>
+where
+    S: StorageRead,
+{
+    let key = storage::get_max_expected_time_per_block_key();
+    let max_expected_time_per_block = storage.read(&key)?;
+    max_expected_time_per_block
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()
+}
+
 /// Read the cost per unit of gas for the provided token
 pub fn read_gas_cost<S>(
     storage: &S,
@@ -320,6 +344,68 @@ where
     Ok(gas_cost_table.get(token).map(|amount| amount.to_owned()))
 }
 
+/// Read the current base fee per unit of gas for the provided token. Returns
+/// `None` if the base fee is disabled or if the token is not allowed for fee
+/// payment.
+pub fn read_base_fee<S>(
+    storage: &S,
+    token: &Address,
+) -> namada_storage::Result<Option<token::Amount>>
+where
+    S: StorageRead,
+{
+    if !storage.has_key(&storage::get_base_fee_params_key())? {
+        return Ok(None);
+    }
+    let base_fee_table: Option<BTreeMap<Address, token::Amount>> =
+        storage.read(&storage::get_base_fee_key())?;
+    Ok(base_fee_table.and_then(|table| table.get(token).copied()))
+}
+
+/// Update the base fee of every token allowed for fee payment from the gas
+/// used by the last block. This is a no-op if the base fee is disabled.
+pub fn update_base_fee<S>(
+    storage: &mut S,
+    block_gas_used: u64,
+) -> namada_storage::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let Some(base_fee_params) =
+        storage.read::<BaseFeeParams>(&storage::get_base_fee_params_key())?
+    else {
+        return Ok(());
+    };
+    let max_block_gas = get_max_block_gas(storage)?;
+    let minimum_gas_price: BTreeMap<Address, token::Amount> = storage
+        .read(&storage::get_gas_cost_key())?
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()?;
+    let base_fee_key = storage::get_base_fee_key();
+    let base_fee: BTreeMap<Address, token::Amount> =
+        storage.read(&base_fee_key)?.unwrap_or_default();
+
+    // The tokens removed from the minimum gas price table are dropped, the
+    // new ones start at their minimum gas price
+    let next_base_fee: BTreeMap<Address, token::Amount> = minimum_gas_price
+        .into_iter()
+        .map(|(token, minimum)| {
+            let current = base_fee.get(&token).copied().unwrap_or(minimum);
+            let next = base_fee_params.next_base_fee(
+                current,
+                minimum,
+                max_block_gas,
+                block_gas_used,
+            );
+            (token, next)
+        })
+        .collect();
+    if next_base_fee != base_fee {
+        storage.write(&base_fee_key, next_base_fee)?;
+    }
+    Ok(())
+}
+
 /// Read all the parameters from storage. Returns the parameters and gas
 /// cost.
 pub fn read<S>(storage: &S) -> namada_storage::Result<Parameters>
@@ -430,6 +516,10 @@ where
         .ok_or(ReadError::ParametersMissing)
         .into_storage_result()?;
 
+    // read the optional base fee parameters
+    let base_fee_params_key = storage::get_base_fee_params_key();
+    let base_fee_params = storage.read(&base_fee_params_key)?;
+
     // read max tx bytes
     let max_tx_bytes_key = storage::get_max_tx_bytes_key();
     let value = storage.read(&max_tx_bytes_key)?;
@@ -453,6 +543,7 @@ where
         minimum_gas_price,
         fee_unshielding_gas_limit,
         fee_unshielding_descriptions_limit,
+        base_fee_params,
     })
 }
 
@@ -45,6 +45,16 @@ struct Keys {
     fee_unshielding_gas_limit: &'static str,
     fee_unshielding_descriptions_limit: &'static str,
     max_signatures_per_transaction: &'static str,
+    base_fee_params: &'static str,
+    /// Sub-key for storing the current base fee of every token allowed for
+    /// fee payment, updated after every block
+    base_fee: &'static str,
+    // ========================================
+    // Storage layout
+    // ========================================
+    /// Sub-key for storing the version of the storage layout, bumped by
+    /// every applied storage migration
+    storage_version: &'static str,
 }
 
 /// Returns if the key is a parameter key.
@@ -115,6 +125,11 @@ pub fn is_max_tx_bytes_key(key: &Key) -> bool {
     is_max_tx_bytes_key_at_addr(key, &ADDRESS)
 }
 
+/// Returns if the key is the storage version key.
+pub fn is_storage_version_key(key: &Key) -> bool {
+    is_storage_version_key_at_addr(key, &ADDRESS)
+}
+
 /// Storage key used for epoch parameter.
 pub fn get_epoch_duration_storage_key() -> Key {
     get_epoch_duration_key_at_addr(ADDRESS)
@@ -185,11 +200,26 @@ pub fn get_gas_cost_key() -> Key {
     get_minimum_gas_price_key_at_addr(ADDRESS)
 }
 
+/// Storage key used for the base fee parameters
+pub fn get_base_fee_params_key() -> Key {
+    get_base_fee_params_key_at_addr(ADDRESS)
+}
+
+/// Storage key used for the current base fee table
+pub fn get_base_fee_key() -> Key {
+    get_base_fee_key_at_addr(ADDRESS)
+}
+
 /// Storage key used for the max signatures per transaction key
 pub fn get_max_signatures_per_transaction_key() -> Key {
     get_max_signatures_per_transaction_key_at_addr(ADDRESS)
 }
 
+/// Storage key used for the version of the storage layout
+pub fn get_storage_version_key() -> Key {
+    get_storage_version_key_at_addr(ADDRESS)
+}
+
 /// Helper function to retrieve the `max_block_gas` protocol parameter from
 /// storage
 pub fn get_max_block_gas(
@@ -0,0 +1,260 @@
+//! PoS events, emitted into the block events
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use namada_core::address::Address;
+use namada_core::dec::Dec;
+use namada_core::event::{Event, EventLevel, EventType};
+use namada_core::storage::{Epoch, Key};
+use namada_core::token;
+use namada_storage::StorageRead;
+
+use crate::storage::{
+    read_pos_params, validator_commission_rate_handle, validator_state_handle,
+};
+use crate::storage_key::{
+    is_validator_commission_rate_key, is_validator_state_key,
+};
+use crate::types::{SlashType, ValidatorState};
+
+/// The reason for jailing a validator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JailReason {
+    /// The validator missed too many votes in the liveness window
+    Liveness,
+    /// The validator misbehaved and was slashed
+    Slash,
+}
+
+/// An event of the PoS system
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PosEvent {
+    /// A slash was enqueued for processing at a later epoch
+    SlashEnqueued {
+        /// The slashed validator
+        validator: Address,
+        /// The type of the infraction
+        slash_type: SlashType,
+        /// The epoch of the infraction
+        infraction_epoch: Epoch,
+        /// The epoch in which the slash is processed
+        processing_epoch: Epoch,
+        /// The block height of the infraction
+        evidence_height: u64,
+    },
+    /// The enqueued slashes of a validator were processed
+    SlashProcessed {
+        /// The slashed validator
+        validator: Address,
+        /// The epoch of the infractions
+        infraction_epoch: Epoch,
+        /// The slash rate, including the cubic slashing rate
+        rate: Dec,
+        /// The amount of tokens slashed from the validator's stake
+        amount: token::Amount,
+    },
+    /// A validator was jailed
+    ValidatorJailed {
+        /// The jailed validator
+        validator: Address,
+        /// The reason for jailing
+        reason: JailReason,
+        /// The first epoch in which the validator is jailed
+        epoch: Epoch,
+    },
+    /// A validator was unjailed
+    ValidatorUnjailed {
+        /// The unjailed validator
+        validator: Address,
+        /// The first epoch in which the validator is not jailed
+        epoch: Epoch,
+    },
+    /// A validator was deactivated
+    ValidatorDeactivated {
+        /// The deactivated validator
+        validator: Address,
+        /// The first epoch in which the validator is inactive
+        epoch: Epoch,
+    },
+    /// A validator was reactivated
+    ValidatorReactivated {
+        /// The reactivated validator
+        validator: Address,
+        /// The first epoch in which the validator is active
+        epoch: Epoch,
+    },
+    /// The commission rate of a validator was changed
+    CommissionChanged {
+        /// The validator
+        validator: Address,
+        /// The new commission rate
+        rate: Dec,
+        /// The first epoch in which the new rate applies
+        epoch: Epoch,
+    },
+}
+
+impl PosEvent {
+    /// The kind of this event, used as its `kind` attribute
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::SlashEnqueued { .. } => "slash_enqueued",
+            Self::SlashProcessed { .. } => "slash_processed",
+            Self::ValidatorJailed { .. } => "validator_jailed",
+            Self::ValidatorUnjailed { .. } => "validator_unjailed",
+            Self::ValidatorDeactivated { .. } => "validator_deactivated",
+            Self::ValidatorReactivated { .. } => "validator_reactivated",
+            Self::CommissionChanged { .. } => "commission_changed",
+        }
+    }
+
+    /// The validator concerned by this event
+    pub fn validator(&self) -> &Address {
+        match self {
+            Self::SlashEnqueued { validator, .. }
+            | Self::SlashProcessed { validator, .. }
+            | Self::ValidatorJailed { validator, .. }
+            | Self::ValidatorUnjailed { validator, .. }
+            | Self::ValidatorDeactivated { validator, .. }
+            | Self::ValidatorReactivated { validator, .. }
+            | Self::CommissionChanged { validator, .. } => validator,
+        }
+    }
+}
+
+impl From<PosEvent> for Event {
+    fn from(event: PosEvent) -> Self {
+        let mut attributes = HashMap::from([
+            ("kind".to_string(), event.kind().to_string()),
+            ("validator".to_string(), event.validator().to_string()),
+        ]);
+        let mut attr = |key: &str, value: String| {
+            attributes.insert(key.to_string(), value);
+        };
+        let level = match event {
+            PosEvent::SlashEnqueued {
+                slash_type,
+                infraction_epoch,
+                processing_epoch,
+                evidence_height,
+                ..
+            } => {
+                let slash_type = match slash_type {
+                    SlashType::DuplicateVote => "duplicate_vote",
+                    SlashType::LightClientAttack => "light_client_attack",
+                };
+                attr("slash_type", slash_type.to_string());
+                attr("infraction_epoch", infraction_epoch.to_string());
+                attr("processing_epoch", processing_epoch.to_string());
+                attr("evidence_height", evidence_height.to_string());
+                EventLevel::Block
+            }
+            PosEvent::SlashProcessed {
+                infraction_epoch,
+                rate,
+                amount,
+                ..
+            } => {
+                attr("infraction_epoch", infraction_epoch.to_string());
+                attr("rate", rate.to_string());
+                attr("amount", amount.to_string_native());
+                EventLevel::Block
+            }
+            PosEvent::ValidatorJailed { reason, epoch, .. } => {
+                let reason = match reason {
+                    JailReason::Liveness => "liveness",
+                    JailReason::Slash => "slash",
+                };
+                attr("reason", reason.to_string());
+                attr("epoch", epoch.to_string());
+                EventLevel::Block
+            }
+            PosEvent::ValidatorUnjailed { epoch, .. }
+            | PosEvent::ValidatorDeactivated { epoch, .. }
+            | PosEvent::ValidatorReactivated { epoch, .. } => {
+                attr("epoch", epoch.to_string());
+                EventLevel::Tx
+            }
+            PosEvent::CommissionChanged { rate, epoch, .. } => {
+                attr("rate", rate.to_string());
+                attr("epoch", epoch.to_string());
+                EventLevel::Tx
+            }
+        };
+        Self {
+            event_type: EventType::ProofOfStake,
+            level,
+            attributes,
+        }
+    }
+}
+
+/// Find the events of the validator state and commission rate changes made
+/// by a transaction, from the keys it changed. Transactions only update
+/// these values from the pipeline epoch, so the new values are compared
+/// with those of the current epoch.
+pub fn tx_events<S>(
+    storage: &S,
+    changed_keys: &BTreeSet<Key>,
+    current_epoch: Epoch,
+) -> namada_storage::Result<Vec<PosEvent>>
+where
+    S: StorageRead,
+{
+    let mut state_changes = BTreeSet::new();
+    let mut commission_changes = BTreeSet::new();
+    for key in changed_keys {
+        if let Some((validator, _epoch)) = is_validator_state_key(key) {
+            state_changes.insert(validator.clone());
+        } else if let Some(validator) = is_validator_commission_rate_key(key) {
+            commission_changes.insert(validator.clone());
+        }
+    }
+    if state_changes.is_empty() && commission_changes.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let params = read_pos_params(storage)?;
+    let epoch = current_epoch + params.pipeline_len;
+    let mut events = BTreeMap::new();
+    for validator in state_changes {
+        let handle = validator_state_handle(&validator);
+        let (Some(current), Some(new)) = (
+            handle.get(storage, current_epoch, &params)?,
+            handle.get(storage, epoch, &params)?,
+        ) else {
+            continue;
+        };
+        let event = match (current, new) {
+            (ValidatorState::Jailed, new) if new != ValidatorState::Jailed => {
+                PosEvent::ValidatorUnjailed { validator, epoch }
+            }
+            (ValidatorState::Inactive, new)
+                if new != ValidatorState::Inactive =>
+            {
+                PosEvent::ValidatorReactivated { validator, epoch }
+            }
+            (current, ValidatorState::Inactive)
+                if current != ValidatorState::Inactive =>
+            {
+                PosEvent::ValidatorDeactivated { validator, epoch }
+            }
+            _ => continue,
+        };
+        events.insert((event.kind(), event.validator().clone()), event);
+    }
+    for validator in commission_changes {
+        let Some(rate) = validator_commission_rate_handle(&validator)
+            .get(storage, epoch, &params)?
+        else {
+            continue;
+        };
+        let event = PosEvent::CommissionChanged {
+            validator,
+            rate,
+            epoch,
+        };
+        events.insert((event.kind(), event.validator().clone()), event);
+    }
+    Ok(events.into_values().collect())
+}
@@ -7,6 +7,7 @@
 #![deny(rustdoc::private_intra_doc_links)]
 
 pub mod epoched;
+pub mod event;
 pub mod parameters;
 pub mod pos_queries;
 pub mod queries;
@@ -27,6 +28,7 @@ use std::cmp::{self};
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 pub use error::*;
+use event::{JailReason, PosEvent};
 use namada_core::address::{Address, InternalAddress};
 use namada_core::dec::Dec;
 use namada_core::event::EmitEvents;
@@ -2429,6 +2431,7 @@ where
 /// Jail validators who failed to match the liveness threshold
 pub fn jail_for_liveness<S>(
     storage: &mut S,
+    events: &mut impl EmitEvents,
     params: &PosParams,
     current_epoch: Epoch,
     jail_epoch: Epoch,
@@ -2477,6 +2480,14 @@ where
             jail_epoch,
         );
         jail_validator(storage, params, validator, current_epoch, jail_epoch)?;
+        events.emit(
+            PosEvent::ValidatorJailed {
+                validator: validator.clone(),
+                reason: JailReason::Liveness,
+                epoch: jail_epoch,
+            }
+            .into(),
+        );
     }
 
     Ok(())
@@ -2769,7 +2780,7 @@ where
 /// Apply PoS updates for a block
 pub fn finalize_block<S>(
     storage: &mut S,
-    _events: &mut impl EmitEvents,
+    events: &mut impl EmitEvents,
     is_new_epoch: bool,
     validator_set_update_epoch: Epoch,
     votes: Vec<VoteInfo>,
@@ -2814,6 +2825,7 @@ where
     // `copy_validator_sets_and_positions` and before `self.update_epoch`.
     slashing::record_slashes_from_evidence(
         storage,
+        events,
         byzantine_validators,
         &pos_params,
         current_epoch,
@@ -2828,7 +2840,9 @@ where
 
         // Process and apply slashes that have already been recorded for the
         // current epoch
-        if let Err(err) = slashing::process_slashes(storage, current_epoch) {
+        if let Err(err) =
+            slashing::process_slashes(storage, events, current_epoch)
+        {
             tracing::error!(
                 "Error while processing slashes queued for epoch {}: {}",
                 current_epoch,
@@ -2858,6 +2872,7 @@ where
     // Jail validators for inactivity
     jail_for_liveness(
         storage,
+        events,
         &pos_params,
         current_epoch,
         validator_set_update_epoch,
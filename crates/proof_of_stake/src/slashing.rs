@@ -6,6 +6,7 @@ use std::collections::{BTreeMap, BTreeSet, HashMap};
 use borsh::BorshDeserialize;
 use namada_core::address::Address;
 use namada_core::dec::Dec;
+use namada_core::event::EmitEvents;
 use namada_core::key::tm_raw_hash_to_string;
 use namada_core::storage::{BlockHeight, Epoch};
 use namada_core::tendermint::abci::types::{Misbehavior, MisbehaviorKind};
@@ -16,6 +17,7 @@ use namada_storage::collections::lazy_map::{
 use namada_storage::collections::LazyMap;
 use namada_storage::{StorageRead, StorageWrite};
 
+use crate::event::{JailReason, PosEvent};
 use crate::storage::{
     enqueued_slashes_handle, read_pos_params, read_validator_last_slash_epoch,
     read_validator_stake, total_bonded_handle, total_unbonded_handle,
@@ -39,6 +41,7 @@ use crate::{
 /// Apply PoS slashes from the evidence
 pub(crate) fn record_slashes_from_evidence<S>(
     storage: &mut S,
+    events: &mut impl EmitEvents,
     byzantine_validators: Vec<Misbehavior>,
     pos_params: &PosParams,
     current_epoch: Epoch,
@@ -115,6 +118,7 @@ where
             );
             if let Err(err) = slash(
                 storage,
+                events,
                 pos_params,
                 current_epoch,
                 evidence_epoch,
@@ -136,6 +140,7 @@ where
 #[allow(clippy::too_many_arguments)]
 pub fn slash<S>(
     storage: &mut S,
+    events: &mut impl EmitEvents,
     params: &PosParams,
     current_epoch: Epoch,
     evidence_epoch: Epoch,
@@ -183,6 +188,25 @@ where
         validator_set_update_epoch,
     )?;
 
+    events.emit(
+        PosEvent::SlashEnqueued {
+            validator: validator.clone(),
+            slash_type,
+            infraction_epoch: evidence_epoch,
+            processing_epoch,
+            evidence_height: evidence_block_height,
+        }
+        .into(),
+    );
+    events.emit(
+        PosEvent::ValidatorJailed {
+            validator: validator.clone(),
+            reason: JailReason::Slash,
+            epoch: validator_set_update_epoch,
+        }
+        .into(),
+    );
+
     // No other actions are performed here until the epoch in which the slash is
     // processed.
 
@@ -193,9 +217,11 @@ where
 /// called upon a new epoch. The final slash rate considering according to the
 /// cubic slashing rate is computed. Then, each slash is recorded in storage
 /// along with its computed rate, and stake is deducted from the affected
-/// validators.
+/// validators. An event with the slashed amount is emitted for every
+/// misbehaving validator.
 pub fn process_slashes<S>(
     storage: &mut S,
+    events: &mut impl EmitEvents,
     current_epoch: Epoch,
 ) -> namada_storage::Result<()>
 where
@@ -228,7 +254,8 @@ where
     // Collect the enqueued slashes and update their rates
     let mut eager_validator_slashes: BTreeMap<Address, Vec<Slash>> =
         BTreeMap::new();
-    let mut eager_validator_slash_rates: HashMap<Address, Dec> = HashMap::new();
+    let mut eager_validator_slash_rates: BTreeMap<Address, Dec> =
+        BTreeMap::new();
 
     // `slashPerValidator` and `slashesMap` while also updating in storage
     for enqueued_slash in enqueued_slashes.iter(storage)? {
@@ -269,12 +296,12 @@ where
 
     // `resultSlashing`
     let mut map_validator_slash: EagerRedelegatedBondsMap = BTreeMap::new();
-    for (validator, slash_rate) in eager_validator_slash_rates {
+    for (validator, slash_rate) in &eager_validator_slash_rates {
         process_validator_slash(
             storage,
             &params,
-            &validator,
-            slash_rate,
+            validator,
+            *slash_rate,
             current_epoch,
             &mut map_validator_slash,
         )?;
@@ -292,6 +319,7 @@ where
     }
 
     // Update the validator stakes
+    let mut slashed_amounts: BTreeMap<Address, token::Amount> = BTreeMap::new();
     for (validator, slash_amounts) in map_validator_slash {
         let mut slash_acc = token::Amount::zero();
 
@@ -337,6 +365,22 @@ where
         // TODO: should we clear some storage here as is done in Quint??
         // Possibly make the `unbonded` LazyMaps epoched so that it is done
         // automatically?
+
+        slashed_amounts.insert(validator, slash_acc);
+    }
+
+    for (validator, rate) in eager_validator_slash_rates {
+        let amount =
+            slashed_amounts.get(&validator).copied().unwrap_or_default();
+        events.emit(
+            PosEvent::SlashProcessed {
+                validator,
+                infraction_epoch,
+                rate,
+                amount,
+            }
+            .into(),
+        );
     }
 
     Ok(())
@@ -1,10 +1,11 @@
 //! PoS system tests
 
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 use assert_matches::assert_matches;
 use namada_core::address::Address;
 use namada_core::dec::Dec;
+use namada_core::event::{Event, EventType};
 use namada_core::key::testing::{common_sk_from_simple_seed, gen_keypair};
 use namada_core::key::RefTo;
 use namada_core::storage::{BlockHeight, Epoch};
@@ -18,6 +19,7 @@ use proptest::test_runner::Config;
 // `tracing` logs from tests
 use test_log::test;
 
+use crate::event::{JailReason, PosEvent};
 use crate::parameters::testing::arb_pos_params;
 use crate::parameters::OwnedPosParams;
 use crate::queries::bonds_and_unbonds;
@@ -847,14 +849,16 @@ fn test_unjail_validator_aux(
     s.commit_block().unwrap();
 
     current_epoch = advance_epoch(&mut s, &params);
-    process_slashes(&mut s, current_epoch).unwrap();
+    process_slashes(&mut s, &mut vec![], current_epoch).unwrap();
 
     // Discover first slash
     let slash_0_evidence_epoch = current_epoch;
+    let mut events = vec![];
     let evidence_block_height = BlockHeight(0); // doesn't matter for slashing logic
     let slash_0_type = SlashType::DuplicateVote;
     slash(
         &mut s,
+        &mut events,
         &params,
         current_epoch,
         slash_0_evidence_epoch,
@@ -864,6 +868,24 @@ fn test_unjail_validator_aux(
         current_epoch.next(),
     )
     .unwrap();
+    assert_eq!(
+        events,
+        vec![
+            Event::from(PosEvent::SlashEnqueued {
+                validator: val_addr.clone(),
+                slash_type: slash_0_type,
+                infraction_epoch: slash_0_evidence_epoch,
+                processing_epoch: slash_0_evidence_epoch
+                    + params.slash_processing_epoch_offset(),
+                evidence_height: evidence_block_height.0,
+            }),
+            Event::from(PosEvent::ValidatorJailed {
+                validator: val_addr.clone(),
+                reason: JailReason::Slash,
+                epoch: current_epoch.next(),
+            }),
+        ]
+    );
 
     let val_stake =
         crate::read_validator_stake(&s, &params, val_addr, current_epoch)
@@ -905,14 +927,36 @@ fn test_unjail_validator_aux(
     // Advance past an epoch in which we can unbond
     let unfreeze_epoch =
         slash_0_evidence_epoch + params.slash_processing_epoch_offset();
+    let mut events = vec![];
     while current_epoch < unfreeze_epoch + 4u64 {
         current_epoch = advance_epoch(&mut s, &params);
-        process_slashes(&mut s, current_epoch).unwrap();
+        process_slashes(&mut s, &mut events, current_epoch).unwrap();
     }
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, EventType::ProofOfStake);
+    assert_eq!(events[0]["kind"], "slash_processed");
+    assert_eq!(events[0]["validator"], val_addr.to_string());
+    assert_eq!(
+        events[0]["infraction_epoch"],
+        slash_0_evidence_epoch.to_string()
+    );
 
     // Unjail the validator
     unjail_validator(&mut s, val_addr, current_epoch).unwrap();
 
+    // The unjailing is found from the changed validator state keys
+    let pipeline_epoch = current_epoch + params.pipeline_len;
+    let changed_keys = BTreeSet::from([validator_state_handle(val_addr)
+        .get_data_handler()
+        .get_data_key(&pipeline_epoch)]);
+    assert_eq!(
+        crate::event::tx_events(&s, &changed_keys, current_epoch).unwrap(),
+        vec![PosEvent::ValidatorUnjailed {
+            validator: val_addr.clone(),
+            epoch: pipeline_epoch,
+        }]
+    );
+
     // Check the validator state
     for epoch in
         Epoch::iter_bounds_inclusive(current_epoch, current_epoch.next())
@@ -953,7 +997,7 @@ fn test_unjail_validator_aux(
 
     // Advance another epoch
     current_epoch = advance_epoch(&mut s, &params);
-    process_slashes(&mut s, current_epoch).unwrap();
+    process_slashes(&mut s, &mut vec![], current_epoch).unwrap();
 
     let second_att = unjail_validator(&mut s, val_addr, current_epoch);
     assert!(second_att.is_err());
@@ -1033,7 +1077,7 @@ fn test_unslashed_bond_amount_aux(validators: Vec<GenesisValidator>) {
 
     // Advance an epoch
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
 
     // Bond to validator 1
     bond_tokens(
@@ -1081,7 +1125,7 @@ fn test_unslashed_bond_amount_aux(validators: Vec<GenesisValidator>) {
 
     // Advance an epoch
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
 
     // Bond to validator 1
     bond_tokens(
@@ -1620,7 +1664,7 @@ fn test_is_delegator_aux(mut validators: Vec<GenesisValidator>) {
 
     // Advance to epoch 1
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
 
     // Delegate in epoch 1 to validator1
     let del1_epoch = current_epoch;
@@ -1636,7 +1680,7 @@ fn test_is_delegator_aux(mut validators: Vec<GenesisValidator>) {
 
     // Advance to epoch 2
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
 
     // Delegate in epoch 2 to validator2
     let del2_epoch = current_epoch;
@@ -1727,7 +1771,8 @@ fn test_jail_for_liveness_aux(validators: Vec<GenesisValidator>) {
                 .unwrap();
         }
 
-        jail_for_liveness(s, &params, current_epoch, jail_epoch).unwrap();
+        jail_for_liveness(s, &mut vec![], &params, current_epoch, jail_epoch)
+            .unwrap();
 
         for GenesisValidator { address, .. } in &validators_who_missed_votes {
             let state_jail_epoch = validator_state_handle(address)
@@ -110,7 +110,7 @@ fn test_simple_redelegation_aux(
 
     for _ in 0..5 {
         current_epoch = advance_epoch(&mut storage, &params);
-        process_slashes(&mut storage, current_epoch).unwrap();
+        process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
     }
 
     let init_epoch = current_epoch;
@@ -128,11 +128,11 @@ fn test_simple_redelegation_aux(
 
     // Advance three epochs
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
 
     // Redelegate in epoch 3
     redelegate_tokens(
@@ -176,11 +176,11 @@ fn test_simple_redelegation_aux(
 
     // Advance three epochs
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
 
     // Unbond in epoch 5 from dest_validator
     let _ = unbond_tokens(
@@ -232,7 +232,7 @@ fn test_simple_redelegation_aux(
     // Advance to withdrawal epoch
     loop {
         current_epoch = advance_epoch(&mut storage, &params);
-        process_slashes(&mut storage, current_epoch).unwrap();
+        process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
         if current_epoch == unbond_end {
             break;
         }
@@ -317,7 +317,7 @@ fn test_slashes_with_unbonding_aux(
     s.commit_block().unwrap();
 
     current_epoch = advance_epoch(&mut s, &params);
-    process_slashes(&mut s, current_epoch).unwrap();
+    process_slashes(&mut s, &mut vec![], current_epoch).unwrap();
 
     // Discover first slash
     let slash_0_evidence_epoch = current_epoch;
@@ -327,6 +327,7 @@ fn test_slashes_with_unbonding_aux(
     let slash_0_type = SlashType::DuplicateVote;
     slash(
         &mut s,
+        &mut vec![],
         &params,
         current_epoch,
         slash_0_evidence_epoch,
@@ -342,7 +343,7 @@ fn test_slashes_with_unbonding_aux(
         slash_0_evidence_epoch + params.slash_processing_epoch_offset();
     while current_epoch < unfreeze_epoch {
         current_epoch = advance_epoch(&mut s, &params);
-        process_slashes(&mut s, current_epoch).unwrap();
+        process_slashes(&mut s, &mut vec![], current_epoch).unwrap();
     }
 
     // Advance more epochs randomly from the generated delay
@@ -365,6 +366,7 @@ fn test_slashes_with_unbonding_aux(
     let slash_1_type = SlashType::DuplicateVote;
     slash(
         &mut s,
+        &mut vec![],
         &params,
         current_epoch,
         slash_1_evidence_epoch,
@@ -379,7 +381,7 @@ fn test_slashes_with_unbonding_aux(
     let withdraw_epoch = unbond_epoch + params.withdrawable_epoch_offset();
     while current_epoch < withdraw_epoch {
         current_epoch = advance_epoch(&mut s, &params);
-        process_slashes(&mut s, current_epoch).unwrap();
+        process_slashes(&mut s, &mut vec![], current_epoch).unwrap();
     }
     let token = staking_token_address(&s);
     let val_balance_pre = read_balance(&s, &token, val_addr).unwrap();
@@ -489,7 +491,7 @@ fn test_redelegation_with_slashing_aux(
 
     for _ in 0..5 {
         current_epoch = advance_epoch(&mut storage, &params);
-        process_slashes(&mut storage, current_epoch).unwrap();
+        process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
     }
 
     let init_epoch = current_epoch;
@@ -507,11 +509,11 @@ fn test_redelegation_with_slashing_aux(
 
     // Advance three epochs
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
 
     // Redelegate in epoch 8
     redelegate_tokens(
@@ -551,11 +553,11 @@ fn test_redelegation_with_slashing_aux(
 
     // Advance three epochs
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
 
     // Unbond in epoch 11 from dest_validator
     let _ = unbond_tokens(
@@ -570,11 +572,12 @@ fn test_redelegation_with_slashing_aux(
 
     // Advance one epoch
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
 
     // Discover evidence
     slash(
         &mut storage,
+        &mut vec![],
         &params,
         current_epoch,
         init_epoch + 2 * params.pipeline_len,
@@ -624,7 +627,7 @@ fn test_redelegation_with_slashing_aux(
     // Advance to withdrawal epoch
     loop {
         current_epoch = advance_epoch(&mut storage, &params);
-        process_slashes(&mut storage, current_epoch).unwrap();
+        process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
         if current_epoch == unbond_end {
             break;
         }
@@ -722,7 +725,7 @@ fn test_chain_redelegations_aux(mut validators: Vec<GenesisValidator>) {
 
     // Advance one epoch
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
 
     // Redelegate in epoch 1 to dest_validator
     let redel_amount_1: token::Amount = 58.into();
@@ -835,9 +838,9 @@ fn test_chain_redelegations_aux(mut validators: Vec<GenesisValidator>) {
 
     // Attempt to redelegate in epoch 3 to dest_validator
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
 
     let redel_amount_2: token::Amount = 23.into();
     let redel_att = redelegate_tokens(
@@ -856,7 +859,7 @@ fn test_chain_redelegations_aux(mut validators: Vec<GenesisValidator>) {
         redel_end.prev() + params.slash_processing_epoch_offset();
     loop {
         current_epoch = advance_epoch(&mut storage, &params);
-        process_slashes(&mut storage, current_epoch).unwrap();
+        process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
         if current_epoch == epoch_can_redel.prev() {
             break;
         }
@@ -875,7 +878,7 @@ fn test_chain_redelegations_aux(mut validators: Vec<GenesisValidator>) {
 
     // Advance one more epoch
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
 
     // Redelegate from dest_validator to dest_validator_2 now
     redelegate_tokens(
@@ -1116,6 +1119,7 @@ fn test_overslashing_aux(mut validators: Vec<GenesisValidator>) {
     // Find a misbehavior committed in epoch 0
     slash(
         &mut storage,
+        &mut vec![],
         &params,
         current_epoch,
         self_bond_epoch,
@@ -1129,6 +1133,7 @@ fn test_overslashing_aux(mut validators: Vec<GenesisValidator>) {
     // Find a misbehavior committed in current epoch
     slash(
         &mut storage,
+        &mut vec![],
         &params,
         current_epoch,
         delegation_epoch,
@@ -1147,7 +1152,7 @@ fn test_overslashing_aux(mut validators: Vec<GenesisValidator>) {
     // Advance to processing epoch 1
     loop {
         current_epoch = advance_epoch(&mut storage, &params);
-        process_slashes(&mut storage, current_epoch).unwrap();
+        process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
         if current_epoch == processing_epoch_1 {
             break;
         }
@@ -1183,7 +1188,7 @@ fn test_overslashing_aux(mut validators: Vec<GenesisValidator>) {
     // Advance to processing epoch 2
     loop {
         current_epoch = advance_epoch(&mut storage, &params);
-        process_slashes(&mut storage, current_epoch).unwrap();
+        process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
         if current_epoch == processing_epoch_2 {
             break;
         }
@@ -1326,7 +1331,7 @@ fn test_slashed_bond_amount_aux(validators: Vec<GenesisValidator>) {
 
     // Advance an epoch to 1
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
 
     // Bond to validator 1
     bond_tokens(
@@ -1374,7 +1379,7 @@ fn test_slashed_bond_amount_aux(validators: Vec<GenesisValidator>) {
 
     // Advance an epoch to ep 2
     current_epoch = advance_epoch(&mut storage, &params);
-    process_slashes(&mut storage, current_epoch).unwrap();
+    process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
 
     // Bond to validator 1
     bond_tokens(
@@ -1412,12 +1417,13 @@ fn test_slashed_bond_amount_aux(validators: Vec<GenesisValidator>) {
     // Advance two epochs to ep 4
     for _ in 0..2 {
         current_epoch = advance_epoch(&mut storage, &params);
-        process_slashes(&mut storage, current_epoch).unwrap();
+        process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
     }
 
     // Find some slashes committed in various epochs
     slash(
         &mut storage,
+        &mut vec![],
         &params,
         current_epoch,
         Epoch(1),
@@ -1429,6 +1435,7 @@ fn test_slashed_bond_amount_aux(validators: Vec<GenesisValidator>) {
     .unwrap();
     slash(
         &mut storage,
+        &mut vec![],
         &params,
         current_epoch,
         Epoch(2),
@@ -1440,6 +1447,7 @@ fn test_slashed_bond_amount_aux(validators: Vec<GenesisValidator>) {
     .unwrap();
     slash(
         &mut storage,
+        &mut vec![],
         &params,
         current_epoch,
         Epoch(2),
@@ -1451,6 +1459,7 @@ fn test_slashed_bond_amount_aux(validators: Vec<GenesisValidator>) {
     .unwrap();
     slash(
         &mut storage,
+        &mut vec![],
         &params,
         current_epoch,
         Epoch(3),
@@ -1464,7 +1473,7 @@ fn test_slashed_bond_amount_aux(validators: Vec<GenesisValidator>) {
     // Advance such that these slashes are all processed
     for _ in 0..params.slash_processing_epoch_offset() {
         current_epoch = advance_epoch(&mut storage, &params);
-        process_slashes(&mut storage, current_epoch).unwrap();
+        process_slashes(&mut storage, &mut vec![], current_epoch).unwrap();
     }
 
     let pipeline_epoch = current_epoch + params.pipeline_len;
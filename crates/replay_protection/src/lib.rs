@@ -2,9 +2,35 @@
 
 use namada_core::hash::Hash;
 use namada_core::storage::Key;
+use namada_core::time::{DateTimeUtc, Duration, DurationSecs};
 
 const ERROR_MSG: &str = "Cannot obtain a valid db key";
 
+/// The lifetime of a transaction that doesn't specify an expiration, counted
+/// from its timestamp
+pub const DEFAULT_TX_LIFETIME: DurationSecs = DurationSecs(24 * 60 * 60);
+
+/// The time kept between the expiration of a transaction and the pruning of
+/// its hashes from storage
+pub const PRUNING_MARGIN: DurationSecs = DurationSecs(60 * 60);
+
+/// Get the time after which a transaction with the given timestamp and
+/// optional expiration gets rejected. A transaction without an expiration
+/// expires [`DEFAULT_TX_LIFETIME`] after its timestamp.
+pub fn tx_expiration(
+    timestamp: DateTimeUtc,
+    expiration: Option<DateTimeUtc>,
+) -> DateTimeUtc {
+    expiration.unwrap_or_else(|| {
+        // The timestamp is provided by the client, so it could overflow
+        timestamp
+            .0
+            .checked_add_signed(Duration::seconds(DEFAULT_TX_LIFETIME.0 as i64))
+            .map(DateTimeUtc)
+            .unwrap_or(timestamp)
+    })
+}
+
 /// Get the transaction hash prefix under the `all` subkey
 pub fn all_prefix() -> Key {
     Key::parse("all").expect(ERROR_MSG)
@@ -24,3 +50,61 @@ pub fn last_prefix() -> Key {
 pub fn last_key(hash: &Hash) -> Key {
     last_prefix().push(&hash.to_string()).expect(ERROR_MSG)
 }
+
+/// Get the prefix of the transaction hashes indexed by their expiration
+/// under the `expiry` subkey
+pub fn expiry_prefix() -> Key {
+    Key::parse("expiry").expect(ERROR_MSG)
+}
+
+/// Get the key indexing the given transaction hash by its expiration under
+/// the `expiry` subkey. The expiration is written in zero-padded unix
+/// seconds, so that the keys are ordered by expiration.
+pub fn expiry_key(expiration: &DateTimeUtc, hash: &Hash) -> Key {
+    let seconds = expiration.0.timestamp().max(0);
+    expiry_prefix()
+        .push(&format!("{seconds:020}"))
+        .and_then(|key| key.push(&hash.to_string()))
+        .expect(ERROR_MSG)
+}
+
+/// Parse a key under the `expiry` subkey into the expiration, in unix
+/// seconds, and the hash of the transaction
+pub fn parse_expiry_key(key: &str) -> Option<(i64, Hash)> {
+    let mut segments = key
+        .strip_prefix(&format!("{}/", expiry_prefix()))?
+        .split('/');
+    let seconds = segments.next()?.parse().ok()?;
+    let hash = segments.next()?.parse().ok()?;
+    Some((seconds, hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiry_keys() {
+        let hash = Hash::sha256("tx".as_bytes());
+        let early = DateTimeUtc::from_unix_timestamp(9).unwrap();
+        let late = DateTimeUtc::from_unix_timestamp(10).unwrap();
+
+        let key = expiry_key(&early, &hash).to_string();
+        assert_eq!(parse_expiry_key(&key), Some((9, hash)));
+        // The keys are ordered by expiration
+        assert!(key < expiry_key(&late, &hash).to_string());
+        assert_eq!(parse_expiry_key(&all_key(&hash).to_string()), None);
+    }
+
+    #[test]
+    fn test_tx_expiration() {
+        let timestamp = DateTimeUtc::from_unix_timestamp(0).unwrap();
+        let expiration = DateTimeUtc::from_unix_timestamp(100).unwrap();
+
+        assert_eq!(tx_expiration(timestamp, Some(expiration)), expiration);
+        assert_eq!(
+            tx_expiration(timestamp, None),
+            timestamp + DEFAULT_TX_LIFETIME
+        );
+    }
+}
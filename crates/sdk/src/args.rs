@@ -8,6 +8,7 @@ use namada_core::address::Address;
 use namada_core::chain::ChainId;
 use namada_core::dec::Dec;
 use namada_core::ethereum_events::EthAddress;
+use namada_core::hash::Hash;
 use namada_core::keccak::KeccakHash;
 use namada_core::key::{common, SchemeType};
 use namada_core::masp::PaymentAddress;
@@ -17,6 +18,7 @@ use namada_core::{storage, token};
 use namada_governance::cli::onchain::{
     DefaultProposal, PgfFundingProposal, PgfStewardProposal,
 };
+use namada_tx::data::schedule::ScheduleTarget;
 use namada_tx::data::GasLimit;
 use namada_tx::Memo;
 use serde::{Deserialize, Serialize};
@@ -24,6 +26,7 @@ use zeroize::Zeroizing;
 
 use crate::eth_bridge::bridge_pool;
 use crate::ibc::core::host::types::identifiers::{ChannelId, PortId};
+use crate::masp::ConsolidationStep;
 use crate::signing::SigningTxData;
 use crate::{rpc, tx, Namada};
 
@@ -200,6 +203,52 @@ impl TxCustom {
     }
 }
 
+/// Batch transaction arguments
+#[derive(Clone, Debug)]
+pub struct TxBatch<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// The serialized transactions to execute in the batch, in order
+    pub serialized_txs: Vec<C::Data>,
+    /// The address that correspond to the signatures/signing-keys
+    pub owner: C::Address,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for TxBatch<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        TxBatch {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> TxBatch<C> {
+    /// Append a serialized transaction to the batch
+    pub fn serialized_tx(mut self, serialized_tx: C::Data) -> Self {
+        self.serialized_txs.push(serialized_tx);
+        self
+    }
+
+    /// The address that correspond to the signatures/signing-keys
+    pub fn owner(self, owner: C::Address) -> Self {
+        Self { owner, ..self }
+    }
+}
+
+impl TxBatch {
+    /// Build a transaction from this builder
+    pub async fn build(
+        &self,
+        context: &impl Namada,
+    ) -> crate::error::Result<(namada_tx::Tx, SigningTxData)> {
+        tx::build_batch(context, self).await
+    }
+}
+
 /// An amount read in by the cli
 #[derive(Copy, Clone, Debug)]
 pub enum InputAmount {
@@ -296,6 +345,97 @@ impl TxTransfer {
     }
 }
 
+/// Shielded note consolidation transaction arguments
+#[derive(Clone, Debug)]
+pub struct TxConsolidateNotes<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// The spending key of the notes to consolidate
+    pub spending_key: C::SpendingKey,
+    /// The token of the notes to consolidate
+    pub token: C::Address,
+    /// Only consolidate the notes holding less than this amount, all the
+    /// notes of the token if none
+    pub threshold: Option<InputAmount>,
+    /// The maximum number of spend and output descriptions of each
+    /// transaction
+    pub max_descriptions: usize,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for TxConsolidateNotes<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        TxConsolidateNotes {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> TxConsolidateNotes<C> {
+    /// The spending key of the notes to consolidate
+    pub fn spending_key(self, spending_key: C::SpendingKey) -> Self {
+        Self {
+            spending_key,
+            ..self
+        }
+    }
+
+    /// The token of the notes to consolidate
+    pub fn token(self, token: C::Address) -> Self {
+        Self { token, ..self }
+    }
+
+    /// Only consolidate the notes holding less than this amount
+    pub fn threshold(self, threshold: InputAmount) -> Self {
+        Self {
+            threshold: Some(threshold),
+            ..self
+        }
+    }
+
+    /// The maximum number of spend and output descriptions of each
+    /// transaction
+    pub fn max_descriptions(self, max_descriptions: usize) -> Self {
+        Self {
+            max_descriptions,
+            ..self
+        }
+    }
+
+    /// Path to the TX WASM code file
+    pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
+        Self {
+            tx_code_path,
+            ..self
+        }
+    }
+}
+
+impl TxConsolidateNotes {
+    /// Plan the transactions merging the notes
+    pub async fn plan(
+        &self,
+        context: &impl Namada,
+    ) -> crate::error::Result<Vec<ConsolidationStep>> {
+        tx::plan_note_consolidation(context, self).await
+    }
+
+    /// Build the transaction of a step of the plan
+    pub async fn build(
+        &self,
+        context: &impl Namada,
+        step: &ConsolidationStep,
+    ) -> crate::error::Result<(namada_tx::Tx, SigningTxData, Option<Epoch>)>
+    {
+        tx::build_consolidate_notes(context, self, step).await
+    }
+}
+
 /// IBC transfer transaction arguments
 #[derive(Clone, Debug)]
 pub struct TxIbcTransfer<C: NamadaTypes = SdkTypes> {
@@ -656,6 +796,8 @@ pub struct TxInitAccount<C: NamadaTypes = SdkTypes> {
     pub tx_code_path: PathBuf,
     /// Public key for the new account
     pub public_keys: Vec<C::PublicKey>,
+    /// The weights of the public keys, all 1 if empty
+    pub public_key_weights: Vec<u8>,
     /// The account multisignature threshold
     pub threshold: Option<u8>,
 }
@@ -681,6 +823,14 @@ impl<C: NamadaTypes> TxInitAccount<C> {
         }
     }
 
+    /// The weights of the public keys, in the same order
+    pub fn public_key_weights(self, public_key_weights: Vec<u8>) -> Self {
+        Self {
+            public_key_weights,
+            ..self
+        }
+    }
+
     /// A threshold to associate with the new account
     pub fn threshold(self, threshold: u8) -> Self {
         Self {
@@ -809,6 +959,8 @@ pub struct TxUpdateAccount<C: NamadaTypes = SdkTypes> {
     pub addr: C::Address,
     /// Public keys
     pub public_keys: Vec<C::PublicKey>,
+    /// The weights of the public keys, all 1 if empty
+    pub public_key_weights: Vec<u8>,
     /// The account threshold
     pub threshold: Option<u8>,
 }
@@ -855,6 +1007,14 @@ impl<C: NamadaTypes> TxUpdateAccount<C> {
         }
     }
 
+    /// The weights of the public keys, in the same order
+    pub fn public_key_weights(self, public_key_weights: Vec<u8>) -> Self {
+        Self {
+            public_key_weights,
+            ..self
+        }
+    }
+
     /// The account threshold
     pub fn threshold(self, threshold: u8) -> Self {
         Self {
@@ -874,6 +1034,439 @@ impl TxUpdateAccount {
     }
 }
 
+/// Fee allowance grant arguments
+#[derive(Clone, Debug)]
+pub struct TxGrantFeeAllowance<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// Address of the account paying for the fees
+    pub granter: C::Address,
+    /// Address of the account whose fees are paid
+    pub grantee: C::Address,
+    /// The token in which the fees can be paid
+    pub token: C::Address,
+    /// The maximum amount of the token that can be spent on fees
+    pub spend_limit: InputAmount,
+    /// The last epoch in which the allowance can be used
+    pub expiration: Option<Epoch>,
+    /// Paths to the WASM codes of the transactions whose fees can be paid.
+    /// If empty, the fees of any transaction can be paid.
+    pub allowed_tx_code_paths: Vec<PathBuf>,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for TxGrantFeeAllowance<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        TxGrantFeeAllowance {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> TxGrantFeeAllowance<C> {
+    /// Address of the account paying for the fees
+    pub fn granter(self, granter: C::Address) -> Self {
+        Self { granter, ..self }
+    }
+
+    /// Address of the account whose fees are paid
+    pub fn grantee(self, grantee: C::Address) -> Self {
+        Self { grantee, ..self }
+    }
+
+    /// The token in which the fees can be paid
+    pub fn token(self, token: C::Address) -> Self {
+        Self { token, ..self }
+    }
+
+    /// The maximum amount of the token that can be spent on fees
+    pub fn spend_limit(self, spend_limit: InputAmount) -> Self {
+        Self {
+            spend_limit,
+            ..self
+        }
+    }
+
+    /// The last epoch in which the allowance can be used
+    pub fn expiration(self, expiration: Epoch) -> Self {
+        Self {
+            expiration: Some(expiration),
+            ..self
+        }
+    }
+
+    /// Paths to the WASM codes of the transactions whose fees can be paid
+    pub fn allowed_tx_code_paths(
+        self,
+        allowed_tx_code_paths: Vec<PathBuf>,
+    ) -> Self {
+        Self {
+            allowed_tx_code_paths,
+            ..self
+        }
+    }
+
+    /// Path to the TX WASM code file
+    pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
+        Self {
+            tx_code_path,
+            ..self
+        }
+    }
+}
+
+impl TxGrantFeeAllowance {
+    /// Build a transaction from this builder
+    pub async fn build(
+        &self,
+        context: &impl Namada,
+    ) -> crate::error::Result<(namada_tx::Tx, SigningTxData)> {
+        tx::build_grant_fee_allowance(context, self).await
+    }
+}
+
+/// Fee allowance revocation arguments
+#[derive(Clone, Debug)]
+pub struct TxRevokeFeeAllowance<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// Address of the account that granted the allowance
+    pub granter: C::Address,
+    /// Address of the account that was granted the allowance
+    pub grantee: C::Address,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for TxRevokeFeeAllowance<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        TxRevokeFeeAllowance {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> TxRevokeFeeAllowance<C> {
+    /// Address of the account that granted the allowance
+    pub fn granter(self, granter: C::Address) -> Self {
+        Self { granter, ..self }
+    }
+
+    /// Address of the account that was granted the allowance
+    pub fn grantee(self, grantee: C::Address) -> Self {
+        Self { grantee, ..self }
+    }
+
+    /// Path to the TX WASM code file
+    pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
+        Self {
+            tx_code_path,
+            ..self
+        }
+    }
+}
+
+impl TxRevokeFeeAllowance {
+    /// Build a transaction from this builder
+    pub async fn build(
+        &self,
+        context: &impl Namada,
+    ) -> crate::error::Result<(namada_tx::Tx, SigningTxData)> {
+        tx::build_revoke_fee_allowance(context, self).await
+    }
+}
+
+/// Tx scheduling arguments
+#[derive(Clone, Debug)]
+pub struct TxScheduleTx<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// Address of the account scheduling the transaction and prepaying its
+    /// fee
+    pub owner: C::Address,
+    /// When to execute the transaction
+    pub target: ScheduleTarget,
+    /// The serialized transaction to schedule
+    pub serialized_tx: C::Data,
+    /// The gas limit of the execution of the scheduled transaction
+    pub gas_limit: GasLimit,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for TxScheduleTx<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        TxScheduleTx {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> TxScheduleTx<C> {
+    /// Address of the account scheduling the transaction
+    pub fn owner(self, owner: C::Address) -> Self {
+        Self { owner, ..self }
+    }
+
+    /// When to execute the transaction
+    pub fn target(self, target: ScheduleTarget) -> Self {
+        Self { target, ..self }
+    }
+
+    /// The serialized transaction to schedule
+    pub fn serialized_tx(self, serialized_tx: C::Data) -> Self {
+        Self {
+            serialized_tx,
+            ..self
+        }
+    }
+
+    /// The gas limit of the execution of the scheduled transaction
+    pub fn gas_limit(self, gas_limit: GasLimit) -> Self {
+        Self { gas_limit, ..self }
+    }
+
+    /// Path to the TX WASM code file
+    pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
+        Self {
+            tx_code_path,
+            ..self
+        }
+    }
+}
+
+impl TxScheduleTx {
+    /// Build a transaction from this builder
+    pub async fn build(
+        &self,
+        context: &impl Namada,
+    ) -> crate::error::Result<(namada_tx::Tx, SigningTxData)> {
+        tx::build_schedule_tx(context, self).await
+    }
+}
+
+/// Scheduled tx cancellation arguments
+#[derive(Clone, Debug)]
+pub struct TxCancelScheduledTx<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// Address of the account that scheduled the transaction
+    pub owner: C::Address,
+    /// When the transaction is scheduled to be executed
+    pub target: ScheduleTarget,
+    /// The hash of the scheduled transaction
+    pub hash: Hash,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for TxCancelScheduledTx<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        TxCancelScheduledTx {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> TxCancelScheduledTx<C> {
+    /// Address of the account that scheduled the transaction
+    pub fn owner(self, owner: C::Address) -> Self {
+        Self { owner, ..self }
+    }
+
+    /// When the transaction is scheduled to be executed
+    pub fn target(self, target: ScheduleTarget) -> Self {
+        Self { target, ..self }
+    }
+
+    /// The hash of the scheduled transaction
+    pub fn hash(self, hash: Hash) -> Self {
+        Self { hash, ..self }
+    }
+
+    /// Path to the TX WASM code file
+    pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
+        Self {
+            tx_code_path,
+            ..self
+        }
+    }
+}
+
+impl TxCancelScheduledTx {
+    /// Build a transaction from this builder
+    pub async fn build(
+        &self,
+        context: &impl Namada,
+    ) -> crate::error::Result<(namada_tx::Tx, SigningTxData)> {
+        tx::build_cancel_scheduled_tx(context, self).await
+    }
+}
+
+/// Spending policy update arguments
+#[derive(Clone, Debug)]
+pub struct TxUpdateSpendingPolicy<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// Address of the account whose debits are restricted
+    pub owner: C::Address,
+    /// The maximum amounts of the tokens that can be spent in an epoch with
+    /// the signatures of the spending keys only
+    pub spending_caps: Vec<(C::Address, InputAmount)>,
+    /// Addresses of the only accounts that can be credited by the debits. If
+    /// empty, any account can be credited.
+    pub allowed_destinations: Vec<C::Address>,
+    /// The public keys authorized to sign the debits within the spending caps
+    pub spending_keys: Vec<C::PublicKey>,
+    /// The number of signatures of the spending keys required to authorize
+    /// the debits within the spending caps
+    pub spending_threshold: Option<u8>,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for TxUpdateSpendingPolicy<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        TxUpdateSpendingPolicy {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> TxUpdateSpendingPolicy<C> {
+    /// Address of the account whose debits are restricted
+    pub fn owner(self, owner: C::Address) -> Self {
+        Self { owner, ..self }
+    }
+
+    /// The maximum amounts of the tokens that can be spent in an epoch with
+    /// the signatures of the spending keys only
+    pub fn spending_caps(
+        self,
+        spending_caps: Vec<(C::Address, InputAmount)>,
+    ) -> Self {
+        Self {
+            spending_caps,
+            ..self
+        }
+    }
+
+    /// Addresses of the only accounts that can be credited by the debits
+    pub fn allowed_destinations(
+        self,
+        allowed_destinations: Vec<C::Address>,
+    ) -> Self {
+        Self {
+            allowed_destinations,
+            ..self
+        }
+    }
+
+    /// The public keys authorized to sign the debits within the spending caps
+    pub fn spending_keys(self, spending_keys: Vec<C::PublicKey>) -> Self {
+        Self {
+            spending_keys,
+            ..self
+        }
+    }
+
+    /// The number of signatures of the spending keys required to authorize
+    /// the debits within the spending caps
+    pub fn spending_threshold(self, spending_threshold: u8) -> Self {
+        Self {
+            spending_threshold: Some(spending_threshold),
+            ..self
+        }
+    }
+
+    /// Path to the TX WASM code file
+    pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
+        Self {
+            tx_code_path,
+            ..self
+        }
+    }
+}
+
+impl TxUpdateSpendingPolicy {
+    /// Build a transaction from this builder
+    pub async fn build(
+        &self,
+        context: &impl Namada,
+    ) -> crate::error::Result<(namada_tx::Tx, SigningTxData)> {
+        tx::build_update_spending_policy(context, self).await
+    }
+}
+
+/// Spending policy removal arguments
+#[derive(Clone, Debug)]
+pub struct TxRemoveSpendingPolicy<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// Address of the account whose debits are restricted
+    pub owner: C::Address,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for TxRemoveSpendingPolicy<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        TxRemoveSpendingPolicy {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> TxRemoveSpendingPolicy<C> {
+    /// Address of the account whose debits are restricted
+    pub fn owner(self, owner: C::Address) -> Self {
+        Self { owner, ..self }
+    }
+
+    /// Path to the TX WASM code file
+    pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
+        Self {
+            tx_code_path,
+            ..self
+        }
+    }
+}
+
+impl TxRemoveSpendingPolicy {
+    /// Build a transaction from this builder
+    pub async fn build(
+        &self,
+        context: &impl Namada,
+    ) -> crate::error::Result<(namada_tx::Tx, SigningTxData)> {
+        tx::build_remove_spending_policy(context, self).await
+    }
+}
+
 /// Bond arguments
 #[derive(Clone, Debug)]
 pub struct Bond<C: NamadaTypes = SdkTypes> {
@@ -1270,6 +1863,15 @@ pub struct QueryAccount<C: NamadaTypes = SdkTypes> {
     pub owner: C::Address,
 }
 
+/// Query the transactions scheduled at a block height or epoch
+#[derive(Clone, Debug)]
+pub struct QueryScheduledTxs<C: NamadaTypes = SdkTypes> {
+    /// Common query args
+    pub query: Query<C>,
+    /// The block height or epoch of the execution
+    pub target: ScheduleTarget,
+}
+
 /// Query token balance(s)
 #[derive(Clone, Debug)]
 pub struct QueryBalance<C: NamadaTypes = SdkTypes> {
@@ -1294,6 +1896,43 @@ pub struct QueryTransfers<C: NamadaTypes = SdkTypes> {
     pub token: Option<C::Address>,
 }
 
+/// The format of an exported shielded history
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShieldedHistoryFormat {
+    /// A JSON array of the entries
+    #[default]
+    Json,
+    /// A CSV row per entry, with a header row
+    Csv,
+}
+
+impl std::str::FromStr for ShieldedHistoryFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!(
+                "Unknown shielded history format {s}, expected json or csv"
+            )),
+        }
+    }
+}
+
+/// Export the shielded history of viewing keys
+#[derive(Clone, Debug)]
+pub struct QueryShieldedHistory<C: NamadaTypes = SdkTypes> {
+    /// Common query args
+    pub query: Query<C>,
+    /// The viewing keys to export the history of, all the known ones if empty
+    pub viewing_keys: Vec<C::ViewingKey>,
+    /// The format of the export
+    pub format: ShieldedHistoryFormat,
+    /// The file to write the export to, the standard output if none
+    pub output: Option<PathBuf>,
+}
+
 /// Query PoS bond(s)
 #[derive(Clone, Debug)]
 pub struct QueryBonds<C: NamadaTypes = SdkTypes> {
@@ -1809,6 +2448,16 @@ pub struct SignTx<C: NamadaTypes = SdkTypes> {
     pub owner: C::Address,
 }
 
+/// Transaction inspection arguments
+#[derive(Clone, Debug)]
+pub struct TxInspect<C: NamadaTypes = SdkTypes> {
+    /// Transaction data
+    pub tx_data: C::Data,
+    /// Path to the WASM checksums file used to resolve the code hashes to
+    /// WASM names
+    pub wasm_checksums_path: Option<PathBuf>,
+}
+
 #[derive(Clone, Debug)]
 /// Sync notes from MASP owned by the provided spending /
 /// viewing keys. Syncing can be told to stop at a given
@@ -1816,14 +2465,19 @@ pub struct SignTx<C: NamadaTypes = SdkTypes> {
 pub struct ShieldedSync<C: NamadaTypes = SdkTypes> {
     /// The ledger address
     pub ledger_address: C::TendermintAddress,
-    /// The number of txs to fetch before caching
+    /// The number of blocks to fetch and scan between two checkpoints
     pub batch_size: u64,
+    /// The maximum number of blocks to fetch concurrently
+    pub max_concurrent_fetches: usize,
     /// Height to sync up to. Defaults to most recent
     pub last_query_height: Option<BlockHeight>,
     /// Spending keys used to determine note ownership
     pub spending_keys: Vec<C::SpendingKey>,
     /// Viewing keys used to determine note ownership
     pub viewing_keys: Vec<C::ViewingKey>,
+    /// The URL of a scanning service to retrieve the shielded transactions
+    /// from instead of the node
+    pub scanning_service: Option<String>,
 }
 
 /// Query PoS commission rate
@@ -1926,8 +2580,13 @@ pub struct Tx<C: NamadaTypes = SdkTypes> {
     pub fee_token: C::AddrOrNativeToken,
     /// The optional spending key for fee unshielding
     pub fee_unshield: Option<C::TransferSource>,
+    /// The optional account whose fee allowance pays for the fees
+    pub fee_granter: Option<C::Address>,
     /// The max amount of gas used to process tx
     pub gas_limit: GasLimit,
+    /// Estimate the gas limit by dry-running the tx and scaling the gas it
+    /// used by the given safety multiplier, instead of using `gas_limit`
+    pub gas_estimate_multiplier: Option<Dec>,
     /// The optional expiration of the transaction
     pub expiration: Option<DateTimeUtc>,
     /// Generate an ephimeral signing key to be used only once to sign a
@@ -2042,10 +2701,25 @@ pub trait TxBuilder<C: NamadaTypes>: Sized {
             ..x
         })
     }
+    /// The optional account whose fee allowance pays for the fees
+    fn fee_granter(self, fee_granter: C::Address) -> Self {
+        self.tx(|x| Tx {
+            fee_granter: Some(fee_granter),
+            ..x
+        })
+    }
     /// The max amount of gas used to process tx
     fn gas_limit(self, gas_limit: GasLimit) -> Self {
         self.tx(|x| Tx { gas_limit, ..x })
     }
+    /// Estimate the gas limit by dry-running the tx and scaling the gas it
+    /// used by the given safety multiplier
+    fn gas_estimate_multiplier(self, gas_estimate_multiplier: Dec) -> Self {
+        self.tx(|x| Tx {
+            gas_estimate_multiplier: Some(gas_estimate_multiplier),
+            ..x
+        })
+    }
     /// The optional expiration of the transaction
     fn expiration(self, expiration: DateTimeUtc) -> Self {
         self.tx(|x| Tx {
@@ -2122,6 +2796,8 @@ pub struct KeyGen {
     pub prompt_bip39_passphrase: bool,
     /// Allow non-compliant derivation path
     pub allow_non_compliant: bool,
+    /// The block height from which to scan for the notes of a spending key
+    pub birthday: Option<BlockHeight>,
 }
 
 /// Wallet restore key and implicit address arguments
@@ -2224,6 +2900,9 @@ pub struct KeyAddressAdd {
     pub value: String,
     /// Don't encrypt the key
     pub unsafe_dont_encrypt: bool,
+    /// The block height from which to scan for the notes of a viewing or
+    /// spending key
+    pub birthday: Option<BlockHeight>,
 }
 
 /// Wallet key / address remove arguments
@@ -228,6 +228,9 @@ pub enum TxSubmitError {
     /// Error in the fee unshielding transaction
     #[error("Error in fee unshielding: {0}")]
     FeeUnshieldingError(String),
+    /// Error in the estimation of the gas limit
+    #[error("Error in gas estimation: {0}")]
+    GasEstimationError(String),
     /// Encoding transaction failure
     #[error("Encoding tx data, {0}, shouldn't fail")]
     EncodeTxFailure(String),
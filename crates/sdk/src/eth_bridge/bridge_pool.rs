@@ -115,7 +115,7 @@ pub async fn build_bridge_pool_tx(
         &mut tx,
         unshield,
         fee_amount,
-        signing_data.fee_payer.clone(),
+        &signing_data,
     )
     .await?;
 
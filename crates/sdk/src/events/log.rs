@@ -1,88 +1,223 @@
-//! A log to store events emitted by `FinalizeBlock` calls in the ledger.
+//! Queries of the events emitted by `FinalizeBlock` calls in the ledger.
 //!
-//! The log can only hold `N` events at a time, where `N` is a configurable
-//! parameter. If the log is holding `N` events, and a new event is logged,
-//! old events are pruned.
-use circular_queue::CircularQueue;
+//! The events are persisted in the event store of the ledger's DB, where they
+//! are indexed by block height, event type and by the values of some of their
+//! attributes (see [`namada_state::event_store`]). The events of old heights
+//! are pruned according to the node's events retention configuration.
+use borsh::{BorshDeserialize, BorshSerialize};
+use itertools::Either;
+use namada_core::storage::BlockHeight;
+use namada_state::{DbError, DbResult, EventIndex, DB};
 
 use crate::events::Event;
 
-pub mod dumb_queries;
+pub mod query;
 
-/// Parameters to configure the pruning of the event log.
-#[derive(Debug, Copy, Clone)]
-pub struct Params {
-    /// Soft limit on the maximum number of events the event log can hold.
-    ///
-    /// If the number of events in the log exceeds this value, the log
-    /// will be pruned.
-    pub max_log_events: usize,
+/// The maximum number of events in a page of query results
+pub const MAX_EVENTS_PER_PAGE: u64 = 100;
+
+/// The maximum number of heights that a query which cannot be served from an
+/// index of the store may scan
+pub const MAX_SCANNED_HEIGHTS: u64 = 1_000;
+
+/// The maximum number of matching events that may precede a page of query
+/// results
+pub const MAX_SKIPPED_EVENTS: u64 = 10_000;
+
+/// The number of index entries read from the store at once
+const INDEX_ENTRIES_PER_READ: usize = 256;
+
+/// A page of the events that match a query
+#[derive(
+    Debug, Clone, Default, PartialEq, BorshSerialize, BorshDeserialize,
+)]
+pub struct EventsPage {
+    /// Whether more events that match the query follow this page
+    pub has_more: bool,
+    /// The events of this page, with the heights at which they were
+    /// emitted, starting from the most recent ones
+    pub events: Vec<(BlockHeight, Event)>,
+}
+
+/// Returns the range of heights of the store that the given query searches,
+/// which is empty if `from > to`.
+fn searched_heights<D>(
+    db: &D,
+    query: &query::EventQuery,
+    last_height: BlockHeight,
+) -> DbResult<(BlockHeight, BlockHeight)>
+where
+    D: DB,
+{
+    let (from, to) = query.height_range();
+    let pruned = db.read_pruned_events_height()?.unwrap_or(BlockHeight(0));
+    let from = from.max(pruned.next_height());
+    let to = to.map_or(last_height, |to| to.min(last_height));
+    Ok((from, to))
 }
 
-impl Default for Params {
-    fn default() -> Self {
-        // TODO: tune the default params
-        Self {
-            max_log_events: 50000,
+/// Returns an iterator over the heights and positions of the events in the
+/// given index of the store, from the `to` height down to the `from` height.
+/// The index is read in chunks, as the iterator advances.
+pub fn iter_event_index<D>(
+    db: &D,
+    index: EventIndex,
+    from: BlockHeight,
+    to: BlockHeight,
+) -> impl Iterator<Item = DbResult<(BlockHeight, u32)>> + '_
+where
+    D: DB,
+{
+    let mut before = (from <= to).then(|| (to.next_height(), 0));
+    let mut positions = std::collections::VecDeque::new();
+    std::iter::from_fn(move || {
+        if positions.is_empty() {
+            let read = match db.read_event_index(
+                &index,
+                from,
+                before?,
+                INDEX_ENTRIES_PER_READ,
+            ) {
+                Ok(read) => read,
+                Err(err) => {
+                    before = None;
+                    return Some(Err(err));
+                }
+            };
+            // A partial chunk is the end of the index
+            before = read
+                .last()
+                .copied()
+                .filter(|_| read.len() == INDEX_ENTRIES_PER_READ);
+            positions.extend(read);
         }
-    }
+        positions.pop_front().map(Ok)
+    })
 }
 
-/// Represents a log of [`Event`] instances emitted by
-/// `FinalizeBlock` calls, in the ledger.
-#[derive(Debug)]
-pub struct EventLog {
-    queue: CircularQueue<Event>,
+/// Returns an iterator over the events in the store that match the given
+/// query, starting from the most recent ones. Heights above `last_height`
+/// are not searched. Queries that cannot be served from an index of the
+/// store scan every event in their height range.
+pub fn iter_matching_events<'a, D>(
+    db: &'a D,
+    query: &'a query::EventQuery,
+    last_height: BlockHeight,
+) -> DbResult<impl Iterator<Item = DbResult<(BlockHeight, Event)>> + 'a>
+where
+    D: DB,
+{
+    let (from, to) = searched_heights(db, query, last_height)?;
+
+    let events: Box<dyn Iterator<Item = DbResult<(BlockHeight, Event)>>> =
+        match query.index() {
+            Some(index) => {
+                Box::new(iter_event_index(db, index, from, to).filter_map(
+                    move |found| {
+                        let (height, position) = match found {
+                            Ok(found) => found,
+                            Err(err) => return Some(Err(err)),
+                        };
+                        db.read_event(height, position)
+                            .map(|event| event.map(|event| (height, event)))
+                            .transpose()
+                    },
+                ))
+            }
+            None => Box::new((from.0..=to.0).rev().map(BlockHeight).flat_map(
+                move |height| {
+                    match db.read_events(height) {
+                        Ok(events) => Either::Left(
+                            events
+                                .into_iter()
+                                .rev()
+                                .map(move |event| Ok((height, event))),
+                        ),
+                        Err(err) => Either::Right(std::iter::once(Err(err))),
+                    }
+                },
+            )),
+        };
+    Ok(events.filter(move |found| match found {
+        Ok((height, event)) => query.matches(*height, event),
+        Err(_) => true,
+    }))
 }
 
-impl Default for EventLog {
-    fn default() -> Self {
-        Self::new(Default::default())
-    }
+/// Returns the most recent event in the store that matches the given query.
+pub fn find_latest_event<D>(
+    db: &D,
+    query: &query::EventQuery,
+    last_height: BlockHeight,
+) -> DbResult<Option<Event>>
+where
+    D: DB,
+{
+    iter_matching_events(db, query, last_height)?
+        .next()
+        .transpose()
+        .map(|found| found.map(|(_height, event)| event))
 }
 
-impl EventLog {
-    /// Return a new event log.
-    pub fn new(params: Params) -> Self {
-        Self {
-            queue: CircularQueue::with_capacity(params.max_log_events),
-        }
+/// Returns the given page of the events in the store that match the given
+/// query, starting from the most recent ones. The pages are numbered from
+/// 1. The search stops as soon as the page is filled.
+///
+/// A query that cannot be served from an index of the store must be bounded
+/// to at most [`MAX_SCANNED_HEIGHTS`] heights, and at most
+/// [`MAX_SKIPPED_EVENTS`] events may precede the page.
+pub fn query_events_page<D>(
+    db: &D,
+    query: &query::EventQuery,
+    last_height: BlockHeight,
+    page: u64,
+    per_page: u64,
+) -> DbResult<EventsPage>
+where
+    D: DB,
+{
+    let skip = page.saturating_sub(1).saturating_mul(per_page);
+    if skip > MAX_SKIPPED_EVENTS {
+        return Err(DbError::DBError(format!(
+            "At most {MAX_SKIPPED_EVENTS} events may precede the requested \
+             page, but {skip} do. Narrow down the heights of the query \
+             instead."
+        )));
     }
-
-    /// Log a new batch of events into the event log.
-    pub fn log_events<E>(&mut self, events: E)
-    where
-        E: IntoIterator<Item = Event>,
-    {
-        let mut num_entries = 0;
-        for event in events.into_iter() {
-            self.queue.push(event);
-            num_entries += 1;
+    if query.index().is_none() {
+        let (from, to) = searched_heights(db, query, last_height)?;
+        let scanned = (to.0 + 1).saturating_sub(from.0);
+        if scanned > MAX_SCANNED_HEIGHTS {
+            return Err(DbError::DBError(format!(
+                "The query would scan the events of {scanned} heights, but at \
+                 most {MAX_SCANNED_HEIGHTS} may be scanned. Bound the query's \
+                 block height, or add a condition on the event type or on one \
+                 of the indexed attributes."
+            )));
         }
-        tracing::debug!(num_entries, "Added new entries to the event log");
-    }
-
-    /// Returns a new iterator over this [`EventLog`].
-    #[inline]
-    pub fn iter(&self) -> impl Iterator<Item = &Event> {
-        self.queue.iter()
     }
 
-    /// Returns a filtering iterator over this [`EventLog`].
-    #[inline]
-    pub fn iter_with_matcher(
-        &self,
-        matcher: dumb_queries::QueryMatcher,
-    ) -> impl Iterator<Item = &Event> {
-        self.queue
-            .iter()
-            .filter(move |&event| matcher.matches(event))
+    let mut result = EventsPage::default();
+    let mut skipped = 0;
+    for found in iter_matching_events(db, query, last_height)? {
+        let found = found?;
+        if skipped < skip {
+            skipped += 1;
+        } else if (result.events.len() as u64) < per_page {
+            result.events.push(found);
+        } else {
+            result.has_more = true;
+            break;
+        }
     }
+    Ok(result)
 }
 
 #[cfg(test)]
 mod tests {
     use namada_core::hash::Hash;
+    use namada_state::mockdb::MockDB;
+    use namada_state::EventsRetention;
 
     use super::*;
     use crate::events::{EventLevel, EventType};
@@ -90,10 +225,13 @@ mod tests {
     const HASH: &str =
         "DEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEF";
 
+    /// The last committed height in the tests
+    const LAST_HEIGHT: BlockHeight = BlockHeight(4);
+
     /// An accepted tx hash query.
     macro_rules! accepted {
         ($hash:expr) => {
-            dumb_queries::QueryMatcher::accepted(Hash::try_from($hash).unwrap())
+            query::EventQuery::accepted(Hash::try_from($hash).unwrap())
         };
     }
 
@@ -120,82 +258,200 @@ mod tests {
         vec![event_1, event_2]
     }
 
-    /// Test adding a couple of events to the event log, and
+    /// Write the events emitted at the given height to the store.
+    fn write_events(db: &mut MockDB, height: u64, events: &[Event]) {
+        let mut batch = MockDB::batch();
+        db.batch_write_events(&mut batch, BlockHeight(height), events)
+            .unwrap();
+        db.exec_batch(batch).unwrap();
+    }
+
+    /// Test adding a couple of events to the event store, and
     /// reading those events back.
     #[test]
-    fn test_log_add() {
-        const NUM_HEIGHTS: usize = 4;
+    fn test_store_add() {
+        const NUM_HEIGHTS: u64 = LAST_HEIGHT.0;
 
-        let mut log = EventLog::new(Params::default());
+        let mut db = MockDB::default();
 
-        // add new events to the log
+        // add new events to the store
         let events = mock_tx_events(HASH);
 
-        for _ in 0..NUM_HEIGHTS {
-            log.log_events(events.clone());
+        for height in 1..=NUM_HEIGHTS {
+            write_events(&mut db, height, &events);
         }
 
-        // inspect log
-        let events_in_log: Vec<_> =
-            log.iter_with_matcher(accepted!(HASH)).cloned().collect();
-
-        assert_eq!(events_in_log.len(), NUM_HEIGHTS);
-
-        for event in events_in_log {
+        // inspect the store
+        let matcher = accepted!(HASH);
+        let events_in_store: Vec<_> =
+            iter_matching_events(&db, &matcher, LAST_HEIGHT)
+                .unwrap()
+                .collect::<DbResult<_>>()
+                .unwrap();
+
+        assert_eq!(events_in_store.len(), NUM_HEIGHTS as usize);
+
+        // the most recent events come first
+        for ((height, event), expected_height) in
+            events_in_store.into_iter().zip((1..=NUM_HEIGHTS).rev())
+        {
+            assert_eq!(height, BlockHeight(expected_height));
             assert_eq!(events[0], event);
         }
+        for height in 1..=NUM_HEIGHTS {
+            assert_eq!(db.read_events(BlockHeight(height)).unwrap(), events);
+        }
+        assert!(find_latest_event(
+            &db,
+            &accepted!(HASH.replace('D', "0")),
+            LAST_HEIGHT
+        )
+        .unwrap()
+        .is_none());
     }
 
-    /// Test pruning old events from the log.
+    /// Test pruning old events from the store.
     #[test]
-    fn test_log_prune() {
-        const LOG_CAP: usize = 4;
-
-        // log cap has to be a multiple of two
-        // for this test
-        if LOG_CAP < 2 || LOG_CAP & 1 != 0 {
-            panic!();
-        }
-
-        const MATCHED_EVENTS: usize = LOG_CAP / 2;
-
-        let mut log = EventLog::new(Params {
-            max_log_events: LOG_CAP,
-        });
+    fn test_store_prune() {
+        const NUM_HEIGHTS: u64 = LAST_HEIGHT.0;
 
-        // completely fill the log with events
-        //
-        // `mock_tx_events` returns 2 events, so
-        // we do `LOG_CAP / 2` iters to fill the log
+        let mut db = MockDB::default();
         let events = mock_tx_events(HASH);
-        assert_eq!(events.len(), 2);
-
-        for _ in 0..(LOG_CAP / 2) {
-            log.log_events(events.clone());
+        for height in 1..=NUM_HEIGHTS {
+            write_events(&mut db, height, &events);
         }
 
-        // inspect log - it should be full
-        let events_in_log: Vec<_> =
-            log.iter_with_matcher(accepted!(HASH)).cloned().collect();
-
-        assert_eq!(events_in_log.len(), MATCHED_EVENTS);
+        // keep the events of the last 2 heights
+        let retention = EventsRetention::KeepLast { heights: 2 };
+        let to = retention.prunable_height(BlockHeight(NUM_HEIGHTS)).unwrap();
+        let mut batch = MockDB::batch();
+        db.prune_events(&mut batch, BlockHeight(1), to).unwrap();
+        db.exec_batch(batch).unwrap();
+        assert_eq!(db.read_pruned_events_height().unwrap(), Some(to));
+
+        let matcher = accepted!(HASH);
+        let heights: Vec<_> = iter_matching_events(&db, &matcher, LAST_HEIGHT)
+            .unwrap()
+            .map(|found| found.unwrap().0)
+            .collect();
+        assert_eq!(heights, vec![BlockHeight(4), BlockHeight(3)]);
+        assert!(db.read_events(BlockHeight(2)).unwrap().is_empty());
+        assert!(iter_event_index(
+            &db,
+            matcher.index().unwrap(),
+            BlockHeight(0),
+            LAST_HEIGHT
+        )
+        .all(|found| found.unwrap().0 > to));
+    }
 
-        for event in events_in_log {
-            assert_eq!(events[0], event);
+    /// Test paginating the results of queries, with and without an index of
+    /// the store.
+    #[test]
+    fn test_store_query_pages() {
+        let mut db = MockDB::default();
+        let events = mock_tx_events(HASH);
+        for height in 1..=LAST_HEIGHT.0 {
+            write_events(&mut db, height, &events);
         }
 
-        // add a new APPLIED event to the log,
-        // pruning the first ACCEPTED event we added
-        log.log_events(Some(events[1].clone()));
+        let heights = |page: &EventsPage| -> Vec<u64> {
+            page.events.iter().map(|(height, _)| height.0).collect()
+        };
 
-        let events_in_log: Vec<_> =
-            log.iter_with_matcher(accepted!(HASH)).cloned().collect();
+        let query: query::EventQuery =
+            format!("applied.hash = '{HASH}' AND block.height >= 2")
+                .parse()
+                .unwrap();
+        let page = query_events_page(&db, &query, LAST_HEIGHT, 1, 2).unwrap();
+        assert!(page.has_more);
+        assert_eq!(heights(&page), vec![4, 3]);
+        let page = query_events_page(&db, &query, LAST_HEIGHT, 2, 2).unwrap();
+        assert!(!page.has_more);
+        assert_eq!(heights(&page), vec![2]);
+        let page = query_events_page(&db, &query, LAST_HEIGHT, 3, 2).unwrap();
+        assert!(!page.has_more);
+        assert!(page.events.is_empty());
+
+        // not served from an index
+        let query: query::EventQuery = "block.height < 3".parse().unwrap();
+        assert!(query.index().is_none());
+        let page = query_events_page(&db, &query, LAST_HEIGHT, 1, 10).unwrap();
+        assert!(!page.has_more);
+        assert_eq!(heights(&page), vec![2, 2, 1, 1]);
+        assert_eq!(page.events[0].1.event_type, EventType::Applied,);
+
+        // heights above the last committed height are not searched
+        let page = query_events_page(
+            &db,
+            &query::EventQuery::all(),
+            BlockHeight(1),
+            1,
+            10,
+        )
+        .unwrap();
+        assert_eq!(page.events.len(), 2);
+    }
 
-        const ACCEPTED_EVENTS: usize = MATCHED_EVENTS - 1;
-        assert_eq!(events_in_log.len(), ACCEPTED_EVENTS);
+    /// Test that the queries which would scan too much of the store are
+    /// refused.
+    #[test]
+    fn test_store_query_limits() {
+        let mut db = MockDB::default();
+        write_events(&mut db, 1, &mock_tx_events(HASH));
+        let last_height = BlockHeight(MAX_SCANNED_HEIGHTS + 1);
+
+        // not served from an index, nor bounded
+        let query = query::EventQuery::all();
+        assert!(query_events_page(&db, &query, last_height, 1, 10).is_err());
+        let query: query::EventQuery =
+            format!("block.height <= {MAX_SCANNED_HEIGHTS}")
+                .parse()
+                .unwrap();
+        let page = query_events_page(&db, &query, last_height, 1, 10).unwrap();
+        assert_eq!(page.events.len(), 2);
+
+        // served from an index, which is not bounded by heights
+        let query = accepted!(HASH);
+        let page = query_events_page(&db, &query, last_height, 1, 10).unwrap();
+        assert_eq!(page.events.len(), 1);
+
+        // too many events before the page
+        let page = MAX_SKIPPED_EVENTS / 10 + 2;
+        assert!(query_events_page(&db, &query, last_height, page, 10).is_err());
+    }
 
-        for event in events_in_log {
-            assert_eq!(events[0], event);
+    /// Test reading an index of the store in chunks.
+    #[test]
+    fn test_iter_event_index() {
+        let mut db = MockDB::default();
+        let events = mock_tx_events(HASH);
+        let num_heights = INDEX_ENTRIES_PER_READ as u64 + 10;
+        for height in 1..=num_heights {
+            write_events(&mut db, height, &events);
         }
+
+        let index = accepted!(HASH).index().unwrap();
+        let heights: Vec<_> = iter_event_index(
+            &db,
+            index.clone(),
+            BlockHeight(2),
+            BlockHeight(num_heights),
+        )
+        .map(|found| found.unwrap().0.0)
+        .collect();
+        // The accepted and applied events have the same hash
+        let expected: Vec<_> = (2..=num_heights)
+            .rev()
+            .flat_map(|height| [height, height])
+            .collect();
+        assert_eq!(heights, expected);
+
+        // An empty range of heights
+        assert_eq!(
+            iter_event_index(&db, index, BlockHeight(3), BlockHeight(2))
+                .count(),
+            0
+        );
     }
 }
@@ -0,0 +1,737 @@
+//! Parser and evaluator of CometBFT event queries.
+//!
+//! A query is a conjunction of conditions, following the CometBFT query
+//! grammar:
+//!
+//! ```text
+//! query     = condition { "AND" condition }
+//! condition = tag ( "EXISTS" | "CONTAINS" string | op operand )
+//! op        = "=" | "<" | "<=" | ">" | ">="
+//! operand   = string | number | "DATE" date | "TIME" time
+//! ```
+//!
+//! Strings are enclosed in single quotes, dates are formatted as
+//! `YYYY-MM-DD` and times as RFC 3339 timestamps. For example:
+//!
+//! ```text
+//! applied.hash EXISTS AND block.height >= 10 AND block.height < 20
+//! ```
+//!
+//! The tags are matched against Namada [`Event`]s as follows:
+//! - `tm.event` matches the CometBFT event carrying the Namada event, which is
+//!   `NewBlock` for all of them, or `Tx` for tx level events
+//! - `block.height` and `tx.height` match the height of the block in which the
+//!   event was emitted
+//! - `<type>.<attr>` matches the attribute `attr` of the events of the given
+//!   type
+//! - a tag without a type matches the attribute of events of any type
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use namada_core::dec::Dec;
+use namada_core::hash::Hash;
+use namada_core::storage::BlockHeight;
+use namada_core::time::DateTimeUtc;
+use namada_state::event_store::{EventIndex, INDEXED_ATTRIBUTES};
+use thiserror::Error;
+
+use crate::events::{Event, EventLevel, EventType};
+use crate::ibc::core::client::types::Height as IbcHeight;
+use crate::ibc::core::host::types::identifiers::{
+    ChannelId, ClientId, PortId, Sequence,
+};
+
+/// The tag of the CometBFT event type
+pub const EVENT_TYPE_TAG: &str = "tm.event";
+/// The tags of the height of the block in which an event was emitted
+pub const HEIGHT_TAGS: [&str; 2] = ["block.height", "tx.height"];
+
+/// Errors of parsing an event query
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    #[error("Unexpected end of the query, expected {0}")]
+    UnexpectedEnd(&'static str),
+    #[error("Unexpected input at position {0}, expected {1}")]
+    UnexpectedInput(usize, &'static str),
+    #[error("Invalid number: {0}")]
+    InvalidNumber(String),
+    #[error("Invalid date: {0}")]
+    InvalidDate(String),
+    #[error("Invalid time: {0}")]
+    InvalidTime(String),
+    #[error("The {0} operator cannot be applied to a string")]
+    StringComparison(&'static str),
+}
+
+/// An operand of a query condition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    /// A string
+    String(String),
+    /// A number, possibly with a fractional part
+    Number(Dec),
+    /// A date, at midnight UTC
+    Date(DateTimeUtc),
+    /// A point in time
+    Time(DateTimeUtc),
+}
+
+/// The operation of a query condition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    /// The value is equal to the operand
+    Eq(Operand),
+    /// The value is lower than the operand
+    Lt(Operand),
+    /// The value is lower than or equal to the operand
+    Lte(Operand),
+    /// The value is greater than the operand
+    Gt(Operand),
+    /// The value is greater than or equal to the operand
+    Gte(Operand),
+    /// The value contains the given string
+    Contains(String),
+    /// The value exists
+    Exists,
+}
+
+/// A condition on the value of a tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    /// The tag of the condition
+    pub tag: String,
+    /// The operation applied to the tag's value
+    pub op: Operation,
+}
+
+/// An [`EventQuery`] verifies if a Namada event matches a given CometBFT
+/// query.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventQuery {
+    conditions: Vec<Condition>,
+}
+
+impl EventQuery {
+    /// Returns a query that matches every event.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Add a condition to this query.
+    pub fn and(mut self, tag: impl Into<String>, op: Operation) -> Self {
+        self.conditions.push(Condition {
+            tag: tag.into(),
+            op,
+        });
+        self
+    }
+
+    /// Add a condition on the exact value of a tag to this query.
+    pub fn and_eq(self, tag: impl Into<String>, value: impl ToString) -> Self {
+        self.and(tag, Operation::Eq(Operand::String(value.to_string())))
+    }
+
+    /// Returns the conditions of this query.
+    pub fn conditions(&self) -> &[Condition] {
+        &self.conditions
+    }
+
+    /// Checks if this [`EventQuery`] validates the given [`Event`], emitted
+    /// at the given height.
+    pub fn matches(&self, height: BlockHeight, event: &Event) -> bool {
+        self.conditions
+            .iter()
+            .all(|condition| condition.matches(height, event))
+    }
+
+    /// Returns the index of the event store that contains every event
+    /// validated by this [`EventQuery`], if any. Indexed attributes are
+    /// preferred over the event type, as they are more selective.
+    pub fn index(&self) -> Option<EventIndex> {
+        let attribute_index = self.conditions.iter().find_map(|condition| {
+            let Operation::Eq(Operand::String(value)) = &condition.op else {
+                return None;
+            };
+            let (_, attr) = condition.event_attribute()?;
+            INDEXED_ATTRIBUTES
+                .contains(&attr)
+                .then(|| EventIndex::Attribute {
+                    key: attr.to_string(),
+                    value: value.clone(),
+                })
+        });
+        attribute_index.or_else(|| {
+            self.conditions.iter().find_map(|condition| {
+                let (event_type, _) = condition.event_attribute()?;
+                // The type index is keyed by the type's string
+                // representation, which the IBC variant holds verbatim
+                let event_type = EventType::from_str(event_type)
+                    .unwrap_or_else(|_| EventType::Ibc(event_type.to_string()));
+                Some(EventIndex::Type(event_type))
+            })
+        })
+    }
+
+    /// Returns the inclusive range of heights that the conditions on the
+    /// block height allow.
+    pub fn height_range(&self) -> (BlockHeight, Option<BlockHeight>) {
+        let mut from = BlockHeight(0);
+        let mut to: Option<BlockHeight> = None;
+        let mut bound_to = |height: u64| {
+            to =
+                Some(to.map_or(BlockHeight(height), |to| {
+                    to.min(BlockHeight(height))
+                }));
+        };
+        for condition in &self.conditions {
+            if !HEIGHT_TAGS.contains(&condition.tag.as_str()) {
+                continue;
+            }
+            let height = |operand: &Operand| match operand {
+                Operand::Number(num) => num
+                    .to_uint()
+                    .map(|num| u64::try_from(num).unwrap_or(u64::MAX)),
+                _ => None,
+            };
+            match &condition.op {
+                Operation::Eq(operand) => {
+                    if let Some(height) = height(operand) {
+                        from = from.max(BlockHeight(height));
+                        bound_to(height);
+                    }
+                }
+                Operation::Lt(operand) => {
+                    if let Some(height) = height(operand) {
+                        bound_to(height.saturating_sub(1));
+                    }
+                }
+                Operation::Lte(operand) => {
+                    if let Some(height) = height(operand) {
+                        bound_to(height);
+                    }
+                }
+                Operation::Gt(operand) => {
+                    if let Some(height) = height(operand) {
+                        from = from.max(BlockHeight(height.saturating_add(1)));
+                    }
+                }
+                Operation::Gte(operand) => {
+                    if let Some(height) = height(operand) {
+                        from = from.max(BlockHeight(height));
+                    }
+                }
+                Operation::Contains(_) | Operation::Exists => {}
+            }
+        }
+        (from, to)
+    }
+
+    /// Returns a query matching the given accepted transaction hash.
+    pub fn accepted(tx_hash: Hash) -> Self {
+        Self::all().and_eq(format!("{}.hash", EventType::Accepted), tx_hash)
+    }
+
+    /// Returns a query matching the given applied transaction hash.
+    pub fn applied(tx_hash: Hash) -> Self {
+        Self::all().and_eq(format!("{}.hash", EventType::Applied), tx_hash)
+    }
+
+    /// Returns a query matching the given IBC UpdateClient parameters
+    pub fn ibc_update_client(
+        client_id: ClientId,
+        consensus_height: BlockHeight,
+    ) -> Self {
+        use crate::ibc::core::client::types::events::{
+            CLIENT_ID_ATTRIBUTE_KEY, CONSENSUS_HEIGHTS_ATTRIBUTE_KEY,
+            UPDATE_CLIENT_EVENT,
+        };
+
+        Self::all()
+            .and_eq(
+                format!("{UPDATE_CLIENT_EVENT}.{CLIENT_ID_ATTRIBUTE_KEY}"),
+                client_id,
+            )
+            .and_eq(
+                format!(
+                    "{UPDATE_CLIENT_EVENT}.{CONSENSUS_HEIGHTS_ATTRIBUTE_KEY}"
+                ),
+                IbcHeight::new(0, consensus_height.0).expect("invalid height"),
+            )
+    }
+
+    /// Returns a query matching the given IBC packet parameters
+    pub fn ibc_packet(
+        event_type: EventType,
+        source_port: PortId,
+        source_channel: ChannelId,
+        destination_port: PortId,
+        destination_channel: ChannelId,
+        sequence: Sequence,
+    ) -> Self {
+        Self::all()
+            .and_eq(format!("{event_type}.packet_src_port"), source_port)
+            .and_eq(format!("{event_type}.packet_src_channel"), source_channel)
+            .and_eq(format!("{event_type}.packet_dst_port"), destination_port)
+            .and_eq(
+                format!("{event_type}.packet_dst_channel"),
+                destination_channel,
+            )
+            .and_eq(format!("{event_type}.packet_sequence"), sequence)
+    }
+}
+
+impl Condition {
+    /// Split the tag of an event attribute into the event type, if any, and
+    /// the attribute's key
+    fn event_attribute(&self) -> Option<(&str, &str)> {
+        let tag = self.tag.as_str();
+        if tag == EVENT_TYPE_TAG || HEIGHT_TAGS.contains(&tag) {
+            return None;
+        }
+        tag.split_once('.')
+    }
+
+    /// Checks if the given [`Event`], emitted at the given height, satisfies
+    /// this condition.
+    fn matches(&self, height: BlockHeight, event: &Event) -> bool {
+        let tag = self.tag.as_str();
+        if tag == EVENT_TYPE_TAG {
+            return match &self.op {
+                Operation::Exists => true,
+                Operation::Eq(Operand::String(value)) => {
+                    value == "NewBlock"
+                        || (value == "Tx" && event.level == EventLevel::Tx)
+                }
+                _ => false,
+            };
+        }
+        if HEIGHT_TAGS.contains(&tag) {
+            return self.op.matches(&height.0.to_string());
+        }
+        let attr = match tag.split_once('.') {
+            Some((event_type, attr)) => {
+                if event.event_type.to_string() != event_type {
+                    return false;
+                }
+                attr
+            }
+            None => tag,
+        };
+        match event.get(attr) {
+            Some(value) => self.op.matches(value),
+            None => false,
+        }
+    }
+}
+
+impl Operation {
+    /// Checks if the given value satisfies this operation.
+    fn matches(&self, value: &str) -> bool {
+        use std::cmp::Ordering;
+
+        let compare = |operand: &Operand| -> Option<Ordering> {
+            match operand {
+                Operand::String(operand) => Some(value.cmp(operand)),
+                Operand::Number(operand) => {
+                    Dec::from_str(value).ok().map(|value| value.cmp(operand))
+                }
+                Operand::Date(operand) => {
+                    parse_date(value).ok().map(|value| value.cmp(operand))
+                }
+                Operand::Time(operand) => DateTimeUtc::from_str(value)
+                    .ok()
+                    .map(|value| value.cmp(operand)),
+            }
+        };
+        match self {
+            Self::Eq(operand) => compare(operand) == Some(Ordering::Equal),
+            Self::Lt(operand) => compare(operand) == Some(Ordering::Less),
+            Self::Lte(operand) => matches!(
+                compare(operand),
+                Some(Ordering::Less | Ordering::Equal)
+            ),
+            Self::Gt(operand) => compare(operand) == Some(Ordering::Greater),
+            Self::Gte(operand) => matches!(
+                compare(operand),
+                Some(Ordering::Greater | Ordering::Equal)
+            ),
+            Self::Contains(sub) => value.contains(sub.as_str()),
+            Self::Exists => true,
+        }
+    }
+}
+
+/// Parse a date formatted as `YYYY-MM-DD`, at midnight UTC
+fn parse_date(date: &str) -> Result<DateTimeUtc, QueryError> {
+    DateTimeUtc::from_str(&format!("{date}T00:00:00Z"))
+        .map_err(|_| QueryError::InvalidDate(date.to_string()))
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(value) => write!(f, "'{value}'"),
+            Self::Number(value) => write!(f, "{value}"),
+            Self::Date(value) => {
+                write!(f, "DATE {}", value.0.format("%Y-%m-%d"))
+            }
+            Self::Time(value) => write!(f, "TIME {}", value.0.to_rfc3339()),
+        }
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tag = &self.tag;
+        match &self.op {
+            Operation::Eq(operand) => write!(f, "{tag} = {operand}"),
+            Operation::Lt(operand) => write!(f, "{tag} < {operand}"),
+            Operation::Lte(operand) => write!(f, "{tag} <= {operand}"),
+            Operation::Gt(operand) => write!(f, "{tag} > {operand}"),
+            Operation::Gte(operand) => write!(f, "{tag} >= {operand}"),
+            Operation::Contains(value) => write!(f, "{tag} CONTAINS '{value}'"),
+            Operation::Exists => write!(f, "{tag} EXISTS"),
+        }
+    }
+}
+
+impl Display for EventQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, condition) in self.conditions.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" AND ")?;
+            }
+            write!(f, "{condition}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for EventQuery {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { input: s, pos: 0 };
+        let mut conditions = vec![];
+        parser.skip_whitespace();
+        if parser.is_at_end() {
+            return Ok(Self::all());
+        }
+        loop {
+            conditions.push(parser.parse_condition()?);
+            parser.skip_whitespace();
+            if parser.is_at_end() {
+                break;
+            }
+            if !parser.parse_keyword("AND") {
+                return Err(QueryError::UnexpectedInput(parser.pos, "AND"));
+            }
+        }
+        Ok(Self { conditions })
+    }
+}
+
+/// A recursive descent parser of the query grammar
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.rest().is_empty()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Take the longest prefix of the remaining input whose chars satisfy
+    /// the predicate
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c: char| !predicate(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// Consume the given keyword, if it's next in the input
+    fn parse_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let is_keyword = rest.starts_with(keyword)
+            && !rest[keyword.len()..].starts_with(is_tag_char);
+        if is_keyword {
+            self.pos += keyword.len();
+        }
+        is_keyword
+    }
+
+    fn parse_condition(&mut self) -> Result<Condition, QueryError> {
+        self.skip_whitespace();
+        let tag = self.take_while(is_tag_char);
+        if tag.is_empty() {
+            return Err(self.unexpected("a tag"));
+        }
+        let tag = tag.to_string();
+        if self.parse_keyword("EXISTS") {
+            return Ok(Condition {
+                tag,
+                op: Operation::Exists,
+            });
+        }
+        if self.parse_keyword("CONTAINS") {
+            let value = self.parse_string()?;
+            return Ok(Condition {
+                tag,
+                op: Operation::Contains(value),
+            });
+        }
+        self.skip_whitespace();
+        let op = self.take_while(|c| matches!(c, '=' | '<' | '>'));
+        let op: fn(Operand) -> Operation = match op {
+            "=" => Operation::Eq,
+            "<" => Operation::Lt,
+            "<=" => Operation::Lte,
+            ">" => Operation::Gt,
+            ">=" => Operation::Gte,
+            _ => return Err(self.unexpected("an operator")),
+        };
+        let operand = self.parse_operand()?;
+        let op = op(operand);
+        let string_comparison = match &op {
+            Operation::Lt(Operand::String(_)) => Some("<"),
+            Operation::Lte(Operand::String(_)) => Some("<="),
+            Operation::Gt(Operand::String(_)) => Some(">"),
+            Operation::Gte(Operand::String(_)) => Some(">="),
+            _ => None,
+        };
+        if let Some(op) = string_comparison {
+            return Err(QueryError::StringComparison(op));
+        }
+        Ok(Condition { tag, op })
+    }
+
+    fn parse_string(&mut self) -> Result<String, QueryError> {
+        self.skip_whitespace();
+        let Some(rest) = self.rest().strip_prefix('\'') else {
+            return Err(self.unexpected("a string"));
+        };
+        let Some(len) = rest.find('\'') else {
+            return Err(QueryError::UnexpectedEnd("the end of a string"));
+        };
+        self.pos += len + 2;
+        Ok(rest[..len].to_string())
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, QueryError> {
+        self.skip_whitespace();
+        if self.rest().starts_with('\'') {
+            return self.parse_string().map(Operand::String);
+        }
+        if self.parse_keyword("DATE") {
+            self.skip_whitespace();
+            let date = self.take_while(|c| !c.is_whitespace());
+            return parse_date(date).map(Operand::Date);
+        }
+        if self.parse_keyword("TIME") {
+            self.skip_whitespace();
+            let time = self.take_while(|c| !c.is_whitespace());
+            return DateTimeUtc::from_str(time)
+                .map(Operand::Time)
+                .map_err(|_| QueryError::InvalidTime(time.to_string()));
+        }
+        let number =
+            self.take_while(|c| c.is_ascii_digit() || c == '.' || c == '-');
+        if number.is_empty() {
+            return Err(self.unexpected("an operand"));
+        }
+        Dec::from_str(number)
+            .map(Operand::Number)
+            .map_err(|_| QueryError::InvalidNumber(number.to_string()))
+    }
+
+    fn unexpected(&self, expected: &'static str) -> QueryError {
+        if self.is_at_end() {
+            QueryError::UnexpectedEnd(expected)
+        } else {
+            QueryError::UnexpectedInput(self.pos, expected)
+        }
+    }
+}
+
+/// Check if the char can be part of a tag
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '.' | '_' | '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    const HASH: &str =
+        "DEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEF";
+
+    fn event(event_type: EventType, attributes: &[(&str, &str)]) -> Event {
+        Event {
+            event_type,
+            level: EventLevel::Tx,
+            attributes: attributes
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    /// Test if query matching is working as expected.
+    #[test]
+    fn test_tm_query_matching() {
+        let matcher = EventQuery::accepted(Hash::from_str(HASH).unwrap());
+
+        let tests = [
+            (event(EventType::Accepted, &[("hash", HASH)]), true),
+            (event(EventType::Applied, &[("hash", HASH)]), false),
+            (event(EventType::Accepted, &[("hash", "BEEF")]), false),
+            (event(EventType::Accepted, &[]), false),
+        ];
+
+        for (ref ev, status) in tests {
+            if matcher.matches(BlockHeight(1), ev) != status {
+                panic!("Test failed");
+            }
+        }
+    }
+
+    /// Test parsing queries and printing them back.
+    #[test]
+    fn test_query_parsing() {
+        let query: EventQuery = format!(
+            "tm.event='NewBlock' AND applied.hash = '{HASH}' AND \
+             applied.code>=0 AND block.height < 100 AND applied.info CONTAINS \
+             'ok' AND applied.gas_used EXISTS AND transfer.date <= DATE \
+             2024-01-31 AND transfer.time > TIME 2024-01-31T10:00:00Z"
+        )
+        .parse()
+        .unwrap();
+        assert_eq!(query.conditions().len(), 8);
+        assert_eq!(
+            query.conditions()[2],
+            Condition {
+                tag: "applied.code".to_string(),
+                op: Operation::Gte(Operand::Number(Dec::zero())),
+            }
+        );
+        assert_eq!(
+            query.conditions()[4].op,
+            Operation::Contains("ok".to_string())
+        );
+        assert_eq!(query.conditions()[5].op, Operation::Exists);
+        // The printed query parses back to the same query
+        assert_eq!(query.to_string().parse::<EventQuery>().unwrap(), query);
+
+        assert_eq!("".parse::<EventQuery>().unwrap(), EventQuery::all());
+        assert_eq!(
+            "applied.hash".parse::<EventQuery>(),
+            Err(QueryError::UnexpectedEnd("an operator"))
+        );
+        assert_eq!(
+            "applied.hash = 'DEAD".parse::<EventQuery>(),
+            Err(QueryError::UnexpectedEnd("the end of a string"))
+        );
+        assert_eq!(
+            "applied.hash > 'DEAD'".parse::<EventQuery>(),
+            Err(QueryError::StringComparison(">"))
+        );
+        assert_eq!(
+            "applied.code = 0 OR applied.code = 1".parse::<EventQuery>(),
+            Err(QueryError::UnexpectedInput(17, "AND"))
+        );
+        assert_eq!(
+            "applied.code = 1x".parse::<EventQuery>(),
+            Err(QueryError::UnexpectedInput(16, "AND"))
+        );
+        assert!(matches!(
+            "transfer.date = DATE 2024-13-01".parse::<EventQuery>(),
+            Err(QueryError::InvalidDate(_))
+        ));
+    }
+
+    /// Test evaluating the operators of a query.
+    #[test]
+    fn test_query_evaluation() {
+        let ev = event(
+            EventType::Applied,
+            &[
+                ("hash", HASH),
+                ("code", "0"),
+                ("gas_used", "1500"),
+                ("info", "Transaction is valid"),
+                ("date", "2024-01-15"),
+            ],
+        );
+        let matches = |query: &str, height: u64| {
+            query
+                .parse::<EventQuery>()
+                .unwrap()
+                .matches(BlockHeight(height), &ev)
+        };
+        assert!(matches("tm.event = 'NewBlock' AND applied.code = 0", 1));
+        assert!(matches("tm.event = 'Tx'", 1));
+        assert!(matches("applied.gas_used > 1000.5", 1));
+        assert!(!matches("applied.gas_used <= 1000", 1));
+        assert!(matches("applied.info CONTAINS 'valid'", 1));
+        assert!(!matches("applied.info CONTAINS 'invalid'", 1));
+        assert!(matches("applied.hash EXISTS", 1));
+        assert!(!matches("applied.memo EXISTS", 1));
+        assert!(!matches("accepted.hash EXISTS", 1));
+        assert!(matches("hash EXISTS AND code = 0", 1));
+        assert!(matches("applied.date < DATE 2024-02-01", 1));
+        assert!(!matches("applied.date >= DATE 2024-02-01", 1));
+        // A value that isn't a number doesn't satisfy numeric conditions
+        assert!(!matches("applied.info > 0", 1));
+        assert!(matches("block.height >= 10 AND block.height < 20", 10));
+        assert!(!matches("block.height >= 10 AND block.height < 20", 20));
+    }
+
+    /// Test selecting an index of the event store and the heights range of a
+    /// query.
+    #[test]
+    fn test_query_index_and_heights() {
+        let query = EventQuery::applied(Hash::from_str(HASH).unwrap());
+        assert_eq!(query.index(), Some(EventIndex::tx_hash(HASH)));
+
+        let query: EventQuery = "applied.code = 0 AND block.height > 10 AND \
+                                 block.height <= 20 AND tx.height < 15"
+            .parse()
+            .unwrap();
+        assert_eq!(query.index(), Some(EventIndex::Type(EventType::Applied)));
+        assert_eq!(
+            query.height_range(),
+            (BlockHeight(11), Some(BlockHeight(14)))
+        );
+
+        let query: EventQuery =
+            "send_packet.packet_sequence = '1'".parse().unwrap();
+        assert_eq!(
+            query.index(),
+            Some(EventIndex::Type(EventType::Ibc("send_packet".to_string())))
+        );
+
+        let query: EventQuery = "block.height = 5".parse().unwrap();
+        assert_eq!(query.index(), None);
+        assert_eq!(
+            query.height_range(),
+            (BlockHeight(5), Some(BlockHeight(5)))
+        );
+        assert_eq!(EventQuery::all().height_range(), (BlockHeight(0), None));
+    }
+}
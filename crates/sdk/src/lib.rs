@@ -39,15 +39,19 @@ use args::{InputAmount, SdkTypes};
 use namada_core::address::Address;
 use namada_core::dec::Dec;
 use namada_core::ethereum_events::EthAddress;
+use namada_core::hash::Hash;
 use namada_core::ibc::core::host::types::identifiers::{ChannelId, PortId};
 use namada_core::key::*;
-use namada_core::masp::{TransferSource, TransferTarget};
+use namada_core::masp::{ExtendedSpendingKey, TransferSource, TransferTarget};
+use namada_tx::data::schedule::ScheduleTarget;
 use namada_tx::data::wrapper::GasLimit;
 use namada_tx::Tx;
 use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use crate::io::Io;
-use crate::masp::{ShieldedContext, ShieldedUtils};
+use crate::masp::{
+    ShieldedContext, ShieldedUtils, DEFAULT_CONSOLIDATION_MAX_DESCRIPTIONS,
+};
 use crate::rpc::{
     denominate_amount, format_denominated_amount, query_native_token,
 };
@@ -55,12 +59,15 @@ use crate::signing::SigningTxData;
 use crate::token::{DenominatedAmount, NATIVE_MAX_DECIMAL_PLACES};
 use crate::tx::{
     ProcessTxResponse, TX_BECOME_VALIDATOR_WASM, TX_BOND_WASM,
-    TX_BRIDGE_POOL_WASM, TX_CHANGE_COMMISSION_WASM,
-    TX_CHANGE_CONSENSUS_KEY_WASM, TX_CHANGE_METADATA_WASM,
-    TX_CLAIM_REWARDS_WASM, TX_DEACTIVATE_VALIDATOR_WASM, TX_IBC_WASM,
+    TX_BRIDGE_POOL_WASM, TX_CANCEL_SCHEDULED_TX_WASM,
+    TX_CHANGE_COMMISSION_WASM, TX_CHANGE_CONSENSUS_KEY_WASM,
+    TX_CHANGE_METADATA_WASM, TX_CLAIM_REWARDS_WASM,
+    TX_DEACTIVATE_VALIDATOR_WASM, TX_GRANT_FEE_ALLOWANCE_WASM, TX_IBC_WASM,
     TX_INIT_ACCOUNT_WASM, TX_INIT_PROPOSAL, TX_REACTIVATE_VALIDATOR_WASM,
-    TX_REDELEGATE_WASM, TX_RESIGN_STEWARD, TX_REVEAL_PK, TX_TRANSFER_WASM,
-    TX_UNBOND_WASM, TX_UNJAIL_VALIDATOR_WASM, TX_UPDATE_ACCOUNT_WASM,
+    TX_REDELEGATE_WASM, TX_REMOVE_SPENDING_POLICY_WASM, TX_RESIGN_STEWARD,
+    TX_REVEAL_PK, TX_REVOKE_FEE_ALLOWANCE_WASM, TX_SCHEDULE_TX_WASM,
+    TX_TRANSFER_WASM, TX_UNBOND_WASM, TX_UNJAIL_VALIDATOR_WASM,
+    TX_UPDATE_ACCOUNT_WASM, TX_UPDATE_SPENDING_POLICY_WASM,
     TX_UPDATE_STEWARD_COMMISSION, TX_VOTE_PROPOSAL, TX_WITHDRAW_WASM,
     VP_USER_WASM,
 };
@@ -132,11 +139,13 @@ pub trait Namada: Sized + MaybeSync + MaybeSend {
             .unwrap(),
             initialized_account_alias: None,
             wallet_alias_force: false,
+            fee_granter: None,
             fee_amount: None,
             wrapper_fee_payer: None,
             fee_token: self.native_token(),
             fee_unshield: None,
             gas_limit: GasLimit::from(20_000),
+            gas_estimate_multiplier: None,
             expiration: None,
             disposable_signing_key: false,
             chain_id: None,
@@ -167,6 +176,23 @@ pub trait Namada: Sized + MaybeSync + MaybeSend {
         }
     }
 
+    /// Make a TxConsolidateNotes builder from the given minimum set of
+    /// arguments
+    fn new_consolidate_notes(
+        &self,
+        spending_key: ExtendedSpendingKey,
+        token: Address,
+    ) -> args::TxConsolidateNotes {
+        args::TxConsolidateNotes {
+            spending_key,
+            token,
+            threshold: None,
+            max_descriptions: DEFAULT_CONSOLIDATION_MAX_DESCRIPTIONS,
+            tx_code_path: PathBuf::from(TX_TRANSFER_WASM),
+            tx: self.tx_builder(),
+        }
+    }
+
     /// Make a InitAccount builder from the given minimum set of arguments
     fn new_init_account(
         &self,
@@ -178,6 +204,7 @@ pub trait Namada: Sized + MaybeSync + MaybeSend {
             vp_code_path: PathBuf::from(VP_USER_WASM),
             tx_code_path: PathBuf::from(TX_INIT_ACCOUNT_WASM),
             public_keys,
+            public_key_weights: vec![],
             threshold,
         }
     }
@@ -285,12 +312,114 @@ pub trait Namada: Sized + MaybeSync + MaybeSend {
             addr,
             vp_code_path: None,
             public_keys: vec![],
+            public_key_weights: vec![],
             threshold: None,
             tx_code_path: PathBuf::from(TX_UPDATE_ACCOUNT_WASM),
             tx: self.tx_builder(),
         }
     }
 
+    /// Make a TxGrantFeeAllowance builder from the given minimum set of
+    /// arguments
+    fn new_grant_fee_allowance(
+        &self,
+        granter: Address,
+        grantee: Address,
+        token: Address,
+        spend_limit: InputAmount,
+    ) -> args::TxGrantFeeAllowance {
+        args::TxGrantFeeAllowance {
+            granter,
+            grantee,
+            token,
+            spend_limit,
+            expiration: None,
+            allowed_tx_code_paths: vec![],
+            tx_code_path: PathBuf::from(TX_GRANT_FEE_ALLOWANCE_WASM),
+            tx: self.tx_builder(),
+        }
+    }
+
+    /// Make a TxRevokeFeeAllowance builder from the given minimum set of
+    /// arguments
+    fn new_revoke_fee_allowance(
+        &self,
+        granter: Address,
+        grantee: Address,
+    ) -> args::TxRevokeFeeAllowance {
+        args::TxRevokeFeeAllowance {
+            granter,
+            grantee,
+            tx_code_path: PathBuf::from(TX_REVOKE_FEE_ALLOWANCE_WASM),
+            tx: self.tx_builder(),
+        }
+    }
+
+    /// Make a TxScheduleTx builder from the given minimum set of arguments
+    fn new_schedule_tx(
+        &self,
+        owner: Address,
+        target: ScheduleTarget,
+        serialized_tx: Vec<u8>,
+        gas_limit: GasLimit,
+    ) -> args::TxScheduleTx {
+        args::TxScheduleTx {
+            owner,
+            target,
+            serialized_tx,
+            gas_limit,
+            tx_code_path: PathBuf::from(TX_SCHEDULE_TX_WASM),
+            tx: self.tx_builder(),
+        }
+    }
+
+    /// Make a TxCancelScheduledTx builder from the given minimum set of
+    /// arguments
+    fn new_cancel_scheduled_tx(
+        &self,
+        owner: Address,
+        target: ScheduleTarget,
+        hash: Hash,
+    ) -> args::TxCancelScheduledTx {
+        args::TxCancelScheduledTx {
+            owner,
+            target,
+            hash,
+            tx_code_path: PathBuf::from(TX_CANCEL_SCHEDULED_TX_WASM),
+            tx: self.tx_builder(),
+        }
+    }
+
+    /// Make a TxUpdateSpendingPolicy builder from the given minimum set of
+    /// arguments
+    fn new_update_spending_policy(
+        &self,
+        owner: Address,
+    ) -> args::TxUpdateSpendingPolicy {
+        args::TxUpdateSpendingPolicy {
+            owner,
+            spending_caps: vec![],
+            allowed_destinations: vec![],
+            spending_keys: vec![],
+            spending_threshold: None,
+            tx_code_path: PathBuf::from(TX_UPDATE_SPENDING_POLICY_WASM),
+            tx: self.tx_builder(),
+        }
+    }
+
+    /// Make a TxRemoveSpendingPolicy builder from the given minimum set of
+    /// arguments
+    fn new_remove_spending_policy(
+        &self,
+        owner: Address,
+    ) -> args::TxRemoveSpendingPolicy {
+        args::TxRemoveSpendingPolicy {
+            owner,
+            tx_code_path: PathBuf::from(TX_REMOVE_SPENDING_POLICY_WASM),
+            tx: self.tx_builder(),
+        }
+    }
+
     /// Make a VoteProposal builder from the given minimum set of arguments
     fn new_vote_prposal(
         &self,
@@ -533,6 +662,15 @@ pub trait Namada: Sized + MaybeSync + MaybeSend {
         }
     }
 
+    /// Make a TxBatch builder from the given minimum set of arguments
+    fn new_batch(&self, owner: Address) -> args::TxBatch {
+        args::TxBatch {
+            owner,
+            tx: self.tx_builder(),
+            serialized_txs: vec![],
+        }
+    }
+
     /// Sign the given transaction using the given signing data
     async fn sign<D, F>(
         &self,
@@ -647,11 +785,13 @@ where
                 .unwrap(),
                 initialized_account_alias: None,
                 wallet_alias_force: false,
+                fee_granter: None,
                 fee_amount: None,
                 wrapper_fee_payer: None,
                 fee_token: native_token,
                 fee_unshield: None,
                 gas_limit: GasLimit::from(20_000),
+                gas_estimate_multiplier: None,
                 expiration: None,
                 disposable_signing_key: false,
                 chain_id: None,
@@ -3,13 +3,15 @@
 use std::cmp::Ordering;
 use std::collections::{btree_map, BTreeMap, BTreeSet, HashMap, HashSet};
 use std::env;
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 use std::ops::Deref;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::{Arc, RwLock};
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use borsh_ext::BorshSerializeExt;
+use futures::StreamExt;
 use itertools::Either;
 use lazy_static::lazy_static;
 use masp_primitives::asset_type::AssetType;
@@ -20,7 +22,7 @@ use masp_primitives::consensus::TestNetwork;
 use masp_primitives::convert::AllowedConversion;
 use masp_primitives::ff::PrimeField;
 use masp_primitives::group::GroupEncoding;
-use masp_primitives::memo::MemoBytes;
+use masp_primitives::memo::{Memo, MemoBytes};
 use masp_primitives::merkle_tree::{
     CommitmentTree, IncrementalWitness, MerklePath,
 };
@@ -59,6 +61,7 @@ pub use namada_core::masp::{
 use namada_core::storage::{BlockHeight, Epoch, IndexedTx, TxIndex};
 use namada_core::time::{DateTimeUtc, DurationSecs};
 use namada_core::uint::Uint;
+use namada_gas::{get_wrapper_gas, MASP_VERIFY_SHIELDED_TX_GAS};
 use namada_ibc::IbcMessage;
 use namada_token::{self as token, Denomination, MaspDigitPos, Transfer};
 use namada_tx::data::{TxResult, WrapperTx};
@@ -103,18 +106,135 @@ pub const OUTPUT_NAME: &str = "masp-output.params";
 /// Convert circuit name
 pub const CONVERT_NAME: &str = "masp-convert.params";
 
+/// The default number of blocks fetched and scanned between two checkpoints
+/// of the shielded context
+pub const DEFAULT_SYNC_BATCH_SIZE: u64 = 100;
+/// The default maximum number of blocks fetched concurrently
+pub const DEFAULT_MAX_CONCURRENT_FETCHES: usize = 10;
+
 /// Type alias for convenience and profit
 pub type IndexedNoteData = BTreeMap<
     IndexedTx,
-    (Epoch, BTreeSet<namada_core::storage::Key>, Transaction),
+    (Epoch, BTreeSet<namada_core::storage::Key>, CompactTx),
 >;
 
 /// Type alias for the entries of [`IndexedNoteData`] iterators
 pub type IndexedNoteEntry = (
     IndexedTx,
-    (Epoch, BTreeSet<namada_core::storage::Key>, Transaction),
+    (Epoch, BTreeSet<namada_core::storage::Key>, CompactTx),
 );
 
+/// The data of a shielded transaction needed to scan it: the commitments and
+/// ciphertexts of its output notes, the nullifiers of its spent notes and the
+/// addresses and values of its transparent inputs and outputs. The proofs,
+/// signatures and conversions of the transaction are left out.
+#[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize)]
+pub struct CompactTx {
+    /// The transparent inputs of the transaction
+    pub transparent_inputs: Vec<CompactTransparent>,
+    /// The transparent outputs of the transaction
+    pub transparent_outputs: Vec<CompactTransparent>,
+    /// The nullifiers of the notes spent by the transaction
+    pub nullifiers: Vec<Nullifier>,
+    /// The output notes of the transaction, in order
+    pub outputs: Vec<CompactOutput>,
+}
+
+impl From<&Transaction> for CompactTx {
+    fn from(shielded: &Transaction) -> Self {
+        let compact_transparent =
+            |address: &TransparentAddress, value| CompactTransparent {
+                address: address.0,
+                value,
+            };
+        let (transparent_inputs, transparent_outputs) = shielded
+            .transparent_bundle()
+            .map_or_else(Default::default, |bundle| {
+                (
+                    bundle
+                        .vin
+                        .iter()
+                        .map(|vin| compact_transparent(&vin.address, vin.value))
+                        .collect(),
+                    bundle
+                        .vout
+                        .iter()
+                        .map(|vout| {
+                            compact_transparent(&vout.address, vout.value)
+                        })
+                        .collect(),
+                )
+            });
+        let (nullifiers, outputs) =
+            shielded
+                .sapling_bundle()
+                .map_or_else(Default::default, |bundle| {
+                    (
+                        bundle
+                            .shielded_spends
+                            .iter()
+                            .map(|ss| ss.nullifier)
+                            .collect(),
+                        bundle
+                            .shielded_outputs
+                            .iter()
+                            .map(|so| CompactOutput {
+                                cmu: so.cmu.to_repr(),
+                                ephemeral_key: so.ephemeral_key.0,
+                                enc_ciphertext: so.enc_ciphertext.to_vec(),
+                            })
+                            .collect(),
+                    )
+                });
+        Self {
+            transparent_inputs,
+            transparent_outputs,
+            nullifiers,
+            outputs,
+        }
+    }
+}
+
+/// A transparent input or output of a [`CompactTx`]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub struct CompactTransparent {
+    /// The hash of the transparent address
+    pub address: [u8; 20],
+    /// The value, in the denomination of its asset type
+    pub value: u64,
+}
+
+/// An output note of a [`CompactTx`]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub struct CompactOutput {
+    /// The commitment to the note
+    pub cmu: [u8; 32],
+    /// The ephemeral key of the note encryption
+    pub ephemeral_key: [u8; 32],
+    /// The encrypted note, with its memo
+    pub enc_ciphertext: Vec<u8>,
+}
+
+impl CompactOutput {
+    /// Rebuild an output description that can be trial-decrypted. The value
+    /// commitment, the outgoing ciphertext and the proof are not needed to
+    /// decrypt the note and are left blank.
+    fn to_output_description(
+        &self,
+    ) -> Option<OutputDescription<<<Authorized as Authorization>::SaplingAuth as masp_primitives::transaction::components::sapling::Authorization>::Proof>>{
+        Some(OutputDescription {
+            cv: masp_primitives::jubjub::ExtendedPoint::identity(),
+            cmu: Option::from(masp_primitives::bls12_381::Scalar::from_repr(
+                self.cmu,
+            ))?,
+            ephemeral_key: self.ephemeral_key.into(),
+            enc_ciphertext: self.enc_ciphertext.clone().try_into().ok()?,
+            out_ciphertext: [0; 80],
+            zkproof: [0; 192],
+        })
+    }
+}
+
 /// Shielded transfer
 #[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
 pub struct ShieldedTransfer {
@@ -128,6 +248,172 @@ pub struct ShieldedTransfer {
     pub epoch: Epoch,
 }
 
+/// The default maximum number of spend and output descriptions of a note
+/// consolidation transaction
+pub const DEFAULT_CONSOLIDATION_MAX_DESCRIPTIONS: usize = 20;
+/// The estimated size of a spend description of a note consolidation
+/// transaction, including the data of the spent note in the MASP builder
+const CONSOLIDATION_SPEND_SIZE: u64 = 1_600;
+/// The estimated size of an output description of a note consolidation
+/// transaction, including the data of the new note in the MASP builder
+const CONSOLIDATION_OUTPUT_SIZE: u64 = 1_200;
+
+/// A shielded self-transfer merging notes of a spending key into a single
+/// note per asset type
+#[derive(Clone, Debug, Default)]
+pub struct ConsolidationStep {
+    /// The positions of the merged notes in the commitment tree
+    pub notes: Vec<usize>,
+    /// The value of the note produced for each asset type
+    pub outputs: BTreeMap<AssetType, u64>,
+    /// The total amount of the token merged by the transfer
+    pub amount: Amount,
+}
+
+impl ConsolidationStep {
+    /// The number of spend and output descriptions of the transfer
+    pub fn descriptions(&self) -> usize {
+        self.notes.len() + self.outputs.len()
+    }
+
+    /// The number of notes removed from the shielded pool by the transfer
+    pub fn merged_notes(&self) -> usize {
+        self.notes.len().saturating_sub(self.outputs.len())
+    }
+
+    /// Estimate the gas used by the transfer, in whole gas units
+    pub fn estimated_gas(&self) -> u64 {
+        estimate_consolidation_gas(self.notes.len(), self.outputs.len())
+    }
+
+    /// Merge the given notes of an asset type, given by their position and
+    /// amount of the token, into a note of the given value
+    fn merge_notes(
+        &mut self,
+        asset_type: AssetType,
+        notes: Vec<(usize, Amount)>,
+        value: u64,
+    ) -> Result<(), Error> {
+        for (note_idx, amount) in notes {
+            self.notes.push(note_idx);
+            self.amount = self.amount.checked_add(amount).ok_or_else(|| {
+                Error::Other(
+                    "Overflow in the amount of merged notes".to_string(),
+                )
+            })?;
+        }
+        self.outputs.insert(asset_type, value);
+        Ok(())
+    }
+}
+
+/// Estimate the gas used by a note consolidation transfer with the given
+/// number of spends and outputs, in whole gas units
+fn estimate_consolidation_gas(spends: usize, outputs: usize) -> u64 {
+    let size = spends as u64 * CONSOLIDATION_SPEND_SIZE
+        + outputs as u64 * CONSOLIDATION_OUTPUT_SIZE;
+    get_wrapper_gas(size)
+        .ok()
+        .and_then(|gas| gas.checked_add(MASP_VERIFY_SHIELDED_TX_GAS.into()))
+        .map_or(u64::MAX, |gas| gas.get_whole_gas_units())
+}
+
+/// Pack the given dust notes, given by their position, asset type, value and
+/// amount of the token, into self-transfers within the given limits of
+/// descriptions and gas. The notes of an asset type are merged smallest first
+/// and at least two at a time, since spending a single note of an asset type
+/// would only replace it with another one.
+fn pack_consolidation_steps(
+    dust: Vec<(usize, AssetType, u64, Amount)>,
+    max_descriptions: usize,
+    gas_limit: u64,
+) -> Result<Vec<ConsolidationStep>, Error> {
+    // Merging two notes of the same asset type takes two spends and one
+    // output
+    if max_descriptions < 3 {
+        return Err(Error::Other(format!(
+            "At least 3 descriptions are required to merge notes, got \
+             {max_descriptions}"
+        )));
+    }
+    let min_gas = estimate_consolidation_gas(2, 1);
+    if gas_limit < min_gas {
+        return Err(Error::Other(format!(
+            "At least {min_gas} gas is required to merge notes, got \
+             {gas_limit}"
+        )));
+    }
+
+    // Keep the notes of an asset type together to merge as many of them as
+    // possible in each transfer
+    let mut groups: BTreeMap<AssetType, Vec<(usize, u64, Amount)>> =
+        BTreeMap::new();
+    for (note_idx, asset_type, value, amount) in dust {
+        groups
+            .entry(asset_type)
+            .or_default()
+            .push((note_idx, value, amount));
+    }
+
+    let mut steps = Vec::new();
+    let mut step = ConsolidationStep::default();
+    for (asset_type, mut notes) in groups {
+        // A single note of an asset type can't be merged
+        if notes.len() < 2 {
+            continue;
+        }
+        notes.sort_by_key(|(_, _, amount)| *amount);
+        // The notes of the asset type to merge in the current transfer and
+        // the value of their merged note
+        let mut merged: Vec<(usize, Amount)> = Vec::new();
+        let mut merged_value = 0u64;
+        for (note_idx, value, amount) in notes {
+            let spends = step.notes.len() + merged.len() + 1;
+            let outputs = step.outputs.len() + 1;
+            let fits = spends + outputs <= max_descriptions
+                && estimate_consolidation_gas(spends, outputs) <= gas_limit;
+            if let Some(sum) = merged_value.checked_add(value).filter(|_| fits)
+            {
+                merged.push((note_idx, amount));
+                merged_value = sum;
+                continue;
+            }
+            // Close the transfer with the notes of the asset type that fit
+            // in it. A single one is carried over to the next transfer
+            // instead.
+            if merged.len() > 1 {
+                step.merge_notes(
+                    asset_type,
+                    std::mem::take(&mut merged),
+                    merged_value,
+                )?;
+                merged_value = 0;
+            }
+            let full = std::mem::take(&mut step);
+            if full.merged_notes() > 0 {
+                steps.push(full);
+            }
+            match merged_value.checked_add(value) {
+                Some(sum) => {
+                    merged.push((note_idx, amount));
+                    merged_value = sum;
+                }
+                None => {
+                    merged = vec![(note_idx, amount)];
+                    merged_value = value;
+                }
+            }
+        }
+        if merged.len() > 1 {
+            step.merge_notes(asset_type, merged, merged_value)?;
+        }
+    }
+    if step.merged_notes() > 0 {
+        steps.push(step);
+    }
+    Ok(steps)
+}
+
 /// Shielded pool data for a token
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct MaspTokenRewardData {
@@ -459,6 +745,99 @@ pub trait ShieldedUtils:
         &self,
         ctx: &ShieldedContext<U>,
     ) -> std::io::Result<()>;
+
+    /// The service to retrieve the shielded transactions from, if any. When
+    /// there is one, they are fetched from it by ranges of block heights
+    /// instead of querying every block from the node.
+    fn scanning_service(&self) -> Option<Arc<dyn ScanningService>> {
+        None
+    }
+}
+
+/// A service indexing the shielded transactions of the chain, so that the
+/// clients that cannot afford to download every block can still find their
+/// notes. It only serves the compact data needed for scanning: the epoch,
+/// the changed keys and the [`CompactTx`] of each shielded transaction.
+#[cfg_attr(feature = "async-send", async_trait::async_trait)]
+#[cfg_attr(not(feature = "async-send"), async_trait::async_trait(?Send))]
+pub trait ScanningService: Debug + MaybeSend + MaybeSync {
+    /// The last block height whose shielded transactions have been indexed
+    async fn last_indexed_height(&self) -> Result<Option<BlockHeight>, Error>;
+
+    /// Obtain the shielded transactions accepted in the blocks between the
+    /// given heights, both inclusive
+    async fn fetch_range(
+        &self,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> Result<IndexedNoteData, Error>;
+}
+
+/// An in-process [`ScanningService`] holding the indexed shielded
+/// transactions in memory. It backs the indexer and stands in for a remote
+/// service in tests. As nothing is evicted, its memory grows with the number
+/// of indexed shielded transactions.
+#[derive(Debug, Default)]
+pub struct LocalScanningService {
+    index: RwLock<(Option<BlockHeight>, IndexedNoteData)>,
+}
+
+impl LocalScanningService {
+    /// Add the shielded transactions of the blocks indexed up to the given
+    /// height
+    pub fn index(&self, height: BlockHeight, txs: IndexedNoteData) {
+        let mut index = self.index.write().expect("the index lock is poisoned");
+        index.0 = std::cmp::max(index.0, Some(height));
+        index.1.extend(txs);
+    }
+
+    /// The last indexed block height
+    pub fn indexed_height(&self) -> Option<BlockHeight> {
+        self.index.read().expect("the index lock is poisoned").0
+    }
+
+    /// The indexed shielded transactions accepted in the blocks between the
+    /// given heights, both inclusive
+    pub fn txs_between(
+        &self,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> IndexedNoteData {
+        if from > to {
+            return IndexedNoteData::new();
+        }
+        let start = IndexedTx {
+            height: from,
+            index: TxIndex(0),
+        };
+        let end = IndexedTx {
+            height: to,
+            index: TxIndex(u32::MAX),
+        };
+        self.index
+            .read()
+            .expect("the index lock is poisoned")
+            .1
+            .range(start..=end)
+            .map(|(indexed_tx, data)| (*indexed_tx, data.clone()))
+            .collect()
+    }
+}
+
+#[cfg_attr(feature = "async-send", async_trait::async_trait)]
+#[cfg_attr(not(feature = "async-send"), async_trait::async_trait(?Send))]
+impl ScanningService for LocalScanningService {
+    async fn last_indexed_height(&self) -> Result<Option<BlockHeight>, Error> {
+        Ok(self.indexed_height())
+    }
+
+    async fn fetch_range(
+        &self,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> Result<IndexedNoteData, Error> {
+        Ok(self.txs_between(from, to))
+    }
 }
 
 /// Make a ViewingKey that can view notes encrypted by given ExtendedSpendingKey
@@ -524,6 +903,138 @@ pub type TransferDelta = HashMap<Address, MaspChange>;
 
 /// Represents the changes that were made to a list of shielded accounts
 pub type TransactionDelta = HashMap<ViewingKey, I128Sum>;
+
+/// The kind of an entry in the shielded history of a viewing key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ShieldedHistoryKind {
+    /// A note received by the viewing key
+    Incoming,
+    /// An amount of an asset type spent from the notes of the viewing key
+    Outgoing,
+    /// The rewards realized by converting the spent notes to the epoch of the
+    /// transaction
+    Conversion,
+}
+
+impl Display for ShieldedHistoryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Incoming => write!(f, "incoming"),
+            Self::Outgoing => write!(f, "outgoing"),
+            Self::Conversion => write!(f, "conversion"),
+        }
+    }
+}
+
+/// An entry in the shielded history of a viewing key
+#[derive(Debug, Clone)]
+pub struct ShieldedHistoryEntry {
+    /// The transaction of the entry
+    pub indexed_tx: IndexedTx,
+    /// The epoch of the transaction
+    pub epoch: Epoch,
+    /// The hash of the transaction, if found in its events
+    pub tx_hash: Option<String>,
+    /// Whether the entry is incoming, outgoing or a conversion
+    pub kind: ShieldedHistoryKind,
+    /// The other party of the transaction, when known
+    pub counterparty: Option<TransferTarget>,
+    /// The asset type of the entry, unless it combines several of them
+    pub asset_type: Option<AssetType>,
+    /// The token of the entry, if its asset type could be decoded
+    pub token: Option<Address>,
+    /// The amount of the token, or the raw value of an undecoded asset type
+    pub amount: Amount,
+    /// The text memo of an incoming note, if any
+    pub memo: Option<String>,
+}
+
+/// The chronological shielded history of each viewing key
+pub type ShieldedHistory = BTreeMap<ViewingKey, Vec<ShieldedHistoryEntry>>;
+
+/// The notes received and the assets spent by a viewing key in a shielded
+/// transaction, before the decoding of their asset types
+#[derive(Debug, Clone)]
+struct TxNotesHistory {
+    /// The transaction
+    indexed_tx: IndexedTx,
+    /// The epoch of the transaction
+    epoch: Epoch,
+    /// The transparent source of a shielding transfer
+    source: Option<TransferTarget>,
+    /// The positions of the notes received by the viewing key
+    received: Vec<usize>,
+    /// The assets spent by the viewing key, net of the notes it received back
+    spent: I128Sum,
+    /// The recipient of the spent assets, when known
+    recipient: Option<TransferTarget>,
+}
+
+/// The rewards realized by exchanging the spent amounts of each token for the
+/// given exchanged amounts
+fn conversion_rewards(
+    exchanged: &ValueSum<Address, token::Change>,
+    spent: &ValueSum<Address, token::Change>,
+) -> Vec<(Address, Amount)> {
+    exchanged
+        .components()
+        .filter_map(|(token, value)| {
+            let reward = *value - spent.get(token);
+            reward
+                .is_positive()
+                .then(|| (token.clone(), Amount::from_change(reward)))
+        })
+        .collect()
+}
+
+/// The notes of a shielded transaction decrypted by a viewing key, with the
+/// offsets of their outputs in the transaction
+pub type DecryptedNotes = Vec<(
+    usize,
+    Note,
+    masp_primitives::sapling::PaymentAddress,
+    MemoBytes,
+)>;
+
+/// Try to decrypt the outputs of the given shielded transaction with the given
+/// viewing key
+pub fn trial_decrypt(shielded: &CompactTx, vk: &ViewingKey) -> DecryptedNotes {
+    let ivk = PreparedIncomingViewingKey::new(&vk.ivk());
+    shielded
+        .outputs
+        .iter()
+        .enumerate()
+        .filter_map(|(offset, output)| {
+            let so = output.to_output_description()?;
+            try_sapling_note_decryption::<_, OutputDescription<<<Authorized as Authorization>::SaplingAuth as masp_primitives::transaction::components::sapling::Authorization>::Proof>>(
+                &NETWORK,
+                1.into(),
+                &ivk,
+                &so,
+            )
+            .map(|(note, pa, memo)| (offset, note, pa, memo))
+        })
+        .collect()
+}
+
+/// Try to decrypt the outputs of the given shielded transaction with each of
+/// the given viewing keys, in parallel if the `multicore` feature is enabled
+fn trial_decrypt_with_keys(
+    shielded: &CompactTx,
+    vks: &[ViewingKey],
+) -> Vec<DecryptedNotes> {
+    #[cfg(feature = "multicore")]
+    {
+        use rayon::prelude::*;
+        vks.par_iter()
+            .map(|vk| trial_decrypt(shielded, vk))
+            .collect()
+    }
+    #[cfg(not(feature = "multicore"))]
+    {
+        vks.iter().map(|vk| trial_decrypt(shielded, vk)).collect()
+    }
+}
 /// A cache of fetched indexed transactions.
 ///
 /// The cache is designed so that it either contains
@@ -661,21 +1172,45 @@ impl<U: ShieldedUtils + MaybeSend + MaybeSync> ShieldedContext<U> {
         self.utils.save(self).await
     }
 
+    /// The block height of the last transaction added to the note commitment
+    /// tree, if any
+    pub fn last_witnessed_height(&self) -> Option<BlockHeight> {
+        self.tx_note_map
+            .keys()
+            .max()
+            .map(|indexed_tx| indexed_tx.height)
+    }
+
+    /// Start tracking the given viewing key if it isn't tracked yet. A key
+    /// with a birthday is considered synced up to the block preceding its
+    /// birthday, so that it is only trial-decrypted from there on.
+    fn track_viewing_key(
+        &mut self,
+        vk: ViewingKey,
+        birthday: Option<BlockHeight>,
+    ) {
+        self.vk_heights.entry(vk).or_insert_with(|| {
+            birthday
+                .filter(|height| height.0 > 1)
+                .map(|height| IndexedTx {
+                    height: height.prev_height(),
+                    index: TxIndex(u32::MAX),
+                })
+        });
+    }
+
     /// Update the merkle tree of witnesses the first time we
     /// scan a new MASP transaction.
     fn update_witness_map(
         &mut self,
         indexed_tx: IndexedTx,
-        shielded: &Transaction,
+        shielded: &CompactTx,
     ) -> Result<(), Error> {
         let mut note_pos = self.tree.size();
         self.tx_note_map.insert(indexed_tx, note_pos);
-        for so in shielded
-            .sapling_bundle()
-            .map_or(&vec![], |x| &x.shielded_outputs)
-        {
+        for output in &shielded.outputs {
             // Create merkle tree leaf node from note commitment
-            let node = Node::new(so.cmu.to_repr());
+            let node = Node::new(output.cmu);
             // Update each merkle tree in the witness map with the latest
             // addition
             for (_, witness) in self.witness_map.iter_mut() {
@@ -696,15 +1231,24 @@ impl<U: ShieldedUtils + MaybeSend + MaybeSync> ShieldedContext<U> {
     }
 
     /// Fetch the current state of the multi-asset shielded pool into a
-    /// ShieldedContext
+    /// ShieldedContext. The blocks are fetched in batches of the given size,
+    /// querying up to the given number of blocks concurrently, and the context
+    /// is saved after each batch has been scanned so that an interrupted sync
+    /// resumes from the last checkpoint. The viewing keys that are not
+    /// tracked yet are only scanned from their birthday, if known. If the
+    /// utils provide a scanning service, the transactions are retrieved from
+    /// it and the sync stops at the last height that it has indexed.
+    #[allow(clippy::too_many_arguments)]
     pub async fn fetch<C: Client + Sync, IO: Io>(
         &mut self,
         client: &C,
         logger: &impl ProgressLogger<IO>,
         last_query_height: Option<BlockHeight>,
-        _batch_size: u64,
+        batch_size: u64,
+        max_concurrent_fetches: usize,
         sks: &[ExtendedSpendingKey],
         fvks: &[ViewingKey],
+        birthdays: &BTreeMap<ViewingKey, BlockHeight>,
     ) -> Result<(), Error> {
         // add new viewing keys
         // Reload the state from file to get the last confirmed state and
@@ -723,10 +1267,10 @@ impl<U: ShieldedUtils + MaybeSend + MaybeSync> ShieldedContext<U> {
 
         for esk in sks {
             let vk = to_viewing_key(esk).vk;
-            self.vk_heights.entry(vk).or_default();
+            self.track_viewing_key(vk, birthdays.get(&vk).copied());
         }
         for vk in fvks {
-            self.vk_heights.entry(*vk).or_default();
+            self.track_viewing_key(*vk, birthdays.get(vk).copied());
         }
         let _ = self.save().await;
         let native_token = query_native_token(client).await?;
@@ -739,76 +1283,175 @@ impl<U: ShieldedUtils + MaybeSend + MaybeSync> ShieldedContext<U> {
         // get the bounds on the block heights to fetch
         let start_idx =
             std::cmp::min(last_witnessed_tx, least_idx).map(|ix| ix.height);
-        // Load all transactions accepted until this point
-        // N.B. the cache is a hash map
-        self.unscanned.extend(
-            self.fetch_shielded_transfers(
-                client,
+        // Query for the last produced block height
+        let last_block_height = query_block(client)
+            .await?
+            .map_or_else(BlockHeight::first, |block| block.height);
+        let mut last_query_height =
+            last_query_height.unwrap_or(last_block_height);
+        let scanning_service = self.utils.scanning_service();
+        if let Some(service) = &scanning_service {
+            let indexed_height = service
+                .last_indexed_height()
+                .await?
+                .unwrap_or(BlockHeight(0));
+            last_query_height =
+                std::cmp::min(last_query_height, indexed_height);
+        }
+        // Fetch all the transactions we do not have yet
+        let first_height_to_query = start_idx.map_or_else(|| 1, |last| last.0);
+        let mut heights =
+            logger.fetch(first_height_to_query..=last_query_height.0);
+        let batch_size =
+            usize::try_from(batch_size).unwrap_or(usize::MAX).max(1);
+        loop {
+            let batch: Vec<u64> = heights.by_ref().take(batch_size).collect();
+            let (Some(first_batch_height), Some(last_batch_height)) =
+                (batch.first().copied(), batch.last().copied())
+            else {
+                break;
+            };
+            // Load the transactions accepted in this batch of blocks
+            // N.B. the cache is a hash map
+            let shielded_txs = match &scanning_service {
+                Some(service) => {
+                    service
+                        .fetch_range(
+                            BlockHeight(first_batch_height),
+                            BlockHeight(last_batch_height),
+                        )
+                        .await?
+                }
+                None => {
+                    self.fetch_shielded_transfers(
+                        client,
+                        batch,
+                        max_concurrent_fetches,
+                    )
+                    .await?
+                }
+            };
+            self.unscanned.extend(shielded_txs);
+            self.scan_unscanned(
                 logger,
-                start_idx,
-                last_query_height,
-            )
-            .await?,
-        );
-        // persist the cache in case of interruptions.
-        let _ = self.save().await;
+                BlockHeight(last_batch_height),
+                &native_token,
+            )?;
+            // Checkpoint the progress in case of interruptions
+            let _ = self.save().await;
+        }
+
+        Ok(())
+    }
 
-        let txs = logger.scan(self.unscanned.clone());
+    /// Scan the cached transactions up to the given block height with the
+    /// viewing keys that have not scanned them yet. The notes of each
+    /// transaction are trial-decrypted with all these viewing keys at once,
+    /// unless the context is speculative.
+    fn scan_unscanned<IO: Io>(
+        &mut self,
+        logger: &impl ProgressLogger<IO>,
+        last_height: BlockHeight,
+        native_token: &Address,
+    ) -> Result<(), Error> {
+        let txs = logger.scan(
+            self.unscanned
+                .clone()
+                .into_iter()
+                .filter(|(indexed_tx, _)| indexed_tx.height <= last_height),
+        );
+        // The transactions are scanned in order, so the last witnessed one
+        // is tracked here instead of being looked up for each of them
+        let mut last_witnessed_tx = self
+            .tx_note_map
+            .last_key_value()
+            .map(|(indexed_tx, _)| *indexed_tx);
         for (indexed_tx, (epoch, tx, stx)) in txs {
             if Some(indexed_tx) > last_witnessed_tx {
                 self.update_witness_map(indexed_tx, &stx)?;
+                last_witnessed_tx = Some(indexed_tx);
             }
-            let mut vk_heights = BTreeMap::new();
-            std::mem::swap(&mut vk_heights, &mut self.vk_heights);
-            for (vk, h) in vk_heights
-                .iter_mut()
+            let vks: Vec<ViewingKey> = self
+                .vk_heights
+                .iter()
                 .filter(|(_vk, h)| **h < Some(indexed_tx))
-            {
-                self.scan_tx(
+                .map(|(vk, _h)| *vk)
+                .collect();
+            // Only try to decrypt the notes if we are syncing (i.e. in a
+            // confirmed status)
+            let decrypted_notes = match self.sync_status {
+                ContextSyncStatus::Confirmed => {
+                    trial_decrypt_with_keys(&stx, &vks)
+                }
+                ContextSyncStatus::Speculative => {
+                    vks.iter().map(|_| vec![]).collect()
+                }
+            };
+            for (vk, decrypted_notes) in vks.iter().zip(decrypted_notes) {
+                self.scan_decrypted_tx(
                     indexed_tx,
                     epoch,
                     &tx,
                     &stx,
                     vk,
+                    decrypted_notes,
                     native_token.clone(),
                 )?;
-                *h = Some(indexed_tx);
+                self.vk_heights.insert(*vk, Some(indexed_tx));
             }
             // possibly remove unneeded elements from the cache.
             self.unscanned.scanned(&indexed_tx);
-            std::mem::swap(&mut vk_heights, &mut self.vk_heights);
-            let _ = self.save().await;
         }
-
         Ok(())
     }
 
-    /// Obtain a chronologically-ordered list of all accepted shielded
-    /// transactions from a node.
-    pub async fn fetch_shielded_transfers<C: Client + Sync, IO: Io>(
+    /// Obtain a chronologically-ordered list of the shielded transactions
+    /// accepted at the given block heights from a node, skipping the heights
+    /// that are already cached. Up to the given number of blocks are queried
+    /// concurrently.
+    pub async fn fetch_shielded_transfers<C: Client + Sync>(
         &self,
         client: &C,
-        logger: &impl ProgressLogger<IO>,
-        last_indexed_tx: Option<BlockHeight>,
-        last_query_height: Option<BlockHeight>,
+        heights: impl IntoIterator<Item = u64>,
+        max_concurrent_fetches: usize,
     ) -> Result<IndexedNoteData, Error> {
-        // Query for the last produced block height
-        let last_block_height = query_block(client)
-            .await?
-            .map_or_else(BlockHeight::first, |block| block.height);
-        let last_query_height = last_query_height.unwrap_or(last_block_height);
+        let heights: Vec<u64> = heights
+            .into_iter()
+            .filter(|height| !self.unscanned.contains_height(*height))
+            .collect();
+        let mut fetches = futures::stream::iter(heights)
+            .map(|height| {
+                Self::fetch_shielded_transfers_at_height(client, height)
+            })
+            .buffered(max_concurrent_fetches.max(1));
 
         let mut shielded_txs = BTreeMap::new();
-        // Fetch all the transactions we do not have yet
-        let first_height_to_query =
-            last_indexed_tx.map_or_else(|| 1, |last| last.0);
-        let heights = logger.fetch(first_height_to_query..=last_query_height.0);
-        for height in heights {
-            if self.unscanned.contains_height(height) {
-                continue;
-            }
-            // Get the valid masp transactions at the specified height
-            let epoch = query_epoch_at_height(client, height.into())
+        while let Some(txs) = fetches.next().await {
+            shielded_txs.extend(txs?);
+        }
+
+        Ok(shielded_txs)
+    }
+
+    /// Obtain the shielded transactions accepted at the given block height
+    async fn fetch_shielded_transfers_at_height<C: Client + Sync>(
+        client: &C,
+        height: u64,
+    ) -> Result<Vec<IndexedNoteEntry>, Error> {
+        // Get the valid masp transactions at the specified height
+        let txs_results = match get_indexed_masp_events_at_height(
+            client,
+            height.into(),
+            None,
+        )
+        .await?
+        {
+            Some(events) => events,
+            None => return Ok(vec![]),
+        };
+
+        let epoch = async {
+            query_epoch_at_height(client, height.into())
                 .await?
                 .ok_or_else(|| {
                     Error::from(QueryError::General(
@@ -816,50 +1459,41 @@ impl<U: ShieldedUtils + MaybeSend + MaybeSync> ShieldedContext<U> {
                          block height"
                             .to_string(),
                     ))
-                })?;
-
-            let txs_results = match get_indexed_masp_events_at_height(
-                client,
-                height.into(),
-                None,
-            )
-            .await?
-            {
-                Some(events) => events,
-                None => continue,
-            };
-
-            // Query the actual block to get the txs bytes. If we only need one
-            // tx it might be slightly better to query the /tx endpoint to
-            // reduce the amount of data sent over the network, but this is a
-            // minimal improvement and it's even hard to tell how many times
-            // we'd need a single masp tx to make this worth it
-            let block = client
+                })
+        };
+        // Query the actual block to get the txs bytes. If we only need one
+        // tx it might be slightly better to query the /tx endpoint to
+        // reduce the amount of data sent over the network, but this is a
+        // minimal improvement and it's even hard to tell how many times
+        // we'd need a single masp tx to make this worth it
+        let block = async {
+            client
                 .block(height as u32)
                 .await
-                .map_err(|e| Error::from(QueryError::General(e.to_string())))?
-                .block
-                .data;
-
-            for (idx, tx_event) in txs_results {
-                let tx = Tx::try_from(block[idx.0 as usize].as_ref())
-                    .map_err(|e| Error::Other(e.to_string()))?;
-                let (changed_keys, masp_transaction) = Self::extract_masp_tx(
-                    &tx,
-                    ExtractShieldedActionArg::Event::<C>(&tx_event),
-                    true,
-                )
-                .await?;
+                .map(|response| response.block.data)
+                .map_err(|e| Error::from(QueryError::General(e.to_string())))
+        };
+        let (epoch, block) = futures::try_join!(epoch, block)?;
 
-                // Collect the current transaction
-                shielded_txs.insert(
-                    IndexedTx {
-                        height: height.into(),
-                        index: idx,
-                    },
-                    (epoch, changed_keys, masp_transaction),
-                );
-            }
+        let mut shielded_txs = Vec::with_capacity(txs_results.len());
+        for (idx, tx_event) in txs_results {
+            let tx = Tx::try_from(block[idx.0 as usize].as_ref())
+                .map_err(|e| Error::Other(e.to_string()))?;
+            let (changed_keys, masp_transaction) = Self::extract_masp_tx(
+                &tx,
+                ExtractShieldedActionArg::Event::<C>(&tx_event),
+                true,
+            )
+            .await?;
+
+            // Collect the current transaction
+            shielded_txs.push((
+                IndexedTx {
+                    height: height.into(),
+                    index: idx,
+                },
+                (epoch, changed_keys, CompactTx::from(&masp_transaction)),
+            ));
         }
 
         Ok(shielded_txs)
@@ -1025,72 +1659,89 @@ impl<U: ShieldedUtils + MaybeSend + MaybeSync> ShieldedContext<U> {
         shielded: &Transaction,
         vk: &ViewingKey,
         native_token: Address,
+    ) -> Result<(), Error> {
+        let shielded = CompactTx::from(shielded);
+        // Only try to decrypt the notes if we are syncing (i.e. in a
+        // confirmed status)
+        let decrypted_notes = match self.sync_status {
+            ContextSyncStatus::Confirmed => trial_decrypt(&shielded, vk),
+            ContextSyncStatus::Speculative => vec![],
+        };
+        self.scan_decrypted_tx(
+            indexed_tx,
+            epoch,
+            tx_changed_keys,
+            &shielded,
+            vk,
+            decrypted_notes,
+            native_token,
+        )
+    }
+
+    /// Applies the given transaction to the supplied context, given the notes
+    /// of the transaction that the viewing key could decrypt. See
+    /// [`Self::scan_tx`].
+    #[allow(clippy::too_many_arguments)]
+    fn scan_decrypted_tx(
+        &mut self,
+        indexed_tx: IndexedTx,
+        epoch: Epoch,
+        tx_changed_keys: &BTreeSet<namada_core::storage::Key>,
+        shielded: &CompactTx,
+        vk: &ViewingKey,
+        decrypted_notes: DecryptedNotes,
+        native_token: Address,
     ) -> Result<(), Error> {
         // For tracking the account changes caused by this Transaction
         let mut transaction_delta = TransactionDelta::new();
         if let ContextSyncStatus::Confirmed = self.sync_status {
-            let mut note_pos = self.tx_note_map[&indexed_tx];
+            let first_note_pos = self.tx_note_map[&indexed_tx];
             // Listen for notes sent to our viewing keys, only if we are syncing
             // (i.e. in a confirmed status)
-            for so in shielded
-                .sapling_bundle()
-                .map_or(&vec![], |x| &x.shielded_outputs)
-            {
-                // Let's try to see if this viewing key can decrypt latest
+            if !shielded.outputs.is_empty() {
+                self.pos_map.entry(*vk).or_default();
+            }
+            // So this current viewing key does decrypt these notes...
+            for (offset, note, pa, memo) in decrypted_notes {
+                let note_pos = first_note_pos + offset;
+                // Add this note to list of notes decrypted by this viewing
+                // key
+                self.pos_map.entry(*vk).or_default().insert(note_pos);
+                // Compute the nullifier now to quickly recognize when spent
+                let nf = note.nf(
+                    &vk.nk,
+                    note_pos.try_into().map_err(|_| {
+                        Error::Other("Can not get nullifier".to_string())
+                    })?,
+                );
+                self.note_map.insert(note_pos, note);
+                self.memo_map.insert(note_pos, memo);
+                // The payment address' diversifier is required to spend
                 // note
-                let notes = self.pos_map.entry(*vk).or_default();
-                let decres = try_sapling_note_decryption::<_, OutputDescription<<<Authorized as Authorization>::SaplingAuth as masp_primitives::transaction::components::sapling::Authorization>::Proof>>(
-                &NETWORK,
-                1.into(),
-                &PreparedIncomingViewingKey::new(&vk.ivk()),
-                so,
-            );
-                // So this current viewing key does decrypt this current note...
-                if let Some((note, pa, memo)) = decres {
-                    // Add this note to list of notes decrypted by this viewing
-                    // key
-                    notes.insert(note_pos);
-                    // Compute the nullifier now to quickly recognize when spent
-                    let nf = note.nf(
-                        &vk.nk,
-                        note_pos.try_into().map_err(|_| {
-                            Error::Other("Can not get nullifier".to_string())
-                        })?,
-                    );
-                    self.note_map.insert(note_pos, note);
-                    self.memo_map.insert(note_pos, memo);
-                    // The payment address' diversifier is required to spend
-                    // note
-                    self.div_map.insert(note_pos, *pa.diversifier());
-                    self.nf_map.insert(nf, note_pos);
-                    // Note the account changes
-                    let balance = transaction_delta
-                        .entry(*vk)
-                        .or_insert_with(I128Sum::zero);
-                    *balance += I128Sum::from_nonnegative(
-                        note.asset_type,
-                        note.value as i128,
+                self.div_map.insert(note_pos, *pa.diversifier());
+                self.nf_map.insert(nf, note_pos);
+                // Note the account changes
+                let balance =
+                    transaction_delta.entry(*vk).or_insert_with(I128Sum::zero);
+                *balance += I128Sum::from_nonnegative(
+                    note.asset_type,
+                    note.value as i128,
+                )
+                .map_err(|()| {
+                    Error::Other(
+                        "found note with invalid value or asset type"
+                            .to_string(),
                     )
-                    .map_err(|()| {
-                        Error::Other(
-                            "found note with invalid value or asset type"
-                                .to_string(),
-                        )
-                    })?;
-                    self.vk_map.insert(note_pos, *vk);
-                }
-                note_pos += 1;
+                })?;
+                self.vk_map.insert(note_pos, *vk);
             }
         }
 
         // Cancel out those of our notes that have been spent
-        for ss in shielded
-            .sapling_bundle()
-            .map_or(&vec![], |x| &x.shielded_spends)
-        {
+        for nullifier in &shielded.nullifiers {
             // If the shielded spend's nullifier is in our map, then target note
             // is rendered unusable
-            if let Some(note_pos) = self.nf_map.get(&ss.nullifier) {
+            if let Some(note_pos) = self.nf_map.get(nullifier) {
                 self.spents.insert(*note_pos);
                 // Note the account changes
                 let balance = transaction_delta
@@ -1117,146 +1768,391 @@ impl<U: ShieldedUtils + MaybeSend + MaybeSync> ShieldedContext<U> {
             .iter()
             .filter_map(is_any_shielded_action_balance_key)
             .collect();
-        let (source, token, amount) = match shielded.transparent_bundle() {
-            Some(transp_bundle) => {
-                // Shielding/Unshielding transfer
-                match (transp_bundle.vin.len(), transp_bundle.vout.len()) {
-                    (0, 0) => {
-                        return Err(Error::Other(
-                            "Expected shielding/unshielding transaction"
-                                .to_string(),
-                        ));
-                    }
-                    (_, 0) => {
-                        // Shielding, only if we are syncing. If in
-                        // speculative context do not update
-                        if let ContextSyncStatus::Confirmed = self.sync_status {
-                            let addresses = balance_keys
-                                .iter()
-                                .find(|addresses| {
-                                    if addresses[1] != &MASP {
-                                        let transp_addr_commit =
-                                            TransparentAddress(
-                                                ripemd::Ripemd160::digest(
-                                                    sha2::Sha256::digest(
-                                                        &addresses[1]
-                                                            .serialize_to_vec(),
-                                                    ),
-                                                )
-                                                .into(),
-                                            );
-                                        // Vins contain the same address, so we
-                                        // can
-                                        // just examine the first one
-                                        transp_bundle.vin.first().is_some_and(
-                                            |vin| {
-                                                vin.address
-                                                    == transp_addr_commit
-                                            },
-                                        )
-                                    } else {
-                                        false
-                                    }
-                                })
-                                .ok_or_else(|| {
-                                    Error::Other(
-                                        "Could not find source of MASP tx"
-                                            .to_string(),
+        let (source, token, amount) = match (
+            shielded.transparent_inputs.len(),
+            shielded.transparent_outputs.len(),
+        ) {
+            (0, 0) => {
+                // Shielded transfer
+                (MASP, native_token, Amount::zero())
+            }
+            (_, 0) => {
+                // Shielding, only if we are syncing. If in
+                // speculative context do not update
+                if let ContextSyncStatus::Confirmed = self.sync_status {
+                    let addresses = balance_keys
+                        .iter()
+                        .find(|addresses| {
+                            if addresses[1] != &MASP {
+                                let transp_addr_commit = TransparentAddress(
+                                    ripemd::Ripemd160::digest(
+                                        sha2::Sha256::digest(
+                                            &addresses[1].serialize_to_vec(),
+                                        ),
                                     )
-                                })?;
+                                    .into(),
+                                );
+                                // Vins contain the same address, so we
+                                // can
+                                // just examine the first one
+                                shielded.transparent_inputs.first().is_some_and(
+                                    |vin| vin.address == transp_addr_commit.0,
+                                )
+                            } else {
+                                false
+                            }
+                        })
+                        .ok_or_else(|| {
+                            Error::Other(
+                                "Could not find source of MASP tx".to_string(),
+                            )
+                        })?;
 
-                            let amount = transp_bundle
-                                .vin
-                                .iter()
-                                .fold(Amount::zero(), |acc, vin| {
-                                    acc + Amount::from_u64(vin.value)
-                                });
+                    let amount = shielded
+                        .transparent_inputs
+                        .iter()
+                        .fold(Amount::zero(), |acc, vin| {
+                            acc + Amount::from_u64(vin.value)
+                        });
 
-                            (
-                                addresses[1].to_owned(),
-                                addresses[0].to_owned(),
-                                amount,
+                    (addresses[1].to_owned(), addresses[0].to_owned(), amount)
+                } else {
+                    return Ok(());
+                }
+            }
+            (0, _) => {
+                // Unshielding
+                let token = balance_keys
+                    .iter()
+                    .find(|addresses| {
+                        if addresses[1] != &MASP {
+                            let transp_addr_commit = TransparentAddress(
+                                ripemd::Ripemd160::digest(
+                                    sha2::Sha256::digest(
+                                        &addresses[1].serialize_to_vec(),
+                                    ),
+                                )
+                                .into(),
+                            );
+
+                            // Vouts contain the same address, so we
+                            // can
+                            // just examine the first one
+                            shielded.transparent_outputs.first().is_some_and(
+                                |vout| vout.address == transp_addr_commit.0,
                             )
                         } else {
-                            return Ok(());
+                            false
                         }
-                    }
-                    (0, _) => {
-                        // Unshielding
-                        let token = balance_keys
-                            .iter()
-                            .find(|addresses| {
-                                if addresses[1] != &MASP {
-                                    let transp_addr_commit = TransparentAddress(
-                                        ripemd::Ripemd160::digest(
-                                            sha2::Sha256::digest(
-                                                &addresses[1]
-                                                    .serialize_to_vec(),
-                                            ),
-                                        )
-                                        .into(),
-                                    );
-
-                                    // Vouts contain the same address, so we
-                                    // can
-                                    // just examine the first one
-                                    transp_bundle.vout.first().is_some_and(
-                                        |vout| {
-                                            vout.address == transp_addr_commit
-                                        },
-                                    )
-                                } else {
-                                    false
-                                }
-                            })
-                            .ok_or_else(|| {
-                                Error::Other(
-                                    "Could not find target of MASP tx"
-                                        .to_string(),
-                                )
-                            })?[0];
+                    })
+                    .ok_or_else(|| {
+                        Error::Other(
+                            "Could not find target of MASP tx".to_string(),
+                        )
+                    })?[0];
+
+                let amount = shielded
+                    .transparent_outputs
+                    .iter()
+                    .fold(Amount::zero(), |acc, vout| {
+                        acc + Amount::from_u64(vout.value)
+                    });
+                (MASP, token.to_owned(), amount)
+            }
+            (_, _) => {
+                return Err(Error::Other(
+                    "MASP transaction cannot contain both transparent inputs \
+                     and outputs"
+                        .to_string(),
+                ));
+            }
+        };
 
-                        let amount = transp_bundle
-                            .vout
-                            .iter()
-                            .fold(Amount::zero(), |acc, vout| {
-                                acc + Amount::from_u64(vout.value)
-                            });
-                        (MASP, token.to_owned(), amount)
-                    }
-                    (_, _) => {
-                        return Err(Error::Other(
-                            "MASP transaction cannot contain both transparent \
-                             inputs and outputs"
-                                .to_string(),
-                        ));
+        transfer_delta.insert(
+            source,
+            MaspChange {
+                asset: token,
+                change: -amount.change(),
+            },
+        );
+        let (_, _, tx_delta) =
+            self.delta_map.entry(indexed_tx).or_insert_with(|| {
+                (epoch, transfer_delta, TransactionDelta::new())
+            });
+        // The transaction can be scanned by several viewing keys, each one
+        // records its own changes
+        if let Some(balance) = transaction_delta.remove(vk) {
+            tx_delta.insert(*vk, balance);
+        }
+        Ok(())
+    }
+
+    /// Summarize the effects on shielded and transparent accounts of each
+    /// Transfer in this context
+    pub fn get_tx_deltas(
+        &self,
+    ) -> &BTreeMap<IndexedTx, (Epoch, TransferDelta, TransactionDelta)> {
+        &self.delta_map
+    }
+
+    /// Build the chronological history of the notes received and spent by
+    /// each of the given viewing keys from the synced context. The hashes of
+    /// the transactions are looked up in the events of their blocks.
+    pub async fn query_history<C: Client + Sync, IO: Io>(
+        &mut self,
+        client: &C,
+        io: &IO,
+        vks: &[ViewingKey],
+    ) -> Result<ShieldedHistory, Error> {
+        // Map the position of the first note of each transaction back to it
+        let note_txs: BTreeMap<usize, IndexedTx> = self
+            .tx_note_map
+            .iter()
+            .map(|(indexed_tx, note_pos)| (*note_pos, *indexed_tx))
+            .collect();
+        let mut tx_hashes: HashMap<BlockHeight, HashMap<TxIndex, String>> =
+            HashMap::new();
+        let mut history = ShieldedHistory::new();
+        for vk in vks {
+            let mut entries = vec![];
+            for TxNotesHistory {
+                indexed_tx,
+                epoch,
+                source,
+                received,
+                spent,
+                recipient,
+            } in self.notes_history(&note_txs, vk)?
+            {
+                if !tx_hashes.contains_key(&indexed_tx.height) {
+                    let hashes =
+                        query_masp_tx_hashes(client, indexed_tx.height).await?;
+                    tx_hashes.insert(indexed_tx.height, hashes);
+                }
+                let tx_hash = tx_hashes[&indexed_tx.height]
+                    .get(&indexed_tx.index)
+                    .cloned();
+                let entry = |kind, counterparty, asset_type, token, amount| {
+                    ShieldedHistoryEntry {
+                        indexed_tx,
+                        epoch,
+                        tx_hash: tx_hash.clone(),
+                        kind,
+                        counterparty,
+                        asset_type,
+                        token,
+                        amount,
+                        memo: None,
                     }
+                };
+
+                for note_pos in received {
+                    let note = self.note_map[&note_pos];
+                    let (token, amount) = self
+                        .decode_value(
+                            client,
+                            note.asset_type,
+                            note.value.into(),
+                        )
+                        .await;
+                    entries.push(ShieldedHistoryEntry {
+                        memo: self.note_memo(&note_pos),
+                        ..entry(
+                            ShieldedHistoryKind::Incoming,
+                            source.clone(),
+                            Some(note.asset_type),
+                            token,
+                            amount,
+                        )
+                    });
+                }
+
+                if spent.is_zero() {
+                    continue;
+                }
+                for (asset_type, value) in spent.components() {
+                    let Ok(value) = u128::try_from(*value) else {
+                        continue;
+                    };
+                    let (token, amount) =
+                        self.decode_value(client, *asset_type, value).await;
+                    entries.push(entry(
+                        ShieldedHistoryKind::Outgoing,
+                        recipient.clone(),
+                        Some(*asset_type),
+                        token,
+                        amount,
+                    ));
+                }
+                // Realize the rewards of the spent notes at the epoch of the
+                // transaction
+                let exchanged = self
+                    .compute_exchanged_amount(
+                        client,
+                        io,
+                        spent.clone(),
+                        epoch,
+                        Conversions::new(),
+                    )
+                    .await?
+                    .0;
+                let (exchanged, _) = self
+                    .decode_combine_sum_to_epoch(client, exchanged, epoch)
+                    .await;
+                let (spent, _) = self
+                    .decode_combine_sum_to_epoch(client, spent, epoch)
+                    .await;
+                for (token, reward) in conversion_rewards(&exchanged, &spent) {
+                    entries.push(entry(
+                        ShieldedHistoryKind::Conversion,
+                        None,
+                        None,
+                        Some(token),
+                        reward,
+                    ));
                 }
             }
-            None => {
-                // Shielded transfer
-                (MASP, native_token, Amount::zero())
+            history.insert(*vk, entries);
+        }
+        Ok(history)
+    }
+
+    /// Collect the notes received and the assets spent by the given viewing
+    /// key in each of the synced transactions that affected it, in
+    /// chronological order. The given map associates the position of the
+    /// first note of each transaction to it.
+    fn notes_history(
+        &self,
+        note_txs: &BTreeMap<usize, IndexedTx>,
+        vk: &ViewingKey,
+    ) -> Result<Vec<TxNotesHistory>, Error> {
+        // The notes received by the viewing key in each transaction
+        let mut received: BTreeMap<IndexedTx, Vec<usize>> = BTreeMap::new();
+        for note_pos in self.pos_map.get(vk).into_iter().flatten() {
+            if let Some((_, indexed_tx)) =
+                note_txs.range(..=*note_pos).next_back()
+            {
+                received.entry(*indexed_tx).or_default().push(*note_pos);
             }
-        };
+        }
+        let txs: BTreeSet<IndexedTx> = self
+            .delta_map
+            .iter()
+            .filter(|(_, (_, _, tx_delta))| tx_delta.contains_key(vk))
+            .map(|(indexed_tx, _)| *indexed_tx)
+            .chain(received.keys().copied())
+            .collect();
 
-        transfer_delta.insert(
-            source,
-            MaspChange {
-                asset: token,
-                change: -amount.change(),
-            },
-        );
-        self.delta_map
-            .insert(indexed_tx, (epoch, transfer_delta, transaction_delta));
-        Ok(())
+        let mut history = vec![];
+        for indexed_tx in txs {
+            let Some((epoch, transfer_delta, tx_delta)) =
+                self.delta_map.get(&indexed_tx)
+            else {
+                continue;
+            };
+            // The source of a shielding transfer
+            let source = transfer_delta
+                .keys()
+                .find(|address| **address != MASP)
+                .cloned()
+                .map(TransferTarget::Address);
+            let received = received.remove(&indexed_tx).unwrap_or_default();
+            let mut received_sum = I128Sum::zero();
+            for note_pos in &received {
+                let note = self.note_map[note_pos];
+                received_sum += I128Sum::from_nonnegative(
+                    note.asset_type,
+                    note.value as i128,
+                )
+                .map_err(|()| {
+                    Error::Other(
+                        "found note with invalid value or asset type"
+                            .to_string(),
+                    )
+                })?;
+            }
+            // The notes received back by the viewing key are deducted from
+            // its balance change to get the assets it spent
+            let mut spent = received_sum;
+            if let Some(balance) = tx_delta.get(vk) {
+                spent -= balance.clone();
+            }
+            let recipient = if spent.is_zero() {
+                None
+            } else {
+                self.tx_recipient(note_txs, &indexed_tx, vk)
+            };
+            history.push(TxNotesHistory {
+                indexed_tx,
+                epoch: *epoch,
+                source,
+                received,
+                spent,
+                recipient,
+            });
+        }
+        Ok(history)
     }
 
-    /// Summarize the effects on shielded and transparent accounts of each
-    /// Transfer in this context
-    pub fn get_tx_deltas(
+    /// The text memo of the note at the given position, if any
+    fn note_memo(&self, note_pos: &usize) -> Option<String> {
+        match self.memo_map.get(note_pos).map(Memo::try_from) {
+            Some(Ok(Memo::Text(text))) => Some(text.to_string()),
+            _ => None,
+        }
+    }
+
+    /// The payment address of the notes of the given transaction received by
+    /// another tracked viewing key than the given one, if there is exactly
+    /// one such address
+    fn tx_recipient(
         &self,
-    ) -> &BTreeMap<IndexedTx, (Epoch, TransferDelta, TransactionDelta)> {
-        &self.delta_map
+        note_txs: &BTreeMap<usize, IndexedTx>,
+        indexed_tx: &IndexedTx,
+        vk: &ViewingKey,
+    ) -> Option<TransferTarget> {
+        let first_note_pos = *self.tx_note_map.get(indexed_tx)?;
+        let last_note_pos = note_txs
+            .range(first_note_pos + 1..)
+            .next()
+            .map_or_else(|| self.tree.size(), |(note_pos, _)| *note_pos);
+        let recipients: HashSet<PaymentAddress> = (first_note_pos
+            ..last_note_pos)
+            .filter_map(|note_pos| {
+                let recipient_vk = self.vk_map.get(&note_pos)?;
+                if recipient_vk == vk {
+                    return None;
+                }
+                recipient_vk
+                    .to_payment_address(*self.div_map.get(&note_pos)?)
+                    .map(PaymentAddress::from)
+            })
+            .collect();
+        let mut recipients = recipients.into_iter();
+        match (recipients.next(), recipients.next()) {
+            (Some(recipient), None) => {
+                Some(TransferTarget::PaymentAddress(recipient))
+            }
+            _ => None,
+        }
+    }
+
+    /// Decode the given value of an asset type into an amount of its token.
+    /// The raw value is returned if the asset type cannot be decoded.
+    async fn decode_value<C: Client + Sync>(
+        &mut self,
+        client: &C,
+        asset_type: AssetType,
+        value: u128,
+    ) -> (Option<Address>, Amount) {
+        self.decode_asset_type(client, asset_type)
+            .await
+            .and_then(|asset| {
+                let amount =
+                    Amount::from_masp_denominated_u128(value, asset.position)?;
+                Some((Some(asset.token), amount))
+            })
+            .unwrap_or_else(|| (None, Amount::from_u128(value)))
     }
 
     /// Compute the total unspent notes associated with the viewing key in the
@@ -1708,6 +2604,49 @@ impl<U: ShieldedUtils + MaybeSend + MaybeSync> ShieldedContext<U> {
         Ok((val_acc, notes, conversions))
     }
 
+    /// Plan the self-transfers merging the unspent notes of the given viewing
+    /// key that hold less than the threshold of the token, or all its notes of
+    /// the token without a threshold. The notes are grouped by asset type,
+    /// smallest first, into transfers within the given limits of descriptions
+    /// and gas. Asset types with a single such note are left out of the
+    /// plan, as spending it alone wouldn't reduce the number of notes.
+    pub async fn plan_note_consolidation<C: Client + Sync>(
+        &mut self,
+        client: &C,
+        vk: &ViewingKey,
+        token: &Address,
+        threshold: Option<Amount>,
+        max_descriptions: usize,
+        gas_limit: u64,
+    ) -> Result<Vec<ConsolidationStep>, Error> {
+        // Select the dust notes among the notes that can be spent by this key
+        let mut dust = Vec::new();
+        let avail_notes = self.pos_map.get(vk).cloned().unwrap_or_default();
+        for note_idx in avail_notes {
+            // Spent notes cannot be merged
+            if self.spents.contains(&note_idx) {
+                continue;
+            }
+            let note = *self.note_map.get(&note_idx).ok_or_else(|| {
+                Error::Other(format!("Unable to get note {note_idx}"))
+            })?;
+            let Some(decoded) =
+                self.decode_asset_type(client, note.asset_type).await
+            else {
+                continue;
+            };
+            if decoded.token != *token {
+                continue;
+            }
+            let amount =
+                Amount::from_masp_denominated(note.value, decoded.position);
+            if threshold.map_or(true, |threshold| amount < threshold) {
+                dust.push((note_idx, note.asset_type, note.value, amount));
+            }
+        }
+        pack_consolidation_steps(dust, max_descriptions, gas_limit)
+    }
+
     /// Compute the combined value of the output notes of the transaction pinned
     /// at the given payment address. This computation uses the supplied viewing
     /// keys to try to decrypt the output notes. If no transaction is pinned at
@@ -1945,12 +2884,6 @@ impl<U: ShieldedUtils + MaybeSend + MaybeSync> ShieldedContext<U> {
         token: &Address,
         amount: token::DenominatedAmount,
     ) -> Result<Option<ShieldedTransfer>, TransferErr> {
-        // No shielded components are needed when neither source nor destination
-        // are shielded
-
-        use rand::rngs::StdRng;
-        use rand_core::SeedableRng;
-
         let spending_key = source.spending_key();
         let payment_address = target.payment_address();
         // No shielded components are needed when neither source nor
@@ -1972,64 +2905,10 @@ impl<U: ShieldedUtils + MaybeSend + MaybeSync> ShieldedContext<U> {
         // possession
         let memo = MemoBytes::empty();
 
-        // Try to get a seed from env var, if any.
-        let rng = StdRng::from_rng(OsRng).unwrap();
-        #[cfg(feature = "testing")]
-        let rng = if let Ok(seed) = env::var(ENV_VAR_MASP_TEST_SEED)
-            .map_err(|e| Error::Other(e.to_string()))
-            .and_then(|seed| {
-                let exp_str =
-                    format!("Env var {ENV_VAR_MASP_TEST_SEED} must be a u64.");
-                let parsed_seed: u64 = FromStr::from_str(&seed)
-                    .map_err(|_| Error::Other(exp_str))?;
-                Ok(parsed_seed)
-            }) {
-            tracing::warn!(
-                "UNSAFE: Using a seed from {ENV_VAR_MASP_TEST_SEED} env var \
-                 to build proofs."
-            );
-            StdRng::seed_from_u64(seed)
-        } else {
-            rng
-        };
+        let rng = masp_tx_rng();
 
         // Now we build up the transaction within this object
-        let expiration_height: u32 = match context.tx_builder().expiration {
-            Some(expiration) => {
-                // Try to match a DateTime expiration with a plausible
-                // corresponding block height
-                let last_block_height: u64 =
-                    crate::rpc::query_block(context.client())
-                        .await?
-                        .map_or_else(|| 1, |block| u64::from(block.height));
-                let current_time = DateTimeUtc::now();
-                let delta_time =
-                    expiration.0.signed_duration_since(current_time.0);
-
-                let max_expected_time_per_block_key =
-                    namada_parameters::storage::get_max_expected_time_per_block_key();
-                let max_block_time =
-                    crate::rpc::query_storage_value::<_, DurationSecs>(
-                        context.client(),
-                        &max_expected_time_per_block_key,
-                    )
-                    .await?;
-
-                let delta_blocks = u32::try_from(
-                    delta_time.num_seconds() / max_block_time.0 as i64,
-                )
-                .map_err(|e| Error::Other(e.to_string()))?;
-                u32::try_from(last_block_height)
-                    .map_err(|e| Error::Other(e.to_string()))?
-                    + delta_blocks
-            }
-            None => {
-                // NOTE: The masp library doesn't support optional expiration so
-                // we set the max to mimic a never-expiring tx. We also need to
-                // remove 20 which is going to be added back by the builder
-                u32::MAX - 20
-            }
-        };
+        let expiration_height = masp_expiration_height(context).await?;
         let mut builder = Builder::<TestNetwork, _>::new_with_rng(
             NETWORK,
             // NOTE: this is going to add 20 more blocks to the actual
@@ -2307,6 +3186,108 @@ impl<U: ShieldedUtils + MaybeSend + MaybeSync> ShieldedContext<U> {
         }))
     }
 
+    /// Make the shielded transfer of a note consolidation step, spending its
+    /// notes with the given spending key and sending their merged value of
+    /// each asset type back to the default payment address of the key
+    pub async fn gen_note_consolidation(
+        context: &impl Namada,
+        spending_key: &namada_core::masp::ExtendedSpendingKey,
+        step: &ConsolidationStep,
+    ) -> Result<ShieldedTransfer, TransferErr> {
+        let sk = ExtendedSpendingKey::from(*spending_key);
+        let vk = to_viewing_key(&sk).vk;
+        let payment_address = sk.default_address().1;
+        // Determine epoch in which to submit the shielded transaction
+        let epoch = rpc::query_epoch(context.client()).await?;
+        let mut builder = Builder::<TestNetwork, _>::new_with_rng(
+            NETWORK,
+            masp_expiration_height(context).await?.into(),
+            masp_tx_rng(),
+        );
+
+        {
+            let mut shielded = context.shielded_mut().await;
+            let _ = shielded.load().await;
+            for note_idx in &step.notes {
+                // Only the unspent notes of the spending key can be merged
+                let is_owned =
+                    shielded.pos_map.get(&vk).map_or(false, |avail_notes| {
+                        avail_notes.contains(note_idx)
+                    });
+                if !is_owned || shielded.spents.contains(note_idx) {
+                    return Err(TransferErr::General(Error::Other(format!(
+                        "Note {note_idx} is not an unspent note of the \
+                         spending key"
+                    ))));
+                }
+                let note =
+                    *shielded.note_map.get(note_idx).ok_or_else(|| {
+                        Error::Other(format!("Unable to get note {note_idx}"))
+                    })?;
+                let merkle_path = shielded
+                    .witness_map
+                    .get(note_idx)
+                    .ok_or_else(|| {
+                        Error::Other(format!("Unable to get note {note_idx}"))
+                    })?
+                    .path()
+                    .ok_or_else(|| {
+                        Error::Other(format!("Unable to get path: {}", line!()))
+                    })?;
+                let diversifier =
+                    *shielded.div_map.get(note_idx).ok_or_else(|| {
+                        Error::Other(format!("Unable to get note {note_idx}"))
+                    })?;
+                builder
+                    .add_sapling_spend(sk, diversifier, note, merkle_path)
+                    .map_err(builder::Error::SaplingBuild)?;
+            }
+        }
+        // Produce a single note per asset type
+        for (asset_type, value) in &step.outputs {
+            builder
+                .add_sapling_output(
+                    Some(sk.expsk.ovk),
+                    payment_address,
+                    *asset_type,
+                    *value,
+                    MemoBytes::empty(),
+                )
+                .map_err(builder::Error::SaplingBuild)?;
+        }
+
+        let builder_clone = builder.clone().map_builder(WalletMap);
+        // Build and return the constructed transaction
+        #[cfg(not(feature = "testing"))]
+        let prover = context.shielded().await.utils.local_tx_prover();
+        #[cfg(feature = "testing")]
+        let prover = testing::MockTxProver(std::sync::Mutex::new(OsRng));
+        let (masp_tx, metadata) =
+            builder.build(&prover, &FeeRule::non_standard(U64Sum::zero()))?;
+
+        // Invalidate the merged notes until the next sync
+        let native_token = query_native_token(context.client()).await?;
+        context
+            .shielded_mut()
+            .await
+            .pre_cache_transaction(
+                context,
+                &masp_tx,
+                &TransferSource::ExtendedSpendingKey(*spending_key),
+                &TransferTarget::PaymentAddress(payment_address.into()),
+                &native_token,
+                epoch,
+            )
+            .await?;
+
+        Ok(ShieldedTransfer {
+            builder: builder_clone,
+            masp_tx,
+            metadata,
+            epoch,
+        })
+    }
+
     // Updates the internal state with the data of the newly generated
     // transaction. More specifically invalidate the spent notes and the
     // transparent balances, but do not cache the newly produced output
@@ -2393,8 +3374,17 @@ impl<U: ShieldedUtils + MaybeSend + MaybeSync> ShieldedContext<U> {
             .values()
             .map(|fvk| ExtendedFullViewingKey::from(*fvk).fvk.vk)
             .collect();
-        self.fetch(client, &DefaultLogger::new(io), None, 1, &[], &fvks)
-            .await?;
+        self.fetch(
+            client,
+            &DefaultLogger::new(io),
+            None,
+            DEFAULT_SYNC_BATCH_SIZE,
+            DEFAULT_MAX_CONCURRENT_FETCHES,
+            &[],
+            &fvks,
+            &BTreeMap::new(),
+        )
+        .await?;
         // Save the update state so that future fetches can be short-circuited
         let _ = self.save().await;
         // Required for filtering out rejected transactions from Tendermint
@@ -2554,6 +3544,73 @@ impl<U: ShieldedUtils + MaybeSend + MaybeSync> ShieldedContext<U> {
     }
 }
 
+/// Make the RNG used to build the proofs of a MASP transaction
+fn masp_tx_rng() -> rand::rngs::StdRng {
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    // Try to get a seed from env var, if any.
+    #[cfg(feature = "testing")]
+    if let Ok(seed) = env::var(ENV_VAR_MASP_TEST_SEED)
+        .map_err(|e| Error::Other(e.to_string()))
+        .and_then(|seed| {
+            let exp_str =
+                format!("Env var {ENV_VAR_MASP_TEST_SEED} must be a u64.");
+            let parsed_seed: u64 =
+                FromStr::from_str(&seed).map_err(|_| Error::Other(exp_str))?;
+            Ok(parsed_seed)
+        })
+    {
+        tracing::warn!(
+            "UNSAFE: Using a seed from {ENV_VAR_MASP_TEST_SEED} env var to \
+             build proofs."
+        );
+        return StdRng::seed_from_u64(seed);
+    }
+    StdRng::from_rng(OsRng).unwrap()
+}
+
+/// The expiration height of a MASP transaction, matching the expiration of
+/// the transaction builder
+async fn masp_expiration_height(context: &impl Namada) -> Result<u32, Error> {
+    Ok(match context.tx_builder().expiration {
+        Some(expiration) => {
+            // Try to match a DateTime expiration with a plausible
+            // corresponding block height
+            let last_block_height: u64 =
+                crate::rpc::query_block(context.client())
+                    .await?
+                    .map_or_else(|| 1, |block| u64::from(block.height));
+            let current_time = DateTimeUtc::now();
+            let delta_time = expiration.0.signed_duration_since(current_time.0);
+
+            let max_expected_time_per_block_key =
+                namada_parameters::storage::get_max_expected_time_per_block_key(
+                );
+            let max_block_time =
+                crate::rpc::query_storage_value::<_, DurationSecs>(
+                    context.client(),
+                    &max_expected_time_per_block_key,
+                )
+                .await?;
+
+            let delta_blocks = u32::try_from(
+                delta_time.num_seconds() / max_block_time.0 as i64,
+            )
+            .map_err(|e| Error::Other(e.to_string()))?;
+            u32::try_from(last_block_height)
+                .map_err(|e| Error::Other(e.to_string()))?
+                + delta_blocks
+        }
+        None => {
+            // NOTE: The masp library doesn't support optional expiration so
+            // we set the max to mimic a never-expiring tx. We also need to
+            // remove 20 which is going to be added back by the builder
+            u32::MAX - 20
+        }
+    })
+}
+
 /// Extract the payload from the given Tx object
 fn extract_payload(
     tx: Tx,
@@ -2612,6 +3669,26 @@ async fn get_indexed_masp_events_at_height<C: Client + Sync>(
         }))
 }
 
+// Retrieves the hashes of the valid masp transactions at the specified height
+// by their index
+async fn query_masp_tx_hashes<C: Client + Sync>(
+    client: &C,
+    height: BlockHeight,
+) -> Result<HashMap<TxIndex, String>, Error> {
+    Ok(get_indexed_masp_events_at_height(client, height, None)
+        .await?
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(idx, event)| {
+            event
+                .attributes
+                .into_iter()
+                .find(|attribute| attribute.key == "hash")
+                .map(|attribute| (idx, attribute.value))
+        })
+        .collect())
+}
+
 enum ExtractShieldedActionArg<'args, C: Client + Sync> {
     Event(&'args crate::tendermint::abci::Event),
     Request((&'args C, BlockHeight, Option<TxIndex>)),
@@ -2842,6 +3919,385 @@ mod tests {
             &fake_params_paths[2].0,
         );
     }
+
+    /// Test that the rewards of the conversions in the shielded history are
+    /// the exchanged amounts in excess of the spent ones
+    #[test]
+    fn test_conversion_rewards() {
+        use masp_primitives::transaction::components::ValueSum;
+        use namada_core::address::testing::{
+            established_address_1, established_address_2, nam,
+        };
+        use namada_core::address::Address;
+        use namada_core::token::Amount;
+
+        use super::conversion_rewards;
+
+        let sum = |amounts: &[(Address, u64)]| {
+            let mut sum = ValueSum::zero();
+            for (token, amount) in amounts {
+                sum += ValueSum::from_pair(
+                    token.clone(),
+                    Amount::from_u64(*amount).change(),
+                )
+                .unwrap();
+            }
+            sum
+        };
+        let exchanged = sum(&[
+            (nam(), 15),
+            (established_address_1(), 5),
+            (established_address_2(), 3),
+        ]);
+        let spent = sum(&[
+            (nam(), 10),
+            (established_address_1(), 5),
+            (established_address_2(), 4),
+        ]);
+        // Only the tokens whose exchanged amount exceeds the spent one
+        // yield a reward
+        assert_eq!(
+            conversion_rewards(&exchanged, &spent),
+            vec![(nam(), Amount::from_u64(5))]
+        );
+        assert!(conversion_rewards(&spent, &spent).is_empty());
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::test_runner::Config::with_cases(8))]
+
+        /// Test that the shielded history of a viewing key records the notes
+        /// it received, with the source of the transfer and their memo, and
+        /// the assets it spent, with their recipient
+        #[test]
+        fn test_notes_history(
+            (esk, _div, note, node) in super::testing::arb_spend_description(
+                masp_primitives::asset_type::AssetType::new(b"token").unwrap(),
+                10,
+            ),
+            (other_esk, other_div, other_note, other_node) in
+                super::testing::arb_spend_description(
+                    masp_primitives::asset_type::AssetType::new(b"token")
+                        .unwrap(),
+                    10,
+                ),
+        ) {
+            use std::collections::{BTreeMap, BTreeSet, HashMap};
+            use std::str::FromStr;
+
+            use masp_primitives::memo::{Memo, MemoBytes};
+            use masp_primitives::transaction::components::I128Sum;
+            use masp_primitives::zip32::ExtendedFullViewingKey;
+            use namada_core::address::testing::established_address_1;
+            use namada_core::address::{Address, MASP};
+            use namada_core::masp::{PaymentAddress, TransferTarget};
+            use namada_core::storage::{BlockHeight, Epoch, IndexedTx, TxIndex};
+
+            use super::fs::FsShieldedUtils;
+            use super::{ShieldedContext, TxNotesHistory};
+
+            let vk = ExtendedFullViewingKey::from(&esk).fvk.vk;
+            let other_vk = ExtendedFullViewingKey::from(&other_esk).fvk.vk;
+            let source = established_address_1();
+            let value = |amount: i128| {
+                I128Sum::from_pair(note.asset_type, amount).unwrap()
+            };
+            // A shielding transfer to the viewing key, followed by a transfer
+            // from the viewing key to the other one
+            let shielding = IndexedTx {
+                height: BlockHeight(1),
+                index: TxIndex(0),
+            };
+            let transfer = IndexedTx {
+                height: BlockHeight(2),
+                index: TxIndex(0),
+            };
+            let mut ctx = ShieldedContext::<FsShieldedUtils>::default();
+            ctx.tree.append(node).unwrap();
+            ctx.tree.append(other_node).unwrap();
+            ctx.tx_note_map = BTreeMap::from([(shielding, 0), (transfer, 1)]);
+            ctx.note_map = HashMap::from([(0, note), (1, other_note)]);
+            ctx.vk_map = HashMap::from([(0, vk), (1, other_vk)]);
+            ctx.div_map = HashMap::from([(1, other_div)]);
+            ctx.pos_map = HashMap::from([
+                (vk, BTreeSet::from([0])),
+                (other_vk, BTreeSet::from([1])),
+            ]);
+            let memo = Memo::from_str("shielded").unwrap();
+            ctx.memo_map = HashMap::from([(0, MemoBytes::from(&memo))]);
+            let change = |address: &Address| super::MaspChange {
+                asset: address.clone(),
+                change: Default::default(),
+            };
+            ctx.delta_map = BTreeMap::from([
+                (
+                    shielding,
+                    (
+                        Epoch(1),
+                        HashMap::from([
+                            (source.clone(), change(&source)),
+                            (MASP, change(&MASP)),
+                        ]),
+                        HashMap::from([(vk, value(10))]),
+                    ),
+                ),
+                (
+                    transfer,
+                    (
+                        Epoch(1),
+                        HashMap::new(),
+                        HashMap::from([
+                            (vk, value(-10)),
+                            (other_vk, value(10)),
+                        ]),
+                    ),
+                ),
+            ]);
+            let note_txs = BTreeMap::from([(0, shielding), (1, transfer)]);
+
+            let history = ctx.notes_history(&note_txs, &vk).unwrap();
+            assert_eq!(history.len(), 2);
+            // The incoming note of the shielding transfer
+            let TxNotesHistory {
+                indexed_tx,
+                source: incoming_source,
+                received,
+                spent,
+                recipient,
+                ..
+            } = &history[0];
+            assert_eq!(*indexed_tx, shielding);
+            assert_eq!(
+                incoming_source.as_ref().map(ToString::to_string),
+                Some(TransferTarget::Address(source.clone()).to_string())
+            );
+            assert_eq!(*received, vec![0]);
+            assert!(spent.is_zero());
+            assert!(recipient.is_none());
+            assert_eq!(ctx.note_memo(&0), Some("shielded".to_string()));
+            // The outgoing assets of the transfer
+            let TxNotesHistory {
+                indexed_tx,
+                source: outgoing_source,
+                received,
+                spent,
+                recipient,
+                ..
+            } = &history[1];
+            assert_eq!(*indexed_tx, transfer);
+            assert!(outgoing_source.is_none());
+            assert!(received.is_empty());
+            assert_eq!(*spent, value(10));
+            let other_pa = other_vk.to_payment_address(other_div).unwrap();
+            assert_eq!(
+                recipient.as_ref().map(ToString::to_string),
+                Some(
+                    TransferTarget::PaymentAddress(PaymentAddress::from(
+                        other_pa
+                    ))
+                    .to_string()
+                )
+            );
+
+            // The recipient only sees the incoming note
+            let history = ctx.notes_history(&note_txs, &other_vk).unwrap();
+            assert_eq!(history.len(), 1);
+            assert_eq!(history[0].indexed_tx, transfer);
+            assert_eq!(history[0].received, vec![1]);
+            assert!(history[0].spent.is_zero());
+            assert_eq!(ctx.note_memo(&1), None);
+        }
+    }
+
+    /// Test that the in-process scanning service serves the indexed shielded
+    /// transactions by ranges of block heights
+    #[test]
+    fn test_local_scanning_service() {
+        use std::collections::BTreeSet;
+
+        use masp_primitives::consensus::BranchId;
+        use masp_primitives::transaction::{TransactionData, TxVersion};
+        use namada_core::storage::{BlockHeight, Epoch, IndexedTx, TxIndex};
+
+        use super::{CompactTx, IndexedNoteData, LocalScanningService};
+
+        let tx = TransactionData::from_parts(
+            TxVersion::MASPv5,
+            BranchId::MASP,
+            0,
+            masp_primitives::consensus::BlockHeight::from_u32(0),
+            None,
+            None,
+        )
+        .freeze()
+        .expect("expected a valid transaction");
+        let tx = CompactTx::from(&tx);
+        let indexed_tx = |height: u64, index: u32| IndexedTx {
+            height: BlockHeight(height),
+            index: TxIndex(index),
+        };
+        let txs: IndexedNoteData = [(1, 0), (1, 1), (3, 0), (5, 2)]
+            .into_iter()
+            .map(|(height, index)| {
+                (
+                    indexed_tx(height, index),
+                    (Epoch(0), BTreeSet::new(), tx.clone()),
+                )
+            })
+            .collect();
+
+        let service = LocalScanningService::default();
+        assert_eq!(service.indexed_height(), None);
+        service.index(BlockHeight(5), txs);
+        service.index(BlockHeight(7), IndexedNoteData::new());
+        assert_eq!(service.indexed_height(), Some(BlockHeight(7)));
+
+        let range = service.txs_between(BlockHeight(1), BlockHeight(3));
+        assert_eq!(
+            range.keys().copied().collect::<Vec<_>>(),
+            vec![indexed_tx(1, 0), indexed_tx(1, 1), indexed_tx(3, 0)]
+        );
+        let range = service.txs_between(BlockHeight(5), BlockHeight(5));
+        assert_eq!(
+            range.keys().copied().collect::<Vec<_>>(),
+            vec![indexed_tx(5, 2)]
+        );
+        for (from, to) in [(2, 2), (6, 7), (4, 1)] {
+            assert!(service
+                .txs_between(BlockHeight(from), BlockHeight(to))
+                .is_empty());
+        }
+    }
+
+    /// Test that the consolidation plan leaves out the asset types with a
+    /// single dust note and merges at least two notes of each asset type it
+    /// includes
+    #[test]
+    fn test_consolidation_skips_single_notes() {
+        use masp_primitives::asset_type::AssetType;
+        use namada_core::token::Amount;
+
+        use super::pack_consolidation_steps;
+
+        let asset_a = AssetType::new(b"a").unwrap();
+        let asset_b = AssetType::new(b"b").unwrap();
+        let asset_c = AssetType::new(b"c").unwrap();
+        let dust = vec![
+            (0, asset_a, 3, Amount::from_u64(3)),
+            (1, asset_b, 1, Amount::from_u64(1)),
+            (2, asset_a, 1, Amount::from_u64(1)),
+            (3, asset_c, 2, Amount::from_u64(2)),
+            (4, asset_a, 2, Amount::from_u64(2)),
+        ];
+        let steps = pack_consolidation_steps(dust, 10, u64::MAX).unwrap();
+        assert_eq!(steps.len(), 1);
+        // The notes of the asset type are merged smallest first
+        assert_eq!(steps[0].notes, vec![2, 4, 0]);
+        assert_eq!(steps[0].outputs.len(), 1);
+        assert_eq!(steps[0].outputs.get(&asset_a), Some(&6));
+        assert_eq!(steps[0].amount, Amount::from_u64(6));
+        assert_eq!(steps[0].merged_notes(), 2);
+
+        // Nothing to merge without two notes of an asset type
+        let dust = vec![
+            (0, asset_a, 1, Amount::from_u64(1)),
+            (1, asset_b, 1, Amount::from_u64(1)),
+        ];
+        assert!(pack_consolidation_steps(dust, 10, u64::MAX)
+            .unwrap()
+            .is_empty());
+    }
+
+    /// Test that the consolidation steps fit in the limits of descriptions
+    /// and gas, without spending a single note of an asset type in a step
+    #[test]
+    fn test_consolidation_limits() {
+        use masp_primitives::asset_type::AssetType;
+        use namada_core::token::Amount;
+
+        use super::{estimate_consolidation_gas, pack_consolidation_steps};
+
+        let asset_a = AssetType::new(b"a").unwrap();
+        let asset_b = AssetType::new(b"b").unwrap();
+        let dust = |notes: &[(AssetType, usize)]| {
+            let mut dust = vec![];
+            for (asset_type, count) in notes {
+                for _ in 0..*count {
+                    dust.push((
+                        dust.len(),
+                        *asset_type,
+                        1,
+                        Amount::from_u64(1),
+                    ));
+                }
+            }
+            dust
+        };
+        let check = |steps: &[super::ConsolidationStep],
+                     dust: &[(usize, AssetType, u64, Amount)],
+                     max_descriptions: usize,
+                     gas_limit: u64| {
+            for step in steps {
+                assert!(step.descriptions() <= max_descriptions);
+                assert!(step.estimated_gas() <= gas_limit);
+                assert!(step.merged_notes() > 0);
+                for (asset_type, value) in &step.outputs {
+                    let spent = step
+                        .notes
+                        .iter()
+                        .filter(|idx| dust[**idx].1 == *asset_type)
+                        .count();
+                    assert!(spent >= 2);
+                    assert_eq!(*value, spent as u64);
+                }
+            }
+        };
+
+        // Three spends and one output per step, the last note is left alone
+        let notes = dust(&[(asset_a, 7)]);
+        let steps =
+            pack_consolidation_steps(notes.clone(), 4, u64::MAX).unwrap();
+        check(&steps, &notes, 4, u64::MAX);
+        assert_eq!(
+            steps
+                .iter()
+                .map(|step| step.notes.len())
+                .collect::<Vec<_>>(),
+            vec![3, 3]
+        );
+
+        // A single note of an asset type that doesn't fit in a step is carried
+        // over to the next one
+        let notes = dust(&[(asset_a, 2), (asset_b, 3)]);
+        let steps =
+            pack_consolidation_steps(notes.clone(), 5, u64::MAX).unwrap();
+        check(&steps, &notes, 5, u64::MAX);
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps.iter().map(|step| step.notes.len()).sum::<usize>(), 5);
+
+        // The gas limit bounds the spends of each step
+        let gas_limit = estimate_consolidation_gas(3, 1);
+        let notes = dust(&[(asset_a, 7)]);
+        let steps =
+            pack_consolidation_steps(notes.clone(), 100, gas_limit).unwrap();
+        check(&steps, &notes, 100, gas_limit);
+        assert_eq!(
+            steps
+                .iter()
+                .map(|step| step.notes.len())
+                .collect::<Vec<_>>(),
+            vec![3, 3]
+        );
+
+        // Limits that can't merge two notes are rejected
+        assert!(pack_consolidation_steps(notes.clone(), 2, u64::MAX).is_err());
+        assert!(pack_consolidation_steps(
+            notes,
+            100,
+            estimate_consolidation_gas(2, 1) - 1
+        )
+        .is_err());
+    }
 }
 
 #[cfg(any(test, feature = "testing"))]
@@ -3728,6 +5184,8 @@ pub mod fs {
     pub struct FsShieldedUtils {
         #[borsh(skip)]
         context_dir: PathBuf,
+        #[borsh(skip)]
+        scanning_service: Option<Arc<dyn ScanningService>>,
     }
 
     impl FsShieldedUtils {
@@ -3763,19 +5221,32 @@ pub mod fs {
                     ContextSyncStatus::Confirmed
                 };
 
-            let utils = Self { context_dir };
+            let utils = Self {
+                context_dir,
+                scanning_service: None,
+            };
             ShieldedContext {
                 utils,
                 sync_status,
                 ..Default::default()
             }
         }
+
+        /// Retrieve the shielded transactions from the given scanning service
+        /// instead of querying every block from the node
+        pub fn set_scanning_service(
+            &mut self,
+            service: Arc<dyn ScanningService>,
+        ) {
+            self.scanning_service = Some(service);
+        }
     }
 
     impl Default for FsShieldedUtils {
         fn default() -> Self {
             Self {
                 context_dir: PathBuf::from(FILE_NAME),
+                scanning_service: None,
             }
         }
     }
@@ -3796,6 +5267,10 @@ pub mod fs {
             }
         }
 
+        fn scanning_service(&self) -> Option<Arc<dyn ScanningService>> {
+            self.scanning_service.clone()
+        }
+
         /// Try to load the last saved shielded context from the given context
         /// directory. If this fails, then leave the current context unchanged.
         async fn load<U: ShieldedUtils + MaybeSend>(
@@ -1035,7 +1035,6 @@ mod test {
             prove: Default::default(),
         };
         let ctx = RequestCtx {
-            event_log: &client.event_log,
             state: &client.state,
             vp_wasm_cache: (),
             tx_wasm_cache: (),
@@ -1054,7 +1053,6 @@ mod test {
             prove: Default::default(),
         };
         let ctx = RequestCtx {
-            event_log: &client.event_log,
             state: &client.state,
             vp_wasm_cache: (),
             tx_wasm_cache: (),
@@ -1071,7 +1069,6 @@ mod test {
             prove: Default::default(),
         };
         let ctx = RequestCtx {
-            event_log: &client.event_log,
             state: &client.state,
             vp_wasm_cache: (),
             tx_wasm_cache: (),
@@ -15,15 +15,21 @@ use namada_core::hints;
 use namada_core::storage::{
     self, BlockHeight, BlockResults, Epoch, KeySeg, PrefixValue,
 };
-use namada_core::token::{Denomination, MaspDigitPos};
+use namada_core::token::{self, Denomination, MaspDigitPos};
 use namada_core::uint::Uint;
 use namada_state::{DBIter, LastBlock, StateRead, StorageHasher, DB};
 use namada_storage::{ResultExt, StorageRead};
+use namada_tx::data::schedule::{
+    scheduled_txs_prefix, ScheduleTarget, ScheduledTx,
+};
 #[cfg(any(test, feature = "async-client"))]
 use namada_tx::data::TxResult;
 
 use self::eth_bridge::{EthBridge, ETH_BRIDGE};
-use crate::events::log::dumb_queries;
+use crate::events::log::query::EventQuery;
+use crate::events::log::{
+    find_latest_event, query_events_page, EventsPage, MAX_EVENTS_PER_PAGE,
+};
 use crate::events::{Event, EventType};
 use crate::ibc::core::host::types::identifiers::{
     ChannelId, ClientId, PortId, Sequence,
@@ -62,6 +68,9 @@ router! {SHELL,
     // The address of the native token
     ( "native_token" ) -> Address = native_token,
 
+    // The current base fee of the given token, if enabled
+    ( "base_fee" / [token: Address] ) -> Option<token::Amount> = base_fee,
+
     // Epoch of the input block height
     ( "epoch_at_height" / [height: BlockHeight]) -> Option<Epoch> = epoch_at_height,
 
@@ -115,6 +124,18 @@ router! {SHELL,
 
     // IBC packet event
     ( "ibc_packet" / [event_type: EventType] / [source_port: PortId] / [source_channel: ChannelId] / [destination_port: PortId] / [destination_channel: ChannelId] / [sequence: Sequence]) -> Option<Event> = ibc_packet,
+
+    // Transactions scheduled for an execution at a future height or epoch
+    ( "scheduled_txs" ) = {
+        ( "height" / [height: BlockHeight] )
+            -> Vec<ScheduledTx> = scheduled_txs_at_height,
+
+        ( "epoch" / [epoch: Epoch] )
+            -> Vec<ScheduledTx> = scheduled_txs_at_epoch,
+    },
+
+    // Page of the events matching the CometBFT query in the request data
+    ( "events" / [page: u64] / [per_page: u64] ) -> EventsPage = (with_options matching_events),
 }
 
 // Handlers:
@@ -321,6 +342,17 @@ where
     Ok(data)
 }
 
+fn base_fee<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    token: Address,
+) -> namada_storage::Result<Option<token::Amount>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    namada_parameters::read_base_fee(ctx.state, &token)
+}
+
 fn epoch_at_height<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
     height: BlockHeight,
@@ -515,13 +547,13 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let matcher = dumb_queries::QueryMatcher::accepted(tx_hash);
-    Ok(ctx
-        .event_log
-        .iter_with_matcher(matcher)
-        .by_ref()
-        .next()
-        .cloned())
+    let matcher = EventQuery::accepted(tx_hash);
+    find_latest_event(
+        ctx.state.db(),
+        &matcher,
+        ctx.state.in_mem().get_last_block_height(),
+    )
+    .into_storage_result()
 }
 
 fn applied<D, H, V, T>(
@@ -532,13 +564,13 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let matcher = dumb_queries::QueryMatcher::applied(tx_hash);
-    Ok(ctx
-        .event_log
-        .iter_with_matcher(matcher)
-        .by_ref()
-        .next()
-        .cloned())
+    let matcher = EventQuery::applied(tx_hash);
+    find_latest_event(
+        ctx.state.db(),
+        &matcher,
+        ctx.state.in_mem().get_last_block_height(),
+    )
+    .into_storage_result()
 }
 
 fn ibc_client_update<D, H, V, T>(
@@ -550,16 +582,13 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let matcher = dumb_queries::QueryMatcher::ibc_update_client(
-        client_id,
-        consensus_height,
-    );
-    Ok(ctx
-        .event_log
-        .iter_with_matcher(matcher)
-        .by_ref()
-        .next()
-        .cloned())
+    let matcher = EventQuery::ibc_update_client(client_id, consensus_height);
+    find_latest_event(
+        ctx.state.db(),
+        &matcher,
+        ctx.state.in_mem().get_last_block_height(),
+    )
+    .into_storage_result()
 }
 
 fn ibc_packet<D, H, V, T>(
@@ -575,7 +604,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let matcher = dumb_queries::QueryMatcher::ibc_packet(
+    let matcher = EventQuery::ibc_packet(
         event_type,
         source_port,
         source_channel,
@@ -583,12 +612,55 @@ where
         destination_channel,
         sequence,
     );
-    Ok(ctx
-        .event_log
-        .iter_with_matcher(matcher)
-        .by_ref()
-        .next()
-        .cloned())
+    find_latest_event(
+        ctx.state.db(),
+        &matcher,
+        ctx.state.in_mem().get_last_block_height(),
+    )
+    .into_storage_result()
+}
+
+/// Query a page of the events that match the CometBFT query given in the
+/// request data, starting from the most recent ones. The pages are numbered
+/// from 1 and hold up to [`MAX_EVENTS_PER_PAGE`] events. The queries that
+/// would scan too much of the event store are refused, see
+/// [`query_events_page`].
+fn matching_events<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    request: &RequestQuery,
+    page: u64,
+    per_page: u64,
+) -> namada_storage::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    if page == 0 {
+        return Err(namada_storage::Error::new_const(
+            "The pages of events are numbered from 1",
+        ));
+    }
+    if per_page == 0 || per_page > MAX_EVENTS_PER_PAGE {
+        return Err(namada_storage::Error::new(format!(
+            "The number of events per page must be between 1 and \
+             {MAX_EVENTS_PER_PAGE}, got {per_page}"
+        )));
+    }
+    let query: String = BorshDeserialize::try_from_slice(&request.data)
+        .into_storage_result()?;
+    let query: EventQuery = query.parse().into_storage_result()?;
+    let events = query_events_page(
+        ctx.state.db(),
+        &query,
+        ctx.state.in_mem().get_last_block_height(),
+        page,
+        per_page,
+    )
+    .into_storage_result()?;
+    Ok(EncodedResponseQuery {
+        data: events.serialize_to_vec(),
+        ..Default::default()
+    })
 }
 
 fn account<D, H, V, T>(
@@ -615,6 +687,41 @@ where
     }
 }
 
+fn scheduled_txs_at_height<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    height: BlockHeight,
+) -> namada_storage::Result<Vec<ScheduledTx>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    scheduled_txs(ctx, ScheduleTarget::Height(height))
+}
+
+fn scheduled_txs_at_epoch<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    epoch: Epoch,
+) -> namada_storage::Result<Vec<ScheduledTx>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    scheduled_txs(ctx, ScheduleTarget::Epoch(epoch))
+}
+
+fn scheduled_txs<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    target: ScheduleTarget,
+) -> namada_storage::Result<Vec<ScheduledTx>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    namada_storage::iter_prefix(ctx.state, &scheduled_txs_prefix(&target))?
+        .map(|entry| entry.map(|(_key, scheduled)| scheduled))
+        .collect()
+}
+
 fn revealed<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
     owner: Address,
@@ -30,7 +30,9 @@ use namada_ethereum_bridge::storage::{
 };
 use namada_proof_of_stake::pos_queries::PosQueries;
 use namada_state::MembershipProof::BridgePool;
-use namada_state::{DBIter, StorageHasher, StoreRef, StoreType, DB};
+use namada_state::{
+    DBIter, EventIndex, StorageHasher, StoreRef, StoreType, DB,
+};
 use namada_storage::{CustomError, ResultExt, StorageRead};
 use namada_vote_ext::validator_set_update::{
     ValidatorSetArgs, VotingPowersMap,
@@ -38,6 +40,7 @@ use namada_vote_ext::validator_set_update::{
 use serde::{Deserialize, Serialize};
 
 use crate::eth_bridge::ethers::abi::AbiDecode;
+use crate::events::log::iter_event_index;
 use crate::events::EventType;
 use crate::queries::{EncodedResponseQuery, RequestCtx, RequestQuery};
 
@@ -70,7 +73,7 @@ pub struct TransferToEthereumStatus {
     /// to be `expired`.
     pub expired: HashSet<KeccakHash>,
     /// Hashes pertaining to bogus data that might have been queried,
-    /// or transfers that were not in the event log, despite having
+    /// or transfers that were not in the event store, despite having
     /// been relayed to Ethereum or expiring from the Bridge pool.
     pub unrecognized: HashSet<KeccakHash>,
 }
@@ -274,43 +277,46 @@ where
         });
     }
 
-    // INVARIANT: transfers that are in the event log will have already
+    // INVARIANT: transfers that are in the event store will have already
     // been processed and therefore removed from the Bridge pool at the
     // time of this query
     let kind_key: String = "kind".into();
-    let completed_transfers = ctx.event_log.iter().filter_map(|ev| {
-        if !matches!(&ev.event_type, EventType::EthereumBridge) {
-            return None;
-        }
-        let eth_event_kind =
-            ev.attributes.get(&kind_key).map(|k| k.as_str())?;
-        let is_relayed = match eth_event_kind {
-            "bridge_pool_relayed" => true,
-            "bridge_pool_expired" => false,
-            _ => return None,
+    let remaining_hashes: Vec<_> = transfer_hashes.iter().cloned().collect();
+    for hash in remaining_hashes {
+        let index = EventIndex::Attribute {
+            key: "tx_hash".to_string(),
+            value: hash.to_string(),
         };
-        let tx_hash: KeccakHash = ev
-            .attributes
-            .get("tx_hash")
-            .expect("The transfer hash must be available")
-            .as_str()
-            .try_into()
-            .expect("We must have a valid KeccakHash");
-        if !transfer_hashes.remove(&tx_hash) {
-            return None;
-        }
-        Some((tx_hash, is_relayed, transfer_hashes.is_empty()))
-    });
-    for (hash, is_relayed, early_exit) in completed_transfers {
-        if hints::likely(is_relayed) {
-            status.relayed.insert(hash.clone());
-        } else {
-            status.expired.insert(hash.clone());
-        }
-        if early_exit {
-            // early drop of the transfer hashes, in
-            // case its storage capacity was big
-            transfer_hashes = Default::default();
+        for found in iter_event_index(
+            ctx.state.db(),
+            index,
+            BlockHeight(0),
+            ctx.state.in_mem().get_last_block_height(),
+        ) {
+            let (height, position) = found.into_storage_result()?;
+            let Some(ev) = ctx
+                .state
+                .db()
+                .read_event(height, position)
+                .into_storage_result()?
+            else {
+                continue;
+            };
+            if !matches!(&ev.event_type, EventType::EthereumBridge) {
+                continue;
+            }
+            let is_relayed =
+                match ev.attributes.get(&kind_key).map(|k| k.as_str()) {
+                    Some("bridge_pool_relayed") => true,
+                    Some("bridge_pool_expired") => false,
+                    _ => continue,
+                };
+            if hints::likely(is_relayed) {
+                status.relayed.insert(hash.clone());
+            } else {
+                status.expired.insert(hash.clone());
+            }
+            transfer_hashes.remove(&hash);
             break;
         }
     }
@@ -1665,12 +1671,13 @@ mod test_ethbridge_router {
             .write(&get_pending_key(&transfer), transfer.clone())
             .expect("Test failed");
 
-        // write transfers into the event log
+        // emit events of transfers in this block, which are written
+        // into the event store when the block is committed
         let mut transfer2 = transfer.clone();
         transfer2.transfer.amount = 1.into();
         let mut transfer3 = transfer.clone();
         transfer3.transfer.amount = 2.into();
-        client.event_log.log_events(vec![
+        client.state.in_mem_mut().block_events = vec![
             ethereum_structs::EthBridgeEvent::BridgePool {
                 tx_hash: transfer2.keccak256(),
                 status: ethereum_structs::BpTransferStatus::Expired,
@@ -1681,10 +1688,10 @@ mod test_ethbridge_router {
                 status: ethereum_structs::BpTransferStatus::Relayed,
             }
             .into(),
-        ]);
+        ];
 
         // some arbitrary transfer - since it's neither in the
-        // Bridge pool nor in the event log, it is assumed it has
+        // Bridge pool nor in the event store, it is assumed it has
         // either been relayed or that it has expired
         let mut transfer4 = transfer.clone();
         transfer4.transfer.amount = 3.into();
@@ -4,7 +4,6 @@ use namada_core::storage::BlockHeight;
 use namada_state::{DBIter, StorageHasher, WlState, DB};
 use thiserror::Error;
 
-use crate::events::log::EventLog;
 use crate::tendermint::merkle::proof::ProofOps;
 pub use crate::tendermint::v0_37::abci::request::Query as RequestQuery;
 /// A request context provides read-only access to storage and WASM compilation
@@ -17,8 +16,6 @@ where
 {
     /// Reference to the ledger's [`WlState`].
     pub state: &'shell WlState<D, H>,
-    /// Log of events emitted by `FinalizeBlock` ABCI calls.
-    pub event_log: &'shell EventLog,
     /// Cache of VP wasm compiled artifacts.
     pub vp_wasm_cache: VpCache,
     /// Cache of transaction wasm compiled artifacts.
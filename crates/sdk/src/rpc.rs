@@ -6,20 +6,23 @@ use std::ops::ControlFlow;
 use std::str::FromStr;
 
 use borsh::BorshDeserialize;
+use borsh_ext::BorshSerializeExt;
 use masp_primitives::asset_type::AssetType;
 use masp_primitives::merkle_tree::MerklePath;
 use masp_primitives::sapling::Node;
-use namada_account::Account;
+use namada_account::{Account, SpendingPolicy};
 use namada_core::address::{Address, InternalAddress};
 use namada_core::hash::Hash;
 use namada_core::key::common;
 use namada_core::storage::{
     BlockHeight, BlockResults, Epoch, Key, PrefixValue,
 };
+use namada_core::time::{DateTimeUtc, DurationSecs};
 use namada_core::token::{
     Amount, DenominatedAmount, Denomination, MaspDigitPos,
 };
 use namada_core::{storage, token};
+use namada_gas::Gas;
 use namada_governance::parameters::GovernanceParameters;
 use namada_governance::pgf::parameters::PgfParameters;
 use namada_governance::pgf::storage::steward::StewardDetail;
@@ -36,12 +39,15 @@ use namada_proof_of_stake::types::{
     BondsAndUnbondsDetails, CommissionPair, ValidatorMetaData, ValidatorState,
 };
 use namada_state::LastBlock;
+use namada_tx::data::schedule::{ScheduleTarget, ScheduledTx};
 use namada_tx::data::{ResultCode, TxResult};
 use serde::Serialize;
 
 use crate::args::InputAmount;
 use crate::control_flow::time;
 use crate::error::{EncodingError, Error, QueryError, TxSubmitError};
+use crate::events::log::query::EventQuery;
+use crate::events::log::EventsPage;
 use crate::events::Event;
 use crate::internal_macros::echo_error;
 use crate::io::Io;
@@ -127,6 +133,16 @@ pub async fn query_native_token<C: crate::queries::Client + Sync>(
     convert_response::<C, _>(RPC.shell().native_token(client).await)
 }
 
+/// Query the current base fee per unit of gas of the given token. Returns
+/// `None` if the base fee is disabled or if the token is not allowed for fee
+/// payment.
+pub async fn query_base_fee<C: crate::queries::Client + Sync>(
+    client: &C,
+    token: &Address,
+) -> Result<Option<token::Amount>, error::Error> {
+    convert_response::<C, _>(RPC.shell().base_fee(client, token).await)
+}
+
 /// Query the epoch of the given block height, if it exists.
 /// Will return none if the input block height is greater than
 /// the latest committed block height.
@@ -457,6 +473,54 @@ pub async fn query_has_storage_key<C: crate::queries::Client + Sync>(
     convert_response::<C, _>(RPC.shell().storage_has_key(client, key).await)
 }
 
+/// Query the transactions scheduled for an execution at the given target
+pub async fn query_scheduled_txs<C: crate::queries::Client + Sync>(
+    client: &C,
+    target: &ScheduleTarget,
+) -> Result<Vec<ScheduledTx>, Error> {
+    convert_response::<C, _>(match target {
+        ScheduleTarget::Height(height) => {
+            RPC.shell().scheduled_txs_at_height(client, height).await
+        }
+        ScheduleTarget::Epoch(epoch) => {
+            RPC.shell().scheduled_txs_at_epoch(client, epoch).await
+        }
+    })
+}
+
+/// Estimate the latest time at which the transactions scheduled at the given
+/// target get executed, from the last committed block
+pub async fn query_schedule_execution_time<C: crate::queries::Client + Sync>(
+    client: &C,
+    target: &ScheduleTarget,
+) -> Result<DateTimeUtc, Error> {
+    let last_block = query_block(client).await?.ok_or_else(|| {
+        Error::Other("No block has been committed yet".to_string())
+    })?;
+    let epoch = query_epoch(client).await?;
+    let max_time_per_block: DurationSecs = query_storage_value(
+        client,
+        &params_storage::get_max_expected_time_per_block_key(),
+    )
+    .await?;
+    let epoch_duration: EpochDuration = query_storage_value(
+        client,
+        &params_storage::get_epoch_duration_storage_key(),
+    )
+    .await?;
+    target
+        .estimate_execution_time(
+            last_block.height,
+            epoch,
+            last_block.time,
+            max_time_per_block,
+            &epoch_duration,
+        )
+        .ok_or_else(|| {
+            Error::Other(format!("The estimated time of {target} overflows"))
+        })
+}
+
 /// Represents a query for an event pertaining to the specified transaction
 #[derive(Debug, Copy, Clone)]
 pub enum TxEventQuery<'a> {
@@ -518,6 +582,25 @@ pub async fn query_tx_events<C: crate::queries::Client + Sync>(
     }
 }
 
+/// Query a page of the events that match the given query. The pages are
+/// numbered from 1, and the most recent events come first. A query that
+/// cannot be served from an index of the event store has to be bounded by
+/// block heights.
+pub async fn query_events<C: crate::queries::Client + Sync>(
+    client: &C,
+    query: &EventQuery,
+    page: u64,
+    per_page: u64,
+) -> Result<EventsPage, Error> {
+    let data = Some(query.to_string().serialize_to_vec());
+    convert_response::<C, _>(
+        RPC.shell()
+            .matching_events(client, data, None, false, &page, &per_page)
+            .await,
+    )
+    .map(|response| response.data)
+}
+
 /// Dry run a transaction
 pub async fn dry_run_tx<N: Namada>(
     context: &N,
@@ -544,9 +627,50 @@ pub async fn dry_run_tx<N: Namada>(
         )
     };
     display_line!(context.io(), "Dry-run result: {result_str}");
+    display_gas_breakdown(context, &result);
     Ok(result)
 }
 
+/// Dry run the given transaction without displaying its result and return
+/// the gas it consumed
+pub async fn query_tx_gas_used<C: crate::queries::Client + Sync>(
+    client: &C,
+    tx_bytes: Vec<u8>,
+) -> Result<Gas, Error> {
+    let (data, height, prove) = (Some(tx_bytes), None, false);
+    let result = convert_response::<C, _>(
+        RPC.shell().dry_run_tx(client, data, height, prove).await,
+    )?
+    .data;
+    Ok(result.gas_used)
+}
+
+/// Print a table of the gas consumed by a dry-run transaction in each
+/// category and by each of the VPs it triggered
+fn display_gas_breakdown<N: Namada>(context: &N, result: &TxResult) {
+    let Some(breakdown) = &result.gas_breakdown else {
+        return;
+    };
+    display_line!(context.io(), "Gas breakdown:");
+    display_line!(context.io(), "  {:<32} {:>12}", "Category", "Gas");
+    for (category, gas) in breakdown.iter() {
+        display_line!(context.io(), "  {:<32} {:>12}", category, gas);
+    }
+    display_line!(context.io(), "  {:<32} {:>12}", "total", breakdown.total());
+    for (vp, vp_breakdown) in &result.vps_result.gas_breakdown {
+        display_line!(context.io(), "VP {vp}:");
+        for (category, gas) in vp_breakdown.iter() {
+            display_line!(context.io(), "  {:<32} {:>12}", category, gas);
+        }
+        display_line!(
+            context.io(),
+            "  {:<32} {:>12}",
+            "total",
+            vp_breakdown.total()
+        );
+    }
+}
+
 /// Data needed for broadcasting a tx and
 /// monitoring its progress on chain
 ///
@@ -927,6 +1051,18 @@ pub async fn get_account_info<C: crate::queries::Client + Sync>(
     )
 }
 
+/// Query the spending policy of an account, if any
+pub async fn get_spending_policy<C: crate::queries::Client + Sync>(
+    client: &C,
+    owner: &Address,
+) -> Result<Option<SpendingPolicy>, error::Error> {
+    let key = namada_account::spending_policy_key(owner);
+    if !query_has_storage_key(client, &key).await? {
+        return Ok(None);
+    }
+    query_storage_value(client, &key).await.map(Some)
+}
+
 /// Query if the public_key is revealed
 pub async fn is_public_key_revealed<C: crate::queries::Client + Sync>(
     client: &C,
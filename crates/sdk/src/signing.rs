@@ -11,7 +11,9 @@ use masp_primitives::transaction::components::sapling::fees::{
     InputView, OutputView,
 };
 use masp_primitives::transaction::Transaction;
-use namada_account::{AccountPublicKeysMap, InitAccount, UpdateAccount};
+use namada_account::{
+    AccountPublicKeysMap, FeeAllowance, InitAccount, UpdateAccount,
+};
 use namada_core::address::{Address, ImplicitAddress, InternalAddress, MASP};
 use namada_core::key::*;
 use namada_core::masp::{AssetData, ExtendedViewingKey, PaymentAddress};
@@ -59,7 +61,7 @@ use crate::tx::{
 };
 pub use crate::wallet::store::AddressVpType;
 use crate::wallet::{Wallet, WalletIo};
-use crate::{args, display_line, rpc, MaybeSend, Namada};
+use crate::{args, display_line, edisplay_line, rpc, MaybeSend, Namada};
 
 /// A structure holding the signing data to craft a transaction
 #[derive(Clone)]
@@ -316,7 +318,30 @@ pub async fn aux_signing_data(
             let account =
                 rpc::get_account_info(context.client(), owner).await?;
             if let Some(account) = account {
-                (Some(account.public_keys_map), account.threshold)
+                // Sign with the spending keys of the account's spending policy
+                // if none of the signing keys belongs to the account
+                let is_spending_signer = !public_keys.is_empty()
+                    && public_keys.iter().all(|public_key| {
+                        account.get_index_from_public_key(public_key).is_none()
+                    });
+                let policy = if is_spending_signer {
+                    rpc::get_spending_policy(context.client(), owner).await?
+                } else {
+                    None
+                };
+                match policy {
+                    Some(policy)
+                        if public_keys.iter().all(|public_key| {
+                            policy.spending_keys.contains(public_key)
+                        }) =>
+                    {
+                        (
+                            Some(policy.spending_keys_map()),
+                            policy.spending_threshold,
+                        )
+                    }
+                    _ => (Some(account.public_keys_map), account.threshold),
+                }
             } else {
                 return Err(Error::from(TxSubmitError::InvalidAccount(
                     owner.encode(),
@@ -449,6 +474,12 @@ pub async fn validate_fee_and_gen_unshield<N: Namada>(
             }
         }
     };
+    // The gas price must also cover the current base fee, if enabled
+    let minimum_fee =
+        match rpc::query_base_fee(context.client(), &args.fee_token).await {
+            Ok(Some(base_fee)) => std::cmp::max(minimum_fee, base_fee),
+            _ => minimum_fee,
+        };
     let validated_minimum_fee = context
         .denominate_amount(&args.fee_token, minimum_fee)
         .await;
@@ -478,7 +509,25 @@ pub async fn validate_fee_and_gen_unshield<N: Namada>(
         None => validated_minimum_fee,
     };
 
-    let balance_key = balance_key(&args.fee_token, &fee_payer_address);
+    let total_fee = fee_amount.amount() * u64::from(args.gas_limit);
+    // The fees are drawn from the balance of the fee granter, if any, within
+    // the limits of its fee allowance
+    let fee_source = match &args.fee_granter {
+        Some(granter) => {
+            validate_fee_allowance(
+                context,
+                args,
+                granter,
+                &fee_payer_address,
+                total_fee,
+            )
+            .await?;
+            granter.clone()
+        }
+        None => fee_payer_address.clone(),
+    };
+
+    let balance_key = balance_key(&args.fee_token, &fee_source);
     let balance = rpc::query_storage_value::<_, token::Amount>(
         context.client(),
         &balance_key,
@@ -486,10 +535,9 @@ pub async fn validate_fee_and_gen_unshield<N: Namada>(
     .await
     .unwrap_or_default();
 
-    let total_fee = fee_amount.amount() * u64::from(args.gas_limit);
     let mut updated_balance = TxSourcePostBalance {
         post_balance: balance,
-        source: fee_payer_address.clone(),
+        source: fee_source.clone(),
         token: args.fee_token.clone(),
     };
 
@@ -600,10 +648,7 @@ pub async fn validate_fee_and_gen_unshield<N: Namada>(
                         context.format_amount(&token_addr, balance).await;
                     return Err(Error::from(
                         TxSubmitError::BalanceTooLowForFees(
-                            fee_payer_address,
-                            token_addr,
-                            fee_amount,
-                            balance,
+                            fee_source, token_addr, fee_amount, balance,
                         ),
                     ));
                 }
@@ -628,6 +673,52 @@ pub async fn validate_fee_and_gen_unshield<N: Namada>(
     Ok((fee_amount, updated_balance, unshield))
 }
 
+/// Check that the fee allowance granted by the granter to the fee payer can
+/// pay for the given total fee
+async fn validate_fee_allowance<N: Namada>(
+    context: &N,
+    args: &args::Tx<SdkTypes>,
+    granter: &Address,
+    fee_payer: &Address,
+    total_fee: Amount,
+) -> Result<(), Error> {
+    if args.fee_unshield.is_some() {
+        return Err(Error::Other(
+            "Fee unshielding cannot be combined with a fee allowance"
+                .to_string(),
+        ));
+    }
+    let allowance_key = namada_account::fee_allowance_key(granter, fee_payer);
+    let allowance = rpc::query_storage_value::<_, FeeAllowance>(
+        context.client(),
+        &allowance_key,
+    )
+    .await;
+    let error = match allowance {
+        Ok(allowance) if allowance.token != args.fee_token => format!(
+            "The fee allowance granted by {granter} only covers fees paid in \
+             {}",
+            allowance.token
+        ),
+        Ok(allowance) if allowance.spend_limit < total_fee => format!(
+            "The remaining fee allowance granted by {granter} is too low: {}",
+            context
+                .format_amount(&args.fee_token, allowance.spend_limit)
+                .await
+        ),
+        Ok(_) => return Ok(()),
+        Err(_) => {
+            format!("No fee allowance granted by {granter} to {fee_payer}")
+        }
+    };
+    if args.force {
+        edisplay_line!(context.io(), "{}", error);
+        Ok(())
+    } else {
+        Err(Error::Other(error))
+    }
+}
+
 /// Create a wrapper tx from a normal tx. Get the hash of the
 /// wrapper and its payload which is needed for monitoring its
 /// progress on chain.
@@ -659,6 +750,9 @@ pub async fn wrap_tx(
         args.gas_limit,
         unshield_section_hash,
     );
+    if let Some(granter) = &args.fee_granter {
+        tx.add_fee_granter(granter.clone());
+    }
 
     Ok(())
 }
@@ -932,6 +1026,25 @@ impl<'a> Display for LedgerProposalType<'a> {
     }
 }
 
+/// Format the given public keys for the hardware wallet, each followed by its
+/// weight if the keys are weighted. The weights can also be updated without
+/// the keys.
+fn public_keys_to_ledger_vector(
+    public_keys: &[common::PublicKey],
+    weights: &[u8],
+) -> Vec<String> {
+    let mut output = Vec::new();
+    for index in 0..std::cmp::max(public_keys.len(), weights.len()) {
+        if let Some(public_key) = public_keys.get(index) {
+            output.push(format!("Public key : {}", public_key));
+        }
+        if let Some(weight) = weights.get(index) {
+            output.push(format!("Weight : {}", weight));
+        }
+    }
+    output
+}
+
 fn proposal_type_to_ledger_vector(
     proposal_type: &ProposalType,
     tx: &Tx,
@@ -1122,23 +1235,19 @@ pub async fn to_ledger_vector(
             HEXLOWER.encode(&extra.code.hash().0)
         };
         tv.output.extend(vec![format!("Type : Init Account")]);
-        tv.output.extend(
-            init_account
-                .public_keys
-                .iter()
-                .map(|k| format!("Public key : {}", k)),
-        );
+        tv.output.extend(public_keys_to_ledger_vector(
+            &init_account.public_keys,
+            &init_account.public_key_weights,
+        ));
         tv.output.extend(vec![
             format!("Threshold : {}", init_account.threshold),
             format!("VP type : {}", vp_code),
         ]);
 
-        tv.output_expert.extend(
-            init_account
-                .public_keys
-                .iter()
-                .map(|k| format!("Public key : {}", k)),
-        );
+        tv.output_expert.extend(public_keys_to_ledger_vector(
+            &init_account.public_keys,
+            &init_account.public_key_weights,
+        ));
         tv.output_expert.extend(vec![
             format!("Threshold : {}", init_account.threshold),
             format!("VP type : {}", HEXLOWER.encode(&extra.code.hash().0)),
@@ -1324,12 +1433,10 @@ pub async fn to_ledger_vector(
             format!("Type : Update Account"),
             format!("Address : {}", update_account.addr),
         ]);
-        tv.output.extend(
-            update_account
-                .public_keys
-                .iter()
-                .map(|k| format!("Public key : {}", k)),
-        );
+        tv.output.extend(public_keys_to_ledger_vector(
+            &update_account.public_keys,
+            &update_account.public_key_weights,
+        ));
         if update_account.threshold.is_some() {
             tv.output.extend(vec![format!(
                 "Threshold : {}",
@@ -1359,12 +1466,10 @@ pub async fn to_ledger_vector(
         }
         tv.output_expert
             .extend(vec![format!("Address : {}", update_account.addr)]);
-        tv.output_expert.extend(
-            update_account
-                .public_keys
-                .iter()
-                .map(|k| format!("Public key : {}", k)),
-        );
+        tv.output_expert.extend(public_keys_to_ledger_vector(
+            &update_account.public_keys,
+            &update_account.public_key_weights,
+        ));
         if let Some(threshold) = update_account.threshold {
             tv.output_expert
                 .extend(vec![format!("Threshold : {}", threshold,)])
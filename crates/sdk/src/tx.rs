@@ -1,7 +1,7 @@
 //! SDK functions to construct different types of transactions
 
 use std::borrow::Cow;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fs::File;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
@@ -18,7 +18,10 @@ use masp_primitives::transaction::components::transparent::fees::{
     InputView as TransparentInputView, OutputView as TransparentOutputView,
 };
 use masp_primitives::transaction::components::I128Sum;
-use namada_account::{InitAccount, UpdateAccount};
+use namada_account::{
+    FeeAllowance, GrantFeeAllowance, InitAccount, RemoveSpendingPolicy,
+    RevokeFeeAllowance, SpendingPolicy, UpdateAccount, UpdateSpendingPolicy,
+};
 use namada_core::address::{Address, InternalAddress, MASP};
 use namada_core::dec::Dec;
 use namada_core::hash::Hash;
@@ -35,6 +38,7 @@ use namada_core::masp::{AssetData, TransferSource, TransferTarget};
 use namada_core::storage::Epoch;
 use namada_core::time::DateTimeUtc;
 use namada_core::{storage, token};
+use namada_gas::Gas;
 use namada_governance::cli::onchain::{
     DefaultProposal, OnChainProposal, PgfFundingProposal, PgfStewardProposal,
 };
@@ -49,15 +53,19 @@ use namada_proof_of_stake::types::{CommissionPair, ValidatorState};
 use namada_token::storage_key::balance_key;
 use namada_token::DenominatedAmount;
 use namada_tx::data::pgf::UpdateStewardCommission;
-use namada_tx::data::{pos, ResultCode, TxResult};
+use namada_tx::data::schedule::{CancelScheduledTx, ScheduledTx};
+use namada_tx::data::{pos, Fee, GasLimit, ResultCode, TxResult, TxType};
 pub use namada_tx::{Signature, *};
+use rand::rngs::OsRng;
 
 use crate::args::{self, InputAmount};
 use crate::control_flow::time;
 use crate::error::{EncodingError, Error, QueryError, Result, TxSubmitError};
 use crate::io::Io;
 use crate::masp::TransferErr::Build;
-use crate::masp::{ShieldedContext, ShieldedTransfer};
+use crate::masp::{
+    to_viewing_key, ConsolidationStep, ShieldedContext, ShieldedTransfer,
+};
 use crate::queries::Client;
 use crate::rpc::{
     self, query_wasm_code_hash, validate_amount, InnerTxResult,
@@ -66,9 +74,11 @@ use crate::rpc::{
 use crate::signing::{self, validate_fee_and_gen_unshield, SigningTxData};
 use crate::tendermint_rpc::endpoint::broadcast::tx_sync::Response;
 use crate::tendermint_rpc::error::Error as RpcError;
-use crate::wallet::WalletIo;
+use crate::wallet::{gen_secret_key, WalletIo};
 use crate::{display_line, edisplay_line, Namada};
 
+pub mod inspect;
+
 /// Initialize account transaction WASM
 pub const TX_INIT_ACCOUNT_WASM: &str = "tx_init_account.wasm";
 /// Become validator transaction WASM path
@@ -117,6 +127,20 @@ pub const TX_UPDATE_STEWARD_COMMISSION: &str =
     "tx_update_steward_commission.wasm";
 /// Redelegate transaction WASM path
 pub const TX_REDELEGATE_WASM: &str = "tx_redelegate.wasm";
+/// Grant fee allowance transaction WASM path
+pub const TX_GRANT_FEE_ALLOWANCE_WASM: &str = "tx_grant_fee_allowance.wasm";
+/// Revoke fee allowance transaction WASM path
+pub const TX_REVOKE_FEE_ALLOWANCE_WASM: &str = "tx_revoke_fee_allowance.wasm";
+/// Schedule tx transaction WASM path
+pub const TX_SCHEDULE_TX_WASM: &str = "tx_schedule_tx.wasm";
+/// Cancel scheduled tx transaction WASM path
+pub const TX_CANCEL_SCHEDULED_TX_WASM: &str = "tx_cancel_scheduled_tx.wasm";
+/// Update spending policy transaction WASM path
+pub const TX_UPDATE_SPENDING_POLICY_WASM: &str =
+    "tx_update_spending_policy.wasm";
+/// Remove spending policy transaction WASM path
+pub const TX_REMOVE_SPENDING_POLICY_WASM: &str =
+    "tx_remove_spending_policy.wasm";
 
 /// Default timeout in seconds for requests to the `/accepted`
 /// and `/applied` ABCI query endpoints.
@@ -188,17 +212,242 @@ pub async fn prepare_tx<C: crate::queries::Client + Sync>(
     tx: &mut Tx,
     unshield: Option<masp_primitives::transaction::Transaction>,
     fee_amount: DenominatedAmount,
-    fee_payer: common::PublicKey,
+    signing_data: &SigningTxData,
 ) -> Result<()> {
     if !args.dry_run {
         let epoch = rpc::query_epoch(client).await?;
-
-        signing::wrap_tx(tx, args, epoch, unshield, fee_amount, fee_payer).await
+        let fee_payer = signing_data.fee_payer.clone();
+
+        match args.gas_estimate_multiplier {
+            Some(multiplier) => {
+                let gas_limit = estimate_gas_limit(
+                    client,
+                    args,
+                    tx,
+                    epoch,
+                    unshield.clone(),
+                    fee_amount,
+                    signing_data,
+                    multiplier,
+                )
+                .await?;
+                check_estimated_fee(
+                    client,
+                    args,
+                    gas_limit,
+                    unshield.is_some(),
+                    fee_amount,
+                    &fee_payer,
+                )
+                .await?;
+                let args = args::Tx {
+                    gas_limit,
+                    ..args.clone()
+                };
+                signing::wrap_tx(
+                    tx, &args, epoch, unshield, fee_amount, fee_payer,
+                )
+                .await
+            }
+            None => {
+                signing::wrap_tx(
+                    tx, args, epoch, unshield, fee_amount, fee_payer,
+                )
+                .await
+            }
+        }
     } else {
         Ok(())
     }
 }
 
+/// Estimate the gas limit of the given, not yet wrapped, transaction by
+/// dry-running it, adding the gas required by its wrapper and its signatures
+/// and scaling the total by the given safety multiplier. The gas of the
+/// wrapper is computed from the size of the transaction once wrapped with the
+/// given arguments and signed with placeholder signatures.
+#[allow(clippy::too_many_arguments)]
+pub async fn estimate_gas_limit<C: crate::queries::Client + Sync>(
+    client: &C,
+    args: &args::Tx,
+    tx: &Tx,
+    epoch: Epoch,
+    unshield: Option<masp_primitives::transaction::Transaction>,
+    fee_amount: DenominatedAmount,
+    signing_data: &SigningTxData,
+    multiplier: Dec,
+) -> Result<GasLimit> {
+    let inner_gas = rpc::query_tx_gas_used(client, tx.to_bytes()).await?;
+    let mut wrapped_tx = tx.clone();
+    signing::wrap_tx(
+        &mut wrapped_tx,
+        args,
+        epoch,
+        unshield,
+        fee_amount,
+        signing_data.fee_payer.clone(),
+    )
+    .await?;
+    // The dry run is not signed, so the verification of the inner signatures
+    // by the VPs must be accounted for separately
+    let inner_signatures =
+        add_placeholder_signatures(&mut wrapped_tx, signing_data);
+    let signatures_gas = namada_gas::VERIFY_TX_SIG_GAS
+        .checked_mul(inner_signatures)
+        .map(Gas::from)
+        .and_then(|gas| gas.checked_add(inner_gas))
+        .ok_or_else(|| {
+            Error::from(TxSubmitError::GasEstimationError(
+                "Overflow in the gas used by the transaction".to_string(),
+            ))
+        })?;
+    gas_limit_estimate(&wrapped_tx.to_bytes(), signatures_gas, multiplier)
+}
+
+/// Sign the given wrapped transaction with placeholder signatures, made with
+/// disposable keys of the same schemes as the keys that will actually sign
+/// it, so that its size matches the one of the signed transaction. Returns
+/// the number of signatures of the inner transaction.
+fn add_placeholder_signatures(
+    tx: &mut Tx,
+    signing_data: &SigningTxData,
+) -> u64 {
+    let disposable_key = |public_key: &common::PublicKey| {
+        let scheme = match public_key {
+            common::PublicKey::Ed25519(_) => SchemeType::Ed25519,
+            common::PublicKey::Secp256k1(_) => SchemeType::Secp256k1,
+        };
+        gen_secret_key(scheme, &mut OsRng)
+    };
+    // Mirror the indexing of the signatures done in `Tx::sign_raw`
+    let secret_keys: BTreeMap<u8, common::SecretKey> =
+        match (&signing_data.owner, &signing_data.account_public_keys_map) {
+            (Some(_), Some(public_keys_map)) => signing_data
+                .public_keys
+                .iter()
+                .filter_map(|public_key| {
+                    public_keys_map
+                        .get_index_from_public_key(public_key)
+                        .map(|index| (index, disposable_key(public_key)))
+                })
+                .collect(),
+            _ => (0..)
+                .zip(signing_data.public_keys.iter().map(disposable_key))
+                .collect(),
+        };
+    let inner_signatures = secret_keys.len() as u64;
+    if !secret_keys.is_empty() {
+        let targets = vec![tx.raw_header_hash()];
+        tx.add_section(Section::Signature(Signature::new(
+            targets,
+            secret_keys,
+            signing_data.owner.clone(),
+        )));
+    }
+    tx.sign_wrapper(disposable_key(&signing_data.fee_payer));
+    inner_signatures
+}
+
+/// Compute the gas limit of a transaction from its wrapped bytes, which the
+/// gas of the wrapper depends on, and the gas used by its inner transaction,
+/// scaled by the given safety multiplier
+fn gas_limit_estimate(
+    wrapped_tx_bytes: &[u8],
+    inner_gas: Gas,
+    multiplier: Dec,
+) -> Result<GasLimit> {
+    let total_gas = namada_gas::get_wrapper_gas(wrapped_tx_bytes.len() as u64)
+        .map_err(|err| {
+            Error::from(TxSubmitError::GasEstimationError(err.to_string()))
+        })?
+        .checked_add(inner_gas)
+        .ok_or_else(|| {
+            Error::from(TxSubmitError::GasEstimationError(
+                "Overflow in the gas used by the transaction".to_string(),
+            ))
+        })?;
+    let estimate = multiplier
+        .checked_mul(&Dec::from(total_gas.get_whole_gas_units()))
+        .filter(|estimate| !estimate.is_negative())
+        .and_then(|estimate| u64::try_from(estimate.ceil().abs()).ok())
+        .ok_or_else(|| {
+            Error::from(TxSubmitError::GasEstimationError(format!(
+                "Invalid safety multiplier {multiplier} for {total_gas} gas"
+            )))
+        })?;
+    Ok(GasLimit::from(estimate))
+}
+
+/// Check that the fee payer, or the fee granter if any, can afford the fees
+/// of the estimated gas limit. The fees were already validated against the
+/// gas limit provided in the arguments, so this only needs to happen if the
+/// estimate exceeds it.
+async fn check_estimated_fee<C: crate::queries::Client + Sync>(
+    client: &C,
+    args: &args::Tx,
+    gas_limit: GasLimit,
+    has_unshield: bool,
+    fee_amount: DenominatedAmount,
+    fee_payer: &common::PublicKey,
+) -> Result<()> {
+    if args.force || u64::from(gas_limit) <= u64::from(args.gas_limit) {
+        return Ok(());
+    }
+    if has_unshield {
+        // The fee unshielding transaction only covers the fees of the
+        // provided gas limit
+        return Err(Error::from(TxSubmitError::FeeUnshieldingError(format!(
+            "The estimated gas limit {} exceeds the provided gas limit {} \
+             that the unshielded fees were computed for",
+            u64::from(gas_limit),
+            u64::from(args.gas_limit),
+        ))));
+    }
+    let fee_payer_address = Address::from(fee_payer);
+    let total_fee = fee_amount
+        .amount()
+        .checked_mul(token::Amount::from(u64::from(gas_limit)))
+        .ok_or_else(|| {
+            Error::from(TxSubmitError::GasEstimationError(format!(
+                "Overflow in the fees of the estimated gas limit {}",
+                u64::from(gas_limit)
+            )))
+        })?;
+    // The fees are drawn from the balance of the fee granter, if any, within
+    // the limits of its fee allowance
+    let fee_source = match &args.fee_granter {
+        Some(granter) => {
+            let allowance_key =
+                namada_account::fee_allowance_key(granter, &fee_payer_address);
+            let allowance = rpc::query_storage_value::<_, FeeAllowance>(
+                client,
+                &allowance_key,
+            )
+            .await?;
+            if allowance.spend_limit < total_fee {
+                return Err(Error::Other(format!(
+                    "The remaining fee allowance granted by {granter} is too \
+                     low for the estimated fees: {}",
+                    DenominatedAmount::new(total_fee, fee_amount.denom())
+                )));
+            }
+            granter.clone()
+        }
+        None => fee_payer_address,
+    };
+    let balance =
+        rpc::get_token_balance(client, &args.fee_token, &fee_source).await?;
+    if total_fee > balance {
+        return Err(Error::from(TxSubmitError::BalanceTooLowForFees(
+            fee_source,
+            args.fee_token.clone(),
+            DenominatedAmount::new(total_fee, fee_amount.denom()).to_string(),
+            DenominatedAmount::new(balance, fee_amount.denom()).to_string(),
+        )));
+    }
+    Ok(())
+}
+
 /// Submit transaction and wait for result. Returns a list of addresses
 /// initialized in the transaction if any. In dry run, this is always empty.
 pub async fn process_tx(
@@ -299,7 +548,7 @@ pub async fn build_reveal_pk(
         do_nothing,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -472,6 +721,15 @@ pub fn display_inner_resp(context: &impl Namada, resp: &TxResponse) {
                 resp.height,
                 inner.gas_used,
             );
+            for (index, batched) in inner.batch_results.iter().enumerate() {
+                display_line!(
+                    context.io(),
+                    "  Inner tx {} with code {} used {} gas.",
+                    index,
+                    batched.code_hash,
+                    batched.gas_used,
+                );
+            }
         }
         InnerTxResult::VpsRejected(inner) => {
             let changed_keys: Vec<_> = inner
@@ -677,7 +935,7 @@ pub async fn build_validator_commission_change(
         do_nothing,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -817,7 +1075,7 @@ pub async fn build_validator_metadata_change(
         do_nothing,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -885,7 +1143,7 @@ pub async fn build_update_steward_commission(
         do_nothing,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -934,7 +1192,7 @@ pub async fn build_resign_steward(
         do_nothing,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -1041,7 +1299,7 @@ pub async fn build_unjail_validator(
         do_nothing,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -1119,7 +1377,7 @@ pub async fn build_deactivate_validator(
         do_nothing,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -1196,7 +1454,7 @@ pub async fn build_reactivate_validator(
         do_nothing,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -1387,7 +1645,7 @@ pub async fn build_redelegation(
         do_nothing,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -1477,7 +1735,7 @@ pub async fn build_withdraw(
         do_nothing,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -1532,7 +1790,7 @@ pub async fn build_claim_rewards(
         do_nothing,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -1667,7 +1925,7 @@ pub async fn build_unbond(
         do_nothing,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await?;
     Ok((tx, signing_data, latest_withdrawal_pre))
@@ -1859,7 +2117,7 @@ pub async fn build_bond(
         do_nothing,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -1916,7 +2174,7 @@ pub async fn build_default_proposal(
         push_data,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -2007,7 +2265,7 @@ pub async fn build_vote_proposal(
         do_nothing,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -2056,7 +2314,7 @@ pub async fn build_pgf_funding_proposal(
         add_section,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -2106,7 +2364,7 @@ pub async fn build_pgf_stewards_proposal(
         add_section,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -2294,7 +2552,7 @@ pub async fn build_ibc_transfer(
         &mut tx,
         unshield,
         fee_amount,
-        signing_data.fee_payer.clone(),
+        &signing_data,
     )
     .await?;
 
@@ -2311,14 +2569,21 @@ pub async fn build<F, D>(
     on_tx: F,
     unshield: Option<masp_primitives::transaction::Transaction>,
     fee_amount: DenominatedAmount,
-    gas_payer: &common::PublicKey,
+    signing_data: &SigningTxData,
 ) -> Result<Tx>
 where
     F: FnOnce(&mut Tx, &mut D) -> Result<()>,
     D: BorshSerialize,
 {
     build_pow_flag(
-        context, tx_args, path, data, on_tx, unshield, fee_amount, gas_payer,
+        context,
+        tx_args,
+        path,
+        data,
+        on_tx,
+        unshield,
+        fee_amount,
+        signing_data,
     )
     .await
 }
@@ -2332,7 +2597,7 @@ async fn build_pow_flag<F, D>(
     on_tx: F,
     unshield: Option<masp_primitives::transaction::Transaction>,
     fee_amount: DenominatedAmount,
-    gas_payer: &common::PublicKey,
+    signing_data: &SigningTxData,
 ) -> Result<Tx>
 where
     F: FnOnce(&mut Tx, &mut D) -> Result<()>,
@@ -2364,7 +2629,7 @@ where
         &mut tx_builder,
         unshield,
         fee_amount,
-        gas_payer.clone(),
+        signing_data,
     )
     .await?;
     Ok(tx_builder)
@@ -2557,7 +2822,7 @@ pub async fn build_transfer<N: Namada>(
         add_shielded,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await?;
     Ok((tx, signing_data, shielded_tx_epoch))
@@ -2610,6 +2875,118 @@ async fn construct_shielded_parts<N: Namada>(
     Ok(Some((shielded_parts, asset_types)))
 }
 
+/// Plan the shielded self-transfers merging the dust notes of a spending key
+pub async fn plan_note_consolidation(
+    context: &impl Namada,
+    args: &args::TxConsolidateNotes,
+) -> Result<Vec<ConsolidationStep>> {
+    let threshold = match args.threshold {
+        Some(threshold) => Some(
+            validate_amount(context, threshold, &args.token, args.tx.force)
+                .await?
+                .amount(),
+        ),
+        None => None,
+    };
+    // Precompute asset types to increase chances of success in decoding
+    let token_map = context.wallet().await.get_addresses();
+    let tokens = token_map.values().collect();
+    let mut shielded = context.shielded_mut().await;
+    let _ = shielded.load().await;
+    let _ = shielded
+        .precompute_asset_types(context.client(), tokens)
+        .await;
+    let vk = to_viewing_key(&args.spending_key.into()).vk;
+    shielded
+        .plan_note_consolidation(
+            context.client(),
+            &vk,
+            &args.token,
+            threshold,
+            args.max_descriptions,
+            u64::from(args.tx.gas_limit),
+        )
+        .await
+}
+
+/// Build the shielded self-transfer of a note consolidation step
+pub async fn build_consolidate_notes<N: Namada>(
+    context: &N,
+    args: &args::TxConsolidateNotes,
+    step: &ConsolidationStep,
+) -> Result<(Tx, SigningTxData, Option<Epoch>)> {
+    let signing_data =
+        signing::aux_signing_data(context, &args.tx, Some(MASP), Some(MASP))
+            .await?;
+    let (fee_amount, _, unshield) = validate_fee_and_gen_unshield(
+        context,
+        &args.tx,
+        &signing_data.fee_payer,
+    )
+    .await?;
+
+    let shielded_transfer =
+        ShieldedContext::<N::ShieldedUtils>::gen_note_consolidation(
+            context,
+            &args.spending_key,
+            step,
+        )
+        .await
+        .map_err(|err| TxSubmitError::MaspError(err.to_string()))?;
+    let shielded_tx_epoch = shielded_transfer.epoch;
+    // Get the decoded asset types used in the transaction to give offline
+    // wallet users more information
+    let asset_types = used_asset_types(context, &shielded_transfer.builder)
+        .await
+        .unwrap_or_default();
+
+    // The transfer is to self, so redact its transparent amount and token
+    let transfer = token::Transfer {
+        source: MASP,
+        target: MASP,
+        token: context.native_token(),
+        amount: token::Amount::zero().into(),
+        key: None,
+        // Link the Transfer to the MASP Transaction by hash code
+        shielded: None,
+    };
+
+    let add_shielded = |tx: &mut Tx, transfer: &mut token::Transfer| {
+        let ShieldedTransfer {
+            builder,
+            masp_tx,
+            metadata,
+            epoch: _,
+        } = shielded_transfer;
+        // Add a MASP Transaction section to the Tx and get the tx hash
+        let masp_tx_hash = tx.add_masp_tx_section(masp_tx).1;
+        transfer.shielded = Some(masp_tx_hash);
+
+        tx.add_masp_builder(MaspBuilder {
+            asset_types,
+            // Store how the Info objects map to Descriptors/Outputs
+            metadata,
+            // Store the data that was used to construct the Transaction
+            builder,
+            // Link the Builder to the Transaction by hash code
+            target: masp_tx_hash,
+        });
+        Ok(())
+    };
+    let tx = build_pow_flag(
+        context,
+        &args.tx,
+        args.tx_code_path.clone(),
+        transfer,
+        add_shielded,
+        unshield,
+        fee_amount,
+        &signing_data,
+    )
+    .await?;
+    Ok((tx, signing_data, Some(shielded_tx_epoch)))
+}
+
 /// Submit a transaction to initialize an account
 pub async fn build_init_account(
     context: &impl Namada,
@@ -2618,6 +2995,7 @@ pub async fn build_init_account(
         vp_code_path,
         tx_code_path,
         public_keys,
+        public_key_weights,
         threshold,
     }: &args::TxInitAccount,
 ) -> Result<(Tx, SigningTxData)> {
@@ -2644,9 +3022,16 @@ pub async fn build_init_account(
             }
         }
     };
+    validate_public_key_weights(
+        public_keys.len(),
+        public_key_weights,
+        threshold,
+        tx_args.force,
+    )?;
 
     let data = InitAccount {
         public_keys: public_keys.clone(),
+        public_key_weights: public_key_weights.clone(),
         // We will add the hash inside the add_code_hash function
         vp_code_hash: Hash::zero(),
         threshold,
@@ -2668,7 +3053,7 @@ pub async fn build_init_account(
         add_code_hash,
         unshield,
         fee_amount,
-        &signing_data.fee_payer,
+        &signing_data,
     )
     .await
     .map(|tx| (tx, signing_data))
@@ -2683,6 +3068,7 @@ pub async fn build_update_account(
         tx_code_path,
         addr,
         public_keys,
+        public_key_weights,
         threshold,
     }: &args::TxUpdateAccount,
 ) -> Result<(Tx, SigningTxData)> {
@@ -2704,6 +3090,20 @@ pub async fn build_update_account(
     let addr = if let Some(account) =
         rpc::get_account_info(context.client(), addr).await?
     {
+        // The weights are reset whenever the public keys are updated
+        if !public_keys.is_empty() || !public_key_weights.is_empty() {
+            let total_public_keys = if public_keys.is_empty() {
+                account.public_keys_map.idx_to_pk.len()
+            } else {
+                public_keys.len()
+            };
+            validate_public_key_weights(
+                total_public_keys,
+                public_key_weights,
+                threshold.unwrap_or(account.threshold),
+                tx_args.force,
+            )?;
+        }
         account.address
     } else if tx_args.force {
         addr.clone()
@@ -2739,6 +3139,7 @@ pub async fn build_update_account(
         addr,
         vp_code_hash: extra_section_hash,
         public_keys: public_keys.clone(),
+        public_key_weights: public_key_weights.clone(),
         threshold: *threshold,
     };
 
@@ -2762,8 +3163,485 @@ pub async fn build_update_account(
         add_code_hash,
         unshield,
         fee_amount,
+        &signing_data,
+    )
+    .await
+    .map(|tx| (tx, signing_data))
+}
+
+/// Check that the weights match the public keys of an account and that the
+/// threshold can be met by their sum
+fn validate_public_key_weights(
+    total_public_keys: usize,
+    public_key_weights: &[u8],
+    threshold: u8,
+    force: bool,
+) -> Result<()> {
+    if force || public_key_weights.is_empty() {
+        return Ok(());
+    }
+    if public_key_weights.len() != total_public_keys {
+        return Err(Error::Other(format!(
+            "Expected {total_public_keys} public key weights, got {}",
+            public_key_weights.len()
+        )));
+    }
+    if public_key_weights.contains(&0) {
+        return Err(Error::Other(
+            "The public key weights must be positive".to_string(),
+        ));
+    }
+    let total_weight: u32 =
+        public_key_weights.iter().copied().map(u32::from).sum();
+    if total_weight < u32::from(threshold) {
+        return Err(Error::Other(format!(
+            "The threshold {threshold} cannot be met by the total weight \
+             {total_weight} of the public keys"
+        )));
+    }
+    Ok(())
+}
+
+/// Craft a transaction to grant a fee allowance
+pub async fn build_grant_fee_allowance(
+    context: &impl Namada,
+    args::TxGrantFeeAllowance {
+        tx: tx_args,
+        granter,
+        grantee,
+        token,
+        spend_limit,
+        expiration,
+        allowed_tx_code_paths,
+        tx_code_path,
+    }: &args::TxGrantFeeAllowance,
+) -> Result<(Tx, SigningTxData)> {
+    let default_signer = Some(granter.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx_args,
+        Some(granter.clone()),
+        default_signer,
+    )
+    .await?;
+    let (fee_amount, _, unshield) = validate_fee_and_gen_unshield(
+        context,
+        tx_args,
+        &signing_data.fee_payer,
+    )
+    .await?;
+
+    if granter == grantee && !tx_args.force {
+        return Err(Error::Other(format!(
+            "The account {granter} cannot grant a fee allowance to itself"
+        )));
+    }
+    let spend_limit =
+        validate_amount(context, *spend_limit, token, tx_args.force).await?;
+    let mut allowed_code_hashes = BTreeSet::new();
+    for code_path in allowed_tx_code_paths {
+        allowed_code_hashes
+            .insert(query_wasm_code_hash_buf(context, code_path).await?);
+    }
+
+    let data = GrantFeeAllowance {
+        granter: granter.clone(),
+        grantee: grantee.clone(),
+        allowance: FeeAllowance {
+            token: token.clone(),
+            spend_limit: spend_limit.amount(),
+            expiration: *expiration,
+            allowed_code_hashes,
+        },
+    };
+
+    build(
+        context,
+        tx_args,
+        tx_code_path.clone(),
+        data,
+        do_nothing,
+        unshield,
+        fee_amount,
+        &signing_data,
+    )
+    .await
+    .map(|tx| (tx, signing_data))
+}
+
+/// Craft a transaction to revoke a fee allowance
+pub async fn build_revoke_fee_allowance(
+    context: &impl Namada,
+    args::TxRevokeFeeAllowance {
+        tx: tx_args,
+        granter,
+        grantee,
+        tx_code_path,
+    }: &args::TxRevokeFeeAllowance,
+) -> Result<(Tx, SigningTxData)> {
+    let default_signer = Some(granter.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx_args,
+        Some(granter.clone()),
+        default_signer,
+    )
+    .await?;
+    let (fee_amount, _, unshield) = validate_fee_and_gen_unshield(
+        context,
+        tx_args,
+        &signing_data.fee_payer,
+    )
+    .await?;
+
+    let allowance_key = namada_account::fee_allowance_key(granter, grantee);
+    if !rpc::query_has_storage_key(context.client(), &allowance_key).await?
+        && !tx_args.force
+    {
+        return Err(Error::Other(format!(
+            "No fee allowance granted by {granter} to {grantee}"
+        )));
+    }
+
+    let data = RevokeFeeAllowance {
+        granter: granter.clone(),
+        grantee: grantee.clone(),
+    };
+
+    build(
+        context,
+        tx_args,
+        tx_code_path.clone(),
+        data,
+        do_nothing,
+        unshield,
+        fee_amount,
+        &signing_data,
+    )
+    .await
+    .map(|tx| (tx, signing_data))
+}
+
+/// Craft a transaction to schedule the given transaction for a future
+/// execution. The scheduled transaction gets signed by the owner with the keys
+/// of the software wallet unless it already carries signatures.
+pub async fn build_schedule_tx(
+    context: &impl Namada,
+    args::TxScheduleTx {
+        tx: tx_args,
+        owner,
+        target,
+        serialized_tx,
+        gas_limit,
+        tx_code_path,
+    }: &args::TxScheduleTx,
+) -> Result<(Tx, SigningTxData)> {
+    let default_signer = Some(owner.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx_args,
+        Some(owner.clone()),
+        default_signer,
+    )
+    .await?;
+    let (fee_amount, _, unshield) = validate_fee_and_gen_unshield(
+        context,
+        tx_args,
+        &signing_data.fee_payer,
+    )
+    .await?;
+
+    let mut scheduled_tx = Tx::deserialize(serialized_tx.as_ref())
+        .map_err(|_| Error::Other("Invalid tx deserialization.".to_string()))?;
+    scheduled_tx.update_header(TxType::Raw);
+    let chain_id = tx_args.chain_id.clone().unwrap();
+    if scheduled_tx.header.chain_id != chain_id {
+        return Err(Error::Other(format!(
+            "The scheduled transaction was built for chain {} instead of {}",
+            scheduled_tx.header.chain_id, chain_id
+        )));
+    }
+    let is_signed = scheduled_tx
+        .sections
+        .iter()
+        .any(|section| matches!(section, Section::Signature(_)));
+    // The tx must not expire before its execution, otherwise it gets dropped
+    // while its prepaid fee is still paid
+    let execution_time =
+        rpc::query_schedule_execution_time(context.client(), target).await?;
+    let expiration = scheduled_tx.header.effective_expiration();
+    if !is_signed && scheduled_tx.header.expiration.is_none() {
+        // Keep the lifetime of the tx, counted from its estimated execution
+        // instead of its timestamp
+        let lifetime = expiration
+            .0
+            .signed_duration_since(scheduled_tx.header.timestamp.0);
+        scheduled_tx.header.expiration = execution_time
+            .0
+            .checked_add_signed(lifetime)
+            .map(DateTimeUtc);
+    } else if expiration < execution_time && !tx_args.force {
+        return Err(Error::Other(format!(
+            "The transaction expires at {expiration}, before the estimated \
+             time of {target} at {execution_time}"
+        )));
+    }
+    if is_signed {
+        // The signatures must already commit to the target, they can't be
+        // updated
+        let bound_target =
+            scheduled_tx.schedule().map(|schedule| schedule.target);
+        if bound_target != Some(*target) {
+            return Err(Error::Other(format!(
+                "The signed transaction is not bound to {target}, it must be \
+                 scheduled before being signed"
+            )));
+        }
+    } else {
+        // Bind the tx to its target before signing it
+        scheduled_tx.set_schedule(*target);
+        if let Some(account_public_keys_map) =
+            signing_data.account_public_keys_map.clone()
+        {
+            let mut wallet = context.wallet_mut().await;
+            let keypairs = signing_data
+                .public_keys
+                .iter()
+                .filter_map(|public_key| {
+                    signing::find_key_by_pk(&mut wallet, tx_args, public_key)
+                        .ok()
+                })
+                .collect::<Vec<common::SecretKey>>();
+            if keypairs.is_empty() && !tx_args.force {
+                return Err(Error::Other(format!(
+                    "No key of the account {owner} found to sign the \
+                     scheduled transaction"
+                )));
+            }
+            scheduled_tx.sign_raw(
+                keypairs,
+                account_public_keys_map,
+                Some(owner.clone()),
+            );
+        }
+    }
+
+    let data = ScheduledTx {
+        owner: owner.clone(),
+        target: *target,
+        gas_limit: *gas_limit,
+        fee: Fee {
+            amount_per_gas_unit: fee_amount,
+            token: tx_args.fee_token.clone(),
+        },
+        tx: scheduled_tx,
+    };
+    if data.get_prepaid_fee().is_none() {
+        return Err(Error::Other(
+            "The prepaid fee of the scheduled transaction overflows"
+                .to_string(),
+        ));
+    }
+
+    build(
+        context,
+        tx_args,
+        tx_code_path.clone(),
+        data,
+        do_nothing,
+        unshield,
+        fee_amount,
+        &signing_data,
+    )
+    .await
+    .map(|tx| (tx, signing_data))
+}
+
+/// Craft a transaction to cancel a scheduled transaction
+pub async fn build_cancel_scheduled_tx(
+    context: &impl Namada,
+    args::TxCancelScheduledTx {
+        tx: tx_args,
+        owner,
+        target,
+        hash,
+        tx_code_path,
+    }: &args::TxCancelScheduledTx,
+) -> Result<(Tx, SigningTxData)> {
+    let default_signer = Some(owner.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx_args,
+        Some(owner.clone()),
+        default_signer,
+    )
+    .await?;
+    let (fee_amount, _, unshield) = validate_fee_and_gen_unshield(
+        context,
+        tx_args,
+        &signing_data.fee_payer,
+    )
+    .await?;
+
+    let data = CancelScheduledTx {
+        owner: owner.clone(),
+        target: *target,
+        hash: *hash,
+    };
+    if !rpc::query_has_storage_key(context.client(), &data.storage_key())
+        .await?
+        && !tx_args.force
+    {
+        return Err(Error::Other(format!(
+            "No transaction {hash} scheduled at {target} by {owner}"
+        )));
+    }
+
+    build(
+        context,
+        tx_args,
+        tx_code_path.clone(),
+        data,
+        do_nothing,
+        unshield,
+        fee_amount,
+        &signing_data,
+    )
+    .await
+    .map(|tx| (tx, signing_data))
+}
+
+/// Craft a transaction to set the spending policy of an account
+pub async fn build_update_spending_policy(
+    context: &impl Namada,
+    args::TxUpdateSpendingPolicy {
+        tx: tx_args,
+        owner,
+        spending_caps,
+        allowed_destinations,
+        spending_keys,
+        spending_threshold,
+        tx_code_path,
+    }: &args::TxUpdateSpendingPolicy,
+) -> Result<(Tx, SigningTxData)> {
+    let default_signer = Some(owner.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx_args,
+        Some(owner.clone()),
+        default_signer,
+    )
+    .await?;
+    let (fee_amount, _, unshield) = validate_fee_and_gen_unshield(
+        context,
+        tx_args,
+        &signing_data.fee_payer,
+    )
+    .await?;
+
+    // Only the user VP of established accounts enforces spending policies
+    if !matches!(owner, Address::Established(_)) && !tx_args.force {
+        return Err(Error::Other(format!(
+            "Spending policies can only be set on established accounts, not \
+             on {owner}"
+        )));
+    }
+    let mut caps = BTreeMap::new();
+    for (token, cap) in spending_caps {
+        let cap = validate_amount(context, *cap, token, tx_args.force).await?;
+        caps.insert(token.clone(), cap.amount());
+    }
+    let spending_threshold = match spending_threshold {
+        Some(threshold) => *threshold,
+        None if spending_keys.len() <= 1 => spending_keys.len() as u8,
+        None => {
+            return Err(Error::Other(
+                "A spending threshold is required with multiple spending keys"
+                    .to_string(),
+            ));
+        }
+    };
+    let policy = SpendingPolicy {
+        spending_caps: caps,
+        allowed_destinations: allowed_destinations.iter().cloned().collect(),
+        spending_keys: spending_keys.clone(),
+        spending_threshold,
+    };
+    if !policy.is_valid() && !tx_args.force {
+        return Err(Error::Other(format!(
+            "The spending threshold {spending_threshold} cannot be met by the \
+             {} spending keys",
+            spending_keys.len()
+        )));
+    }
+
+    let data = UpdateSpendingPolicy {
+        owner: owner.clone(),
+        policy,
+    };
+
+    build(
+        context,
+        tx_args,
+        tx_code_path.clone(),
+        data,
+        do_nothing,
+        unshield,
+        fee_amount,
+        &signing_data,
+    )
+    .await
+    .map(|tx| (tx, signing_data))
+}
+
+/// Craft a transaction to remove the spending policy of an account
+pub async fn build_remove_spending_policy(
+    context: &impl Namada,
+    args::TxRemoveSpendingPolicy {
+        tx: tx_args,
+        owner,
+        tx_code_path,
+    }: &args::TxRemoveSpendingPolicy,
+) -> Result<(Tx, SigningTxData)> {
+    let default_signer = Some(owner.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx_args,
+        Some(owner.clone()),
+        default_signer,
+    )
+    .await?;
+    let (fee_amount, _, unshield) = validate_fee_and_gen_unshield(
+        context,
+        tx_args,
         &signing_data.fee_payer,
     )
+    .await?;
+
+    if rpc::get_spending_policy(context.client(), owner)
+        .await?
+        .is_none()
+        && !tx_args.force
+    {
+        return Err(Error::Other(format!(
+            "No spending policy is set on {owner}"
+        )));
+    }
+
+    let data = RemoveSpendingPolicy {
+        owner: owner.clone(),
+    };
+
+    build(
+        context,
+        tx_args,
+        tx_code_path.clone(),
+        data,
+        do_nothing,
+        unshield,
+        fee_amount,
+        &signing_data,
+    )
     .await
     .map(|tx| (tx, signing_data))
 }
@@ -2822,13 +3700,104 @@ pub async fn build_custom(
         &mut tx,
         unshield,
         fee_amount,
-        signing_data.fee_payer.clone(),
+        &signing_data,
+    )
+    .await?;
+
+    Ok((tx, signing_data))
+}
+
+/// Submit a batch of transactions
+pub async fn build_batch(
+    context: &impl Namada,
+    args::TxBatch {
+        tx: tx_args,
+        serialized_txs,
+        owner,
+    }: &args::TxBatch,
+) -> Result<(Tx, SigningTxData)> {
+    let default_signer = Some(owner.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx_args,
+        Some(owner.clone()),
+        default_signer,
+    )
+    .await?;
+    let (fee_amount, _, unshield) = validate_fee_and_gen_unshield(
+        context,
+        tx_args,
+        &signing_data.fee_payer,
+    )
+    .await?;
+
+    let txs = serialized_txs
+        .iter()
+        .map(|serialized_tx| {
+            Tx::deserialize(serialized_tx.as_ref()).map_err(|_| {
+                Error::Other("Invalid tx deserialization.".to_string())
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let chain_id = tx_args.chain_id.clone().unwrap();
+    let mut tx = compose_batch(Tx::new(chain_id, tx_args.expiration), txs)?;
+    if let Some(memo) = &tx_args.memo {
+        tx.add_memo(memo);
+    }
+
+    prepare_tx(
+        context.client(),
+        tx_args,
+        &mut tx,
+        unshield,
+        fee_amount,
+        &signing_data,
     )
     .await?;
 
     Ok((tx, signing_data))
 }
 
+/// Append the code and data of the given transactions, in order, to the inner
+/// transactions of the given batch. The headers and signatures of the given
+/// transactions are discarded, only their other sections are carried over.
+pub fn compose_batch(mut batch: Tx, txs: Vec<Tx>) -> Result<Tx> {
+    if txs.is_empty() {
+        return Err(Error::Other(
+            "A batch must contain at least one transaction".to_string(),
+        ));
+    }
+    let mut commitments = batch.batch();
+    for tx in txs {
+        if tx.header.chain_id != batch.header.chain_id {
+            return Err(Error::Other(format!(
+                "Transaction {} was built for chain {} instead of {}",
+                tx.header_hash(),
+                tx.header.chain_id,
+                batch.header.chain_id
+            )));
+        }
+        if tx.is_batch() {
+            commitments.extend(tx.batch());
+        } else {
+            commitments.push(TxCommitments {
+                code_hash: *tx.code_sechash(),
+                data_hash: *tx.data_sechash(),
+            });
+        }
+        for section in tx.sections {
+            if matches!(section, Section::Signature(_) | Section::Batch(_))
+                || batch.get_section(&section.get_hash()).is_some()
+            {
+                continue;
+            }
+            batch.add_section(section);
+        }
+    }
+    batch.set_batch(commitments);
+    Ok(batch)
+}
+
 /// Generate IBC shielded transfer
 pub async fn gen_ibc_shielded_transfer<N: Namada>(
     context: &N,
@@ -3149,3 +4118,48 @@ fn proposal_to_vec(proposal: OnChainProposal) -> Result<Vec<u8>> {
     borsh::to_vec(&proposal.content)
         .map_err(|e| Error::from(EncodingError::Conversion(e.to_string())))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gas_limit_estimate() {
+        let wrapped_tx_bytes = [0_u8; 100];
+        let wrapper_gas = namada_gas::get_wrapper_gas(100).unwrap();
+        let inner_gas = Gas::from_whole_units(1_000);
+        let total_gas = wrapper_gas.checked_add(inner_gas).unwrap();
+
+        // Without a margin, the estimate is the wrapper and inner gas
+        let estimate =
+            gas_limit_estimate(&wrapped_tx_bytes, inner_gas, Dec::one())
+                .unwrap();
+        assert_eq!(u64::from(estimate), total_gas.get_whole_gas_units());
+
+        // The gas of the wrapper grows with the size of the wrapped tx
+        let larger_estimate =
+            gas_limit_estimate(&[0_u8; 200], inner_gas, Dec::one()).unwrap();
+        assert!(u64::from(larger_estimate) > u64::from(estimate));
+
+        // The estimate is scaled by the multiplier and rounded up
+        let multiplier = Dec::new(15, 1).unwrap();
+        let estimate =
+            gas_limit_estimate(&wrapped_tx_bytes, inner_gas, multiplier)
+                .unwrap();
+        let total_gas = total_gas.get_whole_gas_units();
+        assert_eq!(u64::from(estimate), (3 * total_gas + 1) / 2);
+
+        // A negative multiplier is rejected
+        let multiplier = Dec::new(-1, 0).unwrap();
+        assert!(gas_limit_estimate(&wrapped_tx_bytes, inner_gas, multiplier)
+            .is_err());
+
+        // The total gas must not overflow
+        assert!(gas_limit_estimate(
+            &wrapped_tx_bytes,
+            u64::MAX.into(),
+            Dec::one()
+        )
+        .is_err());
+    }
+}
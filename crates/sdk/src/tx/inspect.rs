@@ -0,0 +1,494 @@
+//! Human-readable inspection of serialized transactions
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use borsh::BorshDeserialize;
+use data_encoding::HEXLOWER;
+use namada_account::{
+    GrantFeeAllowance, InitAccount, RemoveSpendingPolicy, RevokeFeeAllowance,
+    UpdateAccount, UpdateSpendingPolicy,
+};
+use namada_core::address::Address;
+use namada_core::hash::Hash;
+use namada_core::ibc::apps::transfer::types::msgs::transfer::MsgTransfer;
+use namada_core::ibc::primitives::proto::Any;
+use namada_core::ibc::MsgShieldedTransfer;
+use namada_core::key::common;
+use namada_core::token::Transfer;
+use namada_governance::storage::proposal::{
+    InitProposalData, VoteProposalData,
+};
+use namada_tx::data::pgf::UpdateStewardCommission;
+use namada_tx::data::schedule::{CancelScheduledTx, ScheduledTx};
+use namada_tx::data::{pos, TxType};
+use namada_tx::{Commitment, Section, Signer, Tx, TxCommitments};
+use prost::Message;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::{
+    TX_BECOME_VALIDATOR_WASM, TX_BOND_WASM, TX_BRIDGE_POOL_WASM,
+    TX_CANCEL_SCHEDULED_TX_WASM, TX_CHANGE_COMMISSION_WASM,
+    TX_CHANGE_CONSENSUS_KEY_WASM, TX_CHANGE_METADATA_WASM,
+    TX_CLAIM_REWARDS_WASM, TX_DEACTIVATE_VALIDATOR_WASM,
+    TX_GRANT_FEE_ALLOWANCE_WASM, TX_IBC_WASM, TX_INIT_ACCOUNT_WASM,
+    TX_INIT_PROPOSAL, TX_REACTIVATE_VALIDATOR_WASM, TX_REDELEGATE_WASM,
+    TX_REMOVE_SPENDING_POLICY_WASM, TX_RESIGN_STEWARD, TX_REVEAL_PK,
+    TX_REVOKE_FEE_ALLOWANCE_WASM, TX_SCHEDULE_TX_WASM, TX_TRANSFER_WASM,
+    TX_UNBOND_WASM, TX_UNJAIL_VALIDATOR_WASM, TX_UPDATE_ACCOUNT_WASM,
+    TX_UPDATE_SPENDING_POLICY_WASM, TX_UPDATE_STEWARD_COMMISSION,
+    TX_VOTE_PROPOSAL, TX_WITHDRAW_WASM,
+};
+use crate::eth_bridge_pool::PendingTransfer;
+
+/// Map the code hashes of the WASMs listed in a `checksums.json` file (from
+/// WASM names to WASM file names embedding the code hash) to their WASM name.
+/// Entries whose file name doesn't embed a valid hash are skipped.
+pub fn wasm_names_from_checksums(
+    checksums: &HashMap<String, String>,
+) -> HashMap<Hash, String> {
+    checksums
+        .iter()
+        .filter_map(|(name, file_name)| {
+            let hash = file_name.split('.').nth(1)?;
+            let hash = Hash::from_str(hash).ok()?;
+            Some((hash, name.clone()))
+        })
+        .collect()
+}
+
+/// Describe the given transaction in JSON: its header, the wrapper fee info
+/// if any, every section, the decoded data of every inner transaction and the
+/// sections covered by each signature. The code hashes are resolved against
+/// the given WASM names, which can be obtained with
+/// [`wasm_names_from_checksums`]. The data of an inner transaction is only
+/// decoded if its code is a known WASM, falling back to the code tag.
+pub fn inspect_tx(tx: &Tx, wasm_names: &HashMap<Hash, String>) -> Value {
+    let sections: Vec<Value> = tx
+        .sections
+        .iter()
+        .enumerate()
+        .map(|(index, section)| inspect_section(index, section, wasm_names))
+        .collect();
+
+    // The inner transactions of a scheduled transaction are designated by its
+    // schedule
+    let scheduled = tx.scheduled_inner();
+    let inner = scheduled.as_ref().unwrap_or(tx);
+    let inner_txs: Vec<Value> = if inner.is_batch() {
+        inner
+            .batch()
+            .iter()
+            .map(|commitments| inspect_inner_tx(tx, commitments, wasm_names))
+            .collect()
+    } else {
+        let commitments = TxCommitments {
+            code_hash: *inner.code_sechash(),
+            data_hash: *inner.data_sechash(),
+        };
+        vec![inspect_inner_tx(tx, &commitments, wasm_names)]
+    };
+
+    let signatures: Vec<Value> = tx
+        .sections
+        .iter()
+        .filter_map(|section| match section {
+            Section::Signature(signature) => {
+                Some(inspect_signature(tx, section, signature))
+            }
+            _ => None,
+        })
+        .collect();
+
+    json!({
+        "header_hash": tx.header_hash().to_string(),
+        "raw_header_hash": tx.raw_header_hash().to_string(),
+        "header": inspect_header(tx),
+        "wrapper": inspect_wrapper(tx),
+        "sections": sections,
+        "inner_txs": inner_txs,
+        "signatures": signatures,
+    })
+}
+
+/// Describe the header of the given transaction
+fn inspect_header(tx: &Tx) -> Value {
+    let header = tx.header();
+    let tx_type = match &header.tx_type {
+        TxType::Raw => "Raw",
+        TxType::Wrapper(_) => "Wrapper",
+        TxType::Decrypted(_) => "Decrypted",
+        TxType::Protocol(_) => "Protocol",
+    };
+    let batch: Vec<Value> = header
+        .batch
+        .iter()
+        .map(|commitments| {
+            json!({
+                "code_hash": commitments.code_hash.to_string(),
+                "data_hash": commitments.data_hash.to_string(),
+            })
+        })
+        .collect();
+    let memo = tx
+        .memo()
+        .map(|memo| String::from_utf8_lossy(&memo).into_owned());
+    json!({
+        "chain_id": header.chain_id.to_string(),
+        "timestamp": header.timestamp.to_rfc3339(),
+        "expiration": header.expiration.map(|exp| exp.to_rfc3339()),
+        "tx_type": tx_type,
+        "code_hash": header.code_hash.to_string(),
+        "data_hash": header.data_hash.to_string(),
+        "memo_hash": header.memo_hash.to_string(),
+        "memo": memo,
+        "batch": batch,
+    })
+}
+
+/// Describe the wrapper header of the given transaction, if any
+fn inspect_wrapper(tx: &Tx) -> Value {
+    let Some(wrapper) = tx.header().wrapper() else {
+        return Value::Null;
+    };
+    let max_fee = wrapper.get_tx_fee().ok().map(|fee| fee.to_string());
+    json!({
+        "fee_payer": wrapper.fee_payer().to_string(),
+        "public_key": wrapper.pk.to_string(),
+        "fee_granter": tx.fee_granter().map(|granter| granter.to_string()),
+        "fee_token": wrapper.fee.token.to_string(),
+        "fee_amount_per_gas_unit":
+            wrapper.fee.amount_per_gas_unit.to_string(),
+        "gas_limit": u64::from(wrapper.gas_limit),
+        "max_fee": max_fee,
+        "epoch": wrapper.epoch.to_string(),
+        "unshield_section_hash": wrapper
+            .unshield_section_hash
+            .map(|hash| hash.to_string()),
+    })
+}
+
+/// Describe a section of a transaction
+fn inspect_section(
+    index: usize,
+    section: &Section,
+    wasm_names: &HashMap<Hash, String>,
+) -> Value {
+    let details = match section {
+        Section::Code(code) | Section::ExtraData(code) => {
+            let code_hash = code.code.hash();
+            json!({
+                "code_hash": code_hash.to_string(),
+                "wasm_name": wasm_names.get(&code_hash),
+                "tag": code.tag,
+                "embedded": matches!(code.code, Commitment::Id(_)),
+            })
+        }
+        Section::Data(data) => json!({ "size": data.data.len() }),
+        Section::Signature(signature) => json!({
+            "targets": signature
+                .targets
+                .iter()
+                .map(Hash::to_string)
+                .collect::<Vec<_>>(),
+        }),
+        Section::Ciphertext(ciphertext) => {
+            json!({ "size": ciphertext.opaque.len() })
+        }
+        Section::MaspBuilder(builder) => {
+            json!({ "target": builder.target.to_string() })
+        }
+        Section::Batch(batch) => json!({ "inner_txs": batch.len() }),
+        Section::FeeGranter(granter) => {
+            json!({ "granter": granter.to_string() })
+        }
+        Section::Schedule(schedule) => json!({
+            "target": schedule.target.to_string(),
+            "code_hash": schedule.commitments.code_hash.to_string(),
+            "data_hash": schedule.commitments.data_hash.to_string(),
+        }),
+        Section::MaspTx(_) | Section::Header(_) => json!({}),
+    };
+    json!({
+        "index": index,
+        "kind": section_kind(section),
+        "hash": section.get_hash().to_string(),
+        "details": details,
+    })
+}
+
+/// Get the name of the kind of the given section
+fn section_kind(section: &Section) -> &'static str {
+    match section {
+        Section::Data(_) => "Data",
+        Section::ExtraData(_) => "ExtraData",
+        Section::Code(_) => "Code",
+        Section::Signature(_) => "Signature",
+        Section::Ciphertext(_) => "Ciphertext",
+        Section::MaspTx(_) => "MaspTx",
+        Section::MaspBuilder(_) => "MaspBuilder",
+        Section::Header(_) => "Header",
+        Section::Batch(_) => "Batch",
+        Section::FeeGranter(_) => "FeeGranter",
+        Section::Schedule(_) => "Schedule",
+    }
+}
+
+/// Describe the inner transaction with the given commitments and decode its
+/// data
+fn inspect_inner_tx(
+    tx: &Tx,
+    commitments: &TxCommitments,
+    wasm_names: &HashMap<Hash, String>,
+) -> Value {
+    let code = tx
+        .get_section(&commitments.code_hash)
+        .and_then(|section| section.code_sec());
+    let code_hash = code.as_ref().map(|code| code.code.hash());
+    let wasm_name = code_hash.and_then(|hash| wasm_names.get(&hash).cloned());
+    let tag = code.and_then(|code| code.tag);
+    // Only fall back to the tag, which is not verified, when the code hash is
+    // unknown
+    let name_source = if wasm_name.is_some() {
+        Some("checksums")
+    } else if tag.is_some() {
+        Some("tag")
+    } else {
+        None
+    };
+    let data = tx
+        .get_section(&commitments.data_hash)
+        .and_then(|section| section.data())
+        .map(|data| {
+            inspect_tx_data(wasm_name.as_deref().or(tag.as_deref()), &data.data)
+        });
+    json!({
+        "code_section_hash": commitments.code_hash.to_string(),
+        "data_section_hash": commitments.data_hash.to_string(),
+        "code_hash": code_hash.map(|hash| hash.to_string()),
+        "wasm_name": wasm_name.or(tag),
+        "wasm_name_source": name_source,
+        "data": data,
+    })
+}
+
+/// Decode the given tx data into the type expected by the WASM with the given
+/// name
+fn inspect_tx_data(wasm_name: Option<&str>, data: &[u8]) -> Value {
+    match wasm_name {
+        Some(TX_TRANSFER_WASM) => decode_tx_data::<Transfer>(data),
+        Some(TX_BOND_WASM | TX_UNBOND_WASM) => {
+            decode_tx_data::<pos::Bond>(data)
+        }
+        Some(TX_WITHDRAW_WASM | TX_CLAIM_REWARDS_WASM) => {
+            decode_tx_data::<pos::Withdraw>(data)
+        }
+        Some(TX_REDELEGATE_WASM) => decode_tx_data::<pos::Redelegation>(data),
+        Some(TX_CHANGE_COMMISSION_WASM) => {
+            decode_tx_data::<pos::CommissionChange>(data)
+        }
+        Some(TX_CHANGE_METADATA_WASM) => {
+            decode_tx_data::<pos::MetaDataChange>(data)
+        }
+        Some(TX_CHANGE_CONSENSUS_KEY_WASM) => {
+            decode_tx_data::<pos::ConsensusKeyChange>(data)
+        }
+        Some(TX_BECOME_VALIDATOR_WASM) => {
+            decode_tx_data::<pos::BecomeValidator>(data)
+        }
+        Some(
+            TX_UNJAIL_VALIDATOR_WASM
+            | TX_DEACTIVATE_VALIDATOR_WASM
+            | TX_REACTIVATE_VALIDATOR_WASM
+            | TX_RESIGN_STEWARD,
+        ) => decode_tx_data::<Address>(data),
+        Some(TX_REVEAL_PK) => decode_tx_data::<common::PublicKey>(data),
+        Some(TX_INIT_ACCOUNT_WASM) => decode_tx_data::<InitAccount>(data),
+        Some(TX_UPDATE_ACCOUNT_WASM) => decode_tx_data::<UpdateAccount>(data),
+        Some(TX_GRANT_FEE_ALLOWANCE_WASM) => {
+            decode_tx_data::<GrantFeeAllowance>(data)
+        }
+        Some(TX_REVOKE_FEE_ALLOWANCE_WASM) => {
+            decode_tx_data::<RevokeFeeAllowance>(data)
+        }
+        Some(TX_SCHEDULE_TX_WASM) => decode_tx_data::<ScheduledTx>(data),
+        Some(TX_CANCEL_SCHEDULED_TX_WASM) => {
+            decode_tx_data::<CancelScheduledTx>(data)
+        }
+        Some(TX_UPDATE_SPENDING_POLICY_WASM) => {
+            decode_tx_data::<UpdateSpendingPolicy>(data)
+        }
+        Some(TX_REMOVE_SPENDING_POLICY_WASM) => {
+            decode_tx_data::<RemoveSpendingPolicy>(data)
+        }
+        Some(TX_INIT_PROPOSAL) => decode_tx_data::<InitProposalData>(data),
+        Some(TX_VOTE_PROPOSAL) => decode_tx_data::<VoteProposalData>(data),
+        Some(TX_UPDATE_STEWARD_COMMISSION) => {
+            decode_tx_data::<UpdateStewardCommission>(data)
+        }
+        Some(TX_BRIDGE_POOL_WASM) => decode_tx_data::<PendingTransfer>(data),
+        Some(TX_IBC_WASM) => inspect_ibc_data(data),
+        _ => json!({ "raw": HEXLOWER.encode(data) }),
+    }
+}
+
+/// Decode the given tx data as the given type
+fn decode_tx_data<T>(data: &[u8]) -> Value
+where
+    T: BorshDeserialize + Serialize,
+{
+    match T::try_from_slice(data) {
+        Ok(decoded) => serde_json::to_value(decoded)
+            .unwrap_or_else(|err| json!({ "error": err.to_string() })),
+        Err(err) => json!({
+            "error": format!("Failed to decode the tx data: {}", err),
+            "raw": HEXLOWER.encode(data),
+        }),
+    }
+}
+
+/// Decode the data of an IBC transaction
+fn inspect_ibc_data(data: &[u8]) -> Value {
+    let transfer = if let Ok(any_msg) = Any::decode(data) {
+        match MsgTransfer::try_from(any_msg.clone()) {
+            Ok(transfer) => transfer,
+            Err(_) => {
+                return json!({
+                    "type_url": any_msg.type_url,
+                    "value": HEXLOWER.encode(&any_msg.value),
+                });
+            }
+        }
+    } else if let Ok(shielded) = MsgShieldedTransfer::try_from_slice(data) {
+        shielded.message
+    } else {
+        return json!({
+            "error": "Failed to decode the IBC message",
+            "raw": HEXLOWER.encode(data),
+        });
+    };
+    json!({
+        "type": "MsgTransfer",
+        "source_port": transfer.port_id_on_a.to_string(),
+        "source_channel": transfer.chan_id_on_a.to_string(),
+        "token": format!(
+            "{} {}",
+            transfer.packet_data.token.amount,
+            transfer.packet_data.token.denom
+        ),
+        "sender": transfer.packet_data.sender.to_string(),
+        "receiver": transfer.packet_data.receiver.to_string(),
+        "timeout_height": transfer.timeout_height_on_b.to_string(),
+        "timeout_timestamp": transfer.timeout_timestamp_on_b.to_string(),
+    })
+}
+
+/// Describe a signature section and the sections it covers
+fn inspect_signature(
+    tx: &Tx,
+    section: &Section,
+    signature: &namada_tx::Signature,
+) -> Value {
+    let signer = match &signature.signer {
+        Signer::Address(address) => json!({ "address": address.to_string() }),
+        Signer::PubKeys(pks) => json!({
+            "public_keys": pks
+                .iter()
+                .map(common::PublicKey::to_string)
+                .collect::<Vec<_>>(),
+        }),
+    };
+    let header_hash = tx.header_hash();
+    let raw_header_hash = tx.raw_header_hash();
+    let targets: Vec<Value> = signature
+        .targets
+        .iter()
+        .map(|target| {
+            let covered = if *target == header_hash {
+                "Header".to_string()
+            } else if *target == raw_header_hash {
+                "Raw header".to_string()
+            } else {
+                tx.sections
+                    .iter()
+                    .position(|section| section.get_hash() == *target)
+                    .map(|index| {
+                        format!(
+                            "{} (section {})",
+                            section_kind(&tx.sections[index]),
+                            index
+                        )
+                    })
+                    .unwrap_or_else(|| "Missing section".to_string())
+            };
+            json!({ "hash": target.to_string(), "section": covered })
+        })
+        .collect();
+    // The sections, other than signatures, that this signature leaves out
+    let uncovered: Vec<String> = tx
+        .sections
+        .iter()
+        .filter(|section| !matches!(section, Section::Signature(_)))
+        .map(Section::get_hash)
+        .filter(|hash| !signature.targets.contains(hash))
+        .map(|hash| hash.to_string())
+        .collect();
+    json!({
+        "section_hash": section.get_hash().to_string(),
+        "signer": signer,
+        "signature_indices": signature.signatures.keys().collect::<Vec<_>>(),
+        "targets": targets,
+        "uncovered_sections": uncovered,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use namada_core::address::testing::{
+        established_address_1, established_address_2, nam,
+    };
+    use namada_core::token::{Amount, DenominatedAmount};
+
+    use super::*;
+
+    #[test]
+    fn test_inspect_transfer() {
+        let code = b"transfer code".to_vec();
+        let code_hash = Hash::sha256(&code);
+        let transfer = Transfer {
+            source: established_address_1(),
+            target: established_address_2(),
+            token: nam(),
+            amount: DenominatedAmount::native(Amount::native_whole(10)),
+            key: None,
+            shielded: None,
+        };
+        let mut tx = Tx::from_type(TxType::Raw);
+        tx.add_code(code, Some("unverified tag".to_string()))
+            .add_data(transfer.clone());
+
+        // Without the checksums, the data cannot be decoded from the tag
+        let inspected = inspect_tx(&tx, &HashMap::new());
+        let inner = &inspected["inner_txs"][0];
+        assert_eq!(inner["wasm_name_source"], "tag");
+        assert!(inner["data"].get("raw").is_some());
+
+        let checksums = HashMap::from([(
+            TX_TRANSFER_WASM.to_string(),
+            format!(
+                "tx_transfer.{}.wasm",
+                code_hash.to_string().to_lowercase()
+            ),
+        )]);
+        let wasm_names = wasm_names_from_checksums(&checksums);
+        assert_eq!(wasm_names.get(&code_hash).unwrap(), TX_TRANSFER_WASM);
+
+        let inspected = inspect_tx(&tx, &wasm_names);
+        let inner = &inspected["inner_txs"][0];
+        assert_eq!(inner["wasm_name"], TX_TRANSFER_WASM);
+        assert_eq!(inner["wasm_name_source"], "checksums");
+        assert_eq!(inner["data"], serde_json::to_value(&transfer).unwrap());
+        assert!(inspected["wrapper"].is_null());
+    }
+}
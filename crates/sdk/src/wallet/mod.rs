@@ -17,6 +17,7 @@ use namada_core::key::*;
 use namada_core::masp::{
     ExtendedSpendingKey, ExtendedViewingKey, PaymentAddress,
 };
+use namada_core::storage::BlockHeight;
 pub use pre_genesis::gen_key_to_store;
 use rand::CryptoRng;
 use rand_core::RngCore;
@@ -389,6 +390,13 @@ impl<U> Wallet<U> {
         })
     }
 
+    /// Find the birthday of the viewing or spending key with the given alias
+    /// in the wallet, i.e. the block height before which it cannot own any
+    /// note
+    pub fn find_birthday(&self, alias: impl AsRef<str>) -> Option<BlockHeight> {
+        self.store.find_birthday(alias.as_ref())
+    }
+
     /// Find the payment address with the given alias in the wallet and return
     /// it
     pub fn find_payment_addr(
@@ -456,6 +464,15 @@ impl<U> Wallet<U> {
             .collect()
     }
 
+    /// Get all known birthdays of the viewing and spending keys by their alias
+    pub fn get_birthdays(&self) -> HashMap<String, BlockHeight> {
+        self.store
+            .get_birthdays()
+            .iter()
+            .map(|(alias, value)| (alias.into(), *value))
+            .collect()
+    }
+
     /// Get all known viewing keys by their alias
     pub fn get_spending_keys(
         &self,
@@ -539,6 +556,7 @@ impl<U: WalletIo> Wallet<U> {
             spend_key,
             password,
             Some(derivation_path),
+            None,
         )
         .map(|alias| (alias, spend_key))
     }
@@ -593,17 +611,27 @@ impl<U: WalletIo> Wallet<U> {
         .map(|alias| (alias, sk))
     }
 
-    /// Generate a spending key similarly to how it's done for keypairs
+    /// Generate a spending key similarly to how it's done for keypairs. The
+    /// birthday of the new key, if any, should not be greater than the
+    /// current block height.
     pub fn gen_store_spending_key(
         &mut self,
         alias: String,
         password: Option<Zeroizing<String>>,
+        birthday: Option<BlockHeight>,
         force_alias: bool,
         csprng: &mut (impl CryptoRng + RngCore),
     ) -> Option<(String, ExtendedSpendingKey)> {
         let spend_key = gen_spending_key(csprng);
-        self.insert_spending_key(alias, force_alias, spend_key, password, None)
-            .map(|alias| (alias, spend_key))
+        self.insert_spending_key(
+            alias,
+            force_alias,
+            spend_key,
+            password,
+            None,
+            birthday,
+        )
+        .map(|alias| (alias, spend_key))
     }
 
     /// Generate a new keypair, derive an implicit address from its public key
@@ -703,7 +731,8 @@ impl<U: WalletIo> Wallet<U> {
     /// If no encryption password is provided, the key will be stored raw
     /// without encryption.
     /// Stores the key in decrypted key cache and returns the alias of the key
-    /// and the key itself.
+    /// and the key itself. The birthday of a newly generated key, if any,
+    /// should not be greater than the current block height.
     pub fn derive_store_hd_spendind_key(
         &mut self,
         alias: String,
@@ -711,6 +740,7 @@ impl<U: WalletIo> Wallet<U> {
         seed: Seed,
         derivation_path: DerivationPath,
         password: Option<Zeroizing<String>>,
+        birthday: Option<BlockHeight>,
     ) -> Option<(String, ExtendedSpendingKey)> {
         let spend_key =
             derive_hd_spending_key(seed.as_bytes(), derivation_path.clone());
@@ -720,6 +750,7 @@ impl<U: WalletIo> Wallet<U> {
             spend_key,
             password,
             Some(derivation_path),
+            birthday,
         )
         .map(|alias| (alias, spend_key))
     }
@@ -998,19 +1029,27 @@ impl<U: WalletIo> Wallet<U> {
             .map(Into::into)
     }
 
-    /// Insert a viewing key into the wallet under the given alias
+    /// Insert a viewing key into the wallet under the given alias, together
+    /// with its birthday if known
     pub fn insert_viewing_key(
         &mut self,
         alias: String,
         view_key: ExtendedViewingKey,
+        birthday: Option<BlockHeight>,
         force_alias: bool,
     ) -> Option<String> {
         self.store
-            .insert_viewing_key::<U>(alias.into(), view_key, force_alias)
+            .insert_viewing_key::<U>(
+                alias.into(),
+                view_key,
+                birthday,
+                force_alias,
+            )
             .map(Into::into)
     }
 
-    /// Insert a spending key into the wallet under the given alias
+    /// Insert a spending key into the wallet under the given alias, together
+    /// with its birthday if known
     pub fn insert_spending_key(
         &mut self,
         alias: String,
@@ -1018,6 +1057,7 @@ impl<U: WalletIo> Wallet<U> {
         spend_key: ExtendedSpendingKey,
         password: Option<Zeroizing<String>>,
         path: Option<DerivationPath>,
+        birthday: Option<BlockHeight>,
     ) -> Option<String> {
         self.store
             .insert_spending_key::<U>(
@@ -1025,6 +1065,7 @@ impl<U: WalletIo> Wallet<U> {
                 spend_key,
                 password,
                 path,
+                birthday,
                 force_alias,
             )
             .map(|alias| {
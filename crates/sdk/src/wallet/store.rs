@@ -12,6 +12,7 @@ use namada_core::key::*;
 use namada_core::masp::{
     ExtendedSpendingKey, ExtendedViewingKey, PaymentAddress,
 };
+use namada_core::storage::BlockHeight;
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroizing;
 
@@ -70,6 +71,10 @@ pub struct Store {
     public_keys: BTreeMap<Alias, common::PublicKey>,
     /// Known derivation paths
     derivation_paths: BTreeMap<Alias, DerivationPath>,
+    /// Known birthdays of the viewing and spending keys, i.e. the block
+    /// heights before which they cannot own any note
+    #[serde(default)]
+    birthdays: BTreeMap<Alias, BlockHeight>,
     /// Namada address book
     addresses: BiBTreeMap<Alias, Address>,
     /// Known mappings of public key hashes to their aliases in the `keys`
@@ -142,6 +147,11 @@ impl Store {
         self.view_keys.get(&alias.into())
     }
 
+    /// Find the birthday of the viewing or spending key with the given alias
+    pub fn find_birthday(&self, alias: impl AsRef<str>) -> Option<BlockHeight> {
+        self.birthdays.get(&alias.into()).copied()
+    }
+
     /// Find the payment address with the given alias and return it
     pub fn find_payment_addr(
         &self,
@@ -260,6 +270,12 @@ impl Store {
         &self.spend_keys
     }
 
+    /// Get all known birthdays of the viewing and spending keys by their
+    /// alias.
+    pub fn get_birthdays(&self) -> &BTreeMap<Alias, BlockHeight> {
+        &self.birthdays
+    }
+
     /// Add validator data to the store
     pub fn add_validator_data(
         &mut self,
@@ -367,6 +383,7 @@ impl Store {
         spendkey: ExtendedSpendingKey,
         password: Option<Zeroizing<String>>,
         path: Option<DerivationPath>,
+        birthday: Option<BlockHeight>,
         force: bool,
     ) -> Option<Alias> {
         // abort if the alias is reserved
@@ -385,7 +402,7 @@ impl Store {
                 ConfirmationResponse::Replace => {}
                 ConfirmationResponse::Reselect(new_alias) => {
                     return self.insert_spending_key::<U>(
-                        new_alias, spendkey, password, path, false,
+                        new_alias, spendkey, password, path, birthday, false,
                     );
                 }
                 ConfirmationResponse::Skip => return None,
@@ -400,6 +417,7 @@ impl Store {
             zip32::ExtendedFullViewingKey::from(&spendkey.into()).into();
         self.view_keys.insert(alias.clone(), viewkey);
         path.map(|p| self.derivation_paths.insert(alias.clone(), p));
+        birthday.map(|b| self.birthdays.insert(alias.clone(), b));
         Some(alias)
     }
 
@@ -408,6 +426,7 @@ impl Store {
         &mut self,
         alias: Alias,
         viewkey: ExtendedViewingKey,
+        birthday: Option<BlockHeight>,
         force: bool,
     ) -> Option<Alias> {
         // abort if the alias is reserved
@@ -424,14 +443,16 @@ impl Store {
             match U::show_overwrite_confirmation(&alias, "a viewing key") {
                 ConfirmationResponse::Replace => {}
                 ConfirmationResponse::Reselect(new_alias) => {
-                    return self
-                        .insert_viewing_key::<U>(new_alias, viewkey, false);
+                    return self.insert_viewing_key::<U>(
+                        new_alias, viewkey, birthday, false,
+                    );
                 }
                 ConfirmationResponse::Skip => return None,
             }
         }
         self.remove_alias(&alias);
         self.view_keys.insert(alias.clone(), viewkey);
+        birthday.map(|b| self.birthdays.insert(alias.clone(), b));
         Some(alias)
     }
 
@@ -585,6 +606,7 @@ impl Store {
         self.pkhs.retain(|_key, val| val != alias);
         self.public_keys.remove(alias);
         self.derivation_paths.remove(alias);
+        self.birthdays.remove(alias);
     }
 
     /// Extend this store from another store (typically pre-genesis).
@@ -597,6 +619,7 @@ impl Store {
             secret_keys,
             public_keys,
             derivation_paths,
+            birthdays,
             addresses,
             pkhs,
             validator_data: _,
@@ -608,6 +631,7 @@ impl Store {
         secret_keys.extend(store.secret_keys);
         public_keys.extend(store.public_keys);
         derivation_paths.extend(store.derivation_paths);
+        birthdays.extend(store.birthdays);
         addresses.extend(store.addresses);
         pkhs.extend(store.pkhs);
         address_vp_types.extend(store.address_vp_types);
@@ -793,6 +817,7 @@ mod test_wallet {
     use data_encoding::HEXLOWER;
 
     use super::*;
+    use crate::wallet::fs::FsWalletUtils;
 
     #[test]
     fn gen_sk_from_mnemonic_code_secp256k1() {
@@ -947,4 +972,30 @@ mod test_wallet {
             do_test_gen_sk_from_seed_and_derivation_path(SCHEME, SEED, "m/0'/2147483647'/1'/2147483646'/2'", "551d333177df541ad876a60ea71f00447931c0a9da16f227c11ea080d7391b8d");
         }
     }
+
+    #[test]
+    fn test_spending_key_birthday() {
+        let mut store = Store::default();
+        let spendkey = ExtendedSpendingKey::from(
+            zip32::ExtendedSpendingKey::master(&[0; 32]),
+        );
+        store
+            .insert_spending_key::<FsWalletUtils>(
+                "shielded".into(),
+                spendkey,
+                None,
+                None,
+                Some(BlockHeight(42)),
+                false,
+            )
+            .expect("Inserting a spending key cannot fail");
+        assert_eq!(store.find_birthday("shielded"), Some(BlockHeight(42)));
+
+        // The birthday is persisted with the key
+        let decoded = Store::decode(store.encode()).unwrap();
+        assert_eq!(decoded.find_birthday("shielded"), Some(BlockHeight(42)));
+
+        store.remove_alias(&"shielded".into());
+        assert_eq!(store.find_birthday("shielded"), None);
+    }
 }
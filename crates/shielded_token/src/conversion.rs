@@ -580,6 +580,7 @@ mod tests {
             fee_unshielding_gas_limit: 0,
             fee_unshielding_descriptions_limit: 0,
             minimum_gas_price: Default::default(),
+            base_fee_params: None,
         };
 
         // Initialize the state
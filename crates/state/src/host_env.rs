@@ -1,7 +1,7 @@
 use std::cell::RefCell;
 
 use namada_core::validity_predicate::VpSentinel;
-use namada_gas::{GasMetering, TxGasMeter, VpGasMeter};
+use namada_gas::{GasCategory, GasMetering, TxGasMeter, VpGasMeter};
 use namada_tx::data::TxSentinel;
 
 use crate::in_memory::InMemory;
@@ -67,14 +67,21 @@ where
     }
 
     fn charge_gas(&self, gas: u64) -> Result<()> {
-        self.gas_meter.borrow_mut().consume(gas).map_err(|err| {
-            self.sentinel.borrow_mut().set_out_of_gas();
-            tracing::info!(
-                "Stopping transaction execution because of gas error: {}",
-                err
-            );
-            Error::Gas(err)
-        })
+        self.charge_gas_in(GasCategory::Other, gas)
+    }
+
+    fn charge_gas_in(&self, category: GasCategory, gas: u64) -> Result<()> {
+        self.gas_meter
+            .borrow_mut()
+            .consume_in(category, gas)
+            .map_err(|err| {
+                self.sentinel.borrow_mut().set_out_of_gas();
+                tracing::info!(
+                    "Stopping transaction execution because of gas error: {}",
+                    err
+                );
+                Error::Gas(err)
+            })
     }
 }
 
@@ -115,13 +122,20 @@ where
     }
 
     fn charge_gas(&self, gas: u64) -> Result<()> {
-        self.gas_meter.borrow_mut().consume(gas).map_err(|err| {
-            self.sentinel.borrow_mut().set_out_of_gas();
-            tracing::info!(
-                "Stopping VP execution because of gas error: {}",
-                err
-            );
-            Error::Gas(err)
-        })
+        self.charge_gas_in(GasCategory::Other, gas)
+    }
+
+    fn charge_gas_in(&self, category: GasCategory, gas: u64) -> Result<()> {
+        self.gas_meter
+            .borrow_mut()
+            .consume_in(category, gas)
+            .map_err(|err| {
+                self.sentinel.borrow_mut().set_out_of_gas();
+                tracing::info!(
+                    "Stopping VP execution because of gas error: {}",
+                    err
+                );
+                Error::Gas(err)
+            })
     }
 }
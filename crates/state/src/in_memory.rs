@@ -1,17 +1,19 @@
 use namada_core::address::{Address, EstablishedAddressGen, InternalAddress};
 use namada_core::borsh::{BorshDeserialize, BorshSerialize};
 use namada_core::chain::{ChainId, CHAIN_ID_LENGTH};
+use namada_core::event::Event;
 use namada_core::time::DateTimeUtc;
 use namada_core::{encode, ethereum_structs};
 use namada_gas::MEMORY_ACCESS_GAS_PER_BYTE;
 use namada_merkle_tree::{MerkleRoot, MerkleTree};
 use namada_parameters::{EpochDuration, Parameters};
 use namada_storage::conversion_state::ConversionState;
+use namada_storage::event_store::EventsRetention;
 use namada_storage::tx_queue::{ExpiredTxsQueue, TxQueue};
 use namada_storage::{
-    BlockHash, BlockHeight, BlockResults, Epoch, Epochs, EthEventsQueue,
-    Header, Key, KeySeg, StorageHasher, TxIndex, BLOCK_HASH_LENGTH,
-    BLOCK_HEIGHT_LENGTH, EPOCH_TYPE_LENGTH,
+    BlockHash, BlockHeight, BlockResults, DiffsPruning, Epoch, Epochs,
+    EthEventsQueue, Header, Key, KeySeg, StorageHasher, TxIndex,
+    BLOCK_HASH_LENGTH, BLOCK_HEIGHT_LENGTH, EPOCH_TYPE_LENGTH,
 };
 
 use crate::{Error, Result};
@@ -69,6 +71,14 @@ where
     pub eth_events_queue: EthEventsQueue,
     /// How many block heights in the past can the storage be queried
     pub storage_read_past_height_limit: Option<u64>,
+    /// The pruning mode of the subspace diffs, applied on every commit
+    pub diffs_pruning: DiffsPruning,
+    /// The events emitted in the current block, which are written to the
+    /// event store on commit
+    pub block_events: Vec<Event>,
+    /// The retention of the events in the event store, applied on every
+    /// commit
+    pub events_retention: EventsRetention,
 }
 
 /// Last committed block
@@ -145,6 +155,9 @@ where
             ethereum_height: None,
             eth_events_queue: EthEventsQueue::default(),
             storage_read_past_height_limit,
+            diffs_pruning: DiffsPruning::default(),
+            block_events: Vec::new(),
+            events_retention: EventsRetention::default(),
         }
     }
 
@@ -0,0 +1,212 @@
+//! Offline integrity check of the DB. The merkle tree is rebuilt from scratch
+//! from the subspace and compared with the merkle tree stored for the last
+//! committed block.
+
+use std::collections::{BTreeMap, HashMap};
+
+use namada_core::borsh::BorshSerializeExt;
+use namada_merkle_tree::MerkleRoot;
+
+use crate::wl_state::restore_merkle_tree;
+use crate::{
+    is_pending_transfer_key, BlockHeight, DBIter, Error, Key, KeySeg,
+    MerkleTree, Result, StorageHasher, StoreType, DB,
+};
+
+/// The result of a DB integrity check at the last committed height
+#[derive(Debug, Clone)]
+pub struct IntegrityReport {
+    /// The last committed height
+    pub height: BlockHeight,
+    /// The merkle root stored for the last committed height
+    pub stored_root: MerkleRoot,
+    /// The merkle root rebuilt from the subspace
+    pub rebuilt_root: MerkleRoot,
+    /// The check of every sub-tree
+    pub subtrees: Vec<SubtreeIntegrity>,
+    /// The first keys whose diffs at the last height don't match the subspace
+    pub inconsistent_diffs: Vec<Key>,
+    /// The subspace and diffs keys that couldn't be parsed
+    pub invalid_keys: Vec<String>,
+}
+
+/// The result of a sub-tree integrity check
+#[derive(Debug, Clone)]
+pub struct SubtreeIntegrity {
+    /// The type of the sub-tree
+    pub store_type: StoreType,
+    /// The root restored from the stored merkle tree, or the error that
+    /// prevented restoring it
+    pub stored_root: std::result::Result<MerkleRoot, String>,
+    /// The root rebuilt from the subspace
+    pub rebuilt_root: MerkleRoot,
+    /// The first subspace keys whose leaf in the stored sub-tree differs
+    /// from the rebuilt one
+    pub divergent_keys: Vec<Key>,
+}
+
+impl IntegrityReport {
+    /// Returns `true` if no inconsistency was found
+    pub fn is_ok(&self) -> bool {
+        self.stored_root == self.rebuilt_root
+            && self.subtrees.iter().all(SubtreeIntegrity::is_ok)
+            && self.inconsistent_diffs.is_empty()
+            && self.invalid_keys.is_empty()
+    }
+}
+
+impl SubtreeIntegrity {
+    /// Returns `true` if the stored sub-tree matches the rebuilt one
+    pub fn is_ok(&self) -> bool {
+        self.stored_root.as_ref() == Ok(&self.rebuilt_root)
+            && self.divergent_keys.is_empty()
+    }
+}
+
+/// Check the integrity of the DB at the last committed height. Every
+/// sub-tree is rebuilt from the merklized keys of the subspace and compared
+/// with the sub-tree restored from the stored merkle tree stores and diffs.
+/// The subspace diffs of the last height are checked against the subspace.
+/// At most `max_reported_keys` keys are reported for every kind of
+/// inconsistency.
+pub fn check_db_integrity<D, H>(
+    db: &D,
+    merkle_tree_key_filter: fn(&Key) -> bool,
+    max_reported_keys: usize,
+) -> Result<IntegrityReport>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+    H: StorageHasher,
+{
+    let last_block = db.read_last_block()?.ok_or_else(|| {
+        Error::DBError("No committed block found in the DB".to_string())
+    })?;
+    let height = last_block.height;
+    let stored_root =
+        MerkleRoot(last_block.merkle_tree_stores.get_root(StoreType::Base).0);
+
+    let restored: Vec<(StoreType, Result<MerkleTree<H>>)> =
+        StoreType::iter_subtrees()
+            .map(|st| {
+                let tree = restore_merkle_tree::<D, H>(
+                    db,
+                    merkle_tree_key_filter,
+                    &last_block.pred_epochs,
+                    height,
+                    Some(*st),
+                );
+                (*st, tree)
+            })
+            .collect();
+
+    // Rebuild the whole tree from the subspace
+    let mut rebuilt = MerkleTree::<H>::default();
+    let mut merklized_keys: HashMap<StoreType, Vec<Key>> = HashMap::new();
+    let mut invalid_keys = Vec::new();
+    for (key, value, _gas) in db.iter_prefix(None) {
+        let key = match Key::parse(&key) {
+            Ok(key) => key,
+            Err(_) => {
+                invalid_keys.push(key);
+                continue;
+            }
+        };
+        if !merkle_tree_key_filter(&key) {
+            continue;
+        }
+        let (store_type, _) = StoreType::sub_key(&key)?;
+        if is_pending_transfer_key(&key) {
+            // The bridge pool tree holds the height at which a transfer was
+            // added, which can only be recovered from the stored tree
+            let inserted_height = restored
+                .iter()
+                .find(|(st, _)| *st == StoreType::BridgePool)
+                .and_then(|(_, tree)| tree.as_ref().ok())
+                .and_then(|tree| tree.get(&key).ok())
+                .unwrap_or_else(|| height.serialize_to_vec());
+            rebuilt.update(&key, inserted_height)?;
+        } else {
+            rebuilt.update(&key, value)?;
+        }
+        merklized_keys.entry(store_type).or_default().push(key);
+    }
+
+    let subtrees = restored
+        .iter()
+        .map(|(st, stored)| {
+            let rebuilt_root = rebuilt.sub_root(st);
+            match stored {
+                Ok(stored) => {
+                    let divergent_keys = merklized_keys
+                        .get(st)
+                        .into_iter()
+                        .flatten()
+                        .filter(|key| {
+                            stored.get(key).ok() != rebuilt.get(key).ok()
+                        })
+                        .take(max_reported_keys)
+                        .cloned()
+                        .collect();
+                    SubtreeIntegrity {
+                        store_type: *st,
+                        stored_root: Ok(stored.sub_root(st)),
+                        rebuilt_root,
+                        divergent_keys,
+                    }
+                }
+                Err(err) => SubtreeIntegrity {
+                    store_type: *st,
+                    stored_root: Err(err.to_string()),
+                    rebuilt_root,
+                    divergent_keys: vec![],
+                },
+            }
+        })
+        .collect();
+
+    // Check that the diffs of the last height lead to the subspace
+    let mut inconsistent_diffs = Vec::new();
+    let new_diffs: BTreeMap<String, Vec<u8>> = db
+        .iter_new_diffs(height, None)
+        .map(|(key, value, _gas)| (key, value))
+        .collect();
+    let deleted_keys = db
+        .iter_old_diffs(height, None)
+        .filter_map(|(key, _value, _gas)| {
+            (!new_diffs.contains_key(&key)).then_some(key)
+        })
+        .collect::<Vec<_>>();
+    for (key, new_value) in &new_diffs {
+        let value = db.read_subspace_val(&Key::from(key.to_db_key()))?;
+        if value.as_ref() != Some(new_value) {
+            inconsistent_diffs.push(key);
+        }
+    }
+    for key in &deleted_keys {
+        if db.read_subspace_val(&Key::from(key.to_db_key()))?.is_some() {
+            inconsistent_diffs.push(key);
+        }
+    }
+    let mut inconsistent_diffs = inconsistent_diffs
+        .into_iter()
+        .filter_map(|key| match Key::parse(key) {
+            Ok(key) => Some(key),
+            Err(_) => {
+                invalid_keys.push(key.clone());
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    inconsistent_diffs.sort();
+    inconsistent_diffs.truncate(max_reported_keys);
+    invalid_keys.truncate(max_reported_keys);
+
+    Ok(IntegrityReport {
+        height,
+        stored_root,
+        rebuilt_root: rebuilt.root(),
+        subtrees,
+        inconsistent_diffs,
+        invalid_keys,
+    })
+}
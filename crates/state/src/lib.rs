@@ -2,6 +2,8 @@
 
 mod host_env;
 mod in_memory;
+mod integrity;
+pub mod migrations;
 mod wl_state;
 pub mod write_log;
 
@@ -10,6 +12,7 @@ use std::iter::Peekable;
 
 pub use host_env::{TxHostEnvState, VpHostEnvState};
 pub use in_memory::{BlockStorage, InMemory, LastBlock};
+pub use integrity::{check_db_integrity, IntegrityReport, SubtreeIntegrity};
 use namada_core::address::Address;
 use namada_core::eth_bridge_pool::is_pending_transfer_key;
 pub use namada_core::hash::Sha256Hasher;
@@ -20,8 +23,9 @@ pub use namada_core::storage::{
     EPOCH_TYPE_LENGTH,
 };
 use namada_core::tendermint::merkle::proof::ProofOps;
+use namada_core::time::DateTimeUtc;
 use namada_gas::{
-    MEMORY_ACCESS_GAS_PER_BYTE, STORAGE_ACCESS_GAS_PER_BYTE,
+    GasCategory, MEMORY_ACCESS_GAS_PER_BYTE, STORAGE_ACCESS_GAS_PER_BYTE,
     STORAGE_WRITE_GAS_PER_BYTE,
 };
 use namada_merkle_tree::Error as MerkleTreeError;
@@ -33,16 +37,20 @@ pub use namada_storage as storage;
 pub use namada_storage::conversion_state::{
     ConversionState, WithConversionState,
 };
+pub use namada_storage::event_store::{self, EventIndex, EventsRetention};
 pub use namada_storage::types::{KVBytes, PrefixIterator};
 pub use namada_storage::{
     collections, iter_prefix, iter_prefix_bytes, iter_prefix_with_filter,
-    mockdb, tx_queue, BlockStateRead, BlockStateWrite, DBIter, DBWriteBatch,
-    DbError, DbResult, Error as StorageError, OptionExt,
-    Result as StorageResult, ResultExt, StorageHasher, StorageRead,
-    StorageWrite, DB,
+    merge_height_diffs, mockdb, take_snapshot_page, tx_queue, BlockStateRead,
+    BlockStateWrite, DBIter, DBWriteBatch, DbError, DbResult, DiffsPruning,
+    DiffsPruningJob, Error as StorageError, HeightDiffs, OptionExt,
+    PrunedDiffs, Result as StorageResult, ResultExt, SnapshotEntry,
+    SnapshotStore, StorageHasher, StorageRead, StorageWrite, DB,
 };
 use thiserror::Error;
-pub use wl_state::{FullAccessState, TempWlState, WlState};
+pub use wl_state::{
+    restore_merkle_tree, FullAccessState, TempWlState, WlState,
+};
 use write_log::WriteLog;
 
 /// A result of a function that may fail
@@ -70,6 +78,13 @@ pub trait StateRead: StorageRead + Debug {
 
     fn charge_gas(&self, gas: u64) -> Result<()>;
 
+    /// Charge gas of the given category. The category is only recorded by
+    /// the states that meter gas.
+    fn charge_gas_in(&self, category: GasCategory, gas: u64) -> Result<()> {
+        let _ = category;
+        self.charge_gas(gas)
+    }
+
     /// Check if the given key is present in storage. Returns the result and the
     /// gas cost.
     fn db_has_key(&self, key: &storage::Key) -> Result<(bool, u64)> {
@@ -176,9 +191,14 @@ pub trait State: StateRead + StorageWrite {
     fn split_borrow(&mut self)
     -> (&mut WriteLog, &InMemory<Self::H>, &Self::D);
 
-    /// Write the provided tx hash to write log.
-    fn write_tx_hash(&mut self, hash: Hash) -> write_log::Result<()> {
-        self.write_log_mut().write_tx_hash(hash)
+    /// Write the provided tx hash to write log, to be kept until the given
+    /// expiration of the tx.
+    fn write_tx_hash(
+        &mut self,
+        hash: Hash,
+        expiration: DateTimeUtc,
+    ) -> write_log::Result<()> {
+        self.write_log_mut().write_tx_hash(hash, expiration)
     }
 }
 
@@ -198,7 +218,10 @@ macro_rules! impl_storage_read {
             ) -> namada_storage::Result<Option<Vec<u8>>> {
                 // try to read from the write log first
                 let (log_val, gas) = self.write_log().read(key);
-                self.charge_gas(gas).into_storage_result()?;
+                self.charge_gas_in(
+                    namada_gas::GasCategory::StorageRead,
+                    gas,
+                ).into_storage_result()?;
                 match log_val {
                     Some(write_log::StorageModification::Write { ref value }) => {
                         Ok(Some(value.clone()))
@@ -213,7 +236,10 @@ macro_rules! impl_storage_read {
                     None => {
                         // when not found in write log, try to read from the storage
                         let (value, gas) = self.db_read(key).into_storage_result()?;
-                        self.charge_gas(gas).into_storage_result()?;
+                        self.charge_gas_in(
+                            namada_gas::GasCategory::StorageRead,
+                            gas,
+                        ).into_storage_result()?;
                         Ok(value)
                     }
                 }
@@ -222,7 +248,10 @@ macro_rules! impl_storage_read {
             fn has_key(&self, key: &storage::Key) -> namada_storage::Result<bool> {
                 // try to read from the write log first
                 let (log_val, gas) = self.write_log().read(key);
-                self.charge_gas(gas).into_storage_result()?;
+                self.charge_gas_in(
+                    namada_gas::GasCategory::StorageRead,
+                    gas,
+                ).into_storage_result()?;
                 match log_val {
                     Some(&write_log::StorageModification::Write { .. })
                     | Some(&write_log::StorageModification::InitAccount { .. })
@@ -234,7 +263,10 @@ macro_rules! impl_storage_read {
                     None => {
                         // when not found in write log, try to check the storage
                         let (present, gas) = self.db_has_key(key).into_storage_result()?;
-                        self.charge_gas(gas).into_storage_result()?;
+                        self.charge_gas_in(
+                            namada_gas::GasCategory::StorageRead,
+                            gas,
+                        ).into_storage_result()?;
                         Ok(present)
                     }
                 }
@@ -246,7 +278,10 @@ macro_rules! impl_storage_read {
             ) -> namada_storage::Result<Self::PrefixIter<'iter>> {
                 let (iter, gas) =
                     iter_prefix_post(self.write_log(), self.db(), prefix);
-                self.charge_gas(gas).into_storage_result()?;
+                self.charge_gas_in(
+                    namada_gas::GasCategory::StorageRead,
+                    gas,
+                ).into_storage_result()?;
                 Ok(iter)
             }
 
@@ -255,7 +290,10 @@ macro_rules! impl_storage_read {
                 iter: &mut Self::PrefixIter<'iter>,
             ) -> namada_storage::Result<Option<(String, Vec<u8>)>> {
                 iter.next().map(|(key, val, gas)| {
-                    self.charge_gas(gas).into_storage_result()?;
+                    self.charge_gas_in(
+                        namada_gas::GasCategory::StorageRead,
+                        gas,
+                    ).into_storage_result()?;
                     Ok((key, val))
                 }).transpose()
             }
@@ -346,7 +384,10 @@ macro_rules! impl_storage_write {
                     .write_log_mut()
                     .write(key, val.as_ref().to_vec())
                     .into_storage_result()?;
-                self.charge_gas(gas).into_storage_result()?;
+                self.charge_gas_in(
+                    namada_gas::GasCategory::StorageWrite,
+                    gas,
+                ).into_storage_result()?;
                 Ok(())
             }
 
@@ -355,7 +396,10 @@ macro_rules! impl_storage_write {
                     .write_log_mut()
                     .delete(key)
                     .into_storage_result()?;
-                self.charge_gas(gas).into_storage_result()?;
+                self.charge_gas_in(
+                    namada_gas::GasCategory::StorageWrite,
+                    gas,
+                ).into_storage_result()?;
                 Ok(())
             }
         }
@@ -638,6 +682,9 @@ pub mod testing {
                 ethereum_height: None,
                 eth_events_queue: EthEventsQueue::default(),
                 storage_read_past_height_limit: Some(1000),
+                diffs_pruning: DiffsPruning::default(),
+                block_events: Vec::new(),
+                events_retention: EventsRetention::default(),
             }
         }
     }
@@ -742,6 +789,7 @@ mod tests {
                 fee_unshielding_gas_limit: 20_000,
                 fee_unshielding_descriptions_limit: 15,
                 minimum_gas_price: BTreeMap::default(),
+                base_fee_params: None,
             };
             namada_parameters::init_storage(&parameters, &mut state).unwrap();
             // Initialize pred_epochs to the current height
@@ -1046,6 +1094,58 @@ mod tests {
         assert!(res2.is_none());
     }
 
+    #[test]
+    fn test_db_integrity() {
+        let mut state = TestState::default();
+        state.in_mem_mut().block.height = BlockHeight(1);
+
+        let key1 = test_key_1();
+        let key2 = test_key_2();
+        state.write(&key1, 1u64).unwrap();
+        state.write(&key2, 2u64).unwrap();
+        state.commit_block().unwrap();
+
+        let report = check_db_integrity::<_, Sha256Hasher>(
+            state.db(),
+            merklize_all_keys,
+            10,
+        )
+        .unwrap();
+        assert!(report.is_ok(), "{report:?}");
+        assert_eq!(report.height, BlockHeight(1));
+        assert_eq!(report.stored_root, state.in_mem().block.tree.root());
+
+        // Change a value in the subspace behind the merkle tree's back, at
+        // a height that wasn't committed
+        state
+            .db_mut()
+            .write_subspace_val(
+                BlockHeight(2),
+                &key1,
+                3u64.serialize_to_vec(),
+                true,
+            )
+            .unwrap();
+
+        let report = check_db_integrity::<_, Sha256Hasher>(
+            state.db(),
+            merklize_all_keys,
+            10,
+        )
+        .unwrap();
+        assert!(!report.is_ok());
+        assert_ne!(report.stored_root, report.rebuilt_root);
+        for subtree in &report.subtrees {
+            if subtree.store_type == StoreType::Account {
+                assert!(!subtree.is_ok());
+                assert_eq!(subtree.divergent_keys, vec![key1.clone()]);
+            } else {
+                assert!(subtree.is_ok(), "{subtree:?}");
+            }
+        }
+        assert_eq!(report.inconsistent_diffs, vec![key1]);
+    }
+
     proptest! {
         // Generate arb valid input for `test_prefix_iters_aux`
         #![proptest_config(Config {
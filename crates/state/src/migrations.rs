@@ -0,0 +1,444 @@
+//! Versioned storage migrations. A node binary carries a list of typed
+//! migrations, each of which brings the storage layout from one version to
+//! the next at a scheduled block height. Migrations are applied through the
+//! state's write log during `finalize_block`, so that their changes are
+//! merklized with the rest of the block, and the applied version is recorded
+//! under the storage version parameter key.
+
+use namada_core::borsh::{BorshDeserialize, BorshSerialize, BorshSerializeExt};
+use namada_parameters::storage::get_storage_version_key;
+
+use crate::{
+    iter_prefix_bytes, BlockHeight, Key, StorageError, StorageRead,
+    StorageResult, StorageWrite,
+};
+
+/// A function that re-encodes the bytes of a storage value
+pub type ReEncodeFn = fn(&[u8]) -> std::result::Result<Vec<u8>, String>;
+
+/// A migration from the storage version `version - 1` to `version`
+#[derive(Debug, Clone)]
+pub struct StorageMigration {
+    /// The storage version that this migration leads to
+    pub version: u64,
+    /// The height of the block in which this migration is applied
+    pub height: BlockHeight,
+    /// Human-readable description of the migration
+    pub description: String,
+    /// The steps of the migration, applied in order
+    pub steps: Vec<MigrationStep>,
+}
+
+/// A single step of a storage migration
+#[derive(Debug, Clone)]
+pub enum MigrationStep {
+    /// Move the value of a key to another key
+    RenameKey {
+        /// The current key
+        from: Key,
+        /// The new key
+        to: Key,
+    },
+    /// Replace the value of a key with a re-encoded value
+    ReEncodeValue {
+        /// The key of the value
+        key: Key,
+        /// The function that re-encodes the value
+        re_encode: ReEncodeFn,
+    },
+    /// Delete every key with the given prefix
+    DeletePrefix {
+        /// The prefix of the deleted keys
+        prefix: Key,
+    },
+}
+
+/// A change of a storage value made by a migration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageChange {
+    /// The changed key
+    pub key: Key,
+    /// The value before the migration, if any
+    pub old: Option<Vec<u8>>,
+    /// The value after the migration, if any
+    pub new: Option<Vec<u8>>,
+}
+
+/// Re-encode a Borsh encoded value of type `Old` into a value of type `New`.
+/// Can be used as a [`ReEncodeFn`], e.g. `re_encode::<OldParams, Params>`.
+pub fn re_encode<Old, New>(bytes: &[u8]) -> std::result::Result<Vec<u8>, String>
+where
+    Old: BorshDeserialize,
+    New: From<Old> + BorshSerialize,
+{
+    let old = Old::try_from_slice(bytes).map_err(|err| err.to_string())?;
+    Ok(New::from(old).serialize_to_vec())
+}
+
+/// Read the current storage version. Storage that has never been migrated
+/// is at version 0.
+pub fn read_storage_version<S>(storage: &S) -> StorageResult<u64>
+where
+    S: StorageRead,
+{
+    Ok(storage
+        .read(&get_storage_version_key())?
+        .unwrap_or_default())
+}
+
+/// Write the current storage version
+pub fn write_storage_version<S>(
+    storage: &mut S,
+    version: u64,
+) -> StorageResult<()>
+where
+    S: StorageWrite,
+{
+    storage.write(&get_storage_version_key(), version)
+}
+
+/// Apply the steps of a single migration, without checking or updating the
+/// storage version. Returns every change made to the storage. Keys that
+/// don't exist are skipped.
+pub fn apply_migration<S>(
+    storage: &mut S,
+    migration: &StorageMigration,
+) -> StorageResult<Vec<StorageChange>>
+where
+    S: StorageRead + StorageWrite,
+{
+    let mut changes = vec![];
+    for step in &migration.steps {
+        match step {
+            MigrationStep::RenameKey { from, to } => {
+                let Some(value) = storage.read_bytes(from)? else {
+                    continue;
+                };
+                let old = storage.read_bytes(to)?;
+                storage.write_bytes(to, &value)?;
+                storage.delete(from)?;
+                changes.push(StorageChange {
+                    key: from.clone(),
+                    old: Some(value.clone()),
+                    new: None,
+                });
+                changes.push(StorageChange {
+                    key: to.clone(),
+                    old,
+                    new: Some(value),
+                });
+            }
+            MigrationStep::ReEncodeValue { key, re_encode } => {
+                let Some(value) = storage.read_bytes(key)? else {
+                    continue;
+                };
+                let new_value = re_encode(&value).map_err(|err| {
+                    StorageError::new(format!(
+                        "Failed to re-encode the value of {key} in storage \
+                         migration to version {}: {err}",
+                        migration.version
+                    ))
+                })?;
+                storage.write_bytes(key, &new_value)?;
+                changes.push(StorageChange {
+                    key: key.clone(),
+                    old: Some(value),
+                    new: Some(new_value),
+                });
+            }
+            MigrationStep::DeletePrefix { prefix } => {
+                let entries = iter_prefix_bytes(storage, prefix)?
+                    .collect::<StorageResult<Vec<_>>>()?;
+                for (key, value) in entries {
+                    storage.delete(&key)?;
+                    changes.push(StorageChange {
+                        key,
+                        old: Some(value),
+                        new: None,
+                    });
+                }
+            }
+        }
+    }
+    Ok(changes)
+}
+
+/// Apply the given migrations in version order, starting from the current
+/// storage version. Migrations at or below the current version are skipped,
+/// the others must follow each other without a gap. The storage version is
+/// updated after every applied migration. Returns the applied versions with
+/// their changes.
+pub fn apply_migrations<'a, S>(
+    storage: &mut S,
+    migrations: impl IntoIterator<Item = &'a StorageMigration>,
+) -> StorageResult<Vec<(u64, Vec<StorageChange>)>>
+where
+    S: StorageRead + StorageWrite,
+{
+    let mut migrations: Vec<&StorageMigration> =
+        migrations.into_iter().collect();
+    migrations.sort_by_key(|migration| migration.version);
+
+    let mut version = read_storage_version(storage)?;
+    let mut applied = vec![];
+    for migration in migrations {
+        if migration.version <= version {
+            continue;
+        }
+        if migration.version != version + 1 {
+            return Err(StorageError::new(format!(
+                "Storage migration to version {} cannot be applied to the \
+                 storage at version {version}",
+                migration.version
+            )));
+        }
+        tracing::info!(
+            "Applying storage migration to version {}: {}",
+            migration.version,
+            migration.description
+        );
+        let changes = apply_migration(storage, migration)?;
+        version = migration.version;
+        write_storage_version(storage, version)?;
+        applied.push((version, changes));
+    }
+    Ok(applied)
+}
+
+/// Apply the pending migrations scheduled at or before the given height, in
+/// version order. A migration scheduled after the given height holds back
+/// the following versions, even if these are scheduled earlier.
+pub fn apply_scheduled_migrations<S>(
+    storage: &mut S,
+    migrations: &[StorageMigration],
+    height: BlockHeight,
+) -> StorageResult<Vec<(u64, Vec<StorageChange>)>>
+where
+    S: StorageRead + StorageWrite,
+{
+    let pending = pending_migrations(storage, migrations)?;
+    apply_migrations(
+        storage,
+        pending
+            .into_iter()
+            .take_while(|migration| migration.height <= height),
+    )
+}
+
+/// Get the pending migrations that were scheduled at or before the given
+/// committed height. These have been missed by the node and can't be
+/// applied anymore without diverging from the nodes that applied them.
+pub fn missed_migrations<'a, S>(
+    storage: &S,
+    migrations: &'a [StorageMigration],
+    last_committed_height: BlockHeight,
+) -> StorageResult<Vec<&'a StorageMigration>>
+where
+    S: StorageRead,
+{
+    Ok(pending_migrations(storage, migrations)?
+        .into_iter()
+        .filter(|migration| migration.height <= last_committed_height)
+        .collect())
+}
+
+/// Get the migrations that have not been applied yet, in version order
+pub fn pending_migrations<'a, S>(
+    storage: &S,
+    migrations: &'a [StorageMigration],
+) -> StorageResult<Vec<&'a StorageMigration>>
+where
+    S: StorageRead,
+{
+    let version = read_storage_version(storage)?;
+    let mut pending: Vec<_> = migrations
+        .iter()
+        .filter(|migration| migration.version > version)
+        .collect();
+    pending.sort_by_key(|migration| migration.version);
+    Ok(pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestState;
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    struct OldValue(u32);
+
+    #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+    struct NewValue(u64);
+
+    impl From<OldValue> for NewValue {
+        fn from(OldValue(value): OldValue) -> Self {
+            Self(value.into())
+        }
+    }
+
+    fn key(key: &str) -> Key {
+        Key::parse(key).unwrap()
+    }
+
+    #[test]
+    fn test_apply_scheduled_migrations() {
+        let mut state = TestState::default();
+        state.write(&key("old"), 1_u64).unwrap();
+        state.write(&key("value"), OldValue(2)).unwrap();
+        state.write(&key("prefix/a"), 3_u64).unwrap();
+        state.write(&key("prefix/b"), 4_u64).unwrap();
+        state.write(&key("kept"), 5_u64).unwrap();
+
+        let migrations = vec![
+            StorageMigration {
+                version: 2,
+                height: BlockHeight(10),
+                description: "delete prefix".to_string(),
+                steps: vec![MigrationStep::DeletePrefix {
+                    prefix: key("prefix"),
+                }],
+            },
+            StorageMigration {
+                version: 1,
+                height: BlockHeight(10),
+                description: "rename and re-encode".to_string(),
+                steps: vec![
+                    MigrationStep::RenameKey {
+                        from: key("old"),
+                        to: key("new"),
+                    },
+                    MigrationStep::ReEncodeValue {
+                        key: key("value"),
+                        re_encode: re_encode::<OldValue, NewValue>,
+                    },
+                    MigrationStep::RenameKey {
+                        from: key("missing"),
+                        to: key("other"),
+                    },
+                ],
+            },
+            StorageMigration {
+                version: 3,
+                height: BlockHeight(20),
+                description: "later".to_string(),
+                steps: vec![MigrationStep::DeletePrefix {
+                    prefix: key("kept"),
+                }],
+            },
+        ];
+        assert_eq!(read_storage_version(&state).unwrap(), 0);
+        assert_eq!(pending_migrations(&state, &migrations).unwrap().len(), 3);
+
+        // Nothing is scheduled at this height
+        let applied =
+            apply_scheduled_migrations(&mut state, &migrations, BlockHeight(9))
+                .unwrap();
+        assert!(applied.is_empty());
+        assert_eq!(read_storage_version(&state).unwrap(), 0);
+
+        let applied = apply_scheduled_migrations(
+            &mut state,
+            &migrations,
+            BlockHeight(10),
+        )
+        .unwrap();
+        let versions: Vec<_> =
+            applied.iter().map(|(version, _)| *version).collect();
+        assert_eq!(versions, vec![1, 2]);
+        assert_eq!(applied[0].1.len(), 3);
+        assert_eq!(applied[1].1.len(), 2);
+        assert_eq!(read_storage_version(&state).unwrap(), 2);
+
+        assert!(!state.has_key(&key("old")).unwrap());
+        assert_eq!(state.read::<u64>(&key("new")).unwrap(), Some(1));
+        assert_eq!(
+            state.read::<NewValue>(&key("value")).unwrap(),
+            Some(NewValue(2))
+        );
+        assert!(!state.has_key(&key("other")).unwrap());
+        assert!(!state.has_key(&key("prefix/a")).unwrap());
+        assert!(!state.has_key(&key("prefix/b")).unwrap());
+        assert_eq!(state.read::<u64>(&key("kept")).unwrap(), Some(5));
+
+        // Applying the same migrations again is a no-op
+        let applied = apply_scheduled_migrations(
+            &mut state,
+            &migrations,
+            BlockHeight(10),
+        )
+        .unwrap();
+        assert!(applied.is_empty());
+        let pending = pending_migrations(&state, &migrations).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].version, 3);
+    }
+
+    #[test]
+    fn test_apply_migrations_version_gap() {
+        let mut state = TestState::default();
+        let migrations = vec![StorageMigration {
+            version: 2,
+            height: BlockHeight(1),
+            description: "gap".to_string(),
+            steps: vec![],
+        }];
+        assert!(apply_migrations(&mut state, &migrations).is_err());
+        assert_eq!(read_storage_version(&state).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_apply_past_scheduled_migrations() {
+        let mut state = TestState::default();
+        state.write(&key("a"), 1_u64).unwrap();
+        state.write(&key("b"), 2_u64).unwrap();
+        let migration = |version, height, prefix| StorageMigration {
+            version,
+            height: BlockHeight(height),
+            description: prefix.to_string(),
+            steps: vec![MigrationStep::DeletePrefix {
+                prefix: key(prefix),
+            }],
+        };
+        // The version 3 is scheduled before the version 2
+        let migrations = vec![
+            migration(1, 5, "a"),
+            migration(2, 20, "b"),
+            migration(3, 10, "c"),
+        ];
+
+        // The pending migrations scheduled before the height are missed
+        let missed =
+            missed_migrations(&state, &migrations, BlockHeight(12)).unwrap();
+        let versions: Vec<_> =
+            missed.iter().map(|migration| migration.version).collect();
+        assert_eq!(versions, vec![1, 3]);
+
+        // The migration scheduled before the height is applied, while the
+        // version 3 is held back by the version 2
+        let applied = apply_scheduled_migrations(
+            &mut state,
+            &migrations,
+            BlockHeight(12),
+        )
+        .unwrap();
+        let versions: Vec<_> =
+            applied.iter().map(|(version, _)| *version).collect();
+        assert_eq!(versions, vec![1]);
+        assert!(!state.has_key(&key("a")).unwrap());
+        assert!(state.has_key(&key("b")).unwrap());
+
+        let applied = apply_scheduled_migrations(
+            &mut state,
+            &migrations,
+            BlockHeight(20),
+        )
+        .unwrap();
+        let versions: Vec<_> =
+            applied.iter().map(|(version, _)| *version).collect();
+        assert_eq!(versions, vec![2, 3]);
+        assert!(!state.has_key(&key("b")).unwrap());
+        assert!(missed_migrations(&state, &migrations, BlockHeight(20))
+            .unwrap()
+            .is_empty());
+    }
+}
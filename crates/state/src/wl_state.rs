@@ -5,20 +5,23 @@ use namada_core::address::Address;
 use namada_core::borsh::BorshSerializeExt;
 use namada_core::chain::ChainId;
 use namada_core::storage;
-use namada_core::time::DateTimeUtc;
+use namada_core::time::{DateTimeUtc, Duration};
 use namada_parameters::EpochDuration;
 use namada_replay_protection as replay_protection;
 use namada_storage::conversion_state::{ConversionState, WithConversionState};
-use namada_storage::{BlockHeight, BlockStateRead, BlockStateWrite, ResultExt};
+use namada_storage::{
+    BlockHeight, BlockStateRead, BlockStateWrite, DiffsPruning,
+    DiffsPruningJob, PrunedDiffs, ResultExt,
+};
 
 use crate::in_memory::InMemory;
 use crate::write_log::{
     self, ReProtStorageModification, StorageModification, WriteLog,
 };
 use crate::{
-    is_pending_transfer_key, DBIter, Epoch, Error, Hash, Key, LastBlock,
-    MembershipProof, MerkleTree, MerkleTreeError, ProofOps, Result, State,
-    StateRead, StorageHasher, StorageResult, StoreType, DB,
+    is_pending_transfer_key, DBIter, Epoch, Epochs, Error, Hash, Key,
+    LastBlock, MembershipProof, MerkleTree, MerkleTreeError, ProofOps, Result,
+    State, StateRead, StorageHasher, StorageResult, StoreType, DB,
     EPOCH_SWITCH_BLOCKS_DELAY, STORAGE_ACCESS_GAS_PER_BYTE,
     STORAGE_WRITE_GAS_PER_BYTE,
 };
@@ -216,17 +219,29 @@ where
         debug_assert!(self.0.write_log.block_write_log.is_empty());
 
         // Replay protections specifically
+        let mut block_hashes = vec![];
         for (hash, entry) in
             std::mem::take(&mut self.0.write_log.replay_protection).into_iter()
         {
             match entry {
-                ReProtStorageModification::Write => self
-                    .write_replay_protection_entry(
+                ReProtStorageModification::Write { expiration } => {
+                    self.write_replay_protection_entry(
                         batch,
                         // Can only write tx hashes to the previous block, no
                         // further
                         &replay_protection::last_key(&hash),
-                    )?,
+                    )?;
+                    // Index the hash by the expiration of its tx for pruning.
+                    // The index entry is not removed if the hash gets deleted
+                    // or rolled back, since pruning a missing hash is a no-op
+                    // and a tx can only be written again with the same
+                    // expiration, which is committed to by its hash
+                    self.write_replay_protection_entry(
+                        batch,
+                        &replay_protection::expiry_key(&expiration, &hash),
+                    )?;
+                    block_hashes.push(hash);
+                }
                 ReProtStorageModification::Delete => self
                     .delete_replay_protection_entry(
                         batch,
@@ -247,6 +262,13 @@ where
             }
         }
         debug_assert!(self.0.write_log.replay_protection.is_empty());
+        // Keep the hashes added in this block to be able to roll it back
+        let height = self.0.in_mem.block.height;
+        self.0.db.write_replay_protection_block_hashes(
+            batch,
+            height,
+            &block_hashes,
+        )?;
 
         if let Some(address_gen) = self.0.write_log.address_gen.take() {
             self.0.in_mem.address_gen = address_gen
@@ -366,6 +388,140 @@ where
         Ok(())
     }
 
+    // Prune the subspace diffs according to the pruning mode. Use after
+    // updating self.block.height in the commit. Only the record of the pruned
+    // diffs is written into the batch, the returned pruning has to be run
+    // once the batch is committed.
+    fn prune_diffs(
+        &mut self,
+        batch: &mut D::WriteBatch,
+    ) -> Result<Option<DiffsPruningJob>> {
+        let height = self.in_mem.block.height;
+        // The last block's Merkle tree is restored from the stores written at
+        // the start of its epoch and the diffs of the following heights, so
+        // only the diffs below the epoch's start can be pruned
+        let max_prunable = match self
+            .in_mem
+            .block
+            .pred_epochs
+            .get_start_height_of_epoch(self.in_mem.block.epoch)
+        {
+            Some(BlockHeight(0)) | None => BlockHeight(0),
+            Some(epoch_start_height) => epoch_start_height.prev_height(),
+        };
+        let pruned = self.db.read_pruned_diffs()?;
+        let (new_pruned, job) = match self.in_mem.diffs_pruning {
+            DiffsPruning::Archive => return Ok(None),
+            DiffsPruning::KeepLast { heights } => {
+                // Keep at least the diffs needed to roll back the last block
+                let heights = heights.max(2);
+                if height.0 < heights {
+                    return Ok(None);
+                }
+                // Reading at a height only requires the diffs of the
+                // following heights, so the diffs at the oldest readable
+                // height can be pruned too
+                let oldest = BlockHeight(height.0 - heights + 1)
+                    .min(max_prunable)
+                    .max(pruned.height);
+                if oldest.0 == 0 {
+                    return Ok(None);
+                }
+                let from = if pruned.checkpoints.is_some() {
+                    // Prune the checkpoints left from the previous mode
+                    BlockHeight::first()
+                } else if oldest > pruned.height {
+                    pruned.height.next_height().max(BlockHeight::first())
+                } else {
+                    return Ok(None);
+                };
+                let new_pruned = PrunedDiffs {
+                    height: oldest,
+                    checkpoints: None,
+                };
+                (new_pruned, DiffsPruningJob::Prune { from, to: oldest })
+            }
+            DiffsPruning::KeepEveryNth { interval } => {
+                if interval < 2 {
+                    return Ok(None);
+                }
+                // The latest checkpoint before the last block, so that the
+                // last block's diffs are left intact for a rollback
+                let checkpoint = BlockHeight(
+                    (height.0 - 1).min(max_prunable.0) / interval * interval,
+                );
+                if checkpoint.0 == 0 || checkpoint <= pruned.height {
+                    return Ok(None);
+                }
+                let from =
+                    pruned.height.next_height().max(BlockHeight::first());
+                let checkpoints = match pruned.checkpoints {
+                    Some((prev_interval, since))
+                        if prev_interval == interval =>
+                    {
+                        (interval, since)
+                    }
+                    _ => (interval, checkpoint),
+                };
+                let new_pruned = PrunedDiffs {
+                    height: checkpoint,
+                    checkpoints: Some(checkpoints),
+                };
+                if from >= checkpoint {
+                    self.db.batch_write_pruned_diffs(batch, &new_pruned)?;
+                    return Ok(None);
+                }
+                (
+                    new_pruned,
+                    DiffsPruningJob::Merge {
+                        from,
+                        into: checkpoint,
+                    },
+                )
+            }
+        };
+        self.db.batch_write_pruned_diffs(batch, &new_pruned)?;
+        Ok(Some(job))
+    }
+
+    // Prune the events of the event store according to the retention. Use
+    // after updating self.block.height in the commit.
+    fn prune_events(&mut self, batch: &mut D::WriteBatch) -> Result<()> {
+        let height = self.in_mem.block.height;
+        let Some(to) = self.in_mem.events_retention.prunable_height(height)
+        else {
+            return Ok(());
+        };
+        let from = match self.db.read_pruned_events_height()? {
+            Some(pruned) if pruned >= to => return Ok(()),
+            Some(pruned) => pruned.next_height(),
+            None => BlockHeight(1),
+        };
+        self.0.db.prune_events(batch, from, to)?;
+        Ok(())
+    }
+
+    // Prune the hashes of the txs that expired before the block time, minus
+    // a safety margin. Any replay of these txs is rejected by the expiration
+    // check anyway. Use after updating self.last_block in the commit.
+    fn prune_replay_protection(
+        &mut self,
+        batch: &mut D::WriteBatch,
+    ) -> Result<()> {
+        let Some(last_block) = self.in_mem.last_block.as_ref() else {
+            return Ok(());
+        };
+        let Some(up_to) = last_block.time.0.checked_sub_signed(
+            Duration::seconds(replay_protection::PRUNING_MARGIN.0 as i64),
+        ) else {
+            return Ok(());
+        };
+        self.0
+            .db
+            .prune_replay_protection(batch, &DateTimeUtc(up_to))?;
+        Ok(())
+    }
+
     /// Check it the given transaction's hash is already present in storage
     pub fn has_replay_protection_entry(&self, hash: &Hash) -> Result<bool> {
         Ok(self.db.has_replay_protection_entry(hash)?)
@@ -453,7 +609,7 @@ where
 
     /// Load the full state at the last committed height, if any. Returns the
     /// Merkle root hash and the height of the committed block.
-    fn load_last_state(&mut self) {
+    pub fn load_last_state(&mut self) {
         if let Some(BlockStateRead {
             merkle_tree_stores,
             hash,
@@ -575,7 +731,19 @@ where
             // prune old merkle tree stores
             self.prune_merkle_tree_stores(&mut batch)?;
         }
+        let diffs_pruning = self.prune_diffs(&mut batch)?;
+        let events = std::mem::take(&mut self.in_mem.block_events);
+        self.db.batch_write_events(
+            &mut batch,
+            self.in_mem.block.height,
+            &events,
+        )?;
+        self.prune_events(&mut batch)?;
+        self.prune_replay_protection(&mut batch)?;
         self.db.exec_batch(batch)?;
+        if let Some(job) = diffs_pruning {
+            self.0.db.run_diffs_pruning(job)?;
+        }
         Ok(())
     }
 }
@@ -844,136 +1012,13 @@ where
             height
         };
 
-        let epoch = self
-            .in_mem
-            .block
-            .pred_epochs
-            .get_epoch(height)
-            .unwrap_or_default();
-        let epoch_start_height = match self
-            .in_mem
-            .block
-            .pred_epochs
-            .get_start_height_of_epoch(epoch)
-        {
-            Some(BlockHeight(0)) => BlockHeight(1),
-            Some(height) => height,
-            None => BlockHeight(1),
-        };
-        let stores = self
-            .db
-            .read_merkle_tree_stores(epoch, epoch_start_height, store_type)?
-            .ok_or(Error::NoMerkleTree { height })?;
-        let prefix = store_type.and_then(|st| st.provable_prefix());
-        let mut tree = match store_type {
-            Some(_) => MerkleTree::<H>::new_partial(stores),
-            None => MerkleTree::<H>::new(stores).expect("invalid stores"),
-        };
-        // Restore the tree state with diffs
-        let mut target_height = epoch_start_height;
-        while target_height < height {
-            target_height = target_height.next_height();
-            let mut old_diff_iter =
-                self.db.iter_old_diffs(target_height, prefix.as_ref());
-            let mut new_diff_iter =
-                self.db.iter_new_diffs(target_height, prefix.as_ref());
-
-            let mut old_diff = old_diff_iter.next();
-            let mut new_diff = new_diff_iter.next();
-            loop {
-                match (&old_diff, &new_diff) {
-                    (Some(old), Some(new)) => {
-                        let old_key = Key::parse(old.0.clone())
-                            .expect("the key should be parsable");
-                        let new_key = Key::parse(new.0.clone())
-                            .expect("the key should be parsable");
-
-                        // compare keys as String
-                        match old.0.cmp(&new.0) {
-                            Ordering::Equal => {
-                                // the value was updated
-                                if (self.merkle_tree_key_filter)(&new_key) {
-                                    tree.update(
-                                        &new_key,
-                                        if is_pending_transfer_key(&new_key) {
-                                            target_height.serialize_to_vec()
-                                        } else {
-                                            new.1.clone()
-                                        },
-                                    )?;
-                                }
-                                old_diff = old_diff_iter.next();
-                                new_diff = new_diff_iter.next();
-                            }
-                            Ordering::Less => {
-                                // the value was deleted
-                                if (self.merkle_tree_key_filter)(&old_key) {
-                                    tree.delete(&old_key)?;
-                                }
-                                old_diff = old_diff_iter.next();
-                            }
-                            Ordering::Greater => {
-                                // the value was inserted
-                                if (self.merkle_tree_key_filter)(&new_key) {
-                                    tree.update(
-                                        &new_key,
-                                        if is_pending_transfer_key(&new_key) {
-                                            target_height.serialize_to_vec()
-                                        } else {
-                                            new.1.clone()
-                                        },
-                                    )?;
-                                }
-                                new_diff = new_diff_iter.next();
-                            }
-                        }
-                    }
-                    (Some(old), None) => {
-                        // the value was deleted
-                        let key = Key::parse(old.0.clone())
-                            .expect("the key should be parsable");
-
-                        if (self.merkle_tree_key_filter)(&key) {
-                            tree.delete(&key)?;
-                        }
-
-                        old_diff = old_diff_iter.next();
-                    }
-                    (None, Some(new)) => {
-                        // the value was inserted
-                        let key = Key::parse(new.0.clone())
-                            .expect("the key should be parsable");
-
-                        if (self.merkle_tree_key_filter)(&key) {
-                            tree.update(
-                                &key,
-                                if is_pending_transfer_key(&key) {
-                                    target_height.serialize_to_vec()
-                                } else {
-                                    new.1.clone()
-                                },
-                            )?;
-                        }
-
-                        new_diff = new_diff_iter.next();
-                    }
-                    (None, None) => break,
-                }
-            }
-        }
-        if let Some(st) = store_type {
-            // Add the base tree with the given height
-            let mut stores = self
-                .db
-                .read_merkle_tree_stores(epoch, height, Some(StoreType::Base))?
-                .ok_or(Error::NoMerkleTree { height })?;
-            let restored_stores = tree.stores();
-            // Set the root and store of the rebuilt subtree
-            stores.set_root(&st, *restored_stores.root(&st));
-            stores.set_store(restored_stores.store(&st).to_owned());
-            tree = MerkleTree::<H>::new_partial(stores);
-        }
-        Ok(tree)
+        restore_merkle_tree(
+            &self.db,
+            self.merkle_tree_key_filter,
+            &self.in_mem.block.pred_epochs,
+            height,
+            store_type,
+        )
     }
 
     /// Get the timestamp of the last committed block, or the current timestamp
@@ -1190,3 +1235,139 @@ where
         &mut self.0
     }
 }
+
+/// Restore the Merkle tree at the given height from the stores written at the
+/// start of its epoch and the subspace diffs of the following heights.
+/// Base tree and the specified `store_type` subtree is rebuilt.
+/// If `store_type` isn't given, full Merkle tree is restored.
+pub fn restore_merkle_tree<D, H>(
+    db: &D,
+    merkle_tree_key_filter: fn(&Key) -> bool,
+    pred_epochs: &Epochs,
+    height: BlockHeight,
+    store_type: Option<StoreType>,
+) -> Result<MerkleTree<H>>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+    H: StorageHasher,
+{
+    let epoch = pred_epochs.get_epoch(height).unwrap_or_default();
+    let epoch_start_height = match pred_epochs.get_start_height_of_epoch(epoch)
+    {
+        Some(BlockHeight(0)) => BlockHeight(1),
+        Some(height) => height,
+        None => BlockHeight(1),
+    };
+    let stores = db
+        .read_merkle_tree_stores(epoch, epoch_start_height, store_type)?
+        .ok_or(Error::NoMerkleTree { height })?;
+    let prefix = store_type.and_then(|st| st.provable_prefix());
+    let mut tree = match store_type {
+        Some(_) => MerkleTree::<H>::new_partial(stores),
+        None => MerkleTree::<H>::new(stores).expect("invalid stores"),
+    };
+    // Restore the tree state with diffs
+    let mut target_height = epoch_start_height;
+    while target_height < height {
+        target_height = target_height.next_height();
+        let mut old_diff_iter =
+            db.iter_old_diffs(target_height, prefix.as_ref());
+        let mut new_diff_iter =
+            db.iter_new_diffs(target_height, prefix.as_ref());
+
+        let mut old_diff = old_diff_iter.next();
+        let mut new_diff = new_diff_iter.next();
+        loop {
+            match (&old_diff, &new_diff) {
+                (Some(old), Some(new)) => {
+                    let old_key = Key::parse(old.0.clone())
+                        .expect("the key should be parsable");
+                    let new_key = Key::parse(new.0.clone())
+                        .expect("the key should be parsable");
+
+                    // compare keys as String
+                    match old.0.cmp(&new.0) {
+                        Ordering::Equal => {
+                            // the value was updated
+                            if merkle_tree_key_filter(&new_key) {
+                                tree.update(
+                                    &new_key,
+                                    if is_pending_transfer_key(&new_key) {
+                                        target_height.serialize_to_vec()
+                                    } else {
+                                        new.1.clone()
+                                    },
+                                )?;
+                            }
+                            old_diff = old_diff_iter.next();
+                            new_diff = new_diff_iter.next();
+                        }
+                        Ordering::Less => {
+                            // the value was deleted
+                            if merkle_tree_key_filter(&old_key) {
+                                tree.delete(&old_key)?;
+                            }
+                            old_diff = old_diff_iter.next();
+                        }
+                        Ordering::Greater => {
+                            // the value was inserted
+                            if merkle_tree_key_filter(&new_key) {
+                                tree.update(
+                                    &new_key,
+                                    if is_pending_transfer_key(&new_key) {
+                                        target_height.serialize_to_vec()
+                                    } else {
+                                        new.1.clone()
+                                    },
+                                )?;
+                            }
+                            new_diff = new_diff_iter.next();
+                        }
+                    }
+                }
+                (Some(old), None) => {
+                    // the value was deleted
+                    let key = Key::parse(old.0.clone())
+                        .expect("the key should be parsable");
+
+                    if merkle_tree_key_filter(&key) {
+                        tree.delete(&key)?;
+                    }
+
+                    old_diff = old_diff_iter.next();
+                }
+                (None, Some(new)) => {
+                    // the value was inserted
+                    let key = Key::parse(new.0.clone())
+                        .expect("the key should be parsable");
+
+                    if merkle_tree_key_filter(&key) {
+                        tree.update(
+                            &key,
+                            if is_pending_transfer_key(&key) {
+                                target_height.serialize_to_vec()
+                            } else {
+                                new.1.clone()
+                            },
+                        )?;
+                    }
+
+                    new_diff = new_diff_iter.next();
+                }
+                (None, None) => break,
+            }
+        }
+    }
+    if let Some(st) = store_type {
+        // Add the base tree with the given height
+        let mut stores = db
+            .read_merkle_tree_stores(epoch, height, Some(StoreType::Base))?
+            .ok_or(Error::NoMerkleTree { height })?;
+        let restored_stores = tree.stores();
+        // Set the root and store of the rebuilt subtree
+        stores.set_root(&st, *restored_stores.root(&st));
+        stores.set_store(restored_stores.store(&st).to_owned());
+        tree = MerkleTree::<H>::new_partial(stores);
+    }
+    Ok(tree)
+}
@@ -8,6 +8,7 @@ use namada_core::address::{Address, EstablishedAddressGen, InternalAddress};
 use namada_core::hash::Hash;
 use namada_core::ibc::IbcEvent;
 use namada_core::storage;
+use namada_core::time::DateTimeUtc;
 use namada_gas::{MEMORY_ACCESS_GAS_PER_BYTE, STORAGE_WRITE_GAS_PER_BYTE};
 use namada_trans_token::storage_key::{
     is_any_minted_balance_key, is_any_minter_key, is_any_token_balance_key,
@@ -66,8 +67,11 @@ pub enum StorageModification {
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// A replay protection storage modification
 pub(crate) enum ReProtStorageModification {
-    /// Write an entry
-    Write,
+    /// Write an entry, indexed by the expiration of its tx
+    Write {
+        /// The expiration of the tx
+        expiration: DateTimeUtc,
+    },
     /// Delete an entry
     Delete,
     /// Finalize an entry
@@ -94,6 +98,14 @@ pub struct WriteLog {
     /// cleaned either when committing or dumping the `tx_write_log`
     pub(crate) tx_precommit_write_log:
         HashMap<storage::Key, StorageModification>,
+    /// A precommit bucket for the inner transactions of a batch. The changes
+    /// of the inner transactions already applied are stored here, so that
+    /// they make the prior state of the following ones. Unlike the
+    /// `tx_precommit_write_log`, this log is part of the state read with
+    /// [`WriteLog::read_pre`]. It is committed to the `block_write_log`
+    /// after the `tx_precommit_write_log`, or dropped together with it.
+    pub(crate) batch_precommit_write_log:
+        HashMap<storage::Key, StorageModification>,
     /// The IBC events for the current transaction
     pub(crate) ibc_events: BTreeSet<IbcEvent>,
     /// Storage modifications for the replay protection storage, always
@@ -124,6 +136,7 @@ impl Default for WriteLog {
             block_write_log: HashMap::with_capacity(100_000),
             tx_write_log: HashMap::with_capacity(100),
             tx_precommit_write_log: HashMap::with_capacity(100),
+            batch_precommit_write_log: HashMap::with_capacity(100),
             ibc_events: BTreeSet::new(),
             replay_protection: HashMap::with_capacity(1_000),
         }
@@ -141,6 +154,11 @@ impl WriteLog {
         match self
             .tx_write_log
             .get(key)
+            .or_else(|| {
+                // If not found, then try to read from batch precommit write
+                // log
+                self.batch_precommit_write_log.get(key)
+            })
             .or_else(|| {
                 // If not found, then try to read from tx precommit write log
                 self.tx_precommit_write_log.get(key)
@@ -170,12 +188,17 @@ impl WriteLog {
 
     /// Read a value before the latest tx execution at the given key and return
     /// the value and the gas cost, returns [`None`] if the key is not present
-    /// in the write log
+    /// in the write log. The changes of the previous inner txs of a batch are
+    /// part of the prior state, while those in the tx precommit are not.
     pub fn read_pre(
         &self,
         key: &storage::Key,
     ) -> (Option<&StorageModification>, u64) {
-        match self.block_write_log.get(key) {
+        match self
+            .batch_precommit_write_log
+            .get(key)
+            .or_else(|| self.block_write_log.get(key))
+        {
             Some(v) => {
                 let gas = match v {
                     StorageModification::Write { ref value } => {
@@ -392,6 +415,7 @@ impl WriteLog {
     pub fn get_keys_with_precommit(&self) -> BTreeSet<storage::Key> {
         self.tx_precommit_write_log
             .keys()
+            .chain(self.batch_precommit_write_log.keys())
             .chain(self.tx_write_log.keys())
             .cloned()
             .collect()
@@ -453,11 +477,29 @@ impl WriteLog {
         self.tx_precommit_write_log.extend(tx_log)
     }
 
+    /// Add the entire content of the tx write log, holding the changes of an
+    /// inner tx of a batch, to the batch precommit one. The tx log gets reset
+    /// in the process.
+    pub fn precommit_batch_tx(&mut self) {
+        let tx_log = std::mem::replace(
+            &mut self.tx_write_log,
+            HashMap::with_capacity(100),
+        );
+
+        self.batch_precommit_write_log.extend(tx_log)
+    }
+
     /// Commit the current transaction's write log and precommit log to the
     /// block when it's accepted by all the triggered validity predicates.
     /// Starts a new transaction write log.
     pub fn commit_tx(&mut self) {
-        // First precommit everything
+        // First precommit everything, the changes of the batch being more
+        // recent than those in the tx precommit
+        let batch_log = std::mem::replace(
+            &mut self.batch_precommit_write_log,
+            HashMap::with_capacity(100),
+        );
+        self.tx_precommit_write_log.extend(batch_log);
         self.precommit_tx();
 
         // Then commit to block
@@ -478,6 +520,7 @@ impl WriteLog {
     /// transaction write log.
     pub fn drop_tx(&mut self) {
         self.tx_precommit_write_log.clear();
+        self.batch_precommit_write_log.clear();
         self.tx_write_log.clear();
     }
 
@@ -556,6 +599,11 @@ impl WriteLog {
                 matches.insert(key.to_string(), modification.clone());
             }
         }
+        for (key, modification) in &self.batch_precommit_write_log {
+            if key.split_prefix(prefix).is_some() {
+                matches.insert(key.to_string(), modification.clone());
+            }
+        }
 
         let iter = matches.into_iter();
         PrefixIter { iter }
@@ -571,6 +619,16 @@ impl WriteLog {
                 matches.insert(key.to_string(), modification.clone());
             }
         }
+        for (key, modification) in &self.tx_precommit_write_log {
+            if key.split_prefix(prefix).is_some() {
+                matches.insert(key.to_string(), modification.clone());
+            }
+        }
+        for (key, modification) in &self.batch_precommit_write_log {
+            if key.split_prefix(prefix).is_some() {
+                matches.insert(key.to_string(), modification.clone());
+            }
+        }
         for (key, modification) in &self.tx_write_log {
             if key.split_prefix(prefix).is_some() {
                 matches.insert(key.to_string(), modification.clone());
@@ -589,11 +647,17 @@ impl WriteLog {
             .map(|action| !matches!(action, ReProtStorageModification::Delete))
     }
 
-    /// Write the transaction hash
-    pub fn write_tx_hash(&mut self, hash: Hash) -> Result<()> {
+    /// Write the transaction hash. The hash is kept in storage until the given
+    /// expiration of the transaction has passed, after which a replay of the
+    /// transaction would be rejected anyway.
+    pub fn write_tx_hash(
+        &mut self,
+        hash: Hash,
+        expiration: DateTimeUtc,
+    ) -> Result<()> {
         if self
             .replay_protection
-            .insert(hash, ReProtStorageModification::Write)
+            .insert(hash, ReProtStorageModification::Write { expiration })
             .is_some()
         {
             // Cannot write an hash if other requests have already been
@@ -651,6 +715,7 @@ impl WriteLog {
 mod tests {
     use assert_matches::assert_matches;
     use namada_core::address;
+    use namada_core::time::DurationSecs;
     use pretty_assertions::assert_eq;
     use proptest::prelude::*;
 
@@ -814,6 +879,75 @@ mod tests {
         assert_matches!(result, Error::DeleteVp);
     }
 
+    #[test]
+    fn test_read_pre_with_precommits() {
+        let mut write_log = WriteLog::default();
+        let key =
+            storage::Key::parse("key").expect("cannot parse the key string");
+        let block_val = "block".as_bytes().to_vec();
+        write_log.write(&key, block_val.clone()).unwrap();
+        write_log.commit_tx();
+
+        // A fee unshielding is precommitted before the tx
+        let unshield_val = "unshield".as_bytes().to_vec();
+        write_log.write(&key, unshield_val.clone()).unwrap();
+        write_log.precommit_tx();
+
+        // A tx that is not a batch sees the precommitted value in its posterior
+        // state only
+        let tx_val = "tx".as_bytes().to_vec();
+        write_log.write(&key, tx_val.clone()).unwrap();
+        let (pre, _) = write_log.read_pre(&key);
+        assert_matches!(
+            pre,
+            Some(StorageModification::Write { value }) if value == &block_val
+        );
+        let pre: Vec<_> = write_log.iter_prefix_pre(&key).collect();
+        assert_matches!(
+            &pre[..],
+            [(_, StorageModification::Write { value })] if value == &block_val
+        );
+        let (post, _) = write_log.read(&key);
+        assert_matches!(
+            post,
+            Some(StorageModification::Write { value }) if value == &tx_val
+        );
+
+        // The previous inner txs of a batch make the prior state of the
+        // following ones
+        write_log.precommit_batch_tx();
+        let (pre, _) = write_log.read_pre(&key);
+        assert_matches!(
+            pre,
+            Some(StorageModification::Write { value }) if value == &tx_val
+        );
+        let (post, _) = write_log.read(&key);
+        assert_matches!(
+            post,
+            Some(StorageModification::Write { value }) if value == &tx_val
+        );
+
+        // The batch changes are committed over the precommitted ones
+        write_log.commit_tx();
+        assert!(write_log.batch_precommit_write_log.is_empty());
+        assert!(write_log.tx_precommit_write_log.is_empty());
+        let (pre, _) = write_log.read_pre(&key);
+        assert_matches!(
+            pre,
+            Some(StorageModification::Write { value }) if value == &tx_val
+        );
+
+        // Dropping the tx drops the batch changes too
+        write_log.write(&key, unshield_val).unwrap();
+        write_log.precommit_batch_tx();
+        write_log.drop_tx();
+        let (pre, _) = write_log.read_pre(&key);
+        assert_matches!(
+            pre,
+            Some(StorageModification::Write { value }) if value == &tx_val
+        );
+    }
+
     #[test]
     fn test_commit() {
         let mut state = crate::testing::TestState::default();
@@ -873,18 +1007,19 @@ mod tests {
     #[test]
     fn test_replay_protection_commit() {
         let mut state = crate::testing::TestState::default();
+        let expiration = DateTimeUtc::now() + DurationSecs(60);
 
         {
             let write_log = state.write_log_mut();
             // write some replay protection keys
             write_log
-                .write_tx_hash(Hash::sha256("tx1".as_bytes()))
+                .write_tx_hash(Hash::sha256("tx1".as_bytes()), expiration)
                 .unwrap();
             write_log
-                .write_tx_hash(Hash::sha256("tx2".as_bytes()))
+                .write_tx_hash(Hash::sha256("tx2".as_bytes()), expiration)
                 .unwrap();
             write_log
-                .write_tx_hash(Hash::sha256("tx3".as_bytes()))
+                .write_tx_hash(Hash::sha256("tx3".as_bytes()), expiration)
                 .unwrap();
         }
 
@@ -905,13 +1040,13 @@ mod tests {
             let write_log = state.write_log_mut();
             // write some replay protection keys
             write_log
-                .write_tx_hash(Hash::sha256("tx4".as_bytes()))
+                .write_tx_hash(Hash::sha256("tx4".as_bytes()), expiration)
                 .unwrap();
             write_log
-                .write_tx_hash(Hash::sha256("tx5".as_bytes()))
+                .write_tx_hash(Hash::sha256("tx5".as_bytes()), expiration)
                 .unwrap();
             write_log
-                .write_tx_hash(Hash::sha256("tx6".as_bytes()))
+                .write_tx_hash(Hash::sha256("tx6".as_bytes()), expiration)
                 .unwrap();
 
             // delete previous hash
@@ -961,6 +1096,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_replay_protection_pruning() {
+        let mut state = crate::testing::TestState::default();
+        let expired = Hash::sha256("expired".as_bytes());
+        let valid = Hash::sha256("valid".as_bytes());
+
+        {
+            let write_log = state.write_log_mut();
+            write_log
+                .write_tx_hash(expired, DateTimeUtc::unix_epoch())
+                .unwrap();
+            write_log
+                .write_tx_hash(valid, DateTimeUtc::now() + DurationSecs(60))
+                .unwrap();
+        }
+
+        // commit a block
+        state.commit_block().expect("commit failed");
+
+        // finalize the hashes of the previous block
+        let hashes: Vec<_> = state.iter_replay_protection().collect();
+        for hash in hashes {
+            state.write_log.finalize_tx_hash(hash).unwrap();
+        }
+
+        // commit a block
+        state.commit_block().expect("commit failed");
+
+        // only the hash of the expired tx has been pruned
+        assert!(
+            !state
+                .has_replay_protection_entry(&expired)
+                .expect("read failed")
+        );
+        assert!(
+            state
+                .has_replay_protection_entry(&valid)
+                .expect("read failed")
+        );
+    }
+
     prop_compose! {
         fn arb_verifiers_changed_key_tx_all_key()
             (verifiers_from_tx in testing::arb_verifiers_from_tx())
@@ -1,6 +1,10 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 
 use namada_core::address::EstablishedAddressGen;
+use namada_core::borsh::{BorshDeserialize, BorshSerialize};
+use namada_core::event::Event;
 use namada_core::hash::{Error as HashError, Hash};
 use namada_core::storage::{
     BlockHash, BlockHeight, BlockResults, Epoch, Epochs, EthEventsQueue,
@@ -12,9 +16,11 @@ use namada_merkle_tree::{
     Error as MerkleTreeError, MerkleTreeStoresRead, MerkleTreeStoresWrite,
     StoreType,
 };
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::conversion_state::ConversionState;
+use crate::event_store::EventIndex;
 use crate::tx_queue::TxQueue;
 
 #[allow(missing_docs)]
@@ -38,11 +44,189 @@ pub enum Error {
     NoMerkleTree { height: BlockHeight },
     #[error("Code hash error: {0}")]
     InvalidCodeHash(HashError),
+    #[error(
+        "The subspace diffs needed to read at the height {height} have been \
+         pruned"
+    )]
+    PrunedHeight { height: BlockHeight },
 }
 
 /// A result of a function that may fail
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The stores of a DB whose data is included in state-sync snapshots. Together
+/// they contain everything that's needed to restore the state of the last
+/// committed block (only the subspace diffs since the start of its epoch are
+/// included, so a restored node cannot serve queries for past heights prior to
+/// that).
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+)]
+pub enum SnapshotStore {
+    /// The ledger state metadata
+    State,
+    /// The last block's metadata and results, and the Merkle tree stores
+    /// written at the start of its epoch
+    Block,
+    /// The subspace diffs of the heights after the start of the last block's
+    /// epoch, from which its Merkle tree is restored
+    Diffs,
+    /// Accounts' sub-spaces
+    Subspace,
+    /// Hashes of the processed transactions
+    ReplayProtection,
+}
+
+impl SnapshotStore {
+    /// Iterate all the snapshot stores in the order in which they are written
+    /// into a snapshot
+    pub fn iter() -> std::slice::Iter<'static, Self> {
+        static ALL: [SnapshotStore; 5] = [
+            SnapshotStore::State,
+            SnapshotStore::Block,
+            SnapshotStore::Diffs,
+            SnapshotStore::Subspace,
+            SnapshotStore::ReplayProtection,
+        ];
+        ALL.iter()
+    }
+}
+
+/// A raw key-val entry of a state-sync snapshot. The key is relative to the
+/// [`SnapshotStore`] from which it was read.
+pub type SnapshotEntry = (String, Vec<u8>);
+
+/// Take a page of state-sync snapshot entries from the given entries ordered by
+/// their keys, as specified by [`DB::read_snapshot_page`].
+pub fn take_snapshot_page(
+    entries: impl IntoIterator<Item = SnapshotEntry>,
+    start_after: Option<&str>,
+    max_bytes: usize,
+) -> Vec<SnapshotEntry> {
+    let mut page = vec![];
+    let mut page_bytes = 0_usize;
+    for (key, value) in entries {
+        if matches!(start_after, Some(start) if key.as_str() <= start) {
+            continue;
+        }
+        page_bytes += key.len() + value.len();
+        page.push((key, value));
+        if page_bytes >= max_bytes {
+            break;
+        }
+    }
+    page
+}
+
+/// The pruning mode of the subspace diffs, which are needed to read values at
+/// past block heights. The diffs since the start of the last committed block's
+/// epoch are never pruned, so that the block's Merkle tree can be restored and
+/// the block can be rolled back.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum DiffsPruning {
+    /// Keep the diffs at all heights
+    #[default]
+    Archive,
+    /// Only keep the diffs needed to read values at the given number of the
+    /// most recent heights (at least 2)
+    KeepLast {
+        /// The number of readable heights
+        heights: u64,
+    },
+    /// Only keep the diffs needed to read values at every height that is a
+    /// multiple of the interval and at the heights after the latest such
+    /// height. The diffs of the heights in between are merged together.
+    KeepEveryNth {
+        /// The interval of the readable heights
+        interval: u64,
+    },
+}
+
+/// The record of the subspace diffs that have been pruned from the DB.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize,
+)]
+pub struct PrunedDiffs {
+    /// The lowest height at which values can be read, apart from the
+    /// `checkpoints`. The diffs of the heights up to this one have been
+    /// pruned or merged.
+    pub height: BlockHeight,
+    /// The interval of the heights below `height` that can still be read,
+    /// because the diffs in between them have been merged, and the lowest
+    /// such height.
+    pub checkpoints: Option<(u64, BlockHeight)>,
+}
+
+impl PrunedDiffs {
+    /// Check if the diffs needed to read values at the given height have been
+    /// pruned
+    pub fn is_pruned(&self, height: BlockHeight) -> bool {
+        let is_checkpoint = matches!(
+            self.checkpoints,
+            Some((interval, since))
+                if height >= since && height.0 % interval == 0
+        );
+        height < self.height && !is_checkpoint
+    }
+}
+
+/// A pruning of the subspace diffs that is run once the updated
+/// [`PrunedDiffs`] record has been committed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffsPruningJob {
+    /// Prune the diffs at the heights in the range
+    Prune {
+        /// The first pruned height
+        from: BlockHeight,
+        /// The last pruned height (inclusive)
+        to: BlockHeight,
+    },
+    /// Merge the diffs at the heights in the range into the diffs at the
+    /// `into` height
+    Merge {
+        /// The first merged height
+        from: BlockHeight,
+        /// The height into which the diffs are merged (exclusive)
+        into: BlockHeight,
+    },
+}
+
+/// The old and new values of the subspace keys changed at some height
+pub type HeightDiffs = BTreeMap<String, (Option<Vec<u8>>, Option<Vec<u8>>)>;
+
+/// Merge the diffs of an earlier height into the diffs of a later height. The
+/// merged diffs hold the values from before the earlier height and the values
+/// after the later height. To merge a range of heights, this has to be applied
+/// in descending order of the heights.
+pub fn merge_height_diffs(into: &mut HeightDiffs, earlier: HeightDiffs) {
+    for (key, (old, new)) in earlier {
+        match into.entry(key) {
+            Entry::Vacant(entry) => {
+                entry.insert((old, new));
+            }
+            Entry::Occupied(mut entry) => {
+                entry.get_mut().0 = old;
+                // A key that was created and deleted again is unchanged
+                if entry.get() == &(None, None) {
+                    entry.remove();
+                }
+            }
+        }
+    }
+}
+
 /// The block's state as stored in the database.
 pub struct BlockStateRead {
     /// Merkle tree stores
@@ -121,6 +305,8 @@ pub trait DB: Debug {
     type Cache;
     /// A handle for batch writes
     type WriteBatch: DBWriteBatch;
+    /// A copy of the DB's state that can be read from another thread
+    type Checkpoint: DB + for<'iter> DBIter<'iter> + Send + 'static;
 
     /// Open the database from provided path
     fn open(
@@ -128,6 +314,13 @@ pub trait DB: Debug {
         cache: Option<&Self::Cache>,
     ) -> Self;
 
+    /// Create a copy of the DB's current state. A persistent DB is copied into
+    /// the given directory, which must not exist yet.
+    fn checkpoint_copy(
+        &self,
+        path: &std::path::Path,
+    ) -> Result<Self::Checkpoint>;
+
     /// Flush data on the memory to persistent them
     fn flush(&self, wait: bool) -> Result<()>;
 
@@ -260,6 +453,143 @@ pub trait DB: Debug {
         batch: &mut Self::WriteBatch,
         key: &Key,
     ) -> Result<()>;
+
+    /// Prune the replay protection entries of the txs that expired at or
+    /// before the given time, together with their expiration index entries
+    fn prune_replay_protection(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        up_to: &DateTimeUtc,
+    ) -> Result<()>;
+
+    /// Write the hashes of the txs that have been added to the replay
+    /// protection storage in the block at the given height, which are needed
+    /// to roll the block back
+    fn write_replay_protection_block_hashes(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        hashes: &[Hash],
+    ) -> Result<()>;
+
+    /// Read a page of the entries of the given store that are needed to
+    /// restore the state of the last committed block from a state-sync
+    /// snapshot. The entries are ordered by their keys, starting after the
+    /// `start_after` key (exclusive), if any. The page is filled with entries
+    /// until their total size in bytes reaches `max_bytes`, but it always
+    /// contains at least one entry, unless there are no more entries left.
+    fn read_snapshot_page(
+        &self,
+        store: SnapshotStore,
+        start_after: Option<&str>,
+        max_bytes: usize,
+    ) -> Result<Vec<SnapshotEntry>>;
+
+    /// Batch write the entries restored from a state-sync snapshot into the
+    /// given store.
+    fn batch_write_snapshot_entries(
+        &self,
+        batch: &mut Self::WriteBatch,
+        store: SnapshotStore,
+        entries: &[SnapshotEntry],
+    ) -> Result<()>;
+
+    /// Batch delete all the entries of the given store, to wipe the entries
+    /// restored from a state-sync snapshot that has been rejected.
+    fn batch_wipe_snapshot_store(
+        &self,
+        batch: &mut Self::WriteBatch,
+        store: SnapshotStore,
+    ) -> Result<()>;
+
+    /// Read the record of the subspace diffs that have been pruned
+    fn read_pruned_diffs(&self) -> Result<PrunedDiffs>;
+
+    /// Batch write the record of the subspace diffs that have been pruned
+    fn batch_write_pruned_diffs(
+        &self,
+        batch: &mut Self::WriteBatch,
+        pruned: &PrunedDiffs,
+    ) -> Result<()>;
+
+    /// Prune the subspace diffs at the heights from `from` to `to`
+    /// (inclusive)
+    fn prune_diffs(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> Result<()>;
+
+    /// Merge the subspace diffs at the heights from `from` (inclusive) to
+    /// `into` (exclusive) into the diffs at the `into` height, as specified by
+    /// [`merge_height_diffs`], and prune them
+    fn merge_diffs(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        from: BlockHeight,
+        into: BlockHeight,
+    ) -> Result<()>;
+
+    /// Run a pruning of the subspace diffs whose record has already been
+    /// committed. The pruned diffs cannot be read anymore, so a DB may run it
+    /// in the background. By default, it's run right away.
+    fn run_diffs_pruning(&mut self, job: DiffsPruningJob) -> Result<()> {
+        let mut batch = Self::batch();
+        match job {
+            DiffsPruningJob::Prune { from, to } => {
+                self.prune_diffs(&mut batch, from, to)?
+            }
+            DiffsPruningJob::Merge { from, into } => {
+                self.merge_diffs(&mut batch, from, into)?
+            }
+        }
+        self.exec_batch(batch)
+    }
+
+    /// Batch write the events emitted in the block at the given height into
+    /// the event store, together with their index entries
+    fn batch_write_events(
+        &self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        events: &[Event],
+    ) -> Result<()>;
+
+    /// Read the events emitted in the block at the given height, in the
+    /// order in which they were emitted
+    fn read_events(&self, height: BlockHeight) -> Result<Vec<Event>>;
+
+    /// Read the event at the given position of the block at the given height
+    fn read_event(
+        &self,
+        height: BlockHeight,
+        position: u32,
+    ) -> Result<Option<Event>>;
+
+    /// Read the heights and positions of the events in the given index of the
+    /// event store, in descending order. Only the entries from the `from`
+    /// height up to the `before` height and position (exclusive) are read, at
+    /// most `limit` of them.
+    fn read_event_index(
+        &self,
+        index: &EventIndex,
+        from: BlockHeight,
+        before: (BlockHeight, u32),
+        limit: usize,
+    ) -> Result<Vec<(BlockHeight, u32)>>;
+
+    /// Read the highest height whose events have been pruned, if any
+    fn read_pruned_events_height(&self) -> Result<Option<BlockHeight>>;
+
+    /// Prune the events and their index entries at the heights from `from` to
+    /// `to` (inclusive), and record `to` as the highest pruned height
+    fn prune_events(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> Result<()>;
 }
 
 /// A database prefix iterator.
@@ -0,0 +1,264 @@
+//! Layout of the event store, which persists the events emitted by the ledger
+//! in `FinalizeBlock` calls. The events are keyed by their block height and
+//! their position in the block, and indexed by their type and by the values of
+//! the [`INDEXED_ATTRIBUTES`].
+//!
+//! The store's keys are:
+//! - `log/{h}/{i}`: the `i`-th event emitted at height `h`
+//! - `index/type/{type}/{h}/{i}`: the position of an event of the given type
+//! - `index/attr/{attr}/{value}/{h}/{i}`: the position of an event with the
+//!   given attribute value
+//! - `pruned`: the highest height whose events have been pruned
+//!
+//! The heights and positions are encoded with their order-preserving
+//! [`KeySeg::raw`] encoding.
+
+use namada_core::event::{Event, EventType};
+use namada_core::storage::{BlockHeight, KeySeg};
+use serde::{Deserialize, Serialize};
+
+/// The attributes whose values are indexed in the event store
+pub const INDEXED_ATTRIBUTES: [&str; 8] = [
+    "hash",
+    "tx_hash",
+    "address",
+    "source",
+    "target",
+    "validator",
+    "sender",
+    "receiver",
+];
+
+/// The key of the highest height whose events have been pruned
+pub const PRUNED_EVENTS_KEY: &str = "pruned";
+
+const LOG_PREFIX: &str = "log";
+const INDEX_PREFIX: &str = "index";
+const TYPE_INDEX_PREFIX: &str = "type";
+const ATTRIBUTE_INDEX_PREFIX: &str = "attr";
+
+/// The retention of the events in the event store. The events are pruned on
+/// every commit.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum EventsRetention {
+    /// Keep the events of all heights
+    #[default]
+    Archive,
+    /// Only keep the events of the given number of the most recent heights
+    KeepLast {
+        /// The number of heights whose events are kept
+        heights: u64,
+    },
+}
+
+impl EventsRetention {
+    /// The highest height whose events can be pruned once the block at the
+    /// given height is committed, if any
+    pub fn prunable_height(&self, height: BlockHeight) -> Option<BlockHeight> {
+        match self {
+            Self::Archive => None,
+            Self::KeepLast { heights } => height
+                .0
+                .checked_sub(*heights)
+                .filter(|pruned| *pruned > 0)
+                .map(BlockHeight),
+        }
+    }
+}
+
+/// An index of the event store
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EventIndex {
+    /// The events of the given type
+    Type(EventType),
+    /// The events with the given value of an indexed attribute
+    Attribute {
+        /// One of the [`INDEXED_ATTRIBUTES`]
+        key: String,
+        /// The attribute's value
+        value: String,
+    },
+}
+
+impl EventIndex {
+    /// The events with the given tx hash attribute
+    pub fn tx_hash(hash: impl ToString) -> Self {
+        Self::Attribute {
+            key: "hash".to_string(),
+            value: hash.to_string(),
+        }
+    }
+
+    /// The key prefix of the index's entries
+    pub fn prefix(&self) -> String {
+        match self {
+            Self::Type(event_type) => {
+                format!("{INDEX_PREFIX}/{TYPE_INDEX_PREFIX}/{event_type}/")
+            }
+            Self::Attribute { key, value } => {
+                format!(
+                    "{INDEX_PREFIX}/{ATTRIBUTE_INDEX_PREFIX}/{key}/{value}/"
+                )
+            }
+        }
+    }
+
+    /// Check if the given event belongs to the index
+    pub fn matches(&self, event: &Event) -> bool {
+        match self {
+            Self::Type(event_type) => &event.event_type == event_type,
+            Self::Attribute { key, value } => event.get(key) == Some(value),
+        }
+    }
+}
+
+/// The key of the event at the given position of a block
+pub fn event_key(height: BlockHeight, position: u32) -> String {
+    format!("{}{}", height_events_prefix(height), position.raw())
+}
+
+/// The key prefix of the events emitted at the given height
+pub fn height_events_prefix(height: BlockHeight) -> String {
+    format!("{LOG_PREFIX}/{}/", height.raw())
+}
+
+/// The key prefix of all the events
+pub fn events_prefix() -> String {
+    format!("{LOG_PREFIX}/")
+}
+
+/// The indices the given event belongs to
+pub fn event_indices(event: &Event) -> Vec<EventIndex> {
+    let mut indices = vec![EventIndex::Type(event.event_type.clone())];
+    for key in INDEXED_ATTRIBUTES {
+        if let Some(value) = event.get(key) {
+            indices.push(EventIndex::Attribute {
+                key: key.to_string(),
+                value: value.clone(),
+            });
+        }
+    }
+    indices
+}
+
+/// The keys of the index entries of the event at the given position of a
+/// block
+pub fn event_index_keys(
+    height: BlockHeight,
+    position: u32,
+    event: &Event,
+) -> Vec<String> {
+    let suffix = format!("{}/{}", height.raw(), position.raw());
+    event_indices(event)
+        .into_iter()
+        .map(|index| format!("{}{suffix}", index.prefix()))
+        .collect()
+}
+
+/// The start (inclusive) and end (exclusive) keys of the entries of the
+/// events emitted from the `from` height up to the `to` height inclusive,
+/// under the given prefix of the log or of an index
+pub fn height_range(
+    prefix: &str,
+    from: BlockHeight,
+    to: BlockHeight,
+) -> (String, String) {
+    (
+        format!("{prefix}{}", from.raw()),
+        format!("{prefix}{}", to.next_height().raw()),
+    )
+}
+
+/// The start (inclusive) and end (exclusive) keys of the entries of the given
+/// index from the `from` height up to the `before` height and position
+pub fn index_range(
+    index: &EventIndex,
+    from: BlockHeight,
+    (height, position): (BlockHeight, u32),
+) -> (String, String) {
+    let prefix = index.prefix();
+    (
+        format!("{prefix}{}", from.raw()),
+        format!("{prefix}{}/{}", height.raw(), position.raw()),
+    )
+}
+
+/// Parse the height and position of an event from the key of an event or of
+/// an index entry
+pub fn parse_event_position(key: &str) -> Option<(BlockHeight, u32)> {
+    let mut segments = key.rsplit('/');
+    let position = u32::parse(segments.next()?.to_owned()).ok()?;
+    let height = u64::parse(segments.next()?.to_owned()).ok()?;
+    Some((BlockHeight(height), position))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use namada_core::event::EventLevel;
+
+    use super::*;
+
+    #[test]
+    fn test_event_keys() {
+        let event = Event {
+            event_type: EventType::Applied,
+            level: EventLevel::Tx,
+            attributes: HashMap::from([
+                ("hash".to_string(), "DEADBEEF".to_string()),
+                ("code".to_string(), "0".to_string()),
+            ]),
+        };
+        let height = BlockHeight(300);
+        let key = event_key(height, 2);
+        assert!(key.starts_with(&height_events_prefix(height)));
+        assert_eq!(parse_event_position(&key), Some((height, 2)));
+
+        let index_keys = event_index_keys(height, 2, &event);
+        assert_eq!(index_keys.len(), 2);
+        let type_index = EventIndex::Type(EventType::Applied);
+        let hash_index = EventIndex::tx_hash("DEADBEEF");
+        assert!(index_keys[0].starts_with(&type_index.prefix()));
+        assert!(index_keys[1].starts_with(&hash_index.prefix()));
+        for key in &index_keys {
+            assert_eq!(parse_event_position(key), Some((height, 2)));
+        }
+        assert!(type_index.matches(&event));
+        assert!(hash_index.matches(&event));
+        assert!(!EventIndex::tx_hash("BEEF").matches(&event));
+
+        // The keys are ordered by height and position
+        assert!(event_key(BlockHeight(9), 1) < event_key(BlockHeight(10), 0));
+        assert!(event_key(height, 9) < event_key(height, 10));
+        let (start, end) =
+            height_range(&events_prefix(), BlockHeight(10), height);
+        for key in [event_key(BlockHeight(10), 0), event_key(height, 2)] {
+            assert!(start <= key && key < end);
+        }
+        for key in
+            [event_key(BlockHeight(9), 9), event_key(BlockHeight(301), 0)]
+        {
+            assert!(key < start || end <= key);
+        }
+        let (start, end) =
+            height_range(&hash_index.prefix(), BlockHeight(10), height);
+        assert!(start <= index_keys[1] && index_keys[1] < end);
+    }
+
+    #[test]
+    fn test_events_retention() {
+        let archive = EventsRetention::Archive;
+        assert_eq!(archive.prunable_height(BlockHeight(100)), None);
+        let keep = EventsRetention::KeepLast { heights: 10 };
+        assert_eq!(keep.prunable_height(BlockHeight(10)), None);
+        assert_eq!(keep.prunable_height(BlockHeight(11)), Some(BlockHeight(1)));
+        assert_eq!(
+            keep.prunable_height(BlockHeight(100)),
+            Some(BlockHeight(90))
+        );
+    }
+}
@@ -5,6 +5,7 @@ pub mod collections;
 pub mod conversion_state;
 mod db;
 mod error;
+pub mod event_store;
 pub mod mockdb;
 pub mod tx_queue;
 pub mod types;
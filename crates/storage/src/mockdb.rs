@@ -8,10 +8,11 @@ use std::str::FromStr;
 
 use itertools::Either;
 use namada_core::borsh::{BorshDeserialize, BorshSerializeExt};
+use namada_core::event::Event;
 use namada_core::hash::Hash;
 use namada_core::storage::{
-    BlockHeight, BlockResults, Epoch, EthEventsQueue, Header, Key, KeySeg,
-    KEY_SEGMENT_SEPARATOR,
+    BlockHeight, BlockResults, Epoch, Epochs, EthEventsQueue, Header, Key,
+    KeySeg, KEY_SEGMENT_SEPARATOR,
 };
 use namada_core::time::DateTimeUtc;
 use namada_core::{decode, encode, ethereum_events, ethereum_structs};
@@ -22,12 +23,28 @@ use namada_replay_protection as replay_protection;
 
 use crate::conversion_state::ConversionState;
 use crate::db::{
-    BlockStateRead, BlockStateWrite, DBIter, DBWriteBatch, Error, Result, DB,
+    merge_height_diffs, take_snapshot_page, BlockStateRead, BlockStateWrite,
+    DBIter, DBWriteBatch, Error, HeightDiffs, PrunedDiffs, Result,
+    SnapshotEntry, SnapshotStore, DB,
 };
+use crate::event_store::{self, EventIndex};
 use crate::tx_queue::TxQueue;
 use crate::types::{KVBytes, PrefixIterator};
 
 const SUBSPACE_CF: &str = "subspace";
+const REPLAY_PROTECTION_CF: &str = "replay_protection";
+
+/// The keys of the ledger state metadata
+const STATE_KEYS: [&str; 8] = [
+    "conversion_state",
+    "eth_events_queue",
+    "ethereum_height",
+    "height",
+    "next_epoch_min_start_height",
+    "next_epoch_min_start_time",
+    "tx_queue",
+    "update_epoch_blocks_delay",
+];
 
 const OLD_DIFF_PREFIX: &str = "old";
 const NEW_DIFF_PREFIX: &str = "new";
@@ -53,12 +70,17 @@ pub struct MockDBWriteBatch;
 impl DB for MockDB {
     /// There is no cache for MockDB
     type Cache = ();
+    type Checkpoint = Self;
     type WriteBatch = MockDBWriteBatch;
 
     fn open(_db_path: impl AsRef<Path>, _cache: Option<&Self::Cache>) -> Self {
         Self::default()
     }
 
+    fn checkpoint_copy(&self, _path: &Path) -> Result<Self> {
+        Ok(Self(RefCell::new(self.0.borrow().clone())))
+    }
+
     fn flush(&self, _wait: bool) -> Result<()> {
         Ok(())
     }
@@ -110,7 +132,7 @@ impl DB for MockDB {
             };
 
         let eth_events_queue: EthEventsQueue =
-            match self.0.borrow().get("ethereum_height") {
+            match self.0.borrow().get("eth_events_queue") {
                 Some(bytes) => decode(bytes).map_err(Error::CodingError)?,
                 None => return Ok(None),
             };
@@ -149,8 +171,9 @@ impl DB for MockDB {
                         }
                         None => unknown_key_error(path)?,
                     },
-                    "header" => {
-                        // the block header doesn't have to be restored
+                    "header" | "rollback" => {
+                        // the block header and the data needed to roll the
+                        // block back don't have to be restored
                     }
                     "hash" => {
                         hash = Some(decode(bytes).map_err(Error::CodingError)?)
@@ -458,13 +481,43 @@ impl DB for MockDB {
     fn read_subspace_val_with_height(
         &self,
         key: &Key,
-        _height: BlockHeight,
-        _last_height: BlockHeight,
+        height: BlockHeight,
+        last_height: BlockHeight,
     ) -> Result<Option<Vec<u8>>> {
-        tracing::warn!(
-            "read_subspace_val_with_height is not implemented, will read \
-             subspace value from latest height"
-        );
+        if self.read_pruned_diffs()?.is_pruned(height) {
+            return Err(Error::PrunedHeight { height });
+        }
+        let raw_key = key.to_string();
+        let db = self.0.borrow();
+
+        // If it has a "new" val, it was written at this height
+        if let Some(new_val) =
+            db.get(&diff_key(height, NEW_DIFF_PREFIX, &raw_key))
+        {
+            return Ok(Some(new_val.clone()));
+        }
+        // If it has an "old" val, it was deleted at this height
+        if db.contains_key(&diff_key(height, OLD_DIFF_PREFIX, &raw_key)) {
+            return Ok(None);
+        }
+
+        // If the value didn't change at the given height, we try to look for it
+        // at successor heights, up to the `last_height`
+        for raw_height in height.0 + 1..=last_height.0 {
+            let height = BlockHeight(raw_height);
+            // If it has an "old" val, it's the one we're looking for
+            if let Some(old_val) =
+                db.get(&diff_key(height, OLD_DIFF_PREFIX, &raw_key))
+            {
+                return Ok(Some(old_val.clone()));
+            }
+            // If it was created at this height instead, it wasn't present
+            // before
+            if db.contains_key(&diff_key(height, NEW_DIFF_PREFIX, &raw_key)) {
+                return Ok(None);
+            }
+        }
+        // Read from latest height
         self.read_subspace_val(key)
     }
 
@@ -674,6 +727,387 @@ impl DB for MockDB {
 
         Ok(())
     }
+
+    fn prune_replay_protection(
+        &mut self,
+        _batch: &mut Self::WriteBatch,
+        up_to: &DateTimeUtc,
+    ) -> Result<()> {
+        let prefix = format!(
+            "replay_protection/{}/",
+            replay_protection::expiry_prefix()
+        );
+        let mut db = self.0.borrow_mut();
+        let expired: Vec<(String, Hash)> = db
+            .range(prefix.clone()..)
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(&prefix))
+            .map_while(|key| {
+                let (seconds, hash) = replay_protection::parse_expiry_key(
+                    key.strip_prefix("replay_protection/")?,
+                )?;
+                (seconds <= up_to.0.timestamp()).then(|| (key.clone(), hash))
+            })
+            .collect();
+        for (key, hash) in expired {
+            db.remove(&key);
+            for subkey in [
+                replay_protection::all_key(&hash),
+                replay_protection::last_key(&hash),
+            ] {
+                db.remove(&format!("replay_protection/{subkey}"));
+            }
+        }
+        Ok(())
+    }
+
+    fn write_replay_protection_block_hashes(
+        &mut self,
+        _batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        hashes: &[Hash],
+    ) -> Result<()> {
+        let key = format!("{}/rollback/replay_protection", height.raw());
+        self.0.borrow_mut().insert(key, encode(&hashes));
+
+        Ok(())
+    }
+
+    fn read_snapshot_page(
+        &self,
+        store: SnapshotStore,
+        start_after: Option<&str>,
+        max_bytes: usize,
+    ) -> Result<Vec<SnapshotEntry>> {
+        let db = self.0.borrow();
+        let mut entries: Vec<SnapshotEntry> = match store {
+            SnapshotStore::State => STATE_KEYS
+                .iter()
+                .filter_map(|key| {
+                    db.get(*key).map(|value| (key.to_string(), value.clone()))
+                })
+                .collect(),
+            SnapshotStore::Block => {
+                let height: BlockHeight = match db.get("height") {
+                    Some(bytes) => decode(bytes).map_err(Error::CodingError)?,
+                    None => return Ok(vec![]),
+                };
+                let (epoch, epoch_start_height) =
+                    read_epoch_and_start_height(&db, height)?;
+                let block_prefix = format!("{}/", height.raw());
+                let diff_prefixes = [
+                    format!("{block_prefix}{OLD_DIFF_PREFIX}/"),
+                    format!("{block_prefix}{NEW_DIFF_PREFIX}/"),
+                ];
+                let results_key = format!("results/{}", height.raw());
+                let mut tree_prefixes: Vec<String> = StoreType::iter_subtrees()
+                    .map(|st| format!("{}/", subtree_key_prefix(st, epoch)))
+                    .collect();
+                tree_prefixes.push(format!(
+                    "{}/",
+                    base_tree_key_prefix(epoch_start_height)
+                ));
+                db.iter()
+                    .filter(|(key, _)| {
+                        let is_block_key = key.starts_with(&block_prefix)
+                            && !diff_prefixes
+                                .iter()
+                                .any(|prefix| key.starts_with(prefix));
+                        is_block_key
+                            || **key == results_key
+                            || tree_prefixes
+                                .iter()
+                                .any(|prefix| key.starts_with(prefix))
+                    })
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect()
+            }
+            SnapshotStore::Diffs => {
+                let height: BlockHeight = match db.get("height") {
+                    Some(bytes) => decode(bytes).map_err(Error::CodingError)?,
+                    None => return Ok(vec![]),
+                };
+                let (_epoch, epoch_start_height) =
+                    read_epoch_and_start_height(&db, height)?;
+                let first_key =
+                    format!("{}/", epoch_start_height.next_height().raw());
+                db.range(first_key..)
+                    .filter(|(key, _)| {
+                        let mut segments = key.split('/');
+                        let is_height =
+                            segments.next().map_or(false, |height| {
+                                height.parse::<u64>().is_ok()
+                            });
+                        is_height
+                            && matches!(
+                                segments.next(),
+                                Some(OLD_DIFF_PREFIX | NEW_DIFF_PREFIX)
+                            )
+                    })
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect()
+            }
+            SnapshotStore::Subspace | SnapshotStore::ReplayProtection => {
+                let prefix = format!("{}/", snapshot_store_prefix(store));
+                db.iter()
+                    .filter_map(|(key, value)| {
+                        key.strip_prefix(&prefix)
+                            .map(|key| (key.to_owned(), value.clone()))
+                    })
+                    .collect()
+            }
+        };
+        // The subspace and replay protection keys are stripped of their prefix
+        // and the block keys have different prefixes, so the ordering has to
+        // be restored
+        entries.sort();
+        Ok(take_snapshot_page(entries, start_after, max_bytes))
+    }
+
+    fn batch_write_snapshot_entries(
+        &self,
+        _batch: &mut Self::WriteBatch,
+        store: SnapshotStore,
+        entries: &[SnapshotEntry],
+    ) -> Result<()> {
+        let prefix = snapshot_store_prefix(store);
+        let mut db = self.0.borrow_mut();
+        for (key, value) in entries {
+            let key = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}/{key}")
+            };
+            db.insert(key, value.clone());
+        }
+        Ok(())
+    }
+
+    fn batch_wipe_snapshot_store(
+        &self,
+        _batch: &mut Self::WriteBatch,
+        store: SnapshotStore,
+    ) -> Result<()> {
+        let prefix = snapshot_store_prefix(store);
+        let prefixed_stores = [
+            format!("{SUBSPACE_CF}/"),
+            format!("{REPLAY_PROTECTION_CF}/"),
+        ];
+        // The state, block and diffs keys are not prefixed, so they are all
+        // wiped together
+        self.0.borrow_mut().retain(|key, _| {
+            if prefix.is_empty() {
+                prefixed_stores.iter().any(|prefix| key.starts_with(prefix))
+            } else {
+                !key.starts_with(&format!("{prefix}/"))
+            }
+        });
+        Ok(())
+    }
+
+    fn read_pruned_diffs(&self) -> Result<PrunedDiffs> {
+        match self.0.borrow().get("pruned_diffs") {
+            Some(bytes) => decode(bytes).map_err(Error::CodingError),
+            None => Ok(PrunedDiffs::default()),
+        }
+    }
+
+    fn batch_write_pruned_diffs(
+        &self,
+        _batch: &mut Self::WriteBatch,
+        pruned: &PrunedDiffs,
+    ) -> Result<()> {
+        self.0
+            .borrow_mut()
+            .insert("pruned_diffs".to_owned(), encode(pruned));
+        Ok(())
+    }
+
+    fn prune_diffs(
+        &mut self,
+        _batch: &mut Self::WriteBatch,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> Result<()> {
+        let mut height = from;
+        while height <= to {
+            self.remove_height_diffs(height);
+            height = height.next_height();
+        }
+        Ok(())
+    }
+
+    fn merge_diffs(
+        &mut self,
+        _batch: &mut Self::WriteBatch,
+        from: BlockHeight,
+        into: BlockHeight,
+    ) -> Result<()> {
+        let mut merged = self.read_height_diffs(into);
+        let mut height = into;
+        while height > from {
+            height = height.prev_height();
+            merge_height_diffs(&mut merged, self.read_height_diffs(height));
+            self.remove_height_diffs(height);
+        }
+        self.remove_height_diffs(into);
+        let mut db = self.0.borrow_mut();
+        for (key, (old, new)) in merged {
+            if let Some(old) = old {
+                db.insert(diff_key(into, OLD_DIFF_PREFIX, &key), old);
+            }
+            if let Some(new) = new {
+                db.insert(diff_key(into, NEW_DIFF_PREFIX, &key), new);
+            }
+        }
+        Ok(())
+    }
+
+    fn batch_write_events(
+        &self,
+        _batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        events: &[Event],
+    ) -> Result<()> {
+        let mut db = self.0.borrow_mut();
+        for (position, event) in (0_u32..).zip(events) {
+            db.insert(
+                events_key(&event_store::event_key(height, position)),
+                encode(event),
+            );
+            for index_key in
+                event_store::event_index_keys(height, position, event)
+            {
+                db.insert(events_key(&index_key), vec![]);
+            }
+        }
+        Ok(())
+    }
+
+    fn read_events(&self, height: BlockHeight) -> Result<Vec<Event>> {
+        let prefix = events_key(&event_store::height_events_prefix(height));
+        self.0
+            .borrow()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .map(|(_, bytes)| decode(bytes).map_err(Error::CodingError))
+            .collect()
+    }
+
+    fn read_event(
+        &self,
+        height: BlockHeight,
+        position: u32,
+    ) -> Result<Option<Event>> {
+        let key = events_key(&event_store::event_key(height, position));
+        self.0
+            .borrow()
+            .get(&key)
+            .map(|bytes| decode(bytes).map_err(Error::CodingError))
+            .transpose()
+    }
+
+    fn read_event_index(
+        &self,
+        index: &EventIndex,
+        from: BlockHeight,
+        before: (BlockHeight, u32),
+        limit: usize,
+    ) -> Result<Vec<(BlockHeight, u32)>> {
+        let (start, end) = event_store::index_range(index, from, before);
+        let (start, end) = (events_key(&start), events_key(&end));
+        if start >= end {
+            return Ok(vec![]);
+        }
+        Ok(self
+            .0
+            .borrow()
+            .range(start..end)
+            .rev()
+            .filter_map(|(key, _)| event_store::parse_event_position(key))
+            .take(limit)
+            .collect())
+    }
+
+    fn read_pruned_events_height(&self) -> Result<Option<BlockHeight>> {
+        self.0
+            .borrow()
+            .get(&events_key(event_store::PRUNED_EVENTS_KEY))
+            .map(|bytes| decode(bytes).map_err(Error::CodingError))
+            .transpose()
+    }
+
+    fn prune_events(
+        &mut self,
+        _batch: &mut Self::WriteBatch,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> Result<()> {
+        let mut height = from;
+        while height <= to {
+            let events = self.read_events(height)?;
+            let mut db = self.0.borrow_mut();
+            for (position, event) in (0_u32..).zip(&events) {
+                db.remove(&events_key(&event_store::event_key(
+                    height, position,
+                )));
+                for index_key in
+                    event_store::event_index_keys(height, position, event)
+                {
+                    db.remove(&events_key(&index_key));
+                }
+            }
+            height = height.next_height();
+        }
+        self.0
+            .borrow_mut()
+            .insert(events_key(event_store::PRUNED_EVENTS_KEY), encode(&to));
+        Ok(())
+    }
+}
+
+impl MockDB {
+    /// Read all the subspace diffs at the given height
+    fn read_height_diffs(&self, height: BlockHeight) -> HeightDiffs {
+        let db = self.0.borrow();
+        let mut diffs = HeightDiffs::new();
+        for (kind, is_old) in
+            [(OLD_DIFF_PREFIX, true), (NEW_DIFF_PREFIX, false)]
+        {
+            let prefix = diff_key(height, kind, "");
+            for (key, value) in db.range(prefix.clone()..) {
+                let Some(key) = key.strip_prefix(&prefix) else {
+                    break;
+                };
+                let entry = diffs.entry(key.to_owned()).or_default();
+                if is_old {
+                    entry.0 = Some(value.clone());
+                } else {
+                    entry.1 = Some(value.clone());
+                }
+            }
+        }
+        diffs
+    }
+
+    /// Remove all the subspace diffs at the given height
+    fn remove_height_diffs(&self, height: BlockHeight) {
+        let old_prefix = diff_key(height, OLD_DIFF_PREFIX, "");
+        let new_prefix = diff_key(height, NEW_DIFF_PREFIX, "");
+        self.0.borrow_mut().retain(|key, _| {
+            !key.starts_with(&old_prefix) && !key.starts_with(&new_prefix)
+        });
+    }
+}
+
+/// Get the key of an entry of the event store
+fn events_key(key: &str) -> String {
+    format!("events/{key}")
+}
+
+/// Get the key of the old or new diff of the given subspace key at a height
+fn diff_key(height: BlockHeight, kind: &str, key: &str) -> String {
+    format!("{}/{kind}/{key}", height.raw())
 }
 
 impl<'iter> DBIter<'iter> for MockDB {
@@ -810,6 +1244,38 @@ impl Iterator for PrefixIterator<MockIterator> {
 
 impl DBWriteBatch for MockDBWriteBatch {}
 
+/// Get the prefix of the keys of the given snapshot store in the `MockDB`. The
+/// state and block keys are not prefixed.
+fn snapshot_store_prefix(store: SnapshotStore) -> &'static str {
+    match store {
+        SnapshotStore::State | SnapshotStore::Block | SnapshotStore::Diffs => {
+            ""
+        }
+        SnapshotStore::Subspace => SUBSPACE_CF,
+        SnapshotStore::ReplayProtection => REPLAY_PROTECTION_CF,
+    }
+}
+
+/// Read the epoch of the block at the given height and the height at which
+/// the Merkle tree stores of the epoch have been written
+fn read_epoch_and_start_height(
+    db: &BTreeMap<String, Vec<u8>>,
+    height: BlockHeight,
+) -> Result<(Epoch, BlockHeight)> {
+    let pred_epochs_key = format!("{}/pred_epochs", height.raw());
+    let pred_epochs: Epochs = match db.get(&pred_epochs_key) {
+        Some(bytes) => decode(bytes).map_err(Error::CodingError)?,
+        None => Epochs::default(),
+    };
+    let epoch = pred_epochs.get_epoch(height).unwrap_or_default();
+    let epoch_start_height = match pred_epochs.get_start_height_of_epoch(epoch)
+    {
+        Some(BlockHeight(0)) | None => BlockHeight(1),
+        Some(height) => height,
+    };
+    Ok((epoch, epoch_start_height))
+}
+
 fn unknown_key_error(key: &str) -> Result<()> {
     Err(Error::UnknownKey {
         key: key.to_owned(),
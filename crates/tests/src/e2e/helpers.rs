@@ -22,7 +22,6 @@ use namada::ledger::queries::{Rpc, RPC};
 use namada::tendermint_rpc::HttpClient;
 use namada::token;
 use namada_apps::cli::context::ENV_VAR_CHAIN_ID;
-use namada_apps::config::genesis::chain::DeriveEstablishedAddress;
 use namada_apps::config::genesis::templates;
 use namada_apps::config::utils::convert_tm_addr_to_socket_addr;
 use namada_apps::config::{Config, TendermintMode};
@@ -237,7 +236,7 @@ pub fn get_established_addr_from_pregenesis<P: AsRef<Path>>(
     let acct = established_accounts.iter().find(|&acct| {
         acct.public_keys.len() == 1 && acct.public_keys[0].raw == pk
     })?;
-    Some(acct.derive_address())
+    Some(acct.address())
 }
 
 /// Find the address of an account by its alias from the wallet
@@ -232,6 +232,45 @@ mod tests {
         tx::ctx().init_account(code_hash, &None).unwrap();
     }
 
+    /// Test that the weights of the public keys of an account are rejected
+    /// unless there's a non-zero weight for each key and their sum reaches
+    /// the threshold.
+    #[test]
+    fn test_tx_set_public_key_weights() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        let owner = address::testing::established_address_1();
+        let public_keys = [
+            key::testing::keypair_1().ref_to(),
+            key::testing::keypair_2().ref_to(),
+        ];
+        namada::account::init_account_storage(
+            tx::ctx(),
+            &owner,
+            &public_keys,
+            3,
+        )
+        .unwrap();
+
+        let set_weights = |weights: &[u8]| {
+            namada::account::set_public_key_weights(tx::ctx(), &owner, weights)
+        };
+        // A weight is missing
+        assert!(set_weights(&[3]).is_err());
+        // A weight is zero
+        assert!(set_weights(&[3, 0]).is_err());
+        // The sum of the weights is below the threshold
+        assert!(set_weights(&[1, 1]).is_err());
+        assert!(set_weights(&[2, 1]).is_ok());
+        assert_eq!(
+            namada::account::public_keys_index_map(tx::ctx(), &owner)
+                .unwrap()
+                .total_weight(),
+            3
+        );
+    }
+
     /// Test that a tx updating validity predicate that is not in the allowlist
     /// fails.
     #[test]
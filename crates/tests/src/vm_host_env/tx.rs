@@ -189,6 +189,16 @@ impl TestTxEnv {
             .unwrap();
     }
 
+    /// Set the weights of the public keys of the address.
+    pub fn write_public_key_weights(
+        &mut self,
+        address: &Address,
+        weights: &[u8],
+    ) {
+        account::set_public_key_weights(&mut self.state, address, weights)
+            .expect("Unable to write the public key weights.");
+    }
+
     /// Commit the genesis state. Typically, you'll want to call this after
     /// setting up the initial state, before running a transaction.
     pub fn commit_genesis(&mut self) {
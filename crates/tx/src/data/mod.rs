@@ -10,10 +10,12 @@ pub mod pgf;
 pub mod pos;
 /// transaction protocols made by validators
 pub mod protocol;
+/// txs scheduled for a future execution
+pub mod schedule;
 /// wrapper txs with encrypted payloads
 pub mod wrapper;
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Display};
 use std::str::FromStr;
 
@@ -26,7 +28,7 @@ use namada_core::ethereum_structs::EthBridgeEvent;
 use namada_core::hash::Hash;
 use namada_core::ibc::IbcEvent;
 use namada_core::storage;
-use namada_gas::{Gas, VpsGas};
+use namada_gas::{Gas, GasBreakdown, VpsGas};
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
@@ -185,6 +187,35 @@ pub struct TxResult {
     pub ibc_events: BTreeSet<IbcEvent>,
     /// Ethereum bridge events emitted by the transaction
     pub eth_bridge_events: BTreeSet<EthBridgeEvent>,
+    /// The breakdown by category of the gas used by the transaction, if it
+    /// was recorded (only in dry runs)
+    pub gas_breakdown: Option<GasBreakdown>,
+    /// The results of the inner transactions of a batch, in execution order.
+    /// Empty if the transaction is not a batch
+    pub batch_results: Vec<BatchedTxResult>,
+}
+
+/// The result of the execution of one of the inner transactions of a batch
+// TODO derive BorshSchema after <https://github.com/near/borsh-rs/issues/82>
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct BatchedTxResult {
+    /// The hash of the inner transaction's code section
+    pub code_hash: Hash,
+    /// Gas used by the inner transaction, including the gas used by the VPs
+    /// that validated it
+    pub gas_used: Gas,
+    /// The verifiers requested by the inner transaction
+    pub verifiers: BTreeSet<Address>,
+    /// The result of the VPs triggered by the inner transaction
+    pub vps_result: VpsResult,
 }
 
 impl TxResult {
@@ -212,12 +243,33 @@ pub struct VpsResult {
     pub rejected_vps: BTreeSet<Address>,
     /// The total gas used by all the VPs
     pub gas_used: VpsGas,
+    /// The breakdown by category of the gas used by each VP, if it was
+    /// recorded
+    pub gas_breakdown: BTreeMap<Address, GasBreakdown>,
     /// Errors occurred in any of the VPs, if any
     pub errors: Vec<(Address, String)>,
     /// Sentinel to signal an invalid transaction signature
     pub invalid_sig: bool,
 }
 
+impl VpsResult {
+    /// Merge the result of the VPs of another inner transaction of the same
+    /// batch into this one. The gas used by the VPs is not merged, as it is
+    /// reported in the [`BatchedTxResult`] of each inner transaction.
+    pub fn merge(&mut self, other: VpsResult) {
+        self.accepted_vps.extend(other.accepted_vps);
+        self.rejected_vps.extend(other.rejected_vps);
+        for (addr, breakdown) in other.gas_breakdown {
+            self.gas_breakdown
+                .entry(addr)
+                .or_default()
+                .merge(&breakdown);
+        }
+        self.errors.extend(other.errors);
+        self.invalid_sig |= other.invalid_sig;
+    }
+}
+
 impl fmt::Display for TxResult {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if f.alternate() {
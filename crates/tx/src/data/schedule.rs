@@ -0,0 +1,305 @@
+//! Transactions scheduled for an execution at a future block height or
+//! epoch
+
+use std::fmt::{self, Display};
+
+use namada_core::address::{Address, SCHEDULER};
+use namada_core::borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use namada_core::hash::Hash;
+use namada_core::parameters::EpochDuration;
+use namada_core::storage::{BlockHeight, DbKeySeg, Epoch, Key, KeySeg};
+use namada_core::time::{DateTimeUtc, Duration, DurationSecs};
+use namada_core::token::{Amount, DenominatedAmount};
+use serde::{Deserialize, Serialize};
+
+use crate::data::{Fee, GasLimit};
+use crate::{Tx, TxCommitments};
+
+/// Storage sub-key of the scheduled transactions queue
+const QUEUE_STORAGE_KEY: &str = "queue";
+/// Storage sub-key of the transactions scheduled at a block height
+const HEIGHT_STORAGE_KEY: &str = "height";
+/// Storage sub-key of the transactions scheduled at an epoch
+const EPOCH_STORAGE_KEY: &str = "epoch";
+
+/// The point in time at which a scheduled transaction gets executed
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub enum ScheduleTarget {
+    /// At the beginning of the block with the given height
+    Height(BlockHeight),
+    /// At the beginning of the first block of the given epoch
+    Epoch(Epoch),
+}
+
+impl ScheduleTarget {
+    /// Estimate the latest time at which the transactions scheduled at this
+    /// target get executed, from the block at the given height, epoch and
+    /// time. Blocks are assumed to take at most the given time, and epochs to
+    /// end with the first block after both their minimum duration and number
+    /// of blocks have passed. Returns `None` on overflow.
+    pub fn estimate_execution_time(
+        &self,
+        height: BlockHeight,
+        epoch: Epoch,
+        time: DateTimeUtc,
+        max_time_per_block: DurationSecs,
+        epoch_duration: &EpochDuration,
+    ) -> Option<DateTimeUtc> {
+        let secs = match self {
+            Self::Height(target) => target
+                .0
+                .saturating_sub(height.0)
+                .checked_mul(max_time_per_block.0)?,
+            Self::Epoch(target) => {
+                let blocks_secs = epoch_duration
+                    .min_num_of_blocks
+                    .checked_mul(max_time_per_block.0)?;
+                let epoch_secs =
+                    std::cmp::max(epoch_duration.min_duration.0, blocks_secs)
+                        .checked_add(max_time_per_block.0)?;
+                target.0.saturating_sub(epoch.0).checked_mul(epoch_secs)?
+            }
+        };
+        // Bounded by the maximum duration in milliseconds
+        let secs = i64::try_from(secs)
+            .ok()
+            .filter(|secs| *secs <= i64::MAX / 1_000)?;
+        time.0
+            .checked_add_signed(Duration::seconds(secs))
+            .map(DateTimeUtc)
+    }
+}
+
+impl Display for ScheduleTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Height(height) => write!(f, "height {}", height),
+            Self::Epoch(epoch) => write!(f, "epoch {}", epoch),
+        }
+    }
+}
+
+/// The commitment of a transaction to its execution by the scheduler at the
+/// given target. Designated by the code hash in the header of the transaction,
+/// so that it gets signed together with the header.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct Schedule {
+    /// When the transaction gets executed
+    pub target: ScheduleTarget,
+    /// The commitments to the code and data of the transaction
+    pub commitments: TxCommitments,
+}
+
+/// A signed inner transaction queued for an execution at a future block
+/// height or epoch, together with the gas prepaid by its owner
+#[derive(
+    Debug,
+    Clone,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct ScheduledTx {
+    /// The account that scheduled the transaction and prepaid its gas
+    pub owner: Address,
+    /// When to execute the transaction
+    pub target: ScheduleTarget,
+    /// The gas limit of the execution
+    pub gas_limit: GasLimit,
+    /// The fee paid per gas unit, escrowed when scheduling the transaction
+    pub fee: Fee,
+    /// The signed inner transaction to execute
+    pub tx: Tx,
+}
+
+impl ScheduledTx {
+    /// The hash identifying the scheduled transaction, i.e. the hash of the
+    /// raw header of its inner transaction
+    pub fn hash(&self) -> Hash {
+        self.tx.raw_header_hash()
+    }
+
+    /// The fee escrowed for the execution of the transaction, if it doesn't
+    /// overflow
+    pub fn get_prepaid_fee(&self) -> Option<DenominatedAmount> {
+        self.fee
+            .amount_per_gas_unit
+            .checked_mul(Amount::from(self.gas_limit).into())
+    }
+
+    /// The storage key of the transaction in the scheduled transactions queue
+    pub fn storage_key(&self) -> Key {
+        scheduled_tx_key(&self.target, &self.owner, &self.hash())
+    }
+}
+
+/// A tx data type to cancel a scheduled transaction and refund its prepaid
+/// fee
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct CancelScheduledTx {
+    /// The account that scheduled the transaction
+    pub owner: Address,
+    /// When the transaction was scheduled to be executed
+    pub target: ScheduleTarget,
+    /// The hash of the scheduled transaction
+    pub hash: Hash,
+}
+
+impl CancelScheduledTx {
+    /// The storage key of the transaction in the scheduled transactions queue
+    pub fn storage_key(&self) -> Key {
+        scheduled_tx_key(&self.target, &self.owner, &self.hash)
+    }
+}
+
+/// Storage key prefix of the transactions scheduled at the given target
+pub fn scheduled_txs_prefix(target: &ScheduleTarget) -> Key {
+    let prefix = Key::from(SCHEDULER.to_db_key())
+        .push(&QUEUE_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key");
+    match target {
+        ScheduleTarget::Height(height) => prefix
+            .push(&HEIGHT_STORAGE_KEY.to_owned())
+            .and_then(|key| key.push(height)),
+        ScheduleTarget::Epoch(epoch) => prefix
+            .push(&EPOCH_STORAGE_KEY.to_owned())
+            .and_then(|key| key.push(epoch)),
+    }
+    .expect("Cannot obtain a storage key")
+}
+
+/// Storage key of a transaction scheduled by the given owner
+pub fn scheduled_tx_key(
+    target: &ScheduleTarget,
+    owner: &Address,
+    hash: &Hash,
+) -> Key {
+    scheduled_txs_prefix(target)
+        .push(owner)
+        .and_then(|key| key.push(hash))
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is the key of a scheduled transaction. If
+/// it is, returns its target, owner and hash.
+pub fn is_scheduled_tx_key(
+    key: &Key,
+) -> Option<(ScheduleTarget, &Address, Hash)> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(queue),
+            DbKeySeg::StringSeg(kind),
+            DbKeySeg::StringSeg(target),
+            DbKeySeg::AddressSeg(owner),
+            DbKeySeg::StringSeg(hash),
+        ] if addr == &SCHEDULER && queue.as_str() == QUEUE_STORAGE_KEY => {
+            let target = match kind.as_str() {
+                HEIGHT_STORAGE_KEY => ScheduleTarget::Height(
+                    BlockHeight::parse(target.clone()).ok()?,
+                ),
+                EPOCH_STORAGE_KEY => {
+                    ScheduleTarget::Epoch(Epoch::parse(target.clone()).ok()?)
+                }
+                _ => return None,
+            };
+            Some((target, owner, Hash::parse(hash.clone()).ok()?))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namada_core::address::testing::established_address_1;
+
+    use super::*;
+
+    #[test]
+    fn test_scheduled_tx_key_roundtrip() {
+        let owner = established_address_1();
+        let hash = Hash::sha256(b"scheduled");
+        for target in [
+            ScheduleTarget::Height(BlockHeight(42)),
+            ScheduleTarget::Epoch(Epoch(7)),
+        ] {
+            let key = scheduled_tx_key(&target, &owner, &hash);
+            assert!(
+                key.split_prefix(&scheduled_txs_prefix(&target))
+                    .flatten()
+                    .is_some()
+            );
+            assert_eq!(is_scheduled_tx_key(&key), Some((target, &owner, hash)));
+        }
+        let other = Key::from(SCHEDULER.to_db_key())
+            .push(&QUEUE_STORAGE_KEY.to_owned())
+            .unwrap();
+        assert_eq!(is_scheduled_tx_key(&other), None);
+    }
+
+    #[test]
+    fn test_estimate_execution_time() {
+        let time = DateTimeUtc::now();
+        let epoch_duration = EpochDuration {
+            min_num_of_blocks: 10,
+            min_duration: DurationSecs(60),
+        };
+        let estimate = |target: ScheduleTarget| {
+            target.estimate_execution_time(
+                BlockHeight(100),
+                Epoch(5),
+                time,
+                DurationSecs(30),
+                &epoch_duration,
+            )
+        };
+        assert_eq!(
+            estimate(ScheduleTarget::Height(BlockHeight(110))),
+            Some(time + DurationSecs(300))
+        );
+        // Each epoch takes at most its minimum number of blocks, which last
+        // longer than its minimum duration, and the block ending it
+        assert_eq!(
+            estimate(ScheduleTarget::Epoch(Epoch(7))),
+            Some(time + DurationSecs(2 * 330))
+        );
+        assert_eq!(
+            estimate(ScheduleTarget::Height(BlockHeight(u64::MAX))),
+            None
+        );
+    }
+}
@@ -13,7 +13,7 @@ pub use namada_core::sign::SignatureIndex;
 pub use types::{
     standalone_signature, verify_standalone_sig, Code, Commitment,
     CompressedSignature, Data, DecodeError, Header, MaspBuilder, Memo, Section,
-    Signature, Signed, Signer, Tx, TxError, VerifySigError,
+    Signature, Signed, Signer, Tx, TxCommitments, TxError, VerifySigError,
 };
 
 /// Creates a new event with the hash and height of the transaction
@@ -61,9 +61,11 @@ pub fn new_tx_event(tx: &Tx, height: u64) -> Event {
 #[cfg(test)]
 mod tests {
     use data_encoding::HEXLOWER;
+    use namada_core::storage::BlockHeight;
     use prost::Message;
 
     use super::*;
+    use crate::data::schedule::ScheduleTarget;
 
     #[test]
     fn encoding_round_trip() {
@@ -79,4 +81,89 @@ mod tests {
         let tx_from_bytes = Tx::decode(&tx_from_hex[..]).unwrap();
         assert_eq!(tx, tx_from_bytes);
     }
+
+    #[test]
+    fn batch_inner_keeps_header_hash() {
+        let mut batch = Tx::from_type(TxType::Raw);
+        batch
+            .push_batch_inner(
+                Code::new(vec![1], None),
+                Data::new("first".as_bytes().to_owned()),
+            )
+            .push_batch_inner(
+                Code::new(vec![2], None),
+                Data::new("second".as_bytes().to_owned()),
+            );
+        assert!(batch.is_batch());
+        assert_eq!(batch.batch().len(), 2);
+
+        let commitments = batch.batch();
+        for commitment in &commitments {
+            let inner = batch.batch_inner(commitment);
+            assert_eq!(inner.header_hash(), batch.header_hash());
+            assert_eq!(inner.raw_header_hash(), batch.raw_header_hash());
+            assert_eq!(inner.code_sechash(), &commitment.code_hash);
+            assert!(inner.data().is_some());
+            // An inner tx can be told apart from a standalone tx
+            assert!(!inner.is_batch());
+            assert!(inner.has_undesignated_batch());
+        }
+        assert_eq!(
+            batch.batch_inner(&commitments[1]).data(),
+            Some("second".as_bytes().to_owned())
+        );
+
+        // The batch commitments are part of the hash
+        let mut other = batch.clone();
+        let mut swapped = batch.batch();
+        swapped.swap(0, 1);
+        other.set_batch(swapped);
+        assert_ne!(other.header_hash(), batch.header_hash());
+
+        // A tx that is not a batch has no batch section
+        let tx = Tx::from_type(TxType::Raw);
+        assert!(!tx.is_batch());
+        assert!(!tx.has_undesignated_batch());
+        assert!(tx.batch().is_empty());
+    }
+
+    #[test]
+    fn scheduled_inner_keeps_header_hash() {
+        let target = ScheduleTarget::Height(BlockHeight(10));
+        let mut tx = Tx::from_type(TxType::Raw);
+        tx.set_code(Code::new(vec![1], None));
+        tx.set_data(Data::new("data".as_bytes().to_owned()));
+        let code_hash = *tx.code_sechash();
+        assert!(tx.schedule().is_none());
+        assert!(tx.scheduled_inner().is_none());
+
+        tx.set_schedule(target);
+        assert!(tx.carries_schedule());
+        assert_eq!(tx.schedule().map(|schedule| schedule.target), Some(target));
+        let inner = tx.scheduled_inner().unwrap();
+        assert_eq!(inner.header_hash(), tx.header_hash());
+        assert_eq!(inner.raw_header_hash(), tx.raw_header_hash());
+        assert_eq!(inner.code_sechash(), &code_hash);
+        assert_eq!(inner.data(), Some("data".as_bytes().to_owned()));
+        // The inner tx still carries the schedule, so it can't be wrapped
+        assert!(inner.carries_schedule());
+
+        // The target is part of the hash
+        let mut other = tx.clone();
+        other.set_schedule(ScheduleTarget::Height(BlockHeight(11)));
+        assert_ne!(other.header_hash(), tx.header_hash());
+        assert_eq!(other.scheduled_inner().unwrap().code_sechash(), &code_hash);
+
+        // A scheduled batch keeps the header hash of its inner txs
+        let mut batch = Tx::from_type(TxType::Raw);
+        batch.push_batch_inner(
+            Code::new(vec![2], None),
+            Data::new("inner".as_bytes().to_owned()),
+        );
+        batch.set_schedule(target);
+        let scheduled = batch.scheduled_inner().unwrap();
+        assert!(scheduled.is_batch());
+        let inner = scheduled.batch_inner(&scheduled.batch()[0]);
+        assert_eq!(inner.header_hash(), batch.header_hash());
+    }
 }
@@ -27,6 +27,7 @@ use sha2::{Digest, Sha256};
 use thiserror::Error;
 
 use crate::data::protocol::ProtocolTx;
+use crate::data::schedule::{Schedule, ScheduleTarget};
 use crate::data::{hash_tx, DecryptedTx, Fee, GasLimit, TxType, WrapperTx};
 use crate::proto;
 
@@ -202,6 +203,17 @@ pub fn verify_standalone_sig<T, S: Signable<T>>(
     .map_err(Into::into)
 }
 
+/// The sum of the weights of the verified public keys
+fn verified_weight(
+    verified_pks: &HashSet<u8>,
+    public_keys_index_map: &AccountPublicKeysMap,
+) -> u32 {
+    verified_pks
+        .iter()
+        .map(|idx| u32::from(public_keys_index_map.get_weight_from_index(*idx)))
+        .sum()
+}
+
 /// A section representing transaction data
 #[derive(
     Clone,
@@ -730,6 +742,18 @@ pub enum Section {
     MaspBuilder(MaspBuilder),
     /// Wrap a header with a section for the purposes of computing hashes
     Header(Header),
+    /// The ordered commitments to the inner transactions of a batch, which
+    /// get executed in this order and atomically. Designated by the code hash
+    /// in the header of the batch.
+    Batch(Vec<TxCommitments>),
+    /// The account that granted a fee allowance to the fee payer of the
+    /// wrapper, from which the fees are drawn instead of the fee payer's
+    /// balance
+    FeeGranter(Address),
+    /// The commitment of the transaction to its execution by the scheduler at
+    /// a given target. Designated by the code hash in the header of the
+    /// transaction.
+    Schedule(Schedule),
 }
 
 impl Section {
@@ -752,6 +776,28 @@ impl Section {
                 hasher
             }
             Self::Header(header) => header.hash(hasher),
+            Self::Batch(batch) => {
+                hasher.update(batch.serialize_to_vec());
+                hasher
+            }
+            Self::FeeGranter(granter) => {
+                hasher.update(granter.serialize_to_vec());
+                hasher
+            }
+            Self::Schedule(schedule) => {
+                hasher.update(schedule.serialize_to_vec());
+                hasher
+            }
+        }
+    }
+
+    /// Check if this section is a batch or a schedule committing to the given
+    /// code and data
+    fn commits_to(&self, commitments: &TxCommitments) -> bool {
+        match self {
+            Self::Batch(batch) => batch.contains(commitments),
+            Self::Schedule(schedule) => schedule.commitments == *commitments,
+            _ => false,
         }
     }
 
@@ -844,6 +890,27 @@ impl Section {
     }
 }
 
+/// The commitments to the code and data of one of the inner transactions of a
+/// batch
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct TxCommitments {
+    /// The SHA-256 hash of the inner transaction's code section
+    pub code_hash: namada_core::hash::Hash,
+    /// The SHA-256 hash of the inner transaction's data section
+    pub data_hash: namada_core::hash::Hash,
+}
+
 /// A Namada transaction header indicating where transaction subcomponents can
 /// be found
 #[derive(
@@ -889,6 +956,13 @@ impl Header {
         }
     }
 
+    /// Get the time after which this transaction gets rejected. A transaction
+    /// without an expiration expires
+    /// [`namada_replay_protection::DEFAULT_TX_LIFETIME`] after its timestamp.
+    pub fn effective_expiration(&self) -> DateTimeUtc {
+        namada_replay_protection::tx_expiration(self.timestamp, self.expiration)
+    }
+
     /// Get the hash of this transaction header.
     pub fn hash<'a>(&self, hasher: &'a mut Sha256) -> &'a mut Sha256 {
         hasher.update(self.serialize_to_vec());
@@ -1022,14 +1096,40 @@ impl Tx {
         self.header.clone()
     }
 
+    /// Get the header committed to by the hashes of this transaction. This is
+    /// the header itself, unless this transaction is an inner transaction
+    /// obtained from [`Tx::batch_inner`] or [`Tx::scheduled_inner`], in which
+    /// case it is the header of its batch or of its schedule. Since a batch can
+    /// itself be scheduled, the lookup is repeated, at most once per section.
+    fn committed_header(&self) -> Cow<Header> {
+        let mut header = Cow::Borrowed(&self.header);
+        for _ in 0..self.sections.len() {
+            let designated = TxCommitments {
+                code_hash: header.code_hash,
+                data_hash: header.data_hash,
+            };
+            let Some(section) = self
+                .sections
+                .iter()
+                .find(|section| section.commits_to(&designated))
+            else {
+                break;
+            };
+            let header = header.to_mut();
+            header.code_hash = section.get_hash();
+            header.data_hash = namada_core::hash::Hash::default();
+        }
+        header
+    }
+
     /// Get the transaction header hash
     pub fn header_hash(&self) -> namada_core::hash::Hash {
-        Section::Header(self.header.clone()).get_hash()
+        Section::Header(self.committed_header().into_owned()).get_hash()
     }
 
     /// Gets the hash of the decrypted transaction's header
     pub fn raw_header_hash(&self) -> namada_core::hash::Hash {
-        let mut raw_header = self.header();
+        let mut raw_header = self.committed_header().into_owned();
         raw_header.tx_type = TxType::Raw;
 
         Section::Header(raw_header).get_hash()
@@ -1056,9 +1156,11 @@ impl Tx {
         hash: &namada_core::hash::Hash,
     ) -> Option<Cow<Section>> {
         if self.header_hash() == *hash {
-            return Some(Cow::Owned(Section::Header(self.header.clone())));
+            return Some(Cow::Owned(Section::Header(
+                self.committed_header().into_owned(),
+            )));
         } else if self.raw_header_hash() == *hash {
-            let mut header = self.header();
+            let mut header = self.committed_header().into_owned();
             header.tx_type = TxType::Raw;
             return Some(Cow::Owned(Section::Header(header)));
         }
@@ -1131,6 +1233,134 @@ impl Tx {
         self.sections.last_mut().unwrap()
     }
 
+    /// Check if this transaction is a batch of inner transactions, i.e. if
+    /// its code hash designates a batch section
+    pub fn is_batch(&self) -> bool {
+        self.sections.iter().any(|section| {
+            matches!(section, Section::Batch(_))
+                && section.get_hash() == self.header.code_hash
+        })
+    }
+
+    /// Check if this transaction carries a batch section that is not
+    /// designated by its code hash. Such a transaction may be an inner
+    /// transaction extracted from a batch, so it must be rejected in order
+    /// not to break the atomicity of the batch.
+    pub fn has_undesignated_batch(&self) -> bool {
+        self.sections.iter().any(|section| {
+            matches!(section, Section::Batch(_))
+                && section.get_hash() != self.header.code_hash
+        })
+    }
+
+    /// Get the commitments to the inner transactions of this batch, in
+    /// execution order. Empty if this transaction is not a batch.
+    pub fn batch(&self) -> Vec<TxCommitments> {
+        match self
+            .get_section(self.code_sechash())
+            .as_ref()
+            .map(Cow::as_ref)
+        {
+            Some(Section::Batch(batch)) => batch.clone(),
+            _ => vec![],
+        }
+    }
+
+    /// Set the commitments to the inner transactions of this batch, replacing
+    /// the previous batch section if any
+    pub fn set_batch(&mut self, batch: Vec<TxCommitments>) -> &mut Self {
+        self.sections
+            .retain(|section| !matches!(section, Section::Batch(_)));
+        let sec = Section::Batch(batch);
+        self.set_code_sechash(sec.get_hash());
+        self.set_data_sechash(namada_core::hash::Hash::default());
+        self.sections.push(sec);
+        self
+    }
+
+    /// Append an inner transaction made of the given code and data to this
+    /// batch
+    pub fn push_batch_inner(&mut self, code: Code, data: Data) -> &mut Self {
+        let code_hash = self.add_section(Section::Code(code)).get_hash();
+        let data_hash = self.add_section(Section::Data(data)).get_hash();
+        let mut batch = self.batch();
+        batch.push(TxCommitments {
+            code_hash,
+            data_hash,
+        });
+        self.set_batch(batch)
+    }
+
+    /// Get a copy of this batch whose code and data hashes designate the given
+    /// inner transaction. The copy keeps the sections of the batch, including
+    /// its batch section, so that its header hash is still the one of the
+    /// batch and the signatures over the batch remain valid for the inner
+    /// transaction.
+    pub fn batch_inner(&self, commitments: &TxCommitments) -> Tx {
+        let mut inner = self.clone();
+        inner.header.code_hash = commitments.code_hash;
+        inner.header.data_hash = commitments.data_hash;
+        inner
+    }
+
+    /// Get the schedule this transaction is committed to, if its code hash
+    /// designates a schedule section
+    pub fn schedule(&self) -> Option<Schedule> {
+        match self
+            .get_section(self.code_sechash())
+            .as_ref()
+            .map(Cow::as_ref)
+        {
+            Some(Section::Schedule(schedule)) => Some(schedule.clone()),
+            _ => None,
+        }
+    }
+
+    /// Check if this transaction carries a schedule section, designated or
+    /// not. Such a transaction may only be executed by the scheduler, so it
+    /// must not be accepted in a wrapper.
+    pub fn carries_schedule(&self) -> bool {
+        self.sections
+            .iter()
+            .any(|section| matches!(section, Section::Schedule(_)))
+    }
+
+    /// Commit this transaction to its execution by the scheduler at the given
+    /// target, replacing the previous schedule section if any. This must be
+    /// done before signing the transaction.
+    pub fn set_schedule(&mut self, target: ScheduleTarget) -> &mut Self {
+        let commitments = match self.schedule() {
+            Some(schedule) => schedule.commitments,
+            None => TxCommitments {
+                code_hash: self.header.code_hash,
+                data_hash: self.header.data_hash,
+            },
+        };
+        self.sections
+            .retain(|section| !matches!(section, Section::Schedule(_)));
+        let sec = Section::Schedule(Schedule {
+            target,
+            commitments,
+        });
+        self.set_code_sechash(sec.get_hash());
+        self.set_data_sechash(namada_core::hash::Hash::default());
+        self.sections.push(sec);
+        self
+    }
+
+    /// Get a copy of this scheduled transaction whose code and data hashes
+    /// designate its actual code and data. The copy keeps its schedule
+    /// section, so that its header hash is still the one of the scheduled
+    /// transaction and its signatures remain valid. `None` if this
+    /// transaction is not committed to a schedule.
+    pub fn scheduled_inner(&self) -> Option<Tx> {
+        let schedule = self.schedule()?;
+        let mut inner = self.clone();
+        inner.header.code_hash = schedule.commitments.code_hash;
+        inner.header.data_hash = schedule.commitments.data_hash;
+        Some(inner)
+    }
+
     /// Get the transaction data hash stored in the header
     pub fn data_sechash(&self) -> &namada_core::hash::Hash {
         &self.header.data_hash
@@ -1175,7 +1405,8 @@ impl Tx {
     }
 
     /// Verify that the section with the given hash has been signed by the given
-    /// public key
+    /// public keys. The threshold is met when the sum of the weights of the
+    /// public keys with a valid signature reaches it.
     pub fn verify_signatures<F>(
         &self,
         hashes: &[namada_core::hash::Hash],
@@ -1230,7 +1461,9 @@ impl Tx {
                         witnesses.push(signatures);
                     }
                     // Short-circuit these checks if the threshold is exceeded
-                    if verified_pks.len() >= threshold.into() {
+                    if verified_weight(&verified_pks, &public_keys_index_map)
+                        >= threshold.into()
+                    {
                         return Ok(witnesses);
                     }
                 }
@@ -1238,7 +1471,7 @@ impl Tx {
         }
         Err(VerifySigError::InvalidSectionSignature(format!(
             "signature threshold not met: ({} < {})",
-            verified_pks.len(),
+            verified_weight(&verified_pks, &public_keys_index_map),
             threshold
         )))
     }
@@ -1489,6 +1722,24 @@ impl Tx {
         self
     }
 
+    /// Draw the fees of the wrapper from the fee allowance granted to the fee
+    /// payer by the given account
+    pub fn add_fee_granter(&mut self, granter: Address) -> &mut Self {
+        self.sections
+            .retain(|section| !matches!(section, Section::FeeGranter(_)));
+        self.add_section(Section::FeeGranter(granter));
+        self
+    }
+
+    /// Get the account that granted the fee allowance the fees of the wrapper
+    /// are drawn from, if any
+    pub fn fee_granter(&self) -> Option<Address> {
+        self.sections.iter().find_map(|section| match section {
+            Section::FeeGranter(granter) => Some(granter.clone()),
+            _ => None,
+        })
+    }
+
     /// Add fee payer keypair to the tx builder
     pub fn sign_wrapper(&mut self, keypair: common::SecretKey) -> &mut Self {
         self.protocol_filter();
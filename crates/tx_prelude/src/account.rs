@@ -13,6 +13,11 @@ pub fn init_account(
         &data.public_keys,
         data.threshold,
     )?;
+    namada_account::set_public_key_weights(
+        ctx,
+        owner,
+        &data.public_key_weights,
+    )?;
 
     Ok(owner.to_owned())
 }
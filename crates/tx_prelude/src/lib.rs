@@ -11,6 +11,7 @@ pub mod ibc;
 pub mod key;
 pub mod pgf;
 pub mod proof_of_stake;
+pub mod scheduler;
 pub mod token;
 
 use core::slice;
@@ -0,0 +1,43 @@
+use namada_core::address::SCHEDULER;
+pub use namada_tx::data::schedule::*;
+
+use super::*;
+
+/// Queue the given transaction for an execution at its target and escrow its
+/// prepaid fee.
+pub fn schedule_tx(ctx: &mut Ctx, scheduled: ScheduledTx) -> TxResult {
+    let fee = scheduled.get_prepaid_fee().ok_or_else(|| {
+        Error::new_const("The prepaid fee of the scheduled tx overflows")
+    })?;
+    token::transfer(
+        ctx,
+        &scheduled.owner,
+        &SCHEDULER,
+        &scheduled.fee.token,
+        fee,
+    )?;
+    ctx.write(&scheduled.storage_key(), scheduled)
+}
+
+/// Remove the given transaction from the queue and refund its prepaid fee to
+/// the owner.
+pub fn cancel_scheduled_tx(
+    ctx: &mut Ctx,
+    cancel: CancelScheduledTx,
+) -> TxResult {
+    let key = cancel.storage_key();
+    let scheduled: ScheduledTx = ctx.read(&key)?.ok_or_else(|| {
+        Error::new_const("The scheduled tx to cancel does not exist")
+    })?;
+    let fee = scheduled.get_prepaid_fee().ok_or_else(|| {
+        Error::new_const("The prepaid fee of the scheduled tx overflows")
+    })?;
+    token::transfer(
+        ctx,
+        &SCHEDULER,
+        &scheduled.owner,
+        &scheduled.fee.token,
+        fee,
+    )?;
+    ctx.delete(&key)
+}
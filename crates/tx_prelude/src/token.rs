@@ -29,6 +29,8 @@ pub fn transfer(
         dest_bal.receive(&amount).into_storage_result()?;
         ctx.write(&src_key, src_bal)?;
         ctx.write(&dest_key, dest_bal)?;
+        // Count the debit against the spending caps of the source, if any
+        namada_account::record_spending(ctx, src, token, amount)?;
     }
     Ok(())
 }
@@ -53,6 +55,8 @@ pub fn undenominated_transfer(
         dest_bal.receive(&amount).into_storage_result()?;
         ctx.write(&src_key, src_bal)?;
         ctx.write(&dest_key, dest_bal)?;
+        // Count the debit against the spending caps of the source, if any
+        namada_account::record_spending(ctx, src, token, amount)?;
     }
     Ok(())
 }
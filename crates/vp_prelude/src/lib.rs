@@ -33,6 +33,7 @@ pub use namada_macros::validity_predicate;
 pub use namada_storage::{
     iter_prefix, iter_prefix_bytes, Error, OptionExt, ResultExt, StorageRead,
 };
+pub use namada_tx::data::schedule as scheduler;
 pub use namada_tx::{Section, Tx};
 use namada_vm_env::vp::*;
 use namada_vm_env::{read_from_buffer, read_key_val_bytes_from_buffer};
@@ -66,13 +67,31 @@ pub fn is_proposal_accepted(ctx: &Ctx, proposal_id: u64) -> VpResult {
 
 /// Verify section signatures
 pub fn verify_signatures(ctx: &Ctx, tx: &Tx, owner: &Address) -> VpResult {
-    let max_signatures_per_transaction =
-        parameters::max_signatures_per_transaction(&ctx.pre())?;
-
     let public_keys_index_map =
         account::public_keys_index_map(&ctx.pre(), owner)?;
     let threshold = account::threshold(&ctx.pre(), owner)?.unwrap_or(1);
 
+    verify_signatures_with_keys(
+        ctx,
+        tx,
+        owner,
+        public_keys_index_map,
+        threshold,
+    )
+}
+
+/// Verify section signatures against the given public keys and threshold
+/// instead of the ones of the owner's account
+pub fn verify_signatures_with_keys(
+    ctx: &Ctx,
+    tx: &Tx,
+    owner: &Address,
+    public_keys_index_map: account::AccountPublicKeysMap,
+    threshold: u8,
+) -> VpResult {
+    let max_signatures_per_transaction =
+        parameters::max_signatures_per_transaction(&ctx.pre())?;
+
     // Serialize parameters
     let max_signatures = max_signatures_per_transaction.serialize_to_vec();
     let public_keys_map = public_keys_index_map.serialize_to_vec();
@@ -4,6 +4,8 @@ pub mod tx_become_validator;
 pub mod tx_bond;
 #[cfg(feature = "tx_bridge_pool")]
 pub mod tx_bridge_pool;
+#[cfg(feature = "tx_cancel_scheduled_tx")]
+pub mod tx_cancel_scheduled_tx;
 #[cfg(feature = "tx_change_consensus_key")]
 pub mod tx_change_consensus_key;
 #[cfg(feature = "tx_change_validator_commission")]
@@ -14,6 +16,8 @@ pub mod tx_change_validator_metadata;
 pub mod tx_claim_rewards;
 #[cfg(feature = "tx_deactivate_validator")]
 pub mod tx_deactivate_validator;
+#[cfg(feature = "tx_grant_fee_allowance")]
+pub mod tx_grant_fee_allowance;
 #[cfg(feature = "tx_ibc")]
 pub mod tx_ibc;
 #[cfg(feature = "tx_init_account")]
@@ -24,10 +28,16 @@ pub mod tx_init_proposal;
 pub mod tx_reactivate_validator;
 #[cfg(feature = "tx_redelegate")]
 pub mod tx_redelegate;
+#[cfg(feature = "tx_remove_spending_policy")]
+pub mod tx_remove_spending_policy;
 #[cfg(feature = "tx_resign_steward")]
 pub mod tx_resign_steward;
 #[cfg(feature = "tx_reveal_pk")]
 pub mod tx_reveal_pk;
+#[cfg(feature = "tx_revoke_fee_allowance")]
+pub mod tx_revoke_fee_allowance;
+#[cfg(feature = "tx_schedule_tx")]
+pub mod tx_schedule_tx;
 #[cfg(feature = "tx_transfer")]
 pub mod tx_transfer;
 #[cfg(feature = "tx_unbond")]
@@ -36,6 +46,8 @@ pub mod tx_unbond;
 pub mod tx_unjail_validator;
 #[cfg(feature = "tx_update_account")]
 pub mod tx_update_account;
+#[cfg(feature = "tx_update_spending_policy")]
+pub mod tx_update_spending_policy;
 #[cfg(feature = "tx_update_steward_commission")]
 pub mod tx_update_steward_commission;
 #[cfg(feature = "tx_vote_proposal")]
@@ -0,0 +1,24 @@
+//! A tx to cancel a scheduled tx and refund its prepaid fee.
+
+use namada_tx_prelude::*;
+
+#[transaction(gas = 1002285)] // TODO: needs to be benchmarked
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let cancel = scheduler::CancelScheduledTx::try_from_slice(&data[..])
+        .wrap_err("failed to decode CancelScheduledTx")?;
+    debug_log!(
+        "cancel scheduled tx {} from {} at {}",
+        cancel.hash,
+        cancel.owner,
+        cancel.target
+    );
+
+    scheduler::cancel_scheduled_tx(ctx, cancel)?;
+
+    Ok(())
+}
@@ -0,0 +1,23 @@
+//! A tx to grant an allowance to pay for the wrapper fees of another account.
+
+use namada_tx_prelude::*;
+
+#[transaction(gas = 1002285)] // TODO: needs to be benchmarked
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let grant = account::GrantFeeAllowance::try_from_slice(&data[..])
+        .wrap_err("failed to decode GrantFeeAllowance")?;
+    debug_log!(
+        "grant fee allowance from {} to {}",
+        grant.granter,
+        grant.grantee
+    );
+
+    account::grant_fee_allowance(ctx, grant)?;
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+//! A tx to remove the spending policy of an account.
+
+use namada_tx_prelude::*;
+
+#[transaction(gas = 1002285)] // TODO: needs to be benchmarked
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let account::RemoveSpendingPolicy { owner } =
+        account::RemoveSpendingPolicy::try_from_slice(&data[..])
+            .wrap_err("failed to decode RemoveSpendingPolicy")?;
+    debug_log!("remove spending policy of {}", owner);
+
+    account::remove_spending_policy(ctx, &owner)?;
+
+    Ok(())
+}
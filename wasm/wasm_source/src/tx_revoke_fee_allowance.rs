@@ -0,0 +1,20 @@
+//! A tx to revoke a fee allowance granted to another account.
+
+use namada_tx_prelude::*;
+
+#[transaction(gas = 1002285)] // TODO: needs to be benchmarked
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let account::RevokeFeeAllowance { granter, grantee } =
+        account::RevokeFeeAllowance::try_from_slice(&data[..])
+            .wrap_err("failed to decode RevokeFeeAllowance")?;
+    debug_log!("revoke fee allowance from {} to {}", granter, grantee);
+
+    account::revoke_fee_allowance(ctx, &granter, &grantee)?;
+
+    Ok(())
+}
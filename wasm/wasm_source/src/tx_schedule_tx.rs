@@ -0,0 +1,25 @@
+//! A tx to schedule a signed inner tx for an execution at a future block
+//! height or epoch.
+
+use namada_tx_prelude::*;
+
+#[transaction(gas = 1002285)] // TODO: needs to be benchmarked
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let scheduled = scheduler::ScheduledTx::try_from_slice(&data[..])
+        .wrap_err("failed to decode ScheduledTx")?;
+    debug_log!(
+        "schedule tx {} from {} at {}",
+        scheduled.hash(),
+        scheduled.owner,
+        scheduled.target
+    );
+
+    scheduler::schedule_tx(ctx, scheduled)?;
+
+    Ok(())
+}
@@ -47,7 +47,7 @@ fn apply_tx(ctx: &mut Ctx, tx: Tx) -> TxResult {
     if !tx_data.public_keys.is_empty() {
         account::clear_public_keys(ctx, owner)?;
         for (index, public_key) in tx_data.public_keys.iter().enumerate() {
-            let index = index as u8;
+            let index = account::public_key_index(index)?;
             account::pks_handle(owner).insert(
                 ctx,
                 index,
@@ -56,5 +56,16 @@ fn apply_tx(ctx: &mut Ctx, tx: Tx) -> TxResult {
         }
     }
 
+    if !tx_data.public_keys.is_empty() || !tx_data.public_key_weights.is_empty()
+    {
+        account::set_public_key_weights(
+            ctx,
+            owner,
+            &tx_data.public_key_weights,
+        )?;
+    } else if tx_data.threshold.is_some() {
+        account::validate_threshold(ctx, owner)?;
+    }
+
     Ok(())
 }
@@ -0,0 +1,19 @@
+//! A tx to set the spending policy of an account.
+
+use namada_tx_prelude::*;
+
+#[transaction(gas = 1002285)] // TODO: needs to be benchmarked
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let update = account::UpdateSpendingPolicy::try_from_slice(&data[..])
+        .wrap_err("failed to decode UpdateSpendingPolicy")?;
+    debug_log!("update spending policy of {}", update.owner);
+
+    account::update_spending_policy(ctx, update)?;
+
+    Ok(())
+}
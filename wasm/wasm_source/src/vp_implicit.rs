@@ -9,6 +9,10 @@
 //! It allows to bond, unbond and withdraw tokens to and from PoS system with a
 //! valid signature.
 //!
+//! Granting or revoking a fee allowance requires a valid signature.
+//!
+//! Scheduling a tx or cancelling a scheduled tx requires a valid signature.
+//!
 //! Any other storage key changes are allowed only with a valid signature.
 
 use core::ops::Deref;
@@ -28,6 +32,8 @@ enum KeyType<'a> {
     Masp,
     PgfSteward(&'a Address),
     GovernanceVote(&'a Address),
+    FeeAllowance(&'a Address),
+    ScheduledTx(&'a Address),
     Ibc,
     Unknown,
 }
@@ -56,6 +62,11 @@ impl<'a> From<&'a storage::Key> for KeyType<'a> {
             } else {
                 Self::Unknown
             }
+        } else if let Some((granter, _)) = account::is_fee_allowance_key(key) {
+            Self::FeeAllowance(granter)
+        } else if let Some((_, owner, _)) = scheduler::is_scheduled_tx_key(key)
+        {
+            Self::ScheduledTx(owner)
         } else if token::storage_key::is_masp_key(key) {
             Self::Masp
         } else if ibc::is_ibc_key(key) {
@@ -150,6 +161,14 @@ fn validate_tx(
             KeyType::PoS => validate_pos_changes(ctx, &addr, key, &valid_sig)?,
             KeyType::PgfSteward(address) => address != &addr || *valid_sig,
             KeyType::GovernanceVote(voter) => voter != &addr || *valid_sig,
+            KeyType::FeeAllowance(granter) => {
+                // Fee allowances must be well-formed and granted with a valid
+                // signature
+                granter != &addr
+                    || (*valid_sig
+                        && ctx.read_post::<account::FeeAllowance>(key).is_ok())
+            }
+            KeyType::ScheduledTx(owner) => owner != &addr || *valid_sig,
             KeyType::Masp | KeyType::Ibc => true,
             KeyType::Unknown => {
                 // Unknown changes require a valid signature
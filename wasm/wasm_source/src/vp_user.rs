@@ -3,12 +3,22 @@
 //! This VP currently provides a signature verification against a public key for
 //! sending tokens (receiving tokens is permissive).
 //!
+//! An account with a spending policy can only send tokens to the allowed
+//! destinations of the policy, which must be credited with the whole debit. Up
+//! to the spending cap of a token in an epoch, the signatures of the policy's
+//! spending keys are sufficient to send it. Setting or removing a spending
+//! policy requires a valid signature(s).
+//!
 //! It allows to bond, unbond and withdraw tokens to and from PoS system with a
 //! valid signature(s).
 //!
 //! For validator a tx to change a validator's commission rate or metadata
 //! requires a valid signature(s) only from the validator.
 //!
+//! Granting or revoking a fee allowance requires a valid signature(s).
+//!
+//! Scheduling a tx or cancelling a scheduled tx requires a valid signature(s).
+//!
 //! Any other storage key changes are allowed only with a valid signature.
 
 use core::ops::Deref;
@@ -31,7 +41,10 @@ use proof_of_stake::storage_key::{
 };
 
 enum KeyType<'a> {
-    TokenBalance { owner: &'a Address },
+    TokenBalance {
+        token: &'a Address,
+        owner: &'a Address,
+    },
     TokenMinted,
     TokenMinter(&'a Address),
     PoS,
@@ -39,16 +52,23 @@ enum KeyType<'a> {
     Masp,
     PgfSteward(&'a Address),
     GovernanceVote(&'a Address),
+    FeeAllowance(&'a Address),
+    ScheduledTx(&'a Address),
+    SpendingPolicy(&'a Address),
+    SpentAmount {
+        owner: &'a Address,
+        token: &'a Address,
+    },
     Ibc,
     Unknown,
 }
 
 impl<'a> From<&'a storage::Key> for KeyType<'a> {
     fn from(key: &'a storage::Key) -> KeyType<'a> {
-        if let Some([_, owner]) =
+        if let Some([token, owner]) =
             token::storage_key::is_any_token_balance_key(key)
         {
-            Self::TokenBalance { owner }
+            Self::TokenBalance { token, owner }
         } else if token::storage_key::is_any_minted_balance_key(key).is_some() {
             Self::TokenMinted
         } else if let Some(minter) = token::storage_key::is_any_minter_key(key)
@@ -65,6 +85,15 @@ impl<'a> From<&'a storage::Key> for KeyType<'a> {
             }
         } else if let Some(address) = pgf_storage::keys::is_stewards_key(key) {
             Self::PgfSteward(address)
+        } else if let Some((granter, _)) = account::is_fee_allowance_key(key) {
+            Self::FeeAllowance(granter)
+        } else if let Some((_, owner, _)) = scheduler::is_scheduled_tx_key(key)
+        {
+            Self::ScheduledTx(owner)
+        } else if let Some(owner) = account::is_spending_policy_key(key) {
+            Self::SpendingPolicy(owner)
+        } else if let Some((owner, token)) = account::is_spent_amount_key(key) {
+            Self::SpentAmount { owner, token }
         } else if let Some(address) = key.is_validity_predicate() {
             Self::Vp(address)
         } else if token::storage_key::is_masp_key(key) {
@@ -99,7 +128,7 @@ fn validate_tx(
     for key in keys_changed.iter() {
         let key_type: KeyType = key.into();
         let is_valid = match key_type {
-            KeyType::TokenBalance { owner, .. } => {
+            KeyType::TokenBalance { token, owner } => {
                 if owner == &addr {
                     let pre: token::Amount =
                         ctx.read_pre(key)?.unwrap_or_default();
@@ -107,7 +136,20 @@ fn validate_tx(
                         ctx.read_post(key)?.unwrap_or_default();
                     let change = post.change() - pre.change();
                     // debit has to signed, credit doesn't
-                    let valid = change.non_negative() || *valid_sig;
+                    let valid = change.non_negative()
+                        || match account::spending_policy(&ctx.pre(), &addr)? {
+                            Some(policy) => validate_policy_debit(
+                                ctx,
+                                &tx_data,
+                                &addr,
+                                token,
+                                pre.checked_sub(post).unwrap_or_default(),
+                                &policy,
+                                &keys_changed,
+                                &valid_sig,
+                            )?,
+                            None => *valid_sig,
+                        };
                     debug_log!(
                         "token key: {}, change: {:?}, valid_sig: {}, valid \
                          modification: {}",
@@ -134,6 +176,38 @@ fn validate_tx(
             KeyType::PoS => validate_pos_changes(ctx, &addr, key, &valid_sig)?,
             KeyType::PgfSteward(address) => address != &addr || *valid_sig,
             KeyType::GovernanceVote(voter) => voter != &addr || *valid_sig,
+            KeyType::FeeAllowance(granter) => {
+                // Fee allowances must be well-formed and granted with a valid
+                // signature
+                granter != &addr
+                    || (*valid_sig
+                        && ctx.read_post::<account::FeeAllowance>(key).is_ok())
+            }
+            KeyType::ScheduledTx(owner) => owner != &addr || *valid_sig,
+            KeyType::SpendingPolicy(owner) => {
+                // Spending policies must be well-formed and updated with a
+                // valid signature
+                owner != &addr
+                    || (*valid_sig
+                        && ctx
+                            .read_post::<account::SpendingPolicy>(key)
+                            .map(|policy| {
+                                policy.map_or(true, |policy| policy.is_valid())
+                            })
+                            .unwrap_or_default())
+            }
+            KeyType::SpentAmount { owner, token } => {
+                // The spent amounts can only be increased by the debits of the
+                // account's balances
+                owner != &addr
+                    || *valid_sig
+                    || is_recorded_spending(
+                        ctx,
+                        owner,
+                        token,
+                        balance_debit(ctx, owner, token)?,
+                    )?
+            }
             KeyType::Vp(owner) => {
                 let has_post: bool = ctx.has_key_post(key)?;
                 if owner == &addr {
@@ -157,6 +231,114 @@ fn validate_tx(
     accept()
 }
 
+/// Validate a debit of the owner's balance of the token restricted by the
+/// owner's spending policy
+#[allow(clippy::too_many_arguments)]
+fn validate_policy_debit(
+    ctx: &Ctx,
+    tx_data: &Tx,
+    owner: &Address,
+    token: &Address,
+    debit: token::Amount,
+    policy: &account::SpendingPolicy,
+    keys_changed: &BTreeSet<storage::Key>,
+    valid_sig: &impl Deref<Target = bool>,
+) -> VpResult {
+    // The debited token can only be credited to the allowed destinations
+    let mut allowed_credits = token::Amount::zero();
+    for key in keys_changed {
+        if let Some([credited_token, destination]) =
+            token::storage_key::is_any_token_balance_key(key)
+        {
+            if credited_token != token || destination == owner {
+                continue;
+            }
+            let pre: token::Amount = ctx.read_pre(key)?.unwrap_or_default();
+            let post: token::Amount = ctx.read_post(key)?.unwrap_or_default();
+            let Some(credit) =
+                post.checked_sub(pre).filter(|credit| !credit.is_zero())
+            else {
+                continue;
+            };
+            if !policy.allows_destination(destination) {
+                debug_log!(
+                    "The destination {} is not allowed by the spending policy \
+                     of {}",
+                    destination,
+                    owner
+                );
+                return reject();
+            }
+            allowed_credits =
+                allowed_credits.checked_add(credit).unwrap_or(debit);
+        }
+    }
+    // With an allowlist, the debit must be entirely credited to the allowed
+    // destinations, so that it can't be burnt or sent over IBC unless the
+    // escrow is allowed
+    if !policy.allowed_destinations.is_empty() && allowed_credits < debit {
+        debug_log!(
+            "The debit {} of {} is not credited to the allowed destinations \
+             of its spending policy",
+            debit.to_string_native(),
+            owner
+        );
+        return reject();
+    }
+
+    // The account's threshold authorizes debits above the spending cap
+    if **valid_sig {
+        return accept();
+    }
+
+    // Otherwise, the debit must be signed with the spending keys, recorded
+    // and within the spending cap
+    let Some(spending_cap) = policy.spending_caps.get(token) else {
+        return reject();
+    };
+    if policy.spending_keys.is_empty()
+        || !verify_signatures_with_keys(
+            ctx,
+            tx_data,
+            owner,
+            policy.spending_keys_map(),
+            policy.spending_threshold,
+        )?
+    {
+        return reject();
+    }
+    let epoch = ctx.get_block_epoch()?;
+    let spent = account::spent_amount(&ctx.post(), owner, token, epoch)?;
+    Ok(spent <= *spending_cap
+        && is_recorded_spending(ctx, owner, token, debit)?)
+}
+
+/// The amount by which the owner's balance of the token has been debited
+fn balance_debit(
+    ctx: &Ctx,
+    owner: &Address,
+    token: &Address,
+) -> EnvResult<token::Amount> {
+    let key = token::storage_key::balance_key(token, owner);
+    let pre: token::Amount = ctx.read_pre(&key)?.unwrap_or_default();
+    let post: token::Amount = ctx.read_post(&key)?.unwrap_or_default();
+    Ok(pre.checked_sub(post).unwrap_or_default())
+}
+
+/// Check that the amount of the token spent by the owner in the current epoch
+/// has been increased by exactly the given debit
+fn is_recorded_spending(
+    ctx: &Ctx,
+    owner: &Address,
+    token: &Address,
+    debit: token::Amount,
+) -> VpResult {
+    let epoch = ctx.get_block_epoch()?;
+    let pre = account::spent_amount(&ctx.pre(), owner, token, epoch)?;
+    let post = account::spent_amount(&ctx.post(), owner, token, epoch)?;
+    Ok(pre.checked_add(debit) == Some(post))
+}
+
 fn validate_pos_changes(
     ctx: &Ctx,
     owner: &Address,
@@ -1346,6 +1528,71 @@ mod tests {
         );
     }
 
+    /// Update the VP of an account with weighted keys, signing with the given
+    /// key only, and run the VP
+    fn validate_weighted_vp_update(signer: key::common::SecretKey) -> bool {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+        tx_env.init_parameters(None, None, None, None);
+
+        let vp_owner = address::testing::established_address_1();
+        let public_keys = vec![
+            key::testing::keypair_1().ref_to(),
+            key::testing::keypair_2().ref_to(),
+        ];
+        let vp_code = TestWasms::VpAlwaysTrue.read_bytes();
+        let vp_hash = sha256(&vp_code);
+        // for the update
+        tx_env.store_wasm_code(vp_code);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner]);
+        tx_env.init_account_storage(&vp_owner, public_keys.clone(), 2);
+        tx_env.write_public_key_weights(&vp_owner, &[2, 1]);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Update VP in a transaction
+            tx::ctx()
+                .update_validity_predicate(address, vp_hash, &None)
+                .unwrap();
+        });
+
+        let pks_map = AccountPublicKeysMap::from_iter(public_keys);
+
+        let mut vp_env = vp_host_env::take();
+        let mut tx = vp_env.tx.clone();
+        tx.set_data(Data::new(vec![]));
+        tx.set_code(Code::new(vec![], None));
+        tx.add_section(Section::Signature(Signature::new(
+            vec![tx.raw_header_hash()],
+            pks_map.index_secret_keys(vec![signer]),
+            None,
+        )));
+        let signed_tx = tx.clone();
+        vp_env.tx = signed_tx.clone();
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+            .unwrap()
+    }
+
+    /// Test that a validity predicate update signed by a key whose weight
+    /// meets the threshold is accepted.
+    #[test]
+    fn test_weighted_signed_vp_update_accepted() {
+        assert!(validate_weighted_vp_update(key::testing::keypair_1()));
+    }
+
+    /// Test that a validity predicate update signed by a key whose weight is
+    /// below the threshold is rejected.
+    #[test]
+    fn test_underweight_signed_vp_update_rejected() {
+        assert!(!validate_weighted_vp_update(key::testing::keypair_2()));
+    }
+
     /// Test that a validity predicate update is accepted if allowed
     #[test]
     fn test_signed_vp_update_allowed_accepted() {
@@ -1401,4 +1648,300 @@ mod tests {
                 .unwrap()
         );
     }
+
+    /// Test that granting a fee allowance without a valid signature is
+    /// rejected.
+    #[test]
+    fn test_unsigned_fee_allowance_grant_rejected() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let grantee = address::testing::established_address_2();
+        let keypair = key::testing::keypair_1();
+        let public_key = keypair.ref_to();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &grantee]);
+        tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Grant a fee allowance in a transaction
+            account::grant_fee_allowance(
+                tx::ctx(),
+                account::GrantFeeAllowance {
+                    granter: address.clone(),
+                    grantee: grantee.clone(),
+                    allowance: account::FeeAllowance {
+                        token: address::testing::nam(),
+                        spend_limit: token::Amount::native_whole(10),
+                        expiration: None,
+                        allowed_code_hashes: Default::default(),
+                    },
+                },
+            )
+            .unwrap();
+        });
+
+        let vp_env = vp_host_env::take();
+        let mut tx_data = Tx::from_type(TxType::Raw);
+        tx_data.set_data(Data::new(vec![]));
+        tx_data.set_code(Code::new(vec![], None));
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        assert!(
+            !validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
+    /// Test that revoking a fee allowance with a valid signature is accepted.
+    #[test]
+    fn test_signed_fee_allowance_revoke_accepted() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let grantee = address::testing::established_address_2();
+        let keypair = key::testing::keypair_1();
+        let public_key = keypair.ref_to();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &grantee]);
+        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Revoke a fee allowance in a transaction
+            account::revoke_fee_allowance(tx::ctx(), address, &grantee)
+                .unwrap();
+        });
+
+        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
+
+        let mut vp_env = vp_host_env::take();
+        let mut tx = vp_env.tx.clone();
+        tx.set_data(Data::new(vec![]));
+        tx.set_code(Code::new(vec![], None));
+        tx.add_section(Section::Signature(Signature::new(
+            vec![tx.raw_header_hash()],
+            pks_map.index_secret_keys(vec![keypair]),
+            None,
+        )));
+        let signed_tx = tx.clone();
+        vp_env.tx = signed_tx.clone();
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        assert!(
+            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
+    /// Initialize an account with a spending policy capping the debits of NAM
+    /// signed by the spending key and transfer the given amount from it in a
+    /// tx signed by the given key
+    fn policy_debit_tx(
+        amount: token::Amount,
+        signer: key::common::SecretKey,
+        allowed_destinations: BTreeSet<Address>,
+    ) -> (Tx, Address, BTreeSet<storage::Key>) {
+        let amount = token::DenominatedAmount::new(
+            amount,
+            token::NATIVE_MAX_DECIMAL_PLACES.into(),
+        );
+        policy_tx(signer, allowed_destinations, |owner, target, token| {
+            // Apply transfer in a transaction
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                owner,
+                target,
+                token,
+                amount,
+            )
+            .unwrap();
+        })
+    }
+
+    /// Initialize an account with a spending policy capping the debits of NAM
+    /// signed by the spending key and apply the given changes in a tx signed
+    /// by the given key. The changes are given the owner, the destination of
+    /// the debits and the token.
+    fn policy_tx(
+        signer: key::common::SecretKey,
+        allowed_destinations: BTreeSet<Address>,
+        mut apply: impl FnMut(&Address, &Address, &Address),
+    ) -> (Tx, Address, BTreeSet<storage::Key>) {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let public_key = key::testing::keypair_1().ref_to();
+        let spending_key = key::testing::keypair_2().ref_to();
+        let target = address::testing::established_address_2();
+        let token = address::testing::nam();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &target, &token]);
+        tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
+        account::update_spending_policy(
+            &mut tx_env.state,
+            account::UpdateSpendingPolicy {
+                owner: vp_owner.clone(),
+                policy: account::SpendingPolicy {
+                    spending_caps: [(
+                        token.clone(),
+                        token::Amount::native_whole(100),
+                    )]
+                    .into(),
+                    allowed_destinations,
+                    spending_keys: vec![spending_key.clone()],
+                    spending_threshold: 1,
+                },
+            },
+        )
+        .unwrap();
+
+        // Credit the tokens to the VP owner before running the transaction to
+        // be able to transfer from it
+        tx_env.credit_tokens(
+            &vp_owner,
+            &token,
+            token::Amount::native_whole(1_000),
+        );
+        // write the denomination of NAM into storage
+        token::write_denom(
+            &mut tx_env.state,
+            &token,
+            token::NATIVE_MAX_DECIMAL_PLACES.into(),
+        )
+        .unwrap();
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            apply(address, &target, &token)
+        });
+
+        let pks_map = AccountPublicKeysMap::from_iter(vec![signer.ref_to()]);
+
+        let mut vp_env = vp_host_env::take();
+        let mut tx = vp_env.tx.clone();
+        tx.set_data(Data::new(vec![]));
+        tx.set_code(Code::new(vec![], None));
+        tx.add_section(Section::Signature(Signature::new(
+            vec![tx.raw_header_hash()],
+            pks_map.index_secret_keys(vec![signer]),
+            None,
+        )));
+        let signed_tx = tx.clone();
+        vp_env.tx = signed_tx.clone();
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        vp_host_env::set(vp_env);
+        (signed_tx, vp_owner, keys_changed)
+    }
+
+    /// Test that a debit within the spending cap signed by the spending key is
+    /// accepted.
+    #[test]
+    fn test_spending_key_debit_within_cap_accepted() {
+        let (signed_tx, vp_owner, keys_changed) = policy_debit_tx(
+            token::Amount::native_whole(100),
+            key::testing::keypair_2(),
+            BTreeSet::default(),
+        );
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        assert!(
+            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
+    /// Test that a debit above the spending cap signed by the spending key is
+    /// rejected.
+    #[test]
+    fn test_spending_key_debit_above_cap_rejected() {
+        let (signed_tx, vp_owner, keys_changed) = policy_debit_tx(
+            token::Amount::native_whole(101),
+            key::testing::keypair_2(),
+            BTreeSet::default(),
+        );
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        assert!(
+            !validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
+    /// Test that a debit above the spending cap signed with the account's
+    /// threshold is accepted.
+    #[test]
+    fn test_signed_debit_above_cap_accepted() {
+        let (signed_tx, vp_owner, keys_changed) = policy_debit_tx(
+            token::Amount::native_whole(101),
+            key::testing::keypair_1(),
+            BTreeSet::default(),
+        );
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        assert!(
+            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
+    /// Test that a debit that doesn't credit an allowed destination, e.g. a
+    /// burn, is rejected even with a valid signature, while it's accepted
+    /// without an allowlist.
+    #[test]
+    fn test_signed_debit_without_credit_rejected() {
+        let burn = |owner: &Address, _target: &Address, token: &Address| {
+            let key = token::storage_key::balance_key(token, owner);
+            let balance: token::Amount =
+                tx::ctx().read(&key).unwrap().unwrap_or_default();
+            let balance = balance
+                .checked_sub(token::Amount::native_whole(1))
+                .unwrap();
+            tx::ctx().write(&key, balance).unwrap();
+        };
+
+        let (signed_tx, vp_owner, keys_changed) = policy_tx(
+            key::testing::keypair_1(),
+            [address::testing::established_address_2()].into(),
+            burn,
+        );
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        assert!(
+            !validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+
+        let (signed_tx, vp_owner, keys_changed) =
+            policy_tx(key::testing::keypair_1(), BTreeSet::default(), burn);
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        assert!(
+            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
+    /// Test that a debit to a destination outside of the allowlist is
+    /// rejected even with a valid signature.
+    #[test]
+    fn test_signed_debit_to_disallowed_destination_rejected() {
+        let (signed_tx, vp_owner, keys_changed) = policy_debit_tx(
+            token::Amount::native_whole(1),
+            key::testing::keypair_1(),
+            [address::testing::established_address_3()].into(),
+        );
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        assert!(
+            !validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
 }